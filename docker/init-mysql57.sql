@@ -0,0 +1,27 @@
+SET SQL_MODE = "NO_AUTO_VALUE_ON_ZERO";
+SET time_zone = "+00:00";
+
+-- reference db for the MySQL 5.7 compatibility tests (legacy metadata, utf8 = utf8mb3, generated columns)
+CREATE TABLE IF NOT EXISTS legacy_person
+(
+    id         int          NOT NULL AUTO_INCREMENT,
+    first_name varchar(50)  NOT NULL,
+    last_name  varchar(50)  NOT NULL,
+    full_name  varchar(101) GENERATED ALWAYS AS (concat(first_name, ' ', last_name)) VIRTUAL,
+    name_len   int GENERATED ALWAYS AS (char_length(first_name) + char_length(last_name)) STORED,
+    updated_at timestamp    NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    KEY idx_last_name (last_name(10)),
+    KEY idx_name_len (name_len)
+) ENGINE = InnoDB
+  DEFAULT CHARSET = utf8
+  COLLATE = utf8_general_ci COMMENT ='legacy table';
+
+INSERT INTO legacy_person (id, first_name, last_name, updated_at)
+VALUES (1, 'Ada', 'Lovelace', '2024-01-01 10:00:00'),
+       (2, 'Alan', 'Turing', '2024-01-02 11:00:00'),
+       (3, 'Grace', 'Hopper', '2024-01-03 12:00:00');
+
+CREATE OR REPLACE VIEW v_person AS
+SELECT id, full_name
+FROM legacy_person;
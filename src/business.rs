@@ -1,34 +1,422 @@
-use crate::cli::Commands;
-use fluxforge::config::{get_config_file_path, load_config};
-use fluxforge::{drivers, ops, ForgeSchema};
+use crate::cli::{Commands, ReportFormat};
+use fluxforge::config::validate::{problems_report, validate_config};
+use fluxforge::config::{get_config_file_path, load_config_profile, write_config_template};
+use fluxforge::core::{
+    ForgeAppliedStatements, ForgeDialect, ForgeObjectFilter, ForgeRollbackPlan, ForgeSchemaDiff,
+    ForgeSchemaFormat, ForgeVerifyLevel, apply_column_overrides, apply_column_renames,
+    apply_table_renames, convert_schema, flatten_partitions,
+};
+use fluxforge::ops::audit::AuditRecord;
+use fluxforge::ops::checkpoint::{ReplicationCheckpoint, ResumeToken, strip_password};
+use fluxforge::ops::daemon::{Daemon, JobRequest};
+use fluxforge::ops::incompat::Severity;
+use fluxforge::ops::plan::{SchemaPlan, StatementKind};
+use fluxforge::ops::row_counts::{RowCountMismatch, TableRowCount};
+use fluxforge::ops::session::{ConnectedSession, ReplicationSession};
+use fluxforge::{
+    CancellationToken, ForgeConfig, ForgeError, ForgePreflightCheck, ForgeSchema, MigrateOptions,
+    drivers, ops,
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{error, info, warn};
 
-pub async fn handle_command(command: Commands) -> Result<(), Box<dyn std::error::Error>> {
+/// returned by `diff --check` if the schemas differ, mapped to its own exit code by main
+#[derive(Debug)]
+pub struct SchemaDrift;
+
+impl std::fmt::Display for SchemaDrift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "schema drift detected")
+    }
+}
+
+impl std::error::Error for SchemaDrift {}
+
+/// prints the summary table of the compared row counts, also of a verification failed on them
+fn print_row_counts(result: &Result<Vec<TableRowCount>, Box<dyn std::error::Error>>) {
+    let counts = match result {
+        Ok(counts) => counts,
+        Err(e) => match e.downcast_ref::<RowCountMismatch>() {
+            Some(mismatch) => &mismatch.counts,
+            None => return,
+        },
+    };
+    if !counts.is_empty() {
+        print!("{}", ops::row_counts::row_counts_report(counts));
+    }
+}
+
+/// records applied schema statements with their rollback plan, prints the run id
+fn write_audit_record(
+    config: &ForgeConfig,
+    command: &str,
+    target: &str,
+    statements: &[String],
+    rollback: ForgeRollbackPlan,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if statements.is_empty() {
+        return Ok(());
+    }
+    let record = AuditRecord::new(
+        command,
+        &strip_password(target),
+        statements.to_vec(),
+        rollback,
+    );
+    ops::audit::append_record(&config.get_audit_log(), &record)?;
+    println!(
+        "Schema changes recorded as run {} (revert with: fluxforge rollback --run {} --target <URL>)",
+        record.run_id, record.run_id
+    );
+    Ok(())
+}
+
+/// Records the statements an apply executed with the rollback plan, also those of a failed
+/// apply, and passes its result on. The error of a failed apply takes precedence over one of
+/// the audit log.
+fn audit_apply<T>(
+    config: &ForgeConfig,
+    command: &str,
+    target: &str,
+    applied: &[String],
+    rollback: Option<ForgeRollbackPlan>,
+    result: Result<T, Box<dyn std::error::Error>>,
+) -> Result<T, Box<dyn std::error::Error>> {
+    let Some(rollback) = rollback else {
+        return result;
+    };
+    match (
+        write_audit_record(config, command, target, applied, rollback),
+        result,
+    ) {
+        (Err(e), Ok(_)) => Err(e),
+        (Err(e), Err(apply)) => {
+            warn!(error = %e, "the statements of the failed apply are not recorded");
+            Err(apply)
+        }
+        (Ok(()), result) => result,
+    }
+}
+
+/// writes the rollback plan as SQL script, before the changes are applied
+async fn write_rollback_file(
+    path: &Path,
+    plan: &ForgeRollbackPlan,
+    target: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let script = ops::audit::rollback_script(plan, &strip_password(target));
+    ops::storage::write(path, script.as_bytes())
+        .await
+        .map_err(|e| format!("Error writing rollback file {path:?}: {e}"))?;
+    println!(
+        "Rollback statements written to {path:?} ({} statements, {} irreversible changes)",
+        plan.statements.len(),
+        plan.irreversible.len()
+    );
+    Ok(())
+}
+
+/// writes the classified statements of a schema apply as plan file
+async fn write_plan_file(path: &Path, plan: &SchemaPlan) -> Result<(), Box<dyn std::error::Error>> {
+    let content = ops::plan::render_plan(plan, path)?;
+    ops::storage::write(path, content.as_bytes())
+        .await
+        .map_err(|e| format!("Error writing plan file {path:?}: {e}"))?;
+    println!(
+        "Plan written to {path:?} ({} additive, {} destructive, {} data-rewriting statements)",
+        plan.count(StatementKind::Additive),
+        plan.count(StatementKind::Destructive),
+        plan.count(StatementKind::DataRewriting)
+    );
+    Ok(())
+}
+
+/// asks for a `yes` on the terminal, false for any other answer
+fn confirm(prompt: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    use std::io::{IsTerminal, Write};
+
+    if !std::io::stdin().is_terminal() {
+        return Err(
+            "Confirmation required but stdin is not a terminal, pass --yes to confirm.".into(),
+        );
+    }
+    print!("{prompt}");
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(answer.trim().eq_ignore_ascii_case("yes"))
+}
+
+/// Runs `task` until it ends, the first Ctrl-C cancels `cancel` so the task stops cleanly,
+/// a second Ctrl-C aborts it at once.
+async fn cancel_on_ctrl_c<T>(
+    cancel: &CancellationToken,
+    task: impl Future<Output = Result<T, Box<dyn std::error::Error>>>,
+) -> Result<T, Box<dyn std::error::Error>> {
+    let mut task = std::pin::pin!(task);
+    tokio::select! {
+        result = &mut task => return result,
+        _ = tokio::signal::ctrl_c() => {}
+    }
+    warn!("cancelling after the running statement or chunks, press Ctrl-C again to abort");
+    cancel.cancel();
+    tokio::select! {
+        result = &mut task => result,
+        _ = tokio::signal::ctrl_c() => Err("Interrupted (Ctrl-C).".into()),
+    }
+}
+
+/// logs the known incompatibilities of the schema with the target (all without one) with their
+/// code and hint
+fn report_incompatibilities(schema: &ForgeSchema, target_url: Option<&str>) {
+    let target = target_url.and_then(|url| drivers::database_system(url).parse().ok());
+    for found in ops::incompat::check_schema(schema, target) {
+        match found.severity {
+            Severity::Error => {
+                error!(code = found.code, table = %found.table, object = %found.object, hint = %found.hint, "{}", found.message);
+            }
+            Severity::Warning => {
+                warn!(code = found.code, table = %found.table, object = %found.object, hint = %found.hint, "{}", found.message);
+            }
+        }
+    }
+}
+
+/// warns about type conversions that narrow the data, an error with `strict_types`
+fn report_lossy_types(
+    schema: &ForgeSchema,
+    config: &ForgeConfig,
+    target_url: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let Ok(dialect) = drivers::database_system(target_url).parse::<ForgeDialect>() else {
+        return Ok(());
+    };
+    let found = ops::lossy_types::check_schema(schema, config, dialect);
+    for conversion in &found {
+        warn!(table = %conversion.table, column = %conversion.column, source_type = %conversion.source_type, target_type = %conversion.target_type, "lossy type conversion: {}", conversion.reason);
+    }
+    if found.is_empty() {
+        return Ok(());
+    }
+    print!("{}", ops::lossy_types::lossy_types_report(&found));
+    if config.is_strict_types() {
+        return Err(format!(
+            "ERROR: {} lossy type conversion(s) with --strict-types, adjust the type mapping or the column overrides.",
+            found.len()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// reads a schema JSON or YAML file written by `extract`, `strict` rejects unknown fields and
+/// inconsistent tables
+async fn read_schema_file(
+    path: &Path,
+    strict: bool,
+) -> Result<ForgeSchema, Box<dyn std::error::Error>> {
+    let bytes = ops::storage::read(path)
+        .await
+        .map_err(|e| format!("Error opening Schema-File {path:?}: {e}"))?;
+    let schema = ops::schema_file::parse_schema(&bytes, strict)
+        .map_err(|e| format!("Error parsing Schema-File {e}."))?;
+    Ok(schema)
+}
+
+/// schema of the file, or of the source database without one
+async fn read_or_fetch_schema(
+    source: Option<String>,
+    schema: Option<PathBuf>,
+    forge_config: &ForgeConfig,
+) -> Result<ForgeSchema, Box<dyn std::error::Error>> {
+    if let Some(path) = schema {
+        return read_schema_file(&path, false).await;
+    }
+    let src_url = source.ok_or("Source URL is required.")?;
+    let source_driver = drivers::create_driver(&src_url, forge_config, true).await?;
+    source_driver.fetch_schema(forge_config).await
+}
+
+/// Options of the command line that apply to every command.
+#[derive(Debug, Clone, Default)]
+pub struct GlobalOptions {
+    /// `[profiles.<name>]` of `--profile` applied to the config
+    pub profile: Option<String>,
+    /// `--ssh`, overrides `[ssh] host`
+    pub ssh: Option<String>,
+    /// `--ssh-identity`, overrides `[ssh] identity_file`
+    pub ssh_identity: Option<PathBuf>,
+}
+
+impl GlobalOptions {
+    /// Loads the config of a command with the profile and the SSH flags applied.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config cannot be read or parsed.
+    pub fn load_config(
+        &self,
+        path: Option<PathBuf>,
+    ) -> Result<ForgeConfig, Box<dyn std::error::Error>> {
+        let mut config = load_config_profile(path, self.profile.as_deref())?;
+        if self.ssh.is_some() || self.ssh_identity.is_some() {
+            let ssh = config.ssh.get_or_insert_with(Default::default);
+            if self.ssh.is_some() {
+                ssh.host.clone_from(&self.ssh);
+            }
+            if self.ssh_identity.is_some() {
+                ssh.identity_file.clone_from(&self.ssh_identity);
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// runs a job of the daemon as a replication session until it ends or `cancel` stops it
+fn run_daemon_job(
+    request: JobRequest,
+    cancel: CancellationToken,
+    options: &GlobalOptions,
+) -> Result<(), String> {
+    // the job runs on a blocking thread of the daemon runtime, a cancelled session returns
+    // after its running statement or chunks
+    tokio::runtime::Handle::current()
+        .block_on(replicate_daemon_job(request, cancel, options))
+        .map_err(|e| e.to_string())
+}
+
+/// replicates the job and reports its end to the webhooks of [notifications]
+async fn replicate_daemon_job(
+    request: JobRequest,
+    cancel: CancellationToken,
+    options: &GlobalOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut forge_config = options.load_config(request.config)?;
+    if request.verify {
+        forge_config
+            .general
+            .get_or_insert_with(Default::default)
+            .verify_level = Some(ForgeVerifyLevel::Full);
+    }
+    ops::error_log::configure(&forge_config);
+    let verify = request.verify
+        || forge_config
+            .general
+            .as_ref()
+            .and_then(|general| general.verify_after_write)
+            .unwrap_or(false);
+
+    let started = chrono::Local::now();
+    let before = ops::metrics::global().snapshot();
+    let session = ReplicationSession::new(request.source.clone(), request.target.clone())
+        .config(forge_config)
+        .cancel(cancel)
+        .halt_on_error(request.halt_on_error)
+        .verify(verify);
+    let result = session.run().await;
+
+    let summary = ops::notify::RunSummary::new(
+        "replicate",
+        Some(&request.source),
+        &request.target,
+        started,
+        result.as_ref().err().map(ToString::to_string),
+        &ops::metrics::global().snapshot().since(&before),
+    );
+    ops::notify::notify(session.forge_config(), &summary).await;
+    let outcome = result?;
+    info!(tables = outcome.tables, "job replicated");
+    Ok(())
+}
+
+pub async fn handle_command(
+    command: Commands,
+    options: &GlobalOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // replicate and migrate report their end to the webhooks of [notifications]
+    let notified = match command {
+        Commands::Migrate {
+            ref source,
+            ref target,
+            ref config,
+            dry_run: false,
+            ..
+        } => Some((
+            "migrate",
+            source.clone(),
+            Some(target.clone()),
+            config.clone(),
+        )),
+        Commands::Replicate {
+            ref source,
+            ref target,
+            ref config,
+            dry_run: false,
+            estimate_only: false,
+            ..
+        } => Some(("replicate", source.clone(), target.clone(), config.clone())),
+        _ => None,
+    };
+    let started = chrono::Local::now();
+    let before = ops::metrics::global().snapshot();
+    let result = run_command(command, options).await;
+
+    if let Some((command, source, Some(target), config)) = notified
+        && let Ok(forge_config) = options.load_config(config)
+    {
+        let summary = ops::notify::RunSummary::new(
+            command,
+            source.as_deref(),
+            &target,
+            started,
+            result.as_ref().err().map(ToString::to_string),
+            &ops::metrics::global().snapshot().since(&before),
+        );
+        ops::notify::notify(&forge_config, &summary).await;
+    }
+    result
+}
+
+async fn run_command(
+    command: Commands,
+    options: &GlobalOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
     match command {
         Commands::Extract {
             source,
             schema,
+            format,
             config,
             verbose,
         } => {
             println!("Extracting schema from {source}...");
 
             // load config, uses internal defaults if not file set
-            let forge_config = load_config(config.clone())?;
+            let forge_config = options.load_config(config.clone())?;
 
             let source_driver = drivers::create_driver(&source, &forge_config, true).await?;
 
             let mut extracted_schema = source_driver.fetch_schema(&forge_config).await?;
             extracted_schema.metadata.config_file = get_config_file_path(config.clone());
+            report_incompatibilities(&extracted_schema, None);
 
             if verbose {
                 println!(
                     "Extracted {} tables from source.",
                     extracted_schema.tables.len()
                 );
+                if let Some(ref position) = extracted_schema.metadata.source_position {
+                    println!("Source position: {position}");
+                }
             }
 
-            let file = std::fs::File::create(&schema)?;
-            serde_json::to_writer_pretty(file, &extracted_schema)?;
+            let format = format
+                .or_else(|| ForgeSchemaFormat::from_path(&schema))
+                .unwrap_or_default();
+            let bytes = ops::schema_file::serialize_schema(&extracted_schema, format)?;
+            ops::storage::write(&schema, &bytes).await?;
 
             if verbose {
                 println!("Schema successfully forged and saved to: {schema:?}");
@@ -36,6 +424,249 @@ pub async fn handle_command(command: Commands) -> Result<(), Box<dyn std::error:
             Ok(())
         }
 
+        Commands::Export {
+            source,
+            output,
+            format,
+            config,
+            verbose,
+        } => {
+            let forge_config = options.load_config(config)?;
+            let source_driver = drivers::create_driver(&source, &forge_config, true).await?;
+            let mut schema = source_driver.fetch_schema(&forge_config).await?;
+            apply_column_overrides(&mut schema, &forge_config);
+            if verbose {
+                println!("Exporting {} tables to {output:?}...", schema.tables.len());
+            }
+
+            let exported = ops::export::export_data(
+                source_driver.as_ref(),
+                &schema,
+                &forge_config,
+                format,
+                &output,
+            )
+            .await?;
+            print!("{}", ops::export::export_report(&exported));
+            Ok(())
+        }
+
+        Commands::Document {
+            source,
+            schema,
+            out,
+            format,
+            config,
+        } => {
+            let forge_config = options.load_config(config)?;
+            let schema = read_or_fetch_schema(source, schema, &forge_config).await?;
+            let written = ops::document::write_docs(&schema, format, &out).await?;
+            println!("Documented {} tables in {out:?}.", written.len() - 1);
+            Ok(())
+        }
+
+        Commands::Graph {
+            source,
+            schema,
+            format,
+            out,
+            config,
+        } => {
+            let forge_config = options.load_config(config)?;
+            let schema = read_or_fetch_schema(source, schema, &forge_config).await?;
+            let diagram = ops::graph::render(&schema, format);
+            match out {
+                Some(path) => ops::storage::write(&path, diagram.as_bytes()).await?,
+                None => print!("{diagram}"),
+            }
+            Ok(())
+        }
+
+        Commands::Lint {
+            source,
+            schema,
+            config,
+            format,
+        } => {
+            let forge_config = options.load_config(config)?;
+            let schema = read_or_fetch_schema(source, schema, &forge_config).await?;
+            let found = ops::lint::lint_schema(&schema, &forge_config);
+            match format {
+                ReportFormat::Text => print!("{}", ops::lint::lint_report(&found)),
+                ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&found)?),
+            }
+            if found.iter().any(|f| f.severity == Severity::Error) {
+                return Err("lint found errors".into());
+            }
+            Ok(())
+        }
+
+        Commands::Compat {
+            source,
+            target_dialect,
+            skip_data,
+            config,
+            format,
+        } => {
+            let forge_config = options.load_config(config)?;
+            let source_driver = drivers::create_driver(&source, &forge_config, true).await?;
+            let schema = source_driver.fetch_schema(&forge_config).await?;
+            let mut found = ops::compat::check_schema(&schema, &forge_config, target_dialect);
+            if !skip_data {
+                found.extend(
+                    ops::compat::check_data(
+                        source_driver.as_ref(),
+                        &schema,
+                        &forge_config,
+                        target_dialect,
+                    )
+                    .await?,
+                );
+            }
+            match format {
+                ReportFormat::Text => print!("{}", ops::compat::compat_report(&found)),
+                ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&found)?),
+            }
+            if found.iter().any(|i| i.severity == Severity::Error) {
+                return Err(
+                    format!("the source does not translate cleanly to {target_dialect}").into(),
+                );
+            }
+            Ok(())
+        }
+
+        Commands::ListTables {
+            source,
+            exact,
+            config,
+            format,
+        } => {
+            let forge_config = options.load_config(config)?;
+            let source_driver = drivers::create_driver(&source, &forge_config, true).await?;
+            let schema = source_driver.fetch_schema(&forge_config).await?;
+            let tables = ops::inspect::list_tables(source_driver.as_ref(), &schema, exact).await?;
+            match format {
+                ReportFormat::Text => print!("{}", ops::inspect::tables_report(&tables, exact)),
+                ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&tables)?),
+            }
+            Ok(())
+        }
+
+        Commands::Inspect {
+            table,
+            source,
+            dialect,
+            config,
+            format,
+        } => {
+            let forge_config = options.load_config(config)?;
+            let source_driver = drivers::create_driver(&source, &forge_config, true).await?;
+            let mut schema = source_driver.fetch_schema(&forge_config).await?;
+            let position = ops::inspect::find_table(&schema, &table)?;
+            // the conversion applies the overrides and renames of the target itself
+            if let Some(dialect) = dialect {
+                schema = convert_schema(&schema, &forge_config, dialect);
+            } else {
+                apply_column_overrides(&mut schema, &forge_config);
+            }
+            let table = &schema.tables[position];
+            match format {
+                ReportFormat::Text => print!("{}", ops::inspect::table_report(table)),
+                ReportFormat::Json => println!("{}", serde_json::to_string_pretty(table)?),
+            }
+            Ok(())
+        }
+
+        Commands::DumpData {
+            source,
+            file,
+            config,
+        } => {
+            let forge_config = options.load_config(config)?;
+            let source_driver = drivers::create_driver(&source, &forge_config, true).await?;
+            let mut schema = source_driver.fetch_schema(&forge_config).await?;
+            apply_column_overrides(&mut schema, &forge_config);
+            // parents first, so the rows load in the order of their foreign keys
+            ops::sort_tables_by_dependencies(&schema)
+                .map(|sorted| schema.tables = sorted)
+                .map_err(|e| format!("Circular Dependency Error: {e}"))?;
+
+            let dumped =
+                ops::ndjson::dump_data(source_driver.as_ref(), &schema, &forge_config, &file)
+                    .await?;
+            print!("{}", ops::ndjson::rows_report("Dumped", &dumped));
+            Ok(())
+        }
+        Commands::LoadData {
+            target,
+            file,
+            config,
+            dry_run,
+            halt_on_error,
+        } => {
+            let forge_config = options.load_config(config)?;
+            ops::error_log::configure(&forge_config);
+            let target_driver = drivers::create_driver(&target, &forge_config, false).await?;
+            let loaded =
+                ops::ndjson::load_data(target_driver.as_ref(), &file, dry_run, halt_on_error)
+                    .await?;
+            print!("{}", ops::ndjson::rows_report("Loaded", &loaded));
+            Ok(())
+        }
+
+        // stdout carries the stream, so the report goes to stderr
+        Commands::Send { source, config } => {
+            let forge_config = options.load_config(config)?;
+            let source_driver = drivers::create_driver(&source, &forge_config, true).await?;
+            let mut source_schema = source_driver.fetch_schema(&forge_config).await?;
+            apply_column_overrides(&mut source_schema, &forge_config);
+            ops::sort_tables_by_dependencies(&source_schema)
+                .map(|sorted| source_schema.tables = sorted)
+                .map_err(|e| format!("Circular Dependency Error: {e}"))?;
+
+            // tables are created with their target names, the data is read with the source names
+            let mut target_schema = source_schema.clone();
+            apply_column_renames(&mut target_schema, &forge_config);
+            apply_table_renames(&mut target_schema, &forge_config);
+            flatten_partitions(&mut target_schema, &forge_config);
+
+            let sent = ops::pipe::send(
+                source_driver.as_ref(),
+                &source_schema,
+                &target_schema,
+                &forge_config,
+                &mut tokio::io::stdout(),
+            )
+            .await?;
+            eprint!("{}", ops::ndjson::rows_report("Sent", &sent));
+            Ok(())
+        }
+        Commands::Receive {
+            target,
+            config,
+            dry_run,
+            halt_on_error,
+        } => {
+            let forge_config = options.load_config(config)?;
+            ops::error_log::configure(&forge_config);
+            let target_driver = drivers::create_driver(&target, &forge_config, false).await?;
+            if !dry_run && !target_driver.db_is_empty().await? {
+                return Err("ERROR: Target is not empty!  \
+                    For data loss protection receive only writes into an empty database."
+                    .into());
+            }
+            let received = ops::pipe::receive(
+                target_driver.as_ref(),
+                &forge_config,
+                &mut tokio::io::stdin(),
+                dry_run,
+                halt_on_error,
+            )
+            .await?;
+            print!("{}", ops::ndjson::rows_report("Received", &received));
+            Ok(())
+        }
+
         // only schema diff, NO DATA TRANSFER
         // target-db must exist but can be non-empty
         // schema-file can be omitted, in which case source-db is used which then becomes mandatory
@@ -47,39 +678,140 @@ pub async fn handle_command(command: Commands) -> Result<(), Box<dyn std::error:
             dry_run,
             verbose,
             allow_destructive,
+            yes,
+            only,
+            strict_types,
+            idempotent_ddl,
+            rollback_file,
+            plan_out,
         } => {
             // source = new state (from source which is file or DB)
             // target state = actual state of DB that will be changed
 
-            let forge_config = load_config(config.clone())?;
+            let mut forge_config = options.load_config(config.clone())?;
+            if strict_types {
+                forge_config
+                    .general
+                    .get_or_insert_with(Default::default)
+                    .strict_types = Some(true);
+            }
+            if idempotent_ddl {
+                forge_config
+                    .general
+                    .get_or_insert_with(Default::default)
+                    .idempotent_ddl = Some(true);
+            }
 
+            // the schema file is applied to the target, it must be complete
             let mut schema = if let Some(path) = schema {
-                // reading schema from file
-                let file = std::fs::File::open(&path)
-                    .map_err(|e| format!("Error opening Schema-File {path:?}: {e}"))?;
-                let int_schema: ForgeSchema =
-                    serde_json::from_reader(std::io::BufReader::new(file))
-                        .map_err(|e| format!("Error parsing Schema-File {e}."))?;
-
-                int_schema
+                read_schema_file(&path, true).await?
             } else {
                 // reading schema from source database
                 let src_url = source.as_ref().ok_or("Source URL is required.")?;
                 let s_driver = drivers::create_driver(src_url, &forge_config, true).await?;
                 s_driver.fetch_schema(&forge_config).await?
             };
+            report_incompatibilities(&schema, Some(&target));
+            report_lossy_types(&schema, &forge_config, &target)?;
+            apply_column_overrides(&mut schema, &forge_config);
+            // the target state is compared with the renamed tables and columns
+            apply_column_renames(&mut schema, &forge_config);
+            apply_table_renames(&mut schema, &forge_config);
+            flatten_partitions(&mut schema, &forge_config);
 
             // sort tables (will become more important when foreign keys are implemented)
             ops::sort_tables_by_dependencies(&schema)
                 .map(|sorted| schema.tables = sorted)
                 .map_err(|e| format!("Circular Dependency Error: {e}"))?;
 
+            // staged rollout: only the listed tables are touched in this run
+            let filter = only.unwrap_or_default();
+            if let Some(ref tables) = filter.tables {
+                for name in tables {
+                    if !schema.tables.iter().any(|t| &t.name == name) {
+                        warn!(table = %name, "table of --only is not part of the source schema");
+                    }
+                }
+            }
+
             let target_driver = drivers::create_driver(&target, &forge_config, false).await?;
 
-            // apply schema diff to target
-            let statements = target_driver
-                .diff_and_apply_schema(&schema, &forge_config, dry_run, verbose, allow_destructive)
-                .await?;
+            // the dropped objects are previewed and confirmed before anything is executed
+            if allow_destructive && !dry_run {
+                let planned = target_driver
+                    .diff_and_apply_schema(
+                        &schema,
+                        &forge_config,
+                        &filter,
+                        MigrateOptions {
+                            dry_run: true,
+                            destructive: true,
+                            ..Default::default()
+                        },
+                    )
+                    .await?;
+                if let Some(preview) = ops::plan::destructive_preview(&planned) {
+                    print!("{preview}");
+                    if !yes && !confirm("Type 'yes' to apply these changes: ")? {
+                        return Err("Aborted, the destructive changes were not confirmed.".into());
+                    }
+                }
+            }
+
+            // the rollback plan needs the target schema before the changes
+            let rollback = if dry_run && rollback_file.is_none() && plan_out.is_none() {
+                None
+            } else {
+                let plan = target_driver
+                    .rollback_schema_plan(&schema, &forge_config, &filter, allow_destructive)
+                    .await?;
+                if let Some(ref path) = rollback_file {
+                    write_rollback_file(path, &plan, &target).await?;
+                }
+                Some(plan)
+            };
+
+            // apply schema diff to target, Ctrl-C stops before the next statement
+            let cancel = CancellationToken::new();
+            let applied = ForgeAppliedStatements::default();
+            let statements = cancel_on_ctrl_c(
+                &cancel,
+                target_driver.diff_and_apply_schema(
+                    &schema,
+                    &forge_config,
+                    &filter,
+                    MigrateOptions {
+                        dry_run,
+                        verbose,
+                        destructive: allow_destructive,
+                        defer_foreign_keys: false,
+                        cancel: cancel.clone(),
+                        applied: applied.clone(),
+                    },
+                ),
+            )
+            .await;
+            let statements = audit_apply(
+                &forge_config,
+                "migrate",
+                &target,
+                &applied.snapshot(),
+                rollback.clone().filter(|_| !dry_run),
+                statements,
+            )?;
+
+            if !dry_run {
+                ops::metrics::global().record_schema_changes(statements.len() as u64);
+            }
+
+            if let Some(ref path) = plan_out {
+                let plan = SchemaPlan::new(
+                    &strip_password(&target),
+                    &statements,
+                    rollback.clone().unwrap_or_default(),
+                );
+                write_plan_file(path, &plan).await?;
+            }
 
             if dry_run {
                 println!("--- DRY RUN START : SQL changes ---");
@@ -103,44 +835,153 @@ pub async fn handle_command(command: Commands) -> Result<(), Box<dyn std::error:
             verbose,
             halt_on_error,
             verify,
+            verify_sample,
+            verify_digest,
+            verify_report,
+            checksum_file,
+            seed,
+            insert_workers,
+            ordered_inserts,
+            max_chunk_bytes,
+            disable_constraints,
+            strict_types,
+            idempotent_ddl,
+            scan_unsigned,
+            estimate_only,
+            rollback_file,
+            metrics_addr,
+            resume_from,
+            schema_only,
+            data_only,
+            force_nonempty,
+            truncate,
+            watch,
+            interval,
+            detect_drift,
         } => {
-            // Validation of source and target database combinations
-            let source_type = if source.starts_with("mysql://") {
-                "mysql"
-            } else if source.starts_with("postgres://") || source.starts_with("postgresql://") {
-                "postgres"
-            } else {
-                "unknown"
-            };
-
-            let target_type = if target.starts_with("mysql://") {
-                "mysql"
-            } else if target.starts_with("postgres://") || target.starts_with("postgresql://") {
-                "postgres"
-            } else {
-                "unknown"
-            };
-
-            let allowed = match (source_type, target_type) {
-                ("mysql", "postgres") => true,
-                ("mysql", "mysql") => true,
-                ("postgres", "postgres") => true,
-                ("postgres", "mysql") => false,
-                _ => false,
-            };
+            // flags and checkpoint of an aborted run, explicit arguments take precedence
+            let resume = resume_from
+                .as_deref()
+                .map(ResumeToken::decode)
+                .transpose()?;
+            if resume.is_some() && dump.is_some() {
+                return Err("ERROR: --dump cannot be combined with --resume-from.".into());
+            }
+            let source = source
+                .or_else(|| resume.as_ref().map(|t| t.source.clone()))
+                .ok_or("Source URL is required.")?;
+            let target = target
+                .or_else(|| resume.as_ref().map(|t| t.target.clone()))
+                .ok_or("Target URL is required.")?;
+            let config = config.or_else(|| resume.as_ref().and_then(|t| t.config.clone()));
+            let halt_on_error = halt_on_error || resume.as_ref().is_some_and(|t| t.halt_on_error);
+            let verify_sample =
+                verify_sample.or_else(|| resume.as_ref().and_then(|t| t.verify_sample));
+            let verify_digest = verify_digest || resume.as_ref().is_some_and(|t| t.verify_digest);
+            let verify_report =
+                verify_report.or_else(|| resume.as_ref().and_then(|t| t.verify_report.clone()));
+            let checksum_file =
+                checksum_file.or_else(|| resume.as_ref().and_then(|t| t.checksum_file.clone()));
+            let seed = seed.or_else(|| resume.as_ref().and_then(|t| t.seed));
+            let insert_workers =
+                insert_workers.or_else(|| resume.as_ref().and_then(|t| t.insert_workers));
+            let ordered_inserts =
+                ordered_inserts || resume.as_ref().is_some_and(|t| t.ordered_inserts);
+            let max_chunk_bytes =
+                max_chunk_bytes.or_else(|| resume.as_ref().and_then(|t| t.max_chunk_bytes));
+            let disable_constraints =
+                disable_constraints || resume.as_ref().is_some_and(|t| t.disable_constraints);
+            let strict_types = strict_types || resume.as_ref().is_some_and(|t| t.strict_types);
+            let idempotent_ddl =
+                idempotent_ddl || resume.as_ref().is_some_and(|t| t.idempotent_ddl);
+            let scan_unsigned = scan_unsigned || resume.as_ref().is_some_and(|t| t.scan_unsigned);
+            let data_only = data_only || resume.as_ref().is_some_and(|t| t.data_only);
+            let verify_level = verify.or_else(|| resume.as_ref().and_then(|t| t.verify_level));
+            let verify = verify.is_some()
+                || verify_sample.is_some()
+                || verify_digest
+                || verify_report.is_some()
+                || resume.as_ref().is_some_and(|t| t.verify);
 
-            if !allowed {
-                let msg = format!(
-                    "ERROR: Combination {source_type} -> {target_type} is not allowed.\n\
-                     Allowed combinations are:\n\
-                     - mysql -> postgres\n\
-                     - mysql -> mysql\n\
-                     - postgres -> postgres"
-                );
-                return Err(msg.into());
+            if let Some(addr) = metrics_addr {
+                ops::metrics::serve(addr).await?;
             }
 
-            let forge_config = load_config(config.clone())?;
+            let mut forge_config = options.load_config(config.clone())?;
+            // the command line sample takes precedence over [general] verify_sample
+            if verify_sample.is_some() {
+                forge_config
+                    .general
+                    .get_or_insert_with(Default::default)
+                    .verify_sample = verify_sample;
+            }
+            if verify_digest {
+                forge_config
+                    .general
+                    .get_or_insert_with(Default::default)
+                    .verify_binary_digest = Some(true);
+            }
+            if verify_report.is_some() {
+                forge_config
+                    .general
+                    .get_or_insert_with(Default::default)
+                    .verify_report = verify_report.clone();
+            }
+            if verify_level.is_some() {
+                forge_config
+                    .general
+                    .get_or_insert_with(Default::default)
+                    .verify_level = verify_level;
+            }
+            if seed.is_some() {
+                forge_config
+                    .general
+                    .get_or_insert_with(Default::default)
+                    .seed = seed;
+            }
+            if insert_workers.is_some() {
+                forge_config
+                    .general
+                    .get_or_insert_with(Default::default)
+                    .insert_workers = insert_workers;
+            }
+            if ordered_inserts {
+                forge_config
+                    .general
+                    .get_or_insert_with(Default::default)
+                    .ordered_inserts = Some(true);
+            }
+            if max_chunk_bytes.is_some() {
+                forge_config
+                    .general
+                    .get_or_insert_with(Default::default)
+                    .max_chunk_bytes = max_chunk_bytes;
+            }
+            if disable_constraints {
+                forge_config
+                    .general
+                    .get_or_insert_with(Default::default)
+                    .disable_constraints = Some(true);
+            }
+            if strict_types {
+                forge_config
+                    .general
+                    .get_or_insert_with(Default::default)
+                    .strict_types = Some(true);
+            }
+            if idempotent_ddl {
+                forge_config
+                    .general
+                    .get_or_insert_with(Default::default)
+                    .idempotent_ddl = Some(true);
+            }
+            if scan_unsigned {
+                forge_config
+                    .general
+                    .get_or_insert_with(Default::default)
+                    .scan_unsigned = Some(true);
+            }
+            ops::error_log::configure(&forge_config);
             let verify_enabled = verify
                 || forge_config
                     .general
@@ -148,47 +989,571 @@ pub async fn handle_command(command: Commands) -> Result<(), Box<dyn std::error:
                     .and_then(|general| general.verify_after_write)
                     .unwrap_or(false);
 
-            // target database
-            let target_driver = drivers::create_driver(&target, &forge_config, false).await?;
+            let cancel = CancellationToken::new();
+            let mut session = ReplicationSession::new(source.clone(), target.clone())
+                .config(forge_config)
+                .cancel(cancel.clone())
+                .dry_run(dry_run)
+                .verbose(verbose)
+                .halt_on_error(halt_on_error)
+                .verify(verify_enabled)
+                .dump(dump)
+                .schema_only(schema_only)
+                .data_only(data_only)
+                .force_nonempty(force_nonempty)
+                .truncate(truncate)
+                .require_empty_target(!estimate_only);
+            // a resumed run continues to fill the target of the aborted run
+            if let Some(ref token) = resume {
+                session = session.resume(ReplicationCheckpoint::resume(token.checkpoint.clone()));
+            }
+            let forge_config = session.forge_config();
+            let target_type = drivers::database_system(&target);
 
-            if !target_driver.db_is_empty().await? {
-                return Err("ERROR: Target is not empty!  \
-                    For data loss protection the replication is only allowed into an empty database.".into());
+            let resume_token = |checkpoint: &ReplicationCheckpoint| ResumeToken {
+                source: strip_password(&source),
+                target: strip_password(&target),
+                config: config.clone(),
+                halt_on_error,
+                verify: verify_enabled,
+                verify_level,
+                verify_sample,
+                verify_digest,
+                verify_report: verify_report.clone(),
+                checksum_file: checksum_file.clone(),
+                seed: forge_config.get_run_seed(),
+                insert_workers,
+                ordered_inserts,
+                max_chunk_bytes,
+                disable_constraints,
+                strict_types,
+                idempotent_ddl,
+                scan_unsigned,
+                data_only,
+                checkpoint: checkpoint.snapshot(),
+            };
+
+            let ConnectedSession {
+                source: source_driver,
+                target: target_driver,
+                mut source_schema,
+            } = session.connect().await?;
+            report_incompatibilities(&source_schema, Some(&target));
+            report_lossy_types(&source_schema, forge_config, &target)?;
+            if forge_config.is_scan_unsigned()
+                && let Ok(dialect) = target_type.parse::<ForgeDialect>()
+            {
+                let found = ops::lossy_types::scan_unsigned_overflow(
+                    source_driver.as_ref(),
+                    &source_schema,
+                    forge_config,
+                    dialect,
+                )
+                .await?;
+                for overflow in &found {
+                    warn!(table = %overflow.table, column = %overflow.column, rows = overflow.rows, "unsigned bigint values above the signed bigint range");
+                }
+                print!("{}", ops::lossy_types::unsigned_overflow_report(&found));
             }
+            // start position of a CDC run catching up with the changes made during the copy
+            if let Some(ref position) = source_schema.metadata.source_position {
+                println!("Source position at start of replication: {position}");
+            }
+            let target_schema = session.prepare(&mut source_schema)?;
 
-            // source database
-            let source_driver = drivers::create_driver(&source, &forge_config, true).await?;
-            let mut source_schema = source_driver.fetch_schema(&forge_config).await?;
+            // the estimation is only informative, unless it is all that was asked for
+            if !schema_only {
+                match ops::estimate::estimate(source_driver.as_ref(), &source_schema).await {
+                    Ok(estimate) => print!("{}", ops::estimate::estimate_report(&estimate)),
+                    Err(e) if estimate_only => return Err(e),
+                    Err(e) => warn!(error = %e, "size estimation failed"),
+                }
+            }
+            if estimate_only {
+                return Ok(());
+            }
 
-            // sort tables (will become more important when foreign keys are implemented)
-            ops::sort_tables_by_dependencies(&source_schema)
-                .map(|sorted| source_schema.tables = sorted)
-                .map_err(|e| format!("Circular Dependency Error: {e}"))?;
+            // taken before the copy, a range changing meanwhile is compared by the next verify
+            let checksums = match checksum_file.as_ref().filter(|_| !dry_run) {
+                Some(_) => Some(
+                    ops::checksums::compute(source_driver.as_ref(), &source_schema, forge_config)
+                        .await?,
+                ),
+                None => None,
+            };
+
+            let run = async {
+                let filter = ForgeObjectFilter::default();
+                // a data-only run leaves the schema alone, there is nothing to roll back
+                let rollback = if data_only || (dry_run && rollback_file.is_none()) {
+                    None
+                } else {
+                    let plan = target_driver
+                        .rollback_schema_plan(&target_schema, forge_config, &filter, true)
+                        .await?;
+                    if let Some(ref path) = rollback_file {
+                        write_rollback_file(path, &plan, &target).await?;
+                    }
+                    Some(plan).filter(|_| !dry_run)
+                };
+
+                // apply schema diff to target
+                let statements = session
+                    .apply_schema(target_driver.as_ref(), &target_schema)
+                    .await;
+                let statements = audit_apply(
+                    forge_config,
+                    "replicate",
+                    &target,
+                    &session.applied_statements(),
+                    rollback,
+                    statements,
+                )?;
+
+                if dry_run {
+                    println!("--- DRY RUN START: SQL changes ---");
+                    for sql in statements {
+                        println!("{sql}");
+                    }
+                    println!("--- DRY RUN END: SQL changes ---");
+                }
+                let row_counts = session
+                    .replicate(
+                        source_driver.as_ref(),
+                        target_driver.as_ref(),
+                        &source_schema,
+                    )
+                    .await;
+                print_row_counts(&row_counts);
+                row_counts?;
+
+                // created after the load, the rows are checked once by the database
+                let foreign_keys = session
+                    .apply_foreign_keys(target_driver.as_ref(), &target_schema)
+                    .await?;
+                if dry_run && !foreign_keys.is_empty() {
+                    println!("--- DRY RUN START: foreign keys after the data load ---");
+                    for sql in foreign_keys {
+                        println!("{sql}");
+                    }
+                    println!("--- DRY RUN END: foreign keys after the data load ---");
+                }
+                Ok(())
+            };
+
+            let result = cancel_on_ctrl_c(&cancel, run).await;
+
+            // run summary, the seed of a seeded run reproduces its randomized decisions
+            let seed = forge_config
+                .get_run_seed()
+                .map(|seed| format!(" (seed {seed})"))
+                .unwrap_or_default();
+            if let Err(e) = result {
+                if ForgeError::is_cancelled(e.as_ref()) {
+                    eprintln!("Replication cancelled{seed}.");
+                } else {
+                    eprintln!("Replication failed{seed}.");
+                }
+                if !dry_run && !schema_only {
+                    eprintln!(
+                        "Resume with: {}",
+                        resume_token(session.checkpoint()).command_line()?
+                    );
+                }
+                return Err(e);
+            }
+            println!("Replication finished{seed}.");
+            if let (Some(checksums), Some(path)) = (checksums, &checksum_file) {
+                checksums.write(path)?;
+                info!(path = %path.display(), tables = checksums.tables.len(), "key range checksums written");
+            }
+
+            // smoke tests need the loaded data
+            if !dry_run && !schema_only && forge_config.smoke_tests.is_some() {
+                let results = ops::smoke::run_smoke_tests(
+                    target_driver.as_ref(),
+                    &target_schema,
+                    forge_config,
+                )
+                .await;
+                print!("{}", ops::smoke::smoke_report(&results));
+
+                if results.iter().any(|r| !r.passed) {
+                    return Err("ERROR: smoke tests failed.".into());
+                }
+            }
+
+            if watch {
+                let options = ops::watch::WatchOptions {
+                    interval,
+                    halt_on_error,
+                    detect_drift,
+                };
+                ops::watch::watch(
+                    source_driver.as_ref(),
+                    target_driver.as_ref(),
+                    &source_schema,
+                    forge_config,
+                    options,
+                    &|diff| print!("{}", diff.summary()),
+                )
+                .await?;
+            }
+
+            Ok(())
+        }
 
-            // apply schema diff to target
-            let statements = target_driver
-                .diff_and_apply_schema(&source_schema, &forge_config, dry_run, verbose, true)
+        // verification of an earlier replication, only reads both databases
+        Commands::Verify {
+            source,
+            target,
+            config,
+            checksum_file,
+            tables,
+            pk_range,
+            verify_report,
+        } => {
+            let mut forge_config = options.load_config(config)?;
+            if verify_report.is_some() {
+                forge_config
+                    .general
+                    .get_or_insert_with(Default::default)
+                    .verify_report = verify_report;
+            }
+            let cancel = CancellationToken::new();
+            let session = ReplicationSession::new(source, target)
+                .config(forge_config)
+                .cancel(cancel.clone())
+                .require_empty_target(false);
+            let forge_config = session.forge_config();
+            let ConnectedSession {
+                source: source_driver,
+                target: target_driver,
+                mut source_schema,
+            } = session.connect().await?;
+            // orders the source tables, the target tables exist already
+            session.prepare(&mut source_schema)?;
+            if let Some(missing) = tables
+                .iter()
+                .find(|name| !source_schema.tables.iter().any(|t| &t.name == *name))
+            {
+                return Err(format!("Table `{missing}` not found in the source.").into());
+            }
+            if !tables.is_empty() {
+                source_schema.tables.retain(|t| tables.contains(&t.name));
+            }
+
+            let run = async {
+                let Some(path) = checksum_file else {
+                    let ranges = pk_range
+                        .map(|range| tables.iter().map(|t| (t.clone(), vec![range])).collect())
+                        .unwrap_or_default();
+                    return ops::verify_replication(
+                        source_driver.as_ref(),
+                        target_driver.as_ref(),
+                        &source_schema,
+                        forge_config,
+                        &ranges,
+                        &cancel,
+                    )
+                    .await;
+                };
+                let current =
+                    ops::checksums::compute(source_driver.as_ref(), &source_schema, forge_config)
+                        .await?;
+                let ranges = match ops::checksums::ChecksumFile::read(&path)? {
+                    Some(stored) => stored.changed_ranges(&current),
+                    None => {
+                        warn!(path = %path.display(), "no checksum file yet, every row is compared");
+                        HashMap::new()
+                    }
+                };
+                let row_counts = ops::verify_replication(
+                    source_driver.as_ref(),
+                    target_driver.as_ref(),
+                    &source_schema,
+                    forge_config,
+                    &ranges,
+                    &cancel,
+                )
                 .await?;
+                current.write(&path)?;
+                Ok(row_counts)
+            };
+            let row_counts = cancel_on_ctrl_c(&cancel, run).await;
+            print_row_counts(&row_counts);
+            row_counts?;
+            println!("Verification passed.");
+            Ok(())
+        }
+
+        // read-only comparison of two live schemas, nothing is applied
+        // each side can be a live database or an exported schema file
+        Commands::Diff {
+            source,
+            target,
+            schema_a,
+            schema_b,
+            config,
+            format,
+            check,
+        } => {
+            let forge_config = options.load_config(config)?;
+
+            let source_schema = if let Some(path) = schema_a {
+                read_schema_file(&path, false).await?
+            } else {
+                let src_url = source.as_ref().ok_or("Source URL is required.")?;
+                let source_driver = drivers::create_driver(src_url, &forge_config, true).await?;
+                source_driver.fetch_schema(&forge_config).await?
+            };
+
+            let target_schema = if let Some(path) = schema_b {
+                read_schema_file(&path, false).await?
+            } else {
+                let dst_url = target.as_ref().ok_or("Target URL is required.")?;
+                let target_driver = drivers::create_driver(dst_url, &forge_config, false).await?;
+                target_driver.fetch_schema(&forge_config).await?
+            };
+
+            let diff = ForgeSchemaDiff::compare(&source_schema, &target_schema);
+            if check {
+                print!("{}", diff.summary());
+                if !diff.is_empty() {
+                    return Err(SchemaDrift.into());
+                }
+                return Ok(());
+            }
+
+            match format {
+                ReportFormat::Text => print!("{}", diff.to_text()),
+                ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&diff)?),
+            }
+
+            Ok(())
+        }
+
+        // reverts the schema changes of a recorded run
+        Commands::Rollback {
+            run,
+            target,
+            config,
+            dry_run,
+        } => {
+            let forge_config = options.load_config(config)?;
+            let record = ops::audit::find_record(&forge_config.get_audit_log(), &run)?;
+
+            if strip_password(&target) != record.target {
+                warn!(recorded = %record.target, "rollback target differs from the target of the run");
+            }
+            print!("{}", ops::audit::rollback_report(&record));
 
             if dry_run {
-                println!("--- DRY RUN START: SQL changes ---");
-                for sql in statements {
+                println!("--- DRY RUN START: rollback statements ---");
+                for sql in &record.rollback.statements {
                     println!("{sql}");
                 }
-                println!("--- DRY RUN END: SQL changes ---");
+                println!("--- DRY RUN END: rollback statements ---");
+                return Ok(());
+            }
+
+            let target_driver = drivers::create_driver(&target, &forge_config, false).await?;
+            target_driver
+                .execute_statements(&record.rollback.statements)
+                .await?;
+
+            // the rollback itself can be reverted by executing the original statements again
+            let undo = ForgeRollbackPlan {
+                statements: record.statements.clone(),
+                irreversible: Vec::new(),
+            };
+            write_audit_record(
+                &forge_config,
+                &format!("rollback {run}"),
+                &target,
+                &record.rollback.statements,
+                undo,
+            )?;
+
+            Ok(())
+        }
+
+        // statements of a reviewed plan, executed without a new diff
+        Commands::ApplyPlan {
+            plan,
+            target,
+            config,
+            dry_run,
+        } => {
+            let forge_config = options.load_config(config)?;
+            let bytes = ops::storage::read(&plan)
+                .await
+                .map_err(|e| format!("Error opening plan file {plan:?}: {e}"))?;
+            let schema_plan = ops::plan::parse_plan(&String::from_utf8(bytes)?, &plan)
+                .map_err(|e| format!("Error parsing plan file {plan:?}: {e}"))?;
+
+            if !schema_plan.target.is_empty() && strip_password(&target) != schema_plan.target {
+                warn!(planned = %schema_plan.target, "target differs from the target of the plan");
+            }
+            let statements = schema_plan.sql();
+
+            if dry_run {
+                println!("--- DRY RUN START: plan statements ---");
+                for statement in &schema_plan.statements {
+                    println!("-- {}\n{}", statement.kind, statement.sql);
+                }
+                println!("--- DRY RUN END: plan statements ---");
+                return Ok(());
+            }
+
+            let target_driver = drivers::create_driver(&target, &forge_config, false).await?;
+            target_driver.execute_statements(&statements).await?;
+            println!("{} plan statements applied.", statements.len());
+
+            write_audit_record(
+                &forge_config,
+                "apply-plan",
+                &target,
+                &statements,
+                schema_plan.rollback,
+            )?;
+
+            Ok(())
+        }
+
+        // report of table-level grants, optionally applied to the target
+        Commands::Replay {
+            target,
+            file,
+            config,
+            dry_run,
+        } => {
+            let forge_config = options.load_config(config)?;
+            ops::error_log::configure(&forge_config);
+            let file = file.unwrap_or_else(|| PathBuf::from(ops::dead_letter::DEAD_LETTER_FILE));
+
+            let target_driver = drivers::create_driver(&target, &forge_config, false).await?;
+            let rows = ops::dead_letter::replay(target_driver.as_ref(), &file, dry_run).await?;
+
+            if dry_run {
+                println!("{rows} rows would be replayed.");
+            } else {
+                println!(
+                    "{rows} rows replayed, rows failing again are recorded in {}.",
+                    ops::dead_letter::DEAD_LETTER_FILE
+                );
+            }
+            Ok(())
+        }
+        Commands::Doctor {
+            source,
+            target,
+            config,
+            format,
+        } => {
+            let config_title = match config {
+                Some(ref path) => format!("Config {}", path.display()),
+                None => "Config (built-in default)".to_string(),
+            };
+            let (forge_config, config_check) = match options.load_config(config) {
+                Ok(forge_config) => (forge_config, ForgePreflightCheck::pass("config", "valid")),
+                Err(e) => (
+                    ForgeConfig::default(),
+                    ForgePreflightCheck::fail("config", e.to_string()),
+                ),
+            };
+            let mut config_checks = vec![config_check];
+            if let (Some(source), Some(target)) = (&source, &target) {
+                config_checks.push(ops::doctor::combination_check(source, target));
+            }
+
+            let mut sections = vec![ops::doctor::DoctorSection {
+                title: config_title,
+                checks: config_checks,
+            }];
+            if let Some(ref source) = source {
+                sections.push(ops::doctor::check_database(source, &forge_config, false).await);
+            }
+            if let Some(ref target) = target {
+                sections.push(ops::doctor::check_database(target, &forge_config, true).await);
+            }
+
+            match format {
+                ReportFormat::Text => print!("{}", ops::doctor::doctor_report(&sections)),
+                ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&sections)?),
             }
-            ops::replicate_data(
-                source_driver.as_ref(),
-                target_driver.as_ref(),
-                &source_schema,
-                dump,
-                dry_run,
-                verbose,
-                halt_on_error,
-                verify_enabled,
-            )
-            .await?;
 
+            let failed = ops::doctor::failed_checks(&sections);
+            if failed > 0 {
+                return Err(format!("{failed} pre-flight check(s) failed").into());
+            }
+            Ok(())
+        }
+
+        Commands::Grants {
+            source,
+            target,
+            config,
+            dry_run,
+        } => {
+            let forge_config = options.load_config(config)?;
+
+            let source_driver = drivers::create_driver(&source, &forge_config, true).await?;
+            let grants = source_driver.fetch_grants().await?;
+
+            print!("{}", ops::grants::grants_report(&grants));
+
+            if let Some(target) = target {
+                let target_driver = drivers::create_driver(&target, &forge_config, false).await?;
+                let statements = target_driver
+                    .apply_grants(&grants, &forge_config, dry_run)
+                    .await?;
+
+                if dry_run {
+                    println!("--- DRY RUN START: GRANT statements ---");
+                    for sql in statements {
+                        println!("{sql}");
+                    }
+                    println!("--- DRY RUN END: GRANT statements ---");
+                } else {
+                    println!("{} GRANT statements applied.", statements.len());
+                }
+            }
+
+            Ok(())
+        }
+        Commands::Daemon { listen, config_dir } => {
+            let token = std::env::var("FLUXFORGE_DAEMON_TOKEN")
+                .ok()
+                .filter(|t| !t.is_empty());
+            if token.is_none() && !listen.ip().is_loopback() {
+                warn!(%listen, "the daemon API is reachable from the network without FLUXFORGE_DAEMON_TOKEN");
+            }
+            let options = options.clone();
+            Daemon::new(
+                Arc::new(move |request, cancel| run_daemon_job(request, cancel, &options)),
+                token,
+            )
+            .config_dir(config_dir)
+            .serve(listen)
+            .await
+        }
+        Commands::InitConfig { file, force } => {
+            write_config_template(&file, force)?;
+            println!("Config template written to {}", file.display());
+            Ok(())
+        }
+        Commands::ValidateConfig { config } => {
+            let content = std::fs::read_to_string(&config)
+                .map_err(|e| format!("Error reading config file {}: {e}", config.display()))?;
+            let problems = validate_config(&content);
+            print!(
+                "{}",
+                problems_report(&config.display().to_string(), &problems)
+            );
+            if problems.iter().any(|p| p.severity == Severity::Error) {
+                return Err(format!("config file {} is invalid", config.display()).into());
+            }
             Ok(())
         }
     }
@@ -1,11 +1,76 @@
-use clap::{ArgGroup, Parser, Subcommand};
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+use fluxforge::core::{
+    ForgeDialect, ForgeDocFormat, ForgeExportFormat, ForgeGraphFormat, ForgeSchemaFormat,
+    ForgeVerifyLevel,
+};
+use fluxforge::ops::progress::ProgressMode;
+use fluxforge::{ForgeKeyRange, ForgeObjectFilter, ForgeVerifySample};
+use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(name = "fluxforge", version, about = "Database smithing tool")]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+
+    /// Log level or filter directive (error, warn, info, debug, trace), overrides RUST_LOG
+    #[arg(long, global = true)]
+    pub log_level: Option<String>,
+
+    /// Log output format
+    #[arg(long, global = true, value_enum, default_value_t = LogFormat::Text)]
+    pub log_format: LogFormat,
+
+    /// Progress output on stderr: none (only errors), plain (a line per table, for CI), bar or
+    /// events (JSON lines)
+    #[arg(long, global = true, default_value = "bar")]
+    pub progress: ProgressMode,
+
+    /// Export tracing spans to this OTLP/gRPC collector (e.g. http://localhost:4317), needs the `otel` feature
+    #[arg(long, global = true)]
+    pub otel_endpoint: Option<String>,
+
+    /// Tunnel the database connections through this SSH bastion (user@bastion[:port]), overrides [ssh] host
+    #[arg(long, global = true)]
+    pub ssh: Option<String>,
+
+    /// Private key of the SSH tunnel, overrides [ssh] identity_file
+    #[arg(long, global = true)]
+    pub ssh_identity: Option<PathBuf>,
+
+    /// File holding the password of the source connection, overrides FLUXFORGE_SOURCE_PASSWORD
+    #[arg(long, global = true)]
+    pub source_password_file: Option<PathBuf>,
+
+    /// File holding the password of the target connection, overrides FLUXFORGE_TARGET_PASSWORD
+    #[arg(long, global = true)]
+    pub target_password_file: Option<PathBuf>,
+
+    /// Apply the [profiles.<name>] overrides of the config file
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+
+    /// Print the config after includes and profile are merged to stderr
+    #[arg(long, global = true)]
+    pub print_effective_config: bool,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum LogFormat {
+    /// human readable log lines
+    Text,
+    /// one JSON object per log event
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum ReportFormat {
+    /// human readable report
+    Text,
+    /// JSON document
+    Json,
 }
 
 #[derive(Subcommand)]
@@ -15,9 +80,36 @@ pub enum Commands {
         #[arg(long)]
         source: String,
 
+        /// Schema JSON or YAML file, or s3://bucket/key.json
         #[arg(long)]
         schema: PathBuf,
 
+        /// Schema file format: json or yaml (defaults to the extension of --schema, else json)
+        #[arg(long)]
+        format: Option<ForgeSchemaFormat>,
+
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Verbose output
+        #[arg(long)]
+        verbose: bool,
+    },
+    /// Write the data of every table into a file per table
+    Export {
+        /// source DB-URL
+        #[arg(long)]
+        source: String,
+
+        /// Directory the files are written to, created if missing, or s3://bucket/prefix
+        #[arg(long)]
+        output: PathBuf,
+
+        /// File format: csv or parquet (feature `parquet`)
+        #[arg(long, default_value = "csv")]
+        format: ForgeExportFormat,
+
+        /// Config-File, [export] sets delimiter, quoting and NULL value
         #[arg(long)]
         config: Option<PathBuf>,
 
@@ -25,6 +117,187 @@ pub enum Commands {
         #[arg(long)]
         verbose: bool,
     },
+    /// Render the schema into documentation pages, one page per table
+    #[command(group(ArgGroup::new("input").required(true).args(["source", "schema"])))]
+    Document {
+        /// source DB-URL
+        #[arg(long)]
+        source: Option<String>,
+
+        /// schema JSON or YAML file used instead of --source
+        #[arg(long)]
+        schema: Option<PathBuf>,
+
+        /// Directory the pages are written to, created if missing, or s3://bucket/prefix
+        #[arg(long)]
+        out: PathBuf,
+
+        /// Page format: markdown or html
+        #[arg(long, default_value = "markdown")]
+        format: ForgeDocFormat,
+
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+    /// Print the foreign key graph of the schema as an ER diagram
+    #[command(group(ArgGroup::new("input").required(true).args(["source", "schema"])))]
+    Graph {
+        /// source DB-URL
+        #[arg(long)]
+        source: Option<String>,
+
+        /// schema JSON or YAML file used instead of --source
+        #[arg(long)]
+        schema: Option<PathBuf>,
+
+        /// Diagram format: mermaid or dot
+        #[arg(long, default_value = "mermaid")]
+        format: ForgeGraphFormat,
+
+        /// File the diagram is written to instead of stdout, or s3://bucket/key
+        #[arg(long)]
+        out: Option<PathBuf>,
+
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+    /// Check a schema for migration hazards, severities are set in [lint.rules]
+    #[command(group(ArgGroup::new("input").required(true).args(["source", "schema"])))]
+    Lint {
+        /// source DB-URL
+        #[arg(long)]
+        source: Option<String>,
+
+        /// schema JSON or YAML file used instead of --source
+        #[arg(long)]
+        schema: Option<PathBuf>,
+
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Report format
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+    },
+    /// Report what of the source does not translate cleanly to the target dialect
+    Compat {
+        /// source DB-URL
+        #[arg(long)]
+        source: String,
+
+        /// Dialect of the target: mysql or postgres
+        #[arg(long)]
+        target_dialect: ForgeDialect,
+
+        /// Only check the schema, without queries of the data and routines of the source
+        #[arg(long)]
+        skip_data: bool,
+
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Report format
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+    },
+    /// List the tables of a database with their row counts and sizes
+    ListTables {
+        /// DB-URL
+        #[arg(long)]
+        source: String,
+
+        /// Count the rows instead of the estimate of the catalog statistics
+        #[arg(long)]
+        exact: bool,
+
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Report format
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+    },
+    /// Print a table as FluxForge reads it, after the type mapping of the config
+    Inspect {
+        /// Table name in the source
+        table: String,
+
+        /// DB-URL
+        #[arg(long)]
+        source: String,
+
+        /// Show the table as written to this dialect (mysql or postgres): target types and names
+        #[arg(long)]
+        dialect: Option<ForgeDialect>,
+
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Report format
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+    },
+    /// Dump the rows of every table into a newline-delimited JSON file
+    DumpData {
+        /// source DB-URL
+        #[arg(long)]
+        source: String,
+
+        /// Dump file or s3://bucket/key, gzip compressed if the name ends with .gz
+        #[arg(long)]
+        file: PathBuf,
+
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+    /// Insert the rows of a dump-data file into the existing tables of the target
+    LoadData {
+        /// target DB-URL
+        #[arg(long)]
+        target: String,
+
+        /// Dump file written by dump-data (or replicate --dump), .gz files are decompressed
+        #[arg(long)]
+        file: PathBuf,
+
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Output the INSERT statements without executing them
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Stop at the first rejected row instead of recording it in the dead-letter file
+        #[arg(long)]
+        halt_on_error: bool,
+    },
+    /// Stream schema and data to stdout for `receive`, i.e. `send ... | ssh host fluxforge receive ...`
+    Send {
+        /// source DB-URL
+        #[arg(long)]
+        source: String,
+
+        /// Config-File with the transformations, the same file is passed to receive
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+    /// Create the schema and insert the data of a `send` stream read from stdin
+    Receive {
+        /// target DB-URL, the database must be empty
+        #[arg(long)]
+        target: String,
+
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Output SQL statements without executing them
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Stop at the first rejected row instead of recording it in the dead-letter file
+        #[arg(long)]
+        halt_on_error: bool,
+    },
     /// Migrate structure and optionally data
     #[command(group(
         ArgGroup::new("input")
@@ -36,7 +309,7 @@ pub enum Commands {
         #[arg(long)]
         source: Option<String>,
 
-        /// Path to internal schema JSON file
+        /// Path to internal schema JSON or YAML file, or s3://bucket/key.json
         #[arg(long)]
         schema: Option<PathBuf>,
 
@@ -55,17 +328,43 @@ pub enum Commands {
         #[arg(long)]
         verbose: bool,
 
+        /// Drop tables, columns and indices missing in the source, after a confirmation
         #[arg(long)]
         allow_destructive: bool,
+
+        /// Confirm the destructive changes of --allow-destructive without a prompt
+        #[arg(long)]
+        yes: bool,
+
+        /// Only apply the changes of these tables, i.e. "ddl:users,orders"
+        #[arg(long)]
+        only: Option<ForgeObjectFilter>,
+
+        /// Fail instead of warning if a type mapping narrows the data
+        #[arg(long)]
+        strict_types: bool,
+
+        /// Generate DDL that skips existing objects, to re-run an interrupted migration
+        #[arg(long)]
+        idempotent_ddl: bool,
+
+        /// Write the statements reverting the schema changes to this SQL file, also with --dry-run
+        #[arg(long)]
+        rollback_file: Option<PathBuf>,
+
+        /// Write the planned statements with their classification to this file (.sql or .json),
+        /// applied later with apply-plan
+        #[arg(long)]
+        plan_out: Option<PathBuf>,
     },
     Replicate {
         /// source DB-URL, typically MYSQL
-        #[arg(long)]
-        source: String,
+        #[arg(long, required_unless_present = "resume_from")]
+        source: Option<String>,
 
         /// target DB-URL, typically PostgreSQL
-        #[arg(long)]
-        target: String,
+        #[arg(long, required_unless_present = "resume_from")]
+        target: Option<String>,
 
         /// Config-File with transformations to apply to the schema
         #[arg(long)]
@@ -87,8 +386,320 @@ pub enum Commands {
         #[arg(long)]
         halt_on_error: bool,
 
-        /// Verify data after each table write
+        /// Verify data after write: "full" (default, every row after each table) or "counts"
+        /// (only the row counts of all tables)
+        #[arg(long, num_args = 0..=1, default_missing_value = "full")]
+        verify: Option<ForgeVerifyLevel>,
+
+        /// Verify only a sample of the rows selected by primary key hash, i.e. "1%" (implies --verify)
+        #[arg(long)]
+        verify_sample: Option<ForgeVerifySample>,
+
+        /// Verify binary and large text columns by a SHA-256 digest computed on both servers
+        /// (implies --verify)
+        #[arg(long)]
+        verify_digest: bool,
+
+        /// Record every mismatching row in this JSON file and fail at the end (implies --verify)
+        #[arg(long)]
+        verify_report: Option<PathBuf>,
+
+        /// Store checksums of the primary key ranges in this file, a later verify with the same
+        /// file only compares the ranges changed since
+        #[arg(long)]
+        checksum_file: Option<PathBuf>,
+
+        /// Seed of randomized decisions like the verification sample and the retry jitter, printed in the run summary
+        #[arg(long)]
+        seed: Option<u64>,
+
+        /// Concurrent insert workers per table, each writing through its own connection
+        #[arg(long)]
+        insert_workers: Option<usize>,
+
+        /// Write every table with a single connection in source order
+        #[arg(long, conflicts_with = "insert_workers")]
+        ordered_inserts: bool,
+
+        /// Byte budget of a chunk of rows (default 64 MiB), wide rows are written in smaller chunks
+        #[arg(long)]
+        max_chunk_bytes: Option<u64>,
+
+        /// Disable foreign key checks and triggers of the target during the load, the foreign
+        /// keys are validated afterwards
+        #[arg(long)]
+        disable_constraints: bool,
+
+        /// Fail instead of warning if a type mapping narrows the data
+        #[arg(long)]
+        strict_types: bool,
+
+        /// Generate DDL that skips existing objects, to re-run an interrupted replication
+        #[arg(long)]
+        idempotent_ddl: bool,
+
+        /// Count the bigint unsigned values above the signed bigint range before the data load
+        #[arg(long)]
+        scan_unsigned: bool,
+
+        /// Print the size estimation and predicted duration, then exit without replicating
+        #[arg(long)]
+        estimate_only: bool,
+
+        /// Write the statements reverting the schema changes to this SQL file, also with --dry-run
+        #[arg(long)]
+        rollback_file: Option<PathBuf>,
+
+        /// Expose Prometheus metrics on this address (e.g. 127.0.0.1:9184)
+        #[arg(long)]
+        metrics_addr: Option<SocketAddr>,
+
+        /// Continue an aborted run with the token printed on failure
+        #[arg(long)]
+        resume_from: Option<String>,
+
+        /// Only create the tables of the target, with their foreign keys, without copying rows
+        #[arg(long, conflicts_with_all = ["data_only", "dump", "verify", "checksum_file", "resume_from", "estimate_only"])]
+        schema_only: bool,
+
+        /// Only copy the rows into the existing tables of an earlier --schema-only run
+        #[arg(long)]
+        data_only: bool,
+
+        /// Allow a target with tables, i.e. a prepared database with roles and extensions;
+        /// tables of the replication that already have rows are refused unless --truncate
+        #[arg(long, conflicts_with = "resume_from")]
+        force_nonempty: bool,
+
+        /// Delete the rows of the target tables that already have data before the load
+        #[arg(long, requires = "force_nonempty")]
+        truncate: bool,
+
+        /// Keep running after the initial load and append the new source rows every --interval
+        #[arg(long, conflicts_with_all = ["dry_run", "dump", "estimate_only", "schema_only"])]
+        watch: bool,
+
+        /// Pause between the sync cycles of --watch, i.e. 30s, 15m or 2h
+        #[arg(long, requires = "watch", default_value = "15m", value_parser = fluxforge::ops::watch::parse_interval)]
+        interval: Duration,
+
+        /// Report source schema changes before every sync cycle of --watch
+        #[arg(long, requires = "watch")]
+        detect_drift: bool,
+    },
+    /// Compare the rows of an existing replication again without copying anything
+    Verify {
+        /// source DB-URL of the replication
+        #[arg(long)]
+        source: String,
+
+        /// target DB-URL of the replication
+        #[arg(long)]
+        target: String,
+
+        /// Config-File of the replication, with its mappings and verification settings
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Checksum file of replicate --checksum-file, only the key ranges changed since are
+        /// compared; the file is updated after a clean verification
+        #[arg(long)]
+        checksum_file: Option<PathBuf>,
+
+        /// Only verify these source tables (comma-separated)
+        #[arg(long, value_delimiter = ',', conflicts_with = "checksum_file")]
+        tables: Vec<String>,
+
+        /// Only verify the rows of this inclusive primary key range of --tables, i.e. 100000:200000
+        #[arg(long, requires = "tables")]
+        pk_range: Option<ForgeKeyRange>,
+
+        /// Record every mismatching row in this JSON file and fail at the end
+        #[arg(long)]
+        verify_report: Option<PathBuf>,
+    },
+    /// Read-only report of the schema differences between two databases or schema files
+    #[command(
+        group(ArgGroup::new("side_a").required(true).args(["source", "schema_a"])),
+        group(ArgGroup::new("side_b").required(true).args(["target", "schema_b"])),
+    )]
+    Diff {
+        /// source DB-URL (expected state)
+        #[arg(long)]
+        source: Option<String>,
+
+        /// target DB-URL (actual state)
+        #[arg(long)]
+        target: Option<String>,
+
+        /// schema JSON file used instead of --source
+        #[arg(long)]
+        schema_a: Option<PathBuf>,
+
+        /// schema JSON file used instead of --target
         #[arg(long)]
-        verify: bool,
+        schema_b: Option<PathBuf>,
+
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Report format
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+
+        /// Only print a drift summary and exit with code 2 if the schemas differ
+        #[arg(long)]
+        check: bool,
+    },
+    /// Revert the schema changes of a previous migrate/replicate recorded in the audit log
+    Rollback {
+        /// Run id printed when the changes were applied
+        #[arg(long)]
+        run: String,
+
+        /// target DB-URL the run was applied to
+        #[arg(long)]
+        target: String,
+
+        /// Config-File, [general] audit_log sets the audit log path
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Output the rollback statements without executing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Apply the statements of a plan written by migrate --plan-out verbatim
+    ApplyPlan {
+        /// Plan file (.sql or .json), local or s3://
+        plan: PathBuf,
+
+        /// target DB-URL the plan is applied to
+        #[arg(long)]
+        target: String,
+
+        /// Config-File, [general] audit_log sets the audit log path
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Output the statements of the plan without executing them
+        #[arg(long)]
+        dry_run: bool,
     },
+    /// Insert the rows of a dead-letter file into the target again
+    Replay {
+        /// target DB-URL the rows are inserted into
+        #[arg(long)]
+        target: String,
+
+        /// Dead-letter file written by replicate (defaults to migration_dead_letter.ndjson)
+        #[arg(long)]
+        file: Option<PathBuf>,
+
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Output the INSERT statements without executing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Pre-flight check of connectivity, privileges, server settings and config before a migration
+    #[command(group(
+        ArgGroup::new("database")
+            .required(true)
+            .multiple(true)
+            .args(["source", "target"]),
+    ))]
+    Doctor {
+        /// source DB-URL
+        #[arg(long)]
+        source: Option<String>,
+
+        /// target DB-URL, checked for write privileges and emptiness
+        #[arg(long)]
+        target: Option<String>,
+
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Report format
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+    },
+    /// Report table-level grants of the source and optionally migrate them to the target
+    Grants {
+        /// source DB-URL
+        #[arg(long)]
+        source: String,
+
+        /// target DB-URL, without target only the report is printed
+        #[arg(long)]
+        target: Option<String>,
+
+        /// Config-File with the grantee mapping ([grants.user_mapping])
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Output GRANT statements without executing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Run as a service: start replication jobs and report their progress over an HTTP API,
+    /// clients authenticate with the bearer token of FLUXFORGE_DAEMON_TOKEN if it is set
+    Daemon {
+        /// Address of the HTTP API
+        #[arg(long, default_value = "127.0.0.1:9190")]
+        listen: SocketAddr,
+
+        /// Only accept job config files from this directory
+        #[arg(long)]
+        config_dir: Option<PathBuf>,
+    },
+    /// Write a commented config template with all sections and their defaults
+    InitConfig {
+        /// Config file to write
+        #[arg(long, default_value = "fluxforge.toml")]
+        file: PathBuf,
+
+        /// Overwrite an existing file
+        #[arg(long)]
+        force: bool,
+    },
+    /// Check a config file strictly: unknown keys and type names, with line and column
+    ValidateConfig {
+        /// Config file to check
+        #[arg(long)]
+        config: PathBuf,
+    },
+}
+
+impl Commands {
+    /// config file of the command, `None` for the embedded default
+    pub fn config_path(&self) -> Option<PathBuf> {
+        match self {
+            Commands::Extract { config, .. }
+            | Commands::Export { config, .. }
+            | Commands::Document { config, .. }
+            | Commands::Graph { config, .. }
+            | Commands::Lint { config, .. }
+            | Commands::Compat { config, .. }
+            | Commands::ListTables { config, .. }
+            | Commands::Inspect { config, .. }
+            | Commands::DumpData { config, .. }
+            | Commands::LoadData { config, .. }
+            | Commands::Send { config, .. }
+            | Commands::Receive { config, .. }
+            | Commands::Migrate { config, .. }
+            | Commands::Replicate { config, .. }
+            | Commands::Verify { config, .. }
+            | Commands::Diff { config, .. }
+            | Commands::Rollback { config, .. }
+            | Commands::ApplyPlan { config, .. }
+            | Commands::Replay { config, .. }
+            | Commands::Doctor { config, .. }
+            | Commands::Grants { config, .. } => config.clone(),
+            Commands::ValidateConfig { config } => Some(config.clone()),
+            Commands::Daemon { .. } | Commands::InitConfig { .. } => None,
+        }
+    }
 }
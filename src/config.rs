@@ -1,19 +1,215 @@
 //! Configuration loading and management.
 //!
 //! This module provides functions for loading FluxForge configuration from TOML files
-//! or using embedded defaults.
+//! or using embedded defaults. String values of a file may reference environment variables
+//! as `${NAME}` or `${NAME:-default}`, `$${` keeps a literal `${`.
+//!
+//! A file can `include = ["base.toml"]` other files and define `[profiles.<name>]`
+//! overrides. The merge order is fixed: the included files in their order (each after its
+//! own includes), then the file itself, then the profile of `--profile`. Tables are merged
+//! key by key, any other value (arrays as well) replaces the earlier one.
+
+pub mod validate;
 
 use crate::core::ForgeConfig;
 use std::error::Error;
+use std::path::{Path, PathBuf};
+use toml_edit::visit_mut::{self, VisitMut};
+use toml_edit::{DocumentMut, Formatted, Item, KeyMut};
 
 // this file will be baked into binary as default if no --config option is used
 const DEFAULT_CONFIG_STR: &str = include_str!("../examples/mysql2postgres.toml");
 
+/// Commented template of all config sections, written by `fluxforge init-config`.
+pub const CONFIG_TEMPLATE: &str = include_str!("../examples/template.toml");
+
+/// Expands the `${NAME}` and `${NAME:-default}` references of a value, `lookup` returns
+/// the value of a variable.
+///
+/// # Errors
+///
+/// Returns an error naming the variable if it is unset and has no default, or the
+/// reference is not closed.
+pub fn expand_env_vars(
+    value: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<String, String> {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(escaped) = rest.strip_prefix("$${") {
+            out.push_str("${");
+            rest = escaped;
+        } else if let Some(reference) = rest.strip_prefix("${") {
+            let end = reference
+                .find('}')
+                .ok_or_else(|| format!("unclosed `${{` in `{value}`"))?;
+            let (name, default) = match reference[..end].split_once(":-") {
+                Some((name, default)) => (name, Some(default)),
+                None => (&reference[..end], None),
+            };
+            let expanded = lookup(name)
+                .filter(|v| !v.is_empty() || default.is_none())
+                .or_else(|| default.map(str::to_string))
+                .ok_or_else(|| format!("environment variable {name} is not set"))?;
+            out.push_str(&expanded);
+            rest = &reference[end + 1..];
+        } else {
+            out.push('$');
+            rest = &rest[1..];
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Expands the environment variables of all string values, keys stay as they are.
+struct EnvInterpolation {
+    path: Vec<String>,
+    error: Option<String>,
+}
+
+impl VisitMut for EnvInterpolation {
+    fn visit_table_like_kv_mut(&mut self, key: KeyMut<'_>, node: &mut Item) {
+        self.path.push(key.get().to_string());
+        visit_mut::visit_table_like_kv_mut(self, key, node);
+        self.path.pop();
+    }
+
+    fn visit_string_mut(&mut self, node: &mut Formatted<String>) {
+        if self.error.is_some() || !node.value().contains('$') {
+            return;
+        }
+        match expand_env_vars(node.value(), |name| std::env::var(name).ok()) {
+            Ok(expanded) => {
+                let decor = node.decor().clone();
+                *node = Formatted::new(expanded);
+                *node.decor_mut() = decor;
+            }
+            Err(e) => self.error = Some(format!("{e} (config key {})", self.path.join("."))),
+        }
+    }
+}
+
+/// config content with the environment variables of its string values expanded
+fn interpolate_env(content: &str) -> Result<String, Box<dyn Error>> {
+    if !content.contains('$') {
+        return Ok(content.to_string());
+    }
+    let mut doc: DocumentMut = content.parse()?;
+    let mut interpolation = EnvInterpolation {
+        path: Vec::new(),
+        error: None,
+    };
+    interpolation.visit_document_mut(&mut doc);
+    match interpolation.error {
+        Some(e) => Err(e.into()),
+        None => Ok(doc.to_string()),
+    }
+}
+
+/// Merges `overlay` into `base`, tables key by key, other values replace the base value.
+pub fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base_table)), toml::Value::Table(overlay_table)) => {
+                merge_tables(base_table, overlay_table);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Content of the file with its includes merged below it, `stack` holds the files being
+/// loaded to detect include cycles.
+fn load_layered(path: &Path, stack: &mut Vec<PathBuf>) -> Result<toml::Table, Box<dyn Error>> {
+    let canonical = path
+        .canonicalize()
+        .map_err(|e| format!("Error reading config file {}: {e}", path.display()))?;
+    if stack.contains(&canonical) {
+        return Err(format!("config file {} includes itself", path.display()).into());
+    }
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Error reading config file {}: {e}", path.display()))?;
+    let mut table: toml::Table = toml::from_str(&interpolate_env(&content)?)
+        .map_err(|e| format!("Error parsing config file {}: {e}", path.display()))?;
+
+    let mut merged = toml::Table::new();
+    if let Some(include) = table.remove("include") {
+        let include: Vec<PathBuf> = include
+            .try_into()
+            .map_err(|e| format!("include of {} is no list of files: {e}", path.display()))?;
+        stack.push(canonical);
+        for file in include {
+            let file = path.parent().unwrap_or(Path::new("")).join(file);
+            merge_tables(&mut merged, load_layered(&file, stack)?);
+        }
+        stack.pop();
+    }
+    merge_tables(&mut merged, table);
+    Ok(merged)
+}
+
+/// Applies the profile to the merged config and removes all profiles.
+fn apply_profile(table: &mut toml::Table, profile: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let profiles = table.remove("profiles");
+    let Some(name) = profile else {
+        return Ok(());
+    };
+    let mut profiles = match profiles {
+        Some(toml::Value::Table(profiles)) => profiles,
+        _ => toml::Table::new(),
+    };
+    match profiles.remove(name) {
+        Some(toml::Value::Table(overrides)) => {
+            merge_tables(table, overrides);
+            Ok(())
+        }
+        Some(_) => Err(format!("profile {name} is no table").into()),
+        None => {
+            let mut known: Vec<&String> = profiles.keys().collect();
+            known.sort();
+            Err(format!(
+                "unknown profile {name}, the config has {}",
+                if known.is_empty() {
+                    "no profiles".to_string()
+                } else {
+                    known
+                        .iter()
+                        .map(|k| k.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                }
+            )
+            .into())
+        }
+    }
+}
+
+/// Loads a config file with its includes and the profile applied.
+///
+/// # Errors
+///
+/// Returns an error if a file cannot be read or parsed, the includes form a cycle, the
+/// profile does not exist or the merged config is invalid.
+pub fn load_config_file(path: &Path, profile: Option<&str>) -> Result<ForgeConfig, Box<dyn Error>> {
+    let mut table = load_layered(path, &mut Vec::new())?;
+    apply_profile(&mut table, profile)?;
+    Ok(toml::Value::Table(table).try_into()?)
+}
+
 /// Loads configuration from a file or uses the embedded default.
 ///
 /// If no path is provided, the embedded default configuration (mysql2postgres.toml)
 /// is used. The configuration defines type mappings, transformation rules, and
-/// other settings for database migration.
+/// other settings for database migration. `${NAME}` references of string values are
+/// replaced by the environment variable, `${NAME:-default}` falls back to the default.
+/// The includes of the file and the profile set with [`configure`] are merged as
+/// described in the [module documentation](self).
 ///
 /// # Arguments
 ///
@@ -41,16 +237,120 @@ const DEFAULT_CONFIG_STR: &str = include_str!("../examples/mysql2postgres.toml")
 /// - The specified file cannot be read
 /// - The TOML content is invalid or cannot be parsed
 /// - Required configuration fields are missing
+/// - A referenced environment variable is not set and has no default
+/// - An include cannot be loaded or the profile does not exist
 pub fn load_config(user_path: Option<std::path::PathBuf>) -> Result<ForgeConfig, Box<dyn Error>> {
-    let config_content = match user_path {
-        Some(path) => std::fs::read_to_string(path)?,
-        None => DEFAULT_CONFIG_STR.to_string(),
-    };
+    load_config_profile(user_path, None)
+}
 
-    let config: ForgeConfig = toml::from_str(&config_content)?;
+/// Loads the configuration like [`load_config`] and applies the `[profiles.<name>]` overrides
+/// of `profile` (`--profile`).
+///
+/// # Errors
+///
+/// Returns the errors of [`load_config`], or an error if the profile does not exist or is
+/// given without a config file.
+pub fn load_config_profile(
+    user_path: Option<std::path::PathBuf>,
+    profile: Option<&str>,
+) -> Result<ForgeConfig, Box<dyn Error>> {
+    let mut config: ForgeConfig = match user_path {
+        Some(path) => {
+            let content = std::fs::read_to_string(&path)?;
+            // a file without includes is parsed directly, its errors keep line and column
+            let config: ForgeConfig = toml::from_str(&interpolate_env(&content)?)?;
+            if config.include.is_none() && profile.is_none() {
+                config
+            } else {
+                load_config_file(&path, profile)?
+            }
+        }
+        None => {
+            if let Some(profile) = profile {
+                return Err(format!("--profile {profile} needs a --config file").into());
+            }
+            toml::from_str(DEFAULT_CONFIG_STR)?
+        }
+    };
+    config.profiles = None;
     Ok(config)
 }
 
+/// placeholder of a redacted secret
+const REDACTED: &str = "***";
+
+/// The config as TOML with its secrets redacted, printed by `--print-effective-config`.
+///
+/// Values of keys naming a password, secret, token or key are replaced, as are the passwords
+/// of database URLs and the path and query of HTTP URLs, where webhooks keep their token.
+///
+/// # Errors
+///
+/// Returns an error if the config cannot be serialized.
+pub fn effective_config(config: &ForgeConfig) -> Result<String, Box<dyn Error>> {
+    // through a toml::Value, its tables print their keys sorted
+    let mut value = toml::Value::try_from(config)?;
+    redact_secrets(&mut value, None);
+    Ok(toml::to_string_pretty(&value)?)
+}
+
+/// replaces the secrets of a config value in place, `key` is the key holding the value
+fn redact_secrets(value: &mut toml::Value, key: Option<&str>) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table.iter_mut() {
+                redact_secrets(value, Some(key));
+            }
+        }
+        toml::Value::Array(values) => {
+            for value in values {
+                redact_secrets(value, key);
+            }
+        }
+        toml::Value::String(text) => {
+            if key.is_some_and(is_secret_key) {
+                *text = REDACTED.to_string();
+            } else if let Some(redacted) = redact_url(text) {
+                *text = redacted;
+            }
+        }
+        _ => {}
+    }
+}
+
+/// keys holding a secret itself, not the file or variable it is read from
+fn is_secret_key(key: &str) -> bool {
+    let key = key.to_lowercase();
+    !key.ends_with("_file")
+        && !key.ends_with("_env")
+        && [
+            "password",
+            "secret",
+            "token",
+            "access_key",
+            "api_key",
+            "authorization",
+        ]
+        .iter()
+        .any(|secret| key.contains(secret))
+}
+
+/// URL without password, HTTP URLs also without path and query; `None` if nothing is secret
+fn redact_url(text: &str) -> Option<String> {
+    let mut url = url::Url::parse(text).ok()?;
+    let mut redacted = false;
+    if url.password().is_some() {
+        url.set_password(Some(REDACTED)).ok()?;
+        redacted = true;
+    }
+    if matches!(url.scheme(), "http" | "https") && (url.path() != "/" || url.query().is_some()) {
+        url.set_path(REDACTED);
+        url.set_query(None);
+        redacted = true;
+    }
+    redacted.then(|| url.to_string())
+}
+
 /// Returns the configuration file path as a string.
 ///
 /// This is primarily used for metadata and logging purposes to track which
@@ -79,3 +379,165 @@ pub fn get_config_file_path(user_path: Option<std::path::PathBuf>) -> String {
         None => "../examples/mysql2postgres.toml".to_string(),
     }
 }
+
+/// Writes the commented config template to a file.
+///
+/// # Errors
+///
+/// Returns an error if the file exists and `force` is not set, or it cannot be written.
+pub fn write_config_template(path: &std::path::Path, force: bool) -> Result<(), Box<dyn Error>> {
+    if path.exists() && !force {
+        return Err(format!("{} exists, use --force to overwrite it", path.display()).into());
+    }
+    std::fs::write(path, CONFIG_TEMPLATE)
+        .map_err(|e| format!("Error writing {}: {e}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn template_has_the_defaults_of_the_embedded_config() {
+        let template: ForgeConfig = toml::from_str(CONFIG_TEMPLATE).unwrap();
+        let default = load_config(None).unwrap();
+        for (db, direction) in [
+            ("mysql", "on_read"),
+            ("mysql", "on_write"),
+            ("postgres", "on_read"),
+            ("postgres", "on_write"),
+        ] {
+            assert_eq!(
+                template.get_type_list(db, direction),
+                default.get_type_list(db, direction),
+                "{db}.types.{direction}"
+            );
+        }
+        assert!(crate::drivers::zero_date_on_write(&template));
+    }
+
+    #[test]
+    fn commented_settings_parse() {
+        // every commented line of a section is a valid setting of it
+        let uncommented: String = CONFIG_TEMPLATE
+            .lines()
+            .map(|line| {
+                line.strip_prefix("# ")
+                    .filter(|l| l.starts_with('[') || l.contains(" = "))
+                    .unwrap_or(line)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let config: ForgeConfig = toml::from_str(&uncommented).unwrap();
+        assert_eq!(config.get_insert_workers(), 4);
+        assert!(config.get_ssh(true).is_some());
+    }
+
+    #[test]
+    fn existing_files_need_force() {
+        let path = std::env::temp_dir().join(format!("fluxforge-init-{}.toml", std::process::id()));
+        write_config_template(&path, false).unwrap();
+        assert!(write_config_template(&path, false).is_err());
+        write_config_template(&path, true).unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn environment_variables_are_expanded() {
+        let lookup = |name: &str| match name {
+            "DB_HOST" => Some("db.internal".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        };
+        assert_eq!(
+            expand_env_vars("mysql://${DB_HOST}:3306/shop", lookup).unwrap(),
+            "mysql://db.internal:3306/shop"
+        );
+        assert_eq!(
+            expand_env_vars("${EMPTY:-fallback} ${UNSET:-} $${DB_HOST} $5", lookup).unwrap(),
+            "fallback  ${DB_HOST} $5"
+        );
+        assert_eq!(
+            expand_env_vars("${UNSET}", lookup).unwrap_err(),
+            "environment variable UNSET is not set"
+        );
+        assert!(expand_env_vars("${DB_HOST", lookup).is_err());
+    }
+
+    #[test]
+    fn only_string_values_are_interpolated() {
+        let content = "[general]\nerror_log = { path = \"${FLUXFORGE_TEST_UNSET:-errors.log}\" }\n";
+        let config: ForgeConfig = toml::from_str(&interpolate_env(content).unwrap()).unwrap();
+        assert_eq!(
+            config.general.unwrap().error_log.unwrap().path.unwrap(),
+            std::path::PathBuf::from("errors.log")
+        );
+
+        let err = interpolate_env("[ssh]\nhost = \"${FLUXFORGE_TEST_UNSET}\"\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "environment variable FLUXFORGE_TEST_UNSET is not set (config key ssh.host)"
+        );
+    }
+
+    #[test]
+    fn includes_and_profiles_merge_in_order() {
+        let dir = std::env::temp_dir().join(format!("fluxforge-include-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("shared")).unwrap();
+        std::fs::write(
+            dir.join("shared/base.toml"),
+            "[general]\ninsert_workers = 2\nqueue_chunks = 4\n[postgres.types.on_write]\n\"json\" = \"jsonb\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("app.toml"),
+            "include = [\"shared/base.toml\"]\n[general]\ninsert_workers = 8\n\
+             [profiles.staging.general]\nqueue_chunks = 1\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("loop.toml"), "include = [\"loop.toml\"]\n").unwrap();
+
+        let config = load_config_file(&dir.join("app.toml"), None).unwrap();
+        let staging = load_config_file(&dir.join("app.toml"), Some("staging"));
+        let unknown = load_config_file(&dir.join("app.toml"), Some("prod"));
+        let cycle = load_config_file(&dir.join("loop.toml"), None);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(config.get_insert_workers(), 8);
+        assert_eq!(config.general.as_ref().unwrap().queue_chunks, Some(4));
+        assert!(config.get_type_list("postgres", "on_write").is_some());
+        assert!(config.profiles.is_none() && config.include.is_none());
+        let staging = staging.unwrap();
+        assert_eq!(staging.get_insert_workers(), 8);
+        assert_eq!(staging.general.unwrap().queue_chunks, Some(1));
+        assert_eq!(
+            unknown.unwrap_err().to_string(),
+            "unknown profile prod, the config has staging"
+        );
+        assert!(cycle.unwrap_err().to_string().contains("includes itself"));
+    }
+
+    #[test]
+    fn effective_config_redacts_the_secrets() {
+        let mut value: toml::Value = toml::from_str(
+            "[notifications]\non_failure = \"https://hooks.example.com/services/T0/B0/secret\"\n\
+             [ssh]\nhost = \"ops@bastion\"\npassword_file = \"/run/pw\"\n\
+             [s3]\nsecret_access_key = \"abc\"\nurl = \"mysql://app:hunter2@db/shop\"\n",
+        )
+        .unwrap();
+
+        redact_secrets(&mut value, None);
+
+        let text = toml::to_string(&value).unwrap();
+        assert!(!text.contains("hunter2") && !text.contains("abc") && !text.contains("secret\""));
+        assert_eq!(
+            value["notifications"]["on_failure"].as_str(),
+            Some("https://hooks.example.com/***")
+        );
+        assert_eq!(value["s3"]["url"].as_str(), Some("mysql://app:***@db/shop"));
+        assert_eq!(value["ssh"]["password_file"].as_str(), Some("/run/pw"));
+        assert_eq!(value["ssh"]["host"].as_str(), Some("ops@bastion"));
+    }
+}
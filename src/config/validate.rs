@@ -0,0 +1,527 @@
+//! Strict validation of config files (`fluxforge validate-config`).
+//!
+//! Loading a config ignores unknown keys, so a typo like `[mysql.rule.on_write]` silently
+//! drops the section. The validation parses the file into [`ForgeConfig`], serializes the
+//! result again and reports every key of the file that did not survive the round trip,
+//! together with the type names of the mappings that no engine knows. Every problem
+//! carries the line and column of the file.
+
+use crate::core::{ForgeConfig, ForgeDialect};
+use crate::ops::incompat::Severity;
+use std::fmt;
+use std::fmt::Write as _;
+use toml_edit::{ImDocument, Item, TableLike};
+
+/// type names of MySQL, lowercase without length or precision
+const MYSQL_TYPES: &[&str] = &[
+    "tinyint",
+    "smallint",
+    "mediumint",
+    "int",
+    "integer",
+    "bigint",
+    "decimal",
+    "numeric",
+    "float",
+    "double",
+    "double precision",
+    "real",
+    "bit",
+    "bool",
+    "boolean",
+    "date",
+    "datetime",
+    "timestamp",
+    "time",
+    "year",
+    "char",
+    "varchar",
+    "binary",
+    "varbinary",
+    "tinyblob",
+    "blob",
+    "mediumblob",
+    "longblob",
+    "tinytext",
+    "text",
+    "mediumtext",
+    "longtext",
+    "enum",
+    "set",
+    "json",
+    "geometry",
+    "point",
+    "linestring",
+    "polygon",
+    "multipoint",
+    "multilinestring",
+    "multipolygon",
+    "geometrycollection",
+];
+
+/// type names of PostgreSQL and its common extensions
+const POSTGRES_TYPES: &[&str] = &[
+    "smallint",
+    "integer",
+    "bigint",
+    "int",
+    "int2",
+    "int4",
+    "int8",
+    "smallserial",
+    "serial",
+    "bigserial",
+    "decimal",
+    "numeric",
+    "real",
+    "float4",
+    "float8",
+    "double precision",
+    "money",
+    "char",
+    "character",
+    "varchar",
+    "character varying",
+    "text",
+    "bytea",
+    "timestamp",
+    "timestamptz",
+    "timestamp with time zone",
+    "timestamp without time zone",
+    "date",
+    "time",
+    "timetz",
+    "time with time zone",
+    "time without time zone",
+    "interval",
+    "boolean",
+    "bool",
+    "uuid",
+    "json",
+    "jsonb",
+    "xml",
+    "inet",
+    "cidr",
+    "macaddr",
+    "macaddr8",
+    "bit",
+    "varbit",
+    "bit varying",
+    "tsvector",
+    "tsquery",
+    "point",
+    "line",
+    "lseg",
+    "box",
+    "path",
+    "polygon",
+    "circle",
+    "int4range",
+    "int8range",
+    "numrange",
+    "tsrange",
+    "tstzrange",
+    "daterange",
+    "citext",
+    "hstore",
+    "ltree",
+    "geometry",
+    "geography",
+];
+
+/// type names of the internal representation that are no type of either engine
+const INTERNAL_TYPES: &[&str] = &["datetimetz", "biginteger"];
+
+/// A problem of a config file at a 1-based line and column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigProblem {
+    pub line: usize,
+    pub column: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}: {}",
+            self.line, self.column, self.severity, self.message
+        )
+    }
+}
+
+/// 1-based line and column of a byte offset
+fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+    (line, column)
+}
+
+/// problem at the start of the span (line 1 without span)
+fn problem(
+    content: &str,
+    span: Option<std::ops::Range<usize>>,
+    severity: Severity,
+    message: String,
+) -> ConfigProblem {
+    let (line, column) = span.map_or((1, 1), |s| line_column(content, s.start));
+    ConfigProblem {
+        line,
+        column,
+        severity,
+        message,
+    }
+}
+
+/// number of single-character edits between two keys
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// closest known key with at most two edits
+fn suggestion<'a>(key: &str, known: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    known
+        .map(|k| (edit_distance(key, k), k))
+        .filter(|(distance, _)| *distance <= 2)
+        .min()
+        .map(|(_, k)| k)
+}
+
+/// Deserializer recording the field names a struct asks for.
+struct FieldNames<'a>(&'a mut &'static [&'static str]);
+
+impl<'de> serde::Deserializer<'de> for FieldNames<'_> {
+    type Error = serde::de::value::Error;
+
+    fn deserialize_any<V: serde::de::Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error> {
+        Err(serde::de::Error::custom("no struct"))
+    }
+
+    fn deserialize_struct<V: serde::de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = fields;
+        Err(serde::de::Error::custom("fields recorded"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier
+        ignored_any
+    }
+}
+
+/// field names of a config struct
+fn struct_fields<T: serde::de::DeserializeOwned>() -> &'static [&'static str] {
+    let mut fields: &'static [&'static str] = &[];
+    let _ = T::deserialize(FieldNames(&mut fields));
+    fields
+}
+
+/// known keys of the config structs at this position, `None` for maps with arbitrary keys
+fn known_keys(path: &[String]) -> Option<&'static [&'static str]> {
+    use crate::core::{
+        ForgeCredential, ForgeCredentialsConfig, ForgeDbConfig, ForgeDdlConfig,
+        ForgeErrorLogConfig, ForgeExportConfig, ForgeGeneralConfig, ForgeGrantsConfig,
+        ForgeKeepaliveConfig, ForgeLintConfig, ForgeNotificationsConfig, ForgeReplicaLagConfig,
+        ForgeRetryConfig, ForgeRuleGeneralConfig, ForgeRulesDirectionConfig, ForgeSmokeTest,
+        ForgeSshConfig, ForgeTableSettings, ForgeTypeDirectionConfig, ForgeVerifyTolerance,
+    };
+    let path: Vec<&str> = path.iter().map(String::as_str).collect();
+    Some(match path.as_slice() {
+        [] => struct_fields::<ForgeConfig>(),
+        ["general"] => struct_fields::<ForgeGeneralConfig>(),
+        ["general", "verify_tolerance"] | ["tables", "verify_tolerance", _] => {
+            struct_fields::<ForgeVerifyTolerance>()
+        }
+        ["general", "retry"] => struct_fields::<ForgeRetryConfig>(),
+        ["general", "keepalive"] => struct_fields::<ForgeKeepaliveConfig>(),
+        ["general", "replica_lag"] => struct_fields::<ForgeReplicaLagConfig>(),
+        ["general", "error_log"] => struct_fields::<ForgeErrorLogConfig>(),
+        ["general", "ddl"] => struct_fields::<ForgeDdlConfig>(),
+        ["mysql" | "postgres"] => struct_fields::<ForgeDbConfig>(),
+        ["mysql" | "postgres", "types"] => struct_fields::<ForgeTypeDirectionConfig>(),
+        ["mysql" | "postgres", "rules"] | ["rules", "rules"] => {
+            struct_fields::<ForgeRulesDirectionConfig>()
+        }
+        ["mysql" | "postgres", "rules", _] | ["rules", "rules", _] => {
+            struct_fields::<ForgeRuleGeneralConfig>()
+        }
+        ["rules"] => &["rules"],
+        // the other keys of [tables] are table names, `[tables.<name>]`
+        ["tables", _] => struct_fields::<ForgeTableSettings>(),
+        ["tables", "column_overrides", _, _] => &["type", "nullable", "default"],
+        ["grants"] => struct_fields::<ForgeGrantsConfig>(),
+        ["smoke_tests"] => struct_fields::<ForgeSmokeTest>(),
+        ["export"] => struct_fields::<ForgeExportConfig>(),
+        ["ssh"] => struct_fields::<ForgeSshConfig>(),
+        ["lint"] => struct_fields::<ForgeLintConfig>(),
+        ["notifications"] => struct_fields::<ForgeNotificationsConfig>(),
+        ["lint", "rules"] => crate::ops::lint::RULE_NAMES,
+        ["credentials"] => struct_fields::<ForgeCredentialsConfig>(),
+        ["credentials", _] => struct_fields::<ForgeCredential>(),
+        _ => return None,
+    })
+}
+
+/// Compares the keys of the file with the keys of the parsed config.
+fn unknown_keys(
+    content: &str,
+    table: &dyn TableLike,
+    parsed: Option<&toml::Value>,
+    path: &mut Vec<String>,
+    problems: &mut Vec<ConfigProblem>,
+) {
+    let parsed_table = parsed.and_then(toml::Value::as_table);
+    for (key, item) in table.iter() {
+        let parsed_value = parsed_table.and_then(|t| t.get(key));
+        path.push(key.to_string());
+        if parsed_value.is_none() {
+            let section = if path.len() > 1 {
+                format!("[{}]", path[..path.len() - 1].join("."))
+            } else {
+                "the top level".to_string()
+            };
+            let known = known_keys(&path[..path.len() - 1]).unwrap_or_default();
+            let hint = suggestion(key, known.iter().copied())
+                .map(|k| format!(", did you mean `{k}`?"))
+                .unwrap_or_default();
+            let span = table
+                .get_key_value(key)
+                .and_then(|(k, _)| k.span())
+                .or_else(|| item.span());
+            problems.push(problem(
+                content,
+                span,
+                Severity::Error,
+                format!("unknown key `{key}` in {section}, it is ignored{hint}"),
+            ));
+        } else if let Some(nested) = item.as_table_like() {
+            unknown_keys(content, nested, parsed_value, path, problems);
+        } else if let (Some(tables), Some(toml::Value::Array(values))) =
+            (item.as_array_of_tables(), parsed_value)
+        {
+            for (nested, value) in tables.iter().zip(values) {
+                unknown_keys(content, nested, Some(value), path, problems);
+            }
+        }
+        path.pop();
+    }
+}
+
+/// type name without length and array suffix, "varchar(20)" and "text[]" are checked as their base
+fn type_base(data_type: &str) -> String {
+    data_type
+        .split('(')
+        .next()
+        .unwrap_or(data_type)
+        .trim_end_matches("[]")
+        .trim()
+        .to_lowercase()
+}
+
+/// true if an engine (or the internal representation) knows the type
+fn is_known_type(data_type: &str) -> bool {
+    let base = type_base(data_type);
+    MYSQL_TYPES.contains(&base.as_str())
+        || POSTGRES_TYPES.contains(&base.as_str())
+        || INTERNAL_TYPES.contains(&base.as_str())
+}
+
+/// true if the engine of the dialect knows the type
+pub(crate) fn is_dialect_type(dialect: ForgeDialect, data_type: &str) -> bool {
+    let base = type_base(data_type);
+    match dialect {
+        ForgeDialect::Mysql => MYSQL_TYPES.contains(&base.as_str()),
+        ForgeDialect::Postgres => POSTGRES_TYPES.contains(&base.as_str()),
+    }
+}
+
+/// Reports the type names of `[<db>.types.<direction>]` no engine knows.
+fn unknown_types(content: &str, doc: &ImDocument<&str>, problems: &mut Vec<ConfigProblem>) {
+    for db in ["mysql", "postgres"] {
+        for direction in ["on_read", "on_write"] {
+            let Some(mappings) = doc
+                .get(db)
+                .and_then(|d| d.get("types"))
+                .and_then(|t| t.get(direction))
+                .and_then(Item::as_table_like)
+            else {
+                continue;
+            };
+            for (from, item) in mappings.iter() {
+                if !is_known_type(from) {
+                    let span = mappings.get_key_value(from).and_then(|(k, _)| k.span());
+                    problems.push(problem(
+                        content,
+                        span,
+                        Severity::Warning,
+                        format!("unknown type `{from}` in [{db}.types.{direction}]"),
+                    ));
+                }
+                let Some(to) = item.as_str() else {
+                    problems.push(problem(
+                        content,
+                        item.span(),
+                        Severity::Error,
+                        format!(
+                            "type mapping of `{from}` in [{db}.types.{direction}] is no string"
+                        ),
+                    ));
+                    continue;
+                };
+                if !is_known_type(to) {
+                    problems.push(problem(
+                        content,
+                        item.span(),
+                        Severity::Warning,
+                        format!("unknown type `{to}` in [{db}.types.{direction}]"),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// Validates the content of a config file, problems in file order.
+///
+/// Syntax errors and values of the wrong type end the validation with one problem,
+/// otherwise unknown keys are errors and unknown type names warnings.
+#[must_use]
+pub fn validate_config(content: &str) -> Vec<ConfigProblem> {
+    let doc = match ImDocument::parse(content) {
+        Ok(doc) => doc,
+        Err(e) => {
+            return vec![problem(
+                content,
+                e.span(),
+                Severity::Error,
+                e.message().trim().to_string(),
+            )];
+        }
+    };
+    let config: ForgeConfig = match toml::from_str(content) {
+        Ok(config) => config,
+        Err(e) => {
+            return vec![problem(
+                content,
+                e.span(),
+                Severity::Error,
+                e.message().trim().to_string(),
+            )];
+        }
+    };
+    let parsed = match toml::Value::try_from(&config) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            return vec![problem(
+                content,
+                None,
+                Severity::Error,
+                format!("config cannot be checked: {e}"),
+            )];
+        }
+    };
+
+    let mut problems = Vec::new();
+    unknown_keys(
+        content,
+        doc.as_table(),
+        Some(&parsed),
+        &mut Vec::new(),
+        &mut problems,
+    );
+    unknown_types(content, &doc, &mut problems);
+    problems.sort_by_key(|p| (p.line, p.column));
+    problems
+}
+
+/// report of the problems of a config file, one line per problem
+#[must_use]
+pub fn problems_report(file: &str, problems: &[ConfigProblem]) -> String {
+    let mut out = String::new();
+    for problem in problems {
+        let _ = writeln!(out, "{file}:{problem}");
+    }
+    let errors = problems
+        .iter()
+        .filter(|p| p.severity == Severity::Error)
+        .count();
+    let _ = writeln!(
+        out,
+        "{file}: {errors} errors, {} warnings",
+        problems.len() - errors
+    );
+    out
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn misspelled_sections_are_reported_with_their_position() {
+        let content = "[general]\ninsert_workers = 2\nverfy_level = \"counts\"\n\n[mysql.rule.on_write]\nzero_date = true\n";
+        let problems = validate_config(content);
+        assert_eq!(
+            problems.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "3:1: error: unknown key `verfy_level` in [general], it is ignored, did you mean `verify_level`?",
+                "5:8: error: unknown key `rule` in [mysql], it is ignored, did you mean `rules`?",
+            ]
+        );
+    }
+
+    #[test]
+    fn syntax_and_type_errors_carry_the_position() {
+        let problems = validate_config("[general]\ninsert_workers = \"many\"\n");
+        assert_eq!(problems.len(), 1);
+        assert_eq!((problems[0].line, problems[0].column), (2, 18));
+
+        let problems = validate_config("[general\n");
+        assert_eq!(problems[0].line, 1);
+        assert_eq!(problems[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn nested_maps_and_unknown_types() {
+        let content = "[tables.column_overrides.users]\nstatus = { typ = \"varchar(20)\" }\n\n[postgres.types.on_write]\n\"json\" = \"jsonbb\"\n\"varchar\" = \"character varying(255)\"\n";
+        let problems = validate_config(content);
+        assert_eq!(
+            problems.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "2:12: error: unknown key `typ` in [tables.column_overrides.users.status], it is ignored, did you mean `type`?",
+                "5:10: warning: unknown type `jsonbb` in [postgres.types.on_write]",
+            ]
+        );
+    }
+
+    #[test]
+    fn shipped_configs_are_valid() {
+        for content in [
+            include_str!("../../examples/mysql2postgres.toml"),
+            include_str!("../../examples/mysql2mysql.toml"),
+            include_str!("../../examples/postgres2mysql.toml"),
+            include_str!("../../examples/postgres2postgres.toml"),
+            include_str!("../../examples/template.toml"),
+        ] {
+            assert_eq!(validate_config(content), Vec::new());
+        }
+    }
+}
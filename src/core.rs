@@ -3,6 +3,7 @@
 //! This module defines the fundamental types used throughout FluxForge:
 //! - Configuration structures for type mappings and transformation rules
 //! - Schema representation (tables, columns, indices, foreign keys)
+//! - Schema comparison (diff between two schemas) and object filters
 //! - Universal value types for cross-database data representation
 //! - Error types for database operations
 
@@ -10,8 +11,9 @@ use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
 use indexmap::IndexMap;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use thiserror::Error;
+use tokio_util::sync::CancellationToken;
 
 // config structures for mapping.toml
 
@@ -41,6 +43,24 @@ pub struct ForgeConfig {
     pub rules: Option<ForgeRulesConfig>,
     /// Table-specific overrides and renames
     pub tables: Option<ForgeSchemaTableConfig>,
+    /// Mapping of grantees for the grants pass
+    pub grants: Option<ForgeGrantsConfig>,
+    /// Queries run against the target after the data load
+    pub smoke_tests: Option<Vec<ForgeSmokeTest>>,
+    /// File format settings of `fluxforge export`
+    pub export: Option<ForgeExportConfig>,
+    /// SSH tunnel of the database connections
+    pub ssh: Option<ForgeSshConfig>,
+    /// Users and passwords of the connections, kept out of the URLs
+    pub credentials: Option<ForgeCredentialsConfig>,
+    /// Rule severities of `fluxforge lint`
+    pub lint: Option<ForgeLintConfig>,
+    /// Webhooks called when a replication or migration finishes
+    pub notifications: Option<ForgeNotificationsConfig>,
+    /// Config files merged below this one, relative to it (resolved by `load_config`)
+    pub include: Option<Vec<std::path::PathBuf>>,
+    /// Named overrides selected with `--profile` (resolved by `load_config`)
+    pub profiles: Option<HashMap<String, toml::Table>>,
 }
 
 impl ForgeConfig {
@@ -84,6 +104,693 @@ impl ForgeConfig {
             _ => None,
         }
     }
+
+    /// Maps an internal data type to the type written by the target dialect (`on_write` types).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fluxforge::core::{ForgeConfig, ForgeDialect};
+    ///
+    /// let config: ForgeConfig = toml::from_str(r#"
+    ///     [postgres.types.on_write]
+    ///     "json" = "jsonb"
+    /// "#).unwrap();
+    /// assert_eq!(config.map_write_type(ForgeDialect::Postgres, "JSON"), "jsonb");
+    /// assert_eq!(config.map_write_type(ForgeDialect::Mysql, "JSON"), "json");
+    /// // types unknown to the dialect fall back to their native counterpart
+    /// assert_eq!(config.map_write_type(ForgeDialect::Mysql, "bytea"), "longblob");
+    /// ```
+    #[must_use]
+    pub fn map_write_type(&self, dialect: ForgeDialect, data_type: &str) -> String {
+        let lower = data_type.to_lowercase();
+        self.get_type_list(dialect.config_section(), "on_write")
+            .and_then(|t| t.get(&lower))
+            .cloned()
+            .unwrap_or_else(|| {
+                dialect
+                    .native_type(&lower)
+                    .map_or(lower, ToString::to_string)
+            })
+    }
+
+    /// Gets the target name of a renamed source table (`[tables.renames]`).
+    #[must_use]
+    pub fn get_table_rename(&self, table_name: &str) -> Option<&str> {
+        self.tables
+            .as_ref()?
+            .renames
+            .as_ref()?
+            .get(table_name)
+            .map(String::as_str)
+    }
+
+    /// Gets the name of a source table on the target, renamed or unchanged.
+    #[must_use]
+    pub fn get_target_table_name<'a>(&'a self, table_name: &'a str) -> &'a str {
+        self.get_table_rename(table_name).unwrap_or(table_name)
+    }
+
+    /// Gets the target name of a renamed source column (`[tables.column_renames.<table>]`).
+    #[must_use]
+    pub fn get_column_rename(&self, table_name: &str, column_name: &str) -> Option<&str> {
+        self.tables
+            .as_ref()?
+            .column_renames
+            .as_ref()?
+            .get(table_name)?
+            .get(column_name)
+            .map(String::as_str)
+    }
+
+    /// Gets the name of a source column on the target, renamed or unchanged.
+    #[must_use]
+    pub fn get_target_column_name<'a>(&'a self, table_name: &str, column_name: &'a str) -> &'a str {
+        self.get_column_rename(table_name, column_name)
+            .unwrap_or(column_name)
+    }
+
+    /// Gets the override of a source column (`[tables.column_overrides.<table>]`).
+    #[must_use]
+    pub fn get_column_override(
+        &self,
+        table_name: &str,
+        column_name: &str,
+    ) -> Option<&ForgeColumnOverride> {
+        self.tables
+            .as_ref()?
+            .column_overrides
+            .as_ref()?
+            .get(table_name)?
+            .get(column_name)
+    }
+
+    /// Gets the configured NULL/empty-string policy for a column.
+    ///
+    /// # Arguments
+    ///
+    /// * `table_name` - Name of the source table
+    /// * `column_name` - Name of the source column
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fluxforge::core::{ForgeConfig, ForgeNullPolicy};
+    ///
+    /// let config: ForgeConfig = toml::from_str(r#"
+    ///     [tables.null_policies.users]
+    ///     nickname = "empty_to_null"
+    /// "#).unwrap();
+    /// assert_eq!(config.get_null_policy("users", "nickname"), Some(ForgeNullPolicy::EmptyToNull));
+    /// assert_eq!(config.get_null_policy("users", "email"), None);
+    /// ```
+    #[must_use]
+    pub fn get_null_policy(&self, table_name: &str, column_name: &str) -> Option<ForgeNullPolicy> {
+        self.tables
+            .as_ref()?
+            .null_policies
+            .as_ref()?
+            .get(table_name)?
+            .get(column_name)
+            .copied()
+    }
+
+    /// Gets the target grantee for a source grantee.
+    ///
+    /// Returns `None` if no mapping is configured, an empty string means the grantee is skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fluxforge::core::ForgeConfig;
+    ///
+    /// let config: ForgeConfig = toml::from_str(r#"
+    ///     [grants.user_mapping]
+    ///     "'app'@'%'" = "app_rw"
+    /// "#).unwrap();
+    /// assert_eq!(config.get_grantee_mapping("'app'@'%'"), Some("app_rw"));
+    /// assert_eq!(config.get_grantee_mapping("'report'@'%'"), None);
+    /// ```
+    #[must_use]
+    pub fn get_grantee_mapping(&self, grantee: &str) -> Option<&str> {
+        self.grants
+            .as_ref()?
+            .user_mapping
+            .as_ref()?
+            .get(grantee)
+            .map(String::as_str)
+    }
+
+    /// Gets the NULL ordering used for ordered table streams (defaults to NULLs first).
+    #[must_use]
+    pub fn get_null_ordering(&self) -> ForgeNullOrdering {
+        self.general
+            .as_ref()
+            .and_then(|g| g.null_ordering)
+            .unwrap_or_default()
+    }
+
+    /// Whether binary columns are verified by digest instead of their value (defaults to false).
+    #[must_use]
+    pub fn get_verify_binary_digest(&self) -> bool {
+        self.general
+            .as_ref()
+            .and_then(|g| g.verify_binary_digest)
+            .unwrap_or(false)
+    }
+
+    /// Gets the path of the verify report, `None` to fail on the first mismatch.
+    #[must_use]
+    pub fn get_verify_report(&self) -> Option<std::path::PathBuf> {
+        self.general.as_ref().and_then(|g| g.verify_report.clone())
+    }
+
+    /// Gets the maximum number of rows recorded in the verify report (defaults to 1000).
+    #[must_use]
+    pub fn get_verify_report_limit(&self) -> usize {
+        self.general
+            .as_ref()
+            .and_then(|g| g.verify_report_limit)
+            .unwrap_or(1000)
+    }
+
+    /// Gets the primary key values per range of the stored checksums (defaults to 100000).
+    #[must_use]
+    pub fn get_checksum_range_keys(&self) -> u64 {
+        self.general
+            .as_ref()
+            .and_then(|g| g.checksum_range_keys)
+            .unwrap_or(100_000)
+            .max(1)
+    }
+
+    /// Gets the path of the audit log of applied schema changes.
+    #[must_use]
+    pub fn get_audit_log(&self) -> std::path::PathBuf {
+        self.general
+            .as_ref()
+            .and_then(|g| g.audit_log.clone())
+            .unwrap_or_else(|| std::path::PathBuf::from("fluxforge_audit.jsonl"))
+    }
+
+    /// Gets the comparison tolerances of the verification for a table,
+    /// the table options override the global ones, exact comparison if nothing is set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fluxforge::core::ForgeConfig;
+    ///
+    /// let config: ForgeConfig = toml::from_str(r#"
+    ///     [general.verify_tolerance]
+    ///     ignore_case = true
+    ///     float_epsilon = 0.01
+    ///
+    ///     [tables.verify_tolerance.users]
+    ///     ignore_case = false
+    /// "#).unwrap();
+    /// assert!(config.get_verify_tolerance("orders").ignores_case());
+    /// assert!(!config.get_verify_tolerance("users").ignores_case());
+    /// assert_eq!(config.get_verify_tolerance("users").float_epsilon, Some(0.01));
+    /// ```
+    #[must_use]
+    pub fn get_verify_tolerance(&self, table_name: &str) -> ForgeVerifyTolerance {
+        let global = self
+            .general
+            .as_ref()
+            .and_then(|g| g.verify_tolerance)
+            .unwrap_or_default();
+        match self
+            .tables
+            .as_ref()
+            .and_then(|t| t.verify_tolerance.as_ref())
+            .and_then(|t| t.get(table_name))
+        {
+            Some(table) => global.merged(table),
+            None => global,
+        }
+    }
+
+    /// Gets the level of the verification after write (defaults to full).
+    #[must_use]
+    pub fn get_verify_level(&self) -> ForgeVerifyLevel {
+        self.general
+            .as_ref()
+            .and_then(|g| g.verify_level)
+            .unwrap_or_default()
+    }
+
+    /// Gets the share of rows checked by verification, `None` to verify every row.
+    #[must_use]
+    pub fn get_verify_sample(&self) -> Option<ForgeVerifySample> {
+        self.general
+            .as_ref()
+            .and_then(|g| g.verify_sample)
+            .map(|sample| sample.with_seed(self.get_seed()))
+    }
+
+    /// Gets the seed of the run (defaults to 0).
+    #[must_use]
+    pub fn get_seed(&self) -> u64 {
+        self.get_run_seed().unwrap_or(0)
+    }
+
+    /// Gets the seed set with `[general] seed` or `--seed`, `None` for an unseeded run.
+    #[must_use]
+    pub fn get_run_seed(&self) -> Option<u64> {
+        self.general.as_ref().and_then(|g| g.seed)
+    }
+
+    /// Gets the number of concurrent insert workers per table (defaults to 4, at least 1).
+    #[must_use]
+    pub fn get_insert_workers(&self) -> usize {
+        self.general
+            .as_ref()
+            .and_then(|g| g.insert_workers)
+            .unwrap_or(4)
+            .max(1)
+    }
+
+    /// Gets the number of concurrent insert workers of a table: 1 with `ordered_inserts` or a
+    /// table/all write transaction (one connection), otherwise `[tables.insert_workers]` or the global number.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fluxforge::core::ForgeConfig;
+    ///
+    /// let config: ForgeConfig =
+    ///     toml::from_str("[general]\ninsert_workers = 2\n[tables.insert_workers]\nevents = 8").unwrap();
+    /// assert_eq!(config.get_table_insert_workers("events"), 8);
+    /// assert_eq!(config.get_table_insert_workers("users"), 2);
+    /// assert_eq!(config.get_max_insert_workers(), 8);
+    ///
+    /// let shared: ForgeConfig =
+    ///     toml::from_str("[general]\ninsert_workers = 8\nwrite_transaction = \"table\"").unwrap();
+    /// assert_eq!(shared.get_table_insert_workers("events"), 1);
+    /// ```
+    #[must_use]
+    pub fn get_table_insert_workers(&self, table_name: &str) -> usize {
+        if self.is_ordered_inserts() || self.get_write_transaction() != ForgeWriteTransaction::Chunk
+        {
+            return 1;
+        }
+        self.tables
+            .as_ref()
+            .and_then(|t| t.insert_workers.as_ref())
+            .and_then(|t| t.get(table_name).copied())
+            .map_or_else(|| self.get_insert_workers(), |workers| workers.max(1))
+    }
+
+    /// Gets the settings of `[tables.<name>]` for a source table.
+    #[must_use]
+    pub fn get_table_settings(&self, table_name: &str) -> Option<&ForgeTableSettings> {
+        self.tables.as_ref()?.settings.get(table_name)
+    }
+
+    /// Gets the rows per insert chunk of a table (defaults to 1000).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fluxforge::core::ForgeConfig;
+    ///
+    /// let config: ForgeConfig = toml::from_str("[tables.events]\nchunk_size = 5000").unwrap();
+    /// assert_eq!(config.get_table_chunk_size("events"), 5000);
+    /// assert_eq!(config.get_table_chunk_size("users"), 1000);
+    /// ```
+    #[must_use]
+    pub fn get_table_chunk_size(&self, table_name: &str) -> usize {
+        self.get_table_settings(table_name)
+            .and_then(|t| t.chunk_size)
+            .unwrap_or(1000)
+            .max(1)
+    }
+
+    /// Gets the row filters of the source tables, source table name -> SQL condition.
+    #[must_use]
+    pub fn get_row_filters(&self) -> HashMap<String, String> {
+        self.tables
+            .as_ref()
+            .map(|t| {
+                t.settings
+                    .iter()
+                    .filter_map(|(table, s)| Some((table.clone(), s.filter.clone()?)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Gets the transformation of a source column, if any.
+    #[must_use]
+    pub fn get_column_transform(
+        &self,
+        table_name: &str,
+        column_name: &str,
+    ) -> Option<ForgeColumnTransform> {
+        self.get_table_settings(table_name)?
+            .transforms
+            .as_ref()?
+            .get(column_name)
+            .copied()
+    }
+
+    /// Gets the verification level of a table, `None` if its verification is skipped.
+    #[must_use]
+    pub fn get_table_verify_level(&self, table_name: &str) -> Option<ForgeVerifyLevel> {
+        let settings = self.get_table_settings(table_name);
+        if settings.and_then(|t| t.verify) == Some(false) {
+            return None;
+        }
+        Some(
+            settings
+                .and_then(|t| t.verify_level)
+                .unwrap_or_else(|| self.get_verify_level()),
+        )
+    }
+
+    /// Gets the replication priority of a table (defaults to 0).
+    #[must_use]
+    pub fn get_table_priority(&self, table_name: &str) -> i64 {
+        self.get_table_settings(table_name)
+            .and_then(|t| t.priority)
+            .unwrap_or(0)
+    }
+
+    /// Gets the sync mode of a table in the cycles of `replicate --watch` (defaults to append).
+    #[must_use]
+    pub fn get_table_sync_mode(&self, table_name: &str) -> ForgeSyncMode {
+        self.get_table_settings(table_name)
+            .and_then(|t| t.sync)
+            .unwrap_or_default()
+    }
+
+    /// Gets the SQL condition of the target rows deleted by a reload, `None` deletes all rows.
+    #[must_use]
+    pub fn get_table_reload_filter(&self, table_name: &str) -> Option<&str> {
+        self.get_table_settings(table_name)?
+            .reload_filter
+            .as_deref()
+    }
+
+    /// Checks whether a merge deletes the target rows missing in the source (defaults to false).
+    #[must_use]
+    pub fn is_table_sync_deletes(&self, table_name: &str) -> bool {
+        self.get_table_settings(table_name)
+            .and_then(|t| t.sync_deletes)
+            .unwrap_or(false)
+    }
+
+    /// Gets the position of a table in `[tables] replication_order`, `None` for unlisted tables.
+    #[must_use]
+    pub fn get_table_order_position(&self, table_name: &str) -> Option<usize> {
+        self.tables
+            .as_ref()?
+            .replication_order
+            .as_ref()?
+            .iter()
+            .position(|name| name == table_name)
+    }
+
+    /// Gets the number of chunks buffered between the reader and the insert workers of a table
+    /// (defaults to twice the workers, at least 1). A full queue pauses the reader.
+    #[must_use]
+    pub fn get_queue_chunks(&self, workers: usize) -> usize {
+        self.general
+            .as_ref()
+            .and_then(|g| g.queue_chunks)
+            .unwrap_or(workers * 2)
+            .max(1)
+    }
+
+    /// Gets the byte budget of a chunk (defaults to 64 MiB, at least 1). A chunk is queued when
+    /// it reaches 1000 rows or the approximate size of its values reaches the budget.
+    #[must_use]
+    pub fn get_max_chunk_bytes(&self) -> u64 {
+        self.general
+            .as_ref()
+            .and_then(|g| g.max_chunk_bytes)
+            .unwrap_or(64 * 1024 * 1024)
+            .max(1)
+    }
+
+    /// Gets the transaction boundaries of the data writes (defaults to chunk).
+    #[must_use]
+    pub fn get_write_transaction(&self) -> ForgeWriteTransaction {
+        self.general
+            .as_ref()
+            .and_then(|g| g.write_transaction)
+            .unwrap_or_default()
+    }
+
+    /// Gets the largest number of insert workers of any table, sizes the connection pools.
+    #[must_use]
+    pub fn get_max_insert_workers(&self) -> usize {
+        if self.is_ordered_inserts() || self.get_write_transaction() != ForgeWriteTransaction::Chunk
+        {
+            return 1;
+        }
+        self.tables
+            .as_ref()
+            .and_then(|t| t.insert_workers.as_ref())
+            .and_then(|t| t.values().copied().max())
+            .map_or(0, |workers| workers.max(1))
+            .max(self.get_insert_workers())
+    }
+
+    /// Returns true if every table is written by a single connection in source order.
+    #[must_use]
+    pub fn is_ordered_inserts(&self) -> bool {
+        self.general
+            .as_ref()
+            .and_then(|g| g.ordered_inserts)
+            .unwrap_or(false)
+    }
+
+    /// Returns true if interrupted table reads continue after the last key read (`[general.retry]`).
+    #[must_use]
+    pub fn is_resume_streams(&self) -> bool {
+        self.general
+            .as_ref()
+            .and_then(|g| g.retry.as_ref())
+            .and_then(|r| r.resume_streams)
+            .unwrap_or(true)
+    }
+
+    /// Returns true if foreign key checks and triggers of the target are disabled during the load.
+    #[must_use]
+    pub fn is_disable_constraints(&self) -> bool {
+        self.general
+            .as_ref()
+            .and_then(|g| g.disable_constraints)
+            .unwrap_or(false)
+    }
+
+    /// Returns true if lossy type conversions are errors instead of warnings.
+    #[must_use]
+    pub fn is_strict_types(&self) -> bool {
+        self.general
+            .as_ref()
+            .and_then(|g| g.strict_types)
+            .unwrap_or(false)
+    }
+
+    /// Returns true if the generated DDL skips objects that exist already (and missing dropped ones).
+    #[must_use]
+    pub fn is_idempotent_ddl(&self) -> bool {
+        self.general
+            .as_ref()
+            .and_then(|g| g.idempotent_ddl)
+            .unwrap_or(false)
+    }
+
+    /// Returns true if the schema changes of a PostgreSQL target are applied in one transaction.
+    #[must_use]
+    pub fn is_transactional_ddl(&self) -> bool {
+        self.general
+            .as_ref()
+            .and_then(|g| g.transactional_ddl)
+            .unwrap_or(true)
+    }
+
+    /// Gets the timeouts of the schema statements (`[general.ddl]`).
+    #[must_use]
+    pub fn get_ddl(&self) -> ForgeDdlConfig {
+        self.general
+            .as_ref()
+            .and_then(|g| g.ddl.clone())
+            .unwrap_or_default()
+    }
+
+    /// Returns true if unsigned bigint values are scanned for overflows before the data load.
+    #[must_use]
+    pub fn is_scan_unsigned(&self) -> bool {
+        self.general
+            .as_ref()
+            .and_then(|g| g.scan_unsigned)
+            .unwrap_or(false)
+    }
+
+    /// Returns true if the extension may be created on a PostgreSQL target (`allowed_extensions`).
+    #[must_use]
+    pub fn is_allowed_extension(&self, extension: &str) -> bool {
+        match self
+            .general
+            .as_ref()
+            .and_then(|g| g.allowed_extensions.as_ref())
+        {
+            Some(allowed) => allowed.iter().any(|e| e == extension),
+            None => DEFAULT_EXTENSIONS.contains(&extension),
+        }
+    }
+
+    /// Returns true if partitioned tables are created without their partitions.
+    #[must_use]
+    pub fn is_flatten_partitions(&self) -> bool {
+        self.general
+            .as_ref()
+            .and_then(|g| g.flatten_partitions)
+            .unwrap_or(false)
+    }
+
+    /// Gets the field delimiter of exported CSV files (defaults to `,`).
+    #[must_use]
+    pub fn get_csv_delimiter(&self) -> char {
+        self.export
+            .as_ref()
+            .and_then(|e| e.delimiter)
+            .unwrap_or(',')
+    }
+
+    /// Gets when the fields of exported CSV files are quoted (defaults to when necessary).
+    #[must_use]
+    pub fn get_csv_quote(&self) -> ForgeCsvQuote {
+        self.export
+            .as_ref()
+            .and_then(|e| e.quote)
+            .unwrap_or_default()
+    }
+
+    /// Gets the text written for NULL values in exported files (defaults to an empty field).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fluxforge::core::ForgeConfig;
+    ///
+    /// let config: ForgeConfig = toml::from_str("[export]\nnull_value = '\\N'").unwrap();
+    /// assert_eq!(config.get_csv_null_value(), "\\N");
+    /// assert_eq!(ForgeConfig::default().get_csv_null_value(), "");
+    /// ```
+    #[must_use]
+    pub fn get_csv_null_value(&self) -> &str {
+        self.export
+            .as_ref()
+            .and_then(|e| e.null_value.as_deref())
+            .unwrap_or("")
+    }
+
+    /// Returns true if exported CSV files start with a line of column names (default).
+    #[must_use]
+    pub fn is_csv_header(&self) -> bool {
+        self.export.as_ref().and_then(|e| e.header).unwrap_or(true)
+    }
+
+    /// Gets the rows per row group of exported Parquet files (defaults to 100000).
+    #[must_use]
+    pub fn get_parquet_row_group_size(&self) -> usize {
+        self.export
+            .as_ref()
+            .and_then(|e| e.row_group_size)
+            .filter(|size| *size > 0)
+            .unwrap_or(100_000)
+    }
+
+    /// Gets the compression of exported Parquet files (defaults to snappy).
+    #[must_use]
+    pub fn get_parquet_compression(&self) -> ForgeParquetCompression {
+        self.export
+            .as_ref()
+            .and_then(|e| e.compression)
+            .unwrap_or_default()
+    }
+
+    /// Gets the severity of a lint rule set in `[lint.rules]`, `None` keeps the default of the rule.
+    #[must_use]
+    pub fn get_lint_severity(&self, rule: &str) -> Option<ForgeLintSeverity> {
+        self.lint.as_ref()?.rules.as_ref()?.get(rule).copied()
+    }
+
+    /// Gets the webhooks of `[notifications]`, none by default.
+    #[must_use]
+    pub fn get_notifications(&self) -> ForgeNotificationsConfig {
+        self.notifications.clone().unwrap_or_default()
+    }
+
+    /// Gets the row size above which `lint` reports a table (defaults to 65535, the MySQL limit).
+    #[must_use]
+    pub fn get_lint_max_row_bytes(&self) -> u64 {
+        self.lint
+            .as_ref()
+            .and_then(|l| l.max_row_bytes)
+            .unwrap_or(65_535)
+    }
+
+    /// Gets the SSH tunnel of the source (`is_source`) or target connections, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fluxforge::core::ForgeConfig;
+    ///
+    /// let config: ForgeConfig =
+    ///     toml::from_str("[ssh]\nhost = 'deploy@bastion'\nconnections = 'source'").unwrap();
+    /// assert!(config.get_ssh(true).is_some());
+    /// assert!(config.get_ssh(false).is_none());
+    /// assert!(ForgeConfig::default().get_ssh(true).is_none());
+    /// ```
+    #[must_use]
+    pub fn get_ssh(&self, is_source: bool) -> Option<&ForgeSshConfig> {
+        self.ssh.as_ref().filter(|ssh| ssh.applies_to(is_source))
+    }
+
+    /// Gets the credentials of the source (`is_source`) or target connection, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fluxforge::core::ForgeConfig;
+    ///
+    /// let config: ForgeConfig =
+    ///     toml::from_str("[credentials.target]\npassword_env = 'SHOP_PASSWORD'").unwrap();
+    /// assert!(config.get_credentials(true).is_none());
+    /// assert_eq!(
+    ///     config.get_credentials(false).unwrap().password_env.as_deref(),
+    ///     Some("SHOP_PASSWORD")
+    /// );
+    /// ```
+    #[must_use]
+    pub fn get_credentials(&self, is_source: bool) -> Option<&ForgeCredential> {
+        self.credentials.as_ref().and_then(|c| {
+            if is_source {
+                c.source.as_ref()
+            } else {
+                c.target.as_ref()
+            }
+        })
+    }
+
+    /// Gets the session time zone of the source (`is_source`) or target connections.
+    #[must_use]
+    pub fn get_time_zone(&self, is_source: bool) -> Option<ForgeTimeZone> {
+        self.general.as_ref().and_then(|g| {
+            if is_source {
+                g.source_time_zone
+            } else {
+                g.target_time_zone
+            }
+        })
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
@@ -111,29 +818,1151 @@ pub struct ForgeRulesDirectionConfig {
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct ForgeRuleGeneralConfig {
     pub unsigned_int_to_bigint: Option<bool>,
+    /// Read `bigint unsigned` as `numeric(20,0)`, values above the signed bigint range stay intact
+    pub unsigned_bigint_to_numeric: Option<bool>,
     pub zero_date: Option<bool>,
     pub sql_mode: Option<String>,
+    /// Emulate `ON UPDATE CURRENT_TIMESTAMP` columns with a `set_updated_at()` trigger (PostgreSQL)
+    pub on_update_trigger: Option<bool>,
 }
 
+/// Extensions created on a PostgreSQL target without `allowed_extensions`, all are part of the
+/// PostgreSQL contrib modules or PostGIS.
+pub const DEFAULT_EXTENSIONS: [&str; 9] = [
+    "btree_gin",
+    "btree_gist",
+    "citext",
+    "hstore",
+    "ltree",
+    "pg_trgm",
+    "pgcrypto",
+    "postgis",
+    "uuid-ossp",
+];
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct ForgeGeneralConfig {
     pub on_missing_type: Option<String>,
     pub default_charset: Option<String>,
     pub verify_after_write: Option<bool>,
+    /// Level of the verification after write, "full" (default) or "counts"
+    pub verify_level: Option<ForgeVerifyLevel>,
+    /// Position of NULL values in ordered streams, identical on both engines
+    pub null_ordering: Option<ForgeNullOrdering>,
+    /// Number of concurrent insert workers per table during replication
+    pub insert_workers: Option<usize>,
+    /// Chunks buffered between the reader and the insert workers, limits the memory of a table
+    pub queue_chunks: Option<usize>,
+    /// Approximate byte size of the values of a chunk before it is queued, bounds the memory of wide rows
+    pub max_chunk_bytes: Option<u64>,
+    /// Transaction boundaries of the data writes: "chunk" (default), "table" or "all"
+    pub write_transaction: Option<ForgeWriteTransaction>,
+    /// Write every table with a single connection in source order (i.e. for auto-increment keys
+    /// generated by the target), overrides `insert_workers`
+    pub ordered_inserts: Option<bool>,
+    /// Disable foreign key checks (MySQL) or all triggers (PostgreSQL) of the target during the
+    /// data load, the foreign keys are validated after they are enabled again
+    pub disable_constraints: Option<bool>,
+    /// Fail the schema conversion if a type mapping narrows the data instead of warning
+    pub strict_types: Option<bool>,
+    /// Generate DDL that can run again: `IF NOT EXISTS` / `IF EXISTS` where the dialect has it
+    pub idempotent_ddl: Option<bool>,
+    /// Apply the schema changes of a PostgreSQL target in one transaction (default true),
+    /// statements that cannot run in a transaction are executed after its commit
+    pub transactional_ddl: Option<bool>,
+    /// Count the `bigint unsigned` values above the signed bigint range before the data load
+    pub scan_unsigned: Option<bool>,
+    /// Create partitioned source tables as plain tables on the target
+    pub flatten_partitions: Option<bool>,
+    /// Extensions created on a PostgreSQL target if the source tables need them,
+    /// defaults to `DEFAULT_EXTENSIONS`
+    pub allowed_extensions: Option<Vec<String>>,
+    /// Session time zone of the source connections, i.e. "UTC" or "+02:00"
+    pub source_time_zone: Option<ForgeTimeZone>,
+    /// Session time zone of the target connections
+    pub target_time_zone: Option<ForgeTimeZone>,
+    /// Verify only a sample of the rows, i.e. "1%"
+    pub verify_sample: Option<ForgeVerifySample>,
+    /// Verify binary and large text columns by a SHA-256 digest computed on the servers
+    pub verify_binary_digest: Option<bool>,
+    /// Record all mismatching rows in this JSON file instead of failing on the first one
+    pub verify_report: Option<std::path::PathBuf>,
+    /// Maximum number of rows recorded in the verify report
+    pub verify_report_limit: Option<usize>,
+    /// Primary key values per range of the stored checksums (`--checksum-file`)
+    pub checksum_range_keys: Option<u64>,
+    /// Audit log of applied schema changes (defaults to `fluxforge_audit.jsonl`)
+    pub audit_log: Option<std::path::PathBuf>,
+    /// Comparison tolerances of the verification (`[general.verify_tolerance]`)
+    pub verify_tolerance: Option<ForgeVerifyTolerance>,
+    /// Seed of all randomized decisions of a run, i.e. the rows selected by sampled verification
+    pub seed: Option<u64>,
+    /// Retries of transient database errors (`[general.retry]`)
+    pub retry: Option<ForgeRetryConfig>,
+    /// Keepalive of long-running connections (`[general.keepalive]`)
+    pub keepalive: Option<ForgeKeepaliveConfig>,
+    /// Lag limit of a source replica (`[general.replica_lag]`)
+    pub replica_lag: Option<ForgeReplicaLagConfig>,
+    /// Log of rows the target rejected (`[general.error_log]`)
+    pub error_log: Option<ForgeErrorLogConfig>,
+    /// Timeouts of the schema statements (`[general.ddl]`)
+    pub ddl: Option<ForgeDdlConfig>,
 }
 
+/// Timeouts of the connection executing the schema statements, so an ALTER waiting for a lock
+/// on a busy target gives up instead of queueing all other queries of the table behind it.
+///
+/// # Examples
+///
+/// ```toml
+/// [general.ddl]
+/// lock_timeout_ms = 5000
+/// statement_timeout_ms = 600000
+/// lock_wait_timeout_secs = 5
+/// lock_retries = 5
+/// ```
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
-pub struct ForgeRulesConfig {
-    pub rules: Option<ForgeRulesDirectionConfig>,
+pub struct ForgeDdlConfig {
+    /// PostgreSQL `lock_timeout` of the schema statements in milliseconds (server default if unset)
+    pub lock_timeout_ms: Option<u64>,
+    /// PostgreSQL `statement_timeout` of the schema statements in milliseconds (server default if unset)
+    pub statement_timeout_ms: Option<u64>,
+    /// MySQL `lock_wait_timeout` of the schema statements in seconds (server default if unset)
+    pub lock_wait_timeout_secs: Option<u64>,
+    /// Attempts of a statement that hit a lock timeout, including the first one, with the backoff
+    /// of `[general.retry]` (defaults to 3, 1 disables retries)
+    pub lock_retries: Option<u32>,
+    /// Tool executing the column and index changes of MySQL tables online, plain `ALTER TABLE`
+    /// if unset or not installed
+    pub online_tool: Option<ForgeOnlineTool>,
+    /// Extra arguments of the online tool, i.e. `["--max-load=Threads_running=25"]`
+    pub online_tool_args: Option<Vec<String>>,
+    /// NOT NULL columns with a default added to tables with at least this many (estimated) rows
+    /// are added nullable, backfilled in chunks and then made NOT NULL (no backfill if unset)
+    pub backfill_min_rows: Option<u64>,
+    /// Primary key range updated by one backfill statement (defaults to 10000)
+    pub backfill_chunk_rows: Option<u64>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Default)]
-pub struct ForgeSchemaTableConfig {
-    pub renames: Option<HashMap<String, String>>,
-    pub column_overrides: Option<HashMap<String, HashMap<String, String>>>,
+/// Online schema change tool of MySQL.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForgeOnlineTool {
+    #[serde(rename = "gh-ost")]
+    GhOst,
+    #[serde(rename = "pt-online-schema-change")]
+    PtOsc,
 }
 
-// Schema-Structures for internal representation of schema
+impl ForgeOnlineTool {
+    /// name of the executable
+    #[must_use]
+    pub fn command(self) -> &'static str {
+        match self {
+            Self::GhOst => "gh-ost",
+            Self::PtOsc => "pt-online-schema-change",
+        }
+    }
+}
+
+/// Log of rows the target rejected during replication.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ForgeErrorLogConfig {
+    /// Path of the log (defaults to `migration_errors.log`)
+    pub path: Option<std::path::PathBuf>,
+    /// "text" (default) or "json" (one JSON object per line)
+    pub format: Option<ForgeErrorLogFormat>,
+    /// Rotate the log when it would grow beyond this size in MiB (no rotation by default)
+    pub max_size_mb: Option<u64>,
+    /// Number of rotated logs kept as `<path>.1` to `<path>.N` (defaults to 5)
+    pub max_files: Option<usize>,
+}
+
+/// Line format of the error log.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeErrorLogFormat {
+    /// `TABLE: users | ERROR: ... | DATA: ...`
+    #[default]
+    Text,
+    /// `{"time": ..., "table": ..., "error": ..., "row": {...}}`
+    Json,
+}
+
+/// Retry policy for transient database errors (dropped connections, deadlocks, lock wait timeouts).
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ForgeRetryConfig {
+    /// Attempts including the first one (defaults to 3, 1 disables retries)
+    pub max_attempts: Option<u32>,
+    /// Waiting time before the first retry, doubled for every further retry (defaults to 500)
+    pub initial_backoff_ms: Option<u64>,
+    /// Upper limit of the waiting time (defaults to 30000)
+    pub max_backoff_ms: Option<u64>,
+    /// Randomize the waiting time, so parallel workers do not retry in lockstep (defaults to true)
+    pub jitter: Option<bool>,
+    /// Continue a table read interrupted by a transient error after the last primary key read,
+    /// read in key order (defaults to true, with false the error fails the table)
+    pub resume_streams: Option<bool>,
+}
+
+/// Keepalive of connections that stay open for hours, i.e. behind firewalls dropping idle connections.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ForgeKeepaliveConfig {
+    /// Seconds without traffic before PostgreSQL sends TCP keepalive probes (defaults to 60)
+    pub idle_secs: Option<u64>,
+    /// Seconds between the keepalive probes of PostgreSQL (defaults to 10)
+    pub interval_secs: Option<u64>,
+    /// Unanswered probes before PostgreSQL drops the connection (defaults to 6)
+    pub count: Option<u32>,
+    /// Idle pool connections are closed after this many seconds, before a firewall drops them (defaults to 300)
+    pub pool_idle_timeout_secs: Option<u64>,
+    /// MySQL `net_read_timeout` and `net_write_timeout`, a stream paused by a full queue is not aborted earlier (defaults to 600)
+    pub net_timeout_secs: Option<u64>,
+}
+
+/// Lag limit of a replica the data is read from.
+///
+/// # Examples
+///
+/// ```toml
+/// [general.replica_lag]
+/// max_secs = 30
+/// check_interval_secs = 60
+/// action = "abort"
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ForgeReplicaLagConfig {
+    /// Maximum lag of the source replica in seconds, no checks without it
+    pub max_secs: Option<u64>,
+    /// Seconds between the checks while tables are copied (defaults to 30)
+    pub check_interval_secs: Option<u64>,
+    /// What happens when the lag is too large (defaults to pause)
+    pub action: Option<ForgeReplicaLagAction>,
+}
+
+/// Reaction to a replica lag above the limit.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeReplicaLagAction {
+    /// wait until the replica caught up
+    #[default]
+    Pause,
+    /// fail the replication
+    Abort,
+}
+
+/// Tolerances of the verification for values that are stored differently by the engines.
+///
+/// Set globally in `[general.verify_tolerance]` and per table in `[tables.verify_tolerance.<table>]`.
+///
+/// # Examples
+///
+/// ```
+/// use fluxforge::core::ForgeConfig;
+///
+/// let config: ForgeConfig = toml::from_str(
+///     "[general.verify_tolerance]\nfloat_epsilon = 0.0001\ndatetime_precision = 3",
+/// )
+/// .unwrap();
+/// let tolerance = config.get_verify_tolerance("users");
+/// assert_eq!(tolerance.datetime_precision, Some(3));
+/// assert!(!tolerance.ignores_trailing_spaces());
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct ForgeVerifyTolerance {
+    /// Maximum absolute difference of two float values
+    pub float_epsilon: Option<f64>,
+    /// Number of fractional second digits compared for datetime and time values
+    pub datetime_precision: Option<u16>,
+    /// Compare text without trailing spaces (CHAR padding)
+    pub ignore_trailing_spaces: Option<bool>,
+    /// Unicode normalization form applied to both texts before comparing
+    pub text_normalization: Option<ForgeTextNormalization>,
+    /// Compare text case-insensitively (i.e. target collation `utf8mb4_general_ci`)
+    pub ignore_case: Option<bool>,
+    /// Compare text without accents and other combining marks ("é" equals "e")
+    pub ignore_accents: Option<bool>,
+}
+
+impl ForgeVerifyTolerance {
+    /// Whether text values are compared without trailing spaces (defaults to false).
+    #[must_use]
+    pub fn ignores_trailing_spaces(&self) -> bool {
+        self.ignore_trailing_spaces.unwrap_or(false)
+    }
+
+    /// Whether text values are compared case-insensitively (defaults to false).
+    #[must_use]
+    pub fn ignores_case(&self) -> bool {
+        self.ignore_case.unwrap_or(false)
+    }
+
+    /// Whether text values are compared without accents (defaults to false).
+    #[must_use]
+    pub fn ignores_accents(&self) -> bool {
+        self.ignore_accents.unwrap_or(false)
+    }
+
+    /// Tolerances of `self`, overridden by the options set in `other`.
+    #[must_use]
+    pub fn merged(self, other: &ForgeVerifyTolerance) -> Self {
+        Self {
+            float_epsilon: other.float_epsilon.or(self.float_epsilon),
+            datetime_precision: other.datetime_precision.or(self.datetime_precision),
+            ignore_trailing_spaces: other.ignore_trailing_spaces.or(self.ignore_trailing_spaces),
+            text_normalization: other.text_normalization.or(self.text_normalization),
+            ignore_case: other.ignore_case.or(self.ignore_case),
+            ignore_accents: other.ignore_accents.or(self.ignore_accents),
+        }
+    }
+}
+
+/// Unicode normalization forms for text comparison
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeTextNormalization {
+    /// canonical composition
+    Nfc,
+    /// canonical decomposition
+    Nfd,
+    /// compatibility composition ("ﬁ" equals "fi")
+    Nfkc,
+    /// compatibility decomposition
+    Nfkd,
+}
+
+/// Transaction boundaries of the data writes.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeWriteTransaction {
+    /// every chunk commits on its own, failed chunks are retried row by row
+    #[default]
+    Chunk,
+    /// every table is loaded in one transaction and rolled back on failure
+    Table,
+    /// all tables are loaded in one transaction, nothing is committed on failure
+    All,
+}
+
+impl std::str::FromStr for ForgeWriteTransaction {
+    type Err = String;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode.trim().to_lowercase().as_str() {
+            "chunk" => Ok(ForgeWriteTransaction::Chunk),
+            "table" => Ok(ForgeWriteTransaction::Table),
+            "all" => Ok(ForgeWriteTransaction::All),
+            other => Err(format!(
+                "unknown write transaction '{other}', expected chunk, table or all"
+            )),
+        }
+    }
+}
+
+/// How thoroughly the replicated data is verified.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeVerifyLevel {
+    /// every (or every sampled) row is compared after its table is written
+    #[default]
+    Full,
+    /// only the row counts of all tables are compared after the replication
+    Counts,
+}
+
+impl std::str::FromStr for ForgeVerifyLevel {
+    type Err = String;
+
+    fn from_str(level: &str) -> Result<Self, Self::Err> {
+        match level.trim().to_lowercase().as_str() {
+            "full" => Ok(ForgeVerifyLevel::Full),
+            "counts" => Ok(ForgeVerifyLevel::Counts),
+            other => Err(format!(
+                "unknown verify level '{other}', expected full or counts"
+            )),
+        }
+    }
+}
+
+/// Share of rows checked by sampled verification, parsed from "1%" or "0.5%".
+///
+/// Rows are selected by a hash of their primary key, so source and target
+/// select the same rows and repeated runs with the same seed check the same sample.
+///
+/// # Examples
+///
+/// ```
+/// use fluxforge::core::ForgeVerifySample;
+///
+/// let sample: ForgeVerifySample = "0.5%".parse().unwrap();
+/// assert_eq!(sample.basis_points, 50);
+/// assert_eq!(sample.to_string(), "0.5%");
+/// assert_eq!(sample.with_seed(42).seed, 42);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ForgeVerifySample {
+    /// Sampled rows per [`ForgeVerifySample::BUCKETS`] rows (1 = 0.01%)
+    pub basis_points: u32,
+    /// Salt of the primary key hash, 0 hashes the key only
+    pub seed: u64,
+}
+
+impl ForgeVerifySample {
+    /// Number of hash buckets, a row is sampled if its bucket is below `basis_points`
+    pub const BUCKETS: u32 = 10_000;
+
+    /// The same share of rows, selected with another seed.
+    #[must_use]
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self { seed, ..self }
+    }
+}
+
+impl std::str::FromStr for ForgeVerifySample {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let percent: f64 = spec
+            .trim()
+            .trim_end_matches('%')
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid sample '{spec}', expected a percentage like 1%"))?;
+        if !(percent > 0.0 && percent <= 100.0) {
+            return Err(format!("sample '{spec}' must be between 0% and 100%"));
+        }
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let basis_points = ((percent * 100.0).round() as u32).max(1);
+        Ok(Self {
+            basis_points,
+            seed: 0,
+        })
+    }
+}
+
+impl TryFrom<String> for ForgeVerifySample {
+    type Error = String;
+
+    fn try_from(spec: String) -> Result<Self, Self::Error> {
+        spec.parse()
+    }
+}
+
+impl From<ForgeVerifySample> for String {
+    fn from(sample: ForgeVerifySample) -> Self {
+        sample.to_string()
+    }
+}
+
+impl std::fmt::Display for ForgeVerifySample {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}%", f64::from(self.basis_points) / 100.0)
+    }
+}
+
+/// Inclusive range of integer primary key values, parsed from "100000:200000".
+///
+/// Either end may be left open, "100000:" are all keys from 100000 on.
+///
+/// # Examples
+///
+/// ```
+/// use fluxforge::core::ForgeKeyRange;
+///
+/// let range: ForgeKeyRange = "100000:200000".parse().unwrap();
+/// assert_eq!((range.from, range.to), (100_000, 200_000));
+/// assert_eq!(":5".parse::<ForgeKeyRange>().unwrap().from, i64::MIN);
+/// assert!("5:1".parse::<ForgeKeyRange>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForgeKeyRange {
+    /// First key of the range
+    pub from: i64,
+    /// Last key of the range
+    pub to: i64,
+}
+
+impl std::str::FromStr for ForgeKeyRange {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid key range '{spec}', expected from:to like 100:200");
+        let (from, to) = spec.split_once(':').ok_or_else(invalid)?;
+        let bound = |value: &str, open: i64| match value.trim() {
+            "" => Ok(open),
+            value => value.parse().map_err(|_| invalid()),
+        };
+        let range = Self {
+            from: bound(from, i64::MIN)?,
+            to: bound(to, i64::MAX)?,
+        };
+        if range.from > range.to {
+            return Err(format!("key range '{spec}' ends before it starts"));
+        }
+        Ok(range)
+    }
+}
+
+impl std::fmt::Display for ForgeKeyRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.from, self.to)
+    }
+}
+
+/// Session time zone of a connection, "UTC" or a fixed offset like "+02:00".
+///
+/// Values of time zone aware columns (MySQL `TIMESTAMP`, PostgreSQL `timestamptz`) are read
+/// and written as wall time of the session, replication shifts them from the source to the
+/// target time zone.
+///
+/// # Examples
+///
+/// ```
+/// use fluxforge::core::ForgeTimeZone;
+///
+/// let tz: ForgeTimeZone = "+05:30".parse().unwrap();
+/// assert_eq!(tz.offset_seconds, 19_800);
+/// assert_eq!(tz.to_string(), "+05:30");
+/// assert_eq!("UTC".parse::<ForgeTimeZone>().unwrap().to_string(), "+00:00");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ForgeTimeZone {
+    /// Seconds east of UTC
+    pub offset_seconds: i32,
+}
+
+impl ForgeTimeZone {
+    /// Converts a wall time of this time zone into the wall time of `target`.
+    #[must_use]
+    pub fn convert(self, value: NaiveDateTime, target: ForgeTimeZone) -> NaiveDateTime {
+        value + chrono::TimeDelta::seconds(i64::from(target.offset_seconds - self.offset_seconds))
+    }
+}
+
+impl std::str::FromStr for ForgeTimeZone {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let spec = spec.trim();
+        if spec.eq_ignore_ascii_case("utc") || spec == "Z" {
+            return Ok(Self::default());
+        }
+        let invalid =
+            || format!("invalid time zone '{spec}', expected UTC or an offset like +02:00");
+        let (sign, offset) = match spec.split_at_checked(1) {
+            Some(("+", offset)) => (1, offset),
+            Some(("-", offset)) => (-1, offset),
+            _ => return Err(invalid()),
+        };
+        let (hours, minutes) = offset.split_once(':').ok_or_else(invalid)?;
+        let hours: i32 = hours.parse().map_err(|_| invalid())?;
+        let minutes: i32 = minutes.parse().map_err(|_| invalid())?;
+        if hours > 14 || minutes > 59 {
+            return Err(invalid());
+        }
+        Ok(Self {
+            offset_seconds: sign * (hours * 3600 + minutes * 60),
+        })
+    }
+}
+
+impl TryFrom<String> for ForgeTimeZone {
+    type Error = String;
+
+    fn try_from(spec: String) -> Result<Self, Self::Error> {
+        spec.parse()
+    }
+}
+
+impl From<ForgeTimeZone> for String {
+    fn from(tz: ForgeTimeZone) -> Self {
+        tz.to_string()
+    }
+}
+
+impl std::fmt::Display for ForgeTimeZone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.offset_seconds < 0 { '-' } else { '+' };
+        let minutes = self.offset_seconds.abs() / 60;
+        write!(f, "{sign}{:02}:{:02}", minutes / 60, minutes % 60)
+    }
+}
+
+/// Position of NULL values when streaming ordered table data.
+///
+/// MySQL sorts NULLs first and PostgreSQL sorts them last in ascending order,
+/// so ordered streams must agree on one variant for row-by-row verification.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ForgeNullOrdering {
+    /// NULLs sort before all other values (MySQL default)
+    #[default]
+    First,
+    /// NULLs sort after all other values (PostgreSQL default)
+    Last,
+}
+
+/// Override of a source column, a type or a table with type, nullability and default.
+///
+/// # Examples
+///
+/// ```toml
+/// [tables.column_overrides.users]
+/// balance = "numeric(18,4)"
+/// status = { type = "varchar(20)", nullable = false, default = "'active'" }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum ForgeColumnOverride {
+    /// Type like `numeric(18,4)` or `varchar(100)`
+    Type(String),
+    Attributes {
+        #[serde(rename = "type")]
+        data_type: Option<String>,
+        nullable: Option<bool>,
+        /// Default expression of the target column, i.e. `'active'` or `0`
+        default: Option<String>,
+    },
+}
+
+impl ForgeColumnOverride {
+    /// Overridden type, if any.
+    #[must_use]
+    pub fn data_type(&self) -> Option<&str> {
+        match self {
+            ForgeColumnOverride::Type(spec) => Some(spec),
+            ForgeColumnOverride::Attributes { data_type, .. } => data_type.as_deref(),
+        }
+    }
+
+    /// Applies the overridden type, nullability and default to a column.
+    pub fn apply(&self, column: &mut ForgeSchemaColumn) {
+        if let Some(spec) = self.data_type() {
+            apply_type_spec(column, spec);
+        }
+        if let ForgeColumnOverride::Attributes {
+            nullable, default, ..
+        } = self
+        {
+            if let Some(nullable) = nullable {
+                column.is_nullable = *nullable;
+            }
+            if let Some(default) = default {
+                column.default = Some(default.clone());
+            }
+        }
+    }
+}
+
+/// Per-column policy to reconcile empty strings and NULL values during replication.
+///
+/// # Examples
+///
+/// ```toml
+/// [tables.null_policies.users]
+/// nickname = "empty_to_null"
+/// street = "null_to_empty"
+/// country = "null_to_default"
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ForgeNullPolicy {
+    /// Empty strings are written as NULL
+    EmptyToNull,
+    /// NULL values are written as empty strings
+    NullToEmpty,
+    /// NULL values are replaced by the column default
+    NullToDefault,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ForgeRulesConfig {
+    pub rules: Option<ForgeRulesDirectionConfig>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ForgeSchemaTableConfig {
+    pub renames: Option<HashMap<String, String>>,
+    /// Target names of source columns per source table
+    pub column_renames: Option<HashMap<String, HashMap<String, String>>>,
+    /// Type, nullability and default per source table and column
+    pub column_overrides: Option<HashMap<String, HashMap<String, ForgeColumnOverride>>>,
+    /// NULL/empty-string policies per table and column
+    pub null_policies: Option<HashMap<String, HashMap<String, ForgeNullPolicy>>>,
+    /// Verification tolerances per table, override `[general.verify_tolerance]`
+    pub verify_tolerance: Option<HashMap<String, ForgeVerifyTolerance>>,
+    /// Concurrent insert workers per table, override `[general] insert_workers`
+    pub insert_workers: Option<HashMap<String, usize>>,
+    /// Source tables replicated first and in this order, as far as the foreign keys allow
+    pub replication_order: Option<Vec<String>>,
+    /// Settings of single tables, `[tables.<name>]` with the source name of the table
+    /// (a table cannot be named like one of the other keys of `[tables]`)
+    #[serde(flatten)]
+    pub settings: HashMap<String, ForgeTableSettings>,
+}
+
+/// Settings of one table in a `[tables.<name>]` section.
+///
+/// # Examples
+///
+/// ```toml
+/// [tables.orders]
+/// chunk_size = 5000
+/// filter = "created_at >= '2024-01-01'"
+/// verify_level = "counts"
+/// priority = 10
+///
+/// [tables.countries]
+/// sync = "reload"
+/// reload_filter = "region = 'EU'"
+///
+/// [tables.orders.transforms]
+/// email = "lowercase"
+/// comment = "trim"
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ForgeTableSettings {
+    /// Rows per insert chunk (defaults to 1000)
+    pub chunk_size: Option<usize>,
+    /// SQL condition of the rows read from the source, verification reads the same rows
+    pub filter: Option<String>,
+    /// Transformation of the source values per column, applied before the NULL policies
+    pub transforms: Option<HashMap<String, ForgeColumnTransform>>,
+    /// Verification level of the table, overrides `[general] verify_level`
+    pub verify_level: Option<ForgeVerifyLevel>,
+    /// `false` skips the verification of the table
+    pub verify: Option<bool>,
+    /// Tables with a higher priority are replicated first, as far as the foreign keys allow
+    /// (defaults to 0)
+    pub priority: Option<i64>,
+    /// How the cycles of `replicate --watch` bring the table up to date (defaults to append)
+    pub sync: Option<ForgeSyncMode>,
+    /// SQL condition of the target rows deleted by a reload (defaults to all rows)
+    pub reload_filter: Option<String>,
+    /// `true` deletes the target rows missing in the source in a merge (defaults to false)
+    pub sync_deletes: Option<bool>,
+}
+
+/// How a sync cycle of `replicate --watch` brings a table up to date.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeSyncMode {
+    /// the source rows after the last primary key of the target are appended
+    #[default]
+    Append,
+    /// the target rows are deleted and copied again from the source in one transaction,
+    /// for small reference tables whose rows are updated and deleted too
+    Reload,
+    /// source and target are compared by primary key, only missing and changed rows are
+    /// written (and extra rows deleted with `sync_deletes`)
+    Merge,
+}
+
+impl std::str::FromStr for ForgeSyncMode {
+    type Err = String;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode.trim().to_lowercase().as_str() {
+            "append" => Ok(ForgeSyncMode::Append),
+            "reload" => Ok(ForgeSyncMode::Reload),
+            "merge" => Ok(ForgeSyncMode::Merge),
+            other => Err(format!(
+                "unknown sync mode '{other}', expected append, reload or merge"
+            )),
+        }
+    }
+}
+
+/// Transformation of the values of a column during replication.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ForgeColumnTransform {
+    /// Leading and trailing whitespace of texts is removed
+    Trim,
+    /// Texts are written in lowercase
+    Lowercase,
+    /// Texts are written in uppercase
+    Uppercase,
+    /// Every value is written as NULL, i.e. to drop personal data
+    Null,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ForgeGrantsConfig {
+    /// source grantee (as reported by the source, e.g. `'app'@'%'` or `app`) -> target grantee
+    pub user_mapping: Option<HashMap<String, String>>,
+}
+
+/// Settings of `fluxforge export`.
+///
+/// # Examples
+///
+/// ```toml
+/// [export]
+/// delimiter = ";"
+/// quote = "always"
+/// null_value = "\\N"
+/// header = false
+/// row_group_size = 50000
+/// compression = "zstd"
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ForgeExportConfig {
+    /// Field delimiter of CSV files
+    pub delimiter: Option<char>,
+    /// When CSV fields are quoted
+    pub quote: Option<ForgeCsvQuote>,
+    /// Text written for NULL values, a text equal to it is quoted
+    pub null_value: Option<String>,
+    /// Whether CSV files start with a line of column names
+    pub header: Option<bool>,
+    /// Rows per row group of Parquet files
+    pub row_group_size: Option<usize>,
+    /// Compression of Parquet files
+    pub compression: Option<ForgeParquetCompression>,
+}
+
+/// Settings of `fluxforge lint` (`[lint]`).
+///
+/// # Examples
+///
+/// ```toml
+/// [lint]
+/// max_row_bytes = 8126
+///
+/// [lint.rules]
+/// reserved_word = "off"
+/// missing_primary_key = "error"
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ForgeLintConfig {
+    /// Severity by rule name, overrides the default severity of the rule
+    pub rules: Option<HashMap<String, ForgeLintSeverity>>,
+    /// Estimated row size above which the `row_size` rule reports a table
+    pub max_row_bytes: Option<u64>,
+}
+
+/// Webhooks receiving a JSON summary of a `replicate` or `migrate` run (`[notifications]`).
+///
+/// # Examples
+///
+/// ```toml
+/// [notifications]
+/// on_success = "https://hooks.slack.com/services/T000/B000/XXXX"
+/// on_failure = "${ALERT_WEBHOOK}"
+/// on_table_error = "${ALERT_WEBHOOK}"
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ForgeNotificationsConfig {
+    /// URL posted to after a successful run
+    pub on_success: Option<String>,
+    /// URL posted to after a failed run
+    pub on_failure: Option<String>,
+    /// URL posted to after a run in which rows of a table were rejected
+    pub on_table_error: Option<String>,
+    /// Timeout of a webhook request (defaults to 10 s)
+    pub timeout_secs: Option<u64>,
+}
+
+/// Severity of a lint rule, `error` fails the `lint` command.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeLintSeverity {
+    /// the rule is not checked
+    Off,
+    Warning,
+    Error,
+}
+
+/// SSH tunnel through a bastion host for database connections (`[ssh]`, `--ssh`).
+///
+/// The tunnel is a port forward of the system `ssh` client, authenticated by key without
+/// password prompts.
+///
+/// # Examples
+///
+/// ```toml
+/// [ssh]
+/// host = "deploy@bastion.example.com:2222"
+/// identity_file = "/etc/fluxforge/id_ed25519"
+/// known_hosts_file = "/etc/fluxforge/known_hosts"
+/// host_key_checking = "accept-new"
+/// connections = "source"
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ForgeSshConfig {
+    /// Bastion as `user@host` or `user@host:port`
+    pub host: Option<String>,
+    /// Private key used instead of the keys of the ssh agent and `~/.ssh`
+    pub identity_file: Option<std::path::PathBuf>,
+    /// Known hosts file used instead of `~/.ssh/known_hosts`
+    pub known_hosts_file: Option<std::path::PathBuf>,
+    /// Check of the host key of the bastion
+    pub host_key_checking: Option<ForgeSshHostKeyChecking>,
+    /// Connections opened through the tunnel
+    pub connections: Option<ForgeSshConnections>,
+}
+
+impl ForgeSshConfig {
+    /// Returns true if a bastion is set and the source (`is_source`) or target connections use it.
+    #[must_use]
+    pub fn applies_to(&self, is_source: bool) -> bool {
+        self.host.is_some()
+            && match self.connections.unwrap_or_default() {
+                ForgeSshConnections::All => true,
+                ForgeSshConnections::Source => is_source,
+                ForgeSshConnections::Target => !is_source,
+            }
+    }
+}
+
+/// `StrictHostKeyChecking` of the tunnel.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ForgeSshHostKeyChecking {
+    /// the host key must be in the known hosts file
+    #[default]
+    Yes,
+    /// unknown host keys are added, changed keys are refused
+    AcceptNew,
+    /// every host key is accepted
+    No,
+}
+
+impl ForgeSshHostKeyChecking {
+    /// value of the `StrictHostKeyChecking` option of `ssh`
+    #[must_use]
+    pub fn ssh_option(self) -> &'static str {
+        match self {
+            ForgeSshHostKeyChecking::Yes => "yes",
+            ForgeSshHostKeyChecking::AcceptNew => "accept-new",
+            ForgeSshHostKeyChecking::No => "no",
+        }
+    }
+}
+
+/// Database connections opened through the SSH tunnel.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeSshConnections {
+    #[default]
+    All,
+    Source,
+    Target,
+}
+
+/// Credentials of the source and target connections (`[credentials]`).
+///
+/// # Examples
+///
+/// ```toml
+/// [credentials.source]
+/// user = "replicator"
+/// password_file = "/run/secrets/source_password"
+///
+/// [credentials.target]
+/// password_env = "SHOP_TARGET_PASSWORD"
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ForgeCredentialsConfig {
+    pub source: Option<ForgeCredential>,
+    pub target: Option<ForgeCredential>,
+}
+
+/// User and password source of a connection, they replace the ones of the URL.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ForgeCredential {
+    /// User name
+    pub user: Option<String>,
+    /// File holding the password, a trailing line break is ignored
+    pub password_file: Option<std::path::PathBuf>,
+    /// Environment variable holding the password
+    pub password_env: Option<String>,
+}
+
+/// Compression codec of exported Parquet files.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeParquetCompression {
+    None,
+    #[default]
+    Snappy,
+    Zstd,
+}
+
+/// When a CSV field is put in double quotes.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeCsvQuote {
+    /// fields containing the delimiter, a quote or a line break, and texts equal to the NULL value
+    #[default]
+    Necessary,
+    /// every field except NULL values
+    Always,
+    /// no field, the values must not contain the delimiter
+    Never,
+}
+
+/// File format of `fluxforge export`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeExportFormat {
+    /// one CSV file per table
+    #[default]
+    Csv,
+    /// one Parquet file per table (feature `parquet`)
+    Parquet,
+}
+
+impl ForgeExportFormat {
+    /// file extension of the exported tables
+    #[must_use]
+    pub fn extension(self) -> &'static str {
+        match self {
+            ForgeExportFormat::Csv => "csv",
+            ForgeExportFormat::Parquet => "parquet",
+        }
+    }
+}
+
+impl std::str::FromStr for ForgeExportFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format.trim().to_lowercase().as_str() {
+            "csv" => Ok(ForgeExportFormat::Csv),
+            "parquet" => Ok(ForgeExportFormat::Parquet),
+            other => Err(format!(
+                "unknown export format '{other}', expected csv or parquet"
+            )),
+        }
+    }
+}
+
+/// File format of schema snapshots (`extract --schema`).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeSchemaFormat {
+    /// pretty-printed JSON
+    #[default]
+    Json,
+    /// YAML, shorter diffs in reviews
+    Yaml,
+}
+
+impl ForgeSchemaFormat {
+    /// format of the file extension, `None` for other extensions
+    #[must_use]
+    pub fn from_path(path: &std::path::Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "json" => Some(ForgeSchemaFormat::Json),
+            "yaml" | "yml" => Some(ForgeSchemaFormat::Yaml),
+            _ => None,
+        }
+    }
+}
+
+impl std::str::FromStr for ForgeSchemaFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format.trim().to_lowercase().as_str() {
+            "json" => Ok(ForgeSchemaFormat::Json),
+            "yaml" | "yml" => Ok(ForgeSchemaFormat::Yaml),
+            other => Err(format!(
+                "unknown schema format '{other}', expected json or yaml"
+            )),
+        }
+    }
+}
+
+/// Output format of `fluxforge document`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeDocFormat {
+    /// Markdown pages, rendered by code hosts next to the migration
+    #[default]
+    Markdown,
+    /// standalone HTML pages
+    Html,
+}
+
+impl ForgeDocFormat {
+    /// file extension of the pages
+    #[must_use]
+    pub fn extension(self) -> &'static str {
+        match self {
+            ForgeDocFormat::Markdown => "md",
+            ForgeDocFormat::Html => "html",
+        }
+    }
+}
+
+impl std::str::FromStr for ForgeDocFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format.trim().to_lowercase().as_str() {
+            "markdown" | "md" => Ok(ForgeDocFormat::Markdown),
+            "html" => Ok(ForgeDocFormat::Html),
+            other => Err(format!(
+                "unknown documentation format '{other}', expected markdown or html"
+            )),
+        }
+    }
+}
+
+/// Output format of `fluxforge graph`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeGraphFormat {
+    /// Mermaid `erDiagram`, rendered by code hosts in Markdown
+    #[default]
+    Mermaid,
+    /// Graphviz DOT
+    Dot,
+}
+
+impl std::str::FromStr for ForgeGraphFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format.trim().to_lowercase().as_str() {
+            "mermaid" => Ok(ForgeGraphFormat::Mermaid),
+            "dot" | "graphviz" => Ok(ForgeGraphFormat::Dot),
+            other => Err(format!(
+                "unknown graph format '{other}', expected mermaid or dot"
+            )),
+        }
+    }
+}
+
+/// Post-migration check: a query returning a single value and the expected result.
+///
+/// # Examples
+///
+/// ```toml
+/// [[smoke_tests]]
+/// name = "no negative order totals"
+/// table = "orders"
+/// sql = "SELECT count(*) FROM orders WHERE total < 0"
+/// expect = 0
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ForgeSmokeTest {
+    /// Name shown in the report (defaults to the query)
+    pub name: Option<String>,
+    /// Only run if this table is part of the replication, global test if not set
+    pub table: Option<String>,
+    /// Query returning one value (first column of the first row)
+    pub sql: String,
+    /// Expected value, numbers are compared numerically
+    pub expect: serde_json::Value,
+}
+
+// Schema-Structures for internal representation of schema
 
 /// Complete database schema representation.
 ///
@@ -149,185 +1978,1228 @@ pub struct ForgeSchemaTableConfig {
 /// println!("Schema has {} tables", schema.tables.len());
 /// ```
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
-pub struct ForgeSchema {
-    /// Metadata about the source database and extraction
-    pub metadata: ForgeSchemaMetadata,
-    /// List of all tables in the schema
-    pub tables: Vec<ForgeSchemaTable>,
+pub struct ForgeSchema {
+    /// Metadata about the source database and extraction
+    pub metadata: ForgeSchemaMetadata,
+    /// List of all tables in the schema
+    pub tables: Vec<ForgeSchemaTable>,
+    /// Extensions providing column types or default functions of the tables (PostgreSQL)
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    /// User-defined domains of the source (PostgreSQL), created before the tables
+    #[serde(default)]
+    pub domains: Vec<ForgeSchemaDomain>,
+}
+
+impl ForgeSchema {
+    /// Creates a new empty schema.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fluxforge::core::ForgeSchema;
+    ///
+    /// let schema = ForgeSchema::new();
+    /// assert_eq!(schema.tables.len(), 0);
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// A user-defined PostgreSQL domain, a base type with constraints.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct ForgeSchemaDomain {
+    pub name: String,
+    /// base type with its modifiers, i.e. "character varying(254)"
+    pub data_type: String,
+    pub not_null: bool,
+    pub default: Option<String>,
+    /// CHECK constraints like "CHECK ((VALUE > 0))"
+    #[serde(default)]
+    pub checks: Vec<String>,
+}
+
+/// Metadata about a schema extraction.
+///
+/// Tracks the source database, extraction time, and configuration used.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ForgeSchemaMetadata {
+    /// Layout of the schema file, see [`SCHEMA_FORMAT_VERSION`](crate::ops::schema_file::SCHEMA_FORMAT_VERSION)
+    #[serde(default)]
+    pub format_version: u32,
+    /// Source database type ("mysql" or "postgres")
+    pub source_system: String,
+    /// Name of the source database
+    pub source_database_name: String,
+    /// ISO 8601 timestamp of schema extraction
+    pub created_at: String,
+    /// FluxForge version used for extraction
+    pub forge_version: String,
+    /// Path to configuration file used
+    pub config_file: String,
+    /// Version string reported by the source server (e.g. "8.0.36")
+    #[serde(default)]
+    pub source_server_version: String,
+    /// Replication position of the source when the extraction started,
+    /// the start position of a later CDC or incremental run
+    #[serde(default)]
+    pub source_position: Option<ForgeSourcePosition>,
+}
+
+/// Replication position of a source database.
+///
+/// Only the fields of the source system are set, fields the user has no privilege
+/// to read (i.e. the binlog status without `REPLICATION CLIENT`) stay empty.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct ForgeSourcePosition {
+    /// MySQL `gtid_executed` or MariaDB `gtid_current_pos`, empty without GTID mode
+    pub gtid_set: Option<String>,
+    /// MySQL/MariaDB binary log file
+    pub binlog_file: Option<String>,
+    /// position in the binary log file
+    pub binlog_position: Option<u64>,
+    /// PostgreSQL WAL position (i.e. "0/16B6C50")
+    pub lsn: Option<String>,
+}
+
+impl std::fmt::Display for ForgeSourcePosition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(ref gtid_set) = self.gtid_set {
+            parts.push(format!("gtid {gtid_set}"));
+        }
+        if let (Some(file), Some(position)) = (&self.binlog_file, self.binlog_position) {
+            parts.push(format!("binlog {file}:{position}"));
+        }
+        if let Some(ref lsn) = self.lsn {
+            parts.push(format!("lsn {lsn}"));
+        }
+        if parts.is_empty() {
+            write!(f, "unknown")
+        } else {
+            write!(f, "{}", parts.join(", "))
+        }
+    }
+}
+
+/// Size of a table from the catalog statistics, used for the estimation before a replication.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct ForgeTableEstimate {
+    pub table_name: String,
+    /// estimated row count (`information_schema.TABLES.TABLE_ROWS`, `pg_class.reltuples`)
+    pub rows: u64,
+    /// size of the table data on disk
+    pub bytes: u64,
+}
+
+/// Checksum of the rows of a primary key range, computed by the database.
+///
+/// Range `n` holds the rows with keys from `n * range_keys` to `(n + 1) * range_keys - 1`. The
+/// checksum depends on the server type, it is only compared with checksums of the same server.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct ForgeRangeChecksum {
+    pub range: i64,
+    pub rows: u64,
+    pub checksum: String,
+}
+
+/// Outcome of a pre-flight check.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeCheckStatus {
+    Pass,
+    /// the migration can run, but may be slow or lose information
+    Warn,
+    /// the migration will fail
+    Fail,
+}
+
+/// Result of one check of `fluxforge doctor`, i.e. a privilege or a server setting.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ForgePreflightCheck {
+    pub name: String,
+    pub status: ForgeCheckStatus,
+    /// found value or the reason of a failure
+    pub detail: String,
+}
+
+impl ForgePreflightCheck {
+    #[must_use]
+    pub fn pass(name: &str, detail: impl Into<String>) -> Self {
+        Self::new(name, ForgeCheckStatus::Pass, detail)
+    }
+
+    #[must_use]
+    pub fn warn(name: &str, detail: impl Into<String>) -> Self {
+        Self::new(name, ForgeCheckStatus::Warn, detail)
+    }
+
+    #[must_use]
+    pub fn fail(name: &str, detail: impl Into<String>) -> Self {
+        Self::new(name, ForgeCheckStatus::Fail, detail)
+    }
+
+    fn new(name: &str, status: ForgeCheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Represents a database table with all its components.
+///
+/// # Examples
+///
+/// ```
+/// use fluxforge::core::ForgeSchemaTable;
+///
+/// let table = ForgeSchemaTable::new("users");
+/// assert_eq!(table.name, "users");
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ForgeSchemaTable {
+    /// Table name
+    pub name: String,
+    /// List of columns in the table
+    pub columns: Vec<ForgeSchemaColumn>,
+    /// List of indices defined on the table
+    pub indices: Vec<ForgeSchemaIndex>,
+    /// List of foreign key constraints
+    pub foreign_keys: Vec<ForgeSchemaForeignKey>,
+    /// Optional table comment
+    pub comment: Option<String>,
+    /// Partition strategy and partitions of a partitioned table
+    #[serde(default)]
+    pub partitioning: Option<ForgeSchemaPartitioning>,
+}
+
+/// Partitioning of a table, the partitions hold the rows of the table.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct ForgeSchemaPartitioning {
+    /// Partition strategy ("range", "list" or "hash")
+    pub strategy: String,
+    /// Partition key, columns or expressions (i.e. "created_at")
+    pub key: String,
+    /// Partitions in source order
+    pub partitions: Vec<ForgeSchemaPartition>,
+}
+
+impl ForgeSchemaPartitioning {
+    /// Returns true if the bounds are PostgreSQL SQL (`FOR VALUES ...` or `DEFAULT`).
+    #[must_use]
+    pub fn has_postgres_bounds(&self) -> bool {
+        self.partitions
+            .iter()
+            .any(|p| p.bound.starts_with("FOR VALUES") || p.bound == "DEFAULT")
+    }
+}
+
+/// Represents a partition of a partitioned table.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct ForgeSchemaPartition {
+    /// Partition name
+    pub name: String,
+    /// Bound in the SQL of the source, i.e. "FOR VALUES FROM ('2024-01-01') TO ('2025-01-01')"
+    /// (PostgreSQL) or "VALUES LESS THAN (2025)" (MySQL), empty for hash partitions
+    pub bound: String,
+}
+
+impl ForgeSchemaTable {
+    /// Creates a new table with the given name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fluxforge::core::ForgeSchemaTable;
+    ///
+    /// let table = ForgeSchemaTable::new("products");
+    /// assert_eq!(table.name, "products");
+    /// assert_eq!(table.columns.len(), 0);
+    /// ```
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Represents a table column with all its properties.
+///
+/// # Examples
+///
+/// ```
+/// use fluxforge::core::ForgeSchemaColumn;
+///
+/// let mut col = ForgeSchemaColumn::new("id", "integer");
+/// col.is_primary_key = true;
+/// col.auto_increment = true;
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ForgeSchemaColumn {
+    /// Column name
+    pub name: String,
+    /// Data type (mapped according to configuration)
+    pub data_type: String,
+    /// Length for character types (VARCHAR, CHAR)
+    pub length: Option<u32>,
+    /// Precision for numeric types (DECIMAL, NUMERIC)
+    pub precision: Option<u32>,
+    /// Scale for numeric types (DECIMAL, NUMERIC)
+    pub scale: Option<u32>,
+    /// Whether the column allows NULL values
+    pub is_nullable: bool,
+    /// Whether this column is part of the primary key
+    pub is_primary_key: bool,
+    /// Whether this is an unsigned integer (MySQL)
+    pub is_unsigned: bool,
+    /// Whether this column auto-increments
+    pub auto_increment: bool,
+    /// Default value expression
+    pub default: Option<String>,
+    /// Column comment
+    pub comment: Option<String>,
+    /// ON UPDATE expression (e.g., CURRENT_TIMESTAMP)
+    pub on_update: Option<String>,
+    /// Enum/Set values for ENUM and SET types
+    pub enum_values: Option<Vec<String>>,
+    /// Expression of a generated column (`GENERATED ALWAYS AS (...)`)
+    #[serde(default)]
+    pub generation_expression: Option<String>,
+    /// Whether a generated column is STORED (otherwise VIRTUAL)
+    #[serde(default)]
+    pub is_generated_stored: bool,
+    /// Whether the column is hidden from `SELECT *` (MySQL 8.0.23+ INVISIBLE, i.e. GIPK `my_row_id`)
+    #[serde(default)]
+    pub is_invisible: bool,
+    /// Native column type as reported by the source before mapping (i.e. `int(5) unsigned zerofill`)
+    #[serde(default)]
+    pub source_type: Option<String>,
+    /// Domain of the column (PostgreSQL), `data_type` holds its base type
+    #[serde(default)]
+    pub domain: Option<String>,
 }
 
-impl ForgeSchema {
-    /// Creates a new empty schema.
+impl ForgeSchemaColumn {
+    /// Creates a new column with the given name and data type.
     ///
     /// # Examples
     ///
     /// ```
-    /// use fluxforge::core::ForgeSchema;
+    /// use fluxforge::core::ForgeSchemaColumn;
     ///
-    /// let schema = ForgeSchema::new();
-    /// assert_eq!(schema.tables.len(), 0);
+    /// let col = ForgeSchemaColumn::new("email", "varchar");
+    /// assert_eq!(col.name, "email");
+    /// assert_eq!(col.data_type, "varchar");
     /// ```
     #[must_use]
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(name: &str, data_type: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            ..Default::default()
+        }
     }
 }
 
-/// Metadata about a schema extraction.
-///
-/// Tracks the source database, extraction time, and configuration used.
+/// Represents a database index.
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
-pub struct ForgeSchemaMetadata {
-    /// Source database type ("mysql" or "postgres")
-    pub source_system: String,
-    /// Name of the source database
-    pub source_database_name: String,
-    /// ISO 8601 timestamp of schema extraction
-    pub created_at: String,
-    /// FluxForge version used for extraction
-    pub forge_version: String,
-    /// Path to configuration file used
-    pub config_file: String,
+pub struct ForgeSchemaIndex {
+    /// Index name
+    pub name: String,
+    /// Columns included in the index
+    pub columns: Vec<String>,
+    /// Whether this is a unique index
+    pub is_unique: bool,
+    /// Index type (e.g., "BTREE", "FULLTEXT")
+    pub index_type: Option<String>,
+    /// Prefix lengths for indexed columns (MySQL)
+    pub column_prefixes: Option<Vec<Option<u32>>>,
+    /// Expressions of functional key parts (MySQL 8), their entry in `columns` is empty
+    #[serde(default)]
+    pub expressions: Option<Vec<Option<String>>>,
 }
 
-/// Represents a database table with all its components.
+impl ForgeSchemaIndex {
+    /// Expression of the key part at `position`, `None` for a column.
+    #[must_use]
+    pub fn expression(&self, position: usize) -> Option<&str> {
+        self.expressions.as_ref()?.get(position)?.as_deref()
+    }
+}
+
+/// Represents a foreign key constraint.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ForgeSchemaForeignKey {
+    /// Constraint name
+    pub name: String,
+    /// Column in this table
+    pub column: String,
+    /// Referenced table name
+    pub ref_table: String,
+    /// Referenced column name
+    pub ref_column: String,
+    /// ON DELETE action (CASCADE, SET NULL, etc.)
+    pub on_delete: Option<String>,
+    /// ON UPDATE action (CASCADE, SET NULL, etc.)
+    pub on_update: Option<String>,
+}
+
+/// Table-level privileges of one grantee.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct ForgeSchemaGrant {
+    /// Grantee as reported by the source (`'user'@'host'` for MySQL, role name for PostgreSQL)
+    pub grantee: String,
+    /// Table name
+    pub table_name: String,
+    /// Privileges in upper case (SELECT, INSERT, ...)
+    pub privileges: Vec<String>,
+}
+
+// --- Rollback ---
+
+/// Inverse of a schema apply, recorded in the audit log.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct ForgeRollbackPlan {
+    /// Statements that restore the schema as it was before the apply
+    pub statements: Vec<String>,
+    /// Changes that cannot be reverted (data of dropped tables and columns)
+    pub irreversible: Vec<String>,
+}
+
+// --- Migrate options ---
+
+/// How [`DatabaseDriver::diff_and_apply_schema`](crate::DatabaseDriver::diff_and_apply_schema)
+/// changes the target.
+///
+/// A cancelled `cancel` token stops the apply before the next statement.
 ///
 /// # Examples
 ///
 /// ```
-/// use fluxforge::core::ForgeSchemaTable;
+/// use fluxforge::MigrateOptions;
 ///
-/// let table = ForgeSchemaTable::new("users");
-/// assert_eq!(table.name, "users");
+/// let options = MigrateOptions {
+///     dry_run: true,
+///     ..Default::default()
+/// };
+/// assert!(!options.destructive);
 /// ```
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
-pub struct ForgeSchemaTable {
-    /// Table name
+#[derive(Debug, Clone, Default)]
+pub struct MigrateOptions {
+    /// Return the SQL statements without executing them
+    pub dry_run: bool,
+    /// Enable verbose output
+    pub verbose: bool,
+    /// Drop tables and columns that are not in the source schema
+    pub destructive: bool,
+    /// Leave the foreign keys to [`DatabaseDriver::apply_foreign_keys`](crate::DatabaseDriver::apply_foreign_keys),
+    /// i.e. to create them after the data is loaded
+    pub defer_foreign_keys: bool,
+    /// Stops the apply with [`ForgeError::Cancelled`] when cancelled
+    pub cancel: CancellationToken,
+    /// Records the executed statements, also those of an apply that failed later
+    pub applied: ForgeAppliedStatements,
+}
+
+/// Statements a schema apply has executed, shared with its caller (clones share the list).
+///
+/// A failed apply returns only its error, the caller reads the statements it executed
+/// before the failure here, i.e. to record them in the audit log.
+#[derive(Debug, Clone, Default)]
+pub struct ForgeAppliedStatements(std::sync::Arc<std::sync::Mutex<Vec<String>>>);
+
+impl ForgeAppliedStatements {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<String>> {
+        self.0
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// records executed statements
+    pub fn extend<'a>(&self, statements: impl IntoIterator<Item = &'a String>) {
+        self.lock().extend(statements.into_iter().cloned());
+    }
+
+    /// the statements executed so far, in execution order
+    #[must_use]
+    pub fn snapshot(&self) -> Vec<String> {
+        self.lock().clone()
+    }
+}
+
+// --- Object filter ---
+
+/// Restricts schema changes to a list of objects, parsed from `--only ddl:users,orders`.
+///
+/// The default filter includes every object.
+///
+/// # Examples
+///
+/// ```
+/// use fluxforge::core::ForgeObjectFilter;
+///
+/// let filter: ForgeObjectFilter = "ddl:users,orders".parse().unwrap();
+/// assert!(filter.includes_table("users"));
+/// assert!(!filter.includes_table("audit"));
+/// assert!(ForgeObjectFilter::default().includes_table("audit"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ForgeObjectFilter {
+    /// Tables whose DDL may be changed, `None` for all tables
+    pub tables: Option<HashSet<String>>,
+}
+
+impl ForgeObjectFilter {
+    /// true if DDL of the table may be changed
+    #[must_use]
+    pub fn includes_table(&self, table_name: &str) -> bool {
+        self.tables
+            .as_ref()
+            .is_none_or(|tables| tables.contains(table_name))
+    }
+}
+
+impl std::str::FromStr for ForgeObjectFilter {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let (kind, names) = spec.split_once(':').ok_or_else(|| {
+            format!("invalid object list '{spec}', expected i.e. ddl:users,orders")
+        })?;
+        if kind.trim() != "ddl" {
+            return Err(format!("unsupported object kind '{kind}', expected 'ddl'"));
+        }
+
+        let tables: HashSet<String> = names
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(ToString::to_string)
+            .collect();
+        if tables.is_empty() {
+            return Err(format!("object list '{spec}' contains no tables"));
+        }
+
+        Ok(Self {
+            tables: Some(tables),
+        })
+    }
+}
+
+// --- Schema comparison ---
+
+/// Kind of a difference between two schemas, seen from the source ("expected") schema.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum ForgeSchemaDiffKind {
+    /// Object exists in the source but not in the target
+    Missing,
+    /// Object exists in both, but with different properties
+    Changed,
+    /// Object exists in the target but not in the source
+    Extra,
+}
+
+impl ForgeSchemaDiffKind {
+    fn sign(self) -> &'static str {
+        match self {
+            ForgeSchemaDiffKind::Missing => "-",
+            ForgeSchemaDiffKind::Changed => "~",
+            ForgeSchemaDiffKind::Extra => "+",
+        }
+    }
+}
+
+/// Type of the schema object a difference refers to.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum ForgeSchemaObjectType {
+    Table,
+    Column,
+    Index,
+    ForeignKey,
+}
+
+impl ForgeSchemaObjectType {
+    fn label(self) -> &'static str {
+        match self {
+            ForgeSchemaObjectType::Table => "table",
+            ForgeSchemaObjectType::Column => "column",
+            ForgeSchemaObjectType::Index => "index",
+            ForgeSchemaObjectType::ForeignKey => "foreign key",
+        }
+    }
+}
+
+/// One difference between two schemas.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ForgeSchemaDiffEntry {
+    /// Table the object belongs to
+    pub table: String,
+    pub object_type: ForgeSchemaObjectType,
+    /// Name of the object (table name for tables)
     pub name: String,
-    /// List of columns in the table
-    pub columns: Vec<ForgeSchemaColumn>,
-    /// List of indices defined on the table
-    pub indices: Vec<ForgeSchemaIndex>,
-    /// List of foreign key constraints
-    pub foreign_keys: Vec<ForgeSchemaForeignKey>,
-    /// Optional table comment
-    pub comment: Option<String>,
+    pub kind: ForgeSchemaDiffKind,
+    /// Changed properties as "property: source -> target"
+    pub details: Vec<String>,
+}
+
+/// Result of comparing a source schema with a target schema.
+///
+/// Entries are sorted by table, object type and name.
+///
+/// # Examples
+///
+/// ```
+/// use fluxforge::core::{ForgeSchema, ForgeSchemaDiff, ForgeSchemaTable};
+///
+/// let mut source = ForgeSchema::new();
+/// source.tables.push(ForgeSchemaTable::new("users"));
+/// let target = ForgeSchema::new();
+///
+/// let diff = ForgeSchemaDiff::compare(&source, &target);
+/// assert_eq!(diff.entries.len(), 1);
+/// println!("{}", diff.to_text());
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct ForgeSchemaDiff {
+    pub entries: Vec<ForgeSchemaDiffEntry>,
+}
+
+/// adds "property: source -> target" if the values differ
+fn diff_property<T: PartialEq + std::fmt::Debug>(
+    details: &mut Vec<String>,
+    property: &str,
+    source: &T,
+    target: &T,
+) {
+    if source != target {
+        details.push(format!("{property}: {source:?} -> {target:?}"));
+    }
+}
+
+impl ForgeSchemaDiff {
+    /// Compares `source` (expected state) with `target` (actual state).
+    #[must_use]
+    pub fn compare(source: &ForgeSchema, target: &ForgeSchema) -> Self {
+        let mut diff = Self::default();
+
+        let target_tables: HashMap<&str, &ForgeSchemaTable> =
+            target.tables.iter().map(|t| (t.name.as_str(), t)).collect();
+        let source_tables: HashMap<&str, &ForgeSchemaTable> =
+            source.tables.iter().map(|t| (t.name.as_str(), t)).collect();
+
+        for source_table in &source.tables {
+            match target_tables.get(source_table.name.as_str()) {
+                Some(target_table) => diff.compare_tables(source_table, target_table),
+                None => diff.push(
+                    &source_table.name,
+                    ForgeSchemaObjectType::Table,
+                    &source_table.name,
+                    ForgeSchemaDiffKind::Missing,
+                    Vec::new(),
+                ),
+            }
+        }
+
+        for target_table in &target.tables {
+            if !source_tables.contains_key(target_table.name.as_str()) {
+                diff.push(
+                    &target_table.name,
+                    ForgeSchemaObjectType::Table,
+                    &target_table.name,
+                    ForgeSchemaDiffKind::Extra,
+                    Vec::new(),
+                );
+            }
+        }
+
+        // stable order independent of the order the databases returned their objects in
+        diff.entries.sort_by(|a, b| {
+            (&a.table, a.object_type, &a.name, a.kind).cmp(&(
+                &b.table,
+                b.object_type,
+                &b.name,
+                b.kind,
+            ))
+        });
+
+        diff
+    }
+
+    /// true if both schemas are identical
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// number of entries of one kind
+    #[must_use]
+    pub fn count(&self, kind: ForgeSchemaDiffKind) -> usize {
+        self.entries.iter().filter(|e| e.kind == kind).count()
+    }
+
+    fn push(
+        &mut self,
+        table: &str,
+        object_type: ForgeSchemaObjectType,
+        name: &str,
+        kind: ForgeSchemaDiffKind,
+        details: Vec<String>,
+    ) {
+        self.entries.push(ForgeSchemaDiffEntry {
+            table: table.to_string(),
+            object_type,
+            name: name.to_string(),
+            kind,
+            details,
+        });
+    }
+
+    fn compare_tables(&mut self, source: &ForgeSchemaTable, target: &ForgeSchemaTable) {
+        let table = source.name.as_str();
+
+        let mut details = Vec::new();
+        diff_property(&mut details, "comment", &source.comment, &target.comment);
+        if !details.is_empty() {
+            self.push(
+                table,
+                ForgeSchemaObjectType::Table,
+                table,
+                ForgeSchemaDiffKind::Changed,
+                details,
+            );
+        }
+
+        // columns
+        for src in &source.columns {
+            match target.columns.iter().find(|c| c.name == src.name) {
+                Some(dst) => {
+                    let details = Self::column_details(src, dst);
+                    if !details.is_empty() {
+                        self.push(
+                            table,
+                            ForgeSchemaObjectType::Column,
+                            &src.name,
+                            ForgeSchemaDiffKind::Changed,
+                            details,
+                        );
+                    }
+                }
+                None => self.push(
+                    table,
+                    ForgeSchemaObjectType::Column,
+                    &src.name,
+                    ForgeSchemaDiffKind::Missing,
+                    Vec::new(),
+                ),
+            }
+        }
+        for dst in &target.columns {
+            if !source.columns.iter().any(|c| c.name == dst.name) {
+                self.push(
+                    table,
+                    ForgeSchemaObjectType::Column,
+                    &dst.name,
+                    ForgeSchemaDiffKind::Extra,
+                    Vec::new(),
+                );
+            }
+        }
+
+        // indices
+        for src in &source.indices {
+            match target.indices.iter().find(|i| i.name == src.name) {
+                Some(dst) => {
+                    let mut details = Vec::new();
+                    diff_property(&mut details, "columns", &src.columns, &dst.columns);
+                    diff_property(&mut details, "unique", &src.is_unique, &dst.is_unique);
+                    diff_property(
+                        &mut details,
+                        "expressions",
+                        &src.expressions,
+                        &dst.expressions,
+                    );
+                    if !details.is_empty() {
+                        self.push(
+                            table,
+                            ForgeSchemaObjectType::Index,
+                            &src.name,
+                            ForgeSchemaDiffKind::Changed,
+                            details,
+                        );
+                    }
+                }
+                None => self.push(
+                    table,
+                    ForgeSchemaObjectType::Index,
+                    &src.name,
+                    ForgeSchemaDiffKind::Missing,
+                    Vec::new(),
+                ),
+            }
+        }
+        for dst in &target.indices {
+            if !source.indices.iter().any(|i| i.name == dst.name) {
+                self.push(
+                    table,
+                    ForgeSchemaObjectType::Index,
+                    &dst.name,
+                    ForgeSchemaDiffKind::Extra,
+                    Vec::new(),
+                );
+            }
+        }
+
+        // foreign keys
+        for src in &source.foreign_keys {
+            match target.foreign_keys.iter().find(|f| f.name == src.name) {
+                Some(dst) => {
+                    let mut details = Vec::new();
+                    diff_property(&mut details, "column", &src.column, &dst.column);
+                    diff_property(&mut details, "ref_table", &src.ref_table, &dst.ref_table);
+                    diff_property(&mut details, "ref_column", &src.ref_column, &dst.ref_column);
+                    diff_property(&mut details, "on_delete", &src.on_delete, &dst.on_delete);
+                    diff_property(&mut details, "on_update", &src.on_update, &dst.on_update);
+                    if !details.is_empty() {
+                        self.push(
+                            table,
+                            ForgeSchemaObjectType::ForeignKey,
+                            &src.name,
+                            ForgeSchemaDiffKind::Changed,
+                            details,
+                        );
+                    }
+                }
+                None => self.push(
+                    table,
+                    ForgeSchemaObjectType::ForeignKey,
+                    &src.name,
+                    ForgeSchemaDiffKind::Missing,
+                    Vec::new(),
+                ),
+            }
+        }
+        for dst in &target.foreign_keys {
+            if !source.foreign_keys.iter().any(|f| f.name == dst.name) {
+                self.push(
+                    table,
+                    ForgeSchemaObjectType::ForeignKey,
+                    &dst.name,
+                    ForgeSchemaDiffKind::Extra,
+                    Vec::new(),
+                );
+            }
+        }
+    }
+
+    fn column_details(src: &ForgeSchemaColumn, dst: &ForgeSchemaColumn) -> Vec<String> {
+        let mut details = Vec::new();
+        diff_property(
+            &mut details,
+            "data_type",
+            &src.data_type.to_lowercase(),
+            &dst.data_type.to_lowercase(),
+        );
+        diff_property(&mut details, "length", &src.length, &dst.length);
+        diff_property(&mut details, "precision", &src.precision, &dst.precision);
+        diff_property(&mut details, "scale", &src.scale, &dst.scale);
+        diff_property(&mut details, "nullable", &src.is_nullable, &dst.is_nullable);
+        diff_property(
+            &mut details,
+            "primary_key",
+            &src.is_primary_key,
+            &dst.is_primary_key,
+        );
+        diff_property(&mut details, "unsigned", &src.is_unsigned, &dst.is_unsigned);
+        diff_property(
+            &mut details,
+            "auto_increment",
+            &src.auto_increment,
+            &dst.auto_increment,
+        );
+        diff_property(&mut details, "default", &src.default, &dst.default);
+        diff_property(&mut details, "on_update", &src.on_update, &dst.on_update);
+        diff_property(
+            &mut details,
+            "enum_values",
+            &src.enum_values,
+            &dst.enum_values,
+        );
+        diff_property(
+            &mut details,
+            "generation_expression",
+            &src.generation_expression,
+            &dst.generation_expression,
+        );
+        diff_property(
+            &mut details,
+            "is_invisible",
+            &src.is_invisible,
+            &dst.is_invisible,
+        );
+        details
+    }
+
+    /// Renders the diff as human readable text report.
+    #[must_use]
+    pub fn to_text(&self) -> String {
+        use std::fmt::Write as _;
+
+        let mut out = String::new();
+        if self.is_empty() {
+            out.push_str("Schemas are identical.\n");
+            return out;
+        }
+
+        let mut current_table = "";
+        for entry in &self.entries {
+            if entry.table != current_table {
+                let _ = writeln!(out, "table {}:", entry.table);
+                current_table = &entry.table;
+            }
+            let sign = entry.kind.sign();
+            let object = entry.object_type.label();
+            let kind = match entry.kind {
+                ForgeSchemaDiffKind::Missing => "missing in target",
+                ForgeSchemaDiffKind::Changed => "changed",
+                ForgeSchemaDiffKind::Extra => "only in target",
+            };
+            let _ = writeln!(out, "  {sign} {object} {} ({kind})", entry.name);
+            for detail in &entry.details {
+                let _ = writeln!(out, "      {detail}");
+            }
+        }
+
+        let _ = writeln!(out, "\n{}", self.counts_text());
+        out
+    }
+
+    /// Concise drift summary for CI logs: one headline and one line per differing object
+    #[must_use]
+    pub fn summary(&self) -> String {
+        use std::fmt::Write as _;
+
+        if self.is_empty() {
+            return "No schema drift.\n".to_string();
+        }
+
+        let tables: HashSet<&str> = self.entries.iter().map(|e| e.table.as_str()).collect();
+        let mut out = format!(
+            "Schema drift in {} table(s): {}\n",
+            tables.len(),
+            self.counts_text()
+        );
+        for entry in &self.entries {
+            let name = match entry.object_type {
+                ForgeSchemaObjectType::Table => entry.table.clone(),
+                _ => format!("{}.{}", entry.table, entry.name),
+            };
+            let _ = writeln!(
+                out,
+                "  {} {} {name}",
+                entry.kind.sign(),
+                entry.object_type.label()
+            );
+        }
+        out
+    }
+
+    fn counts_text(&self) -> String {
+        format!(
+            "{} missing, {} changed, {} extra",
+            self.count(ForgeSchemaDiffKind::Missing),
+            self.count(ForgeSchemaDiffKind::Changed),
+            self.count(ForgeSchemaDiffKind::Extra)
+        )
+    }
+}
+
+// --- schema conversion ---
+
+/// SQL dialect of a target database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeDialect {
+    Mysql,
+    Postgres,
+}
+
+impl ForgeDialect {
+    /// Name of the config section of the dialect (`[mysql]`, `[postgres]`)
+    #[must_use]
+    pub fn config_section(self) -> &'static str {
+        match self {
+            ForgeDialect::Mysql => "mysql",
+            ForgeDialect::Postgres => "postgres",
+        }
+    }
+
+    /// Type of the dialect for a type name only the other dialect knows, i.e. the MySQL type
+    /// of a column of a PostgreSQL source.
+    #[must_use]
+    pub fn native_type(self, data_type: &str) -> Option<&'static str> {
+        match self {
+            ForgeDialect::Mysql => match data_type {
+                "character varying" => Some("varchar"),
+                "character" | "bpchar" => Some("char"),
+                "bytea" => Some("longblob"),
+                "jsonb" => Some("json"),
+                "uuid" => Some("char(36)"),
+                // longest IPv6 address with netmask
+                "inet" | "cidr" => Some("varchar(43)"),
+                "macaddr" => Some("varchar(17)"),
+                "boolean" | "bool" => Some("tinyint(1)"),
+                "smallserial" | "int2" => Some("smallint"),
+                "serial" | "int4" => Some("integer"),
+                "bigserial" | "int8" => Some("bigint"),
+                "real" | "float4" => Some("float"),
+                "double precision" | "float8" => Some("double"),
+                // PostgreSQL keeps microseconds, DATETIME covers its range without a time zone
+                "timestamp without time zone" | "timestamp with time zone" | "timestamptz" => {
+                    Some("datetime(6)")
+                }
+                "time without time zone" | "time with time zone" | "timetz" => Some("time(6)"),
+                // the internal type of a MySQL TIMESTAMP
+                "datetimetz" => Some("timestamp"),
+                "xml" | "citext" => Some("longtext"),
+                array if array.ends_with("[]") => Some("json"),
+                _ => None,
+            },
+            ForgeDialect::Postgres => None,
+        }
+    }
 }
 
-impl ForgeSchemaTable {
-    /// Creates a new table with the given name.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use fluxforge::core::ForgeSchemaTable;
-    ///
-    /// let table = ForgeSchemaTable::new("products");
-    /// assert_eq!(table.name, "products");
-    /// assert_eq!(table.columns.len(), 0);
-    /// ```
-    #[must_use]
-    pub fn new(name: &str) -> Self {
-        Self {
-            name: name.to_string(),
-            ..Default::default()
+impl std::str::FromStr for ForgeDialect {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.trim().to_lowercase().as_str() {
+            "mysql" | "mariadb" => Ok(ForgeDialect::Mysql),
+            "postgres" | "postgresql" | "pg" => Ok(ForgeDialect::Postgres),
+            other => Err(format!(
+                "unknown dialect '{other}', expected mysql or postgres"
+            )),
         }
     }
 }
 
-/// Represents a table column with all its properties.
+impl std::fmt::Display for ForgeDialect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.config_section())
+    }
+}
+
+/// applies a type like `numeric(18,4)` or `varchar(100)` to a column
+fn apply_type_spec(column: &mut ForgeSchemaColumn, spec: &str) {
+    let spec = spec.trim().to_lowercase();
+    // an explicit type replaces the domain
+    column.domain = None;
+    let Some((base, params)) = spec.split_once('(') else {
+        column.data_type = spec;
+        return;
+    };
+    column.data_type = base.trim().to_string();
+    let params: Vec<u32> = params
+        .trim_end_matches(')')
+        .split(',')
+        .filter_map(|p| p.trim().parse().ok())
+        .collect();
+    match (column.data_type.as_str(), params.as_slice()) {
+        ("numeric" | "decimal" | "float", [p, rest @ ..]) => {
+            column.precision = Some(*p);
+            column.scale = rest.first().copied();
+            column.length = None;
+        }
+        (_, [l, ..]) => column.length = Some(*l),
+        _ => {}
+    }
+}
+
+/// Applies `[tables.column_overrides]` to the columns of a source schema (in place).
+///
+/// The target tables are created with the overridden types, nullability and defaults,
+/// the `on_write` type mappings of the target still apply to the overridden types.
+pub fn apply_column_overrides(schema: &mut ForgeSchema, config: &ForgeConfig) {
+    for table in &mut schema.tables {
+        for column in &mut table.columns {
+            if let Some(column_override) = config.get_column_override(&table.name, &column.name) {
+                column_override.apply(column);
+            }
+        }
+    }
+}
+
+/// Converts a schema into the schema the target dialect is written with, without any database
+/// connection: table renames, column type overrides, the `on_write` type mappings of the target
+/// and the limits of the target (PostgreSQL has no unsigned integers).
 ///
 /// # Examples
 ///
 /// ```
-/// use fluxforge::core::ForgeSchemaColumn;
+/// use fluxforge::core::{convert_schema, ForgeConfig, ForgeDialect, ForgeSchema, ForgeSchemaColumn, ForgeSchemaTable};
 ///
-/// let mut col = ForgeSchemaColumn::new("id", "integer");
-/// col.is_primary_key = true;
-/// col.auto_increment = true;
+/// let config: ForgeConfig = toml::from_str(r#"
+///     [postgres.types.on_write]
+///     "datetime" = "timestamp"
+///
+///     [tables.renames]
+///     tbl_users = "users"
+/// "#).unwrap();
+///
+/// let mut table = ForgeSchemaTable::new("tbl_users");
+/// table.columns.push(ForgeSchemaColumn::new("created", "datetime"));
+/// let schema = ForgeSchema { tables: vec![table], ..ForgeSchema::default() };
+///
+/// let converted = convert_schema(&schema, &config, ForgeDialect::Postgres);
+/// assert_eq!(converted.tables[0].name, "users");
+/// assert_eq!(converted.tables[0].columns[0].data_type, "timestamp");
 /// ```
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
-pub struct ForgeSchemaColumn {
-    /// Column name
-    pub name: String,
-    /// Data type (mapped according to configuration)
-    pub data_type: String,
-    /// Length for character types (VARCHAR, CHAR)
-    pub length: Option<u32>,
-    /// Precision for numeric types (DECIMAL, NUMERIC)
-    pub precision: Option<u32>,
-    /// Scale for numeric types (DECIMAL, NUMERIC)
-    pub scale: Option<u32>,
-    /// Whether the column allows NULL values
-    pub is_nullable: bool,
-    /// Whether this column is part of the primary key
-    pub is_primary_key: bool,
-    /// Whether this is an unsigned integer (MySQL)
-    pub is_unsigned: bool,
-    /// Whether this column auto-increments
-    pub auto_increment: bool,
-    /// Default value expression
-    pub default: Option<String>,
-    /// Column comment
-    pub comment: Option<String>,
-    /// ON UPDATE expression (e.g., CURRENT_TIMESTAMP)
-    pub on_update: Option<String>,
-    /// Enum/Set values for ENUM and SET types
-    pub enum_values: Option<Vec<String>>,
+#[must_use]
+pub fn convert_schema(
+    schema: &ForgeSchema,
+    config: &ForgeConfig,
+    target_dialect: ForgeDialect,
+) -> ForgeSchema {
+    let mut converted = schema.clone();
+    for table in &mut converted.tables {
+        for column in &mut table.columns {
+            let column_override = config.get_column_override(&table.name, &column.name);
+            if let Some(column_override) = column_override {
+                column_override.apply(column);
+            }
+            if column_override
+                .and_then(ForgeColumnOverride::data_type)
+                .is_none()
+            {
+                column.data_type = config.map_write_type(target_dialect, &column.data_type);
+            }
+            if target_dialect == ForgeDialect::Postgres {
+                column.is_unsigned = false;
+            }
+        }
+    }
+    apply_column_renames(&mut converted, config);
+    apply_table_renames(&mut converted, config);
+    converted
 }
 
-impl ForgeSchemaColumn {
-    /// Creates a new column with the given name and data type.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use fluxforge::core::ForgeSchemaColumn;
-    ///
-    /// let col = ForgeSchemaColumn::new("email", "varchar");
-    /// assert_eq!(col.name, "email");
-    /// assert_eq!(col.data_type, "varchar");
-    /// ```
-    #[must_use]
-    pub fn new(name: &str, data_type: &str) -> Self {
-        Self {
-            name: name.to_string(),
-            data_type: data_type.to_string(),
-            ..Default::default()
+/// Applies `[tables.column_renames]` to a source schema (in place), before the tables are renamed.
+///
+/// Index columns and the columns of foreign keys, on both ends, get the target names.
+pub fn apply_column_renames(schema: &mut ForgeSchema, config: &ForgeConfig) {
+    for table in &mut schema.tables {
+        let table_name = table.name.clone();
+        let rename = |column: &mut String| {
+            if let Some(renamed) = config.get_column_rename(&table_name, column) {
+                *column = renamed.to_string();
+            }
+        };
+        for column in &mut table.columns {
+            rename(&mut column.name);
+        }
+        for index in &mut table.indices {
+            index.columns.iter_mut().for_each(rename);
+            for expression in index.expressions.iter_mut().flatten().flatten() {
+                *expression = rename_quoted_identifiers(expression, |column| {
+                    config.get_column_rename(&table_name, column)
+                });
+            }
+        }
+        for fk in &mut table.foreign_keys {
+            rename(&mut fk.column);
+            if let Some(renamed) = config.get_column_rename(&fk.ref_table, &fk.ref_column) {
+                fk.ref_column = renamed.to_string();
+            }
         }
     }
 }
 
-/// Represents a database index.
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
-pub struct ForgeSchemaIndex {
-    /// Index name
-    pub name: String,
-    /// Columns included in the index
-    pub columns: Vec<String>,
-    /// Whether this is a unique index
-    pub is_unique: bool,
-    /// Index type (e.g., "BTREE", "FULLTEXT")
-    pub index_type: Option<String>,
-    /// Prefix lengths for indexed columns (MySQL)
-    pub column_prefixes: Option<Vec<Option<u32>>>,
+/// renames the backtick quoted identifiers of a MySQL expression
+fn rename_quoted_identifiers<'a>(
+    expression: &str,
+    rename: impl Fn(&str) -> Option<&'a str>,
+) -> String {
+    let mut out = String::with_capacity(expression.len());
+    // odd parts are the quoted identifiers
+    for (i, part) in expression.split('`').enumerate() {
+        if i > 0 {
+            out.push('`');
+        }
+        if i % 2 == 1 {
+            out.push_str(rename(part).unwrap_or(part));
+        } else {
+            out.push_str(part);
+        }
+    }
+    out
 }
 
-/// Represents a foreign key constraint.
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
-pub struct ForgeSchemaForeignKey {
-    /// Constraint name
-    pub name: String,
-    /// Column in this table
-    pub column: String,
-    /// Referenced table name
-    pub ref_table: String,
-    /// Referenced column name
-    pub ref_column: String,
-    /// ON DELETE action (CASCADE, SET NULL, etc.)
-    pub on_delete: Option<String>,
-    /// ON UPDATE action (CASCADE, SET NULL, etc.)
-    pub on_update: Option<String>,
+/// Replaces the table name in an object name where it stands between `_` separators or at the
+/// start or end, `idx_username` of the table `user` keeps its name.
+fn rename_table_in_name(name: &str, table: &str, renamed: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut last = 0;
+    for (pos, _) in name.match_indices(table) {
+        let end = pos + table.len();
+        let starts = pos == 0 || name[..pos].ends_with('_');
+        let ends = end == name.len() || name[end..].starts_with('_');
+        if starts && ends {
+            out.push_str(&name[last..pos]);
+            out.push_str(renamed);
+            last = end;
+        }
+    }
+    out.push_str(&name[last..]);
+    out
+}
+
+/// Applies `[tables.renames]` to a source schema (in place).
+///
+/// Foreign keys reference the renamed tables, index and foreign key names containing the source
+/// table name as a `_`-separated part get the target name (`idx_tbl_users_email` becomes
+/// `idx_users_email`).
+pub fn apply_table_renames(schema: &mut ForgeSchema, config: &ForgeConfig) {
+    for table in &mut schema.tables {
+        for fk in &mut table.foreign_keys {
+            if let Some(renamed) = config.get_table_rename(&fk.ref_table) {
+                fk.ref_table = renamed.to_string();
+            }
+        }
+        let Some(renamed) = config.get_table_rename(&table.name) else {
+            continue;
+        };
+        for index in &mut table.indices {
+            index.name = rename_table_in_name(&index.name, &table.name, renamed);
+        }
+        for fk in &mut table.foreign_keys {
+            fk.name = rename_table_in_name(&fk.name, &table.name, renamed);
+        }
+        for partition in table
+            .partitioning
+            .iter_mut()
+            .flat_map(|p| &mut p.partitions)
+        {
+            partition.name = rename_table_in_name(&partition.name, &table.name, renamed);
+        }
+        table.name = renamed.to_string();
+    }
+}
+
+/// Removes the partitioning of all tables if `[general] flatten_partitions` is set (in place).
+pub fn flatten_partitions(schema: &mut ForgeSchema, config: &ForgeConfig) {
+    if config.is_flatten_partitions() {
+        for table in &mut schema.tables {
+            table.partitioning = None;
+        }
+    }
 }
 
 // --- UNIVERSAL-Intermediate data types ---
@@ -376,12 +3248,89 @@ pub enum ForgeUniversalDataField {
     Uuid(sqlx::types::Uuid),
     /// IP network address (PostgreSQL INET/CIDR)
     Inet(sqlx::types::ipnetwork::IpNetwork),
+    /// PostgreSQL array
+    Array(ForgeArray),
     /// NULL value
     Null,
     /// MySQL zero datetime (0000-00-00 00:00:00)
     ZeroDateTime,
 }
 
+impl std::fmt::Display for ForgeUniversalDataField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Integer(v) => write!(f, "{v}"),
+            Self::UnsignedInteger(v) => write!(f, "{v}"),
+            Self::Float(v) => write!(f, "{v}"),
+            Self::Text(v) => write!(f, "{v}"),
+            Self::Binary(v) => {
+                write!(f, "0x")?;
+                v.iter().try_for_each(|b| write!(f, "{b:02x}"))
+            }
+            Self::Boolean(v) => write!(f, "{v}"),
+            Self::Year(v) => write!(f, "{v}"),
+            Self::Time(v) => write!(f, "{v}"),
+            Self::Date(v) => write!(f, "{v}"),
+            Self::DateTime(v) => write!(f, "{v}"),
+            Self::Decimal(v) => write!(f, "{v}"),
+            Self::Json(v) => write!(f, "{v}"),
+            Self::Uuid(v) => write!(f, "{v}"),
+            Self::Inet(v) => write!(f, "{v}"),
+            Self::Array(v) => write!(f, "{}", v.to_json()),
+            Self::Null => write!(f, "NULL"),
+            Self::ZeroDateTime => write!(f, "0000-00-00 00:00:00"),
+        }
+    }
+}
+
+/// Elements of a one-dimensional PostgreSQL array.
+///
+/// Arrays stay typed between PostgreSQL databases, MySQL has no arrays and stores them as
+/// JSON array (see [`ForgeArray::to_json`]).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ForgeArray {
+    SmallInt(Vec<Option<i16>>),
+    Integer(Vec<Option<i32>>),
+    BigInt(Vec<Option<i64>>),
+    Real(Vec<Option<f32>>),
+    Double(Vec<Option<f64>>),
+    Text(Vec<Option<String>>),
+    Boolean(Vec<Option<bool>>),
+}
+
+impl ForgeArray {
+    /// JSON array of the elements, NULL elements become `null`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fluxforge::core::ForgeArray;
+    ///
+    /// let array = ForgeArray::Integer(vec![Some(1), None, Some(3)]);
+    /// assert_eq!(array.to_json().to_string(), "[1,null,3]");
+    /// ```
+    #[must_use]
+    pub fn to_json(&self) -> serde_json::Value {
+        fn elements<T: Into<serde_json::Value> + Clone>(values: &[Option<T>]) -> serde_json::Value {
+            serde_json::Value::Array(
+                values
+                    .iter()
+                    .map(|v| v.clone().map_or(serde_json::Value::Null, Into::into))
+                    .collect(),
+            )
+        }
+        match self {
+            Self::SmallInt(v) => elements(v),
+            Self::Integer(v) => elements(v),
+            Self::BigInt(v) => elements(v),
+            Self::Real(v) => elements(v),
+            Self::Double(v) => elements(v),
+            Self::Text(v) => elements(v),
+            Self::Boolean(v) => elements(v),
+        }
+    }
+}
+
 /// Represents a Database row with Universal Data columns
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct ForgeUniversalDataRow {
@@ -447,9 +3396,28 @@ pub enum ForgeError {
     /// Indicates an unexpected internal state that should not occur during normal operation.
     #[error("General Internal Error: {0}")]
     Internal(String),
+
+    /// The operation was cancelled by its [`CancellationToken`].
+    ///
+    /// Written chunks and applied statements stay in the target, the checkpoint records the
+    /// completed tables.
+    #[error("Operation cancelled")]
+    Cancelled,
+}
+
+impl ForgeError {
+    /// true if `error` is a [`ForgeError::Cancelled`]
+    #[must_use]
+    pub fn is_cancelled(error: &(dyn std::error::Error + 'static)) -> bool {
+        matches!(
+            error.downcast_ref::<ForgeError>(),
+            Some(ForgeError::Cancelled)
+        )
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
 mod tests {
     use super::*;
     use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
@@ -457,6 +3425,32 @@ mod tests {
     use serde_json::json;
     use std::str::FromStr;
 
+    #[test]
+    fn test_source_position_display_and_old_schema_files() {
+        let mysql = ForgeSourcePosition {
+            gtid_set: Some("3E11FA47-71CA-11E1-9E33-C80AA9429562:1-5".to_string()),
+            binlog_file: Some("binlog.000003".to_string()),
+            binlog_position: Some(157),
+            lsn: None,
+        };
+        assert_eq!(
+            mysql.to_string(),
+            "gtid 3E11FA47-71CA-11E1-9E33-C80AA9429562:1-5, binlog binlog.000003:157"
+        );
+        assert_eq!(ForgeSourcePosition::default().to_string(), "unknown");
+
+        // schema files written before the position was recorded
+        let metadata: ForgeSchemaMetadata = serde_json::from_value(json!({
+            "source_system": "postgres",
+            "source_database_name": "shop",
+            "created_at": "2024-01-01T00:00:00+00:00",
+            "forge_version": "0.1.0",
+            "config_file": ""
+        }))
+        .unwrap();
+        assert_eq!(metadata.source_position, None);
+    }
+
     #[test]
     fn test_forge_universal_data_transfer_packet_serialization() {
         let mut row = IndexMap::new();
@@ -545,4 +3539,447 @@ mod tests {
 
         assert_eq!(packet, deserialized);
     }
+
+    fn diff_test_schema() -> ForgeSchema {
+        let mut users = ForgeSchemaTable::new("users");
+        let mut id = ForgeSchemaColumn::new("id", "int");
+        id.is_primary_key = true;
+        users.columns.push(id);
+        let mut email = ForgeSchemaColumn::new("email", "varchar");
+        email.length = Some(100);
+        users.columns.push(email);
+        users.indices.push(ForgeSchemaIndex {
+            name: "uq_email".to_string(),
+            columns: vec!["email".to_string()],
+            is_unique: true,
+            index_type: None,
+            column_prefixes: None,
+            expressions: None,
+        });
+
+        let mut schema = ForgeSchema::new();
+        schema.tables.push(users);
+        schema.tables.push(ForgeSchemaTable::new("orders"));
+        schema
+    }
+
+    #[test]
+    fn time_zone_parse_and_convert() {
+        let berlin: ForgeTimeZone = "+02:00".parse().unwrap();
+        let new_york: ForgeTimeZone = "-04:00".parse().unwrap();
+        assert_eq!(berlin.offset_seconds, 7200);
+        assert_eq!(new_york.to_string(), "-04:00");
+        assert_eq!(
+            "z".parse::<ForgeTimeZone>(),
+            Err("invalid time zone 'z', expected UTC or an offset like +02:00".to_string())
+        );
+        assert!("Europe/Berlin".parse::<ForgeTimeZone>().is_err());
+        assert!("+15:00".parse::<ForgeTimeZone>().is_err());
+
+        let noon = NaiveDate::from_ymd_opt(2026, 3, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        assert_eq!(
+            berlin.convert(noon, new_york).to_string(),
+            "2026-03-01 06:00:00"
+        );
+
+        let config: ForgeConfig =
+            toml::from_str("[general]\nsource_time_zone = \"+02:00\"\ntarget_time_zone = \"UTC\"")
+                .unwrap();
+        assert_eq!(config.get_time_zone(true), Some(berlin));
+        assert_eq!(config.get_time_zone(false), Some(ForgeTimeZone::default()));
+        assert!(toml::from_str::<ForgeConfig>("[general]\nsource_time_zone = \"CET\"").is_err());
+    }
+
+    #[test]
+    fn verify_sample_parse() {
+        let sample: ForgeVerifySample = "1%".parse().unwrap();
+        assert_eq!(sample.basis_points, 100);
+        assert_eq!("0.01".parse::<ForgeVerifySample>().unwrap().basis_points, 1);
+        assert_eq!(
+            "100 %".parse::<ForgeVerifySample>().unwrap().basis_points,
+            10_000
+        );
+        assert!("0%".parse::<ForgeVerifySample>().is_err());
+        assert!("150%".parse::<ForgeVerifySample>().is_err());
+        assert!("abc".parse::<ForgeVerifySample>().is_err());
+
+        let config: ForgeConfig = toml::from_str("[general]\nverify_sample = \"2.5%\"").unwrap();
+        assert_eq!(
+            config.get_verify_sample(),
+            Some(ForgeVerifySample {
+                basis_points: 250,
+                seed: 0
+            })
+        );
+
+        let config: ForgeConfig =
+            toml::from_str("[general]\nverify_sample = \"1%\"\nseed = 7").unwrap();
+        assert_eq!(config.get_verify_sample().map(|s| s.seed), Some(7));
+    }
+
+    #[test]
+    fn object_filter_parse() {
+        let filter: ForgeObjectFilter = "ddl: users, orders,".parse().unwrap();
+        assert_eq!(
+            filter.tables,
+            Some(HashSet::from(["users".to_string(), "orders".to_string()]))
+        );
+        assert!(filter.includes_table("orders"));
+        assert!(!filter.includes_table("audit"));
+
+        assert!("users,orders".parse::<ForgeObjectFilter>().is_err());
+        assert!("data:users".parse::<ForgeObjectFilter>().is_err());
+        assert!("ddl:".parse::<ForgeObjectFilter>().is_err());
+    }
+
+    #[test]
+    fn schema_diff_identical() {
+        let diff = ForgeSchemaDiff::compare(&diff_test_schema(), &diff_test_schema());
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_text(), "Schemas are identical.\n");
+        assert_eq!(diff.summary(), "No schema drift.\n");
+    }
+
+    #[test]
+    fn schema_diff_missing_changed_extra() {
+        let source = diff_test_schema();
+        let mut target = diff_test_schema();
+
+        // orders missing, audit extra
+        target.tables.retain(|t| t.name != "orders");
+        target.tables.push(ForgeSchemaTable::new("audit"));
+        // email changed, id missing, extra column
+        let users = &mut target.tables[0];
+        users.columns.retain(|c| c.name != "id");
+        users.columns[0].length = Some(255);
+        users.columns[0].data_type = "VARCHAR".to_string();
+        users.columns.push(ForgeSchemaColumn::new("legacy", "text"));
+        users.indices[0].is_unique = false;
+
+        let diff = ForgeSchemaDiff::compare(&source, &target);
+        let find = |name: &str| diff.entries.iter().find(|e| e.name == name).unwrap();
+
+        assert_eq!(find("id").kind, ForgeSchemaDiffKind::Missing);
+        assert_eq!(find("email").kind, ForgeSchemaDiffKind::Changed);
+        // data type comparison is case insensitive
+        assert_eq!(
+            find("email").details,
+            vec!["length: Some(100) -> Some(255)"]
+        );
+        assert_eq!(find("legacy").kind, ForgeSchemaDiffKind::Extra);
+        assert_eq!(find("uq_email").object_type, ForgeSchemaObjectType::Index);
+        assert_eq!(find("orders").kind, ForgeSchemaDiffKind::Missing);
+        assert_eq!(find("audit").kind, ForgeSchemaDiffKind::Extra);
+
+        let text = diff.to_text();
+        assert!(text.contains("table users:\n"));
+        assert!(text.contains("  - column id (missing in target)\n"));
+        assert!(text.contains("2 missing, 2 changed, 2 extra"));
+
+        // sorted by table, object type and name
+        let order: Vec<(&str, &str)> = diff
+            .entries
+            .iter()
+            .map(|e| (e.table.as_str(), e.name.as_str()))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                ("audit", "audit"),
+                ("orders", "orders"),
+                ("users", "email"),
+                ("users", "id"),
+                ("users", "legacy"),
+                ("users", "uq_email"),
+            ]
+        );
+
+        assert_eq!(
+            diff.summary(),
+            "Schema drift in 3 table(s): 2 missing, 2 changed, 2 extra\n\
+             \x20 + table audit\n\
+             \x20 - table orders\n\
+             \x20 ~ column users.email\n\
+             \x20 - column users.id\n\
+             \x20 + column users.legacy\n\
+             \x20 ~ index users.uq_email\n"
+        );
+
+        let json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(json["entries"][0]["kind"], "extra");
+    }
+
+    #[test]
+    fn convert_schema_applies_overrides_renames_and_target_limits() {
+        let config: ForgeConfig = toml::from_str(
+            r#"
+            [mysql.types.on_write]
+            "integer" = "int"
+
+            [tables.renames]
+            tbl_users = "users"
+
+            [tables.column_overrides.tbl_orders]
+            total = "numeric(18,4)"
+            code = { type = "VARCHAR(12)", nullable = false, default = "'n/a'" }
+            "#,
+        )
+        .unwrap();
+
+        let mut users = ForgeSchemaTable::new("tbl_users");
+        let mut id = ForgeSchemaColumn::new("id", "integer");
+        id.is_unsigned = true;
+        users.columns.push(id);
+
+        let mut orders = ForgeSchemaTable::new("tbl_orders");
+        orders
+            .columns
+            .push(ForgeSchemaColumn::new("total", "float"));
+        orders.columns.push(ForgeSchemaColumn::new("code", "text"));
+        orders.foreign_keys.push(ForgeSchemaForeignKey {
+            name: "fk_user".to_string(),
+            column: "user_id".to_string(),
+            ref_table: "tbl_users".to_string(),
+            ref_column: "id".to_string(),
+            ..ForgeSchemaForeignKey::default()
+        });
+
+        let schema = ForgeSchema {
+            tables: vec![users, orders],
+            ..ForgeSchema::default()
+        };
+
+        let mysql = convert_schema(&schema, &config, ForgeDialect::Mysql);
+        assert_eq!(mysql.tables[0].name, "users");
+        assert_eq!(mysql.tables[0].columns[0].data_type, "int");
+        assert!(mysql.tables[0].columns[0].is_unsigned);
+        assert_eq!(mysql.tables[1].name, "tbl_orders");
+        assert_eq!(mysql.tables[1].foreign_keys[0].ref_table, "users");
+
+        let total = &mysql.tables[1].columns[0];
+        assert_eq!(
+            (total.data_type.as_str(), total.precision, total.scale),
+            ("numeric", Some(18), Some(4))
+        );
+        let code = &mysql.tables[1].columns[1];
+        assert_eq!(
+            (code.data_type.as_str(), code.length),
+            ("varchar", Some(12))
+        );
+        assert!(!code.is_nullable);
+        assert_eq!(code.default.as_deref(), Some("'n/a'"));
+
+        let postgres = convert_schema(&schema, &config, ForgeDialect::Postgres);
+        assert_eq!(postgres.tables[0].columns[0].data_type, "integer");
+        assert!(!postgres.tables[0].columns[0].is_unsigned);
+        // the source schema is unchanged
+        assert_eq!(schema.tables[0].name, "tbl_users");
+    }
+
+    #[test]
+    fn table_renames_rewrite_references_and_names() {
+        let config: ForgeConfig =
+            toml::from_str("[tables.renames]\ntbl_users = \"users\"").unwrap();
+        let mut users = ForgeSchemaTable::new("tbl_users");
+        users.indices.push(ForgeSchemaIndex {
+            name: "idx_tbl_users_email".to_string(),
+            columns: vec!["email".to_string()],
+            ..ForgeSchemaIndex::default()
+        });
+        let mut orders = ForgeSchemaTable::new("orders");
+        orders.foreign_keys.push(ForgeSchemaForeignKey {
+            name: "fk_orders_tbl_users".to_string(),
+            column: "user_id".to_string(),
+            ref_table: "tbl_users".to_string(),
+            ref_column: "id".to_string(),
+            ..ForgeSchemaForeignKey::default()
+        });
+        let mut schema = ForgeSchema {
+            tables: vec![users, orders],
+            ..ForgeSchema::default()
+        };
+
+        apply_table_renames(&mut schema, &config);
+        assert_eq!(schema.tables[0].name, "users");
+        assert_eq!(schema.tables[0].indices[0].name, "idx_users_email");
+        // the foreign key of another table keeps its name but references the new table
+        let fk = &schema.tables[1].foreign_keys[0];
+        assert_eq!(
+            (fk.name.as_str(), fk.ref_table.as_str()),
+            ("fk_orders_tbl_users", "users")
+        );
+        assert_eq!(config.get_target_table_name("orders"), "orders");
+    }
+
+    #[test]
+    fn table_renames_replace_whole_name_parts_only() {
+        let config: ForgeConfig = toml::from_str("[tables.renames]\nuser = \"account\"").unwrap();
+        let mut user = ForgeSchemaTable::new("user");
+        for name in [
+            "idx_username",
+            "idx_user_email",
+            "user_idx",
+            "idx_user",
+            "superuser_idx",
+        ] {
+            user.indices.push(ForgeSchemaIndex {
+                name: name.to_string(),
+                ..ForgeSchemaIndex::default()
+            });
+        }
+        user.foreign_keys.push(ForgeSchemaForeignKey {
+            name: "fk_user_users_group".to_string(),
+            ..ForgeSchemaForeignKey::default()
+        });
+        let mut schema = ForgeSchema {
+            tables: vec![user],
+            ..ForgeSchema::default()
+        };
+
+        apply_table_renames(&mut schema, &config);
+        let names: Vec<&str> = schema.tables[0]
+            .indices
+            .iter()
+            .map(|i| i.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "idx_username",
+                "idx_account_email",
+                "account_idx",
+                "idx_account",
+                "superuser_idx"
+            ]
+        );
+        assert_eq!(
+            schema.tables[0].foreign_keys[0].name,
+            "fk_account_users_group"
+        );
+    }
+
+    #[test]
+    fn flatten_partitions_removes_the_partitioning() {
+        let mut table = ForgeSchemaTable::new("events");
+        table.partitioning = Some(ForgeSchemaPartitioning {
+            strategy: "range".to_string(),
+            key: "created".to_string(),
+            partitions: Vec::new(),
+        });
+        let mut schema = ForgeSchema {
+            tables: vec![table],
+            ..ForgeSchema::default()
+        };
+
+        flatten_partitions(&mut schema, &ForgeConfig::default());
+        assert!(schema.tables[0].partitioning.is_some());
+
+        let config: ForgeConfig = toml::from_str("[general]\nflatten_partitions = true").unwrap();
+        flatten_partitions(&mut schema, &config);
+        assert!(schema.tables[0].partitioning.is_none());
+    }
+
+    #[test]
+    fn column_renames_rewrite_indices_and_foreign_keys() {
+        let config: ForgeConfig = toml::from_str(
+            "[tables.column_renames.users]\nUserID = \"id\"\ne_mail = \"email\"\n\
+             [tables.column_renames.orders]\nfk_user = \"user_id\"",
+        )
+        .unwrap();
+        let mut users = ForgeSchemaTable::new("users");
+        users.columns.push(ForgeSchemaColumn::new("UserID", "int"));
+        users
+            .columns
+            .push(ForgeSchemaColumn::new("e_mail", "varchar"));
+        users.indices.push(ForgeSchemaIndex {
+            name: "idx_mail".to_string(),
+            columns: vec!["e_mail".to_string()],
+            ..ForgeSchemaIndex::default()
+        });
+        users.indices.push(ForgeSchemaIndex {
+            name: "idx_domain".to_string(),
+            columns: vec![String::new()],
+            expressions: Some(vec![Some(
+                "substring_index(`e_mail`,_utf8mb4'@',-1)".to_string(),
+            )]),
+            ..ForgeSchemaIndex::default()
+        });
+        let mut orders = ForgeSchemaTable::new("orders");
+        orders
+            .columns
+            .push(ForgeSchemaColumn::new("fk_user", "int"));
+        orders.foreign_keys.push(ForgeSchemaForeignKey {
+            name: "fk_orders_users".to_string(),
+            column: "fk_user".to_string(),
+            ref_table: "users".to_string(),
+            ref_column: "UserID".to_string(),
+            ..ForgeSchemaForeignKey::default()
+        });
+        let mut schema = ForgeSchema {
+            tables: vec![users, orders],
+            ..ForgeSchema::default()
+        };
+
+        apply_column_renames(&mut schema, &config);
+        let names: Vec<&str> = schema.tables[0]
+            .columns
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["id", "email"]);
+        assert_eq!(schema.tables[0].indices[0].columns, vec!["email"]);
+        assert_eq!(
+            schema.tables[0].indices[1].expression(0),
+            Some("substring_index(`email`,_utf8mb4'@',-1)")
+        );
+        let fk = &schema.tables[1].foreign_keys[0];
+        assert_eq!(
+            (fk.column.as_str(), fk.ref_column.as_str()),
+            ("user_id", "id")
+        );
+        assert_eq!(config.get_target_column_name("orders", "total"), "total");
+    }
+
+    #[test]
+    fn column_overrides_apply_attributes_without_type() {
+        let config: ForgeConfig = toml::from_str(
+            r#"
+            [tables.column_overrides.users]
+            balance = "numeric(18,4)"
+            status = { nullable = true }
+            "#,
+        )
+        .unwrap();
+        let mut users = ForgeSchemaTable::new("users");
+        users
+            .columns
+            .push(ForgeSchemaColumn::new("balance", "double"));
+        users
+            .columns
+            .push(ForgeSchemaColumn::new("status", "varchar"));
+        let mut schema = ForgeSchema {
+            tables: vec![users],
+            ..ForgeSchema::default()
+        };
+
+        apply_column_overrides(&mut schema, &config);
+        let balance = &schema.tables[0].columns[0];
+        assert_eq!(
+            (balance.data_type.as_str(), balance.precision, balance.scale),
+            ("numeric", Some(18), Some(4))
+        );
+        let status = &schema.tables[0].columns[1];
+        assert_eq!(status.data_type, "varchar");
+        assert!(status.is_nullable);
+    }
+
+    #[test]
+    fn dialect_parse() {
+        assert_eq!("PostgreSQL".parse(), Ok(ForgeDialect::Postgres));
+        assert_eq!("mariadb".parse(), Ok(ForgeDialect::Mysql));
+        assert!("oracle".parse::<ForgeDialect>().is_err());
+    }
 }
@@ -0,0 +1,107 @@
+//! Keepalive of long-running connections (`[general.keepalive]`).
+//!
+//! A table copy streams for hours on one connection while the other pool connections
+//! sit idle, and firewalls silently drop connections without traffic. PostgreSQL sends TCP
+//! keepalive probes from the server side, MySQL gets session timeouts long enough for a
+//! stream paused by a full insert queue, and idle pool connections are closed (and reopened
+//! on demand) before a firewall drops them.
+
+use crate::ForgeConfig;
+use std::time::Duration;
+
+/// Resolved keepalive settings, unset options use the defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Keepalive {
+    pub idle: Duration,
+    pub interval: Duration,
+    pub count: u32,
+    pub pool_idle_timeout: Duration,
+    pub net_timeout: Duration,
+}
+
+impl Default for Keepalive {
+    fn default() -> Self {
+        Self {
+            idle: Duration::from_secs(60),
+            interval: Duration::from_secs(10),
+            count: 6,
+            pool_idle_timeout: Duration::from_secs(300),
+            net_timeout: Duration::from_secs(600),
+        }
+    }
+}
+
+impl Keepalive {
+    /// settings of `[general.keepalive]`
+    #[must_use]
+    pub fn from_config(config: &ForgeConfig) -> Self {
+        let defaults = Self::default();
+        let Some(keepalive) = config.general.as_ref().and_then(|g| g.keepalive.as_ref()) else {
+            return defaults;
+        };
+        Self {
+            idle: keepalive
+                .idle_secs
+                .map_or(defaults.idle, Duration::from_secs),
+            interval: keepalive
+                .interval_secs
+                .map_or(defaults.interval, Duration::from_secs),
+            count: keepalive.count.unwrap_or(defaults.count),
+            pool_idle_timeout: keepalive
+                .pool_idle_timeout_secs
+                .map_or(defaults.pool_idle_timeout, Duration::from_secs),
+            net_timeout: keepalive
+                .net_timeout_secs
+                .map_or(defaults.net_timeout, Duration::from_secs),
+        }
+    }
+
+    /// session settings of the TCP keepalive probes of PostgreSQL, one statement
+    #[must_use]
+    pub fn postgres_sql(&self) -> String {
+        format!(
+            "SELECT set_config('tcp_keepalives_idle', '{}', false), \
+             set_config('tcp_keepalives_interval', '{}', false), \
+             set_config('tcp_keepalives_count', '{}', false)",
+            self.idle.as_secs(),
+            self.interval.as_secs(),
+            self.count
+        )
+    }
+
+    /// session timeouts of MySQL for reads and writes of a stream
+    #[must_use]
+    pub fn mysql_sql(&self) -> String {
+        let secs = self.net_timeout.as_secs();
+        format!("SET SESSION net_read_timeout = {secs}, net_write_timeout = {secs}")
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_fall_back_to_the_defaults() {
+        let config: ForgeConfig =
+            toml::from_str("[general.keepalive]\nidle_secs = 30\nnet_timeout_secs = 3600").unwrap();
+        let keepalive = Keepalive::from_config(&config);
+        assert_eq!(keepalive.idle, Duration::from_secs(30));
+        assert_eq!(keepalive.count, 6);
+        assert_eq!(
+            keepalive.postgres_sql(),
+            "SELECT set_config('tcp_keepalives_idle', '30', false), \
+             set_config('tcp_keepalives_interval', '10', false), \
+             set_config('tcp_keepalives_count', '6', false)"
+        );
+        assert_eq!(
+            keepalive.mysql_sql(),
+            "SET SESSION net_read_timeout = 3600, net_write_timeout = 3600"
+        );
+        assert_eq!(
+            Keepalive::from_config(&ForgeConfig::default()),
+            Keepalive::default()
+        );
+    }
+}
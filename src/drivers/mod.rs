@@ -4,25 +4,650 @@
 //! for MySQL and PostgreSQL databases, along with a factory function for creating
 //! driver instances from connection URLs.
 
+pub mod credentials;
+pub mod keepalive;
 pub mod mysql;
+pub mod online_ddl;
 pub mod postgres;
+pub mod script;
+pub mod tunnel;
 
 pub use mysql::MySqlDriver;
 pub use postgres::PostgresDriver;
+pub use script::ScriptDriver;
 
-use crate::core::ForgeConfig;
-use crate::drivers::mysql::get_mysql_init_session_sql_mode;
 use crate::DatabaseDriver;
-use sqlx::mysql::{MySqlConnectOptions, MySqlPoolOptions};
-use sqlx::ConnectOptions;
-use sqlx::{MySqlPool, PgPool};
+use crate::ForgeError;
+use crate::core::{
+    ForgeConfig, ForgeDialect, ForgeObjectFilter, ForgeRollbackPlan, ForgeSchema,
+    ForgeSchemaColumn, ForgeSchemaTable, ForgeTableEstimate, ForgeUniversalDataField,
+};
+use crate::drivers::mysql::get_mysql_init_session_sql_mode;
+use crate::ops::metrics;
+use crate::ops::retry::{RetryPolicy, RetryableError};
+use futures::{Stream, StreamExt};
+use indexmap::IndexMap;
+use sqlx::mysql::MySqlConnectOptions;
+use sqlx::postgres::PgPoolOptions;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
+use std::ops::Range;
+use std::pin::Pin;
 use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use tracing::{debug, warn};
+
+/// Schema objects sorted by name.
+///
+/// Generated statements must not depend on the order the database returned the objects in,
+/// otherwise consecutive dry-runs of the same schema produce differently ordered plans.
+/// Column additions keep the source column order, because it defines the column positions.
+pub(crate) fn sorted_by_name<T>(items: &[T], name: impl Fn(&T) -> &String) -> Vec<&T> {
+    let mut sorted: Vec<&T> = items.iter().collect();
+    sorted.sort_by(|a, b| name(a).cmp(name(b)));
+    sorted
+}
+
+/// Table as it looks after `alter_table_migration_sql(source, target, destructive)`.
+///
+/// Without `destructive` the columns and indices only present in the target are kept,
+/// a rollback must not try to create them again.
+pub(crate) fn applied_table(
+    source: &ForgeSchemaTable,
+    target: &ForgeSchemaTable,
+    destructive: bool,
+) -> ForgeSchemaTable {
+    let mut applied = source.clone();
+    if !destructive {
+        for col in &target.columns {
+            if !source.columns.iter().any(|c| c.name == col.name) {
+                applied.columns.push(col.clone());
+            }
+        }
+        for idx in &target.indices {
+            if !source.indices.iter().any(|i| i.name == idx.name) {
+                applied.indices.push(idx.clone());
+            }
+        }
+        if let (Some(applied), Some(target)) = (&mut applied.partitioning, &target.partitioning) {
+            for partition in &target.partitions {
+                if !applied.partitions.iter().any(|p| p.name == partition.name) {
+                    applied.partitions.push(partition.clone());
+                }
+            }
+        }
+    }
+    applied
+}
+
+/// Foreign key constraint with all its columns, the schema holds an entry per column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ForeignKeyConstraint {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub ref_table: String,
+    pub ref_columns: Vec<String>,
+    /// `None` for the default `NO ACTION`
+    pub on_delete: Option<String>,
+    pub on_update: Option<String>,
+}
+
+/// referential action in upper case, `None` for the default
+fn referential_action(action: Option<&str>) -> Option<String> {
+    // MySQL reports its default as RESTRICT, which behaves like NO ACTION
+    let action = action?.trim().to_uppercase();
+    match action.as_str() {
+        "" | "NO ACTION" | "RESTRICT" => None,
+        _ => Some(action),
+    }
+}
+
+/// Foreign key constraints of a table, sorted by name.
+pub(crate) fn foreign_key_constraints(table: &ForgeSchemaTable) -> Vec<ForeignKeyConstraint> {
+    let mut constraints: Vec<ForeignKeyConstraint> = Vec::new();
+    for fk in &table.foreign_keys {
+        match constraints.iter_mut().find(|c| c.name == fk.name) {
+            Some(constraint) => {
+                if !constraint.columns.contains(&fk.column) {
+                    constraint.columns.push(fk.column.clone());
+                }
+                if !constraint.ref_columns.contains(&fk.ref_column) {
+                    constraint.ref_columns.push(fk.ref_column.clone());
+                }
+            }
+            None => constraints.push(ForeignKeyConstraint {
+                name: fk.name.clone(),
+                columns: vec![fk.column.clone()],
+                ref_table: fk.ref_table.clone(),
+                ref_columns: vec![fk.ref_column.clone()],
+                on_delete: referential_action(fk.on_delete.as_deref()),
+                on_update: referential_action(fk.on_update.as_deref()),
+            }),
+        }
+    }
+    constraints.sort_by(|a, b| a.name.cmp(&b.name));
+    constraints
+}
+
+/// Foreign keys to drop before the other schema changes and to add after them, per table.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ForeignKeyChanges {
+    /// the constraints as they are in the target
+    pub drop: Vec<(String, ForeignKeyConstraint)>,
+    pub add: Vec<(String, ForeignKeyConstraint)>,
+}
+
+impl ForeignKeyChanges {
+    /// Changes that bring the foreign keys of `target` in line with `source`.
+    ///
+    /// A changed foreign key is dropped and added again, one of the target only is dropped
+    /// with `destructive`. Foreign keys to tables in neither schema cannot be created.
+    pub(crate) fn between(
+        source: &ForgeSchema,
+        target: &ForgeSchema,
+        filter: &ForgeObjectFilter,
+        destructive: bool,
+    ) -> Self {
+        let mut changes = Self::default();
+        for source_table in &source.tables {
+            if !filter.includes_table(&source_table.name) {
+                continue;
+            }
+            let target_constraints = target
+                .tables
+                .iter()
+                .find(|t| t.name == source_table.name)
+                .map(foreign_key_constraints)
+                .unwrap_or_default();
+            let source_constraints = foreign_key_constraints(source_table);
+
+            for constraint in &source_constraints {
+                let existing = target_constraints
+                    .iter()
+                    .find(|c| c.name == constraint.name);
+                if existing == Some(constraint) {
+                    continue;
+                }
+                if !source.tables.iter().any(|t| t.name == constraint.ref_table)
+                    && !target.tables.iter().any(|t| t.name == constraint.ref_table)
+                {
+                    debug!(table = %source_table.name, foreign_key = %constraint.name, "referenced table missing, foreign key skipped");
+                    continue;
+                }
+                if let Some(existing) = existing {
+                    changes
+                        .drop
+                        .push((source_table.name.clone(), existing.clone()));
+                }
+                changes
+                    .add
+                    .push((source_table.name.clone(), constraint.clone()));
+            }
+            if destructive {
+                for constraint in &target_constraints {
+                    if !source_constraints.iter().any(|c| c.name == constraint.name) {
+                        changes
+                            .drop
+                            .push((source_table.name.clone(), constraint.clone()));
+                    }
+                }
+            }
+        }
+        changes
+    }
+
+    /// Changes that restore the foreign keys of `target` after these changes were applied to it.
+    pub(crate) fn reverted(&self, target: &ForgeSchema) -> Self {
+        Self {
+            // the foreign keys of created tables are dropped with their tables
+            drop: self
+                .add
+                .iter()
+                .filter(|(table, _)| target.tables.iter().any(|t| &t.name == table))
+                .cloned()
+                .collect(),
+            add: self.drop.clone(),
+        }
+    }
+}
+
+/// Descriptions of the target columns dropped by a destructive apply, their data is lost.
+pub(crate) fn dropped_columns(source: &ForgeSchemaTable, target: &ForgeSchemaTable) -> Vec<String> {
+    sorted_by_name(&target.columns, |c| &c.name)
+        .into_iter()
+        .filter(|col| !source.columns.iter().any(|c| c.name == col.name))
+        .map(|col| {
+            format!(
+                "column {}.{} is dropped, its data cannot be restored",
+                target.name, col.name
+            )
+        })
+        .collect()
+}
+
+/// Schema statements of a SQL driver, the building blocks of [`rollback_schema_plan`].
+pub(crate) trait SchemaStatements {
+    fn create_table(
+        &self,
+        table: &ForgeSchemaTable,
+        config: &ForgeConfig,
+    ) -> Result<Vec<String>, Box<dyn Error>>;
+    fn drop_table(&self, table: &ForgeSchemaTable) -> Result<Vec<String>, Box<dyn Error>>;
+    /// statements changing `target` into `source`
+    fn alter_table(
+        &self,
+        source: &ForgeSchemaTable,
+        target: &ForgeSchemaTable,
+        config: &ForgeConfig,
+        destructive: bool,
+    ) -> Result<Vec<String>, Box<dyn Error>>;
+    /// drops and adds of the foreign key changes
+    fn foreign_keys(&self, changes: &ForeignKeyChanges) -> (Vec<String>, Vec<String>);
+}
+
+/// Statements reverting the apply of `source_schema` to `target_schema`, the current target.
+///
+/// Foreign keys are dropped first and added last. Tables dropped by a destructive apply are
+/// recreated (without their data), then the tables are reverted in reverse dependency order.
+pub(crate) fn rollback_schema_plan(
+    statements: &dyn SchemaStatements,
+    source_schema: &ForgeSchema,
+    target_schema: &ForgeSchema,
+    config: &ForgeConfig,
+    filter: &ForgeObjectFilter,
+    destructive: bool,
+) -> Result<ForgeRollbackPlan, Box<dyn Error>> {
+    let mut plan = ForgeRollbackPlan::default();
+    let (drop_foreign_keys, add_foreign_keys) = statements.foreign_keys(
+        &ForeignKeyChanges::between(source_schema, target_schema, filter, destructive)
+            .reverted(target_schema),
+    );
+    plan.statements.extend(drop_foreign_keys);
+
+    let source_tables: HashSet<&str> = source_schema
+        .tables
+        .iter()
+        .map(|t| t.name.as_str())
+        .collect();
+
+    // dropped tables are recreated first, the apply dropped them last
+    if destructive {
+        for table in sorted_by_name(&target_schema.tables, |t| &t.name) {
+            if !source_tables.contains(table.name.as_str()) && filter.includes_table(&table.name) {
+                plan.statements
+                    .extend(statements.create_table(table, config)?);
+                plan.irreversible.push(format!(
+                    "table {} is dropped, its data cannot be restored",
+                    table.name
+                ));
+            }
+        }
+    }
+
+    // reverse dependency order: referencing tables are reverted before the referenced ones
+    for source_table in source_schema.tables.iter().rev() {
+        if !filter.includes_table(&source_table.name) {
+            continue;
+        }
+        match target_schema
+            .tables
+            .iter()
+            .find(|t| t.name == source_table.name)
+        {
+            Some(target_table) => {
+                // the applied state is the actual one, the current target the desired one
+                let applied = applied_table(source_table, target_table, destructive);
+                plan.statements.extend(statements.alter_table(
+                    target_table,
+                    &applied,
+                    config,
+                    true,
+                )?);
+                if destructive {
+                    plan.irreversible
+                        .extend(dropped_columns(source_table, target_table));
+                }
+            }
+            None => {
+                plan.statements.extend(statements.drop_table(source_table)?);
+            }
+        }
+    }
+    plan.statements.extend(add_foreign_keys);
+
+    Ok(plan)
+}
+
+/// Columns added NOT NULL with a default to a big existing table, backfilled in chunks of its
+/// integer primary key instead of one `ALTER TABLE` rewriting all rows (`[general.ddl] backfill_min_rows`).
+#[derive(Debug, Clone)]
+pub(crate) struct Backfill {
+    /// the added columns as they are finally, NOT NULL with their default
+    pub columns: Vec<ForgeSchemaColumn>,
+    pub key: String,
+    /// half-open key ranges `[start, end)` of the chunks
+    pub ranges: Vec<(i64, i64)>,
+}
+
+impl Backfill {
+    /// source table with the backfilled columns added nullable and without default
+    pub(crate) fn nullable_table(&self, source: &ForgeSchemaTable) -> ForgeSchemaTable {
+        let mut table = source.clone();
+        for column in &mut table.columns {
+            if self.columns.iter().any(|c| c.name == column.name) {
+                column.is_nullable = true;
+                column.default = None;
+            }
+        }
+        table
+    }
+}
+
+/// Added NOT NULL columns with a default of `source` that need a backfill of existing rows.
+pub(crate) fn backfill_columns(
+    source: &ForgeSchemaTable,
+    target: &ForgeSchemaTable,
+) -> Vec<ForgeSchemaColumn> {
+    source
+        .columns
+        .iter()
+        .filter(|c| {
+            !c.is_nullable
+                && c.default.is_some()
+                && !c.auto_increment
+                && c.generation_expression.is_none()
+                && !target.columns.iter().any(|t| t.name == c.name)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Half-open chunks `[start, end)` of `chunk_rows` keys covering `min..=max`.
+pub(crate) fn key_ranges(min: i64, max: i64, chunk_rows: u64) -> Vec<(i64, i64)> {
+    let step = i64::try_from(chunk_rows.max(1)).unwrap_or(i64::MAX);
+    let mut ranges = Vec::new();
+    let mut start = min;
+    while start <= max {
+        let end = start.saturating_add(step);
+        ranges.push((start, end));
+        if end == i64::MAX {
+            break;
+        }
+        start = end;
+    }
+    ranges
+}
+
+/// Backfill of the added NOT NULL columns of an existing table with at least `backfill_min_rows`
+/// estimated rows, `None` if the columns are added with a single `ALTER TABLE`. `quote` quotes
+/// identifiers of the dialect.
+///
+/// # Errors
+///
+/// Returns an error if the key range of the table cannot be read.
+pub(crate) async fn backfill_plan<D: DatabaseDriver + ?Sized>(
+    driver: &D,
+    source: &ForgeSchemaTable,
+    target: &ForgeSchemaTable,
+    estimates: &[ForgeTableEstimate],
+    config: &ForgeConfig,
+    quote: fn(&str) -> String,
+) -> Result<Option<Backfill>, Box<dyn Error>> {
+    let ddl = config.get_ddl();
+    let Some(min_rows) = ddl.backfill_min_rows else {
+        return Ok(None);
+    };
+    let columns = backfill_columns(source, target);
+    let rows = estimates
+        .iter()
+        .find(|e| e.table_name == target.name)
+        .map_or(0, |e| e.rows);
+    if columns.is_empty() || rows < min_rows {
+        return Ok(None);
+    }
+
+    let keys: Vec<&ForgeSchemaColumn> =
+        target.columns.iter().filter(|c| c.is_primary_key).collect();
+    let key = match keys.as_slice() {
+        [key] if key.data_type.to_lowercase().contains("int") => key.name.clone(),
+        _ => {
+            warn!(table = %target.name, "no single integer primary key, the NOT NULL columns are added without a chunked backfill");
+            return Ok(None);
+        }
+    };
+    let bound = |function: &str| {
+        format!(
+            "SELECT {function}({}) FROM {}",
+            quote(&key),
+            quote(&target.name)
+        )
+    };
+    let as_i64 = |value: ForgeUniversalDataField| match value {
+        ForgeUniversalDataField::Integer(n) => Some(n),
+        ForgeUniversalDataField::UnsignedInteger(n) => i64::try_from(n).ok(),
+        _ => None,
+    };
+    let min = as_i64(driver.query_scalar(&bound("MIN")).await?);
+    let max = as_i64(driver.query_scalar(&bound("MAX")).await?);
+    let (Some(min), Some(max)) = (min, max) else {
+        return Ok(None);
+    };
+    Ok(Some(Backfill {
+        columns,
+        key,
+        ranges: key_ranges(min, max, ddl.backfill_chunk_rows.unwrap_or(10_000)),
+    }))
+}
+
+/// bind parameters of one statement, the limit of the MySQL and PostgreSQL protocols
+pub(crate) const MAX_BIND_PARAMS: usize = 65535;
+
+/// estimated protocol overhead per bound value (length prefix, type, placeholder)
+const VALUE_OVERHEAD_BYTES: u64 = 8;
+
+/// Row ranges of a chunk that fit into one multi-row INSERT.
+///
+/// A batch ends before it would exceed `max_params` bind parameters or about `max_bytes`
+/// of encoded values. A single row larger than `max_bytes` is still sent alone,
+/// the server rejects it and the row is logged like any other failed row.
+pub(crate) fn batch_ranges(
+    rows: &[IndexMap<String, ForgeUniversalDataField>],
+    columns: usize,
+    max_params: usize,
+    max_bytes: u64,
+) -> Vec<Range<usize>> {
+    let rows_per_params = (max_params / columns.max(1)).max(1);
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut bytes = 0u64;
+    for (i, row) in rows.iter().enumerate() {
+        let size = metrics::row_size(row) + VALUE_OVERHEAD_BYTES * columns as u64;
+        if i > start && (i - start >= rows_per_params || bytes + size > max_bytes) {
+            ranges.push(start..i);
+            start = i;
+            bytes = 0;
+        }
+        bytes += size;
+    }
+    if start < rows.len() {
+        ranges.push(start..rows.len());
+    }
+    ranges
+}
+
+/// Conflict clause completing the INSERT of an upsert: a row whose key exists already gets the
+/// values of the other columns. Columns and keys are quoted identifiers.
+pub(crate) fn upsert_clause(
+    dialect: ForgeDialect,
+    columns: &[String],
+    key_columns: &[String],
+) -> String {
+    let updates: Vec<&String> = columns
+        .iter()
+        .filter(|c| !key_columns.contains(c))
+        .collect();
+    match dialect {
+        ForgeDialect::Postgres if updates.is_empty() => {
+            format!("ON CONFLICT ({}) DO NOTHING", key_columns.join(", "))
+        }
+        ForgeDialect::Postgres => format!(
+            "ON CONFLICT ({}) DO UPDATE SET {}",
+            key_columns.join(", "),
+            updates
+                .iter()
+                .map(|c| format!("{c} = EXCLUDED.{c}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        // a key only table updates its key to itself, MySQL has no DO NOTHING
+        ForgeDialect::Mysql => {
+            let updates = if updates.is_empty() {
+                key_columns.iter().collect()
+            } else {
+                updates
+            };
+            format!(
+                "ON DUPLICATE KEY UPDATE {}",
+                updates
+                    .iter()
+                    .map(|c| format!("{c} = VALUES({c})"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        }
+    }
+}
+
+/// Connections of a pool: every insert worker writes through its own connection,
+/// one more is left for the reader and the metadata queries.
+pub(crate) fn max_connections(config: &ForgeConfig, default: u32) -> u32 {
+    let workers = u32::try_from(config.get_max_insert_workers()).unwrap_or(u32::MAX);
+    default.max(workers.saturating_add(1))
+}
+
+/// Adds the row filter of a source table (`[tables.<name>] filter`) to the clause that
+/// follows the `FROM` of a SELECT, i.e. `" WHERE (a) > (?) ORDER BY a"` or `""`.
+pub(crate) fn filtered_clause(
+    row_filters: &HashMap<String, String>,
+    table_name: &str,
+    clause: &str,
+) -> String {
+    match (row_filters.get(table_name), clause.strip_prefix(" WHERE ")) {
+        (None, _) => clause.to_string(),
+        (Some(filter), Some(rest)) => format!(" WHERE ({filter}) AND {rest}"),
+        (Some(filter), None) => format!(" WHERE ({filter}){clause}"),
+    }
+}
+
+/// stream of the rows of a driver, the item of `stream_table_data`
+pub(crate) type RowStream<'a> = Pin<
+    Box<
+        dyn Stream<Item = Result<IndexMap<String, ForgeUniversalDataField>, ForgeError>>
+            + Send
+            + 'a,
+    >,
+>;
+
+/// error of reopening a stream as item of the stream
+fn stream_error(error: Box<dyn Error>) -> ForgeError {
+    match error.downcast::<ForgeError>() {
+        Ok(e) => *e,
+        Err(error) => match error.downcast::<sqlx::Error>() {
+            Ok(e) => ForgeError::Database(*e),
+            Err(error) => ForgeError::Internal(error.to_string()),
+        },
+    }
+}
+
+/// Stream of the rows in key order after the key `after` (all rows for `None`) that is
+/// reopened after the last key read when a transient error (i.e. a connection dropped by
+/// a firewall) interrupts it.
+///
+/// The rows of the interrupted stream are not read again, so a copy of many hours
+/// survives a reconnect. Errors after the attempts of `retry` end the stream.
+pub(crate) fn resuming_stream<'a, D: DatabaseDriver + ?Sized>(
+    driver: &'a D,
+    table_name: String,
+    key_columns: Vec<String>,
+    after: Option<Vec<ForgeUniversalDataField>>,
+    retry: RetryPolicy,
+) -> RowStream<'a> {
+    Box::pin(async_stream::try_stream! {
+        let mut rows = driver
+            .stream_table_data_after(&table_name, &key_columns, after.as_deref())
+            .await
+            .map_err(stream_error)?;
+        let mut last_key = after;
+        let mut failed_attempts = 0;
+        while let Some(row) = rows.next().await {
+            let row = match row {
+                Ok(row) => row,
+                Err(e) if failed_attempts + 1 < retry.max_attempts && e.is_transient() => {
+                    failed_attempts += 1;
+                    warn!(table = %table_name, attempt = failed_attempts, error = %e, "stream interrupted, reconnecting after last key");
+                    tokio::time::sleep(retry.backoff(failed_attempts)).await;
+                    rows = driver
+                        .stream_table_data_after(&table_name, &key_columns, last_key.as_deref())
+                        .await
+                        .map_err(stream_error)?;
+                    continue;
+                }
+                Err(e) => Err(e)?,
+            };
+            failed_attempts = 0;
+            last_key = Some(
+                key_columns
+                    .iter()
+                    .map(|k| row.get(k).cloned().unwrap_or(ForgeUniversalDataField::Null))
+                    .collect(),
+            );
+            yield row;
+        }
+    })
+}
+
+/// Database system of a connection URL: "mysql", "postgres" or "unknown".
+///
+/// A SQL script target (`postgres+script://migration.sql`) is of the system of its dialect.
+#[must_use]
+pub fn database_system(url: &str) -> &'static str {
+    if let Some((dialect, _)) = script::parse_script_url(url) {
+        dialect.config_section()
+    } else if url.starts_with("mysql://") {
+        "mysql"
+    } else if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+        "postgres"
+    } else {
+        "unknown"
+    }
+}
+
+/// Returns true if data can be replicated from the `source` to the `target` system.
+#[must_use]
+pub fn is_supported_combination(source: &str, target: &str) -> bool {
+    matches!(
+        (source, target),
+        ("mysql" | "postgres", "mysql" | "postgres")
+    )
+}
+
+/// true if zero dates are written to MySQL as they are (`[mysql.rules.on_write] zero_date`)
+pub(crate) fn zero_date_on_write(config: &ForgeConfig) -> bool {
+    config
+        .mysql
+        .as_ref()
+        .and_then(|r| r.rules.as_ref())
+        .and_then(|r| r.on_write.as_ref())
+        .and_then(|w| w.zero_date)
+        .unwrap_or(false) // default false, if not in config
+}
 
 /// Creates a database driver from a connection URL.
 ///
 /// Automatically detects the database type from the URL protocol and returns
-/// the appropriate driver implementation. Supports MySQL and PostgreSQL.
+/// the appropriate driver implementation. Supports MySQL and PostgreSQL, and SQL script
+/// targets (`mysql+script://<path>`, `postgres+script://<path>`) writing the statements into a file.
 ///
 /// # Arguments
 ///
@@ -57,7 +682,10 @@ use std::str::FromStr;
 /// # Errors
 ///
 /// Returns an error if:
-/// - The URL protocol is not supported (only mysql:// and postgres:// are supported)
+/// - The URL protocol is not supported (only mysql://, postgres:// and script URLs are supported)
+/// - A SQL script cannot be created or is used as source
+/// - A password file cannot be read
+/// - The SSH tunnel of the connection cannot be opened
 /// - Database connection fails (invalid credentials, host unreachable, etc.)
 /// - Connection pool cannot be established
 pub async fn create_driver(
@@ -65,55 +693,412 @@ pub async fn create_driver(
     config: &ForgeConfig,
     is_source_driver: bool,
 ) -> Result<Box<dyn DatabaseDriver>, Box<dyn Error>> {
+    let null_ordering = config.get_null_ordering();
+    let retry = RetryPolicy::from_config(config);
+
+    if let Some((dialect, path)) = script::parse_script_url(url) {
+        if is_source_driver {
+            return Err(format!("A SQL script target cannot be read: {url}").into());
+        }
+        return Ok(Box::new(ScriptDriver::create(dialect, &path, config)?));
+    }
+    // the URL with the merged credentials is never logged or shown in errors
+    let connect_url = credentials::apply(url, config, is_source_driver)?;
+    let (connect_url, tunnel) = tunnel::open_for(&connect_url, config, is_source_driver).await?;
+
+    let keepalive = keepalive::Keepalive::from_config(config);
+    // the target is written completely, only the reads of the source are filtered
+    let row_filters = if is_source_driver {
+        config.get_row_filters()
+    } else {
+        HashMap::new()
+    };
+
     if url.starts_with("mysql://") {
-        let zero_date_on_write = config
-            .mysql
-            .as_ref()
-            .and_then(|r| r.rules.as_ref())
-            .and_then(|r| r.on_write.as_ref())
-            .and_then(|w| w.zero_date)
-            .unwrap_or(false); // default false, if not in config
+        let zero_date_on_write = zero_date_on_write(config);
 
         let sql_mode = get_mysql_init_session_sql_mode(config, is_source_driver);
+        let default_connections = if sql_mode.is_empty() { 10 } else { 5 };
+        let foreign_key_checks_off = Arc::new(AtomicBool::new(false));
 
-        if sql_mode == "".to_string() {
-            let pool = MySqlPool::connect(url).await?;
-            let driver = MySqlDriver {
-                pool,
-                zero_date_on_write,
-            };
-            Ok(Box::new(driver))
-        } else {
-            let sql_command_for_hook = sql_mode.clone(); // copy for outer Closure
-
-            let opts = MySqlConnectOptions::from_str(url)?;
-
-            // create pool with options
-            let pool = MySqlPoolOptions::new()
-                .max_connections(5)
-                .after_connect(move |conn, _meta| {
-                    // IMPORTANT: wen need a new copy for every call which is then "moved" into the async block
-                    let cmd = sql_command_for_hook.clone();
-
-                    Box::pin(async move {
-                        sqlx::query(&cmd).execute(conn).await?;
-                        Ok(())
-                    })
-                })
-                .connect_with(opts)
-                .await?;
-            let driver = MySqlDriver {
-                pool,
-                zero_date_on_write,
-            };
-            Ok(Box::new(driver))
+        let opts = MySqlConnectOptions::from_str(&connect_url)?;
+
+        // create pool with the session hooks
+        let mut init_sql = vec![keepalive.mysql_sql()];
+        if !sql_mode.is_empty() {
+            init_sql.push(sql_mode);
         }
+        if let Some(time_zone) = config.get_time_zone(is_source_driver) {
+            init_sql.push(mysql::mysql_time_zone_sql(time_zone));
+        }
+        // only the schema changes of a target run online
+        let ddl = config.get_ddl();
+        let online_ddl = match ddl.online_tool {
+            Some(tool) if !is_source_driver => Some(online_ddl::OnlineSchemaChange::from_url(
+                tool,
+                &connect_url,
+                ddl.online_tool_args.unwrap_or_default(),
+            )?),
+            _ => None,
+        };
+        let pool = mysql::session_pool_options(init_sql, Arc::clone(&foreign_key_checks_off))
+            .max_connections(max_connections(config, default_connections))
+            .idle_timeout(keepalive.pool_idle_timeout)
+            .connect_with(opts)
+            .await?;
+        let driver = MySqlDriver {
+            pool,
+            zero_date_on_write,
+            null_ordering,
+            retry,
+            write_tx: tokio::sync::Mutex::new(None),
+            foreign_key_checks_off,
+            tunnel,
+            row_filters,
+            idempotent_ddl: config.is_idempotent_ddl(),
+            online_ddl,
+            metadata: Default::default(),
+        };
+        Ok(Box::new(driver))
     }
     // if mysql
     else if url.starts_with("postgres://") || url.starts_with("postgresql://") {
-        let pool = PgPool::connect(url).await?;
-        Ok(Box::new(postgres::PostgresDriver { pool: Some(pool) }))
+        let time_zone = config.get_time_zone(is_source_driver);
+        let pool = PgPoolOptions::new()
+            .max_connections(max_connections(config, 10))
+            .idle_timeout(keepalive.pool_idle_timeout)
+            .after_connect(move |conn, _meta| {
+                Box::pin(async move {
+                    sqlx::query(&keepalive.postgres_sql())
+                        .execute(&mut *conn)
+                        .await?;
+                    if let Some(time_zone) = time_zone {
+                        sqlx::query(&postgres::postgres_time_zone_sql(time_zone))
+                            .execute(&mut *conn)
+                            .await?;
+                    }
+                    Ok(())
+                })
+            })
+            .connect(&connect_url)
+            .await?;
+        Ok(Box::new(postgres::PostgresDriver {
+            pool: Some(pool),
+            null_ordering,
+            retry,
+            write_tx: tokio::sync::Mutex::new(None),
+            time_zone,
+            tunnel,
+            row_filters,
+            idempotent_ddl: config.is_idempotent_ddl(),
+        }))
     } else {
         Err(format!("Unsupported database protocol in URL: {url}").into())
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::ForgeSchemaColumn;
+    use crate::core::ForgeSchemaIndex;
+
+    fn table(columns: &[&str], indices: &[&str]) -> ForgeSchemaTable {
+        let mut table = ForgeSchemaTable::new("users");
+        for name in columns {
+            table.columns.push(ForgeSchemaColumn::new(name, "int"));
+        }
+        for name in indices {
+            table.indices.push(ForgeSchemaIndex {
+                name: (*name).to_string(),
+                columns: vec!["id".to_string()],
+                ..Default::default()
+            });
+        }
+        table
+    }
+
+    #[test]
+    fn row_filters_are_added_to_the_where_clause() {
+        let filters = HashMap::from([("orders".to_string(), "total > 0 OR paid".to_string())]);
+        assert_eq!(
+            filtered_clause(&filters, "orders", " WHERE (`id`) > (?) ORDER BY `id`"),
+            " WHERE (total > 0 OR paid) AND (`id`) > (?) ORDER BY `id`"
+        );
+        assert_eq!(
+            filtered_clause(&filters, "orders", " ORDER BY id"),
+            " WHERE (total > 0 OR paid) ORDER BY id"
+        );
+        assert_eq!(filtered_clause(&filters, "users", ""), "");
+    }
+
+    #[test]
+    fn applied_table_keeps_target_objects_without_destructive() {
+        let source = table(&["id", "age"], &["idx_age"]);
+        let target = table(&["id", "legacy"], &["idx_legacy"]);
+
+        let applied = applied_table(&source, &target, false);
+        let columns: Vec<&str> = applied.columns.iter().map(|c| c.name.as_str()).collect();
+        let indices: Vec<&str> = applied.indices.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(columns, vec!["id", "age", "legacy"]);
+        assert_eq!(indices, vec!["idx_age", "idx_legacy"]);
+
+        let applied = applied_table(&source, &target, true);
+        assert_eq!(applied.columns.len(), 2);
+        assert_eq!(applied.indices.len(), 1);
+    }
+
+    #[test]
+    fn foreign_key_changes_group_columns_and_replace_changed_keys() {
+        let fk = |name: &str, column: &str, ref_table: &str, on_delete: Option<&str>| {
+            crate::core::ForgeSchemaForeignKey {
+                name: name.to_string(),
+                column: column.to_string(),
+                ref_table: ref_table.to_string(),
+                ref_column: format!("{column}_ref"),
+                on_delete: on_delete.map(str::to_string),
+                on_update: None,
+            }
+        };
+        let mut orders = table(&["id"], &[]);
+        orders.name = "orders".to_string();
+        let mut users = orders.clone();
+        users.name = "users".to_string();
+        let mut source_orders = orders.clone();
+        source_orders.foreign_keys = vec![
+            fk("fk_user", "tenant", "users", Some("cascade")),
+            fk("fk_user", "user_id", "users", Some("cascade")),
+            fk("fk_shop", "shop_id", "shops", None),
+            fk("fk_status", "status", "users", None),
+        ];
+        orders.foreign_keys = vec![
+            fk("fk_status", "status", "users", Some("RESTRICT")),
+            fk("fk_legacy", "legacy", "users", None),
+        ];
+        let source = ForgeSchema {
+            tables: vec![users.clone(), source_orders],
+            ..Default::default()
+        };
+        let target = ForgeSchema {
+            tables: vec![users, orders],
+            ..Default::default()
+        };
+
+        let changes =
+            ForeignKeyChanges::between(&source, &target, &ForgeObjectFilter::default(), false);
+        // fk_status is unchanged, RESTRICT is the default, fk_shop references a missing table
+        assert!(changes.drop.is_empty());
+        assert_eq!(changes.add.len(), 1);
+        let (table, added) = &changes.add[0];
+        assert_eq!(table, "orders");
+        assert_eq!(added.columns, vec!["tenant", "user_id"]);
+        assert_eq!(added.on_delete.as_deref(), Some("CASCADE"));
+
+        let changes =
+            ForeignKeyChanges::between(&source, &target, &ForgeObjectFilter::default(), true);
+        let dropped: Vec<&str> = changes.drop.iter().map(|(_, c)| c.name.as_str()).collect();
+        assert_eq!(dropped, vec!["fk_legacy"]);
+        let reverted = changes.reverted(&target);
+        assert_eq!(reverted.drop.len(), 1);
+        assert_eq!(reverted.add[0].1.name, "fk_legacy");
+    }
+
+    fn sized_row(bytes: usize) -> IndexMap<String, ForgeUniversalDataField> {
+        let mut row = IndexMap::new();
+        row.insert(
+            "payload".to_string(),
+            ForgeUniversalDataField::Binary(vec![0; bytes]),
+        );
+        row
+    }
+
+    #[test]
+    fn batch_ranges_respect_bind_parameters() {
+        let rows: Vec<_> = (0..10).map(|_| sized_row(1)).collect();
+        assert_eq!(
+            batch_ranges(&rows, 3, 12, u64::MAX),
+            vec![0..4, 4..8, 8..10]
+        );
+        assert_eq!(batch_ranges(&rows, 1, 65535, u64::MAX), vec![0..10]);
+        assert!(batch_ranges(&[], 3, 12, u64::MAX).is_empty());
+    }
+
+    #[test]
+    fn batch_ranges_respect_encoded_size() {
+        // 92 bytes + 8 bytes overhead per row
+        let mut rows: Vec<_> = (0..5).map(|_| sized_row(92)).collect();
+        assert_eq!(batch_ranges(&rows, 1, 65535, 250), vec![0..2, 2..4, 4..5]);
+
+        // an oversized row is sent alone
+        rows.insert(1, sized_row(1000));
+        assert_eq!(
+            batch_ranges(&rows, 1, 65535, 250),
+            vec![0..1, 1..2, 2..4, 4..6]
+        );
+    }
+
+    #[test]
+    fn max_connections_cover_all_insert_workers() {
+        let config: ForgeConfig =
+            toml::from_str("[general]\ninsert_workers = 2\n[tables.insert_workers]\nevents = 16")
+                .unwrap();
+        assert_eq!(max_connections(&config, 10), 17);
+        assert_eq!(max_connections(&ForgeConfig::default(), 10), 10);
+
+        let ordered: ForgeConfig =
+            toml::from_str("[general]\nordered_inserts = true\ninsert_workers = 32").unwrap();
+        assert_eq!(max_connections(&ordered, 5), 5);
+    }
+
+    #[test]
+    fn dropped_columns_lists_lost_data() {
+        let source = table(&["id"], &[]);
+        let target = table(&["id", "zip", "legacy"], &[]);
+        assert_eq!(
+            dropped_columns(&source, &target),
+            vec![
+                "column users.legacy is dropped, its data cannot be restored",
+                "column users.zip is dropped, its data cannot be restored",
+            ]
+        );
+    }
+
+    #[test]
+    fn backfill_covers_added_not_null_columns_in_key_chunks() {
+        let target = table(&["id"], &[]);
+        let mut source = table(&["id", "note"], &[]);
+        let mut status = ForgeSchemaColumn::new("status", "varchar");
+        status.default = Some("'new'".to_string());
+        source.columns.push(status);
+
+        let columns = backfill_columns(&source, &target);
+        let names: Vec<&str> = columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["status"]);
+        let backfill = Backfill {
+            columns,
+            key: "id".to_string(),
+            ranges: key_ranges(1, 25, 10),
+        };
+        assert_eq!(backfill.ranges, vec![(1, 11), (11, 21), (21, 31)]);
+        let nullable = backfill.nullable_table(&source);
+        let added = nullable.columns.last().unwrap();
+        assert!(added.is_nullable && added.default.is_none());
+        assert!(key_ranges(5, 4, 10).is_empty());
+    }
+
+    #[test]
+    fn upsert_clauses_update_the_other_columns() {
+        let names =
+            |names: &[&str]| -> Vec<String> { names.iter().map(ToString::to_string).collect() };
+        assert_eq!(
+            upsert_clause(
+                ForgeDialect::Postgres,
+                &names(&["id", "name"]),
+                &names(&["id"])
+            ),
+            "ON CONFLICT (id) DO UPDATE SET name = EXCLUDED.name"
+        );
+        assert_eq!(
+            upsert_clause(ForgeDialect::Postgres, &names(&["id"]), &names(&["id"])),
+            "ON CONFLICT (id) DO NOTHING"
+        );
+        assert_eq!(
+            upsert_clause(
+                ForgeDialect::Mysql,
+                &names(&["`id`", "`name`"]),
+                &names(&["`id`"])
+            ),
+            "ON DUPLICATE KEY UPDATE `name` = VALUES(`name`)"
+        );
+        assert_eq!(
+            upsert_clause(ForgeDialect::Mysql, &names(&["`id`"]), &names(&["`id`"])),
+            "ON DUPLICATE KEY UPDATE `id` = VALUES(`id`)"
+        );
+    }
+
+    /// statements naming the action and the table
+    struct NamedStatements;
+
+    impl SchemaStatements for NamedStatements {
+        fn create_table(
+            &self,
+            table: &ForgeSchemaTable,
+            _config: &ForgeConfig,
+        ) -> Result<Vec<String>, Box<dyn Error>> {
+            Ok(vec![format!("create {}", table.name)])
+        }
+
+        fn drop_table(&self, table: &ForgeSchemaTable) -> Result<Vec<String>, Box<dyn Error>> {
+            Ok(vec![format!("drop {}", table.name)])
+        }
+
+        fn alter_table(
+            &self,
+            source: &ForgeSchemaTable,
+            target: &ForgeSchemaTable,
+            _config: &ForgeConfig,
+            _destructive: bool,
+        ) -> Result<Vec<String>, Box<dyn Error>> {
+            let added: Vec<&str> = target
+                .columns
+                .iter()
+                .filter(|c| !source.columns.iter().any(|s| s.name == c.name))
+                .map(|c| c.name.as_str())
+                .collect();
+            Ok(vec![format!("alter {} {}", source.name, added.join(","))])
+        }
+
+        fn foreign_keys(&self, _changes: &ForeignKeyChanges) -> (Vec<String>, Vec<String>) {
+            (vec!["drop fks".to_string()], vec!["add fks".to_string()])
+        }
+    }
+
+    #[test]
+    fn rollback_plan_reverts_tables_in_reverse_order() {
+        let mut orders = table(&["id"], &[]);
+        orders.name = "orders".to_string();
+        let mut archive = table(&["id"], &[]);
+        archive.name = "archive".to_string();
+        let source = ForgeSchema {
+            tables: vec![table(&["id", "email"], &[]), orders],
+            ..Default::default()
+        };
+        // orders is created by the apply, archive dropped by a destructive one
+        let target = ForgeSchema {
+            tables: vec![table(&["id"], &[]), archive],
+            ..Default::default()
+        };
+        let plan = |destructive| {
+            rollback_schema_plan(
+                &NamedStatements,
+                &source,
+                &target,
+                &ForgeConfig::default(),
+                &ForgeObjectFilter::default(),
+                destructive,
+            )
+            .unwrap()
+        };
+
+        let destructive = plan(true);
+        assert_eq!(
+            destructive.statements,
+            vec![
+                "drop fks",
+                "create archive",
+                "drop orders",
+                "alter users email",
+                "add fks"
+            ]
+        );
+        assert_eq!(
+            destructive.irreversible,
+            vec!["table archive is dropped, its data cannot be restored"]
+        );
+        assert_eq!(
+            plan(false).statements,
+            vec!["drop fks", "drop orders", "alter users email", "add fks"]
+        );
+    }
+}
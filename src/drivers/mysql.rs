@@ -2,23 +2,75 @@ use async_trait::async_trait;
 use futures::{Stream, StreamExt};
 use indexmap::IndexMap;
 use sqlx::{
-    mysql::{MySqlPool, MySqlRow}, Column, Row, TypeInfo,
-    ValueRef,
+    Column, Row, TypeInfo, ValueRef,
+    mysql::{MySqlPool, MySqlPoolOptions, MySqlRow},
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
 
 use crate::core::{
-    ForgeConfig, ForgeError, ForgeSchema, ForgeSchemaForeignKey, ForgeSchemaIndex,
-    ForgeSchemaMetadata, ForgeSchemaTable, ForgeUniversalDataField,
+    ForgeAppliedStatements, ForgeConfig, ForgeDialect, ForgeError, ForgeKeyRange,
+    ForgeNullOrdering, ForgeObjectFilter, ForgePreflightCheck, ForgeRangeChecksum,
+    ForgeRollbackPlan, ForgeSchema, ForgeSchemaForeignKey, ForgeSchemaGrant, ForgeSchemaIndex,
+    ForgeSchemaMetadata, ForgeSchemaPartition, ForgeSchemaPartitioning, ForgeSchemaTable,
+    ForgeSourcePosition, ForgeTableEstimate, ForgeTimeZone, ForgeUniversalDataField,
+    ForgeVerifySample, MigrateOptions,
 };
-use crate::ops::log_error_to_file;
-use crate::{DatabaseDriver, ForgeSchemaColumn};
+use crate::drivers::online_ddl::{OnlineSchemaChange, online_alter};
+use crate::drivers::tunnel::SshTunnel;
+use crate::drivers::{
+    Backfill, ForeignKeyChanges, ForeignKeyConstraint, MAX_BIND_PARAMS, SchemaStatements,
+    backfill_plan, batch_ranges, filtered_clause, resuming_stream, rollback_schema_plan,
+    sorted_by_name, upsert_clause,
+};
+use crate::ops::retry::RetryPolicy;
+use crate::ops::schema_file::SCHEMA_FORMAT_VERSION;
+use crate::ops::{grants, log_rejected_row};
+use crate::{CancellationToken, DatabaseDriver, ForgeSchemaColumn};
 
 pub struct MySqlDriver {
     pub pool: MySqlPool,
+    /// retries of transient errors of metadata queries and inserts
+    pub retry: RetryPolicy,
+    /// open write transaction of `begin_write`, inserts and deletes run in it
+    pub write_tx: tokio::sync::Mutex<Option<sqlx::Transaction<'static, sqlx::MySql>>>,
+    /// set by `disable_constraints`, the pool hooks turn `FOREIGN_KEY_CHECKS` off while set
+    pub foreign_key_checks_off: Arc<AtomicBool>,
     pub zero_date_on_write: bool,
+    pub null_ordering: ForgeNullOrdering,
+    /// SSH tunnel of the pool, closed when the driver is dropped
+    pub tunnel: Option<SshTunnel>,
+    /// SQL conditions of the rows read per table (`[tables.<name>] filter` of a source)
+    pub row_filters: HashMap<String, String>,
+    /// `CREATE TABLE IF NOT EXISTS` / `DROP TABLE IF EXISTS` (`[general] idempotent_ddl`)
+    pub idempotent_ddl: bool,
+    /// tool executing the column and index changes of a schema apply (`[general.ddl] online_tool`)
+    pub online_ddl: Option<OnlineSchemaChange>,
+    /// server metadata read once instead of per chunk
+    pub metadata: Arc<MySqlMetadataCache>,
+}
+
+/// Server metadata of a driver that every chunk needs, read on first use.
+///
+/// The generated columns are read again after schema statements were executed.
+#[derive(Debug, Default)]
+pub struct MySqlMetadataCache {
+    server_version: tokio::sync::OnceCell<String>,
+    max_batch_bytes: tokio::sync::OnceCell<u64>,
+    generated_columns: std::sync::Mutex<HashMap<String, Vec<String>>>,
+}
+
+impl MySqlMetadataCache {
+    fn generated_columns(&self) -> std::sync::MutexGuard<'_, HashMap<String, Vec<String>>> {
+        self.generated_columns
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
 }
 
 pub fn get_mysql_init_session_sql_mode(config: &ForgeConfig, is_source: bool) -> String {
@@ -48,9 +100,829 @@ pub fn get_mysql_init_session_sql_mode(config: &ForgeConfig, is_source: bool) ->
     "".to_string()
 }
 
+/// `SET` statement of the `[general.ddl]` lock wait timeout of the connection running the schema
+/// statements
+#[must_use]
+pub fn mysql_ddl_timeout_sql(config: &ForgeConfig) -> Option<String> {
+    config
+        .get_ddl()
+        .lock_wait_timeout_secs
+        .map(|secs| format!("SET SESSION lock_wait_timeout = {secs}"))
+}
+
+/// `SET time_zone` of a session, TIMESTAMP values are read and written in this time zone
+#[must_use]
+pub fn mysql_time_zone_sql(time_zone: ForgeTimeZone) -> String {
+    format!("SET time_zone = '{time_zone}'")
+}
+
+/// Pool options whose sessions run the `init_sql` statements (i.e. the SQL mode) after connecting.
+///
+/// While `foreign_key_checks_off` is set, every acquired session runs with
+/// `FOREIGN_KEY_CHECKS = 0` and is switched back when it returns to the pool,
+/// so the setting never leaks into sessions used after the load.
+pub fn session_pool_options(
+    init_sql: Vec<String>,
+    foreign_key_checks_off: Arc<AtomicBool>,
+) -> MySqlPoolOptions {
+    let on_connect = Arc::clone(&foreign_key_checks_off);
+    let on_acquire = Arc::clone(&foreign_key_checks_off);
+    let on_release = foreign_key_checks_off;
+    MySqlPoolOptions::new()
+        .after_connect(move |conn, _meta| {
+            // IMPORTANT: wen need a new copy for every call which is then "moved" into the async block
+            let statements = init_sql.clone();
+            let checks_off = on_connect.load(Ordering::SeqCst);
+
+            Box::pin(async move {
+                for cmd in &statements {
+                    sqlx::query(cmd).execute(&mut *conn).await?;
+                }
+                if checks_off {
+                    sqlx::query("SET SESSION FOREIGN_KEY_CHECKS = 0")
+                        .execute(&mut *conn)
+                        .await?;
+                }
+                Ok(())
+            })
+        })
+        .before_acquire(move |conn, _meta| {
+            let checks_off = on_acquire.load(Ordering::SeqCst);
+            Box::pin(async move {
+                if checks_off {
+                    sqlx::query("SET SESSION FOREIGN_KEY_CHECKS = 0")
+                        .execute(&mut *conn)
+                        .await?;
+                }
+                Ok(true)
+            })
+        })
+        .after_release(move |conn, _meta| {
+            let checks_off = on_release.load(Ordering::SeqCst);
+            Box::pin(async move {
+                if checks_off {
+                    sqlx::query("SET SESSION FOREIGN_KEY_CHECKS = 1")
+                        .execute(&mut *conn)
+                        .await?;
+                }
+                Ok(true)
+            })
+        })
+}
+
+/// Parsed server version of a MySQL (or MariaDB) server.
+///
+/// Several metadata details differ between MySQL 5.6, 5.7 and 8.0, so the
+/// extraction queries are branched on the detected version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MySqlServerVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+    pub is_mariadb: bool,
+}
+
+impl MySqlServerVersion {
+    /// parses `SELECT VERSION()` output like "8.0.36", "5.7.44-log" or "10.11.6-MariaDB-1:10.11.6"
+    #[must_use]
+    pub fn parse(version: &str) -> Self {
+        let mut numbers = version
+            .split(|c: char| !c.is_ascii_digit())
+            .filter(|part| !part.is_empty())
+            .map(|part| part.parse::<u32>().unwrap_or(0));
+
+        Self {
+            major: numbers.next().unwrap_or(0),
+            minor: numbers.next().unwrap_or(0),
+            patch: numbers.next().unwrap_or(0),
+            is_mariadb: version.to_lowercase().contains("mariadb"),
+        }
+    }
+
+    #[must_use]
+    pub fn at_least(&self, major: u32, minor: u32, patch: u32) -> bool {
+        (self.major, self.minor, self.patch) >= (major, minor, patch)
+    }
+
+    /// generated columns (and `information_schema.COLUMNS.GENERATION_EXPRESSION`)
+    /// exist since MySQL 5.7 and MariaDB 10.2
+    #[must_use]
+    pub fn has_generated_columns(&self) -> bool {
+        if self.is_mariadb {
+            self.at_least(10, 2, 0)
+        } else {
+            self.at_least(5, 7, 0)
+        }
+    }
+
+    /// MySQL 8.0 serves table metadata from the transactional data dictionary,
+    /// older versions (and MariaDB) have to open every table for `information_schema.TABLES`
+    #[must_use]
+    pub fn has_data_dictionary(&self) -> bool {
+        !self.is_mariadb && self.at_least(8, 0, 0)
+    }
+
+    /// `SHOW MASTER STATUS` was renamed in MySQL 8.2 and removed in 8.4
+    #[must_use]
+    pub fn binlog_status_sql(&self) -> &'static str {
+        if !self.is_mariadb && self.at_least(8, 2, 0) {
+            "SHOW BINARY LOG STATUS"
+        } else {
+            "SHOW MASTER STATUS"
+        }
+    }
+
+    /// system variable with the executed GTIDs, MariaDB has its own GTID format
+    #[must_use]
+    pub fn gtid_set_sql(&self) -> &'static str {
+        if self.is_mariadb {
+            "SELECT @@GLOBAL.gtid_current_pos"
+        } else {
+            "SELECT @@GLOBAL.gtid_executed"
+        }
+    }
+
+    /// pre-flight check of the version, the metadata queries need MySQL 5.6 or MariaDB 10.0
+    #[must_use]
+    pub fn version_check(&self) -> ForgePreflightCheck {
+        let name = if self.is_mariadb { "MariaDB" } else { "MySQL" };
+        let version = format!("{name} {}.{}.{}", self.major, self.minor, self.patch);
+        let supported = if self.is_mariadb {
+            self.at_least(10, 0, 0)
+        } else {
+            self.at_least(5, 6, 0)
+        };
+        if !supported {
+            ForgePreflightCheck::fail("server version", format!("{version} is not supported"))
+        } else if !self.has_generated_columns() {
+            ForgePreflightCheck::warn(
+                "server version",
+                format!("{version}, generated columns are not read"),
+            )
+        } else {
+            ForgePreflightCheck::pass("server version", version)
+        }
+    }
+
+    /// since MySQL 8.0.30 the deprecated `utf8` charset is reported as `utf8mb3`
+    #[must_use]
+    pub fn reports_utf8mb3(&self) -> bool {
+        !self.is_mariadb && self.at_least(8, 0, 30)
+    }
+
+    /// normalizes the `utf8`/`utf8mb3` alias (also in collation names) to the spelling of this version
+    #[must_use]
+    pub fn normalize_charset_name(&self, name: &str) -> String {
+        let lower = name.to_lowercase();
+        if self.reports_utf8mb3() {
+            if lower == "utf8" || lower.starts_with("utf8_") {
+                return lower.replacen("utf8", "utf8mb3", 1);
+            }
+        } else if lower == "utf8mb3" || lower.starts_with("utf8mb3_") {
+            return lower.replacen("utf8mb3", "utf8", 1);
+        }
+        lower
+    }
+}
+
+/// Privileges of `required` not granted on all tables of `database` by the `SHOW GRANTS` lines.
+///
+/// Grants on `*.*` and `` `database`.* `` count, `ALL PRIVILEGES` includes every privilege.
+/// Table and column grants are ignored, a migration creates new tables.
+#[must_use]
+pub fn missing_privileges(grants: &[String], database: &str, required: &[&str]) -> Vec<String> {
+    let mut granted = HashSet::new();
+    for line in grants {
+        // role grants like "GRANT `reader`@`%` TO ..." have no ON clause
+        let Some((privileges, rest)) = line
+            .strip_prefix("GRANT ")
+            .and_then(|rest| rest.split_once(" ON "))
+        else {
+            continue;
+        };
+        let scope = rest.split(" TO ").next().unwrap_or_default().trim();
+        let applies = scope == "*.*"
+            || scope
+                .strip_suffix(".*")
+                .is_some_and(|db| db.trim_matches('`').replace('\\', "") == database);
+        if !applies {
+            continue;
+        }
+        for privilege in privileges.split(',') {
+            let privilege = privilege.trim().to_uppercase();
+            if !privilege.contains('(') {
+                granted.insert(privilege);
+            }
+        }
+    }
+
+    if granted.contains("ALL PRIVILEGES") || granted.contains("ALL") {
+        return Vec::new();
+    }
+    required
+        .iter()
+        .filter(|privilege| !granted.contains(**privilege))
+        .map(|privilege| (*privilege).to_string())
+        .collect()
+}
+
+/// pre-flight checks of the session settings, a target needs strict mode
+#[must_use]
+pub fn mysql_setting_checks(
+    sql_mode: &str,
+    max_allowed_packet: u64,
+    wait_timeout: u64,
+    net_write_timeout: u64,
+    as_target: bool,
+) -> Vec<ForgePreflightCheck> {
+    let mut checks = Vec::new();
+
+    let strict = sql_mode
+        .split(',')
+        .any(|mode| mode == "STRICT_TRANS_TABLES" || mode == "STRICT_ALL_TABLES");
+    let shown_mode = if sql_mode.is_empty() {
+        "(empty)"
+    } else {
+        sql_mode
+    };
+    checks.push(if as_target && !strict {
+        ForgePreflightCheck::warn(
+            "sql_mode",
+            format!("{shown_mode}, without strict mode invalid values are changed silently"),
+        )
+    } else {
+        ForgePreflightCheck::pass("sql_mode", shown_mode)
+    });
+
+    let packet_mib = max_allowed_packet / (1024 * 1024);
+    checks.push(if max_allowed_packet < 16 * 1024 * 1024 {
+        ForgePreflightCheck::warn(
+            "max_allowed_packet",
+            format!("{packet_mib} MiB, large rows need at least 16 MiB"),
+        )
+    } else {
+        ForgePreflightCheck::pass("max_allowed_packet", format!("{packet_mib} MiB"))
+    });
+
+    // a source stream waiting for busy insert workers is cut after net_write_timeout
+    let timeouts = format!("wait_timeout {wait_timeout}s, net_write_timeout {net_write_timeout}s");
+    checks.push(
+        if wait_timeout < 300 || (!as_target && net_write_timeout < 60) {
+            ForgePreflightCheck::warn(
+                "timeouts",
+                format!("{timeouts}, long transfers may lose their connection"),
+            )
+        } else {
+            ForgePreflightCheck::pass("timeouts", timeouts)
+        },
+    );
+    checks
+}
+
+/// Bound of a partition of `information_schema.PARTITIONS`, i.e. "VALUES LESS THAN (2025)".
+#[must_use]
+pub fn mysql_partition_bound(method: &str, description: Option<&str>) -> String {
+    let method = method.to_uppercase();
+    match description {
+        Some("MAXVALUE") if method.starts_with("RANGE") => "VALUES LESS THAN MAXVALUE".to_string(),
+        Some(values) if method.starts_with("RANGE") => format!("VALUES LESS THAN ({values})"),
+        Some(values) if method.starts_with("LIST") => format!("VALUES IN ({values})"),
+        // HASH and KEY partitions have no bound
+        _ => String::new(),
+    }
+}
+
+/// `PARTITION BY` clause of a CREATE TABLE statement
+#[must_use]
+pub fn mysql_partition_by_sql(partitioning: &ForgeSchemaPartitioning) -> String {
+    let partitions: Vec<String> = partitioning
+        .partitions
+        .iter()
+        .map(|p| {
+            format!("PARTITION `{}` {}", p.name, p.bound)
+                .trim_end()
+                .to_string()
+        })
+        .collect();
+    format!(
+        "\nPARTITION BY {} ({})\n({})",
+        partitioning.strategy.to_uppercase(),
+        partitioning.key,
+        partitions.join(",\n ")
+    )
+}
+
+/// index prefix of `TEXT`/`BLOB` key columns, `MySQL` cannot index them completely
+pub const KEY_PREFIX_LENGTH: u32 = 255;
+
+/// whether a key on a column of the type needs a prefix length
+#[must_use]
+pub fn needs_key_prefix(sql_type: &str) -> bool {
+    let base = sql_type.split('(').next().unwrap_or(sql_type);
+    base.ends_with("text") || base.ends_with("blob")
+}
+
+/// fractional seconds of a temporal type, i.e. 6 for `datetime(6)`
+fn fractional_seconds(sql_type: &str, length: Option<u32>) -> Option<u32> {
+    match sql_type.split_once('(') {
+        Some((_, params)) => params.trim_end_matches(')').parse().ok(),
+        None if matches!(sql_type, "datetime" | "timestamp" | "time") => length,
+        None => None,
+    }
+}
+
+/// Value of a `DEFAULT` clause, PostgreSQL casts, quoted literals and booleans are translated.
+///
+/// Returns `None` for `NULL` and for function calls `MySQL` has no counterpart for.
+#[must_use]
+pub fn mysql_default_sql(default: &str, sql_type: &str, fsp: Option<u32>) -> Option<String> {
+    let mut value = default.trim();
+    loop {
+        let before = value;
+        // PostgreSQL casts like 'active'::character varying
+        if let Some(pos) = value.rfind("::")
+            && !value[pos..].contains('\'')
+        {
+            value = value[..pos].trim_end();
+        }
+        // parenthesized values like (-1)
+        if value.len() >= 2 && value.starts_with('(') && value.ends_with(')') {
+            value = value[1..value.len() - 1].trim();
+        }
+        if value == before {
+            break;
+        }
+    }
+    let lower = value.to_lowercase();
+    match lower.as_str() {
+        "null" => return None,
+        "current_timestamp" | "current_timestamp()" | "now()" | "localtimestamp" => {
+            // the precision of the default has to match the column
+            return Some(match fsp {
+                Some(p) if p > 0 => format!("CURRENT_TIMESTAMP({p})"),
+                _ => "CURRENT_TIMESTAMP".to_string(),
+            });
+        }
+        "true" | "false" if sql_type == "tinyint(1)" => {
+            return Some(if lower == "true" { "1" } else { "0" }.to_string());
+        }
+        _ => {}
+    }
+    if lower.starts_with("current_timestamp(") {
+        return Some(value.to_uppercase());
+    }
+
+    let literal = if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+        value[1..value.len() - 1].replace("''", "'")
+    } else {
+        let is_call = value.ends_with(')')
+            && value.split('(').next().is_some_and(|f| {
+                !f.is_empty() && f.chars().all(|c| c.is_alphanumeric() || c == '_')
+            });
+        if is_call {
+            return None;
+        }
+        value.to_string()
+    };
+    Some(format!("'{}'", literal.replace('\'', "''")))
+}
+
+/// `[mysql.rules.on_read] unsigned_bigint_to_numeric`
+fn unsigned_bigint_to_numeric(config: &ForgeConfig) -> bool {
+    config
+        .mysql
+        .as_ref()
+        .and_then(|c| c.rules.as_ref())
+        .and_then(|r| r.on_read.as_ref())
+        .and_then(|o| o.unsigned_bigint_to_numeric)
+        .unwrap_or(false)
+}
+
+/// reads an integer metadata column independent of its SQL type,
+/// i.e. `Seq_in_index` is BIGINT on 5.7 but INT UNSIGNED on 8.0
+fn get_metadata_u64(row: &MySqlRow, column: &str) -> Option<u64> {
+    row.try_get::<Option<u64>, _>(column)
+        .ok()
+        .flatten()
+        .or_else(|| {
+            row.try_get::<Option<i64>, _>(column)
+                .ok()
+                .flatten()
+                .and_then(|v| u64::try_from(v).ok())
+        })
+        .or_else(|| {
+            row.try_get::<Option<u32>, _>(column)
+                .ok()
+                .flatten()
+                .map(u64::from)
+        })
+        .or_else(|| {
+            row.try_get::<Option<Vec<u8>>, _>(column)
+                .ok()
+                .flatten()
+                .and_then(|b| String::from_utf8_lossy(&b).trim().parse::<u64>().ok())
+        })
+}
+
 impl MySqlDriver {
+    /// Statements of a chunked backfill: the default of new rows, the existing rows per key
+    /// range, then the final NOT NULL definition.
+    fn backfill_sql(&self, table: &str, backfill: &Backfill, config: &ForgeConfig) -> Vec<String> {
+        let mut statements = Vec::new();
+        for column in &backfill.columns {
+            let nullable = ForgeSchemaColumn {
+                is_nullable: true,
+                ..column.clone()
+            };
+            statements.push(format!(
+                "ALTER TABLE `{table}` MODIFY COLUMN {};",
+                self.field_migration_sql(nullable, config)
+            ));
+        }
+        for (start, end) in &backfill.ranges {
+            for column in &backfill.columns {
+                statements.push(format!(
+                    "UPDATE `{table}` SET `{column}` = DEFAULT WHERE `{key}` >= {start} AND `{key}` < {end} AND `{column}` IS NULL;",
+                    column = column.name,
+                    key = backfill.key
+                ));
+            }
+        }
+        for column in &backfill.columns {
+            statements.push(format!(
+                "ALTER TABLE `{table}` MODIFY COLUMN {};",
+                self.field_migration_sql(column.clone(), config)
+            ));
+        }
+        statements
+    }
+
+    /// Executes schema statements on one connection with the `[general.ddl]` lock wait timeout,
+    /// a statement that hit it is attempted again. Column and index changes are executed by the
+    /// online tool if one is set and installed. A cancellation stops before the next statement.
+    async fn execute_schema_statements(
+        &self,
+        statements: &[String],
+        config: &ForgeConfig,
+        cancel: &CancellationToken,
+        applied: &ForgeAppliedStatements,
+    ) -> Result<(), Box<dyn Error>> {
+        let retry = RetryPolicy::ddl_from_config(config);
+        let timeout = mysql_ddl_timeout_sql(config);
+        let mut conn = self.pool.acquire().await?;
+        if let Some(ref sql) = timeout {
+            sqlx::query(sql).execute(&mut *conn).await?;
+        }
+        let online = self.online_ddl.as_ref().filter(|osc| {
+            let available = osc.is_available();
+            if !available {
+                warn!(
+                    tool = osc.tool.command(),
+                    "online schema change tool not found, the changes are plain ALTER TABLE statements"
+                );
+            }
+            available
+        });
+
+        let mut result = Ok(());
+        let mut online_error = None;
+        let mut cancelled = false;
+        for sql in statements {
+            if cancel.is_cancelled() {
+                cancelled = true;
+                break;
+            }
+            if let Some((osc, (table, alter))) =
+                online.and_then(|osc| Some((osc, online_alter(sql)?)))
+            {
+                if let Err(e) = osc.run(&table, &alter).await {
+                    online_error = Some(e.to_string());
+                    break;
+                }
+                applied.extend([sql]);
+                continue;
+            }
+            result = retry
+                .run_on_lock_timeout("apply_schema", &mut *conn, |conn| {
+                    let sql = sql.clone();
+                    Box::pin(async move {
+                        debug!(statement = %sql, "executing schema statement");
+                        sqlx::query(&sql).execute(conn).await.map(|_| ())
+                    })
+                })
+                .await;
+            if result.is_err() {
+                break;
+            }
+            applied.extend([sql]);
+        }
+        // the statements may have added or dropped generated columns
+        self.metadata.generated_columns().clear();
+
+        // the session keeps the timeout, it is not returned to the pool
+        if timeout.is_some() {
+            conn.close().await?;
+        }
+        if let Some(e) = online_error {
+            return Err(e.into());
+        }
+        result?;
+        if cancelled {
+            return Err(ForgeError::Cancelled.into());
+        }
+        Ok(())
+    }
+
+    /// counts the values of an unsigned column above the signed bigint range
+    #[must_use]
+    pub fn unsigned_overflow_sql(&self, table_name: &str, column_name: &str) -> String {
+        format!(
+            "SELECT CAST(COUNT(*) AS SIGNED) FROM `{table_name}` WHERE `{column_name}` > {}",
+            i64::MAX
+        )
+    }
+
+    /// counts rows with foreign key values without referenced row, `foreign_key` holds the column
+    /// pairs of one constraint; a row with a NULL in any column is not checked (`MATCH SIMPLE`)
+    #[must_use]
+    pub fn foreign_key_violations_sql(
+        &self,
+        table_name: &str,
+        foreign_key: &[ForgeSchemaForeignKey],
+    ) -> String {
+        let Some(first) = foreign_key.first() else {
+            return "SELECT CAST(0 AS SIGNED)".to_string();
+        };
+        let join = foreign_key
+            .iter()
+            .map(|fk| format!("c.`{}` = r.`{}`", fk.column, fk.ref_column))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        let not_null = foreign_key
+            .iter()
+            .map(|fk| format!("c.`{}` IS NOT NULL", fk.column))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        format!(
+            "SELECT CAST(COUNT(*) AS SIGNED) FROM `{table_name}` c LEFT JOIN `{}` r \
+             ON {join} \
+             WHERE {not_null} AND r.`{}` IS NULL",
+            first.ref_table, first.ref_column
+        )
+    }
+
+    /// multi-row INSERT with the values of all rows bound
+    fn bind_rows<'q>(
+        &self,
+        sql: &'q str,
+        columns: &[String],
+        rows: &'q [IndexMap<String, ForgeUniversalDataField>],
+    ) -> sqlx::query::Query<'q, sqlx::MySql, sqlx::mysql::MySqlArguments> {
+        // create query and bind values
+        let mut query = sqlx::query(sql);
+        for row in rows {
+            for col in columns {
+                // value from IndexMap holen, Fallback to Null
+                let val = row.get(col).unwrap_or(&ForgeUniversalDataField::Null);
+
+                // binding based on UniversalEnums
+                query = self.bind_universal(query, val);
+            }
+        }
+        query
+    }
+
+    /// Byte budget of the values of a statement: statements larger than `max_allowed_packet` are
+    /// rejected as a whole, a quarter is left for the statement text and the inaccuracy of the
+    /// estimate.
+    async fn max_batch_bytes(&self) -> Result<u64, Box<dyn Error>> {
+        let bytes = self
+            .metadata
+            .max_batch_bytes
+            .get_or_try_init(|| async {
+                let max_packet: i64 = self
+                    .retry
+                    .run("max_allowed_packet", || {
+                        sqlx::query_scalar("SELECT CAST(@@max_allowed_packet AS SIGNED)")
+                            .fetch_one(&self.pool)
+                    })
+                    .await?;
+                Ok::<_, sqlx::Error>(max_packet.max(0) as u64 / 4 * 3)
+            })
+            .await?;
+        Ok(*bytes)
+    }
+
+    /// runs a statement with the values of all rows bound, in the write transaction if one is open
+    async fn execute_rows(
+        &self,
+        operation: &str,
+        sql: &str,
+        columns: &[String],
+        rows: &[IndexMap<String, ForgeUniversalDataField>],
+    ) -> Result<(), Box<dyn Error>> {
+        if let Some(tx) = self.write_tx.lock().await.as_mut() {
+            self.bind_rows(sql, columns, rows)
+                .execute(&mut **tx)
+                .await?;
+            return Ok(());
+        }
+        self.retry
+            .run(operation, || {
+                self.bind_rows(sql, columns, rows).execute(&self.pool)
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// one multi-row INSERT of a batch, failed batches are retried row by row for logging
+    async fn insert_batch(
+        &self,
+        table_name: &str,
+        columns: &[String],
+        column_names: &str,
+        rows: &[IndexMap<String, ForgeUniversalDataField>],
+        dry_run: bool,
+        halt_on_error: bool,
+    ) -> Result<(), Box<dyn Error>> {
+        // prepare SQL-Statement
+        let mut sql = format!("INSERT INTO `{table_name}` ({column_names}) VALUES ");
+
+        let mut placeholders = Vec::new();
+        for _ in 0..rows.len() {
+            let row_placeholders = vec!["?"; columns.len()].join(", ");
+            placeholders.push(format!("({row_placeholders})"));
+        }
+        sql.push_str(&placeholders.join(", "));
+
+        if dry_run {
+            info!(table = table_name, rows = rows.len(), %sql, "dry run insert");
+        } else {
+            // inside a write transaction the first failure ends the transaction
+            if let Some(tx) = self.write_tx.lock().await.as_mut() {
+                self.bind_rows(&sql, columns, rows)
+                    .execute(&mut **tx)
+                    .await?;
+                return Ok(());
+            }
+
+            // the multi-row insert is atomic, so it is retried as a whole on transient errors
+            let result = self
+                .retry
+                .run("insert_chunk", || {
+                    self.bind_rows(&sql, columns, rows).execute(&self.pool)
+                })
+                .await;
+
+            if let Err(e) = result {
+                warn!(
+                    table = table_name,
+                    error = %e,
+                    "batch insert failed, retrying row-by-row for logging"
+                );
+
+                // we build SQL for one row at a time: INSERT INTO `table` (`col1`) VALUES (?)
+                let single_sql = format!(
+                    "INSERT INTO `{table_name}` ({column_names}) VALUES ({})",
+                    vec!["?"; columns.len()].join(", ")
+                );
+
+                for row_map in rows {
+                    let mut single_query = sqlx::query(&single_sql);
+
+                    for col in columns {
+                        let val = row_map.get(col).unwrap_or(&ForgeUniversalDataField::Null);
+                        single_query = self.bind_universal(single_query, val);
+                    }
+
+                    // execute one row
+                    if let Err(single_err) = single_query.execute(&self.pool).await {
+                        let row_data = format!("{row_map:?}");
+                        let err_msg = single_err.to_string();
+
+                        // now we can log the error of one row
+                        error!(table = table_name, row = %row_data, error = %err_msg, "row insert failed");
+                        log_rejected_row(table_name, row_map, &err_msg);
+                    }
+                }
+                if halt_on_error {
+                    return Err(e.into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// GTID set and binlog position, fields without privilege or binary log stay empty
+    async fn fetch_source_position(&self, version: &MySqlServerVersion) -> ForgeSourcePosition {
+        let gtid_set = sqlx::query_scalar::<_, Option<String>>(version.gtid_set_sql())
+            .fetch_one(&self.pool)
+            .await
+            .unwrap_or_else(|e| {
+                debug!(error = %e, "gtid set not readable");
+                None
+            })
+            .filter(|gtid| !gtid.trim().is_empty());
+
+        let status = sqlx::query(version.binlog_status_sql())
+            .fetch_optional(&self.pool)
+            .await
+            .unwrap_or_else(|e| {
+                debug!(error = %e, "binlog status not readable");
+                None
+            });
+        let binlog_file = status
+            .as_ref()
+            .and_then(|row| row.try_get::<String, _>("File").ok());
+        let binlog_position = status
+            .as_ref()
+            .and_then(|row| get_metadata_u64(row, "Position"));
+
+        ForgeSourcePosition {
+            gtid_set,
+            binlog_file,
+            binlog_position,
+            lsn: None,
+        }
+    }
+
+    /// reads the complete schema, retried as a whole by `fetch_schema`
+    async fn read_schema(&self, config: &ForgeConfig) -> Result<ForgeSchema, Box<dyn Error>> {
+        // get database name from database
+        let db_name: String = sqlx::query_scalar("SELECT DATABASE()")
+            .fetch_one(&self.pool)
+            .await?;
+
+        // metadata queries depend on the server version
+        let version_string = self.fetch_server_version_string().await?;
+        let version = MySqlServerVersion::parse(&version_string);
+        debug!(version = %version_string, "detected mysql server version");
+
+        // captured before the tables are read, a CDC run starting here misses no change
+        let source_position = self.fetch_source_position(&version).await;
+        debug!(position = %source_position, "source position");
+
+        // get all basic table structures
+        let mut tables = self.fetch_tables(&version).await?;
+
+        // get details of all tables
+        for table in &mut tables {
+            // fetch all columns with applying mapping config
+            table.columns = self.fetch_columns(&table.name, config).await?;
+
+            let expressions = self
+                .fetch_generation_expressions(&table.name, &version)
+                .await?;
+            for col in &mut table.columns {
+                col.generation_expression = expressions.get(&col.name).cloned();
+            }
+
+            // fetch all indices (no mapping conf for them)
+            table.indices = self.fetch_indices(&table.name).await?;
+
+            // fetch all foreign keys (no mapping conf for them)
+            table.foreign_keys = self.fetch_foreign_keys(&table.name).await?;
+
+            table.partitioning = self.fetch_partitioning(&table.name).await?;
+        }
+
+        Ok(ForgeSchema {
+            metadata: ForgeSchemaMetadata {
+                format_version: SCHEMA_FORMAT_VERSION,
+                source_system: "mysql".to_string(),
+                source_database_name: db_name,
+                created_at: chrono::Local::now().to_rfc3339(),
+                forge_version: env!("CARGO_PKG_VERSION").to_string(),
+                config_file: String::new(),
+                source_server_version: version_string,
+                source_position: Some(source_position),
+            },
+            tables,
+            extensions: Vec::new(),
+            domains: Vec::new(),
+        })
+    }
+
     // only visible in module, not part of public trait
 
+    /// detects the server version via `SELECT VERSION()`
+    pub async fn fetch_server_version(&self) -> Result<MySqlServerVersion, Box<dyn Error>> {
+        Ok(MySqlServerVersion::parse(
+            &self.fetch_server_version_string().await?,
+        ))
+    }
+
+    /// output of `SELECT VERSION()`, queried once per driver
+    async fn fetch_server_version_string(&self) -> Result<String, Box<dyn Error>> {
+        let version = self
+            .metadata
+            .server_version
+            .get_or_try_init(|| sqlx::query_scalar("SELECT VERSION()").fetch_one(&self.pool))
+            .await?;
+        Ok(version.clone())
+    }
+
     pub fn bind_universal<'q>(
         &self,
         query: sqlx::query::Query<'q, sqlx::MySql, sqlx::mysql::MySqlArguments>,
@@ -71,6 +943,7 @@ impl MySqlDriver {
             ForgeUniversalDataField::Json(j) => query.bind(j),
             ForgeUniversalDataField::Uuid(u) => query.bind(u.to_string()),
             ForgeUniversalDataField::Inet(i) => query.bind(i.to_string()),
+            ForgeUniversalDataField::Array(a) => query.bind(a.to_json()),
             ForgeUniversalDataField::Null => query.bind(None::<String>),
             ForgeUniversalDataField::ZeroDateTime => {
                 if self.zero_date_on_write {
@@ -82,21 +955,39 @@ impl MySqlDriver {
         }
     }
 
-    pub async fn fetch_tables(&self) -> Result<Vec<ForgeSchemaTable>, Box<dyn Error>> {
-        // SHOW TABLE STATUS gives also name and comment
-        let rows = sqlx::query("SHOW TABLE STATUS")
-            .fetch_all(&self.pool)
-            .await?;
+    pub async fn fetch_tables(
+        &self,
+        version: &MySqlServerVersion,
+    ) -> Result<Vec<ForgeSchemaTable>, Box<dyn Error>> {
+        // 8.0 reads names and comments cheaply from the data dictionary,
+        // older versions open every table for information_schema, SHOW TABLE STATUS is faster there
+        let sql = if version.has_data_dictionary() {
+            "SELECT TABLE_NAME AS `Name`, TABLE_COMMENT AS `Comment`, ENGINE AS `Engine`
+             FROM information_schema.TABLES
+             WHERE TABLE_SCHEMA = DATABASE() AND TABLE_TYPE = 'BASE TABLE'
+             ORDER BY TABLE_NAME"
+        } else {
+            "SHOW TABLE STATUS"
+        };
+        let rows = sqlx::query(sql).fetch_all(&self.pool).await?;
 
         let mut tables = Vec::new();
 
         for row in rows {
-            // Index 0 is "Name", Index 1 is "Engine", Index 17 is "Comment" usw.
-            // TODO find values from their names
-            let table_name = self.get_string_at_index(&row, 0).unwrap_or_default();
-            let comment = self.get_string_at_index(&row, 17); // Index für Comment in SHOW TABLE STATUS
+            // helper for reliable reading because mysql gives metadata as (VAR)BINARY
+            let get_s = |col: &str| -> Option<String> {
+                row.try_get::<Option<Vec<u8>>, _>(col)
+                    .ok()
+                    .flatten()
+                    .map(|b| String::from_utf8_lossy(&b).into_owned())
+                    .or_else(|| row.try_get::<Option<String>, _>(col).ok().flatten())
+            };
+
+            let table_name = get_s("Name").unwrap_or_default();
+            let comment = get_s("Comment").filter(|c| !c.is_empty());
 
-            if table_name.is_empty() {
+            // SHOW TABLE STATUS also lists views (without engine)
+            if table_name.is_empty() || get_s("Engine").is_none() {
                 continue;
             }
 
@@ -106,6 +997,7 @@ impl MySqlDriver {
                 indices: Vec::new(),
                 foreign_keys: Vec::new(),
                 comment,
+                partitioning: None,
             });
         }
 
@@ -143,6 +1035,11 @@ impl MySqlDriver {
             target_type = "bigint".to_string();
         }
 
+        // bigint unsigned exceeds every signed integer type
+        if is_unsigned && mysql_data_type_lower == "bigint" && unsigned_bigint_to_numeric(config) {
+            target_type = "numeric".to_string();
+        }
+
         target_type
     }
 
@@ -166,6 +1063,7 @@ impl MySqlDriver {
             .and_then(|r| r.on_read.as_ref())
             .and_then(|o| o.unsigned_int_to_bigint)
             .unwrap_or(false);
+        let unsigned_bigint_to_numeric = unsigned_bigint_to_numeric(config);
 
         for row in rows {
             // helper for reliable reading because mysql gives metadata as (VAR)BINARY
@@ -198,7 +1096,13 @@ impl MySqlDriver {
             // if unsigned_int_to_bigint in config is set, we convert unsigned always to bigint and set is_unsigned to false
             // because a set is_unsigned would be obsolete/confusing with bigint
             let mut is_unsigned = mysql_column_type.to_lowercase().contains("unsigned");
-            if mysql_data_type.contains("int") && is_unsigned && unsigned_int_to_bigint {
+            // bigint unsigned exceeds the signed bigint, numeric(20,0) holds every value
+            let unsigned_bigint_as_numeric =
+                mysql_data_type == "bigint" && is_unsigned && unsigned_bigint_to_numeric;
+            if unsigned_bigint_as_numeric {
+                target_data_type = "numeric".to_string();
+                is_unsigned = false;
+            } else if mysql_data_type.contains("int") && is_unsigned && unsigned_int_to_bigint {
                 target_data_type = "bigint".to_string();
                 is_unsigned = false;
             }
@@ -211,13 +1115,27 @@ impl MySqlDriver {
             };
 
             // extract extra info like AUTO_INCREMENT, ON UPDATE ...
+            // 8.0 prefixes expression defaults with DEFAULT_GENERATED ("DEFAULT_GENERATED on update CURRENT_TIMESTAMP")
+            // 8.0.23+ and MariaDB append INVISIBLE, i.e. "auto_increment INVISIBLE" for a GIPK
             let extra = get_s("Extra");
+            let is_invisible = extra
+                .split_whitespace()
+                .any(|w| w.eq_ignore_ascii_case("INVISIBLE"));
+            let extra_visible = extra
+                .split_whitespace()
+                .filter(|w| !w.eq_ignore_ascii_case("INVISIBLE"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let extra_clean = extra_visible.trim_start_matches("DEFAULT_GENERATED").trim();
             // if extra starts with "ON UPDATE", we use the remaining and assign it to on_update variable
-            let on_update = if extra.len() >= 10 && extra[..10].eq_ignore_ascii_case("ON UPDATE ") {
-                Some(extra[10..].to_string())
+            let on_update = if extra_clean.len() >= 10
+                && extra_clean[..10].eq_ignore_ascii_case("ON UPDATE ")
+            {
+                Some(extra_clean[10..].to_string())
             } else {
                 None
             };
+            let is_generated_stored = extra_clean.eq_ignore_ascii_case("STORED GENERATED");
 
             // extract length (from strings) or precision/scale from numbers
             let mut length: Option<u32> = None;
@@ -258,46 +1176,354 @@ impl MySqlDriver {
                     }
                 }
             }
+            if unsigned_bigint_as_numeric {
+                (length, precision, scale) = (None, Some(20), Some(0));
+            }
+
+            columns.push(ForgeSchemaColumn {
+                name: col_name,
+                data_type: target_data_type,
+                length,
+                precision,
+                scale,
+                is_nullable: get_s("Null") == "YES",
+                is_primary_key: get_s("Key") == "PRI",
+                is_unsigned,
+                auto_increment: extra.contains("auto_increment"),
+                default: row
+                    .try_get::<Option<Vec<u8>>, _>("Default")
+                    .ok()
+                    .flatten()
+                    .map(|b| String::from_utf8_lossy(&b).into_owned()),
+                comment: Some(get_s("Comment")),
+                on_update,
+                enum_values,
+                generation_expression: None, // filled by fetch_generation_expressions()
+                is_generated_stored,
+                is_invisible,
+                source_type: Some(mysql_column_type.to_lowercase()),
+                domain: None,
+            });
+        }
+        Ok(columns)
+    }
+
+    // extracts 'bla','fasel' from enum('bla','fasel') / set('a','b')
+    #[must_use]
+    pub fn parse_mysql_enum_values(&self, col_type: &str) -> Vec<String> {
+        let trimmed = col_type.trim();
+        let lower = trimmed.to_lowercase();
+        let without_prefix = if lower.starts_with("enum(") || lower.starts_with("set(") {
+            trimmed.split_once('(').map_or(trimmed, |x| x.1)
+        } else {
+            trimmed
+        };
+
+        without_prefix
+            .trim_end_matches(')')
+            .split(',')
+            .map(|v| v.trim_matches('\'').to_string())
+            .collect()
+    }
+
+    /// reads the expressions of generated columns (only available since 5.7)
+    pub async fn fetch_generation_expressions(
+        &self,
+        table_name: &str,
+        version: &MySqlServerVersion,
+    ) -> Result<HashMap<String, String>, Box<dyn Error>> {
+        if !version.has_generated_columns() {
+            return Ok(HashMap::new());
+        }
+
+        let rows = sqlx::query(
+            "SELECT COLUMN_NAME, GENERATION_EXPRESSION
+             FROM information_schema.COLUMNS
+             WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ?
+               AND GENERATION_EXPRESSION IS NOT NULL AND GENERATION_EXPRESSION <> ''",
+        )
+        .bind(table_name)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut expressions = HashMap::new();
+        for row in rows {
+            let get_s = |col: &str| -> String {
+                row.try_get::<Vec<u8>, _>(col)
+                    .map(|b| String::from_utf8_lossy(&b).into_owned())
+                    .or_else(|_| row.try_get::<String, _>(col))
+                    .unwrap_or_default()
+            };
+            expressions.insert(get_s("COLUMN_NAME"), get_s("GENERATION_EXPRESSION"));
+        }
+        Ok(expressions)
+    }
+
+    /// names of the generated columns of a target table, these must not be part of an INSERT;
+    /// read once per table until the next schema statements
+    pub async fn fetch_generated_column_names(
+        &self,
+        table_name: &str,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        if let Some(names) = self.metadata.generated_columns().get(table_name) {
+            return Ok(names.clone());
+        }
+        let rows = sqlx::query(
+            "SELECT COLUMN_NAME FROM information_schema.COLUMNS
+             WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ?
+               AND (EXTRA LIKE '%VIRTUAL GENERATED%' OR EXTRA LIKE '%STORED GENERATED%')",
+        )
+        .bind(table_name)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let names: Vec<String> = rows
+            .iter()
+            .filter_map(|row| {
+                row.try_get::<Vec<u8>, _>(0)
+                    .map(|b| String::from_utf8_lossy(&b).into_owned())
+                    .or_else(|_| row.try_get::<String, _>(0))
+                    .ok()
+            })
+            .collect();
+        self.metadata
+            .generated_columns()
+            .insert(table_name.to_string(), names.clone());
+        Ok(names)
+    }
+
+    /// names of all columns of a table (including invisible ones) in table order
+    pub async fn fetch_column_names(
+        &self,
+        table_name: &str,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        let rows = sqlx::query(
+            "SELECT COLUMN_NAME FROM information_schema.COLUMNS
+             WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ?
+             ORDER BY ORDINAL_POSITION",
+        )
+        .bind(table_name)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                row.try_get::<Vec<u8>, _>(0)
+                    .map(|b| String::from_utf8_lossy(&b).into_owned())
+                    .or_else(|_| row.try_get::<String, _>(0))
+                    .ok()
+            })
+            .collect())
+    }
+
+    /// names of the primary key columns of a table in key order
+    pub async fn fetch_primary_key_column_names(
+        &self,
+        table_name: &str,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        let rows = sqlx::query(
+            "SELECT COLUMN_NAME FROM information_schema.KEY_COLUMN_USAGE
+             WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ?
+               AND CONSTRAINT_NAME = 'PRIMARY'
+             ORDER BY ORDINAL_POSITION",
+        )
+        .bind(table_name)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                row.try_get::<Vec<u8>, _>(0)
+                    .map(|b| String::from_utf8_lossy(&b).into_owned())
+                    .or_else(|_| row.try_get::<String, _>(0))
+                    .ok()
+            })
+            .collect())
+    }
 
-            columns.push(ForgeSchemaColumn {
-                name: col_name,
-                data_type: target_data_type,
-                length,
-                precision,
-                scale,
-                is_nullable: get_s("Null") == "YES",
-                is_primary_key: get_s("Key") == "PRI",
-                is_unsigned,
-                auto_increment: extra.contains("auto_increment"),
-                default: row
-                    .try_get::<Option<Vec<u8>>, _>("Default")
+    /// names of the invisible columns of a table, these are not part of `SELECT *`
+    pub async fn fetch_invisible_column_names(
+        &self,
+        table_name: &str,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        let rows = sqlx::query(
+            "SELECT COLUMN_NAME FROM information_schema.COLUMNS
+             WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ?
+               AND EXTRA LIKE '%INVISIBLE%'
+             ORDER BY ORDINAL_POSITION",
+        )
+        .bind(table_name)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                row.try_get::<Vec<u8>, _>(0)
+                    .map(|b| String::from_utf8_lossy(&b).into_owned())
+                    .or_else(|_| row.try_get::<String, _>(0))
                     .ok()
-                    .flatten()
-                    .map(|b| String::from_utf8_lossy(&b).into_owned()),
-                comment: Some(get_s("Comment")),
-                on_update,
-                enum_values,
-            });
+            })
+            .collect())
+    }
+
+    /// SELECT of all columns, invisible columns must be listed explicitly
+    #[must_use]
+    pub fn build_select_all_sql(&self, table_name: &str, invisible_columns: &[String]) -> String {
+        let mut select = String::from("*");
+        for col in invisible_columns {
+            select.push_str(&format!(", `{col}`"));
         }
-        Ok(columns)
+        format!("SELECT {select} FROM `{table_name}`")
     }
 
-    // extracts 'bla','fasel' from enum('bla','fasel') / set('a','b')
+    /// SELECT of the columns with digest columns replaced by their hex SHA-256 (same as PostgreSQL),
+    /// text is hashed in the character set of its column, the same bytes as PostgreSQL for utf8mb4
     #[must_use]
-    pub fn parse_mysql_enum_values(&self, col_type: &str) -> Vec<String> {
-        let trimmed = col_type.trim();
-        let lower = trimmed.to_lowercase();
-        let without_prefix = if lower.starts_with("enum(") || lower.starts_with("set(") {
-            trimmed.split_once('(').map_or(trimmed, |x| x.1)
+    pub fn build_select_digest_sql(
+        &self,
+        table_name: &str,
+        columns: &[String],
+        digest_columns: &[String],
+    ) -> String {
+        let select = columns
+            .iter()
+            .map(|col| {
+                if digest_columns.contains(col) {
+                    format!("SHA2(`{col}`, 256) AS `{col}`")
+                } else {
+                    format!("`{col}`")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("SELECT {select} FROM `{table_name}`")
+    }
+
+    /// selects the rows of a verification sample: first 32 bits of the MD5 of the key columns,
+    /// identical to the PostgreSQL predicate for keys with the same text representation
+    #[must_use]
+    pub fn build_sample_predicate(
+        &self,
+        key_columns: &[String],
+        sample: ForgeVerifySample,
+    ) -> String {
+        // a seed salts the hash, so another seed selects other rows
+        let columns = (sample.seed != 0)
+            .then(|| format!("'{}'", sample.seed))
+            .into_iter()
+            .chain(key_columns.iter().map(|col| format!("`{col}`")))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            "CONV(SUBSTRING(MD5(CONCAT_WS('|', {columns})), 1, 8), 16, 10) % {} < {}",
+            ForgeVerifySample::BUCKETS,
+            sample.basis_points
+        )
+    }
+
+    /// WHERE/ORDER BY of a keyset stream, i.e. " WHERE (`a`, `b`) > (?, ?) ORDER BY `a`, `b`"
+    #[must_use]
+    pub fn build_keyset_clause(&self, key_columns: &[String], after: bool) -> String {
+        let columns = key_columns
+            .iter()
+            .map(|col| format!("`{col}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if after {
+            let placeholders = vec!["?"; key_columns.len()].join(", ");
+            format!(" WHERE ({columns}) > ({placeholders}) ORDER BY {columns}")
         } else {
-            trimmed
+            format!(" ORDER BY {columns}")
+        }
+    }
+
+    /// WHERE of the row with the given key values, i.e. " WHERE (`a`, `b`) = (?, ?)"
+    #[must_use]
+    pub fn build_key_clause(&self, key_columns: &[String]) -> String {
+        let columns = key_columns
+            .iter()
+            .map(|col| format!("`{col}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let placeholders = vec!["?"; key_columns.len()].join(", ");
+        format!(" WHERE ({columns}) = ({placeholders})")
+    }
+
+    /// SELECT of the key values of the last row in key order
+    #[must_use]
+    pub fn build_last_key_sql(&self, table_name: &str, key_columns: &[String]) -> String {
+        let columns = key_columns
+            .iter()
+            .map(|col| format!("`{col}`"))
+            .collect::<Vec<_>>();
+        let order = columns
+            .iter()
+            .map(|col| format!("{col} DESC"))
+            .collect::<Vec<_>>();
+        format!(
+            "SELECT {} FROM `{table_name}` ORDER BY {} LIMIT 1",
+            columns.join(", "),
+            order.join(", ")
+        )
+    }
+
+    /// GRANT statement for one source grant, `None` if the grantee is skipped
+    /// or none of the privileges exists on MySQL table level
+    #[must_use]
+    pub fn build_grant_sql(
+        &self,
+        grant: &ForgeSchemaGrant,
+        config: &ForgeConfig,
+    ) -> Option<String> {
+        const TABLE_PRIVILEGES: [&str; 12] = [
+            "SELECT",
+            "INSERT",
+            "UPDATE",
+            "DELETE",
+            "CREATE",
+            "DROP",
+            "REFERENCES",
+            "INDEX",
+            "ALTER",
+            "CREATE VIEW",
+            "SHOW VIEW",
+            "TRIGGER",
+        ];
+
+        let grantee = grants::map_grantee(&grant.grantee, config)?;
+        // plain role names (i.e. from postgres) become users on any host
+        let grantee = if grantee.contains('@') {
+            grantee
+        } else {
+            format!("'{grantee}'@'%'")
         };
 
-        without_prefix
-            .trim_end_matches(')')
-            .split(',')
-            .map(|v| v.trim_matches('\'').to_string())
-            .collect()
+        let privileges: Vec<&str> = grant
+            .privileges
+            .iter()
+            .map(String::as_str)
+            .filter(|p| {
+                let supported = TABLE_PRIVILEGES.contains(p);
+                if !supported {
+                    warn!(table = %grant.table_name, privilege = %p, "privilege not supported on mysql table level, skipped");
+                }
+                supported
+            })
+            .collect();
+
+        if privileges.is_empty() {
+            return None;
+        }
+
+        Some(format!(
+            "GRANT {} ON `{}` TO {};",
+            privileges.join(", "),
+            grant.table_name,
+            grantee
+        ))
     }
 
     pub async fn fetch_indices(
@@ -321,8 +1547,13 @@ impl MySqlDriver {
 
             let index_name = get_s("Key_name");
             let column_name = get_s("Column_name");
+            // functional key parts (8.0.13+) have no column but the expression of
+            // information_schema.STATISTICS.EXPRESSION, older servers have no such column
+            let expression =
+                Some(get_s("Expression").replace("\\'", "'")).filter(|e| !e.is_empty());
             let index_type = get_s("Index_type");
-            let seq_in_index = row.try_get::<u32, _>("Seq_in_index").unwrap_or(1);
+            // integer types of SHOW INDEX differ between 5.7 (BIGINT) and 8.0 (INT UNSIGNED)
+            let seq_in_index = get_metadata_u64(&row, "Seq_in_index").unwrap_or(1);
 
             let seq_index = if seq_in_index > 0 {
                 (seq_in_index - 1) as usize
@@ -330,14 +1561,10 @@ impl MySqlDriver {
                 0
             };
 
-            let sub_part = row
-                .try_get::<Option<i64>, _>("Sub_part")
-                .ok()
-                .flatten()
-                .map(|v| v as u32);
+            let sub_part = get_metadata_u64(&row, "Sub_part").map(|v| v as u32);
 
             // Non_unique is usually Integer (0 = Unique/PK, 1 = Normal)
-            let is_unique = row.try_get::<i64, _>("Non_unique").unwrap_or(1) == 0;
+            let is_unique = get_metadata_u64(&row, "Non_unique").unwrap_or(1) == 0;
 
             // we ignore primary, because is it covered by ForgeSchemaColumn.is_primary_key
             if index_name == "PRIMARY" {
@@ -353,6 +1580,7 @@ impl MySqlDriver {
                     is_unique,
                     index_type: None,
                     column_prefixes: None,
+                    expressions: None,
                 });
 
             if entry.index_type.is_none() && !index_type.is_empty() {
@@ -373,29 +1601,135 @@ impl MySqlDriver {
                 }
                 prefixes[seq_index] = sub_part;
             }
+
+            if expression.is_some() || entry.expressions.is_some() {
+                let expressions = entry
+                    .expressions
+                    .get_or_insert_with(|| vec![None; entry.columns.len()]);
+                if expressions.len() < entry.columns.len() {
+                    expressions.resize(entry.columns.len(), None);
+                }
+                expressions[seq_index] = expression;
+            }
         }
 
-        // convert map into Vec
-        Ok(indices_map.into_values().collect())
+        // convert map into Vec, sorted by name for a stable schema file and statement order
+        let mut indices: Vec<ForgeSchemaIndex> = indices_map.into_values().collect();
+        indices.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(indices)
+    }
+
+    /// Partition method, expression and partitions of a partitioned table, subpartitions are
+    /// not preserved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `information_schema.PARTITIONS` cannot be read.
+    pub async fn fetch_partitioning(
+        &self,
+        table_name: &str,
+    ) -> Result<Option<ForgeSchemaPartitioning>, Box<dyn Error>> {
+        let rows = sqlx::query(
+            "SELECT PARTITION_NAME, PARTITION_METHOD, PARTITION_EXPRESSION, PARTITION_DESCRIPTION
+             FROM information_schema.PARTITIONS
+             WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ? AND PARTITION_NAME IS NOT NULL
+             ORDER BY PARTITION_ORDINAL_POSITION, SUBPARTITION_ORDINAL_POSITION",
+        )
+        .bind(table_name)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut partitioning: Option<ForgeSchemaPartitioning> = None;
+        for row in rows {
+            // helper for reliable reading of metadata
+            let get_s = |col: &str| -> Option<String> {
+                row.try_get::<Option<Vec<u8>>, _>(col)
+                    .ok()
+                    .flatten()
+                    .map(|b| String::from_utf8_lossy(&b).into_owned())
+            };
+            let name = get_s("PARTITION_NAME").unwrap_or_default();
+            let method = get_s("PARTITION_METHOD").unwrap_or_default();
+            let partitioning = partitioning.get_or_insert_with(|| ForgeSchemaPartitioning {
+                strategy: method.to_lowercase(),
+                key: get_s("PARTITION_EXPRESSION").unwrap_or_default(),
+                partitions: Vec::new(),
+            });
+            // one row per subpartition
+            if partitioning.partitions.iter().any(|p| p.name == name) {
+                continue;
+            }
+            let description = get_s("PARTITION_DESCRIPTION");
+            partitioning.partitions.push(ForgeSchemaPartition {
+                bound: mysql_partition_bound(&method, description.as_deref()),
+                name,
+            });
+        }
+        Ok(partitioning)
     }
 
     pub async fn fetch_foreign_keys(
         &self,
-        _table_name: &str,
+        table_name: &str,
     ) -> Result<Vec<ForgeSchemaForeignKey>, Box<dyn Error>> {
-        // TODO implement after first release
-        Ok(Vec::new())
+        let rows = sqlx::query(
+            "SELECT kcu.CONSTRAINT_NAME, kcu.COLUMN_NAME, kcu.REFERENCED_TABLE_NAME,
+                    kcu.REFERENCED_COLUMN_NAME, rc.DELETE_RULE, rc.UPDATE_RULE
+             FROM information_schema.KEY_COLUMN_USAGE kcu
+             JOIN information_schema.REFERENTIAL_CONSTRAINTS rc
+               ON rc.CONSTRAINT_SCHEMA = kcu.CONSTRAINT_SCHEMA
+              AND rc.CONSTRAINT_NAME = kcu.CONSTRAINT_NAME
+              AND rc.TABLE_NAME = kcu.TABLE_NAME
+             WHERE kcu.TABLE_SCHEMA = DATABASE() AND kcu.TABLE_NAME = ?
+               AND kcu.REFERENCED_TABLE_NAME IS NOT NULL
+             ORDER BY kcu.CONSTRAINT_NAME, kcu.ORDINAL_POSITION",
+        )
+        .bind(table_name)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut fks = Vec::new();
+        for row in rows {
+            // helper for reliable reading of metadata
+            let get_s = |col: &str| -> String {
+                row.try_get::<Vec<u8>, _>(col)
+                    .map(|b| String::from_utf8_lossy(&b).into_owned())
+                    .or_else(|_| row.try_get::<String, _>(col))
+                    .unwrap_or_default()
+            };
+            fks.push(ForgeSchemaForeignKey {
+                name: get_s("CONSTRAINT_NAME"),
+                column: get_s("COLUMN_NAME"),
+                ref_table: get_s("REFERENCED_TABLE_NAME"),
+                ref_column: get_s("REFERENCED_COLUMN_NAME"),
+                on_delete: Some(get_s("DELETE_RULE")).filter(|r| !r.is_empty()),
+                on_update: Some(get_s("UPDATE_RULE")).filter(|r| !r.is_empty()),
+            });
+        }
+        Ok(fks)
     }
 
+    /// Column type written to `MySQL`, types without the parameters MySQL needs are widened.
     #[must_use]
-    pub fn field_migration_sql(&self, field: ForgeSchemaColumn, config: &ForgeConfig) -> String {
-        let target_types = config.get_type_list("mysql", "on_write");
+    pub fn column_write_type(&self, field: &ForgeSchemaColumn, config: &ForgeConfig) -> String {
+        let sql_type = config.map_write_type(ForgeDialect::Mysql, &field.data_type);
+        match sql_type.as_str() {
+            // PostgreSQL varchar without length is unbounded
+            "varchar"
+                if field.length.is_none()
+                    && field.data_type.eq_ignore_ascii_case("character varying") =>
+            {
+                "longtext".to_string()
+            }
+            // DECIMAL without precision is DECIMAL(10,0), PostgreSQL numeric is arbitrary
+            "decimal" | "numeric" if field.precision.is_none() => "decimal(65,30)".to_string(),
+            _ => sql_type,
+        }
+    }
 
-        let data_type_lower = field.data_type.to_lowercase();
-        let sql_type = target_types
-            .and_then(|t| t.get(&data_type_lower))
-            .cloned()
-            .unwrap_or(data_type_lower);
+    #[must_use]
+    pub fn field_migration_sql(&self, field: ForgeSchemaColumn, config: &ForgeConfig) -> String {
+        let sql_type = self.column_write_type(&field, config);
 
         let mut ret = String::new();
 
@@ -406,17 +1740,17 @@ impl MySqlDriver {
         ret.push_str(&format!(" {sql_type}"));
 
         match sql_type.as_str() {
-            "decimal" => {
+            "decimal" | "numeric" => {
                 if let (Some(p), Some(s)) = (field.precision, field.scale) {
                     ret.push_str(&format!("({p},{s})"));
                 } else if let Some(p) = field.precision {
                     ret.push_str(&format!("({p})"));
                 }
             }
-            "tinyint" | "smallint" | "mediumint" | "int" | "integer" | "bigint" => {
-                if field.is_unsigned {
-                    ret.push_str(" unsigned");
-                }
+            "tinyint" | "smallint" | "mediumint" | "int" | "integer" | "bigint"
+                if field.is_unsigned =>
+            {
+                ret.push_str(" unsigned");
             }
 
             "varchar" | "char" | "binary" | "varbinary" | "bit" | "datetime" | "timestamp"
@@ -435,6 +1769,23 @@ impl MySqlDriver {
             _ => {}
         }
 
+        // generated columns have no NULL/DEFAULT clauses of their own
+        if let Some(ref expr) = field.generation_expression {
+            let storage = if field.is_generated_stored {
+                "STORED"
+            } else {
+                "VIRTUAL"
+            };
+            ret.push_str(&format!(" GENERATED ALWAYS AS ({expr}) {storage}"));
+            if !field.is_nullable {
+                ret.push_str(" NOT NULL");
+            }
+            if field.is_invisible {
+                ret.push_str(" INVISIBLE");
+            }
+            return ret;
+        }
+
         let sql_type_lower = sql_type.to_lowercase();
         let skip_default = sql_type_lower.contains("text")
             || sql_type_lower.contains("blob")
@@ -450,14 +1801,19 @@ impl MySqlDriver {
             ret.push_str(" NOT NULL");
         }
 
-        // Default Value
+        // Default Value, sequences of auto-increment columns (PostgreSQL nextval) are implied
         if let Some(ref def) = field.default
             && !skip_default
+            && !field.auto_increment
         {
-            if def.to_lowercase() == "current_timestamp" {
-                ret.push_str(" DEFAULT CURRENT_TIMESTAMP");
-            } else {
-                ret.push_str(&format!(" DEFAULT '{def}'"));
+            let fsp = fractional_seconds(&sql_type, field.length);
+            match mysql_default_sql(def, &sql_type, fsp) {
+                Some(value) => ret.push_str(&format!(" DEFAULT {value}")),
+                None => warn!(
+                    column = %field.name,
+                    default = %def,
+                    "default expression has no MySQL counterpart and is dropped"
+                ),
             }
         }
 
@@ -471,6 +1827,10 @@ impl MySqlDriver {
             ret.push_str(&format!(" ON UPDATE {on_upd}"));
         }
 
+        if field.is_invisible {
+            ret.push_str(" INVISIBLE");
+        }
+
         ret
     }
 
@@ -489,7 +1849,16 @@ impl MySqlDriver {
             col_defs.push(def);
 
             if col.is_primary_key {
-                pks.push(format!("`{}`", col.name));
+                if needs_key_prefix(&self.column_write_type(col, config)) {
+                    warn!(
+                        table = %table.name,
+                        column = %col.name,
+                        "primary key on a text column is limited to a prefix of {KEY_PREFIX_LENGTH} characters"
+                    );
+                    pks.push(format!("`{}`({KEY_PREFIX_LENGTH})", col.name));
+                } else {
+                    pks.push(format!("`{}`", col.name));
+                }
             }
         }
 
@@ -497,8 +1866,26 @@ impl MySqlDriver {
             col_defs.push(format!("  PRIMARY KEY ({})", pks.join(", ")));
         }
 
+        let partition_by = match table.partitioning {
+            Some(ref p) if !p.partitions.is_empty() && !p.has_postgres_bounds() => {
+                mysql_partition_by_sql(p)
+            }
+            Some(_) => {
+                warn!(
+                    table = %table.name,
+                    "partitioning of the source cannot be expressed on MySQL, the table is created without partitions"
+                );
+                String::new()
+            }
+            None => String::new(),
+        };
         format!(
-            "CREATE TABLE `{}` (\n{}\n) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;",
+            "CREATE TABLE {}`{}` (\n{}\n) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4{partition_by};",
+            if self.idempotent_ddl {
+                "IF NOT EXISTS "
+            } else {
+                ""
+            },
             table.name,
             col_defs.join(",\n")
         )
@@ -513,18 +1900,61 @@ impl MySqlDriver {
         let sql = self.build_mysql_create_table_sql(dst_table, config);
         stmts.push(sql);
         // after table is created, create all non-primary-key indices
-        for index in &dst_table.indices {
-            let idx_sql = self.build_mysql_create_index_sql(&dst_table.name, index);
+        for index in sorted_by_name(&dst_table.indices, |i| &i.name) {
+            let index = self.with_key_prefixes(dst_table, index, config);
+            let idx_sql = self.build_mysql_create_index_sql(&dst_table.name, &index);
             stmts.push(idx_sql);
         }
         Ok(stmts)
     }
 
+    /// index with a prefix length on its text and blob columns, i.e. of a PostgreSQL source
+    #[must_use]
+    pub fn with_key_prefixes(
+        &self,
+        table: &ForgeSchemaTable,
+        index: &ForgeSchemaIndex,
+        config: &ForgeConfig,
+    ) -> ForgeSchemaIndex {
+        let mut index = index.clone();
+        let index_type = index.index_type.as_deref().unwrap_or("").to_uppercase();
+        if index_type == "FULLTEXT" || index_type == "SPATIAL" {
+            return index;
+        }
+        let mut prefixes = index
+            .column_prefixes
+            .take()
+            .unwrap_or_else(|| vec![None; index.columns.len()]);
+        prefixes.resize(index.columns.len(), None);
+        for (name, prefix) in index.columns.iter().zip(prefixes.iter_mut()) {
+            let needs_prefix = table
+                .columns
+                .iter()
+                .find(|c| &c.name == name)
+                .is_some_and(|c| needs_key_prefix(&self.column_write_type(c, config)));
+            if prefix.is_none() && needs_prefix {
+                *prefix = Some(KEY_PREFIX_LENGTH);
+            }
+        }
+        if prefixes.iter().any(Option::is_some) {
+            index.column_prefixes = Some(prefixes);
+        }
+        index
+    }
+
     pub fn delete_table_migration_sql(
         &self,
         dst_table: &ForgeSchemaTable,
     ) -> Result<Vec<String>, Box<dyn Error>> {
-        let sql = format!("DROP TABLE `{}`;", dst_table.name);
+        let sql = format!(
+            "DROP TABLE {}`{}`;",
+            if self.idempotent_ddl {
+                "IF EXISTS "
+            } else {
+                ""
+            },
+            dst_table.name
+        );
         Ok(vec![sql])
     }
     pub fn alter_table_migration_sql(
@@ -572,7 +2002,7 @@ impl MySqlDriver {
 
         // Check all columns in DST (current state)
         if destructive {
-            for dst_col in &dst_table.columns {
+            for dst_col in sorted_by_name(&dst_table.columns, |c| &c.name) {
                 if !src_cols.contains_key(&dst_col.name) {
                     // In DST but NOT in SRC -> DROP (if destructive)
                     all_statements.push(self.drop_column_migration(&dst_table.name, &dst_col.name));
@@ -590,8 +2020,10 @@ impl MySqlDriver {
             dst_idx_map.insert(idx.name.clone(), idx);
         }
 
-        // Check all indices in SRC (desired state)
-        for (name, src_idx) in &src_idx_map {
+        // Check all indices in SRC (desired state), sorted by name for a stable statement order
+        for src_idx in sorted_by_name(&src_table.indices, |i| &i.name) {
+            let src_idx = &self.with_key_prefixes(src_table, src_idx, config);
+            let name = &src_idx.name;
             match dst_idx_map.get(name) {
                 None => {
                     // In SRC but NOT in DST -> CREATE
@@ -613,10 +2045,15 @@ impl MySqlDriver {
 
         // Check all indices in DST (current state)
         if destructive {
-            for name in dst_idx_map.keys() {
-                if !src_idx_map.contains_key(name) {
+            for dst_idx in sorted_by_name(&dst_table.indices, |i| &i.name) {
+                // MySQL creates an index for a foreign key without one, it cannot be dropped
+                let foreign_key_index = dst_table
+                    .foreign_keys
+                    .iter()
+                    .any(|fk| fk.name == dst_idx.name);
+                if !src_idx_map.contains_key(&dst_idx.name) && !foreign_key_index {
                     // In DST but NOT in SRC -> DROP (if destructive)
-                    let sql = self.build_mysql_drop_index_sql(&dst_table.name, name);
+                    let sql = self.build_mysql_drop_index_sql(&dst_table.name, &dst_idx.name);
                     all_statements.push(sql);
                 }
             }
@@ -654,7 +2091,8 @@ impl MySqlDriver {
 
         let mut changed = src_col.data_type != dst_col.data_type
             || src_col.length != dst_col.length
-            || src_col.is_nullable != dst_col.is_nullable;
+            || src_col.is_nullable != dst_col.is_nullable
+            || src_col.is_invisible != dst_col.is_invisible;
 
         // special handling for FLOAT: numerical comparison of default values
         if !changed {
@@ -716,6 +2154,10 @@ impl MySqlDriver {
             .iter()
             .enumerate()
             .map(|(i, c)| {
+                // functional key parts need their own parentheses
+                if let Some(expression) = index.expression(i) {
+                    return format!("({expression})");
+                }
                 let prefix = index
                     .column_prefixes
                     .as_ref()
@@ -735,6 +2177,61 @@ impl MySqlDriver {
         )
     }
 
+    /// builds ADD CONSTRAINT ... FOREIGN KEY Statement
+    #[must_use]
+    pub(crate) fn build_mysql_add_foreign_key_sql(
+        &self,
+        table_name: &str,
+        constraint: &ForeignKeyConstraint,
+    ) -> String {
+        let quoted = |names: &[String]| {
+            names
+                .iter()
+                .map(|n| format!("`{n}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let mut sql = format!(
+            "ALTER TABLE `{table_name}` ADD CONSTRAINT `{}` FOREIGN KEY ({}) REFERENCES `{}` ({})",
+            constraint.name,
+            quoted(&constraint.columns),
+            constraint.ref_table,
+            quoted(&constraint.ref_columns)
+        );
+        if let Some(ref action) = constraint.on_delete {
+            sql.push_str(&format!(" ON DELETE {action}"));
+        }
+        if let Some(ref action) = constraint.on_update {
+            sql.push_str(&format!(" ON UPDATE {action}"));
+        }
+        sql.push(';');
+        sql
+    }
+
+    /// statements of the foreign key changes: the drops before and the additions after the
+    /// other schema changes
+    pub(crate) fn foreign_key_migration_sql(
+        &self,
+        changes: &ForeignKeyChanges,
+    ) -> (Vec<String>, Vec<String>) {
+        let drops = changes
+            .drop
+            .iter()
+            .map(|(table, constraint)| {
+                format!(
+                    "ALTER TABLE `{table}` DROP FOREIGN KEY `{}`;",
+                    constraint.name
+                )
+            })
+            .collect();
+        let adds = changes
+            .add
+            .iter()
+            .map(|(table, constraint)| self.build_mysql_add_foreign_key_sql(table, constraint))
+            .collect();
+        (drops, adds)
+    }
+
     /// builds DROP INDEX Statement
     #[must_use]
     pub fn build_mysql_drop_index_sql(&self, table_name: &str, index_name: &str) -> String {
@@ -776,6 +2273,9 @@ impl MySqlDriver {
             if a_prefixes.get(i) != b_prefixes.get(i) {
                 return false;
             }
+            if a.expression(i) != b.expression(i) {
+                return false;
+            }
         }
         true
     }
@@ -987,64 +2487,92 @@ impl MySqlDriver {
     } // map_row_to_universal_values
 } // impl MySqlDriver
 
-#[async_trait]
-impl DatabaseDriver for MySqlDriver {
-    async fn db_is_empty(&self) -> Result<bool, Box<dyn Error>> {
-        let count: i64 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM information_schema.tables WHERE table_schema = DATABASE()",
-        )
-        .fetch_one(&self.pool)
-        .await?;
-
-        Ok(count == 0)
+impl SchemaStatements for MySqlDriver {
+    fn create_table(
+        &self,
+        table: &ForgeSchemaTable,
+        config: &ForgeConfig,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        self.create_table_migration_sql(table, config)
     }
 
-    async fn fetch_schema(&self, config: &ForgeConfig) -> Result<ForgeSchema, Box<dyn Error>> {
-        // get database name from database
-        let db_name: String = sqlx::query_scalar("SELECT DATABASE()")
-            .fetch_one(&self.pool)
-            .await?;
-
-        // get all basic table structures
-        let mut tables = self.fetch_tables().await?;
+    fn drop_table(&self, table: &ForgeSchemaTable) -> Result<Vec<String>, Box<dyn Error>> {
+        self.delete_table_migration_sql(table)
+    }
 
-        // get details of all tables
-        for table in &mut tables {
-            // fetch all columns with applying mapping config
-            table.columns = self.fetch_columns(&table.name, config).await?;
+    fn alter_table(
+        &self,
+        source: &ForgeSchemaTable,
+        target: &ForgeSchemaTable,
+        config: &ForgeConfig,
+        destructive: bool,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        self.alter_table_migration_sql(source, target, config, destructive)
+    }
 
-            // fetch all indices (no mapping conf for them)
-            table.indices = self.fetch_indices(&table.name).await?;
+    fn foreign_keys(&self, changes: &ForeignKeyChanges) -> (Vec<String>, Vec<String>) {
+        self.foreign_key_migration_sql(changes)
+    }
+}
 
-            // fetch all foreign keys (no mapping conf for them)
-            table.foreign_keys = self.fetch_foreign_keys(&table.name).await?;
-        }
+#[async_trait]
+impl DatabaseDriver for MySqlDriver {
+    async fn db_is_empty(&self) -> Result<bool, Box<dyn Error>> {
+        let count: i64 = self
+            .retry
+            .run("db_is_empty", || {
+                sqlx::query_scalar(
+                    "SELECT COUNT(*) FROM information_schema.tables WHERE table_schema = DATABASE()",
+                )
+                .fetch_one(&self.pool)
+            })
+            .await?;
 
-        Ok(ForgeSchema {
-            metadata: ForgeSchemaMetadata {
-                source_system: "mysql".to_string(),
-                source_database_name: db_name,
-                created_at: chrono::Local::now().to_rfc3339(),
-                forge_version: env!("CARGO_PKG_VERSION").to_string(),
-                config_file: String::new(),
-            },
-            tables,
-        })
+        Ok(count == 0)
+    }
+
+    #[tracing::instrument(name = "fetch_schema", skip_all, fields(engine = "mysql"))]
+    async fn fetch_schema(&self, config: &ForgeConfig) -> Result<ForgeSchema, Box<dyn Error>> {
+        self.retry
+            .run("fetch_schema", || self.read_schema(config))
+            .await
     }
 
+    #[tracing::instrument(
+        name = "apply_schema",
+        skip_all,
+        fields(engine = "mysql", dry_run = options.dry_run, destructive = options.destructive)
+    )]
     async fn diff_and_apply_schema(
         &self,
         source_schema: &ForgeSchema,
         config: &ForgeConfig,
-        dry_run: bool,
-        verbose: bool,
-        destructive: bool,
+        filter: &ForgeObjectFilter,
+        options: MigrateOptions,
     ) -> Result<Vec<String>, Box<dyn Error>> {
+        let MigrateOptions {
+            dry_run,
+            verbose,
+            destructive,
+            defer_foreign_keys,
+            cancel,
+            applied,
+        } = options;
         // source = new schema (from source db)
         // target = actual schema (of target that will be changed)
 
         let target_schema = self.fetch_schema(config).await?;
-        let mut all_statements = Vec::new();
+        // foreign keys are dropped before their columns change and added after all tables exist
+        let (mut all_statements, add_foreign_keys) = if defer_foreign_keys {
+            (Vec::new(), Vec::new())
+        } else {
+            self.foreign_key_migration_sql(&ForeignKeyChanges::between(
+                source_schema,
+                &target_schema,
+                filter,
+                destructive,
+            ))
+        };
 
         let mut source_tables: HashMap<String, &ForgeSchemaTable> = HashMap::new();
         for table in &source_schema.tables {
@@ -1056,17 +2584,46 @@ impl DatabaseDriver for MySqlDriver {
             target_tables.insert(table.name.clone(), table);
         }
 
+        // row estimates decide which added NOT NULL columns are backfilled in chunks
+        let estimates = if config.get_ddl().backfill_min_rows.is_some() {
+            self.estimate_table_sizes().await?
+        } else {
+            Vec::new()
+        };
+
         // compare all tables that are in source_schema
         for source_table in &source_schema.tables {
+            if !filter.includes_table(&source_table.name) {
+                continue;
+            }
             if let Some(target_table) = target_tables.get(&source_table.name) {
-                // if in source and target -> alter_table_migration_sql()
-                let stmts = self.alter_table_migration_sql(
+                let backfill = backfill_plan(
+                    self,
                     source_table,
                     target_table,
+                    &estimates,
                     config,
-                    destructive,
-                )?;
-                all_statements.extend(stmts);
+                    |name| format!("`{name}`"),
+                )
+                .await?;
+                if let Some(backfill) = backfill {
+                    all_statements.extend(self.alter_table_migration_sql(
+                        &backfill.nullable_table(source_table),
+                        target_table,
+                        config,
+                        destructive,
+                    )?);
+                    all_statements.extend(self.backfill_sql(&source_table.name, &backfill, config));
+                } else {
+                    // if in source and target -> alter_table_migration_sql()
+                    let stmts = self.alter_table_migration_sql(
+                        source_table,
+                        target_table,
+                        config,
+                        destructive,
+                    )?;
+                    all_statements.extend(stmts);
+                }
             } else {
                 // if in source but not in target -> create_table_migration_sql()
                 let stmts = self.create_table_migration_sql(source_table, config)?;
@@ -1076,28 +2633,82 @@ impl DatabaseDriver for MySqlDriver {
 
         // if in target, but not in source AND destructive -> delete_table_migration_sql()
         if destructive {
-            for table in &target_schema.tables {
-                if !source_tables.contains_key(&table.name) {
+            for table in sorted_by_name(&target_schema.tables, |t| &t.name) {
+                if !source_tables.contains_key(&table.name) && filter.includes_table(&table.name) {
                     let stmts = self.delete_table_migration_sql(table)?;
                     all_statements.extend(stmts);
                 }
             }
         }
+        all_statements.extend(add_foreign_keys);
 
         if !dry_run {
-            let mut success_count = 0;
-            for sql in &all_statements {
-                sqlx::query(sql).execute(&self.pool).await?;
-                success_count += 1;
-            }
+            self.execute_schema_statements(&all_statements, config, &cancel, &applied)
+                .await?;
             if verbose {
-                println!("{success_count} SQL-Statements executed.");
+                info!(
+                    statements = all_statements.len(),
+                    "schema statements executed"
+                );
             }
         }
 
         Ok(all_statements)
     }
 
+    #[tracing::instrument(
+        name = "apply_foreign_keys",
+        skip_all,
+        fields(engine = "mysql", dry_run = options.dry_run)
+    )]
+    async fn apply_foreign_keys(
+        &self,
+        schema: &ForgeSchema,
+        config: &ForgeConfig,
+        filter: &ForgeObjectFilter,
+        options: MigrateOptions,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        let target_schema = self.fetch_schema(config).await?;
+        let changes =
+            ForeignKeyChanges::between(schema, &target_schema, filter, options.destructive);
+        let (mut statements, adds) = self.foreign_key_migration_sql(&changes);
+        statements.extend(adds);
+        if !options.dry_run {
+            self.execute_schema_statements(&statements, config, &options.cancel, &options.applied)
+                .await?;
+            info!(statements = statements.len(), "foreign keys applied");
+        }
+        Ok(statements)
+    }
+
+    #[tracing::instrument(name = "rollback_plan", skip_all, fields(engine = "mysql"))]
+    async fn rollback_schema_plan(
+        &self,
+        source_schema: &ForgeSchema,
+        config: &ForgeConfig,
+        filter: &ForgeObjectFilter,
+        destructive: bool,
+    ) -> Result<ForgeRollbackPlan, Box<dyn Error>> {
+        // the current target is the state the rollback restores
+        let target_schema = self.fetch_schema(config).await?;
+        rollback_schema_plan(
+            self,
+            source_schema,
+            &target_schema,
+            config,
+            filter,
+            destructive,
+        )
+    }
+
+    async fn execute_statements(&self, statements: &[String]) -> Result<(), Box<dyn Error>> {
+        for sql in statements {
+            debug!(statement = %sql, "executing statement");
+            sqlx::query(sql).execute(&self.pool).await?;
+        }
+        Ok(())
+    }
+
     async fn stream_table_data(
         &self,
         table_name: &str,
@@ -1111,7 +2722,27 @@ impl DatabaseDriver for MySqlDriver {
         >,
         Box<dyn Error>,
     > {
-        let query_string = format!("SELECT * FROM `{table_name}`");
+        // with `resume_streams`, tables with a primary key are read in key order and reconnect
+        // after the last key
+        if self.retry.resumes_streams() {
+            let key_columns = self.fetch_primary_key_column_names(table_name).await?;
+            if !key_columns.is_empty() {
+                return Ok(resuming_stream(
+                    self,
+                    table_name.to_string(),
+                    key_columns,
+                    None,
+                    self.retry.clone(),
+                ));
+            }
+        }
+
+        let invisible = self.fetch_invisible_column_names(table_name).await?;
+        let query_string = format!(
+            "{}{}",
+            self.build_select_all_sql(table_name, &invisible),
+            filtered_clause(&self.row_filters, table_name, "")
+        );
 
         let stream = async_stream::try_stream! {
             let mut rows = sqlx::query(&query_string).fetch(&self.pool);
@@ -1136,6 +2767,9 @@ impl DatabaseDriver for MySqlDriver {
         &self,
         table_name: &str,
         order_by: &[String],
+        sample: Option<ForgeVerifySample>,
+        key_range: Option<ForgeKeyRange>,
+        digest_columns: &[String],
     ) -> Result<
         Pin<
             Box<
@@ -1149,15 +2783,50 @@ impl DatabaseDriver for MySqlDriver {
         let order_clause = if order_by.is_empty() {
             String::new()
         } else {
+            // MySQL sorts NULLs first, "col IS NULL" moves them to the end
             let columns = order_by
                 .iter()
-                .map(|col| format!("`{col}`"))
+                .map(|col| match self.null_ordering {
+                    ForgeNullOrdering::First => format!("`{col}`"),
+                    ForgeNullOrdering::Last => format!("`{col}` IS NULL, `{col}`"),
+                })
                 .collect::<Vec<_>>()
                 .join(", ");
             format!(" ORDER BY {columns}")
         };
 
-        let query_string = format!("SELECT * FROM `{table_name}`{order_clause}");
+        let predicates: Vec<String> = order_by
+            .first()
+            .map(|key| {
+                let sample = sample.map(|sample| self.build_sample_predicate(order_by, sample));
+                let range = key_range
+                    .map(|range| format!("`{key}` BETWEEN {} AND {}", range.from, range.to));
+                sample.into_iter().chain(range)
+            })
+            .into_iter()
+            .flatten()
+            .collect();
+        let where_clause = if predicates.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", predicates.join(" AND "))
+        };
+
+        let select = if digest_columns.is_empty() {
+            let invisible = self.fetch_invisible_column_names(table_name).await?;
+            self.build_select_all_sql(table_name, &invisible)
+        } else {
+            let columns = self.fetch_column_names(table_name).await?;
+            self.build_select_digest_sql(table_name, &columns, digest_columns)
+        };
+        let query_string = format!(
+            "{select}{}",
+            filtered_clause(
+                &self.row_filters,
+                table_name,
+                &format!("{where_clause}{order_clause}")
+            )
+        );
 
         let stream = async_stream::try_stream! {
             let mut rows = sqlx::query(&query_string).fetch(&self.pool);
@@ -1178,6 +2847,104 @@ impl DatabaseDriver for MySqlDriver {
         Ok(Box::pin(stream))
     }
 
+    async fn stream_table_data_after(
+        &self,
+        table_name: &str,
+        key_columns: &[String],
+        after: Option<&[ForgeUniversalDataField]>,
+    ) -> Result<
+        Pin<
+            Box<
+                dyn Stream<Item = Result<IndexMap<String, ForgeUniversalDataField>, ForgeError>>
+                    + Send
+                    + '_,
+            >,
+        >,
+        Box<dyn Error>,
+    > {
+        let invisible = self.fetch_invisible_column_names(table_name).await?;
+        let query_string = format!(
+            "{}{}",
+            self.build_select_all_sql(table_name, &invisible),
+            filtered_clause(
+                &self.row_filters,
+                table_name,
+                &self.build_keyset_clause(key_columns, after.is_some())
+            )
+        );
+        let after = after.map(<[ForgeUniversalDataField]>::to_vec);
+
+        let stream = async_stream::try_stream! {
+            let mut query = sqlx::query(&query_string);
+            for value in after.iter().flatten() {
+                query = self.bind_universal(query, value);
+            }
+            let mut rows = query.fetch(&self.pool);
+
+            while let Some(row) = rows.next().await {
+                let row: MySqlRow = row?;
+                let values = self.map_row_to_universal_values(&row)?;
+
+                let mut row_map = IndexMap::new();
+                for (col, val) in row.columns().iter().zip(values) {
+                    row_map.insert(col.name().to_string(), val);
+                }
+
+                yield row_map;
+            }
+        };
+
+        Ok(Box::pin(stream))
+    }
+
+    async fn fetch_last_key(
+        &self,
+        table_name: &str,
+        key_columns: &[String],
+    ) -> Result<Option<Vec<ForgeUniversalDataField>>, Box<dyn Error>> {
+        let row = sqlx::query(&self.build_last_key_sql(table_name, key_columns))
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(match row {
+            Some(row) => Some(self.map_row_to_universal_values(&row)?),
+            None => None,
+        })
+    }
+
+    async fn fetch_row(
+        &self,
+        table_name: &str,
+        key_columns: &[String],
+        key: &[ForgeUniversalDataField],
+    ) -> Result<Option<IndexMap<String, ForgeUniversalDataField>>, Box<dyn Error>> {
+        let invisible = self.fetch_invisible_column_names(table_name).await?;
+        let query_string = format!(
+            "{}{}",
+            self.build_select_all_sql(table_name, &invisible),
+            filtered_clause(
+                &self.row_filters,
+                table_name,
+                &self.build_key_clause(key_columns)
+            )
+        );
+        let mut query = sqlx::query(&query_string);
+        for value in key {
+            query = self.bind_universal(query, value);
+        }
+        let Some(row) = query.fetch_optional(&self.pool).await? else {
+            return Ok(None);
+        };
+        let values = self.map_row_to_universal_values(&row)?;
+        Ok(Some(
+            row.columns()
+                .iter()
+                .map(|col| col.name().to_string())
+                .zip(values)
+                .collect(),
+        ))
+    }
+
+    #[tracing::instrument(name = "insert_chunk", skip_all, fields(table = table_name, rows = chunk.len()))]
     async fn insert_chunk(
         &self,
         table_name: &str,
@@ -1189,88 +2956,430 @@ impl DatabaseDriver for MySqlDriver {
             return Ok(());
         }
 
-        // extract column names from first record
+        // extract column names from first record, generated columns are computed by the target
+        let generated = self.fetch_generated_column_names(table_name).await?;
         let first_row = chunk.first().ok_or("Chunk is empty")?;
-        let columns: Vec<String> = first_row.keys().cloned().collect();
+        let columns: Vec<String> = first_row
+            .keys()
+            .filter(|c| !generated.contains(c))
+            .cloned()
+            .collect();
         let column_names = columns
             .iter()
             .map(|c| format!("`{c}`"))
             .collect::<Vec<_>>()
             .join(", ");
 
-        // prepare SQL-Statement
-        let mut sql = format!("INSERT INTO `{table_name}` ({column_names}) VALUES ");
+        let max_bytes = self.max_batch_bytes().await?;
+        for range in batch_ranges(&chunk, columns.len(), MAX_BIND_PARAMS, max_bytes) {
+            self.insert_batch(
+                table_name,
+                &columns,
+                &column_names,
+                &chunk[range],
+                dry_run,
+                halt_on_error,
+            )
+            .await?;
+        }
 
-        let mut placeholders = Vec::new();
-        for _ in 0..chunk.len() {
-            let row_placeholders = vec!["?"; columns.len()].join(", ");
-            placeholders.push(format!("({row_placeholders})"));
+        Ok(())
+    }
+
+    async fn upsert_chunk(
+        &self,
+        table_name: &str,
+        key_columns: &[String],
+        chunk: Vec<IndexMap<String, ForgeUniversalDataField>>,
+    ) -> Result<(), Box<dyn Error>> {
+        let Some(first_row) = chunk.first() else {
+            return Ok(());
+        };
+        let generated = self.fetch_generated_column_names(table_name).await?;
+        let columns: Vec<String> = first_row
+            .keys()
+            .filter(|c| !generated.contains(c))
+            .cloned()
+            .collect();
+        let quoted =
+            |names: &[String]| -> Vec<String> { names.iter().map(|c| format!("`{c}`")).collect() };
+        let conflict = upsert_clause(ForgeDialect::Mysql, &quoted(&columns), &quoted(key_columns));
+        let row_placeholders = format!("({})", vec!["?"; columns.len()].join(", "));
+
+        let max_bytes = self.max_batch_bytes().await?;
+        for range in batch_ranges(&chunk, columns.len(), MAX_BIND_PARAMS, max_bytes) {
+            let sql = format!(
+                "INSERT INTO `{table_name}` ({}) VALUES {} {conflict}",
+                quoted(&columns).join(", "),
+                vec![row_placeholders.as_str(); range.len()].join(", ")
+            );
+            self.execute_rows("upsert_chunk", &sql, &columns, &chunk[range])
+                .await?;
         }
-        sql.push_str(&placeholders.join(", "));
+        Ok(())
+    }
 
-        if dry_run {
-            println!("Dry run SQL = {sql}");
-        } else {
-            // create query and bind values
-            let mut query = sqlx::query(&sql);
+    async fn delete_rows(
+        &self,
+        table_name: &str,
+        key_columns: &[String],
+        keys: Vec<IndexMap<String, ForgeUniversalDataField>>,
+    ) -> Result<(), Box<dyn Error>> {
+        let key_names = key_columns
+            .iter()
+            .map(|c| format!("`{c}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let key_placeholders = format!("({})", vec!["?"; key_columns.len()].join(", "));
+
+        let max_bytes = self.max_batch_bytes().await?;
+        for range in batch_ranges(&keys, key_columns.len(), MAX_BIND_PARAMS, max_bytes) {
+            let sql = format!(
+                "DELETE FROM `{table_name}` WHERE ({key_names}) IN ({})",
+                vec![key_placeholders.as_str(); range.len()].join(", ")
+            );
+            self.execute_rows("delete_rows", &sql, key_columns, &keys[range])
+                .await?;
+        }
+        Ok(())
+    }
 
-            for row in &chunk {
-                for col in &columns {
-                    // value from IndexMap holen, Fallback to Null
-                    let val = row.get(col).unwrap_or(&ForgeUniversalDataField::Null);
+    async fn get_table_row_count(&self, table_name: &str) -> Result<u64, Box<dyn Error>> {
+        let query = format!(
+            "SELECT COUNT(*) FROM `{table_name}`{}",
+            filtered_clause(&self.row_filters, table_name, "")
+        );
+        let row: (i64,) = self
+            .retry
+            .run("row_count", || sqlx::query_as(&query).fetch_one(&self.pool))
+            .await?;
+        Ok(row.0 as u64)
+    }
 
-                    // binding based on UniversalEnums
-                    query = self.bind_universal(query, val);
-                }
-            }
+    async fn range_checksums(
+        &self,
+        table_name: &str,
+        key_column: &str,
+        range_keys: u64,
+    ) -> Result<Vec<ForgeRangeChecksum>, Box<dyn Error>> {
+        // NULL is skipped by CONCAT_WS, the ISNULL flags tell it apart from an empty text
+        let values: Vec<String> = self
+            .fetch_column_names(table_name)
+            .await?
+            .iter()
+            .map(|c| format!("`{c}`, ISNULL(`{c}`)"))
+            .collect();
+        let query = format!(
+            "SELECT CAST(FLOOR(`{key_column}` / {range_keys}) AS SIGNED), CAST(COUNT(*) AS SIGNED), \
+             CAST(SUM(CAST(CONV(LEFT(MD5(CONCAT_WS('#', {})), 16), 16, 10) AS UNSIGNED)) AS CHAR) \
+             FROM `{table_name}`{}",
+            values.join(", "),
+            filtered_clause(&self.row_filters, table_name, " GROUP BY 1 ORDER BY 1")
+        );
+        let rows: Vec<(i64, i64, String)> = self
+            .retry
+            .run("range_checksums", || {
+                sqlx::query_as(&query).fetch_all(&self.pool)
+            })
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(range, rows, checksum)| ForgeRangeChecksum {
+                range,
+                rows: rows.max(0) as u64,
+                checksum,
+            })
+            .collect())
+    }
 
-            if let Err(e) = query.execute(&self.pool).await {
-                eprintln!(
-                    "Batch insert failed for table `{table_name}`. Retrying row-by-row for logging..."
-                );
+    async fn estimate_table_sizes(&self) -> Result<Vec<ForgeTableEstimate>, Box<dyn Error>> {
+        let rows: Vec<(String, i64, i64)> = self
+            .retry
+            .run("table_sizes", || {
+                sqlx::query_as(
+                    "SELECT CAST(TABLE_NAME AS CHAR), CAST(COALESCE(TABLE_ROWS, 0) AS SIGNED), \
+                     CAST(COALESCE(DATA_LENGTH, 0) AS SIGNED) \
+                     FROM information_schema.TABLES \
+                     WHERE TABLE_SCHEMA = DATABASE() AND TABLE_TYPE = 'BASE TABLE' \
+                     ORDER BY TABLE_NAME",
+                )
+                .fetch_all(&self.pool)
+            })
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(table_name, rows, bytes)| ForgeTableEstimate {
+                table_name,
+                rows: rows.max(0) as u64,
+                bytes: bytes.max(0) as u64,
+            })
+            .collect())
+    }
 
-                // we build SQL for one row at a time: INSERT INTO `table` (`col1`) VALUES (?)
-                let single_sql = format!(
-                    "INSERT INTO `{}` ({}) VALUES ({})",
-                    table_name,
-                    columns
-                        .iter()
-                        .map(|c| format!("`{c}`"))
-                        .collect::<Vec<_>>()
-                        .join(", "),
-                    vec!["?"; columns.len()].join(", ")
-                );
+    async fn delete_table_data(
+        &self,
+        table_name: &str,
+        filter: Option<&str>,
+    ) -> Result<(), Box<dyn Error>> {
+        let sql = match filter {
+            Some(filter) => format!("DELETE FROM `{table_name}` WHERE {filter}"),
+            None => format!("DELETE FROM `{table_name}`"),
+        };
+        debug!(statement = %sql, "deleting table data");
+        match self.write_tx.lock().await.as_mut() {
+            Some(tx) => sqlx::query(&sql).execute(&mut **tx).await?,
+            None => sqlx::query(&sql).execute(&self.pool).await?,
+        };
+        Ok(())
+    }
 
-                for row_map in &chunk {
-                    let mut single_query = sqlx::query(&single_sql);
+    async fn begin_write(&self) -> Result<(), Box<dyn Error>> {
+        let mut write_tx = self.write_tx.lock().await;
+        if write_tx.is_some() {
+            return Err("A write transaction is already open".into());
+        }
+        *write_tx = Some(self.pool.begin().await?);
+        debug!("write transaction started");
+        Ok(())
+    }
 
-                    for col in &columns {
-                        let val = row_map.get(col).unwrap_or(&ForgeUniversalDataField::Null);
-                        single_query = self.bind_universal(single_query, val);
-                    }
+    async fn commit_write(&self) -> Result<(), Box<dyn Error>> {
+        let tx = self
+            .write_tx
+            .lock()
+            .await
+            .take()
+            .ok_or("No write transaction is open")?;
+        tx.commit().await?;
+        debug!("write transaction committed");
+        Ok(())
+    }
 
-                    // execute one row
-                    if let Err(single_err) = single_query.execute(&self.pool).await {
-                        let row_data = format!("{row_map:?}");
-                        let err_msg = single_err.to_string();
+    async fn rollback_write(&self) -> Result<(), Box<dyn Error>> {
+        if let Some(tx) = self.write_tx.lock().await.take() {
+            tx.rollback().await?;
+            debug!("write transaction rolled back");
+        }
+        Ok(())
+    }
 
-                        // now we can log the error of one row
-                        eprintln!("Error in Row: {row_data} | Error: {err_msg}");
-                        log_error_to_file(table_name, &row_data, &err_msg);
-                    }
-                }
-                if halt_on_error {
-                    return Err(e.into());
+    async fn disable_constraints(&self, tables: &[String]) -> Result<(), Box<dyn Error>> {
+        // sessions already in use keep their checks, so the switch must precede the load
+        if self.write_tx.lock().await.is_some() {
+            return Err("Constraints cannot be disabled inside a write transaction".into());
+        }
+        self.foreign_key_checks_off.store(true, Ordering::SeqCst);
+        info!(
+            tables = tables.len(),
+            "foreign key checks disabled, MySQL triggers stay active"
+        );
+        Ok(())
+    }
+
+    async fn enable_constraints(&self, tables: &[String]) -> Result<(), Box<dyn Error>> {
+        self.foreign_key_checks_off.store(false, Ordering::SeqCst);
+        info!(tables = tables.len(), "foreign key checks enabled");
+        Ok(())
+    }
+
+    async fn count_foreign_key_violations(
+        &self,
+        table_name: &str,
+        foreign_key: &[ForgeSchemaForeignKey],
+    ) -> Result<u64, Box<dyn Error>> {
+        let query = self.foreign_key_violations_sql(table_name, foreign_key);
+        let count: i64 = self
+            .retry
+            .run("foreign_key_violations", || {
+                sqlx::query_scalar(&query).fetch_one(&self.pool)
+            })
+            .await?;
+        Ok(count as u64)
+    }
+
+    async fn count_unsigned_overflow(
+        &self,
+        table_name: &str,
+        column_name: &str,
+    ) -> Result<u64, Box<dyn Error>> {
+        let query = self.unsigned_overflow_sql(table_name, column_name);
+        let count: i64 = self
+            .retry
+            .run("unsigned_overflow", || {
+                sqlx::query_scalar(&query).fetch_one(&self.pool)
+            })
+            .await?;
+        Ok(count as u64)
+    }
+
+    async fn preflight_checks(
+        &self,
+        as_target: bool,
+    ) -> Result<Vec<ForgePreflightCheck>, Box<dyn Error>> {
+        let version = self.fetch_server_version().await?;
+        let mut checks = vec![version.version_check()];
+
+        let (sql_mode, max_allowed_packet, wait_timeout, net_write_timeout, database): (
+            String,
+            i64,
+            i64,
+            i64,
+            Option<String>,
+        ) = sqlx::query_as(
+            "SELECT CAST(@@SESSION.sql_mode AS CHAR), CAST(@@max_allowed_packet AS SIGNED), \
+             CAST(@@SESSION.wait_timeout AS SIGNED), CAST(@@SESSION.net_write_timeout AS SIGNED), \
+             DATABASE()",
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        checks.extend(mysql_setting_checks(
+            &sql_mode,
+            max_allowed_packet.max(0) as u64,
+            wait_timeout.max(0) as u64,
+            net_write_timeout.max(0) as u64,
+            as_target,
+        ));
+
+        let required: &[&str] = if as_target {
+            &[
+                "SELECT",
+                "INSERT",
+                "DELETE",
+                "CREATE",
+                "ALTER",
+                "INDEX",
+                "REFERENCES",
+                "TRIGGER",
+            ]
+        } else {
+            &["SELECT"]
+        };
+        match sqlx::query_scalar::<_, String>("SHOW GRANTS FOR CURRENT_USER()")
+            .fetch_all(&self.pool)
+            .await
+        {
+            Ok(grants) => {
+                let database = database.unwrap_or_default();
+                let missing = missing_privileges(&grants, &database, required);
+                checks.push(if missing.is_empty() {
+                    ForgePreflightCheck::pass("privileges", required.join(", "))
+                } else {
+                    ForgePreflightCheck::fail(
+                        "privileges",
+                        format!("missing on `{database}`: {}", missing.join(", ")),
+                    )
+                });
+                // the binlog position of the schema metadata needs REPLICATION CLIENT
+                if !as_target
+                    && !missing_privileges(&grants, &database, &["REPLICATION CLIENT"]).is_empty()
+                {
+                    checks.push(ForgePreflightCheck::warn(
+                        "replication client",
+                        "missing, the source position is not recorded",
+                    ));
                 }
             }
+            Err(e) => checks.push(ForgePreflightCheck::fail("privileges", e.to_string())),
         }
+        Ok(checks)
+    }
 
-        Ok(())
+    async fn replication_lag(&self) -> Result<Option<Duration>, Box<dyn Error>> {
+        let rows = match sqlx::query("SHOW REPLICA STATUS")
+            .fetch_all(&self.pool)
+            .await
+        {
+            Ok(rows) => rows,
+            // before MySQL 8.0.22
+            Err(_) => {
+                sqlx::query("SHOW SLAVE STATUS")
+                    .fetch_all(&self.pool)
+                    .await?
+            }
+        };
+
+        // one row per replication channel, the slowest one counts
+        let mut lag: Option<Duration> = None;
+        for row in &rows {
+            let seconds = ["Seconds_Behind_Source", "Seconds_Behind_Master"]
+                .iter()
+                .find_map(|col| {
+                    row.try_get::<Option<u64>, _>(*col)
+                        .or_else(|_| {
+                            row.try_get::<Option<i64>, _>(*col)
+                                .map(|v| v.map(|v| u64::try_from(v).unwrap_or(0)))
+                        })
+                        .ok()
+                })
+                .ok_or("SHOW REPLICA STATUS has no Seconds_Behind_Source")?
+                .ok_or(
+                    "replication of the source replica is stopped (Seconds_Behind_Source is NULL)",
+                )?;
+            lag = lag.max(Some(Duration::from_secs(seconds)));
+        }
+        Ok(lag)
     }
 
-    async fn get_table_row_count(&self, table_name: &str) -> Result<u64, Box<dyn Error>> {
-        let query = format!("SELECT COUNT(*) FROM `{table_name}`");
-        let row: (i64,) = sqlx::query_as(&query).fetch_one(&self.pool).await?;
-        Ok(row.0 as u64)
+    async fn query_scalar(&self, sql: &str) -> Result<ForgeUniversalDataField, Box<dyn Error>> {
+        let row = sqlx::query(sql).fetch_optional(&self.pool).await?;
+        let value = match row {
+            Some(row) => self
+                .map_row_to_universal_values(&row)?
+                .into_iter()
+                .next()
+                .unwrap_or(ForgeUniversalDataField::Null),
+            None => ForgeUniversalDataField::Null,
+        };
+        Ok(value)
+    }
+
+    async fn fetch_grants(&self) -> Result<Vec<ForgeSchemaGrant>, Box<dyn Error>> {
+        let rows = sqlx::query(
+            "SELECT GRANTEE, TABLE_NAME, PRIVILEGE_TYPE
+             FROM information_schema.TABLE_PRIVILEGES
+             WHERE TABLE_SCHEMA = DATABASE()",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut grant_rows = Vec::with_capacity(rows.len());
+        for row in rows {
+            // helper for reliable reading because mysql gives metadata as (VAR)BINARY
+            let get_s = |col: &str| -> String {
+                row.try_get::<Vec<u8>, _>(col)
+                    .map(|b| String::from_utf8_lossy(&b).into_owned())
+                    .or_else(|_| row.try_get::<String, _>(col))
+                    .unwrap_or_default()
+            };
+            grant_rows.push((
+                get_s("GRANTEE"),
+                get_s("TABLE_NAME"),
+                get_s("PRIVILEGE_TYPE"),
+            ));
+        }
+
+        Ok(grants::group_grants(grant_rows))
+    }
+
+    #[tracing::instrument(name = "apply_grants", skip_all, fields(engine = "mysql", dry_run))]
+    async fn apply_grants(
+        &self,
+        grants: &[ForgeSchemaGrant],
+        config: &ForgeConfig,
+        dry_run: bool,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        let statements: Vec<String> = grants
+            .iter()
+            .filter_map(|grant| self.build_grant_sql(grant, config))
+            .collect();
+
+        if !dry_run {
+            for sql in &statements {
+                debug!(statement = %sql, "executing grant statement");
+                sqlx::query(sql).execute(&self.pool).await?;
+            }
+        }
+
+        Ok(statements)
     }
 }
@@ -1,23 +1,1058 @@
 use crate::core::{
-    ForgeConfig, ForgeError, ForgeSchema, ForgeSchemaForeignKey, ForgeSchemaIndex,
-    ForgeSchemaMetadata, ForgeSchemaTable, ForgeUniversalDataField,
+    ForgeAppliedStatements, ForgeArray, ForgeConfig, ForgeDialect, ForgeError, ForgeKeyRange,
+    ForgeNullOrdering, ForgeObjectFilter, ForgePreflightCheck, ForgeRangeChecksum,
+    ForgeRollbackPlan, ForgeSchema, ForgeSchemaDomain, ForgeSchemaForeignKey, ForgeSchemaGrant,
+    ForgeSchemaIndex, ForgeSchemaMetadata, ForgeSchemaPartition, ForgeSchemaPartitioning,
+    ForgeSchemaTable, ForgeSourcePosition, ForgeTableEstimate, ForgeTimeZone,
+    ForgeUniversalDataField, ForgeVerifySample, MigrateOptions,
 };
-use crate::ops::log_error_to_file;
-use crate::{DatabaseDriver, ForgeSchemaColumn};
+use crate::drivers::tunnel::SshTunnel;
+use crate::drivers::{
+    Backfill, ForeignKeyChanges, ForeignKeyConstraint, MAX_BIND_PARAMS, SchemaStatements,
+    backfill_plan, batch_ranges, filtered_clause, resuming_stream, rollback_schema_plan,
+    sorted_by_name, upsert_clause,
+};
+use crate::ops::incompat::{MYSQL_INDEX_FUNCTION, mysql_only_function};
+use crate::ops::retry::RetryPolicy;
+use crate::ops::schema_file::SCHEMA_FORMAT_VERSION;
+use crate::ops::{grants, log_rejected_row};
+use crate::{CancellationToken, DatabaseDriver, ForgeSchemaColumn};
 use async_trait::async_trait;
 use futures::{Stream, StreamExt};
 use indexmap::IndexMap;
-use sqlx::postgres::PgRow;
-use sqlx::{Column, PgPool, Row, TypeInfo, ValueRef};
-use std::collections::HashMap;
+use sqlx::postgres::{PgRow, PgTypeKind};
+use sqlx::{Column, Connection, PgPool, Postgres, Row, TypeInfo, ValueRef};
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::pin::Pin;
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+/// upper limit of the encoded values of one INSERT, keeps the statements far below
+/// the 1 GB message limit and the memory of server and client bounded
+const MAX_BATCH_BYTES: u64 = 64 * 1024 * 1024;
+
+/// `SET TIME ZONE` of a session, timestamptz values are read and written as its wall time
+///
+/// The interval form keeps the ISO sign, a plain '+02:00' is a POSIX zone west of UTC.
+#[must_use]
+pub fn postgres_time_zone_sql(time_zone: ForgeTimeZone) -> String {
+    format!("SET TIME ZONE INTERVAL '{time_zone}' HOUR TO MINUTE")
+}
+
+/// name of the trigger function emulating `ON UPDATE CURRENT_TIMESTAMP`
+pub const SET_UPDATED_AT_FUNCTION: &str = "set_updated_at";
+
+/// Trigger function setting the columns named in its arguments to `CURRENT_TIMESTAMP`.
+///
+/// Like MySQL a column is only set if the row changed and the update left the column untouched.
+#[must_use]
+pub fn set_updated_at_function_sql() -> String {
+    [
+        &format!("CREATE OR REPLACE FUNCTION {SET_UPDATED_AT_FUNCTION}() RETURNS trigger AS $$"),
+        "DECLARE",
+        "  col text;",
+        "BEGIN",
+        "  IF to_jsonb(NEW) IS DISTINCT FROM to_jsonb(OLD) THEN",
+        "    FOREACH col IN ARRAY TG_ARGV LOOP",
+        "      IF to_jsonb(NEW) -> col IS NOT DISTINCT FROM to_jsonb(OLD) -> col THEN",
+        "        NEW := jsonb_populate_record(NEW, jsonb_build_object(col, CURRENT_TIMESTAMP));",
+        "      END IF;",
+        "    END LOOP;",
+        "  END IF;",
+        "  RETURN NEW;",
+        "END;",
+        "$$ LANGUAGE plpgsql",
+    ]
+    .join("\n")
+}
+
+/// value lists of a multi-row statement, `($1, $2), ($3, $4)` for two rows of two columns
+fn numbered_placeholders(rows: usize, columns: usize) -> String {
+    (0..rows)
+        .map(|row| {
+            let values: Vec<String> = (1..=columns)
+                .map(|column| format!("${}", row * columns + column))
+                .collect();
+            format!("({})", values.join(", "))
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// `ON UPDATE` columns of the table, sorted by name
+fn on_update_columns(table: &ForgeSchemaTable) -> Vec<&str> {
+    let mut columns: Vec<&str> = table
+        .columns
+        .iter()
+        .filter(|c| c.on_update.is_some())
+        .map(|c| c.name.as_str())
+        .collect();
+    columns.sort_unstable();
+    columns
+}
+
+/// Function and trigger statements emulating the `ON UPDATE` columns of the table.
+///
+/// The trigger is dropped first, so the statements also replace the trigger of an altered table.
+#[must_use]
+pub fn on_update_trigger_sql(table: &ForgeSchemaTable) -> Vec<String> {
+    let columns = on_update_columns(table);
+    if columns.is_empty() {
+        return Vec::new();
+    }
+    let trigger = format!("{}_{SET_UPDATED_AT_FUNCTION}", table.name);
+    let args: Vec<String> = columns.iter().map(|c| format!("'{c}'")).collect();
+    vec![
+        set_updated_at_function_sql(),
+        format!("DROP TRIGGER IF EXISTS {trigger} ON {}", table.name),
+        format!(
+            "CREATE TRIGGER {trigger} BEFORE UPDATE ON {} FOR EACH ROW EXECUTE FUNCTION {SET_UPDATED_AT_FUNCTION}({})",
+            table.name,
+            args.join(", ")
+        ),
+    ]
+}
+
+/// `CREATE EXTENSION` statements of the required extensions missing on the target.
+///
+/// Extensions outside of the allowlist (`[general] allowed_extensions`) are only reported,
+/// they have to be installed by an administrator.
+#[must_use]
+pub fn create_extension_statements(
+    required: &[String],
+    installed: &HashSet<String>,
+    config: &ForgeConfig,
+) -> Vec<String> {
+    let mut statements = Vec::new();
+    for extension in required {
+        if installed.contains(extension) {
+            continue;
+        }
+        if config.is_allowed_extension(extension) {
+            statements.push(format!("CREATE EXTENSION IF NOT EXISTS \"{extension}\""));
+        } else {
+            warn!(
+                extension = %extension,
+                "extension is missing on the target and not in allowed_extensions, create it manually"
+            );
+        }
+    }
+    statements
+}
+
+/// `CREATE DOMAIN` statement of a domain.
+#[must_use]
+pub fn build_postgres_create_domain_sql(domain: &ForgeSchemaDomain) -> String {
+    let mut sql = format!("CREATE DOMAIN {} AS {}", domain.name, domain.data_type);
+    if let Some(ref default) = domain.default {
+        sql.push_str(&format!(" DEFAULT {default}"));
+    }
+    if domain.not_null {
+        sql.push_str(" NOT NULL");
+    }
+    for check in &domain.checks {
+        sql.push(' ');
+        sql.push_str(check);
+    }
+    sql
+}
+
+/// column of a domain type, nullability and default on top of the domain constraints
+fn domain_field_sql(field: &ForgeSchemaColumn, domain: &str) -> String {
+    let mut sql = format!("{} {domain}", field.name);
+    sql.push_str(if field.is_nullable {
+        " NULL"
+    } else {
+        " NOT NULL"
+    });
+    if let Some(ref default) = field.default {
+        sql.push_str(&format!(" DEFAULT {default}"));
+    }
+    sql
+}
+
+/// value of a column, domain columns skip the type check and are decoded as their base type
+fn decode_column<'r, T>(row: &'r PgRow, index: usize, unchecked: bool) -> Result<T, sqlx::Error>
+where
+    T: sqlx::Decode<'r, Postgres> + sqlx::Type<Postgres>,
+{
+    if unchecked {
+        row.try_get_unchecked(index)
+    } else {
+        row.try_get(index)
+    }
+}
+
+/// Partitioning of a `pg_get_partkeydef` result like "RANGE (created_at)", without partitions.
+#[must_use]
+pub fn parse_partition_key(key_def: &str) -> Option<ForgeSchemaPartitioning> {
+    let (strategy, key) = key_def.trim().split_once(' ')?;
+    let key = key.trim().strip_prefix('(')?.strip_suffix(')')?;
+    Some(ForgeSchemaPartitioning {
+        strategy: strategy.to_lowercase(),
+        key: key.to_string(),
+        partitions: Vec::new(),
+    })
+}
+
+/// PostgreSQL partitioning of a MySQL partitioned table.
+///
+/// RANGE, LIST and HASH/KEY partitions on plain columns are translated, `None` for expression
+/// keys (i.e. `YEAR(created_at)`) and multi-column LIST partitions.
+#[must_use]
+pub fn postgres_partitioning_of_mysql(
+    partitioning: &ForgeSchemaPartitioning,
+) -> Option<ForgeSchemaPartitioning> {
+    let columns: Vec<&str> = partitioning
+        .key
+        .split(',')
+        .map(|c| c.trim().trim_matches('`'))
+        .collect();
+    if columns
+        .iter()
+        .any(|c| c.is_empty() || !c.chars().all(|ch| ch.is_alphanumeric() || ch == '_'))
+    {
+        return None;
+    }
+
+    let mut partitions = Vec::with_capacity(partitioning.partitions.len());
+    let strategy = match partitioning.strategy.as_str() {
+        "range" | "range columns" => {
+            // each partition starts at the upper bound of the previous one
+            let mut from = vec!["MINVALUE"; columns.len()].join(", ");
+            for partition in &partitioning.partitions {
+                let to = partition.bound.strip_prefix("VALUES LESS THAN")?.trim();
+                let to = to
+                    .strip_prefix('(')
+                    .and_then(|t| t.strip_suffix(')'))
+                    .unwrap_or(to)
+                    .to_string();
+                partitions.push(ForgeSchemaPartition {
+                    name: partition.name.clone(),
+                    bound: format!("FOR VALUES FROM ({from}) TO ({to})"),
+                });
+                from = to;
+            }
+            "range"
+        }
+        "list" | "list columns" if columns.len() == 1 => {
+            for partition in &partitioning.partitions {
+                let values = partition.bound.strip_prefix("VALUES IN")?.trim();
+                partitions.push(ForgeSchemaPartition {
+                    name: partition.name.clone(),
+                    bound: format!("FOR VALUES IN {values}"),
+                });
+            }
+            "list"
+        }
+        "hash" | "linear hash" | "key" | "linear key" => {
+            let modulus = partitioning.partitions.len();
+            for (remainder, partition) in partitioning.partitions.iter().enumerate() {
+                partitions.push(ForgeSchemaPartition {
+                    name: partition.name.clone(),
+                    bound: format!("FOR VALUES WITH (MODULUS {modulus}, REMAINDER {remainder})"),
+                });
+            }
+            "hash"
+        }
+        _ => return None,
+    };
+    Some(ForgeSchemaPartitioning {
+        strategy: strategy.to_string(),
+        key: columns.join(", "),
+        partitions,
+    })
+}
+
+/// partitioning of a table on PostgreSQL, untranslatable MySQL partitioning is dropped
+fn postgres_partitioning(table: &ForgeSchemaTable) -> Option<ForgeSchemaPartitioning> {
+    let partitioning = table.partitioning.as_ref()?;
+    if partitioning.partitions.is_empty() || partitioning.has_postgres_bounds() {
+        return Some(partitioning.clone());
+    }
+    let translated = postgres_partitioning_of_mysql(partitioning);
+    if translated.is_none() {
+        warn!(
+            table = %table.name,
+            strategy = %partitioning.strategy,
+            key = %partitioning.key,
+            "partitioning has no PostgreSQL counterpart, the table is created without partitions"
+        );
+    }
+    translated
+}
+
+/// `CREATE TABLE ... PARTITION OF` statement of a partition, `if_not_exists` skips an existing one
+#[must_use]
+pub fn build_postgres_partition_sql(
+    table_name: &str,
+    partition: &ForgeSchemaPartition,
+    if_not_exists: bool,
+) -> String {
+    format!(
+        "CREATE TABLE {}{} PARTITION OF {table_name} {}",
+        if if_not_exists { "IF NOT EXISTS " } else { "" },
+        partition.name,
+        partition.bound
+    )
+}
+
+/// special logic for "NOT NULL" with time/date types (because of mysql ...)
+fn is_time_type(pg_type_lower: &str) -> bool {
+    pg_type_lower.contains("timestamp")
+        || pg_type_lower.contains("date")
+        || pg_type_lower.contains("time")
+}
+
+/// `DEFAULT` value of a column, MySQL zero dates and literals of time types are translated
+fn postgres_default_sql(field: &ForgeSchemaColumn, pg_type: &str) -> Option<String> {
+    // Do not carry over default nextval(...) from source; IDENTITY already covers it
+    if field.auto_increment {
+        return None;
+    }
+    let def = field.default.as_ref()?;
+    if !is_time_type(&pg_type.to_lowercase()) {
+        return Some(def.clone());
+    }
+    // MySQL Zero-Dates to NULL
+    if def.contains("0000-00-00") || def == "0" || def == "'0'" {
+        Some("NULL".to_string())
+    }
+    // MySQL functions to Postgres functions
+    else if def.to_uppercase() == "CURRENT_TIMESTAMP" {
+        Some("CURRENT_TIMESTAMP".to_string()) // In PG without "
+    }
+    // fix missing " in literals (the ":" error)
+    else if def.contains(':') && !def.starts_with('\'') {
+        Some(format!("'{def}'"))
+    } else {
+        Some(def.clone())
+    }
+}
+
+/// `SET` statements of the `[general.ddl]` timeouts of the connection running the schema statements
+#[must_use]
+pub fn postgres_ddl_timeout_sql(config: &ForgeConfig) -> Vec<String> {
+    let ddl = config.get_ddl();
+    let mut statements = Vec::new();
+    if let Some(ms) = ddl.lock_timeout_ms {
+        statements.push(format!("SET lock_timeout = {ms}"));
+    }
+    if let Some(ms) = ddl.statement_timeout_ms {
+        statements.push(format!("SET statement_timeout = {ms}"));
+    }
+    statements
+}
+
+/// Returns true for statements PostgreSQL refuses to run in a transaction block,
+/// i.e. `CREATE INDEX CONCURRENTLY`
+#[must_use]
+pub fn runs_outside_transaction(sql: &str) -> bool {
+    let sql = sql.trim_start().to_uppercase();
+    let words: Vec<&str> = sql.split_whitespace().collect();
+    // `{CREATE | DROP} INDEX CONCURRENTLY`, `REINDEX ... CONCURRENTLY`
+    let concurrently = words.windows(2).any(|w| w == ["INDEX", "CONCURRENTLY"])
+        || (sql.starts_with("REINDEX") && words.contains(&"CONCURRENTLY"));
+    concurrently
+        || ["VACUUM", "ALTER SYSTEM", "CREATE DATABASE", "DROP DATABASE"]
+            .iter()
+            .any(|prefix| sql.starts_with(prefix))
+}
+
+/// MySQL functional index expression without identifier quotes and charset introducers,
+/// functions both engines know (i.e. `lower`) carry over
+#[must_use]
+pub fn postgres_index_expression(expression: &str) -> String {
+    ["_utf8mb4'", "_utf8mb3'", "_utf8'", "_latin1'", "_binary'"]
+        .iter()
+        .fold(expression.replace('`', ""), |e, introducer| {
+            e.replace(introducer, "'")
+        })
+}
+
+/// Returns false for a functional index calling a MySQL-only function, it is skipped with a
+/// warning (reported as FF006).
+fn is_postgres_index(table_name: &str, index: &ForgeSchemaIndex) -> bool {
+    let expressions = index.expressions.iter().flatten().flatten();
+    let Some(function) = expressions.into_iter().find_map(|e| mysql_only_function(e)) else {
+        return true;
+    };
+    warn!(
+        code = MYSQL_INDEX_FUNCTION.code,
+        table = table_name,
+        index = %index.name,
+        "index skipped: {function}() is a MySQL-only function"
+    );
+    false
+}
+
+/// whether `ON UPDATE` columns are emulated by triggers (`[postgres.rules.on_write] on_update_trigger`)
+fn on_update_trigger(config: &ForgeConfig) -> bool {
+    config
+        .postgres
+        .as_ref()
+        .and_then(|c| c.rules.as_ref())
+        .and_then(|r| r.on_write.as_ref())
+        .and_then(|o| o.on_update_trigger)
+        .unwrap_or(false)
+}
+
+/// Pre-flight checks of the server version and settings.
+///
+/// A source needs `wal_level = logical` for a CDC run after the snapshot, a
+/// `statement_timeout` can cancel the long running reads and writes of a table.
+#[must_use]
+pub fn postgres_setting_checks(
+    version: &str,
+    version_num: i64,
+    wal_level: &str,
+    statement_timeout: &str,
+    as_target: bool,
+) -> Vec<ForgePreflightCheck> {
+    let mut checks = Vec::new();
+
+    checks.push(if version_num < 120_000 {
+        ForgePreflightCheck::warn(
+            "server version",
+            format!("PostgreSQL {version} is no longer supported upstream"),
+        )
+    } else {
+        ForgePreflightCheck::pass("server version", format!("PostgreSQL {version}"))
+    });
+
+    checks.push(if !as_target && wal_level != "logical" {
+        ForgePreflightCheck::warn(
+            "wal_level",
+            format!("{wal_level}, a CDC run after the snapshot needs logical"),
+        )
+    } else {
+        ForgePreflightCheck::pass("wal_level", wal_level)
+    });
+
+    checks.push(if statement_timeout == "0" {
+        ForgePreflightCheck::pass("statement_timeout", "disabled")
+    } else {
+        ForgePreflightCheck::warn(
+            "statement_timeout",
+            format!("{statement_timeout}, long reads and writes of a table may be cancelled"),
+        )
+    });
+    checks
+}
+
+pub struct PostgresDriver {
+    pub pool: Option<PgPool>,
+    pub null_ordering: ForgeNullOrdering,
+    /// retries of transient errors of metadata queries and inserts
+    pub retry: RetryPolicy,
+    /// open write transaction of `begin_write`, inserts and deletes run in it; the lock is held
+    /// for each statement, so a table with a shared transaction is written by one insert worker
+    /// (see [`ForgeConfig::get_table_insert_workers`])
+    pub write_tx: tokio::sync::Mutex<Option<sqlx::Transaction<'static, sqlx::Postgres>>>,
+    /// session time zone, timestamptz values are read as its wall time (UTC if unset)
+    pub time_zone: Option<ForgeTimeZone>,
+    /// SSH tunnel of the pool, closed when the driver is dropped
+    pub tunnel: Option<SshTunnel>,
+    /// SQL conditions of the rows read per table (`[tables.<name>] filter` of a source)
+    pub row_filters: HashMap<String, String>,
+    /// `IF NOT EXISTS` / `IF EXISTS` on created and dropped objects (`[general] idempotent_ddl`)
+    pub idempotent_ddl: bool,
+}
+
+impl PostgresDriver {
+    /// Statements of a chunked backfill: the default of new rows, the existing rows per key
+    /// range, then NOT NULL (time types stay nullable, see `field_migration_sql`).
+    fn backfill_sql(&self, table: &str, backfill: &Backfill, config: &ForgeConfig) -> Vec<String> {
+        let mut statements = Vec::new();
+        for column in &backfill.columns {
+            let pg_type = self.map_to_postgres_write_type(&column.data_type, config);
+            if let Some(default) = postgres_default_sql(column, &pg_type) {
+                statements.push(format!(
+                    "ALTER TABLE {table} ALTER COLUMN {} SET DEFAULT {default}",
+                    column.name
+                ));
+            }
+        }
+        for (start, end) in &backfill.ranges {
+            for column in &backfill.columns {
+                statements.push(format!(
+                    "UPDATE {table} SET {column} = DEFAULT WHERE {key} >= {start} AND {key} < {end} AND {column} IS NULL",
+                    column = column.name,
+                    key = backfill.key
+                ));
+            }
+        }
+        for column in &backfill.columns {
+            let pg_type = self.map_to_postgres_write_type(&column.data_type, config);
+            if !is_time_type(&pg_type.to_lowercase()) {
+                statements.push(format!(
+                    "ALTER TABLE {table} ALTER COLUMN {} SET NOT NULL",
+                    column.name
+                ));
+            }
+        }
+        statements
+    }
+
+    /// Executes schema statements on one connection with the `[general.ddl]` timeouts, in one
+    /// transaction with `transactional_ddl`. A statement (or the transaction) that hit the lock
+    /// timeout is attempted again. A cancellation stops before the next statement (or transaction).
+    async fn execute_schema_statements(
+        &self,
+        statements: &[String],
+        config: &ForgeConfig,
+        cancel: &CancellationToken,
+        applied: &ForgeAppliedStatements,
+    ) -> Result<(), Box<dyn Error>> {
+        let pool = self.pool.as_ref().ok_or("No database pool available")?;
+        let retry = RetryPolicy::ddl_from_config(config);
+        let timeouts = postgres_ddl_timeout_sql(config);
+        let mut conn = pool.acquire().await?;
+        for sql in &timeouts {
+            sqlx::query(sql).execute(&mut *conn).await?;
+        }
+
+        // a chunked backfill commits every chunk, it and the statements after it run on their own
+        let backfill_start = statements
+            .iter()
+            .position(|sql| sql.starts_with("UPDATE "))
+            .unwrap_or(statements.len());
+        let (mut outside, inside): (Vec<String>, Vec<String>) = if config.is_transactional_ddl() {
+            statements[..backfill_start]
+                .iter()
+                .cloned()
+                .partition(|sql| runs_outside_transaction(sql))
+        } else {
+            (statements[..backfill_start].to_vec(), Vec::new())
+        };
+        outside.extend_from_slice(&statements[backfill_start..]);
+        let mut result = Ok(());
+        let mut cancelled = cancel.is_cancelled();
+        if !inside.is_empty() && !cancelled {
+            // DDL is transactional on PostgreSQL, a failure leaves the target unchanged
+            result = retry
+                .run_on_lock_timeout("apply_schema", &mut *conn, |conn| {
+                    let inside = inside.clone();
+                    Box::pin(async move {
+                        let mut tx = conn.begin().await?;
+                        for sql in &inside {
+                            debug!(statement = %sql, "executing schema statement");
+                            if let Err(e) = sqlx::query(sql).execute(&mut *tx).await {
+                                warn!(statement = %sql, "schema statement failed, all changes are rolled back");
+                                tx.rollback().await?;
+                                return Err(e);
+                            }
+                        }
+                        tx.commit().await
+                    })
+                })
+                .await;
+            if result.is_ok() {
+                applied.extend(&inside);
+            }
+        }
+        if result.is_ok() {
+            for sql in &outside {
+                if cancel.is_cancelled() {
+                    cancelled = true;
+                    break;
+                }
+                result = retry
+                    .run_on_lock_timeout("apply_schema", &mut *conn, |conn| {
+                        let sql = sql.clone();
+                        Box::pin(async move {
+                            debug!(statement = %sql, "executing schema statement");
+                            sqlx::query(&sql).execute(conn).await.map(|_| ())
+                        })
+                    })
+                    .await;
+                if result.is_err() {
+                    break;
+                }
+                applied.extend([sql]);
+            }
+        }
+
+        // the session keeps the timeouts, it is not returned to the pool
+        if !timeouts.is_empty() {
+            conn.close().await?;
+        }
+        result?;
+        if cancelled {
+            return Err(ForgeError::Cancelled.into());
+        }
+        Ok(())
+    }
+
+    /// `IF NOT EXISTS ` of a created object if the DDL is idempotent
+    fn if_not_exists(&self) -> &'static str {
+        if self.idempotent_ddl {
+            "IF NOT EXISTS "
+        } else {
+            ""
+        }
+    }
+
+    /// `ALTER TABLE ... ENABLE/DISABLE TRIGGER ALL`, including the foreign key triggers
+    #[must_use]
+    pub fn triggers_sql(&self, tables: &[String], enabled: bool) -> Vec<String> {
+        let action = if enabled { "ENABLE" } else { "DISABLE" };
+        tables
+            .iter()
+            .map(|table| format!("ALTER TABLE {table} {action} TRIGGER ALL"))
+            .collect()
+    }
+
+    /// switches the triggers of the tables, all or none in one transaction
+    async fn set_triggers_enabled(
+        &self,
+        tables: &[String],
+        enabled: bool,
+    ) -> Result<(), Box<dyn Error>> {
+        let pool = self.pool.as_ref().ok_or("No database pool available")?;
+        let mut tx = pool.begin().await?;
+        for sql in self.triggers_sql(tables, enabled) {
+            debug!(statement = %sql, "switching triggers");
+            sqlx::query(&sql).execute(&mut *tx).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// counts rows with foreign key values without referenced row, `foreign_key` holds the column
+    /// pairs of one constraint; a row with a NULL in any column is not checked (`MATCH SIMPLE`)
+    #[must_use]
+    pub fn foreign_key_violations_sql(
+        &self,
+        table_name: &str,
+        foreign_key: &[ForgeSchemaForeignKey],
+    ) -> String {
+        let Some(first) = foreign_key.first() else {
+            return "SELECT 0::bigint".to_string();
+        };
+        let join = foreign_key
+            .iter()
+            .map(|fk| format!("c.{} = r.{}", fk.column, fk.ref_column))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        let not_null = foreign_key
+            .iter()
+            .map(|fk| format!("c.{} IS NOT NULL", fk.column))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        format!(
+            "SELECT count(*) FROM {table_name} c LEFT JOIN {} r \
+             ON {join} \
+             WHERE {not_null} AND r.{} IS NULL",
+            first.ref_table, first.ref_column
+        )
+    }
+
+    /// multi-row INSERT with the values of all rows bound
+    fn bind_rows<'q>(
+        &self,
+        sql: &'q str,
+        columns: &[String],
+        rows: &'q [IndexMap<String, ForgeUniversalDataField>],
+    ) -> sqlx::query::Query<'q, sqlx::Postgres, sqlx::postgres::PgArguments> {
+        let mut query = sqlx::query(sql);
+        for row in rows {
+            for col in columns {
+                let val = row.get(col).unwrap_or(&ForgeUniversalDataField::Null);
+                query = self.bind_universal(query, val);
+            }
+        }
+        query
+    }
+
+    /// runs a statement with the values of all rows bound, in the write transaction if one is open
+    async fn execute_rows(
+        &self,
+        operation: &str,
+        sql: &str,
+        columns: &[String],
+        rows: &[IndexMap<String, ForgeUniversalDataField>],
+    ) -> Result<(), Box<dyn Error>> {
+        if let Some(tx) = self.write_tx.lock().await.as_mut() {
+            self.bind_rows(sql, columns, rows)
+                .execute(&mut **tx)
+                .await?;
+            return Ok(());
+        }
+        let pool = self.pool.as_ref().ok_or("No database pool available")?;
+        self.retry
+            .run(operation, || {
+                self.bind_rows(sql, columns, rows).execute(pool)
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// one multi-row INSERT of a batch, failed batches are retried row by row for logging
+    async fn insert_batch(
+        &self,
+        table_name: &str,
+        columns: &[String],
+        column_names: &str,
+        rows: &[IndexMap<String, ForgeUniversalDataField>],
+        dry_run: bool,
+        halt_on_error: bool,
+    ) -> Result<(), Box<dyn Error>> {
+        let sql = format!(
+            "INSERT INTO {} ({}) VALUES {}",
+            table_name,
+            column_names,
+            numbered_placeholders(rows.len(), columns.len())
+        );
+
+        if dry_run {
+            info!(table = table_name, rows = rows.len(), %sql, "dry run insert");
+        } else {
+            // inside a write transaction the first failure aborts the transaction
+            if let Some(tx) = self.write_tx.lock().await.as_mut() {
+                self.bind_rows(&sql, columns, rows)
+                    .execute(&mut **tx)
+                    .await?;
+                return Ok(());
+            }
+
+            let pool = self.pool.as_ref().ok_or("No database pool available")?;
+            // the multi-row insert is atomic, so it is retried as a whole on transient errors
+            let result = self
+                .retry
+                .run("insert_chunk", || {
+                    self.bind_rows(&sql, columns, rows).execute(pool)
+                })
+                .await;
+
+            if let Err(e) = result {
+                if halt_on_error {
+                    return Err(Box::new(e));
+                }
+                warn!(
+                    table = table_name,
+                    error = %e,
+                    "batch insert failed, retrying row-by-row for logging"
+                );
+                // Row by row retry for better error logging with careful NULL/JSON handling
+                for row_map in rows {
+                    let mut value_sql_parts: Vec<String> = Vec::with_capacity(columns.len());
+                    let mut arg_index = 1;
+
+                    // Build value list with per-value casting where needed
+                    for col in columns {
+                        let val = row_map.get(col).unwrap_or(&ForgeUniversalDataField::Null);
+                        match val {
+                            ForgeUniversalDataField::Null
+                            | ForgeUniversalDataField::ZeroDateTime => {
+                                value_sql_parts.push("NULL".to_string());
+                            }
+                            ForgeUniversalDataField::Json(_) => {
+                                value_sql_parts.push(format!("${arg_index}::jsonb"));
+                                arg_index += 1;
+                            }
+                            _ => {
+                                value_sql_parts.push(format!("${arg_index}"));
+                                arg_index += 1;
+                            }
+                        }
+                    }
+
+                    let single_sql = format!(
+                        "INSERT INTO {} ({}) VALUES ({})",
+                        table_name,
+                        column_names,
+                        value_sql_parts.join(", ")
+                    );
+
+                    let mut single_query = sqlx::query(&single_sql);
+
+                    // Bind only the non-NULL parameters in the same order we generated above
+                    for col in columns {
+                        let val = row_map.get(col).unwrap_or(&ForgeUniversalDataField::Null);
+                        match val {
+                            ForgeUniversalDataField::Null
+                            | ForgeUniversalDataField::ZeroDateTime => { /* no bind */ }
+                            ForgeUniversalDataField::Json(j) => {
+                                single_query = single_query.bind(sqlx::types::Json(j));
+                            }
+                            other => {
+                                single_query = self.bind_universal(single_query, other);
+                            }
+                        }
+                    }
+
+                    if let Err(se) = single_query.execute(pool).await {
+                        let row_str = format!("{row_map:?}");
+                        error!(table = table_name, row = %row_str, error = %se, "row insert failed");
+                        log_rejected_row(table_name, row_map, &se.to_string());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// reads the complete schema, retried as a whole by `fetch_schema`
+    async fn read_schema(&self, config: &ForgeConfig) -> Result<ForgeSchema, Box<dyn Error>> {
+        let pool = self.pool.as_ref().ok_or("No database pool available")?;
+        let db_name: String = sqlx::query_scalar("SELECT current_database()")
+            .fetch_one(pool)
+            .await?;
+        let server_version: String = sqlx::query_scalar("SHOW server_version")
+            .fetch_one(pool)
+            .await?;
+
+        // captured before the tables are read, a CDC run starting here misses no change;
+        // a standby reports the replayed position
+        let lsn = sqlx::query_scalar::<_, Option<String>>(
+            "SELECT (CASE WHEN pg_is_in_recovery() THEN pg_last_wal_replay_lsn() ELSE pg_current_wal_lsn() END)::text",
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap_or_else(|e| {
+            debug!(error = %e, "wal position not readable");
+            None
+        });
+        let source_position = ForgeSourcePosition {
+            lsn,
+            ..ForgeSourcePosition::default()
+        };
+        debug!(position = %source_position, "source position");
+
+        let mut tables = self.fetch_tables().await?;
+        for table in &mut tables {
+            table.columns = self.fetch_columns(&table.name, config).await?;
+
+            // Mark primary key columns
+            let pk_cols = self.fetch_primary_key_column_names(&table.name).await?;
+            for col in &mut table.columns {
+                if pk_cols.iter().any(|c| c == &col.name) {
+                    col.is_primary_key = true;
+                }
+            }
+
+            table.indices = self.fetch_indices(&table.name).await?;
+            table.foreign_keys = self.fetch_foreign_keys(&table.name).await?;
+            table.partitioning = self.fetch_partitioning(&table.name).await?;
+        }
+        let extensions = self.fetch_required_extensions().await?;
+        let domains = self.fetch_domains().await?;
+
+        Ok(ForgeSchema {
+            metadata: ForgeSchemaMetadata {
+                format_version: SCHEMA_FORMAT_VERSION,
+                source_system: "postgres".to_string(),
+                source_database_name: db_name,
+                created_at: chrono::Local::now().to_rfc3339(),
+                forge_version: env!("CARGO_PKG_VERSION").to_string(),
+                config_file: String::new(),
+                source_server_version: server_version,
+                source_position: Some(source_position),
+            },
+            tables,
+            extensions,
+            domains,
+        })
+    }
+
+    /// SELECT of the columns with digest columns replaced by their hex SHA-256 (same as MySQL),
+    /// `sha256()` is built in since PostgreSQL 11; `text_columns` are hashed as UTF-8 bytes
+    #[must_use]
+    pub fn build_select_digest_sql(
+        &self,
+        table_name: &str,
+        columns: &[String],
+        digest_columns: &[String],
+        text_columns: &[String],
+    ) -> String {
+        let select = columns
+            .iter()
+            .map(|col| {
+                if digest_columns.contains(col) && text_columns.contains(col) {
+                    format!("encode(sha256(convert_to({col}, 'UTF8')), 'hex') AS {col}")
+                } else if digest_columns.contains(col) {
+                    format!("encode(sha256({col}), 'hex') AS {col}")
+                } else {
+                    col.clone()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("SELECT {select} FROM {table_name}")
+    }
+
+    /// names of the primary key columns of a table in key order
+    pub async fn fetch_primary_key_column_names(
+        &self,
+        table_name: &str,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        let pool = self.pool.as_ref().ok_or("No database pool available")?;
+        let rows = sqlx::query(
+            "SELECT a.attname as column_name
+             FROM pg_index i
+             JOIN pg_attribute a ON a.attrelid = i.indrelid AND a.attnum = ANY(i.indkey)
+             JOIN pg_class c ON c.oid = i.indrelid
+             WHERE c.relname = $1 AND i.indisprimary
+             ORDER BY array_position(i.indkey::int2[], a.attnum)",
+        )
+        .bind(table_name)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| r.get::<String, _>("column_name"))
+            .collect())
+    }
+
+    /// names of all columns of a table in table order
+    pub async fn fetch_column_names(
+        &self,
+        table_name: &str,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        let pool = self.pool.as_ref().ok_or("No database pool available")?;
+        let names = sqlx::query_scalar(
+            "SELECT column_name::text FROM information_schema.columns
+            WHERE table_schema = 'public' AND table_name = $1
+            ORDER BY ordinal_position",
+        )
+        .bind(table_name)
+        .fetch_all(pool)
+        .await?;
+        Ok(names)
+    }
+
+    /// names of the columns of a table that are no `bytea`, their digest is taken of the UTF-8 text
+    pub async fn fetch_non_binary_column_names(
+        &self,
+        table_name: &str,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        let pool = self.pool.as_ref().ok_or("No database pool available")?;
+        let names = sqlx::query_scalar(
+            "SELECT column_name::text FROM information_schema.columns
+            WHERE table_schema = 'public' AND table_name = $1 AND data_type <> 'bytea'
+            ORDER BY ordinal_position",
+        )
+        .bind(table_name)
+        .fetch_all(pool)
+        .await?;
+        Ok(names)
+    }
 
-pub struct PostgresDriver {
-    pub pool: Option<PgPool>,
-}
+    /// selects the rows of a verification sample: first 32 bits of the MD5 of the key columns,
+    /// identical to the MySQL predicate for keys with the same text representation
+    #[must_use]
+    pub fn build_sample_predicate(
+        &self,
+        key_columns: &[String],
+        sample: ForgeVerifySample,
+    ) -> String {
+        // a seed salts the hash, so another seed selects other rows
+        let columns = (sample.seed != 0)
+            .then(|| format!("'{}'", sample.seed))
+            .into_iter()
+            .chain(key_columns.iter().cloned())
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            "('x' || substr(md5(concat_ws('|', {columns})), 1, 8))::bit(32)::bigint % {} < {}",
+            ForgeVerifySample::BUCKETS,
+            sample.basis_points
+        )
+    }
+
+    /// WHERE/ORDER BY of a keyset stream, i.e. " WHERE (a, b) > ($1, $2) ORDER BY a, b"
+    #[must_use]
+    pub fn build_keyset_clause(&self, key_columns: &[String], after: bool) -> String {
+        let columns = key_columns.join(", ");
+        if after {
+            let placeholders = (1..=key_columns.len())
+                .map(|i| format!("${i}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(" WHERE ({columns}) > ({placeholders}) ORDER BY {columns}")
+        } else {
+            format!(" ORDER BY {columns}")
+        }
+    }
+
+    /// WHERE of the row with the given key values, i.e. " WHERE (a, b) = ($1, $2)"
+    #[must_use]
+    pub fn build_key_clause(&self, key_columns: &[String]) -> String {
+        let placeholders = (1..=key_columns.len())
+            .map(|i| format!("${i}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(" WHERE ({}) = ({placeholders})", key_columns.join(", "))
+    }
+
+    /// SELECT of the key values of the last row in key order
+    #[must_use]
+    pub fn build_last_key_sql(&self, table_name: &str, key_columns: &[String]) -> String {
+        let order = key_columns
+            .iter()
+            .map(|col| format!("{col} DESC"))
+            .collect::<Vec<_>>();
+        format!(
+            "SELECT {} FROM {table_name} ORDER BY {} LIMIT 1",
+            key_columns.join(", "),
+            order.join(", ")
+        )
+    }
+
+    /// GRANT statement for one source grant, `None` if the grantee is skipped
+    /// or none of the privileges exists on PostgreSQL table level
+    #[must_use]
+    pub fn build_grant_sql(
+        &self,
+        grant: &ForgeSchemaGrant,
+        config: &ForgeConfig,
+    ) -> Option<String> {
+        const TABLE_PRIVILEGES: [&str; 7] = [
+            "SELECT",
+            "INSERT",
+            "UPDATE",
+            "DELETE",
+            "TRUNCATE",
+            "REFERENCES",
+            "TRIGGER",
+        ];
+
+        // mysql grantees 'user'@'host' become the role "user"
+        let role = grants::grantee_user_name(&grants::map_grantee(&grant.grantee, config)?);
+        let role = if role.eq_ignore_ascii_case("public") {
+            "PUBLIC".to_string()
+        } else {
+            format!("\"{role}\"")
+        };
+
+        let privileges: Vec<&str> = grant
+            .privileges
+            .iter()
+            .map(String::as_str)
+            .filter(|p| {
+                let supported = TABLE_PRIVILEGES.contains(p);
+                if !supported {
+                    warn!(table = %grant.table_name, privilege = %p, "privilege not supported on postgres table level, skipped");
+                }
+                supported
+            })
+            .collect();
+
+        if privileges.is_empty() {
+            return None;
+        }
+
+        Some(format!(
+            "GRANT {} ON {} TO {};",
+            privileges.join(", "),
+            grant.table_name,
+            role
+        ))
+    }
 
-impl PostgresDriver {
     pub fn bind_universal<'q>(
         &self,
         query: sqlx::query::Query<'q, sqlx::Postgres, sqlx::postgres::PgArguments>,
@@ -25,7 +1060,11 @@ impl PostgresDriver {
     ) -> sqlx::query::Query<'q, sqlx::Postgres, sqlx::postgres::PgArguments> {
         match val {
             ForgeUniversalDataField::Integer(i) => query.bind(i),
-            ForgeUniversalDataField::UnsignedInteger(u) => query.bind(*u as i64), // Postgres lacks unsigned
+            // Postgres lacks unsigned, values above bigint are bound as numeric (numeric(20,0) column)
+            ForgeUniversalDataField::UnsignedInteger(u) => match i64::try_from(*u) {
+                Ok(i) => query.bind(i),
+                Err(_) => query.bind(rust_decimal::Decimal::from(*u)),
+            },
             ForgeUniversalDataField::Float(f) => query.bind(f),
             ForgeUniversalDataField::Text(s) => query.bind(s),
             ForgeUniversalDataField::Binary(bin) => query.bind(bin),
@@ -38,6 +1077,15 @@ impl PostgresDriver {
             ForgeUniversalDataField::Json(j) => query.bind(j),
             ForgeUniversalDataField::Uuid(u) => query.bind(u),
             ForgeUniversalDataField::Inet(i) => query.bind(i),
+            ForgeUniversalDataField::Array(a) => match a {
+                ForgeArray::SmallInt(v) => query.bind(v),
+                ForgeArray::Integer(v) => query.bind(v),
+                ForgeArray::BigInt(v) => query.bind(v),
+                ForgeArray::Real(v) => query.bind(v),
+                ForgeArray::Double(v) => query.bind(v),
+                ForgeArray::Text(v) => query.bind(v),
+                ForgeArray::Boolean(v) => query.bind(v),
+            },
             ForgeUniversalDataField::Null => query.bind(None::<String>),
             ForgeUniversalDataField::ZeroDateTime => query.bind(None::<String>), // Postgres doesn't support 0000-00-00
         }
@@ -45,10 +1093,15 @@ impl PostgresDriver {
 
     pub async fn fetch_tables(&self) -> Result<Vec<ForgeSchemaTable>, Box<dyn Error>> {
         let pool = self.pool.as_ref().ok_or("No database pool available")?;
+        // partitions are part of their partitioned table, not tables of their own
         let rows = sqlx::query(
             "SELECT table_name, NULL as table_comment 
              FROM information_schema.tables 
-             WHERE table_schema = 'public' AND table_type = 'BASE TABLE'",
+             WHERE table_schema = 'public' AND table_type = 'BASE TABLE'
+               AND NOT EXISTS (
+                 SELECT 1 FROM pg_class c JOIN pg_namespace n ON n.oid = c.relnamespace
+                 WHERE n.nspname = 'public' AND c.relname = table_name AND c.relispartition)
+             ORDER BY table_name",
         )
         .fetch_all(pool)
         .await?;
@@ -62,6 +1115,7 @@ impl PostgresDriver {
                 indices: Vec::new(),
                 foreign_keys: Vec::new(),
                 comment: None,
+                partitioning: None,
             });
         }
         Ok(tables)
@@ -93,7 +1147,9 @@ impl PostgresDriver {
                 numeric_scale,
                 is_nullable,
                 column_default,
-                udt_name
+                udt_name,
+                is_identity,
+                domain_name
             FROM information_schema.columns
             WHERE table_schema = 'public' AND table_name = $1
             ORDER BY ordinal_position";
@@ -108,15 +1164,16 @@ impl PostgresDriver {
             let data_type: String = row.get("data_type");
 
             // Determine effective and mapped type with special handling for arrays
+            let effective_type = if data_type == "USER-DEFINED" || data_type == "ARRAY" {
+                &udt_name
+            } else {
+                &data_type
+            };
             let mapped_type = if data_type.eq_ignore_ascii_case("ARRAY") {
-                // for replication we simplify arrays > json jsonb
-                "json".to_string()
+                // udt_name of an array is its element type with a leading underscore
+                let element = udt_name.strip_prefix('_').unwrap_or(&udt_name);
+                format!("{}[]", self.map_postgres_type(element, config))
             } else {
-                let effective_type = if data_type == "USER-DEFINED" {
-                    &udt_name
-                } else {
-                    &data_type
-                };
                 self.map_postgres_type(effective_type, config)
             };
 
@@ -125,6 +1182,10 @@ impl PostgresDriver {
             let scale: Option<i32> = row.get("numeric_scale");
             let is_nullable: String = row.get("is_nullable");
             let default: Option<String> = row.get("column_default");
+            // serial columns default to their sequence, identity columns have no default
+            let is_identity: Option<String> = row.get("is_identity");
+            // information_schema reports the base type of a domain column
+            let domain: Option<String> = row.get("domain_name");
 
             columns.push(ForgeSchemaColumn {
                 name,
@@ -135,11 +1196,17 @@ impl PostgresDriver {
                 is_nullable: is_nullable == "YES",
                 is_primary_key: false, // Will be updated in fetch_indices or similar logic
                 is_unsigned: false,    // Postgres has no unsigned
-                auto_increment: default.as_deref().is_some_and(|d| d.contains("nextval")),
+                auto_increment: default.as_deref().is_some_and(|d| d.contains("nextval"))
+                    || is_identity.as_deref() == Some("YES"),
                 default,
                 comment: None,
                 on_update: None,
                 enum_values: None,
+                generation_expression: None,
+                is_generated_stored: false,
+                is_invisible: false,
+                source_type: Some(effective_type.to_lowercase()),
+                domain,
             });
         }
 
@@ -167,7 +1234,7 @@ impl PostgresDriver {
                 AND i.oid = ix.indexrelid
                 AND a.attrelid = t.oid
                 AND a.attnum = ANY(ix.indkey)
-                AND t.relkind = 'r'
+                AND t.relkind IN ('r', 'p')
                 AND t.relname = $1
             ORDER BY
                 t.relname,
@@ -190,6 +1257,7 @@ impl PostgresDriver {
                     is_unique,
                     index_type: None,
                     column_prefixes: None,
+                    expressions: None,
                 });
             entry.columns.push(column_name);
         }
@@ -197,26 +1265,161 @@ impl PostgresDriver {
         Ok(indices_map.into_iter().map(|(_, v)| v).collect())
     }
 
+    /// Extensions owning a column type (or its array element type) or a function of a column
+    /// default of the tables.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the catalog cannot be read.
+    pub async fn fetch_required_extensions(&self) -> Result<Vec<String>, Box<dyn Error>> {
+        let pool = self.pool.as_ref().ok_or("No database pool available")?;
+        let extensions = sqlx::query_scalar(
+            "SELECT e.extname
+             FROM pg_attribute a
+             JOIN pg_class c ON c.oid = a.attrelid
+             JOIN pg_namespace n ON n.oid = c.relnamespace
+             JOIN pg_type t ON t.oid = a.atttypid
+             JOIN pg_depend d ON d.classid = 'pg_type'::regclass AND d.deptype = 'e'
+               AND d.objid = CASE WHEN t.typcategory = 'A' THEN t.typelem ELSE t.oid END
+             JOIN pg_extension e ON e.oid = d.refobjid
+             WHERE n.nspname = 'public' AND c.relkind IN ('r', 'p')
+               AND a.attnum > 0 AND NOT a.attisdropped
+             UNION
+             SELECT e.extname
+             FROM pg_attrdef ad
+             JOIN pg_class c ON c.oid = ad.adrelid
+             JOIN pg_namespace n ON n.oid = c.relnamespace
+             JOIN pg_depend f ON f.classid = 'pg_attrdef'::regclass AND f.objid = ad.oid
+               AND f.refclassid = 'pg_proc'::regclass
+             JOIN pg_depend d ON d.classid = 'pg_proc'::regclass AND d.objid = f.refobjid
+               AND d.deptype = 'e'
+             JOIN pg_extension e ON e.oid = d.refobjid
+             WHERE n.nspname = 'public'
+             ORDER BY 1",
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(extensions)
+    }
+
+    /// Domains of the public schema in creation order, a domain over a domain follows its base.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the catalog cannot be read.
+    pub async fn fetch_domains(&self) -> Result<Vec<ForgeSchemaDomain>, Box<dyn Error>> {
+        let pool = self.pool.as_ref().ok_or("No database pool available")?;
+        let rows = sqlx::query(
+            "SELECT t.typname AS name,
+                    format_type(t.typbasetype, t.typtypmod) AS data_type,
+                    t.typnotnull AS not_null,
+                    t.typdefault AS default_value,
+                    COALESCE(array_agg(pg_get_constraintdef(c.oid) ORDER BY c.conname)
+                        FILTER (WHERE c.oid IS NOT NULL), '{}') AS checks
+             FROM pg_type t
+             JOIN pg_namespace n ON n.oid = t.typnamespace
+             LEFT JOIN pg_constraint c ON c.contypid = t.oid AND c.contype = 'c'
+             WHERE t.typtype = 'd' AND n.nspname = 'public'
+             GROUP BY t.oid, t.typname, t.typbasetype, t.typtypmod, t.typnotnull, t.typdefault
+             ORDER BY t.oid",
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| ForgeSchemaDomain {
+                name: row.get("name"),
+                data_type: row.get("data_type"),
+                not_null: row.get("not_null"),
+                default: row.get("default_value"),
+                checks: row.get("checks"),
+            })
+            .collect())
+    }
+
+    /// names of the extensions installed in the database
+    async fn fetch_installed_extensions(&self) -> Result<HashSet<String>, Box<dyn Error>> {
+        let pool = self.pool.as_ref().ok_or("No database pool available")?;
+        let extensions: Vec<String> = sqlx::query_scalar("SELECT extname FROM pg_extension")
+            .fetch_all(pool)
+            .await?;
+        Ok(extensions.into_iter().collect())
+    }
+
+    /// Partition strategy, key and partitions of a declaratively partitioned table.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the catalog cannot be read.
+    pub async fn fetch_partitioning(
+        &self,
+        table_name: &str,
+    ) -> Result<Option<ForgeSchemaPartitioning>, Box<dyn Error>> {
+        let pool = self.pool.as_ref().ok_or("No database pool available")?;
+        let key_def: Option<String> = sqlx::query_scalar(
+            "SELECT pg_get_partkeydef(c.oid)
+             FROM pg_partitioned_table pt
+             JOIN pg_class c ON c.oid = pt.partrelid
+             JOIN pg_namespace n ON n.oid = c.relnamespace
+             WHERE n.nspname = 'public' AND c.relname = $1",
+        )
+        .bind(table_name)
+        .fetch_optional(pool)
+        .await?;
+        let Some(mut partitioning) = key_def.as_deref().and_then(parse_partition_key) else {
+            return Ok(None);
+        };
+
+        let rows = sqlx::query(
+            "SELECT c.relname AS name, pg_get_expr(c.relpartbound, c.oid) AS bound
+             FROM pg_inherits i
+             JOIN pg_class c ON c.oid = i.inhrelid
+             JOIN pg_class p ON p.oid = i.inhparent
+             JOIN pg_namespace n ON n.oid = p.relnamespace
+             WHERE n.nspname = 'public' AND p.relname = $1
+             ORDER BY c.relname",
+        )
+        .bind(table_name)
+        .fetch_all(pool)
+        .await?;
+        for row in rows {
+            partitioning.partitions.push(ForgeSchemaPartition {
+                name: row.get("name"),
+                bound: row.get("bound"),
+            });
+        }
+        Ok(Some(partitioning))
+    }
+
     pub async fn fetch_foreign_keys(
         &self,
         table_name: &str,
     ) -> Result<Vec<ForgeSchemaForeignKey>, Box<dyn Error>> {
         let pool = self.pool.as_ref().ok_or("No database pool available")?;
+        // the referenced column is paired by its position in the referenced key
         let sql = "
             SELECT
                 tc.constraint_name,
                 kcu.column_name,
-                ccu.table_name AS foreign_table_name,
-                ccu.column_name AS foreign_column_name
+                ref.table_name AS foreign_table_name,
+                ref.column_name AS foreign_column_name,
+                rc.delete_rule,
+                rc.update_rule
             FROM
                 information_schema.table_constraints AS tc
                 JOIN information_schema.key_column_usage AS kcu
                   ON tc.constraint_name = kcu.constraint_name
                   AND tc.table_schema = kcu.table_schema
-                JOIN information_schema.constraint_column_usage AS ccu
-                  ON ccu.constraint_name = tc.constraint_name
-                  AND ccu.table_schema = tc.table_schema
-            WHERE tc.constraint_type = 'FOREIGN KEY' AND tc.table_name=$1";
+                JOIN information_schema.referential_constraints AS rc
+                  ON rc.constraint_name = tc.constraint_name
+                  AND rc.constraint_schema = tc.table_schema
+                JOIN information_schema.key_column_usage AS ref
+                  ON ref.constraint_name = rc.unique_constraint_name
+                  AND ref.constraint_schema = rc.unique_constraint_schema
+                  AND ref.ordinal_position = kcu.position_in_unique_constraint
+            WHERE tc.constraint_type = 'FOREIGN KEY' AND tc.table_schema = 'public'
+              AND tc.table_name=$1
+            ORDER BY tc.constraint_name, kcu.ordinal_position";
 
         let rows = sqlx::query(sql).bind(table_name).fetch_all(pool).await?;
 
@@ -227,8 +1430,8 @@ impl PostgresDriver {
                 column: row.get("column_name"),
                 ref_table: row.get("foreign_table_name"),
                 ref_column: row.get("foreign_column_name"),
-                on_delete: None,
-                on_update: None,
+                on_delete: row.get("delete_rule"),
+                on_update: row.get("update_rule"),
             });
         }
         Ok(fks)
@@ -236,18 +1439,15 @@ impl PostgresDriver {
 
     #[must_use]
     pub fn map_to_postgres_write_type(&self, internal_type: &str, config: &ForgeConfig) -> String {
-        let lower = internal_type.to_lowercase();
-        // Check config on_write mappings first
-        if let Some(write_types) = config.get_type_list("postgres", "on_write")
-            && let Some(mapped) = write_types.get(&lower)
-        {
-            return mapped.clone();
-        }
-        lower
+        config.map_write_type(ForgeDialect::Postgres, internal_type)
     }
 
     #[must_use]
     pub fn field_migration_sql(&self, field: &ForgeSchemaColumn, config: &ForgeConfig) -> String {
+        if let Some(ref domain) = field.domain {
+            // length and precision are part of the domain
+            return domain_field_sql(field, domain);
+        }
         // Map internal type to valid Postgres type via on_write config + fallbacks
         let pg_type = self.map_to_postgres_write_type(&field.data_type, config);
         let t = pg_type.to_lowercase();
@@ -274,54 +1474,27 @@ impl PostgresDriver {
                 }
             }
             // Append precision/scale only for numeric/decimal
-            else if (t == "numeric" || t == "decimal")
-                && let (Some(p), Some(s)) = (field.precision, field.scale)
-            {
-                sql.push_str(&format!("({p},{s})"));
+            else if t == "numeric" || t == "decimal" {
+                match (field.precision, field.scale) {
+                    (Some(p), Some(s)) => sql.push_str(&format!("({p},{s})")),
+                    (Some(p), None) => sql.push_str(&format!("({p})")),
+                    _ => {}
+                }
             }
             // Arrays, integer/bigint/double precision/timestamp: no size/precision suffix
         }
 
-        let time_date_force_nullable = true; // TODO from config.toml
-
-        // special logic for "NUT NULL" with time/date types (because of mysql ...)
-        let is_time_type = t.contains("timestamp") || t.contains("date") || t.contains("time");
-
         // NULLABILITY LOGIC
         // if it is a time-type we force NULL (allow NULL)
         // because MySQL hides "0000-00-00" in NOT NULL columns
-        if is_time_type {
+        if is_time_type(&t) {
             sql.push_str(" NULL"); // overwrites the NOT NULL from Source
         } else if !field.is_nullable {
             sql.push_str(" NOT NULL");
         } else {
             sql.push_str(" NULL");
         }
-        // *****************************************************************
-        // Do not carry over default nextval(...) from source; IDENTITY already covers it
-        if !field.auto_increment
-            && let Some(def) = &field.default
-        {
-            let t = pg_type.to_lowercase();
-            let is_time_type = t.contains("timestamp") || t.contains("date") || t.contains("time");
-
-            let mut clean_def = def.clone();
-
-            if is_time_type {
-                // MySQL Zero-Dates to NULL
-                if def.contains("0000-00-00") || def == "0" || def == "'0'" {
-                    clean_def = "NULL".to_string();
-                }
-                // MySQL functions to Postgres functions
-                else if def.to_uppercase() == "CURRENT_TIMESTAMP" {
-                    clean_def = "CURRENT_TIMESTAMP".to_string(); // In PG without "
-                }
-                // fix missing " in literals (the ":" error)
-                else if def.contains(':') && !def.starts_with('\'') {
-                    clean_def = format!("'{}'", def);
-                }
-            }
-
+        if let Some(clean_def) = postgres_default_sql(field, &pg_type) {
             sql.push_str(&format!(" DEFAULT {}", clean_def));
         }
 
@@ -340,7 +1513,15 @@ impl PostgresDriver {
             .map(|c| self.field_migration_sql(c, config))
             .collect();
 
-        format!("CREATE TABLE {} (\n  {}\n)", table.name, cols.join(",\n  "))
+        let partition_by = postgres_partitioning(table)
+            .map(|p| format!(" PARTITION BY {} ({})", p.strategy.to_uppercase(), p.key))
+            .unwrap_or_default();
+        format!(
+            "CREATE TABLE {}{} (\n  {}\n){partition_by}",
+            self.if_not_exists(),
+            table.name,
+            cols.join(",\n  ")
+        )
     }
 
     pub fn create_table_migration_sql(
@@ -351,8 +1532,25 @@ impl PostgresDriver {
         let mut statements = Vec::new();
         statements.push(self.build_postgres_create_table_sql(target_table, config));
 
-        for index in &target_table.indices {
-            statements.push(self.build_postgres_create_index_sql(&target_table.name, index));
+        // partitions before the indices, indices of the table are created on all partitions
+        if let Some(partitioning) = postgres_partitioning(target_table) {
+            for partition in &partitioning.partitions {
+                statements.push(build_postgres_partition_sql(
+                    &target_table.name,
+                    partition,
+                    self.idempotent_ddl,
+                ));
+            }
+        }
+
+        for index in sorted_by_name(&target_table.indices, |i| &i.name) {
+            if is_postgres_index(&target_table.name, index) {
+                statements.push(self.build_postgres_create_index_sql(&target_table.name, index));
+            }
+        }
+
+        if on_update_trigger(config) {
+            statements.extend(on_update_trigger_sql(target_table));
         }
 
         Ok(statements)
@@ -408,19 +1606,72 @@ impl PostgresDriver {
                 }
             } else {
                 statements.push(format!(
-                    "ALTER TABLE {} ADD COLUMN {}",
+                    "ALTER TABLE {} ADD COLUMN {}{}",
                     source_table.name,
+                    self.if_not_exists(),
                     self.field_migration_sql(source_col, config)
                 ));
             }
         }
 
+        // partitions, the partitioning of an existing table cannot be changed
+        if let Some(partitioning) = postgres_partitioning(source_table) {
+            let target_partitions = target_table
+                .partitioning
+                .as_ref()
+                .map(|p| p.partitions.as_slice())
+                .unwrap_or_default();
+            if target_table.partitioning.is_none() {
+                warn!(
+                    table = %source_table.name,
+                    "target table is not partitioned, the partitions are not created"
+                );
+            } else {
+                for partition in &partitioning.partitions {
+                    if !target_partitions.iter().any(|p| p.name == partition.name) {
+                        statements.push(build_postgres_partition_sql(
+                            &source_table.name,
+                            partition,
+                            self.idempotent_ddl,
+                        ));
+                    }
+                }
+                if destructive {
+                    for partition in sorted_by_name(target_partitions, |p| &p.name) {
+                        if !partitioning
+                            .partitions
+                            .iter()
+                            .any(|p| p.name == partition.name)
+                        {
+                            statements.push(format!("DROP TABLE IF EXISTS {}", partition.name));
+                        }
+                    }
+                }
+            }
+        }
+
+        // the target does not report `ON UPDATE`, the trigger is replaced when such a column is added
+        if on_update_trigger(config)
+            && source_table
+                .columns
+                .iter()
+                .any(|c| c.on_update.is_some() && !target_cols.contains_key(&c.name))
+        {
+            statements.extend(on_update_trigger_sql(source_table));
+        }
+
         if destructive {
-            for target_col in &target_table.columns {
+            for target_col in sorted_by_name(&target_table.columns, |c| &c.name) {
                 if !source_cols.contains_key(&target_col.name) {
                     statements.push(format!(
-                        "ALTER TABLE {} DROP COLUMN {}",
-                        source_table.name, target_col.name
+                        "ALTER TABLE {} DROP COLUMN {}{}",
+                        source_table.name,
+                        if self.idempotent_ddl {
+                            "IF EXISTS "
+                        } else {
+                            ""
+                        },
+                        target_col.name
                     ));
                 }
             }
@@ -437,15 +1688,18 @@ impl PostgresDriver {
             target_indices.insert(idx.name.clone(), idx);
         }
 
-        for source_idx in &source_table.indices {
-            if !target_indices.contains_key(&source_idx.name) {
+        // sorted by name for a stable statement order
+        for source_idx in sorted_by_name(&source_table.indices, |i| &i.name) {
+            if !target_indices.contains_key(&source_idx.name)
+                && is_postgres_index(&source_table.name, source_idx)
+            {
                 statements
                     .push(self.build_postgres_create_index_sql(&source_table.name, source_idx));
             }
         }
 
         if destructive {
-            for target_idx in &target_table.indices {
+            for target_idx in sorted_by_name(&target_table.indices, |i| &i.name) {
                 if !source_indices.contains_key(&target_idx.name) {
                     statements.push(format!("DROP INDEX IF EXISTS {}", target_idx.name));
                 }
@@ -455,6 +1709,58 @@ impl PostgresDriver {
         Ok(statements)
     }
 
+    /// `ALTER TABLE ... ADD CONSTRAINT ... FOREIGN KEY`
+    #[must_use]
+    pub(crate) fn build_postgres_add_foreign_key_sql(
+        &self,
+        table_name: &str,
+        constraint: &ForeignKeyConstraint,
+    ) -> String {
+        let mut sql = format!(
+            "ALTER TABLE {table_name} ADD CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {} ({})",
+            constraint.name,
+            constraint.columns.join(", "),
+            constraint.ref_table,
+            constraint.ref_columns.join(", ")
+        );
+        if let Some(ref action) = constraint.on_delete {
+            sql.push_str(&format!(" ON DELETE {action}"));
+        }
+        if let Some(ref action) = constraint.on_update {
+            sql.push_str(&format!(" ON UPDATE {action}"));
+        }
+        sql
+    }
+
+    /// statements of the foreign key changes: the drops before and the additions after the
+    /// other schema changes
+    pub(crate) fn foreign_key_migration_sql(
+        &self,
+        changes: &ForeignKeyChanges,
+    ) -> (Vec<String>, Vec<String>) {
+        let drops = changes
+            .drop
+            .iter()
+            .map(|(table, constraint)| {
+                format!(
+                    "ALTER TABLE {table} DROP CONSTRAINT {}{}",
+                    if self.idempotent_ddl {
+                        "IF EXISTS "
+                    } else {
+                        ""
+                    },
+                    constraint.name
+                )
+            })
+            .collect();
+        let adds = changes
+            .add
+            .iter()
+            .map(|(table, constraint)| self.build_postgres_add_foreign_key_sql(table, constraint))
+            .collect();
+        (drops, adds)
+    }
+
     #[must_use]
     pub fn build_postgres_create_index_sql(
         &self,
@@ -462,12 +1768,22 @@ impl PostgresDriver {
         index: &ForgeSchemaIndex,
     ) -> String {
         let unique = if index.is_unique { "UNIQUE " } else { "" };
+        let parts: Vec<String> = index
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, c)| match index.expression(i) {
+                Some(expression) => format!("({})", postgres_index_expression(expression)),
+                None => c.clone(),
+            })
+            .collect();
         format!(
-            "CREATE {}INDEX {} ON {} ({})",
+            "CREATE {}INDEX {}{} ON {} ({})",
             unique,
+            self.if_not_exists(),
             index.name,
             table_name,
-            index.columns.join(", ")
+            parts.join(", ")
         )
     }
 
@@ -478,7 +1794,16 @@ impl PostgresDriver {
         let mut values = Vec::with_capacity(row.columns().iter().count());
 
         for (i, col) in row.columns().iter().enumerate() {
-            let type_name = col.type_info().name();
+            // a domain is read as its base type
+            let mut type_info = col.type_info();
+            let mut is_domain = false;
+            while let PgTypeKind::Domain(base) = type_info.kind() {
+                type_info = base;
+                is_domain = true;
+            }
+            // extension types like citext report their lowercase name
+            let type_name = type_info.name().to_uppercase();
+            let type_name = type_name.as_str();
             let col_name = col.name();
 
             // local error adapter
@@ -496,113 +1821,87 @@ impl PostgresDriver {
 
             let val = match type_name {
                 "INT2" | "SMALLINT" | "SMALLSERIAL" => ForgeUniversalDataField::Integer(i64::from(
-                    row.try_get::<i16, _>(i).map_err(to_decode_err)?,
+                    decode_column::<i16>(row, i, is_domain).map_err(to_decode_err)?,
                 )),
                 "INT4" | "INTEGER" | "SERIAL" => ForgeUniversalDataField::Integer(i64::from(
-                    row.try_get::<i32, _>(i).map_err(to_decode_err)?,
+                    decode_column::<i32>(row, i, is_domain).map_err(to_decode_err)?,
                 )),
                 "INT8" | "BIGINT" | "BIGSERIAL" => ForgeUniversalDataField::Integer(
-                    row.try_get::<i64, _>(i).map_err(to_decode_err)?,
+                    decode_column::<i64>(row, i, is_domain).map_err(to_decode_err)?,
                 ),
                 "FLOAT4" | "REAL" => ForgeUniversalDataField::Float(f64::from(
-                    row.try_get::<f32, _>(i).map_err(to_decode_err)?,
+                    decode_column::<f32>(row, i, is_domain).map_err(to_decode_err)?,
                 )),
-                "FLOAT8" | "DOUBLE PRECISION" => {
-                    ForgeUniversalDataField::Float(row.get::<f64, _>(i))
-                }
-                "TEXT" | "VARCHAR" | "CHAR" | "BPCHAR" | "NAME" => ForgeUniversalDataField::Text(
-                    row.try_get::<String, _>(i).map_err(to_decode_err)?,
+                "FLOAT8" | "DOUBLE PRECISION" => ForgeUniversalDataField::Float(
+                    decode_column::<f64>(row, i, is_domain).map_err(to_decode_err)?,
                 ),
+                "TEXT" | "VARCHAR" | "CHAR" | "BPCHAR" | "NAME" | "CITEXT" => {
+                    ForgeUniversalDataField::Text(
+                        decode_column::<String>(row, i, is_domain).map_err(to_decode_err)?,
+                    )
+                }
                 "BYTEA" => ForgeUniversalDataField::Binary(
-                    row.try_get::<Vec<u8>, _>(i).map_err(to_decode_err)?,
+                    decode_column::<Vec<u8>>(row, i, is_domain).map_err(to_decode_err)?,
                 ),
                 "BOOL" | "BOOLEAN" => ForgeUniversalDataField::Boolean(
-                    row.try_get::<bool, _>(i).map_err(to_decode_err)?,
+                    decode_column::<bool>(row, i, is_domain).map_err(to_decode_err)?,
                 ),
                 "DATE" => ForgeUniversalDataField::Date(
-                    row.try_get::<chrono::NaiveDate, _>(i)
-                        .map_err(to_decode_err)?,
+                    decode_column::<chrono::NaiveDate>(row, i, is_domain).map_err(to_decode_err)?,
                 ),
                 "TIME" | "TIMETZ" => ForgeUniversalDataField::Time(
-                    row.try_get::<chrono::NaiveTime, _>(i)
-                        .map_err(to_decode_err)?,
+                    decode_column::<chrono::NaiveTime>(row, i, is_domain).map_err(to_decode_err)?,
                 ),
                 "TIMESTAMP" => ForgeUniversalDataField::DateTime(
-                    row.try_get::<chrono::NaiveDateTime, _>(i)
+                    decode_column::<chrono::NaiveDateTime>(row, i, is_domain)
                         .map_err(to_decode_err)?,
                 ),
                 "TIMESTAMPTZ" => {
-                    let dt_utc = row
-                        .try_get::<chrono::DateTime<chrono::Utc>, _>(i)
+                    let dt_utc = decode_column::<chrono::DateTime<chrono::Utc>>(row, i, is_domain)
                         .map_err(to_decode_err)?;
-                    ForgeUniversalDataField::DateTime(dt_utc.naive_utc())
+                    let utc = ForgeTimeZone::default();
+                    ForgeUniversalDataField::DateTime(
+                        utc.convert(dt_utc.naive_utc(), self.time_zone.unwrap_or(utc)),
+                    )
                 }
                 "NUMERIC" | "DECIMAL" => ForgeUniversalDataField::Decimal(
-                    row.try_get::<rust_decimal::Decimal, _>(i)
+                    decode_column::<rust_decimal::Decimal>(row, i, is_domain)
                         .map_err(to_decode_err)?,
                 ),
                 "JSON" | "JSONB" => ForgeUniversalDataField::Json(
-                    row.try_get::<serde_json::Value, _>(i)
-                        .map_err(to_decode_err)?,
+                    decode_column::<serde_json::Value>(row, i, is_domain).map_err(to_decode_err)?,
                 ),
                 "UUID" => ForgeUniversalDataField::Uuid(
-                    row.try_get::<sqlx::types::Uuid, _>(i)
-                        .map_err(to_decode_err)?,
+                    decode_column::<sqlx::types::Uuid>(row, i, is_domain).map_err(to_decode_err)?,
                 ),
                 "INET" | "CIDR" => ForgeUniversalDataField::Inet(
-                    row.try_get::<ipnetwork::IpNetwork, _>(i)
+                    decode_column::<ipnetwork::IpNetwork>(row, i, is_domain)
                         .map_err(to_decode_err)?,
                 ),
-                // convert arrays into JSON
-                s if s == "INT2[]" || s == "SMALLINT[]" => {
-                    let v = row.try_get::<Vec<i16>, _>(i).map_err(to_decode_err)?;
-                    let arr = v
-                        .into_iter()
-                        .map(|x| serde_json::Value::from(i64::from(x)))
-                        .collect();
-                    ForgeUniversalDataField::Json(serde_json::Value::Array(arr))
-                }
-                s if s == "INT4[]" || s == "INTEGER[]" => {
-                    let v = row.try_get::<Vec<i32>, _>(i).map_err(to_decode_err)?;
-                    let arr = v
-                        .into_iter()
-                        .map(|x| serde_json::Value::from(i64::from(x)))
-                        .collect();
-                    ForgeUniversalDataField::Json(serde_json::Value::Array(arr))
-                }
-                s if s == "INT8[]" || s == "BIGINT[]" => {
-                    let v = row.try_get::<Vec<i64>, _>(i).map_err(to_decode_err)?;
-                    let arr = v.into_iter().map(serde_json::Value::from).collect();
-                    ForgeUniversalDataField::Json(serde_json::Value::Array(arr))
-                }
-                s if s == "TEXT[]"
-                    || s == "VARCHAR[]"
-                    || s == "CHAR[]"
-                    || s == "BPCHAR[]"
-                    || s == "NAME[]" =>
-                {
-                    let v = row.try_get::<Vec<String>, _>(i).map_err(to_decode_err)?;
-                    let arr = v.into_iter().map(serde_json::Value::from).collect();
-                    ForgeUniversalDataField::Json(serde_json::Value::Array(arr))
-                }
-                s if s == "BOOL[]" || s == "BOOLEAN[]" => {
-                    let v = row.try_get::<Vec<bool>, _>(i).map_err(to_decode_err)?;
-                    let arr = v.into_iter().map(serde_json::Value::from).collect();
-                    ForgeUniversalDataField::Json(serde_json::Value::Array(arr))
-                }
-                s if s == "FLOAT4[]" || s == "REAL[]" => {
-                    let v = row.try_get::<Vec<f32>, _>(i).map_err(to_decode_err)?;
-                    let arr = v
-                        .into_iter()
-                        .map(|x| serde_json::Value::from(f64::from(x)))
-                        .collect();
-                    ForgeUniversalDataField::Json(serde_json::Value::Array(arr))
-                }
-                s if s == "FLOAT8[]" || s == "DOUBLE PRECISION[]" => {
-                    let v = row.try_get::<Vec<f64>, _>(i).map_err(to_decode_err)?;
-                    let arr = v.into_iter().map(serde_json::Value::from).collect();
-                    ForgeUniversalDataField::Json(serde_json::Value::Array(arr))
+                // arrays stay typed, a MySQL target writes them as JSON
+                "INT2[]" | "SMALLINT[]" => ForgeUniversalDataField::Array(ForgeArray::SmallInt(
+                    decode_column(row, i, is_domain).map_err(to_decode_err)?,
+                )),
+                "INT4[]" | "INTEGER[]" => ForgeUniversalDataField::Array(ForgeArray::Integer(
+                    decode_column(row, i, is_domain).map_err(to_decode_err)?,
+                )),
+                "INT8[]" | "BIGINT[]" => ForgeUniversalDataField::Array(ForgeArray::BigInt(
+                    decode_column(row, i, is_domain).map_err(to_decode_err)?,
+                )),
+                "TEXT[]" | "VARCHAR[]" | "CHAR[]" | "BPCHAR[]" | "NAME[]" | "CITEXT[]" => {
+                    ForgeUniversalDataField::Array(ForgeArray::Text(
+                        decode_column(row, i, is_domain).map_err(to_decode_err)?,
+                    ))
                 }
+                "BOOL[]" | "BOOLEAN[]" => ForgeUniversalDataField::Array(ForgeArray::Boolean(
+                    decode_column(row, i, is_domain).map_err(to_decode_err)?,
+                )),
+                "FLOAT4[]" | "REAL[]" => ForgeUniversalDataField::Array(ForgeArray::Real(
+                    decode_column(row, i, is_domain).map_err(to_decode_err)?,
+                )),
+                "FLOAT8[]" | "DOUBLE PRECISION[]" => ForgeUniversalDataField::Array(
+                    ForgeArray::Double(decode_column(row, i, is_domain).map_err(to_decode_err)?),
+                ),
                 _ => {
                     return Err(ForgeError::UnsupportedPostgresType {
                         column: col_name.parse().unwrap(),
@@ -616,76 +1915,100 @@ impl PostgresDriver {
     }
 }
 
+impl SchemaStatements for PostgresDriver {
+    fn create_table(
+        &self,
+        table: &ForgeSchemaTable,
+        config: &ForgeConfig,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        self.create_table_migration_sql(table, config)
+    }
+
+    fn drop_table(&self, table: &ForgeSchemaTable) -> Result<Vec<String>, Box<dyn Error>> {
+        self.delete_table_migration_sql(table)
+    }
+
+    fn alter_table(
+        &self,
+        source: &ForgeSchemaTable,
+        target: &ForgeSchemaTable,
+        config: &ForgeConfig,
+        destructive: bool,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        self.alter_table_migration_sql(source, target, config, destructive)
+    }
+
+    fn foreign_keys(&self, changes: &ForeignKeyChanges) -> (Vec<String>, Vec<String>) {
+        self.foreign_key_migration_sql(changes)
+    }
+}
+
 #[async_trait]
 impl DatabaseDriver for PostgresDriver {
     async fn db_is_empty(&self) -> Result<bool, Box<dyn Error>> {
         let pool = self.pool.as_ref().ok_or("No database pool available")?;
-        let count: i64 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM information_schema.tables WHERE table_schema = 'public'",
-        )
-        .fetch_one(pool)
-        .await?;
+        let count: i64 = self
+            .retry
+            .run("db_is_empty", || {
+                sqlx::query_scalar(
+                    "SELECT COUNT(*) FROM information_schema.tables WHERE table_schema = 'public'",
+                )
+                .fetch_one(pool)
+            })
+            .await?;
         Ok(count == 0)
     }
 
+    #[tracing::instrument(name = "fetch_schema", skip_all, fields(engine = "postgres"))]
     async fn fetch_schema(&self, config: &ForgeConfig) -> Result<ForgeSchema, Box<dyn Error>> {
-        let pool = self.pool.as_ref().ok_or("No database pool available")?;
-        let db_name: String = sqlx::query_scalar("SELECT current_database()")
-            .fetch_one(pool)
-            .await?;
-
-        let mut tables = self.fetch_tables().await?;
-        for table in &mut tables {
-            table.columns = self.fetch_columns(&table.name, config).await?;
-
-            // Mark primary key columns
-            let pk_rows = sqlx::query(
-                "SELECT a.attname as column_name
-                 FROM pg_index i
-                 JOIN pg_attribute a ON a.attrelid = i.indrelid AND a.attnum = ANY(i.indkey)
-                 JOIN pg_class c ON c.oid = i.indrelid
-                 WHERE c.relname = $1 AND i.indisprimary",
-            )
-            .bind(&table.name)
-            .fetch_all(pool)
-            .await?;
-
-            let pk_cols: Vec<String> = pk_rows
-                .into_iter()
-                .map(|r| r.get::<String, _>("column_name"))
-                .collect();
-            for col in &mut table.columns {
-                if pk_cols.iter().any(|c| c == &col.name) {
-                    col.is_primary_key = true;
-                }
-            }
-
-            table.indices = self.fetch_indices(&table.name).await?;
-            table.foreign_keys = self.fetch_foreign_keys(&table.name).await?;
-        }
-
-        Ok(ForgeSchema {
-            metadata: ForgeSchemaMetadata {
-                source_system: "postgres".to_string(),
-                source_database_name: db_name,
-                created_at: chrono::Local::now().to_rfc3339(),
-                forge_version: env!("CARGO_PKG_VERSION").to_string(),
-                config_file: String::new(),
-            },
-            tables,
-        })
+        self.retry
+            .run("fetch_schema", || self.read_schema(config))
+            .await
     }
 
+    #[tracing::instrument(
+        name = "apply_schema",
+        skip_all,
+        fields(engine = "postgres", dry_run = options.dry_run, destructive = options.destructive)
+    )]
     async fn diff_and_apply_schema(
         &self,
         source_schema: &ForgeSchema,
         config: &ForgeConfig,
-        dry_run: bool,
-        _verbose: bool,
-        destructive: bool,
+        filter: &ForgeObjectFilter,
+        options: MigrateOptions,
     ) -> Result<Vec<String>, Box<dyn Error>> {
+        let MigrateOptions {
+            dry_run,
+            verbose,
+            destructive,
+            defer_foreign_keys,
+            cancel,
+            applied,
+        } = options;
         let target_schema = self.fetch_schema(config).await?;
-        let mut all_statements = Vec::new();
+        let installed = self.fetch_installed_extensions().await?;
+        // extensions first, the tables use their types and functions
+        let mut all_statements =
+            create_extension_statements(&source_schema.extensions, &installed, config);
+        // then the domains used as column types
+        for domain in &source_schema.domains {
+            if !target_schema.domains.iter().any(|d| d.name == domain.name) {
+                all_statements.push(build_postgres_create_domain_sql(domain));
+            }
+        }
+        // foreign keys are dropped before their columns change and added after all tables exist
+        let (drop_foreign_keys, add_foreign_keys) = if defer_foreign_keys {
+            (Vec::new(), Vec::new())
+        } else {
+            self.foreign_key_migration_sql(&ForeignKeyChanges::between(
+                source_schema,
+                &target_schema,
+                filter,
+                destructive,
+            ))
+        };
+        all_statements.extend(drop_foreign_keys);
 
         let mut source_tables: HashMap<String, &ForgeSchemaTable> = HashMap::new();
         for table in &source_schema.tables {
@@ -697,15 +2020,44 @@ impl DatabaseDriver for PostgresDriver {
             target_tables.insert(table.name.clone(), table);
         }
 
+        // row estimates decide which added NOT NULL columns are backfilled in chunks
+        let estimates = if config.get_ddl().backfill_min_rows.is_some() {
+            self.estimate_table_sizes().await?
+        } else {
+            Vec::new()
+        };
+
         for source_table in &source_schema.tables {
+            if !filter.includes_table(&source_table.name) {
+                continue;
+            }
             if let Some(target_table) = target_tables.get(&source_table.name) {
-                let stmts = self.alter_table_migration_sql(
+                let backfill = backfill_plan(
+                    self,
                     source_table,
                     target_table,
+                    &estimates,
                     config,
-                    destructive,
-                )?;
-                all_statements.extend(stmts);
+                    str::to_string,
+                )
+                .await?;
+                if let Some(backfill) = backfill {
+                    all_statements.extend(self.alter_table_migration_sql(
+                        &backfill.nullable_table(source_table),
+                        target_table,
+                        config,
+                        destructive,
+                    )?);
+                    all_statements.extend(self.backfill_sql(&source_table.name, &backfill, config));
+                } else {
+                    let stmts = self.alter_table_migration_sql(
+                        source_table,
+                        target_table,
+                        config,
+                        destructive,
+                    )?;
+                    all_statements.extend(stmts);
+                }
             } else {
                 let stmts = self.create_table_migration_sql(source_table, config)?;
                 all_statements.extend(stmts);
@@ -713,22 +2065,81 @@ impl DatabaseDriver for PostgresDriver {
         }
 
         if destructive {
-            for table in &target_schema.tables {
-                if !source_tables.contains_key(&table.name) {
+            for table in sorted_by_name(&target_schema.tables, |t| &t.name) {
+                if !source_tables.contains_key(&table.name) && filter.includes_table(&table.name) {
                     let stmts = self.delete_table_migration_sql(table)?;
                     all_statements.extend(stmts);
                 }
             }
         }
+        all_statements.extend(add_foreign_keys);
+
+        if !dry_run {
+            self.execute_schema_statements(&all_statements, config, &cancel, &applied)
+                .await?;
+            if verbose {
+                info!(
+                    statements = all_statements.len(),
+                    "schema statements executed"
+                );
+            }
+        }
+
+        Ok(all_statements)
+    }
+
+    #[tracing::instrument(
+        name = "apply_foreign_keys",
+        skip_all,
+        fields(engine = "postgres", dry_run = options.dry_run)
+    )]
+    async fn apply_foreign_keys(
+        &self,
+        schema: &ForgeSchema,
+        config: &ForgeConfig,
+        filter: &ForgeObjectFilter,
+        options: MigrateOptions,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        let target_schema = self.fetch_schema(config).await?;
+        let changes =
+            ForeignKeyChanges::between(schema, &target_schema, filter, options.destructive);
+        let (mut statements, adds) = self.foreign_key_migration_sql(&changes);
+        statements.extend(adds);
+        if !options.dry_run {
+            self.execute_schema_statements(&statements, config, &options.cancel, &options.applied)
+                .await?;
+            info!(statements = statements.len(), "foreign keys applied");
+        }
+        Ok(statements)
+    }
+
+    #[tracing::instrument(name = "rollback_plan", skip_all, fields(engine = "postgres"))]
+    async fn rollback_schema_plan(
+        &self,
+        source_schema: &ForgeSchema,
+        config: &ForgeConfig,
+        filter: &ForgeObjectFilter,
+        destructive: bool,
+    ) -> Result<ForgeRollbackPlan, Box<dyn Error>> {
+        // the current target is the state the rollback restores
+        let target_schema = self.fetch_schema(config).await?;
+        rollback_schema_plan(
+            self,
+            source_schema,
+            &target_schema,
+            config,
+            filter,
+            destructive,
+        )
+    }
 
-        if !dry_run {
-            let pool = self.pool.as_ref().ok_or("No database pool available")?;
-            for sql in &all_statements {
-                sqlx::query(sql).execute(pool).await?;
-            }
+    async fn execute_statements(&self, statements: &[String]) -> Result<(), Box<dyn Error>> {
+        let pool = self.pool.as_ref().ok_or("No database pool available")?;
+        for sql in statements {
+            debug!(statement = %sql, "executing statement");
+            sqlx::query(sql).execute(pool).await?;
         }
-
-        Ok(all_statements)
+        Ok(())
     }
 
     async fn stream_table_data(
@@ -744,8 +2155,26 @@ impl DatabaseDriver for PostgresDriver {
         >,
         Box<dyn Error>,
     > {
+        // with `resume_streams`, tables with a primary key are read in key order and reconnect
+        // after the last key
+        if self.retry.resumes_streams() {
+            let key_columns = self.fetch_primary_key_column_names(table_name).await?;
+            if !key_columns.is_empty() {
+                return Ok(resuming_stream(
+                    self,
+                    table_name.to_string(),
+                    key_columns,
+                    None,
+                    self.retry.clone(),
+                ));
+            }
+        }
+
         let pool = self.pool.as_ref().ok_or("No database pool available")?;
-        let query_string = format!("SELECT * FROM {table_name}");
+        let query_string = format!(
+            "SELECT * FROM {table_name}{}",
+            filtered_clause(&self.row_filters, table_name, "")
+        );
 
         let stream = async_stream::try_stream! {
             let mut rows = sqlx::query(&query_string).fetch(pool);
@@ -768,6 +2197,9 @@ impl DatabaseDriver for PostgresDriver {
         &self,
         table_name: &str,
         order_by: &[String],
+        sample: Option<ForgeVerifySample>,
+        key_range: Option<ForgeKeyRange>,
+        digest_columns: &[String],
     ) -> Result<
         Pin<
             Box<
@@ -782,10 +2214,48 @@ impl DatabaseDriver for PostgresDriver {
         let order_clause = if order_by.is_empty() {
             String::new()
         } else {
-            let columns = order_by.join(", ");
+            let nulls = match self.null_ordering {
+                ForgeNullOrdering::First => "NULLS FIRST",
+                ForgeNullOrdering::Last => "NULLS LAST",
+            };
+            let columns = order_by
+                .iter()
+                .map(|col| format!("{col} {nulls}"))
+                .collect::<Vec<_>>()
+                .join(", ");
             format!(" ORDER BY {columns}")
         };
-        let query_string = format!("SELECT * FROM {table_name}{order_clause}");
+        let predicates: Vec<String> = order_by
+            .first()
+            .map(|key| {
+                let sample = sample.map(|sample| self.build_sample_predicate(order_by, sample));
+                let range =
+                    key_range.map(|range| format!("{key} BETWEEN {} AND {}", range.from, range.to));
+                sample.into_iter().chain(range)
+            })
+            .into_iter()
+            .flatten()
+            .collect();
+        let where_clause = if predicates.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", predicates.join(" AND "))
+        };
+        let select = if digest_columns.is_empty() {
+            format!("SELECT * FROM {table_name}")
+        } else {
+            let columns = self.fetch_column_names(table_name).await?;
+            let text_columns = self.fetch_non_binary_column_names(table_name).await?;
+            self.build_select_digest_sql(table_name, &columns, digest_columns, &text_columns)
+        };
+        let query_string = format!(
+            "{select}{}",
+            filtered_clause(
+                &self.row_filters,
+                table_name,
+                &format!("{where_clause}{order_clause}")
+            )
+        );
 
         let stream = async_stream::try_stream! {
             let mut rows = sqlx::query(&query_string).fetch(pool);
@@ -804,6 +2274,101 @@ impl DatabaseDriver for PostgresDriver {
         Ok(Box::pin(stream))
     }
 
+    async fn stream_table_data_after(
+        &self,
+        table_name: &str,
+        key_columns: &[String],
+        after: Option<&[ForgeUniversalDataField]>,
+    ) -> Result<
+        Pin<
+            Box<
+                dyn Stream<Item = Result<IndexMap<String, ForgeUniversalDataField>, ForgeError>>
+                    + Send
+                    + '_,
+            >,
+        >,
+        Box<dyn Error>,
+    > {
+        let pool = self.pool.as_ref().ok_or("No database pool available")?;
+        let query_string = format!(
+            "SELECT * FROM {table_name}{}",
+            filtered_clause(
+                &self.row_filters,
+                table_name,
+                &self.build_keyset_clause(key_columns, after.is_some())
+            )
+        );
+        let after = after.map(<[ForgeUniversalDataField]>::to_vec);
+
+        let stream = async_stream::try_stream! {
+            let mut query = sqlx::query(&query_string);
+            for value in after.iter().flatten() {
+                query = self.bind_universal(query, value);
+            }
+            let mut rows = query.fetch(pool);
+
+            while let Some(row) = rows.next().await {
+                let row: PgRow = row?;
+                let values = self.map_row_to_universal_values(&row)?;
+                let mut row_map = IndexMap::new();
+                for (col, val) in row.columns().iter().zip(values) {
+                    row_map.insert(col.name().to_string(), val);
+                }
+                yield row_map;
+            }
+        };
+
+        Ok(Box::pin(stream))
+    }
+
+    async fn fetch_last_key(
+        &self,
+        table_name: &str,
+        key_columns: &[String],
+    ) -> Result<Option<Vec<ForgeUniversalDataField>>, Box<dyn Error>> {
+        let pool = self.pool.as_ref().ok_or("No database pool available")?;
+        let row = sqlx::query(&self.build_last_key_sql(table_name, key_columns))
+            .fetch_optional(pool)
+            .await?;
+        Ok(match row {
+            Some(row) => Some(self.map_row_to_universal_values(&row)?),
+            None => None,
+        })
+    }
+
+    async fn fetch_row(
+        &self,
+        table_name: &str,
+        key_columns: &[String],
+        key: &[ForgeUniversalDataField],
+    ) -> Result<Option<IndexMap<String, ForgeUniversalDataField>>, Box<dyn Error>> {
+        let pool = self.pool.as_ref().ok_or("No database pool available")?;
+        let query_string = format!(
+            "SELECT * FROM {table_name}{}",
+            filtered_clause(
+                &self.row_filters,
+                table_name,
+                &self.build_key_clause(key_columns)
+            )
+        );
+        let mut query = sqlx::query(&query_string);
+        for value in key {
+            query = self.bind_universal(query, value);
+        }
+        let Some(row) = query.fetch_optional(pool).await? else {
+            return Ok(None);
+        };
+        let values = self.map_row_to_universal_values(&row)?;
+        Ok(Some(
+            row.columns()
+                .iter()
+                .map(|col| col.name().to_string())
+                .zip(values)
+                .collect(),
+        ))
+    }
+
+    #[tracing::instrument(name = "insert_chunk", skip_all, fields(table = table_name, rows = chunk.len()))]
     async fn insert_chunk(
         &self,
         table_name: &str,
@@ -821,103 +2386,351 @@ impl DatabaseDriver for PostgresDriver {
         };
         let column_names = columns.join(", ");
 
-        let mut placeholders = Vec::new();
-        let mut arg_count = 1;
-        for _ in 0..chunk.len() {
-            let mut row_placeholders = Vec::new();
-            for _ in 0..columns.len() {
-                row_placeholders.push(format!("${arg_count}"));
-                arg_count += 1;
-            }
-            placeholders.push(format!("({})", row_placeholders.join(", ")));
+        for range in batch_ranges(&chunk, columns.len(), MAX_BIND_PARAMS, MAX_BATCH_BYTES) {
+            self.insert_batch(
+                table_name,
+                &columns,
+                &column_names,
+                &chunk[range],
+                dry_run,
+                halt_on_error,
+            )
+            .await?;
         }
+        Ok(())
+    }
 
-        let sql = format!(
-            "INSERT INTO {} ({}) VALUES {}",
-            table_name,
-            column_names,
-            placeholders.join(", ")
-        );
+    async fn upsert_chunk(
+        &self,
+        table_name: &str,
+        key_columns: &[String],
+        chunk: Vec<IndexMap<String, ForgeUniversalDataField>>,
+    ) -> Result<(), Box<dyn Error>> {
+        let columns: Vec<String> = match chunk.first() {
+            Some(first_row) => first_row.keys().cloned().collect(),
+            None => return Ok(()),
+        };
+        let conflict = upsert_clause(ForgeDialect::Postgres, &columns, key_columns);
+        for range in batch_ranges(&chunk, columns.len(), MAX_BIND_PARAMS, MAX_BATCH_BYTES) {
+            let sql = format!(
+                "INSERT INTO {table_name} ({}) VALUES {} {conflict}",
+                columns.join(", "),
+                numbered_placeholders(range.len(), columns.len())
+            );
+            self.execute_rows("upsert_chunk", &sql, &columns, &chunk[range])
+                .await?;
+        }
+        Ok(())
+    }
 
-        if dry_run {
-            println!("Dry run SQL: {sql}");
-        } else {
-            let pool = self.pool.as_ref().ok_or("No database pool available")?;
-            let mut query = sqlx::query(&sql);
-            for row in &chunk {
-                for col in &columns {
-                    let val = row.get(col).unwrap_or(&ForgeUniversalDataField::Null);
-                    query = self.bind_universal(query, val);
-                }
-            }
+    async fn delete_rows(
+        &self,
+        table_name: &str,
+        key_columns: &[String],
+        keys: Vec<IndexMap<String, ForgeUniversalDataField>>,
+    ) -> Result<(), Box<dyn Error>> {
+        for range in batch_ranges(&keys, key_columns.len(), MAX_BIND_PARAMS, MAX_BATCH_BYTES) {
+            let sql = format!(
+                "DELETE FROM {table_name} WHERE ({}) IN ({})",
+                key_columns.join(", "),
+                numbered_placeholders(range.len(), key_columns.len())
+            );
+            self.execute_rows("delete_rows", &sql, key_columns, &keys[range])
+                .await?;
+        }
+        Ok(())
+    }
 
-            if let Err(e) = query.execute(pool).await {
-                if halt_on_error {
-                    return Err(Box::new(e));
-                }
-                // Row by row retry for better error logging with careful NULL/JSON handling
-                for row_map in &chunk {
-                    let mut value_sql_parts: Vec<String> = Vec::with_capacity(columns.len());
-                    let mut arg_index = 1;
+    async fn get_table_row_count(&self, table_name: &str) -> Result<u64, Box<dyn Error>> {
+        let pool = self.pool.as_ref().ok_or("No database pool available")?;
+        let query = format!(
+            "SELECT COUNT(*) FROM {table_name}{}",
+            filtered_clause(&self.row_filters, table_name, "")
+        );
+        let count: i64 = self
+            .retry
+            .run("row_count", || sqlx::query_scalar(&query).fetch_one(pool))
+            .await?;
+        Ok(count as u64)
+    }
 
-                    // Build value list with per-value casting where needed
-                    for col in &columns {
-                        let val = row_map.get(col).unwrap_or(&ForgeUniversalDataField::Null);
-                        match val {
-                            ForgeUniversalDataField::Null
-                            | ForgeUniversalDataField::ZeroDateTime => {
-                                value_sql_parts.push("NULL".to_string());
-                            }
-                            ForgeUniversalDataField::Json(_) => {
-                                value_sql_parts.push(format!("${arg_index}::jsonb"));
-                                arg_index += 1;
-                            }
-                            _ => {
-                                value_sql_parts.push(format!("${arg_index}"));
-                                arg_index += 1;
-                            }
-                        }
-                    }
+    async fn range_checksums(
+        &self,
+        table_name: &str,
+        key_column: &str,
+        range_keys: u64,
+    ) -> Result<Vec<ForgeRangeChecksum>, Box<dyn Error>> {
+        let pool = self.pool.as_ref().ok_or("No database pool available")?;
+        // the hash of the row text covers all columns, its sum does not depend on the row order
+        let query = format!(
+            "SELECT floor({key_column}::numeric / {range_keys})::bigint, count(*), \
+             sum(hashtextextended(t::text, 0))::text FROM {table_name} t{}",
+            filtered_clause(&self.row_filters, table_name, " GROUP BY 1 ORDER BY 1")
+        );
+        let rows: Vec<(i64, i64, String)> = self
+            .retry
+            .run("range_checksums", || sqlx::query_as(&query).fetch_all(pool))
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(range, rows, checksum)| ForgeRangeChecksum {
+                range,
+                rows: rows.max(0) as u64,
+                checksum,
+            })
+            .collect())
+    }
 
-                    let single_sql = format!(
-                        "INSERT INTO {} ({}) VALUES ({})",
-                        table_name,
-                        column_names,
-                        value_sql_parts.join(", ")
-                    );
+    async fn estimate_table_sizes(&self) -> Result<Vec<ForgeTableEstimate>, Box<dyn Error>> {
+        let pool = self.pool.as_ref().ok_or("No database pool available")?;
+        // reltuples is -1 for tables never analyzed
+        let rows: Vec<(String, i64, i64)> = self
+            .retry
+            .run("table_sizes", || {
+                sqlx::query_as(
+                    "SELECT c.relname::text, GREATEST(c.reltuples, 0)::bigint, pg_table_size(c.oid) \
+                     FROM pg_class c JOIN pg_namespace n ON n.oid = c.relnamespace \
+                     WHERE n.nspname = current_schema() AND c.relkind IN ('r', 'p') \
+                     ORDER BY c.relname",
+                )
+                .fetch_all(pool)
+            })
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(table_name, rows, bytes)| ForgeTableEstimate {
+                table_name,
+                rows: rows.max(0) as u64,
+                bytes: bytes.max(0) as u64,
+            })
+            .collect())
+    }
 
-                    let mut single_query = sqlx::query(&single_sql);
+    async fn delete_table_data(
+        &self,
+        table_name: &str,
+        filter: Option<&str>,
+    ) -> Result<(), Box<dyn Error>> {
+        let pool = self.pool.as_ref().ok_or("No database pool available")?;
+        let sql = match filter {
+            Some(filter) => format!("DELETE FROM {table_name} WHERE {filter}"),
+            None => format!("DELETE FROM {table_name}"),
+        };
+        debug!(statement = %sql, "deleting table data");
+        match self.write_tx.lock().await.as_mut() {
+            Some(tx) => sqlx::query(&sql).execute(&mut **tx).await?,
+            None => sqlx::query(&sql).execute(pool).await?,
+        };
+        Ok(())
+    }
 
-                    // Bind only the non-NULL parameters in the same order we generated above
-                    for col in &columns {
-                        let val = row_map.get(col).unwrap_or(&ForgeUniversalDataField::Null);
-                        match val {
-                            ForgeUniversalDataField::Null
-                            | ForgeUniversalDataField::ZeroDateTime => { /* no bind */ }
-                            ForgeUniversalDataField::Json(j) => {
-                                single_query = single_query.bind(sqlx::types::Json(j));
-                            }
-                            other => {
-                                single_query = self.bind_universal(single_query, other);
-                            }
-                        }
-                    }
+    async fn begin_write(&self) -> Result<(), Box<dyn Error>> {
+        let pool = self.pool.as_ref().ok_or("No database pool available")?;
+        let mut write_tx = self.write_tx.lock().await;
+        if write_tx.is_some() {
+            return Err("A write transaction is already open".into());
+        }
+        *write_tx = Some(pool.begin().await?);
+        debug!("write transaction started");
+        Ok(())
+    }
 
-                    if let Err(se) = single_query.execute(pool).await {
-                        let row_str = format!("{row_map:?}");
-                        log_error_to_file(table_name, &row_str, &se.to_string());
-                    }
-                }
-            }
+    async fn commit_write(&self) -> Result<(), Box<dyn Error>> {
+        let tx = self
+            .write_tx
+            .lock()
+            .await
+            .take()
+            .ok_or("No write transaction is open")?;
+        tx.commit().await?;
+        debug!("write transaction committed");
+        Ok(())
+    }
+
+    async fn rollback_write(&self) -> Result<(), Box<dyn Error>> {
+        if let Some(tx) = self.write_tx.lock().await.take() {
+            tx.rollback().await?;
+            debug!("write transaction rolled back");
         }
         Ok(())
     }
 
-    async fn get_table_row_count(&self, table_name: &str) -> Result<u64, Box<dyn Error>> {
+    async fn disable_constraints(&self, tables: &[String]) -> Result<(), Box<dyn Error>> {
+        self.set_triggers_enabled(tables, false).await?;
+        info!(
+            tables = tables.len(),
+            "triggers and foreign key checks disabled"
+        );
+        Ok(())
+    }
+
+    async fn enable_constraints(&self, tables: &[String]) -> Result<(), Box<dyn Error>> {
+        self.set_triggers_enabled(tables, true).await?;
+        info!(
+            tables = tables.len(),
+            "triggers and foreign key checks enabled"
+        );
+        Ok(())
+    }
+
+    async fn count_foreign_key_violations(
+        &self,
+        table_name: &str,
+        foreign_key: &[ForgeSchemaForeignKey],
+    ) -> Result<u64, Box<dyn Error>> {
         let pool = self.pool.as_ref().ok_or("No database pool available")?;
-        let count: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table_name}"))
-            .fetch_one(pool)
+        let query = self.foreign_key_violations_sql(table_name, foreign_key);
+        let count: i64 = self
+            .retry
+            .run("foreign_key_violations", || {
+                sqlx::query_scalar(&query).fetch_one(pool)
+            })
             .await?;
         Ok(count as u64)
     }
+
+    async fn count_unsigned_overflow(
+        &self,
+        _table_name: &str,
+        _column_name: &str,
+    ) -> Result<u64, Box<dyn Error>> {
+        // PostgreSQL has no unsigned integers
+        Ok(0)
+    }
+
+    async fn preflight_checks(
+        &self,
+        as_target: bool,
+    ) -> Result<Vec<ForgePreflightCheck>, Box<dyn Error>> {
+        let pool = self.pool.as_ref().ok_or("No database pool available")?;
+        let (version, version_num, wal_level, statement_timeout): (String, i32, String, String) =
+            sqlx::query_as(
+                "SELECT current_setting('server_version'), \
+                 current_setting('server_version_num')::int, \
+                 current_setting('wal_level'), current_setting('statement_timeout')",
+            )
+            .fetch_one(pool)
+            .await?;
+        let mut checks = postgres_setting_checks(
+            &version,
+            i64::from(version_num),
+            &wal_level,
+            &statement_timeout,
+            as_target,
+        );
+
+        if as_target {
+            // the tables are created by the migration, so the user owns them
+            let (can_create, is_superuser): (bool, bool) = sqlx::query_as(
+                "SELECT has_schema_privilege(current_schema(), 'CREATE'), \
+                 (SELECT rolsuper FROM pg_roles WHERE rolname = current_user)",
+            )
+            .fetch_one(pool)
+            .await?;
+            checks.push(if can_create {
+                ForgePreflightCheck::pass("privileges", "CREATE on the current schema")
+            } else {
+                ForgePreflightCheck::fail("privileges", "missing CREATE on the current schema")
+            });
+            checks.push(if is_superuser {
+                ForgePreflightCheck::pass("superuser", "yes")
+            } else {
+                ForgePreflightCheck::warn(
+                    "superuser",
+                    "no, --disable-constraints cannot disable the foreign key triggers",
+                )
+            });
+        } else {
+            let unreadable: Vec<String> = sqlx::query_scalar(
+                "SELECT table_name::text FROM information_schema.tables \
+                 WHERE table_schema = current_schema() AND table_type = 'BASE TABLE' \
+                 AND NOT has_table_privilege(format('%I.%I', table_schema, table_name), 'SELECT') \
+                 ORDER BY table_name",
+            )
+            .fetch_all(pool)
+            .await?;
+            checks.push(if unreadable.is_empty() {
+                ForgePreflightCheck::pass("privileges", "SELECT on all tables")
+            } else {
+                ForgePreflightCheck::fail(
+                    "privileges",
+                    format!("missing SELECT on {}", unreadable.join(", ")),
+                )
+            });
+        }
+        Ok(checks)
+    }
+
+    async fn replication_lag(&self) -> Result<Option<Duration>, Box<dyn Error>> {
+        let pool = self.pool.as_ref().ok_or("No database pool available")?;
+        // a standby that replayed all received WAL is caught up, however old its last transaction
+        let row = sqlx::query(
+            "SELECT pg_is_in_recovery() AS replica,
+                    CASE WHEN pg_last_wal_receive_lsn() = pg_last_wal_replay_lsn() THEN 0
+                         ELSE EXTRACT(EPOCH FROM now() - pg_last_xact_replay_timestamp())
+                    END::float8 AS lag",
+        )
+        .fetch_one(pool)
+        .await?;
+        if !row.try_get::<bool, _>("replica")? {
+            return Ok(None);
+        }
+        let lag: Option<f64> = row.try_get("lag")?;
+        let lag = lag.ok_or("replica lag unknown, the standby replayed no transaction yet")?;
+        Ok(Some(Duration::from_secs_f64(lag.max(0.0))))
+    }
+
+    async fn query_scalar(&self, sql: &str) -> Result<ForgeUniversalDataField, Box<dyn Error>> {
+        let pool = self.pool.as_ref().ok_or("No database pool available")?;
+        let row = sqlx::query(sql).fetch_optional(pool).await?;
+        let value = match row {
+            Some(row) => self
+                .map_row_to_universal_values(&row)?
+                .into_iter()
+                .next()
+                .unwrap_or(ForgeUniversalDataField::Null),
+            None => ForgeUniversalDataField::Null,
+        };
+        Ok(value)
+    }
+
+    async fn fetch_grants(&self) -> Result<Vec<ForgeSchemaGrant>, Box<dyn Error>> {
+        let pool = self.pool.as_ref().ok_or("No database pool available")?;
+
+        // the owner has all privileges implicitly, these are not migrated
+        let rows: Vec<(String, String, String)> = sqlx::query_as(
+            "SELECT g.grantee::text, g.table_name::text, g.privilege_type::text
+             FROM information_schema.role_table_grants g
+             JOIN pg_catalog.pg_tables t
+               ON t.schemaname = g.table_schema AND t.tablename = g.table_name
+             WHERE g.table_schema = 'public' AND g.grantee <> t.tableowner",
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(grants::group_grants(rows))
+    }
+
+    #[tracing::instrument(name = "apply_grants", skip_all, fields(engine = "postgres", dry_run))]
+    async fn apply_grants(
+        &self,
+        grants: &[ForgeSchemaGrant],
+        config: &ForgeConfig,
+        dry_run: bool,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        let statements: Vec<String> = grants
+            .iter()
+            .filter_map(|grant| self.build_grant_sql(grant, config))
+            .collect();
+
+        if !dry_run {
+            let pool = self.pool.as_ref().ok_or("No database pool available")?;
+            for sql in &statements {
+                debug!(statement = %sql, "executing grant statement");
+                sqlx::query(sql).execute(pool).await?;
+            }
+        }
+
+        Ok(statements)
+    }
 }
@@ -0,0 +1,948 @@
+//! SQL script target for an offline apply.
+//!
+//! Instead of executing against a live database, the DDL and the INSERT statements with their
+//! values inlined as literals are written into a `.sql` file of the target dialect, for a DBA
+//! to review and apply manually. A script target is addressed by a URL like
+//! `postgres+script://migration.sql` or `mysql+script:///var/tmp/migration.sql`, the rest of
+//! the URL is the path of the script. The script always describes an empty target database.
+
+use crate::DatabaseDriver;
+use crate::core::{
+    ForgeArray, ForgeConfig, ForgeDialect, ForgeError, ForgeKeyRange, ForgeObjectFilter,
+    ForgePreflightCheck, ForgeRangeChecksum, ForgeRollbackPlan, ForgeSchema, ForgeSchemaForeignKey,
+    ForgeSchemaGrant, ForgeTableEstimate, ForgeUniversalDataField, ForgeVerifySample,
+    MigrateOptions,
+};
+use crate::drivers::mysql::{MySqlDriver, get_mysql_init_session_sql_mode, mysql_time_zone_sql};
+use crate::drivers::postgres::{
+    PostgresDriver, build_postgres_create_domain_sql, create_extension_statements,
+    postgres_time_zone_sql,
+};
+use crate::drivers::{ForeignKeyChanges, MAX_BIND_PARAMS, batch_ranges, upsert_clause};
+use crate::ops::retry::RetryPolicy;
+use async_trait::async_trait;
+use futures::Stream;
+use indexmap::IndexMap;
+use sqlx::mysql::{MySqlConnectOptions, MySqlPoolOptions};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tracing::{debug, info};
+
+/// upper limit of the values of one INSERT of the script, far below the default
+/// `max_allowed_packet` of MySQL and easy on the memory of `mysql` and `psql`
+const MAX_STATEMENT_BYTES: u64 = 1024 * 1024;
+
+/// Dialect and path of a script URL (`mysql+script://<path>`, `postgres+script://<path>`).
+#[must_use]
+pub fn parse_script_url(url: &str) -> Option<(ForgeDialect, PathBuf)> {
+    let (dialect, path) = if let Some(path) = url.strip_prefix("mysql+script://") {
+        (ForgeDialect::Mysql, path)
+    } else if let Some(path) = url
+        .strip_prefix("postgres+script://")
+        .or_else(|| url.strip_prefix("postgresql+script://"))
+    {
+        (ForgeDialect::Postgres, path)
+    } else {
+        return None;
+    };
+    Some((dialect, PathBuf::from(path)))
+}
+
+/// Quoted SQL string literal of the dialect.
+///
+/// MySQL reads a backslash as an escape character (default SQL mode), PostgreSQL
+/// with `standard_conforming_strings` only needs the quotes doubled.
+fn quote_text(dialect: ForgeDialect, text: &str) -> String {
+    let escaped = match dialect {
+        ForgeDialect::Mysql => text
+            .replace('\\', "\\\\")
+            .replace('\'', "''")
+            .replace('\0', "\\0"),
+        ForgeDialect::Postgres => text.replace('\'', "''"),
+    };
+    format!("'{escaped}'")
+}
+
+/// PostgreSQL `ARRAY[...]` literal, typed so that empty and all-NULL arrays are valid
+fn postgres_array_literal(array: &ForgeArray) -> String {
+    fn elements<T>(values: &[Option<T>], literal: impl Fn(&T) -> String) -> String {
+        values
+            .iter()
+            .map(|v| v.as_ref().map_or_else(|| "NULL".to_string(), &literal))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+    let float = |f: f64| {
+        if f.is_finite() {
+            f.to_string()
+        } else {
+            quote_text(ForgeDialect::Postgres, &float_special(f))
+        }
+    };
+    let (values, element_type) = match array {
+        ForgeArray::SmallInt(v) => (elements(v, i16::to_string), "smallint"),
+        ForgeArray::Integer(v) => (elements(v, i32::to_string), "integer"),
+        ForgeArray::BigInt(v) => (elements(v, i64::to_string), "bigint"),
+        ForgeArray::Real(v) => (elements(v, |f| float(f64::from(*f))), "real"),
+        ForgeArray::Double(v) => (elements(v, |f| float(*f)), "double precision"),
+        ForgeArray::Text(v) => (
+            elements(v, |s| quote_text(ForgeDialect::Postgres, s)),
+            "text",
+        ),
+        ForgeArray::Boolean(v) => (elements(v, |b| b.to_string().to_uppercase()), "boolean"),
+    };
+    format!("ARRAY[{values}]::{element_type}[]")
+}
+
+/// PostgreSQL input text of NaN and the infinities
+fn float_special(f: f64) -> String {
+    if f.is_nan() {
+        "NaN".to_string()
+    } else if f > 0.0 {
+        "Infinity".to_string()
+    } else {
+        "-Infinity".to_string()
+    }
+}
+
+/// SQL literal of a value, as the driver of the dialect would have bound it.
+///
+/// `zero_date_on_write` keeps MySQL zero dates (`[mysql.rules.on_write] zero_date`),
+/// otherwise they are written as NULL. Non-finite floats are NULL in MySQL, which has none.
+#[must_use]
+pub fn sql_literal(
+    dialect: ForgeDialect,
+    value: &ForgeUniversalDataField,
+    zero_date_on_write: bool,
+) -> String {
+    let quoted = |text: &str| quote_text(dialect, text);
+    match value {
+        ForgeUniversalDataField::Integer(i) => i.to_string(),
+        ForgeUniversalDataField::UnsignedInteger(u) => u.to_string(),
+        ForgeUniversalDataField::Year(y) => y.to_string(),
+        ForgeUniversalDataField::Decimal(d) => d.to_string(),
+        ForgeUniversalDataField::Float(f) if f.is_finite() => f.to_string(),
+        ForgeUniversalDataField::Float(f) => match dialect {
+            ForgeDialect::Mysql => "NULL".to_string(),
+            ForgeDialect::Postgres => quoted(&float_special(*f)),
+        },
+        ForgeUniversalDataField::Boolean(b) => match dialect {
+            ForgeDialect::Mysql => u8::from(*b).to_string(),
+            ForgeDialect::Postgres => b.to_string().to_uppercase(),
+        },
+        ForgeUniversalDataField::Binary(bytes) => {
+            let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+            match dialect {
+                ForgeDialect::Mysql => format!("X'{hex}'"),
+                ForgeDialect::Postgres => format!("'\\x{hex}'"),
+            }
+        }
+        ForgeUniversalDataField::Text(s) => quoted(s),
+        ForgeUniversalDataField::Time(t) => quoted(&t.to_string()),
+        ForgeUniversalDataField::Date(d) => quoted(&d.to_string()),
+        ForgeUniversalDataField::DateTime(dt) => quoted(&dt.to_string()),
+        ForgeUniversalDataField::Json(j) => quoted(&j.to_string()),
+        ForgeUniversalDataField::Uuid(u) => quoted(&u.to_string()),
+        ForgeUniversalDataField::Inet(i) => quoted(&i.to_string()),
+        ForgeUniversalDataField::Array(a) => match dialect {
+            ForgeDialect::Mysql => quoted(&a.to_json().to_string()),
+            ForgeDialect::Postgres => postgres_array_literal(a),
+        },
+        ForgeUniversalDataField::Null => "NULL".to_string(),
+        ForgeUniversalDataField::ZeroDateTime => match dialect {
+            ForgeDialect::Mysql if zero_date_on_write => quoted("0000-00-00 00:00:00"),
+            _ => "NULL".to_string(),
+        },
+    }
+}
+
+/// driver of the dialect generating the DDL, it is never connected
+enum Generator {
+    Mysql(MySqlDriver),
+    Postgres(PostgresDriver),
+}
+
+/// Target writing all statements into a SQL script instead of executing them.
+pub struct ScriptDriver {
+    pub dialect: ForgeDialect,
+    pub path: PathBuf,
+    generator: Generator,
+    writer: Mutex<BufWriter<File>>,
+    /// generated columns of the created tables, their values are computed by the target
+    generated: Mutex<HashMap<String, HashSet<String>>>,
+    /// rows written per table, the row count of the script
+    rows: Mutex<HashMap<String, u64>>,
+}
+
+impl ScriptDriver {
+    /// Creates (or truncates) the script and writes its header with the session settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created.
+    pub fn create(
+        dialect: ForgeDialect,
+        path: &Path,
+        config: &ForgeConfig,
+    ) -> Result<Self, Box<dyn Error>> {
+        let file = File::create(path)
+            .map_err(|e| format!("Error creating SQL script {}: {e}", path.display()))?;
+        let null_ordering = config.get_null_ordering();
+        let retry = RetryPolicy::from_config(config);
+        let time_zone = config.get_time_zone(false);
+
+        let mut header = vec![format!(
+            "-- FluxForge migration script ({})",
+            dialect.config_section()
+        )];
+        let generator = match dialect {
+            ForgeDialect::Mysql => {
+                header.push("SET NAMES utf8mb4".to_string());
+                let sql_mode = get_mysql_init_session_sql_mode(config, false);
+                if !sql_mode.is_empty() {
+                    header.push(sql_mode);
+                }
+                header.extend(time_zone.map(mysql_time_zone_sql));
+                Generator::Mysql(MySqlDriver {
+                    // a lazy pool never connects, only the SQL builders are used
+                    pool: MySqlPoolOptions::new().connect_lazy_with(MySqlConnectOptions::new()),
+                    zero_date_on_write: super::zero_date_on_write(config),
+                    null_ordering,
+                    retry,
+                    write_tx: tokio::sync::Mutex::new(None),
+                    foreign_key_checks_off: Arc::default(),
+                    tunnel: None,
+                    row_filters: HashMap::new(),
+                    idempotent_ddl: config.is_idempotent_ddl(),
+                    online_ddl: None,
+                    metadata: Default::default(),
+                })
+            }
+            ForgeDialect::Postgres => {
+                header.push("SET client_encoding = 'UTF8'".to_string());
+                header.extend(time_zone.map(postgres_time_zone_sql));
+                Generator::Postgres(PostgresDriver {
+                    pool: None,
+                    null_ordering,
+                    retry,
+                    write_tx: tokio::sync::Mutex::new(None),
+                    time_zone,
+                    tunnel: None,
+                    row_filters: HashMap::new(),
+                    idempotent_ddl: config.is_idempotent_ddl(),
+                })
+            }
+        };
+
+        let driver = Self {
+            dialect,
+            path: path.to_path_buf(),
+            generator,
+            writer: Mutex::new(BufWriter::new(file)),
+            generated: Mutex::new(HashMap::new()),
+            rows: Mutex::new(HashMap::new()),
+        };
+        driver.write_statements(&header)?;
+        Ok(driver)
+    }
+
+    fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+        mutex
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// appends the statements terminated by `;`, a comment line stays as it is
+    fn write_statements(&self, statements: &[String]) -> Result<(), Box<dyn Error>> {
+        let mut writer = Self::lock(&self.writer);
+        for sql in statements {
+            let sql = sql.trim_end();
+            if sql.starts_with("--") || sql.ends_with(';') {
+                writeln!(writer, "{sql}")?;
+            } else {
+                writeln!(writer, "{sql};")?;
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// quoted table or column name of the dialect, like the drivers write them into INSERTs
+    fn identifier(&self, name: &str) -> String {
+        match self.dialect {
+            ForgeDialect::Mysql => format!("`{name}`"),
+            ForgeDialect::Postgres => name.to_string(),
+        }
+    }
+
+    fn zero_date_on_write(&self) -> bool {
+        match &self.generator {
+            Generator::Mysql(driver) => driver.zero_date_on_write,
+            Generator::Postgres(_) => false,
+        }
+    }
+
+    /// multi-row INSERT statements of a chunk, the values inlined as literals
+    #[must_use]
+    pub fn insert_statements(
+        &self,
+        table_name: &str,
+        chunk: &[IndexMap<String, ForgeUniversalDataField>],
+    ) -> Vec<String> {
+        let Some(first_row) = chunk.first() else {
+            return Vec::new();
+        };
+        let generated = Self::lock(&self.generated)
+            .get(table_name)
+            .cloned()
+            .unwrap_or_default();
+        let columns: Vec<&String> = first_row
+            .keys()
+            .filter(|c| !generated.contains(*c))
+            .collect();
+        let column_names = columns
+            .iter()
+            .map(|c| self.identifier(c))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let zero_date_on_write = self.zero_date_on_write();
+        batch_ranges(chunk, columns.len(), MAX_BIND_PARAMS, MAX_STATEMENT_BYTES)
+            .into_iter()
+            .map(|range| {
+                let values: Vec<String> = chunk[range]
+                    .iter()
+                    .map(|row| {
+                        let literals: Vec<String> = columns
+                            .iter()
+                            .map(|c| {
+                                let value = row.get(*c).unwrap_or(&ForgeUniversalDataField::Null);
+                                sql_literal(self.dialect, value, zero_date_on_write)
+                            })
+                            .collect();
+                        format!("({})", literals.join(", "))
+                    })
+                    .collect();
+                format!(
+                    "INSERT INTO {} ({column_names}) VALUES\n{}",
+                    self.identifier(table_name),
+                    values.join(",\n")
+                )
+            })
+            .collect()
+    }
+
+    /// INSERT statements of a chunk that update the rows whose key exists already
+    fn upsert_statements(
+        &self,
+        table_name: &str,
+        key_columns: &[String],
+        chunk: &[IndexMap<String, ForgeUniversalDataField>],
+    ) -> Vec<String> {
+        let Some(first_row) = chunk.first() else {
+            return Vec::new();
+        };
+        let generated = Self::lock(&self.generated)
+            .get(table_name)
+            .cloned()
+            .unwrap_or_default();
+        let columns: Vec<String> = first_row
+            .keys()
+            .filter(|c| !generated.contains(*c))
+            .map(|c| self.identifier(c))
+            .collect();
+        let key_columns: Vec<String> = key_columns.iter().map(|c| self.identifier(c)).collect();
+        let conflict = upsert_clause(self.dialect, &columns, &key_columns);
+        self.insert_statements(table_name, chunk)
+            .into_iter()
+            .map(|insert| format!("{insert}\n{conflict}"))
+            .collect()
+    }
+
+    /// statements creating the tables of the schema in an empty database
+    fn create_statements(
+        &self,
+        schema: &ForgeSchema,
+        config: &ForgeConfig,
+        filter: &ForgeObjectFilter,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut statements = Vec::new();
+        if let Generator::Postgres(_) = self.generator {
+            // extensions first, the tables use their types and functions, then the domains
+            statements.extend(create_extension_statements(
+                &schema.extensions,
+                &HashSet::new(),
+                config,
+            ));
+            statements.extend(schema.domains.iter().map(build_postgres_create_domain_sql));
+        }
+        for table in &schema.tables {
+            if !filter.includes_table(&table.name) {
+                continue;
+            }
+            statements.extend(match &self.generator {
+                Generator::Mysql(driver) => driver.create_table_migration_sql(table, config)?,
+                Generator::Postgres(driver) => driver.create_table_migration_sql(table, config)?,
+            });
+        }
+        Ok(statements)
+    }
+
+    /// statements adding the foreign keys of the schema to the created tables
+    fn foreign_key_statements(
+        &self,
+        schema: &ForgeSchema,
+        filter: &ForgeObjectFilter,
+    ) -> Vec<String> {
+        let changes = ForeignKeyChanges::between(schema, &ForgeSchema::default(), filter, false);
+        match &self.generator {
+            Generator::Mysql(driver) => driver.foreign_key_migration_sql(&changes).1,
+            Generator::Postgres(driver) => driver.foreign_key_migration_sql(&changes).1,
+        }
+    }
+
+    fn not_readable(&self) -> Box<dyn Error> {
+        format!(
+            "The SQL script {} is a write-only target and cannot be read",
+            self.path.display()
+        )
+        .into()
+    }
+}
+
+#[async_trait]
+impl DatabaseDriver for ScriptDriver {
+    async fn db_is_empty(&self) -> Result<bool, Box<dyn Error>> {
+        // the script is created new and applied to an empty database
+        Ok(true)
+    }
+
+    async fn fetch_schema(&self, _config: &ForgeConfig) -> Result<ForgeSchema, Box<dyn Error>> {
+        Ok(ForgeSchema::default())
+    }
+
+    async fn diff_and_apply_schema(
+        &self,
+        schema: &ForgeSchema,
+        config: &ForgeConfig,
+        filter: &ForgeObjectFilter,
+        options: MigrateOptions,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        let dry_run = options.dry_run;
+        let mut statements = self.create_statements(schema, config, filter)?;
+        if !options.defer_foreign_keys {
+            statements.extend(self.foreign_key_statements(schema, filter));
+        }
+
+        let mut generated = Self::lock(&self.generated);
+        for table in &schema.tables {
+            let columns: HashSet<String> = table
+                .columns
+                .iter()
+                .filter(|c| c.generation_expression.is_some())
+                .map(|c| c.name.clone())
+                .collect();
+            generated.insert(table.name.clone(), columns);
+        }
+        drop(generated);
+
+        if !dry_run {
+            self.write_statements(&statements)?;
+            options.applied.extend(&statements);
+            info!(statements = statements.len(), path = %self.path.display(), "schema statements written");
+        }
+        Ok(statements)
+    }
+
+    async fn apply_foreign_keys(
+        &self,
+        schema: &ForgeSchema,
+        _config: &ForgeConfig,
+        filter: &ForgeObjectFilter,
+        options: MigrateOptions,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        // written after the INSERTs of a replication
+        let statements = self.foreign_key_statements(schema, filter);
+        if !options.dry_run {
+            self.write_statements(&statements)?;
+        }
+        Ok(statements)
+    }
+
+    async fn rollback_schema_plan(
+        &self,
+        schema: &ForgeSchema,
+        _config: &ForgeConfig,
+        filter: &ForgeObjectFilter,
+        _destructive: bool,
+    ) -> Result<ForgeRollbackPlan, Box<dyn Error>> {
+        // every table of the script is new, referencing tables are dropped first
+        let mut plan = ForgeRollbackPlan::default();
+        for table in schema.tables.iter().rev() {
+            if filter.includes_table(&table.name) {
+                plan.statements.extend(match &self.generator {
+                    Generator::Mysql(driver) => driver.delete_table_migration_sql(table)?,
+                    Generator::Postgres(driver) => driver.delete_table_migration_sql(table)?,
+                });
+            }
+        }
+        Ok(plan)
+    }
+
+    async fn execute_statements(&self, statements: &[String]) -> Result<(), Box<dyn Error>> {
+        self.write_statements(statements)
+    }
+
+    async fn stream_table_data(
+        &self,
+        _table_name: &str,
+    ) -> Result<
+        Pin<
+            Box<
+                dyn Stream<Item = Result<IndexMap<String, ForgeUniversalDataField>, ForgeError>>
+                    + Send
+                    + '_,
+            >,
+        >,
+        Box<dyn Error>,
+    > {
+        Err(self.not_readable())
+    }
+
+    async fn stream_table_data_ordered(
+        &self,
+        _table_name: &str,
+        _order_by: &[String],
+        _sample: Option<ForgeVerifySample>,
+        _key_range: Option<ForgeKeyRange>,
+        _digest_columns: &[String],
+    ) -> Result<
+        Pin<
+            Box<
+                dyn Stream<Item = Result<IndexMap<String, ForgeUniversalDataField>, ForgeError>>
+                    + Send
+                    + '_,
+            >,
+        >,
+        Box<dyn Error>,
+    > {
+        Err(self.not_readable())
+    }
+
+    async fn stream_table_data_after(
+        &self,
+        _table_name: &str,
+        _key_columns: &[String],
+        _after: Option<&[ForgeUniversalDataField]>,
+    ) -> Result<
+        Pin<
+            Box<
+                dyn Stream<Item = Result<IndexMap<String, ForgeUniversalDataField>, ForgeError>>
+                    + Send
+                    + '_,
+            >,
+        >,
+        Box<dyn Error>,
+    > {
+        Err(self.not_readable())
+    }
+
+    async fn fetch_last_key(
+        &self,
+        _table_name: &str,
+        _key_columns: &[String],
+    ) -> Result<Option<Vec<ForgeUniversalDataField>>, Box<dyn Error>> {
+        Err(self.not_readable())
+    }
+
+    async fn fetch_row(
+        &self,
+        _table_name: &str,
+        _key_columns: &[String],
+        _key: &[ForgeUniversalDataField],
+    ) -> Result<Option<IndexMap<String, ForgeUniversalDataField>>, Box<dyn Error>> {
+        Err(self.not_readable())
+    }
+
+    async fn insert_chunk(
+        &self,
+        table_name: &str,
+        dry_run: bool,
+        _halt_on_error: bool,
+        chunk: Vec<IndexMap<String, ForgeUniversalDataField>>,
+    ) -> Result<(), Box<dyn Error>> {
+        let statements = self.insert_statements(table_name, &chunk);
+        if dry_run {
+            debug!(table = table_name, rows = chunk.len(), "dry run insert");
+            return Ok(());
+        }
+        self.write_statements(&statements)?;
+        *Self::lock(&self.rows)
+            .entry(table_name.to_string())
+            .or_default() += chunk.len() as u64;
+        Ok(())
+    }
+
+    async fn upsert_chunk(
+        &self,
+        table_name: &str,
+        key_columns: &[String],
+        chunk: Vec<IndexMap<String, ForgeUniversalDataField>>,
+    ) -> Result<(), Box<dyn Error>> {
+        self.write_statements(&self.upsert_statements(table_name, key_columns, &chunk))
+    }
+
+    async fn delete_rows(
+        &self,
+        table_name: &str,
+        key_columns: &[String],
+        keys: Vec<IndexMap<String, ForgeUniversalDataField>>,
+    ) -> Result<(), Box<dyn Error>> {
+        if keys.is_empty() {
+            return Ok(());
+        }
+        let zero_date_on_write = self.zero_date_on_write();
+        let key_names: Vec<String> = key_columns.iter().map(|c| self.identifier(c)).collect();
+        let statements: Vec<String> = batch_ranges(
+            &keys,
+            key_columns.len(),
+            MAX_BIND_PARAMS,
+            MAX_STATEMENT_BYTES,
+        )
+        .into_iter()
+        .map(|range| {
+            let tuples: Vec<String> = keys[range]
+                .iter()
+                .map(|key| {
+                    let literals: Vec<String> = key_columns
+                        .iter()
+                        .map(|c| {
+                            let value = key.get(c).unwrap_or(&ForgeUniversalDataField::Null);
+                            sql_literal(self.dialect, value, zero_date_on_write)
+                        })
+                        .collect();
+                    format!("({})", literals.join(", "))
+                })
+                .collect();
+            format!(
+                "DELETE FROM {} WHERE ({}) IN ({})",
+                self.identifier(table_name),
+                key_names.join(", "),
+                tuples.join(", ")
+            )
+        })
+        .collect();
+        self.write_statements(&statements)
+    }
+
+    async fn get_table_row_count(&self, table_name: &str) -> Result<u64, Box<dyn Error>> {
+        Ok(Self::lock(&self.rows).get(table_name).copied().unwrap_or(0))
+    }
+
+    async fn estimate_table_sizes(&self) -> Result<Vec<ForgeTableEstimate>, Box<dyn Error>> {
+        Ok(Vec::new())
+    }
+
+    async fn range_checksums(
+        &self,
+        _table_name: &str,
+        _key_column: &str,
+        _range_keys: u64,
+    ) -> Result<Vec<ForgeRangeChecksum>, Box<dyn Error>> {
+        Err("A script has no rows to checksum".into())
+    }
+
+    async fn delete_table_data(
+        &self,
+        table_name: &str,
+        filter: Option<&str>,
+    ) -> Result<(), Box<dyn Error>> {
+        let table = self.identifier(table_name);
+        match filter {
+            Some(filter) => self.write_statements(&[format!("DELETE FROM {table} WHERE {filter}")]),
+            None => {
+                Self::lock(&self.rows).remove(table_name);
+                self.write_statements(&[format!("DELETE FROM {table}")])
+            }
+        }
+    }
+
+    async fn begin_write(&self) -> Result<(), Box<dyn Error>> {
+        self.write_statements(&[match self.dialect {
+            ForgeDialect::Mysql => "START TRANSACTION".to_string(),
+            ForgeDialect::Postgres => "BEGIN".to_string(),
+        }])
+    }
+
+    async fn commit_write(&self) -> Result<(), Box<dyn Error>> {
+        self.write_statements(&["COMMIT".to_string()])
+    }
+
+    async fn rollback_write(&self) -> Result<(), Box<dyn Error>> {
+        self.write_statements(&["ROLLBACK".to_string()])
+    }
+
+    async fn disable_constraints(&self, tables: &[String]) -> Result<(), Box<dyn Error>> {
+        match &self.generator {
+            Generator::Mysql(_) => {
+                self.write_statements(&["SET FOREIGN_KEY_CHECKS = 0".to_string()])
+            }
+            Generator::Postgres(driver) => {
+                self.write_statements(&driver.triggers_sql(tables, false))
+            }
+        }
+    }
+
+    async fn enable_constraints(&self, tables: &[String]) -> Result<(), Box<dyn Error>> {
+        match &self.generator {
+            Generator::Mysql(_) => {
+                self.write_statements(&["SET FOREIGN_KEY_CHECKS = 1".to_string()])
+            }
+            Generator::Postgres(driver) => {
+                self.write_statements(&driver.triggers_sql(tables, true))
+            }
+        }
+    }
+
+    async fn count_foreign_key_violations(
+        &self,
+        _table_name: &str,
+        _foreign_key: &[ForgeSchemaForeignKey],
+    ) -> Result<u64, Box<dyn Error>> {
+        // checked by the database the script is applied to
+        Ok(0)
+    }
+
+    async fn count_unsigned_overflow(
+        &self,
+        _table_name: &str,
+        _column_name: &str,
+    ) -> Result<u64, Box<dyn Error>> {
+        Err(self.not_readable())
+    }
+
+    async fn preflight_checks(
+        &self,
+        _as_target: bool,
+    ) -> Result<Vec<ForgePreflightCheck>, Box<dyn Error>> {
+        Ok(vec![ForgePreflightCheck::pass(
+            "script",
+            format!(
+                "{} statements written to {}",
+                self.dialect.config_section(),
+                self.path.display()
+            ),
+        )])
+    }
+
+    async fn replication_lag(&self) -> Result<Option<std::time::Duration>, Box<dyn Error>> {
+        Ok(None)
+    }
+
+    async fn query_scalar(&self, _sql: &str) -> Result<ForgeUniversalDataField, Box<dyn Error>> {
+        Err(self.not_readable())
+    }
+
+    async fn fetch_grants(&self) -> Result<Vec<ForgeSchemaGrant>, Box<dyn Error>> {
+        Ok(Vec::new())
+    }
+
+    async fn apply_grants(
+        &self,
+        grants: &[ForgeSchemaGrant],
+        config: &ForgeConfig,
+        dry_run: bool,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        let statements: Vec<String> = grants
+            .iter()
+            .filter_map(|grant| match &self.generator {
+                Generator::Mysql(driver) => driver.build_grant_sql(grant, config),
+                Generator::Postgres(driver) => driver.build_grant_sql(grant, config),
+            })
+            .collect();
+        if !dry_run {
+            self.write_statements(&statements)?;
+        }
+        Ok(statements)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::ForgeSchemaColumn;
+    use crate::core::ForgeSchemaTable;
+    use ForgeUniversalDataField::{Binary, Boolean, Float, Integer, Null, Text, ZeroDateTime};
+
+    #[test]
+    fn script_urls_name_dialect_and_path() {
+        assert_eq!(
+            parse_script_url("postgres+script://out/migration.sql"),
+            Some((ForgeDialect::Postgres, PathBuf::from("out/migration.sql")))
+        );
+        assert_eq!(
+            parse_script_url("mysql+script:///tmp/migration.sql"),
+            Some((ForgeDialect::Mysql, PathBuf::from("/tmp/migration.sql")))
+        );
+        assert_eq!(parse_script_url("mysql://db/shop"), None);
+    }
+
+    #[test]
+    fn literals_follow_the_dialect() {
+        use ForgeDialect::{Mysql, Postgres};
+        assert_eq!(
+            sql_literal(Mysql, &Text("it's a\\b".into()), false),
+            "'it''s a\\\\b'"
+        );
+        assert_eq!(
+            sql_literal(Postgres, &Text("it's a\\b".into()), false),
+            "'it''s a\\b'"
+        );
+        assert_eq!(sql_literal(Mysql, &Binary(vec![0, 255]), false), "X'00ff'");
+        assert_eq!(
+            sql_literal(Postgres, &Binary(vec![0, 255]), false),
+            "'\\x00ff'"
+        );
+        assert_eq!(sql_literal(Mysql, &Boolean(true), false), "1");
+        assert_eq!(sql_literal(Postgres, &Boolean(true), false), "TRUE");
+        assert_eq!(sql_literal(Mysql, &Float(f64::NAN), false), "NULL");
+        assert_eq!(
+            sql_literal(Postgres, &Float(f64::NEG_INFINITY), false),
+            "'-Infinity'"
+        );
+        assert_eq!(
+            sql_literal(Mysql, &ZeroDateTime, true),
+            "'0000-00-00 00:00:00'"
+        );
+        assert_eq!(sql_literal(Postgres, &ZeroDateTime, true), "NULL");
+        assert_eq!(
+            sql_literal(
+                Postgres,
+                &ForgeUniversalDataField::Array(ForgeArray::Integer(vec![Some(1), None])),
+                false
+            ),
+            "ARRAY[1, NULL]::integer[]"
+        );
+    }
+
+    #[tokio::test]
+    async fn script_holds_ddl_and_inserts() {
+        let path = std::env::temp_dir().join(format!("fluxforge-{}.sql", std::process::id()));
+        let config = ForgeConfig::default();
+        let driver = ScriptDriver::create(ForgeDialect::Postgres, &path, &config).unwrap();
+
+        let mut table = ForgeSchemaTable::new("users");
+        table.columns.push(ForgeSchemaColumn::new("id", "int"));
+        table
+            .columns
+            .push(ForgeSchemaColumn::new("name", "varchar"));
+        let mut total = ForgeSchemaColumn::new("total", "int");
+        total.generation_expression = Some("id * 2".to_string());
+        table.columns.push(total);
+        let schema = ForgeSchema {
+            tables: vec![table],
+            ..Default::default()
+        };
+        let filter = ForgeObjectFilter::default();
+        driver
+            .diff_and_apply_schema(
+                &schema,
+                &config,
+                &filter,
+                MigrateOptions {
+                    destructive: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let rows = vec![
+            IndexMap::from([
+                ("id".to_string(), Integer(1)),
+                ("name".to_string(), Text("Ann".into())),
+                ("total".to_string(), Integer(2)),
+            ]),
+            IndexMap::from([
+                ("id".to_string(), Integer(2)),
+                ("name".to_string(), Null),
+                ("total".to_string(), Integer(4)),
+            ]),
+        ];
+        driver
+            .insert_chunk("users", false, false, rows)
+            .await
+            .unwrap();
+        assert_eq!(driver.get_table_row_count("users").await.unwrap(), 2);
+
+        let script = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(script.starts_with(
+            "-- FluxForge migration script (postgres)\nSET client_encoding = 'UTF8';\n"
+        ));
+        assert!(script.contains("CREATE TABLE users ("));
+        assert!(script.ends_with("INSERT INTO users (id, name) VALUES\n(1, 'Ann'),\n(2, NULL);\n"));
+    }
+
+    #[tokio::test]
+    async fn deferred_foreign_keys_follow_the_inserts() {
+        let path = std::env::temp_dir().join(format!("fluxforge-fk-{}.sql", std::process::id()));
+        let config = ForgeConfig::default();
+        let driver = ScriptDriver::create(ForgeDialect::Mysql, &path, &config).unwrap();
+
+        let mut users = ForgeSchemaTable::new("users");
+        users.columns.push(ForgeSchemaColumn::new("id", "int"));
+        let mut orders = ForgeSchemaTable::new("orders");
+        orders
+            .columns
+            .push(ForgeSchemaColumn::new("user_id", "int"));
+        orders
+            .foreign_keys
+            .push(crate::core::ForgeSchemaForeignKey {
+                name: "fk_orders_user".to_string(),
+                column: "user_id".to_string(),
+                ref_table: "users".to_string(),
+                ref_column: "id".to_string(),
+                on_delete: Some("CASCADE".to_string()),
+                on_update: None,
+            });
+        let schema = ForgeSchema {
+            tables: vec![orders, users],
+            ..Default::default()
+        };
+        let filter = ForgeObjectFilter::default();
+        let options = MigrateOptions {
+            defer_foreign_keys: true,
+            ..Default::default()
+        };
+        let created = driver
+            .diff_and_apply_schema(&schema, &config, &filter, options.clone())
+            .await
+            .unwrap();
+        assert!(!created.iter().any(|sql| sql.contains("FOREIGN KEY")));
+        driver
+            .insert_chunk(
+                "orders",
+                false,
+                false,
+                vec![IndexMap::from([("user_id".to_string(), Integer(7))])],
+            )
+            .await
+            .unwrap();
+        driver
+            .apply_foreign_keys(&schema, &config, &filter, options)
+            .await
+            .unwrap();
+
+        let script = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(script.ends_with(
+            "(7);\nALTER TABLE `orders` ADD CONSTRAINT `fk_orders_user` FOREIGN KEY (`user_id`) REFERENCES `users` (`id`) ON DELETE CASCADE;\n"
+        ));
+    }
+}
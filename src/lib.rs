@@ -31,9 +31,20 @@ pub mod ops;
 
 // Re-export for easier access
 pub use crate::core::ForgeUniversalDataTransferPacket;
-pub use crate::core::{ForgeConfig, ForgeError};
-pub use crate::core::{ForgeSchema, ForgeSchemaColumn, ForgeSchemaTable};
-pub use crate::core::{ForgeUniversalDataField, ForgeUniversalDataRow};
+pub use crate::core::{
+    ForgeConfig, ForgeError, ForgeKeyRange, ForgeObjectFilter, ForgeRollbackPlan, ForgeTimeZone,
+    ForgeVerifySample, MigrateOptions,
+};
+pub use crate::core::{
+    ForgePreflightCheck, ForgeRangeChecksum, ForgeTableEstimate, ForgeUniversalDataField,
+    ForgeUniversalDataRow,
+};
+pub use crate::core::{
+    ForgeSchema, ForgeSchemaColumn, ForgeSchemaForeignKey, ForgeSchemaGrant, ForgeSchemaTable,
+};
+
+/// Cancels a replication or schema apply, see [`ForgeError::Cancelled`].
+pub use tokio_util::sync::CancellationToken;
 
 use async_trait::async_trait;
 use futures::Stream;
@@ -117,23 +128,23 @@ pub trait DatabaseDriver: Send + Sync {
     ///
     /// * `schema` - The source schema to apply
     /// * `config` - Configuration for type mappings and transformation rules
-    /// * `dry_run` - If true, returns SQL statements without executing them
-    /// * `verbose` - Enable verbose output
-    /// * `destructive` - If true, allows dropping tables and columns not in source schema
+    /// * `filter` - Only tables included by the filter are created, altered or dropped
+    /// * `options` - Dry run, verbose output, dropping of tables and columns not in the
+    ///   source schema and deferral of the foreign keys
     ///
     /// # Examples
     ///
     /// ```no_run
-    /// # use fluxforge::{DatabaseDriver, ForgeSchema, core::ForgeConfig};
+    /// # use fluxforge::{DatabaseDriver, ForgeSchema, MigrateOptions, core::{ForgeConfig, ForgeObjectFilter}};
     /// # async fn example(driver: &dyn DatabaseDriver, schema: &ForgeSchema) -> Result<(), Box<dyn std::error::Error>> {
     /// let config = ForgeConfig::default();
-    /// let statements = driver.diff_and_apply_schema(
-    ///     schema,
-    ///     &config,
-    ///     true,  // dry_run
-    ///     false, // verbose
-    ///     false  // destructive
-    /// ).await?;
+    /// let options = MigrateOptions {
+    ///     dry_run: true,
+    ///     ..Default::default()
+    /// };
+    /// let statements = driver
+    ///     .diff_and_apply_schema(schema, &config, &ForgeObjectFilter::default(), options)
+    ///     .await?;
     /// for sql in statements {
     ///     println!("Would execute: {}", sql);
     /// }
@@ -151,11 +162,58 @@ pub trait DatabaseDriver: Send + Sync {
         &self,
         schema: &ForgeSchema,
         config: &ForgeConfig,
-        dry_run: bool,
-        verbose: bool,
-        destructive: bool,
+        filter: &ForgeObjectFilter,
+        options: MigrateOptions,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>>;
+
+    /// Brings the foreign keys of the target in line with the schema, without other changes.
+    ///
+    /// Creates the missing foreign keys, replaces the changed ones and, with `destructive`,
+    /// drops the ones not in the schema. Foreign keys to tables in neither schema are skipped.
+    /// Used after a data load, when `diff_and_apply_schema` ran with `defer_foreign_keys`:
+    /// the rows may then be loaded in any order and are checked once by the database.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the target schema cannot be read or a statement fails, i.e.
+    /// because loaded rows violate a foreign key.
+    async fn apply_foreign_keys(
+        &self,
+        schema: &ForgeSchema,
+        config: &ForgeConfig,
+        filter: &ForgeObjectFilter,
+        options: MigrateOptions,
     ) -> Result<Vec<String>, Box<dyn std::error::Error>>;
 
+    /// Builds the statements that revert `diff_and_apply_schema` with the same arguments.
+    ///
+    /// Must be called before the schema is applied, because the current target schema
+    /// is the state the rollback restores. Dropped tables and columns are recreated empty
+    /// and listed as irreversible.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Database connection fails
+    /// - SQL statements cannot be generated
+    async fn rollback_schema_plan(
+        &self,
+        schema: &ForgeSchema,
+        config: &ForgeConfig,
+        filter: &ForgeObjectFilter,
+        destructive: bool,
+    ) -> Result<ForgeRollbackPlan, Box<dyn std::error::Error>>;
+
+    /// Executes statements one by one in the given order (i.e. a recorded rollback).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a statement fails, the following statements are not executed.
+    async fn execute_statements(
+        &self,
+        statements: &[String],
+    ) -> Result<(), Box<dyn std::error::Error>>;
+
     /// Streams all rows from a table as universal values.
     ///
     /// # Arguments
@@ -203,6 +261,11 @@ pub trait DatabaseDriver: Send + Sync {
     ///
     /// * `table_name` - Name of the table to stream
     /// * `order_by` - Column names to order by
+    /// * `sample` - Only rows whose hash of the `order_by` columns falls into the sample,
+    ///   both engines select the same rows for keys with the same text representation
+    /// * `key_range` - Only rows whose first `order_by` column, an integer key, is in the range
+    /// * `digest_columns` - Columns returned as lowercase hex SHA-256 digest (text) instead of
+    ///   their value, computed by the server so large binary and text values are not transferred
     ///
     /// # Examples
     ///
@@ -212,7 +275,10 @@ pub trait DatabaseDriver: Send + Sync {
     /// # async fn example(driver: &dyn DatabaseDriver) -> Result<(), Box<dyn std::error::Error>> {
     /// let mut stream = driver.stream_table_data_ordered(
     ///     "users",
-    ///     &["id".to_string()]
+    ///     &["id".to_string()],
+    ///     None,
+    ///     None,
+    ///     &[]
     /// ).await?;
     /// while let Some(row) = stream.next().await {
     ///     let row = row?;
@@ -232,6 +298,9 @@ pub trait DatabaseDriver: Send + Sync {
         &self,
         table_name: &str,
         order_by: &[String],
+        sample: Option<ForgeVerifySample>,
+        key_range: Option<ForgeKeyRange>,
+        digest_columns: &[String],
     ) -> Result<
         Pin<
             Box<
@@ -243,6 +312,63 @@ pub trait DatabaseDriver: Send + Sync {
         Box<dyn std::error::Error>,
     >;
 
+    /// Streams all rows ordered by a unique key, starting after the row with the key values `after`.
+    ///
+    /// Used to restart a stream interrupted by a transient error without reading the
+    /// already copied rows again.
+    ///
+    /// # Arguments
+    ///
+    /// * `table_name` - Name of the table to stream
+    /// * `key_columns` - Columns of the primary key, the rows are ordered by them
+    /// * `after` - Key values of the last row read, `None` streams all rows
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the table or the key columns do not exist or the connection fails.
+    async fn stream_table_data_after(
+        &self,
+        table_name: &str,
+        key_columns: &[String],
+        after: Option<&[ForgeUniversalDataField]>,
+    ) -> Result<
+        Pin<
+            Box<
+                dyn Stream<Item = Result<IndexMap<String, ForgeUniversalDataField>, ForgeError>>
+                    + Send
+                    + '_,
+            >,
+        >,
+        Box<dyn std::error::Error>,
+    >;
+
+    /// Key values of the last row of a table in key order, `None` for an empty table.
+    ///
+    /// The rows of the source after this key are the rows a watch cycle appends to the target.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the table or the key columns do not exist or the connection fails.
+    async fn fetch_last_key(
+        &self,
+        table_name: &str,
+        key_columns: &[String],
+    ) -> Result<Option<Vec<ForgeUniversalDataField>>, Box<dyn std::error::Error>>;
+
+    /// The row of a table with the key values `key`, `None` if there is none.
+    ///
+    /// Used by verification to compare the values of a row whose digests differ.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the table or the key columns do not exist or the connection fails.
+    async fn fetch_row(
+        &self,
+        table_name: &str,
+        key_columns: &[String],
+        key: &[ForgeUniversalDataField],
+    ) -> Result<Option<IndexMap<String, ForgeUniversalDataField>>, Box<dyn std::error::Error>>;
+
     /// Inserts a batch of rows into a table.
     ///
     /// # Arguments
@@ -281,6 +407,33 @@ pub trait DatabaseDriver: Send + Sync {
         chunk: Vec<IndexMap<String, ForgeUniversalDataField>>,
     ) -> Result<(), Box<dyn std::error::Error>>;
 
+    /// Inserts a batch of rows, rows whose key exists already are updated instead.
+    ///
+    /// Used by the merge sync of `replicate --watch`, a failed batch is not retried row by row.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key columns are not the primary key (or a unique key) of the
+    /// table or a row is rejected.
+    async fn upsert_chunk(
+        &self,
+        table_name: &str,
+        key_columns: &[String],
+        chunk: Vec<IndexMap<String, ForgeUniversalDataField>>,
+    ) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Deletes the rows with the given keys, each key a map of the key columns to their values.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the table or the key columns do not exist or rows are still referenced.
+    async fn delete_rows(
+        &self,
+        table_name: &str,
+        key_columns: &[String],
+        keys: Vec<IndexMap<String, ForgeUniversalDataField>>,
+    ) -> Result<(), Box<dyn std::error::Error>>;
+
     /// Gets the total number of rows in a table.
     ///
     /// # Arguments
@@ -307,4 +460,210 @@ pub trait DatabaseDriver: Send + Sync {
         &self,
         table_name: &str,
     ) -> Result<u64, Box<dyn std::error::Error>>;
+
+    /// Estimates rows and bytes of all tables from the catalog statistics.
+    ///
+    /// Much faster than [`get_table_row_count`](Self::get_table_row_count) on large tables,
+    /// but the counts are only as accurate as the last `ANALYZE`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the catalog cannot be queried.
+    async fn estimate_table_sizes(
+        &self,
+    ) -> Result<Vec<ForgeTableEstimate>, Box<dyn std::error::Error>>;
+
+    /// Computes the checksums of the rows per range of `range_keys` values of the integer key
+    /// column, in one scan of the table. Ranges without rows are left out.
+    ///
+    /// The source tables of a replication are checksummed, so a later verification only
+    /// compares the ranges whose checksum changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the table or the key column do not exist or the query fails.
+    async fn range_checksums(
+        &self,
+        table_name: &str,
+        key_column: &str,
+        range_keys: u64,
+    ) -> Result<Vec<ForgeRangeChecksum>, Box<dyn std::error::Error>>;
+
+    /// Deletes all rows of a table, i.e. partially loaded data of an aborted replication, or only
+    /// the rows matching the SQL condition `filter`.
+    ///
+    /// Uses `DELETE` instead of `TRUNCATE`, so tables referenced by foreign keys can be cleared too.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the table does not exist, the filter is invalid or rows are still
+    /// referenced.
+    async fn delete_table_data(
+        &self,
+        table_name: &str,
+        filter: Option<&str>,
+    ) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Starts the write transaction: `insert_chunk` and `delete_table_data` run in it
+    /// on a single connection until [`commit_write`](Self::commit_write) or
+    /// [`rollback_write`](Self::rollback_write). Without it every chunk commits on its own.
+    ///
+    /// Inside the transaction a failed chunk is returned as an error instead of being
+    /// retried row by row, the transaction cannot be continued after a failed statement.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a write transaction is already open or cannot be started.
+    async fn begin_write(&self) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Commits the write transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no write transaction is open or the commit fails.
+    async fn commit_write(&self) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Rolls the write transaction back, does nothing if none is open.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the rollback fails.
+    async fn rollback_write(&self) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Disables the constraint checks of a bulk load into the tables.
+    ///
+    /// MySQL turns `FOREIGN_KEY_CHECKS` off in the sessions of the load (triggers cannot be
+    /// disabled), PostgreSQL runs `ALTER TABLE ... DISABLE TRIGGER ALL`, which includes the
+    /// foreign key triggers and needs superuser rights. Rows loaded meanwhile are not checked,
+    /// validate them with [`count_foreign_key_violations`](Self::count_foreign_key_violations).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the checks cannot be disabled (i.e. missing privileges).
+    async fn disable_constraints(
+        &self,
+        tables: &[String],
+    ) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Enables the constraint checks disabled by [`disable_constraints`](Self::disable_constraints) again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the checks cannot be enabled.
+    async fn enable_constraints(&self, tables: &[String])
+    -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Counts the rows of a table whose foreign key columns reference a missing row.
+    ///
+    /// `foreign_key` holds the entries of one constraint, one per column pair, so a composite
+    /// foreign key is checked as a whole.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails (i.e. the table does not exist).
+    async fn count_foreign_key_violations(
+        &self,
+        table_name: &str,
+        foreign_key: &[ForgeSchemaForeignKey],
+    ) -> Result<u64, Box<dyn std::error::Error>>;
+
+    /// Counts the values of an unsigned column above the signed bigint range (2^63 - 1).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails (i.e. the column does not exist).
+    async fn count_unsigned_overflow(
+        &self,
+        table_name: &str,
+        column_name: &str,
+    ) -> Result<u64, Box<dyn std::error::Error>>;
+
+    /// Checks server version, privileges and settings before a migration (`fluxforge doctor`).
+    ///
+    /// `as_target` selects the checks of the written side, i.e. the CREATE and INSERT privileges
+    /// instead of SELECT. Checks whose query fails are reported as failed checks.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server cannot be queried at all.
+    async fn preflight_checks(
+        &self,
+        as_target: bool,
+    ) -> Result<Vec<ForgePreflightCheck>, Box<dyn std::error::Error>>;
+
+    /// Measures how far the server lags behind its primary, `None` if it is no replica.
+    ///
+    /// MySQL reports `Seconds_Behind_Source` of `SHOW REPLICA STATUS` (`SHOW SLAVE STATUS` on
+    /// older servers), PostgreSQL the age of the last replayed transaction unless all received
+    /// WAL is replayed (`pg_last_wal_replay_lsn`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the status cannot be queried or the replication is stopped.
+    async fn replication_lag(
+        &self,
+    ) -> Result<Option<std::time::Duration>, Box<dyn std::error::Error>>;
+
+    /// Runs a query and returns the first column of the first row (`Null` if there is no row).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use fluxforge::DatabaseDriver;
+    /// # async fn example(driver: &dyn DatabaseDriver) -> Result<(), Box<dyn std::error::Error>> {
+    /// let negative = driver.query_scalar("SELECT count(*) FROM orders WHERE total < 0").await?;
+    /// println!("negative totals: {negative}");
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails or the value cannot be decoded.
+    async fn query_scalar(
+        &self,
+        sql: &str,
+    ) -> Result<ForgeUniversalDataField, Box<dyn std::error::Error>>;
+
+    /// Fetches the table-level grants of the database, grouped by grantee and table.
+    ///
+    /// Grants of the table owner (PostgreSQL) are not included.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use fluxforge::DatabaseDriver;
+    /// # async fn example(driver: &dyn DatabaseDriver) -> Result<(), Box<dyn std::error::Error>> {
+    /// for grant in driver.fetch_grants().await? {
+    ///     println!("{} on {}: {}", grant.grantee, grant.table_name, grant.privileges.join(", "));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the privilege tables cannot be queried.
+    async fn fetch_grants(&self) -> Result<Vec<ForgeSchemaGrant>, Box<dyn std::error::Error>>;
+
+    /// Generates (and unless `dry_run` executes) GRANT statements for source grants.
+    ///
+    /// Grantees are translated with the `[grants.user_mapping]` section of the config,
+    /// privileges the target does not support on table level are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `grants` - Grants fetched from the source
+    /// * `config` - Configuration with the grantee mapping
+    /// * `dry_run` - If true, returns the statements without executing them
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a statement fails (i.e. the target grantee does not exist).
+    async fn apply_grants(
+        &self,
+        grants: &[ForgeSchemaGrant],
+        config: &ForgeConfig,
+        dry_run: bool,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>>;
 }
@@ -1,15 +1,128 @@
 mod business;
 mod cli;
+#[cfg(feature = "otel")]
+mod otel;
 
 use clap::Parser;
-use cli::Cli;
+use cli::{Cli, LogFormat};
+use fluxforge::ops::progress::{self, ProgressMode};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+/// handles that must be flushed before the process exits
+#[derive(Default)]
+struct TelemetryGuard {
+    #[cfg(feature = "otel")]
+    provider: Option<opentelemetry_sdk::trace::SdkTracerProvider>,
+}
+
+impl TelemetryGuard {
+    fn shutdown(self) {
+        #[cfg(feature = "otel")]
+        if let Some(provider) = self.provider {
+            otel::shutdown(&provider);
+        }
+    }
+}
+
+/// log events go to stderr, so stdout stays reserved for command output (i.e. dry-run SQL)
+fn init_logging(args: &Cli) -> Result<TelemetryGuard, Box<dyn std::error::Error>> {
+    // without progress output the log is quiet too, unless a level is asked for, and
+    // JSON progress events are not mixed with log lines
+    let default_level = match args.progress {
+        ProgressMode::None | ProgressMode::Events => "error",
+        ProgressMode::Plain | ProgressMode::Bar => "info",
+    };
+    let filter = match args.log_level.as_deref() {
+        Some(level) => EnvFilter::new(level),
+        None => EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_level)),
+    };
+
+    let fmt_layer = match args.log_format {
+        LogFormat::Text => tracing_subscriber::fmt::layer()
+            .with_writer(std::io::stderr)
+            .boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .with_writer(std::io::stderr)
+            .boxed(),
+    };
+
+    #[allow(unused_mut)]
+    let mut guard = TelemetryGuard::default();
+
+    #[cfg(feature = "otel")]
+    let otel_layer = match args.otel_endpoint.as_deref() {
+        Some(endpoint) => {
+            let (provider, layer) = otel::init(endpoint)?;
+            guard.provider = Some(provider);
+            Some(layer)
+        }
+        None => None,
+    };
+    #[cfg(not(feature = "otel"))]
+    if args.otel_endpoint.is_some() {
+        return Err("--otel-endpoint requires a build with the 'otel' feature".into());
+    }
+
+    let registry = tracing_subscriber::registry().with(filter).with(fmt_layer);
+    #[cfg(feature = "otel")]
+    let registry = registry.with(otel_layer);
+    registry.init();
+
+    Ok(guard)
+}
 
 #[tokio::main]
 async fn main() {
     let args = Cli::parse();
+    let guard = match init_logging(&args) {
+        Ok(guard) => guard,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    fluxforge::drivers::credentials::configure(
+        args.source_password_file,
+        args.target_password_file,
+    );
+    let options = business::GlobalOptions {
+        profile: args.profile,
+        ssh: args.ssh,
+        ssh_identity: args.ssh_identity,
+    };
+    if args.print_effective_config {
+        let effective = options
+            .load_config(args.command.config_path())
+            .and_then(|config| fluxforge::config::effective_config(&config));
+        match effective {
+            Ok(effective) => eprintln!("# effective config\n{effective}"),
+            Err(e) => {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let progress_display = progress::display(args.progress);
+    let result = business::handle_command(args.command, &options).await;
+    drop(progress_display);
+    guard.shutdown();
 
-    if let Err(e) = business::handle_command(args.command).await {
+    if let Err(e) = result {
         eprintln!("Error: {e}");
-        std::process::exit(1);
+        // drift is an expected outcome of `diff --check`, CI can tell it apart from failures
+        let code = if e.is::<business::SchemaDrift>() {
+            2
+        } else if fluxforge::ForgeError::is_cancelled(e.as_ref()) {
+            // the exit code of a process stopped by SIGINT
+            130
+        } else {
+            1
+        };
+        std::process::exit(code);
     }
 }
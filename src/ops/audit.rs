@@ -0,0 +1,198 @@
+//! Audit log of applied schema changes and their rollback plans.
+//!
+//! Every schema apply that executed statements appends one JSON line with a run id,
+//! the executed statements and the statements that revert them. `rollback --run <id>`
+//! reads the record back and executes its rollback plan.
+
+use crate::ForgeRollbackPlan;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+/// One applied schema change.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub run_id: String,
+    pub created_at: String,
+    /// Command that applied the statements (migrate, replicate, rollback)
+    pub command: String,
+    /// Target URL without password
+    pub target: String,
+    /// Executed statements in execution order
+    pub statements: Vec<String>,
+    pub rollback: ForgeRollbackPlan,
+}
+
+impl AuditRecord {
+    /// record with a new, time based run id
+    #[must_use]
+    pub fn new(
+        command: &str,
+        target: &str,
+        statements: Vec<String>,
+        rollback: ForgeRollbackPlan,
+    ) -> Self {
+        let now = chrono::Local::now();
+        Self {
+            run_id: now.format("%Y%m%d-%H%M%S-%3f").to_string(),
+            created_at: now.to_rfc3339(),
+            command: command.to_string(),
+            target: target.to_string(),
+            statements,
+            rollback,
+        }
+    }
+}
+
+/// appends a record as one JSON line
+///
+/// # Errors
+///
+/// Returns an error if the audit log cannot be written.
+pub fn append_record(path: &Path, record: &AuditRecord) -> Result<(), Box<dyn std::error::Error>> {
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Error opening audit log {path:?}: {e}"))?;
+    let mut line = serde_json::to_vec(record)?;
+    line.push(b'\n');
+    file.write_all(&line)?;
+    Ok(())
+}
+
+/// finds the record of a run
+///
+/// # Errors
+///
+/// Returns an error if the audit log cannot be read or contains no such run.
+pub fn find_record(path: &Path, run_id: &str) -> Result<AuditRecord, Box<dyn std::error::Error>> {
+    let file =
+        std::fs::File::open(path).map_err(|e| format!("Error opening audit log {path:?}: {e}"))?;
+
+    for line in std::io::BufReader::new(file).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: AuditRecord = serde_json::from_str(&line)
+            .map_err(|e| format!("Error parsing audit log {path:?}: {e}"))?;
+        if record.run_id == run_id {
+            return Ok(record);
+        }
+    }
+
+    Err(format!("Run {run_id} not found in audit log {path:?}").into())
+}
+
+/// Summary of a rollback: what is reverted and what cannot be.
+#[must_use]
+pub fn rollback_report(record: &AuditRecord) -> String {
+    use std::fmt::Write as _;
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Rollback of run {} ({} at {}): {} statements revert {} applied statements.",
+        record.run_id,
+        record.command,
+        record.created_at,
+        record.rollback.statements.len(),
+        record.statements.len()
+    );
+    if record.rollback.irreversible.is_empty() {
+        out.push_str("All changes are reversible.\n");
+    } else {
+        out.push_str("Irreversible changes (structure is restored, data is not):\n");
+        for change in &record.rollback.irreversible {
+            let _ = writeln!(out, "  - {change}");
+        }
+    }
+    out
+}
+
+/// SQL script of a rollback plan (`--rollback-file`), irreversible changes are listed as comments.
+#[must_use]
+pub fn rollback_script(plan: &ForgeRollbackPlan, target: &str) -> String {
+    use std::fmt::Write as _;
+
+    let mut out = format!(
+        "-- FluxForge rollback of the schema changes to {target}, created {}\n",
+        chrono::Local::now().to_rfc3339()
+    );
+    for change in &plan.irreversible {
+        let _ = writeln!(out, "-- irreversible: {change}");
+    }
+    for sql in &plan.statements {
+        let sql = sql.trim_end();
+        if sql.ends_with(';') {
+            let _ = writeln!(out, "{sql}");
+        } else {
+            let _ = writeln!(out, "{sql};");
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_roundtrip_by_run_id() {
+        let path =
+            std::env::temp_dir().join(format!("fluxforge_audit_test_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut first = AuditRecord::new(
+            "migrate",
+            "mysql://app@db/shop",
+            vec!["ALTER TABLE `users` ADD COLUMN `age` int NULL;".to_string()],
+            ForgeRollbackPlan {
+                statements: vec!["ALTER TABLE `users` DROP COLUMN `age`;".to_string()],
+                irreversible: Vec::new(),
+            },
+        );
+        first.run_id = "run-1".to_string();
+        let mut second = first.clone();
+        second.run_id = "run-2".to_string();
+        append_record(&path, &first).unwrap();
+        append_record(&path, &second).unwrap();
+
+        assert_eq!(find_record(&path, "run-1").unwrap(), first);
+        assert_eq!(find_record(&path, "run-2").unwrap(), second);
+        assert!(find_record(&path, "run-3").is_err());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn report_lists_irreversible_changes() {
+        let mut record = AuditRecord::new(
+            "migrate",
+            "postgres://app@db/shop",
+            vec!["DROP TABLE IF EXISTS audit CASCADE".to_string()],
+            ForgeRollbackPlan {
+                statements: vec!["CREATE TABLE audit (\n  id integer NOT NULL\n)".to_string()],
+                irreversible: vec![
+                    "table audit is dropped, its data cannot be restored".to_string(),
+                ],
+            },
+        );
+        record.run_id = "run-1".to_string();
+
+        let report = rollback_report(&record);
+        assert!(report.starts_with("Rollback of run run-1 (migrate at "));
+        assert!(report.contains("1 statements revert 1 applied statements"));
+        assert!(report.contains("  - table audit is dropped, its data cannot be restored\n"));
+
+        let script = rollback_script(&record.rollback, "postgres://app@db/shop");
+        assert!(script.starts_with(
+            "-- FluxForge rollback of the schema changes to postgres://app@db/shop, created "
+        ));
+        assert!(script.ends_with(
+            "-- irreversible: table audit is dropped, its data cannot be restored\nCREATE TABLE audit (\n  id integer NOT NULL\n);\n"
+        ));
+    }
+}
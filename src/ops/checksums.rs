@@ -0,0 +1,186 @@
+//! Checksums of the primary key ranges of the source tables (`--checksum-file`).
+//!
+//! A replication records the checksum of every range of `[general] checksum_range_keys` key
+//! values per table, computed by the source database before the rows are copied. A later
+//! `fluxforge verify` with the same file computes them again and compares only the rows of the
+//! ranges whose checksum changed, the other rows are unchanged since they were copied or last
+//! verified. Tables without a single integer primary key are always verified completely.
+
+use crate::{
+    DatabaseDriver, ForgeConfig, ForgeKeyRange, ForgeRangeChecksum, ForgeSchema, ForgeSchemaTable,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use tracing::info;
+
+/// Checksums of all tables, the content of the checksum file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChecksumFile {
+    /// key values per range, checksums of another range size are not compared
+    pub range_keys: u64,
+    /// source table name -> checksums
+    pub tables: BTreeMap<String, TableChecksums>,
+}
+
+/// Checksums of the key ranges of one table.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableChecksums {
+    pub key_column: String,
+    /// ranges with rows, ordered by range
+    pub ranges: Vec<ForgeRangeChecksum>,
+}
+
+/// single integer primary key column of a table, the ranges are built from its values
+#[must_use]
+pub fn range_key_column(table: &ForgeSchemaTable) -> Option<&str> {
+    let keys: Vec<_> = table.columns.iter().filter(|c| c.is_primary_key).collect();
+    match keys.as_slice() {
+        [key] if key.data_type.to_lowercase().contains("int") => Some(key.name.as_str()),
+        _ => None,
+    }
+}
+
+/// Computes the checksums of the source tables with a single integer primary key.
+///
+/// # Errors
+///
+/// Returns an error if the checksums of a table cannot be computed.
+pub async fn compute(
+    source: &dyn DatabaseDriver,
+    schema: &ForgeSchema,
+    config: &ForgeConfig,
+) -> Result<ChecksumFile, Box<dyn std::error::Error>> {
+    let range_keys = config.get_checksum_range_keys();
+    let mut tables = BTreeMap::new();
+    for table in &schema.tables {
+        let Some(key_column) = range_key_column(table) else {
+            continue;
+        };
+        let ranges = source
+            .range_checksums(&table.name, key_column, range_keys)
+            .await?;
+        info!(table = %table.name, ranges = ranges.len(), "key range checksums computed");
+        tables.insert(
+            table.name.clone(),
+            TableChecksums {
+                key_column: key_column.to_string(),
+                ranges,
+            },
+        );
+    }
+    Ok(ChecksumFile { range_keys, tables })
+}
+
+impl ChecksumFile {
+    /// Reads a checksum file, `None` if it does not exist yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is no checksum file.
+    pub fn read(path: &Path) -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("Error reading checksum file {path:?}: {e}").into()),
+        };
+        let file = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid checksum file {path:?}: {e}"))?;
+        Ok(Some(file))
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn write(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let file = std::fs::File::create(path)
+            .map_err(|e| format!("Error creating checksum file {path:?}: {e}"))?;
+        serde_json::to_writer(std::io::BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    /// Key ranges of the tables whose checksum differs from `current`.
+    ///
+    /// Tables missing in this file, with another key column or range size are not listed,
+    /// they are verified completely. A table without changes is listed without ranges.
+    #[must_use]
+    pub fn changed_ranges(&self, current: &ChecksumFile) -> HashMap<String, Vec<ForgeKeyRange>> {
+        if self.range_keys != current.range_keys {
+            return HashMap::new();
+        }
+        let range_keys = i64::try_from(self.range_keys).unwrap_or(i64::MAX);
+        let mut changed = HashMap::new();
+        for (table, now) in &current.tables {
+            let Some(stored) = self.tables.get(table) else {
+                continue;
+            };
+            if stored.key_column != now.key_column {
+                continue;
+            }
+            let before: HashMap<i64, &ForgeRangeChecksum> =
+                stored.ranges.iter().map(|r| (r.range, r)).collect();
+            let after: HashMap<i64, &ForgeRangeChecksum> =
+                now.ranges.iter().map(|r| (r.range, r)).collect();
+            let ranges: BTreeSet<i64> = before.keys().chain(after.keys()).copied().collect();
+            let keys = ranges
+                .into_iter()
+                .filter(|range| before.get(range) != after.get(range))
+                .map(|range| {
+                    let from = range.saturating_mul(range_keys);
+                    ForgeKeyRange {
+                        from,
+                        to: from.saturating_add(range_keys - 1),
+                    }
+                })
+                .collect();
+            changed.insert(table.clone(), keys);
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    fn checksums(ranges: &[(i64, u64, &str)]) -> ChecksumFile {
+        let ranges = ranges
+            .iter()
+            .map(|(range, rows, checksum)| ForgeRangeChecksum {
+                range: *range,
+                rows: *rows,
+                checksum: (*checksum).to_string(),
+            })
+            .collect();
+        ChecksumFile {
+            range_keys: 1000,
+            tables: BTreeMap::from([(
+                "orders".to_string(),
+                TableChecksums {
+                    key_column: "id".to_string(),
+                    ranges,
+                },
+            )]),
+        }
+    }
+
+    #[test]
+    fn only_changed_added_and_removed_ranges_are_verified() {
+        let stored = checksums(&[(0, 999, "a"), (1, 1000, "b"), (2, 1000, "c"), (-1, 5, "d")]);
+        let current = checksums(&[(0, 999, "a"), (1, 1000, "B"), (3, 1, "e"), (-1, 5, "d")]);
+
+        let changed: Vec<String> = stored.changed_ranges(&current)["orders"]
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(changed, ["1000:1999", "2000:2999", "3000:3999"]);
+        assert!(stored.changed_ranges(&stored)["orders"].is_empty());
+
+        let resized = ChecksumFile {
+            range_keys: 500,
+            ..current
+        };
+        assert!(stored.changed_ranges(&resized).is_empty());
+    }
+}
@@ -0,0 +1,354 @@
+//! Cross-engine compatibility report (`fluxforge compat`).
+//!
+//! Collects everything of a source schema that does not translate cleanly to the target
+//! dialect, each with a suggested config rule where one exists. The schema checks need no
+//! target connection: the known incompatibilities of [`incompat`](crate::ops::incompat),
+//! lossy type conversions, enums, index types and types the target does not know. The data
+//! checks query the source for zero dates, overflowing unsigned values and stored routines.
+
+use crate::config::validate::is_dialect_type;
+use crate::core::{ForgeDialect, ForgeUniversalDataField, convert_schema};
+use crate::ops::incompat::Severity;
+use crate::ops::{incompat, lossy_types};
+use crate::{DatabaseDriver, ForgeConfig, ForgeSchema};
+use serde::Serialize;
+use std::fmt;
+use std::fmt::Write as _;
+
+/// Something of the source that does not translate cleanly to the target.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CompatIssue {
+    /// kind of the issue, i.e. "lossy_type" or an incompatibility code like "FF001"
+    pub kind: String,
+    pub severity: Severity,
+    /// empty for issues of the database, like stored routines
+    pub table: String,
+    /// column or index name
+    pub object: String,
+    pub message: String,
+    /// config rule or manual step that resolves the issue
+    pub suggestion: Option<String>,
+}
+
+impl CompatIssue {
+    fn new(kind: &str, severity: Severity, table: &str, object: &str, message: String) -> Self {
+        Self {
+            kind: kind.to_string(),
+            severity,
+            table: table.to_string(),
+            object: object.to_string(),
+            message,
+            suggestion: None,
+        }
+    }
+
+    fn suggest(mut self, suggestion: String) -> Self {
+        self.suggestion = Some(suggestion);
+        self
+    }
+}
+
+impl fmt::Display for CompatIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}] ", self.severity, self.kind)?;
+        match (self.table.is_empty(), self.object.is_empty()) {
+            (true, _) => {}
+            (false, true) => write!(f, "{}: ", self.table)?,
+            (false, false) => write!(f, "{}.{}: ", self.table, self.object)?,
+        }
+        write!(f, "{}", self.message)?;
+        if let Some(ref suggestion) = self.suggestion {
+            write!(f, "\n    suggestion: {suggestion}")?;
+        }
+        Ok(())
+    }
+}
+
+/// dialect of the source schema, MySQL for schema files without a source system
+fn source_dialect(schema: &ForgeSchema) -> ForgeDialect {
+    schema
+        .metadata
+        .source_system
+        .parse()
+        .unwrap_or(ForgeDialect::Mysql)
+}
+
+/// Issues of the schema that are found without reading any data, in table order.
+#[must_use]
+pub fn check_schema(
+    schema: &ForgeSchema,
+    config: &ForgeConfig,
+    target: ForgeDialect,
+) -> Vec<CompatIssue> {
+    let mut found: Vec<CompatIssue> = incompat::check_schema(schema, Some(target))
+        .into_iter()
+        .map(|i| {
+            CompatIssue::new(
+                i.code,
+                i.severity,
+                &i.table,
+                &i.object,
+                i.message.to_string(),
+            )
+            .suggest(i.hint)
+        })
+        .collect();
+
+    for conversion in lossy_types::check_schema(schema, config, target) {
+        let suggestion = if conversion.source_type.contains("bigint")
+            && conversion.source_type.contains("unsigned")
+        {
+            "[mysql.rules.on_read] unsigned_bigint_to_numeric = true".to_string()
+        } else {
+            format!(
+                "[tables.column_overrides.{}] {} = \"<wider type>\"",
+                conversion.table, conversion.column
+            )
+        };
+        found.push(
+            CompatIssue::new(
+                "lossy_type",
+                Severity::Warning,
+                &conversion.table,
+                &conversion.column,
+                format!(
+                    "{} -> {}, {}",
+                    conversion.source_type, conversion.target_type, conversion.reason
+                ),
+            )
+            .suggest(suggestion),
+        );
+    }
+
+    let source = source_dialect(schema);
+    let converted = convert_schema(schema, config, target);
+    for (table, target_table) in schema.tables.iter().zip(&converted.tables) {
+        for (column, written) in table.columns.iter().zip(&target_table.columns) {
+            let data_type = written.data_type.to_lowercase();
+            if target == ForgeDialect::Postgres && (data_type == "enum" || data_type == "set") {
+                found.push(
+                    CompatIssue::new(
+                        "enum",
+                        Severity::Error,
+                        &table.name,
+                        &column.name,
+                        format!("PostgreSQL has no {} type", data_type.to_uppercase()),
+                    )
+                    .suggest(format!(
+                        "[{source}.types.on_read] \"{data_type}\" = \"varchar\""
+                    )),
+                );
+            } else if written.domain.is_none() && !is_dialect_type(target, &data_type) {
+                found.push(
+                    CompatIssue::new(
+                        "unsupported_type",
+                        Severity::Error,
+                        &table.name,
+                        &column.name,
+                        format!("{target} has no type {data_type}"),
+                    )
+                    .suggest(format!(
+                        "[{target}.types.on_write] \"{data_type}\" = \"<{target} type>\""
+                    )),
+                );
+            }
+        }
+        for index in &table.indices {
+            let Some(ref index_type) = index.index_type else {
+                continue;
+            };
+            let index_type = index_type.to_uppercase();
+            if target == ForgeDialect::Postgres
+                && (index_type == "FULLTEXT" || index_type == "SPATIAL")
+            {
+                found.push(
+                    CompatIssue::new(
+                        "index_type",
+                        Severity::Warning,
+                        &table.name,
+                        &index.name,
+                        format!("{index_type} index is created as a B-tree index"),
+                    )
+                    .suggest(if index_type == "FULLTEXT" {
+                        "replace it with a GIN index on to_tsvector(...) after the migration"
+                            .to_string()
+                    } else {
+                        "replace it with a GiST index of PostGIS after the migration".to_string()
+                    }),
+                );
+            }
+        }
+    }
+    found
+}
+
+/// count of a `count(*)` query
+async fn count(source: &dyn DatabaseDriver, sql: &str) -> Result<u64, Box<dyn std::error::Error>> {
+    match source.query_scalar(sql).await? {
+        ForgeUniversalDataField::Integer(n) => Ok(u64::try_from(n).unwrap_or(0)),
+        ForgeUniversalDataField::UnsignedInteger(n) => Ok(n),
+        other => Ok(other.to_string().parse()?),
+    }
+}
+
+/// Issues found in the data and catalog of the source: zero dates (MySQL), unsigned
+/// values above the signed range of the target and stored routines, which are not migrated.
+///
+/// # Errors
+///
+/// Returns an error if a query of the source fails.
+pub async fn check_data(
+    source: &dyn DatabaseDriver,
+    schema: &ForgeSchema,
+    config: &ForgeConfig,
+    target: ForgeDialect,
+) -> Result<Vec<CompatIssue>, Box<dyn std::error::Error>> {
+    let mut found = Vec::new();
+    let dialect = source_dialect(schema);
+
+    if dialect == ForgeDialect::Mysql {
+        for table in &schema.tables {
+            for column in &table.columns {
+                let native = column
+                    .source_type
+                    .as_deref()
+                    .unwrap_or(&column.data_type)
+                    .to_lowercase();
+                let base = native.split(['(', ' ']).next().unwrap_or_default();
+                if !matches!(base, "date" | "datetime" | "timestamp") {
+                    continue;
+                }
+                let rows = count(
+                    source,
+                    &format!(
+                        "SELECT CAST(COUNT(*) AS SIGNED) FROM `{}` WHERE CAST(`{}` AS CHAR) LIKE '0000-00-00%'",
+                        table.name, column.name
+                    ),
+                )
+                .await?;
+                if rows == 0 {
+                    continue;
+                }
+                let issue = CompatIssue::new(
+                    "zero_dates",
+                    if column.is_nullable || target == ForgeDialect::Mysql {
+                        Severity::Warning
+                    } else {
+                        Severity::Error
+                    },
+                    &table.name,
+                    &column.name,
+                    format!("{rows} rows with zero dates (0000-00-00)"),
+                );
+                found.push(match target {
+                    ForgeDialect::Mysql => {
+                        issue.suggest("[mysql.rules.on_write] zero_date = true".to_string())
+                    }
+                    ForgeDialect::Postgres if !column.is_nullable => issue.suggest(format!(
+                        "zero dates are written as NULL: [tables.column_overrides.{}] {} = {{ nullable = true }}",
+                        table.name, column.name
+                    )),
+                    ForgeDialect::Postgres => issue.suggest(
+                        "zero dates are written as NULL, update them on the source to keep a value"
+                            .to_string(),
+                    ),
+                });
+            }
+        }
+    }
+
+    for overflow in lossy_types::scan_unsigned_overflow(source, schema, config, target).await? {
+        found.push(
+            CompatIssue::new(
+                "unsigned_overflow",
+                Severity::Error,
+                &overflow.table,
+                &overflow.column,
+                format!("{} rows above {}", overflow.rows, i64::MAX),
+            )
+            .suggest("[mysql.rules.on_read] unsigned_bigint_to_numeric = true".to_string()),
+        );
+    }
+
+    let routines_sql = match dialect {
+        ForgeDialect::Mysql => {
+            "SELECT CAST(COUNT(*) AS SIGNED) FROM information_schema.ROUTINES WHERE ROUTINE_SCHEMA = DATABASE()"
+        }
+        ForgeDialect::Postgres => {
+            "SELECT count(*) FROM pg_proc p JOIN pg_namespace n ON n.oid = p.pronamespace WHERE n.nspname = current_schema()"
+        }
+    };
+    let routines = count(source, routines_sql).await?;
+    if routines > 0 {
+        found.push(
+            CompatIssue::new(
+                "routines",
+                Severity::Warning,
+                "",
+                "",
+                format!("{routines} stored routines are not migrated"),
+            )
+            .suggest(format!("port them to {target} by hand")),
+        );
+    }
+    Ok(found)
+}
+
+/// one entry per issue with its suggestion and a summary
+#[must_use]
+pub fn compat_report(found: &[CompatIssue]) -> String {
+    let mut out = String::new();
+    for issue in found {
+        let _ = writeln!(out, "{issue}");
+    }
+    let errors = found
+        .iter()
+        .filter(|i| i.severity == Severity::Error)
+        .count();
+    let _ = writeln!(out, "{} issue(s), {errors} error(s)", found.len());
+    out
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::core::ForgeSchemaIndex;
+    use crate::{ForgeSchemaColumn, ForgeSchemaTable};
+
+    #[test]
+    fn schema_issues_carry_suggestions() {
+        let mut table = ForgeSchemaTable::new("posts");
+        let mut state = ForgeSchemaColumn::new("state", "enum");
+        state.source_type = Some("enum('draft','live')".to_string());
+        state.enum_values = Some(vec!["draft".to_string(), "live".to_string()]);
+        table.columns.push(state);
+        let mut shape = ForgeSchemaColumn::new("shape", "geometry");
+        shape.source_type = Some("geometry".to_string());
+        table.columns.push(shape);
+        table.indices.push(ForgeSchemaIndex {
+            name: "ft_body".to_string(),
+            columns: vec!["state".to_string()],
+            index_type: Some("FULLTEXT".to_string()),
+            ..Default::default()
+        });
+        let mut schema = ForgeSchema {
+            tables: vec![table],
+            ..Default::default()
+        };
+        schema.metadata.source_system = "mysql".to_string();
+
+        let found = check_schema(&schema, &ForgeConfig::default(), ForgeDialect::Mysql);
+        assert!(found.is_empty(), "{found:?}");
+
+        let found = check_schema(&schema, &ForgeConfig::default(), ForgeDialect::Postgres);
+        let lines: Vec<String> = found.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "error [enum] posts.state: PostgreSQL has no ENUM type\n    suggestion: [mysql.types.on_read] \"enum\" = \"varchar\"",
+                "warning [index_type] posts.ft_body: FULLTEXT index is created as a B-tree index\n    suggestion: replace it with a GIN index on to_tsvector(...) after the migration",
+            ]
+        );
+    }
+}
@@ -0,0 +1,611 @@
+//! HTTP control and status API of `fluxforge daemon`.
+//!
+//! The daemon runs replication jobs started over HTTP, one at a time, and reports their
+//! state and per-table progress from the [`progress`](super::progress) events:
+//!
+//! - `POST /jobs` starts a job (JSON [`JobRequest`]), `409` while another job runs
+//! - `GET /jobs` and `GET /jobs/<id>` return the [`JobStatus`] of all jobs or one job
+//! - `POST /jobs/<id>/stop` stops a running job
+//! - `GET /health` and `GET /metrics` (Prometheus, see [`metrics`](super::metrics))
+//!
+//! With a token every request needs the header `Authorization: Bearer <token>`. The config
+//! file of a job is read by the daemon, restrict it to a directory with [`Daemon::config_dir`].
+
+use crate::CancellationToken;
+use crate::ops::checkpoint::strip_password;
+use crate::ops::metrics;
+use crate::ops::progress::{self, ProgressEvent, ProgressObserver};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+
+/// largest accepted request (headers and body)
+const MAX_REQUEST_BYTES: usize = 64 * 1024;
+
+/// time a client has to send its whole request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// pause between the checks of a stopped job at shutdown
+const JOB_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Replication started by `POST /jobs`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobRequest {
+    pub source: String,
+    pub target: String,
+    /// config file on the host of the daemon, relative to and within the config directory
+    /// of the daemon if it has one
+    #[serde(default)]
+    pub config: Option<PathBuf>,
+    #[serde(default)]
+    pub verify: bool,
+    #[serde(default)]
+    pub halt_on_error: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Running,
+    Succeeded,
+    Failed,
+    Stopped,
+}
+
+/// progress of one table of a job
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TableProgress {
+    pub rows_total: u64,
+    pub rows_written: u64,
+    pub done: bool,
+}
+
+/// State of a job as returned by `GET /jobs/<id>`, the URLs without password.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JobStatus {
+    pub id: u64,
+    pub state: JobState,
+    pub source: String,
+    pub target: String,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub current_table: Option<String>,
+    pub tables: IndexMap<String, TableProgress>,
+    pub last_error: Option<String>,
+}
+
+/// Runs a job to its end on a blocking thread, it must return soon after its token is cancelled.
+pub type JobRunner = Arc<dyn Fn(JobRequest, CancellationToken) -> Result<(), String> + Send + Sync>;
+
+/// All jobs of the daemon.
+#[derive(Debug, Default)]
+pub struct Jobs {
+    jobs: BTreeMap<u64, JobStatus>,
+    stops: HashMap<u64, CancellationToken>,
+    stopping: Vec<u64>,
+}
+
+impl Jobs {
+    fn running(&mut self) -> Option<&mut JobStatus> {
+        self.jobs
+            .values_mut()
+            .find(|job| job.state == JobState::Running)
+    }
+
+    /// Records a new running job.
+    ///
+    /// # Errors
+    ///
+    /// Returns the id of the running job if there is one.
+    pub fn start(&mut self, request: &JobRequest, stop: CancellationToken) -> Result<u64, u64> {
+        if let Some(running) = self.running() {
+            return Err(running.id);
+        }
+        let id = self.jobs.keys().next_back().map_or(1, |id| id + 1);
+        self.jobs.insert(
+            id,
+            JobStatus {
+                id,
+                state: JobState::Running,
+                source: strip_password(&request.source),
+                target: strip_password(&request.target),
+                started_at: chrono::Local::now().to_rfc3339(),
+                finished_at: None,
+                current_table: None,
+                tables: IndexMap::new(),
+                last_error: None,
+            },
+        );
+        self.stops.insert(id, stop);
+        Ok(id)
+    }
+
+    /// Cancels the token of a running job, false if it is not running.
+    pub fn stop(&mut self, id: u64) -> bool {
+        match self.stops.remove(&id) {
+            Some(stop) => {
+                stop.cancel();
+                self.stopping.push(id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Records the end of a job.
+    pub fn finish(&mut self, id: u64, result: Result<(), String>) {
+        self.stops.remove(&id);
+        let stopped = self.stopping.contains(&id);
+        if let Some(job) = self.jobs.get_mut(&id) {
+            job.finished_at = Some(chrono::Local::now().to_rfc3339());
+            job.current_table = None;
+            job.state = match result {
+                _ if stopped => JobState::Stopped,
+                Ok(()) => JobState::Succeeded,
+                Err(e) => {
+                    job.last_error = Some(e);
+                    JobState::Failed
+                }
+            };
+        }
+    }
+
+    /// Applies a progress event to the running job.
+    pub fn record(&mut self, event: &ProgressEvent) {
+        let Some(job) = self.running() else {
+            return;
+        };
+        let table = job.tables.entry(event.table().to_string()).or_default();
+        match event {
+            ProgressEvent::TableStart { rows_total, .. } => {
+                table.rows_total = *rows_total;
+                job.current_table = Some(event.table().to_string());
+            }
+            ProgressEvent::ChunkDone { rows, .. } => table.rows_written = *rows,
+            ProgressEvent::TableDone { rows, .. } => {
+                table.rows_written = *rows;
+                table.done = true;
+                job.current_table = None;
+            }
+            // the job state shows the copy, verification fails the job on a mismatch
+            ProgressEvent::VerifyStart { .. }
+            | ProgressEvent::VerifyProgress { .. }
+            | ProgressEvent::VerifyDone { .. } => {}
+        }
+    }
+
+    #[must_use]
+    pub fn get(&self, id: u64) -> Option<&JobStatus> {
+        self.jobs.get(&id)
+    }
+}
+
+/// jobs shared by the server, the job threads and the progress observer
+#[derive(Debug, Default, Clone)]
+struct SharedJobs(Arc<Mutex<Jobs>>);
+
+impl SharedJobs {
+    // a poisoned lock only means a job thread panicked, the states are still usable
+    fn lock(&self) -> MutexGuard<'_, Jobs> {
+        self.0
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl ProgressObserver for SharedJobs {
+    fn on_event(&self, event: &ProgressEvent) {
+        self.lock().record(event);
+    }
+}
+
+/// HTTP response: status code, content type and body
+type Response = (u16, &'static str, String);
+
+fn json_response(status: u16, body: &impl Serialize) -> Response {
+    (
+        status,
+        "application/json",
+        serde_json::to_string(body).unwrap_or_default(),
+    )
+}
+
+fn error_response(status: u16, message: &str) -> Response {
+    json_response(status, &serde_json::json!({ "error": message }))
+}
+
+/// Job control server of `fluxforge daemon`.
+pub struct Daemon {
+    jobs: SharedJobs,
+    runner: JobRunner,
+    token: Option<String>,
+    config_dir: Option<PathBuf>,
+}
+
+impl Daemon {
+    /// daemon running its jobs with `runner`, `token` is required from every client if set
+    #[must_use]
+    pub fn new(runner: JobRunner, token: Option<String>) -> Self {
+        Self {
+            jobs: SharedJobs::default(),
+            runner,
+            token,
+            config_dir: None,
+        }
+    }
+
+    /// Only config files in `dir` can be named by a job, without it any file readable by the
+    /// daemon can.
+    #[must_use]
+    pub fn config_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.config_dir = dir;
+        self
+    }
+
+    /// Answers one request, `authorization` is the value of its `Authorization` header.
+    ///
+    /// Starting a job spawns it on a blocking thread of the current tokio runtime.
+    #[must_use]
+    pub fn handle(
+        &self,
+        method: &str,
+        path: &str,
+        authorization: Option<&str>,
+        body: &str,
+    ) -> Response {
+        if let Some(ref token) = self.token
+            && !authorization
+                .and_then(|a| a.strip_prefix("Bearer "))
+                .is_some_and(|given| constant_time_eq(given.as_bytes(), token.as_bytes()))
+        {
+            return error_response(401, "missing or wrong bearer token");
+        }
+
+        let segments: Vec<&str> = path
+            .split('?')
+            .next()
+            .unwrap_or_default()
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .collect();
+        let job_id = |segment: &str| segment.parse::<u64>().ok();
+        match (method, segments.as_slice()) {
+            ("GET", ["health"]) => json_response(200, &serde_json::json!({ "status": "ok" })),
+            ("GET", ["metrics"]) => (200, "text/plain; version=0.0.4", metrics::global().render()),
+            ("GET", ["jobs"]) => {
+                let jobs: Vec<JobStatus> = self.jobs.lock().jobs.values().cloned().collect();
+                json_response(200, &jobs)
+            }
+            ("POST", ["jobs"]) => self.start_job(body),
+            ("GET", ["jobs", id]) => {
+                match job_id(id).and_then(|id| self.jobs.lock().get(id).cloned()) {
+                    Some(job) => json_response(200, &job),
+                    None => error_response(404, "unknown job"),
+                }
+            }
+            ("POST", ["jobs", id, "stop"]) => {
+                let Some(id) = job_id(id).filter(|id| self.jobs.lock().get(*id).is_some()) else {
+                    return error_response(404, "unknown job");
+                };
+                if self.jobs.lock().stop(id) {
+                    info!(job = id, "stopping job");
+                    json_response(202, &serde_json::json!({ "id": id }))
+                } else {
+                    error_response(409, "job is not running")
+                }
+            }
+            (_, ["health" | "metrics" | "jobs"] | ["jobs", _] | ["jobs", _, "stop"]) => {
+                error_response(405, "method not allowed")
+            }
+            _ => error_response(404, "not found"),
+        }
+    }
+
+    fn start_job(&self, body: &str) -> Response {
+        let mut request: JobRequest = match serde_json::from_str(body) {
+            Ok(request) => request,
+            Err(e) => return error_response(400, &format!("invalid job: {e}")),
+        };
+        if let (Some(dir), Some(config)) = (&self.config_dir, &request.config) {
+            match config_in_dir(dir, config) {
+                Ok(path) => request.config = Some(path),
+                Err(e) => return error_response(403, &e),
+            }
+        }
+        let cancel = CancellationToken::new();
+        let id = match self.jobs.lock().start(&request, cancel.clone()) {
+            Ok(id) => id,
+            Err(running) => return error_response(409, &format!("job {running} is running")),
+        };
+        info!(job = id, source = %strip_password(&request.source), target = %strip_password(&request.target), "starting job");
+
+        let jobs = self.jobs.clone();
+        let runner = Arc::clone(&self.runner);
+        tokio::task::spawn_blocking(move || {
+            let result = runner(request, cancel);
+            let mut jobs = jobs.lock();
+            jobs.finish(id, result);
+            if let Some(job) = jobs.get(id) {
+                match job.last_error {
+                    Some(ref e) => warn!(job = id, error = %e, "job failed"),
+                    None => info!(job = id, state = ?job.state, "job finished"),
+                }
+            }
+        });
+        json_response(201, &serde_json::json!({ "id": id }))
+    }
+
+    /// Serves the API on `addr` until Ctrl-C, a running job is stopped and awaited first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address cannot be bound.
+    pub async fn serve(self, addr: SocketAddr) -> Result<(), Box<dyn Error>> {
+        let listener = TcpListener::bind(addr).await?;
+        info!(%addr, "daemon listening");
+        let _subscription = progress::subscribe(Arc::new(self.jobs.clone()));
+        let daemon = Arc::new(self);
+
+        loop {
+            let (stream, peer) = tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok(conn) => conn,
+                    Err(e) => {
+                        warn!(error = %e, "daemon accept failed");
+                        continue;
+                    }
+                },
+                _ = tokio::signal::ctrl_c() => break,
+            };
+            let daemon = Arc::clone(&daemon);
+            tokio::spawn(async move {
+                if let Err(e) = daemon.respond(stream).await {
+                    debug!(%peer, error = %e, "daemon request failed");
+                }
+            });
+        }
+
+        let running: Vec<u64> = daemon.jobs.lock().stops.keys().copied().collect();
+        for id in running {
+            info!(job = id, "stopping job before shutdown");
+            daemon.jobs.lock().stop(id);
+        }
+        // the job ends after its running statement or chunks
+        while daemon.jobs.lock().running().is_some() {
+            tokio::time::sleep(JOB_POLL_INTERVAL).await;
+        }
+        Ok(())
+    }
+
+    /// reads one request from the connection and writes the response
+    async fn respond(&self, mut stream: TcpStream) -> std::io::Result<()> {
+        // a slow client must not keep the connection open
+        let deadline = Instant::now() + REQUEST_TIMEOUT;
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 4096];
+        let (header_end, content_length) = loop {
+            let read = read_until(&mut stream, &mut chunk, deadline).await?;
+            if read == 0 {
+                return Ok(());
+            }
+            buf.extend_from_slice(&chunk[..read]);
+            if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                let head = String::from_utf8_lossy(&buf[..end]);
+                break (
+                    end + 4,
+                    header(&head, "content-length")
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(0),
+                );
+            }
+            if buf.len() > MAX_REQUEST_BYTES {
+                return write_response(&mut stream, error_response(413, "request too large")).await;
+            }
+        };
+        let Some(request_end) = header_end
+            .checked_add(content_length)
+            .filter(|end| *end <= MAX_REQUEST_BYTES)
+        else {
+            return write_response(&mut stream, error_response(413, "request too large")).await;
+        };
+        while buf.len() < request_end {
+            let read = read_until(&mut stream, &mut chunk, deadline).await?;
+            if read == 0 {
+                break;
+            }
+            buf.extend_from_slice(&chunk[..read]);
+        }
+
+        let head = String::from_utf8_lossy(&buf[..header_end]).into_owned();
+        let body_end = buf.len().min(request_end);
+        let body = String::from_utf8_lossy(&buf[header_end..body_end]);
+        let mut request_line = head.lines().next().unwrap_or_default().split_whitespace();
+        let method = request_line.next().unwrap_or_default();
+        let path = request_line.next().unwrap_or("/");
+        let response = self.handle(method, path, header(&head, "authorization"), &body);
+        write_response(&mut stream, response).await
+    }
+}
+
+/// reads from the connection, a timeout error once `deadline` has passed
+async fn read_until(
+    stream: &mut TcpStream,
+    chunk: &mut [u8],
+    deadline: Instant,
+) -> std::io::Result<usize> {
+    tokio::time::timeout_at(deadline, stream.read(chunk))
+        .await
+        .map_err(|_| {
+            std::io::Error::new(std::io::ErrorKind::TimedOut, "request not read in time")
+        })?
+}
+
+/// compares the bearer token without stopping at the first differing byte, so the time
+/// of a wrong guess does not tell how much of it was right
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// resolves the config file of a job within `dir`, also through `..` and symlinks
+fn config_in_dir(dir: &Path, config: &Path) -> Result<PathBuf, String> {
+    let outside = || format!("config must be a file in {}", dir.display());
+    let dir = dir.canonicalize().map_err(|_| outside())?;
+    let path = dir.join(config).canonicalize().map_err(|_| outside())?;
+    if path.starts_with(&dir) {
+        Ok(path)
+    } else {
+        Err(outside())
+    }
+}
+
+/// value of a header, the name is case-insensitive
+fn header<'a>(head: &'a str, name: &str) -> Option<&'a str> {
+    head.lines().skip(1).find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+    })
+}
+
+async fn write_response(
+    stream: &mut TcpStream,
+    (status, content_type, body): Response,
+) -> std::io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        _ => "Internal Server Error",
+    };
+    let response = format!(
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn jobs_are_started_tracked_and_stopped() {
+        // the job runs until it is stopped
+        let runner: JobRunner = Arc::new(|_, cancel: CancellationToken| {
+            tokio::runtime::Handle::current().block_on(cancel.cancelled());
+            Err("cancelled".to_string())
+        });
+        let daemon = Daemon::new(runner, Some("secret".to_string()));
+        let auth = Some("Bearer secret");
+        let job = r#"{"source": "mysql://root:pw@db1/shop", "target": "postgres://app@db2/shop"}"#;
+
+        assert_eq!(daemon.handle("GET", "/jobs", None, "").0, 401);
+        assert_eq!(daemon.handle("POST", "/jobs", auth, job).0, 201);
+        assert_eq!(daemon.handle("POST", "/jobs", auth, job).0, 409);
+        assert_eq!(daemon.handle("POST", "/jobs", auth, "{}").0, 400);
+
+        daemon.jobs.on_event(&ProgressEvent::TableStart {
+            table: "users".to_string(),
+            rows_total: 10,
+        });
+        daemon.jobs.on_event(&ProgressEvent::ChunkDone {
+            table: "users".to_string(),
+            rows: 4,
+        });
+        let (status, _, body) = daemon.handle("GET", "/jobs/1", auth, "");
+        assert_eq!(status, 200);
+        let job: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(job["state"], "running");
+        assert_eq!(job["source"], "mysql://root@db1/shop");
+        assert_eq!(job["current_table"], "users");
+        assert_eq!(job["tables"]["users"]["rows_written"], 4);
+
+        assert_eq!(daemon.handle("POST", "/jobs/1/stop", auth, "").0, 202);
+        for _ in 0..100 {
+            if daemon.jobs.lock().get(1).unwrap().state != JobState::Running {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(daemon.jobs.lock().get(1).unwrap().state, JobState::Stopped);
+        assert_eq!(daemon.handle("POST", "/jobs/1/stop", auth, "").0, 409);
+        assert_eq!(daemon.handle("GET", "/jobs/2", auth, "").0, 404);
+        assert_eq!(daemon.handle("DELETE", "/jobs/1", auth, "").0, 405);
+    }
+
+    #[test]
+    fn job_configs_stay_in_the_config_directory() {
+        let dir = std::env::temp_dir().join(format!("fluxforge-daemon-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("mapping.toml"), "").unwrap();
+        let runner: JobRunner = Arc::new(|_, _| Ok(()));
+        let daemon = Daemon::new(runner, None).config_dir(Some(dir.clone()));
+
+        for config in ["../mapping.toml", "/etc/passwd", "missing.toml"] {
+            let job = serde_json::json!({ "source": "a", "target": "b", "config": config });
+            let (status, _, body) = daemon.handle("POST", "/jobs", None, &job.to_string());
+            assert_eq!(status, 403, "{config}: {body}");
+        }
+        assert_eq!(
+            config_in_dir(&dir, Path::new("mapping.toml")).unwrap(),
+            dir.canonicalize().unwrap().join("mapping.toml")
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn bearer_tokens_are_compared_whole() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+        assert!(!constant_time_eq(b"", b"secret"));
+    }
+
+    /// sends `request` to a served daemon and returns the response
+    async fn exchange(request: &[u8]) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let runner: JobRunner = Arc::new(|_, _| Ok(()));
+        let daemon = Daemon::new(runner, None);
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            daemon.respond(stream).await
+        });
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        client.write_all(request).await.unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).await.unwrap();
+        server.await.unwrap().unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn oversized_content_lengths_are_refused() {
+        let request = format!(
+            "POST /jobs HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            usize::MAX
+        );
+        let response = exchange(request.as_bytes()).await;
+        assert!(response.starts_with("HTTP/1.1 413 "), "{response}");
+
+        let response = exchange(b"GET /health HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 200 "), "{response}");
+    }
+}
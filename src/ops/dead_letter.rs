@@ -0,0 +1,193 @@
+//! Dead-letter file of rows the target rejected, and their replay.
+//!
+//! Every failed row is appended as one `ForgeUniversalDataTransferPacket` JSON line, the
+//! same format as `replicate --dump`. `fluxforge replay` inserts the rows again after the
+//! cause (i.e. a too short column or a missing referenced row) has been fixed.
+
+use crate::{DatabaseDriver, ForgeUniversalDataField, ForgeUniversalDataTransferPacket};
+use indexmap::IndexMap;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// dead-letter file in the current directory, next to `migration_errors.log`
+pub const DEAD_LETTER_FILE: &str = "migration_dead_letter.ndjson";
+
+/// rows per insert during the replay, the size of a replication chunk
+const REPLAY_CHUNK_SIZE: usize = 1000;
+
+/// appends a packet as one JSON line
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be written.
+pub fn append_packet(
+    path: &Path,
+    packet: &ForgeUniversalDataTransferPacket,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Error opening dead-letter file {path:?}: {e}"))?;
+    let mut line = serde_json::to_vec(packet)?;
+    line.push(b'\n');
+    file.write_all(&line)?;
+    Ok(())
+}
+
+/// Records a row the target rejected in [`DEAD_LETTER_FILE`].
+///
+/// Failures are only logged, the replication continues like for `log_error_to_file`.
+pub fn write_dead_letter(table: &str, row: &IndexMap<String, ForgeUniversalDataField>) {
+    let packet = ForgeUniversalDataTransferPacket {
+        t: table.to_string(),
+        r: row.clone(),
+    };
+    if let Err(e) = append_packet(Path::new(DEAD_LETTER_FILE), &packet) {
+        tracing::error!(table, error = %e, "row could not be written to the dead-letter file");
+    }
+}
+
+/// reads all packets of a dead-letter (or dump) file in file order
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or a line is no packet.
+pub fn read_packets(
+    path: &Path,
+) -> Result<Vec<ForgeUniversalDataTransferPacket>, Box<dyn std::error::Error>> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| format!("Error opening dead-letter file {path:?}: {e}"))?;
+
+    let mut packets = Vec::new();
+    for (number, line) in std::io::BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let packet = serde_json::from_str(&line).map_err(|e| {
+            format!(
+                "Error parsing dead-letter file {path:?} line {}: {e}",
+                number + 1
+            )
+        })?;
+        packets.push(packet);
+    }
+    Ok(packets)
+}
+
+/// Consecutive rows of the same table, at most `REPLAY_CHUNK_SIZE` per chunk.
+///
+/// The file order is kept, so parent rows are inserted before the rows referencing them.
+#[must_use]
+pub fn replay_chunks(
+    packets: Vec<ForgeUniversalDataTransferPacket>,
+) -> Vec<(String, Vec<IndexMap<String, ForgeUniversalDataField>>)> {
+    let mut chunks: Vec<(String, Vec<IndexMap<String, ForgeUniversalDataField>>)> = Vec::new();
+    for packet in packets {
+        match chunks.last_mut() {
+            Some((table, rows)) if *table == packet.t && rows.len() < REPLAY_CHUNK_SIZE => {
+                rows.push(packet.r);
+            }
+            _ => chunks.push((packet.t, vec![packet.r])),
+        }
+    }
+    chunks
+}
+
+/// Inserts the rows of a dead-letter file into the target.
+///
+/// The file is renamed to `<file>.replayed` first, so rows failing again are recorded in
+/// a fresh dead-letter file and an aborted replay can be repeated with the renamed file.
+/// Returns the number of replayed rows.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or renamed, or if an insert fails completely.
+pub async fn replay(
+    target: &dyn DatabaseDriver,
+    path: &Path,
+    dry_run: bool,
+) -> Result<u64, Box<dyn std::error::Error>> {
+    let packets = read_packets(path)?;
+    if !dry_run {
+        let mut replayed = path.as_os_str().to_owned();
+        replayed.push(".replayed");
+        let replayed = PathBuf::from(replayed);
+        std::fs::rename(path, &replayed)
+            .map_err(|e| format!("Error renaming dead-letter file {path:?}: {e}"))?;
+        info!(file = ?replayed, "dead-letter file renamed for the replay");
+    }
+
+    let mut total_rows = 0u64;
+    for (table, rows) in replay_chunks(packets) {
+        let chunk_rows = rows.len() as u64;
+        info!(table = %table, rows = chunk_rows, "replaying rows");
+        target.insert_chunk(&table, dry_run, false, rows).await?;
+        total_rows += chunk_rows;
+    }
+    Ok(total_rows)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    fn packet(table: &str, id: i64) -> ForgeUniversalDataTransferPacket {
+        let mut r = IndexMap::new();
+        r.insert("id".to_string(), ForgeUniversalDataField::Integer(id));
+        r.insert(
+            "created".to_string(),
+            ForgeUniversalDataField::DateTime(
+                chrono::NaiveDate::from_ymd_opt(2024, 2, 29)
+                    .unwrap()
+                    .and_hms_opt(12, 0, 0)
+                    .unwrap(),
+            ),
+        );
+        ForgeUniversalDataTransferPacket {
+            t: table.to_string(),
+            r,
+        }
+    }
+
+    #[test]
+    fn packets_roundtrip_in_file_order() {
+        let path = std::env::temp_dir().join(format!(
+            "fluxforge_dead_letter_{}.ndjson",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let written = vec![packet("users", 1), packet("orders", 7), packet("users", 2)];
+        for p in &written {
+            append_packet(&path, p).unwrap();
+        }
+        let read = read_packets(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(read, written);
+    }
+
+    #[test]
+    fn replay_chunks_keeps_file_order() {
+        let mut packets = vec![packet("users", 1), packet("users", 2), packet("orders", 1)];
+        packets.extend((0..1001).map(|id| packet("users", id)));
+
+        let chunks: Vec<(String, usize)> = replay_chunks(packets)
+            .into_iter()
+            .map(|(table, rows)| (table, rows.len()))
+            .collect();
+        assert_eq!(
+            chunks,
+            vec![
+                ("users".to_string(), 2),
+                ("orders".to_string(), 1),
+                ("users".to_string(), 1000),
+                ("users".to_string(), 1),
+            ]
+        );
+    }
+}
@@ -0,0 +1,166 @@
+//! Pre-flight checks of `fluxforge doctor`.
+//!
+//! Connectivity, privileges, server versions and settings of both databases, the emptiness
+//! of the target and the config are checked before a long migration is attempted.
+
+use crate::ForgeConfig;
+use crate::core::{ForgeCheckStatus, ForgePreflightCheck};
+use crate::drivers;
+use crate::ops::checkpoint::strip_password;
+use serde::Serialize;
+use std::fmt::Write as _;
+
+/// Checks of one database (or the config) under a title like "Source mysql://app@db1/shop".
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DoctorSection {
+    pub title: String,
+    pub checks: Vec<ForgePreflightCheck>,
+}
+
+/// check of the source/target combination, `replicate` refuses the others
+#[must_use]
+pub fn combination_check(source: &str, target: &str) -> ForgePreflightCheck {
+    let source_type = drivers::database_system(source);
+    let target_type = drivers::database_system(target);
+    let combination = format!("{source_type} -> {target_type}");
+    if drivers::is_supported_combination(source_type, target_type) {
+        ForgePreflightCheck::pass("combination", combination)
+    } else {
+        ForgePreflightCheck::fail("combination", format!("{combination} is not supported"))
+    }
+}
+
+/// Connects to the database and runs its checks, a failed connection ends the section.
+pub async fn check_database(url: &str, config: &ForgeConfig, as_target: bool) -> DoctorSection {
+    let side = if as_target { "Target" } else { "Source" };
+    let mut section = DoctorSection {
+        title: format!("{side} {}", strip_password(url)),
+        checks: Vec::new(),
+    };
+
+    let driver = match drivers::create_driver(url, config, !as_target).await {
+        Ok(driver) => driver,
+        Err(e) => {
+            section
+                .checks
+                .push(ForgePreflightCheck::fail("connection", e.to_string()));
+            return section;
+        }
+    };
+    section
+        .checks
+        .push(ForgePreflightCheck::pass("connection", "connected"));
+
+    match driver.preflight_checks(as_target).await {
+        Ok(checks) => section.checks.extend(checks),
+        Err(e) => section
+            .checks
+            .push(ForgePreflightCheck::fail("server checks", e.to_string())),
+    }
+
+    if as_target {
+        section.checks.push(match driver.db_is_empty().await {
+            Ok(true) => ForgePreflightCheck::pass("empty target", "no tables"),
+            Ok(false) => ForgePreflightCheck::fail(
+                "empty target",
+                "the target has tables, replicate only writes into an empty database",
+            ),
+            Err(e) => ForgePreflightCheck::fail("empty target", e.to_string()),
+        });
+    }
+    section
+}
+
+/// number of failed checks of all sections
+#[must_use]
+pub fn failed_checks(sections: &[DoctorSection]) -> usize {
+    sections
+        .iter()
+        .flat_map(|s| &s.checks)
+        .filter(|c| c.status == ForgeCheckStatus::Fail)
+        .count()
+}
+
+/// pass/warn/fail report of all sections with a summary line
+#[must_use]
+pub fn doctor_report(sections: &[DoctorSection]) -> String {
+    let mut out = String::new();
+    let (mut passed, mut warnings) = (0, 0);
+    for section in sections {
+        let _ = writeln!(out, "{}", section.title);
+        for check in &section.checks {
+            let status = match check.status {
+                ForgeCheckStatus::Pass => {
+                    passed += 1;
+                    "PASS"
+                }
+                ForgeCheckStatus::Warn => {
+                    warnings += 1;
+                    "WARN"
+                }
+                ForgeCheckStatus::Fail => "FAIL",
+            };
+            let _ = writeln!(out, "  [{status}] {}: {}", check.name, check.detail);
+        }
+    }
+    let _ = writeln!(
+        out,
+        "Doctor: {passed} passed, {warnings} warning(s), {} failed",
+        failed_checks(sections)
+    );
+    out
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combination_check_follows_replicate() {
+        assert_eq!(
+            combination_check("mysql://db1/shop", "postgres://db2/shop").status,
+            ForgeCheckStatus::Pass
+        );
+        assert_eq!(
+            combination_check("postgres://db1/shop", "mysql://db2/shop").status,
+            ForgeCheckStatus::Pass
+        );
+        let check = combination_check("sqlite://shop.db", "mysql://db2/shop");
+        assert_eq!(check.status, ForgeCheckStatus::Fail);
+        assert_eq!(check.detail, "unknown -> mysql is not supported");
+    }
+
+    #[test]
+    fn report_counts_all_statuses() {
+        let sections = vec![
+            DoctorSection {
+                title: "Config mapping.toml".to_string(),
+                checks: vec![ForgePreflightCheck::pass("config", "valid")],
+            },
+            DoctorSection {
+                title: "Target postgres://app@db2/shop".to_string(),
+                checks: vec![
+                    ForgePreflightCheck::warn("statement_timeout", "30s"),
+                    ForgePreflightCheck::fail("empty target", "the target has tables"),
+                ],
+            },
+        ];
+        assert_eq!(failed_checks(&sections), 1);
+        assert_eq!(
+            doctor_report(&sections),
+            "Config mapping.toml\n  [PASS] config: valid\n\
+             Target postgres://app@db2/shop\n  [WARN] statement_timeout: 30s\n  \
+             [FAIL] empty target: the target has tables\n\
+             Doctor: 1 passed, 1 warning(s), 1 failed\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn unreachable_database_fails_the_connection_check() {
+        let section = check_database("oracle://db/shop", &ForgeConfig::default(), true).await;
+        assert_eq!(section.checks.len(), 1);
+        assert_eq!(section.checks[0].name, "connection");
+        assert_eq!(section.checks[0].status, ForgeCheckStatus::Fail);
+    }
+}
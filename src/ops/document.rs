@@ -0,0 +1,447 @@
+//! Schema documentation (`fluxforge document`).
+//!
+//! A schema is rendered into an index page and one page per table with its columns,
+//! indices, foreign keys and comments, as Markdown or HTML. Both formats render the same
+//! sections, so the pages of a schema look alike in a repository and in a browser.
+
+use crate::core::{ForgeDocFormat, ForgeSchemaColumn};
+use crate::ops::storage;
+use crate::{ForgeSchema, ForgeSchemaTable};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// One page of the documentation, `file` is relative to the output directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocPage {
+    pub file: String,
+    pub content: String,
+}
+
+/// heading with a table of cells, cells hold plain text
+struct Section {
+    title: &'static str,
+    headers: &'static [&'static str],
+    rows: Vec<Vec<String>>,
+}
+
+/// file name of a table page, characters unsafe in paths are replaced
+fn page_name(table: &str, format: ForgeDocFormat) -> String {
+    let stem: String = table
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{stem}.{}", format.extension())
+}
+
+/// type of the column with its parameters, i.e. "varchar(255)" or "int unsigned"
+pub(crate) fn column_type(column: &ForgeSchemaColumn) -> String {
+    let mut label = column.data_type.clone();
+    match (column.precision, column.scale, column.length) {
+        (Some(p), Some(s), _) => {
+            let _ = write!(label, "({p},{s})");
+        }
+        (Some(p), None, _) => {
+            let _ = write!(label, "({p})");
+        }
+        (None, _, Some(l)) => {
+            let _ = write!(label, "({l})");
+        }
+        _ => {}
+    }
+    if let Some(ref values) = column.enum_values {
+        let _ = write!(label, "({})", values.join(", "));
+    }
+    if column.is_unsigned {
+        label.push_str(" unsigned");
+    }
+    label
+}
+
+/// key, auto increment, generation, visibility and domain of a column
+pub(crate) fn column_attributes(column: &ForgeSchemaColumn) -> String {
+    let mut attributes = Vec::new();
+    if column.is_primary_key {
+        attributes.push("primary key".to_string());
+    }
+    if column.auto_increment {
+        attributes.push("auto increment".to_string());
+    }
+    if let Some(ref expression) = column.generation_expression {
+        let kind = if column.is_generated_stored {
+            "stored"
+        } else {
+            "virtual"
+        };
+        attributes.push(format!("generated {kind}: {expression}"));
+    }
+    if column.is_invisible {
+        attributes.push("invisible".to_string());
+    }
+    if let Some(ref on_update) = column.on_update {
+        attributes.push(format!("on update {on_update}"));
+    }
+    if let Some(ref domain) = column.domain {
+        attributes.push(format!("domain {domain}"));
+    }
+    attributes.join(", ")
+}
+
+/// sections of a table page, empty sections are left out
+fn table_sections(schema: &ForgeSchema, table: &ForgeSchemaTable) -> Vec<Section> {
+    let columns = Section {
+        title: "Columns",
+        headers: &[
+            "Column",
+            "Type",
+            "Nullable",
+            "Default",
+            "Attributes",
+            "Comment",
+        ],
+        rows: table
+            .columns
+            .iter()
+            .map(|c| {
+                vec![
+                    c.name.clone(),
+                    column_type(c),
+                    if c.is_nullable { "yes" } else { "no" }.to_string(),
+                    c.default.clone().unwrap_or_default(),
+                    column_attributes(c),
+                    c.comment.clone().unwrap_or_default(),
+                ]
+            })
+            .collect(),
+    };
+    let indices = Section {
+        title: "Indices",
+        headers: &["Index", "Columns", "Unique", "Type"],
+        rows: table
+            .indices
+            .iter()
+            .map(|index| {
+                let parts = index
+                    .columns
+                    .iter()
+                    .enumerate()
+                    .map(|(i, column)| index.expression(i).unwrap_or(column).to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                vec![
+                    index.name.clone(),
+                    parts,
+                    if index.is_unique { "yes" } else { "no" }.to_string(),
+                    index.index_type.clone().unwrap_or_default(),
+                ]
+            })
+            .collect(),
+    };
+    let foreign_keys = Section {
+        title: "Foreign Keys",
+        headers: &[
+            "Foreign Key",
+            "Column",
+            "References",
+            "On Delete",
+            "On Update",
+        ],
+        rows: table
+            .foreign_keys
+            .iter()
+            .map(|fk| {
+                vec![
+                    fk.name.clone(),
+                    fk.column.clone(),
+                    format!("{}.{}", fk.ref_table, fk.ref_column),
+                    fk.on_delete.clone().unwrap_or_default(),
+                    fk.on_update.clone().unwrap_or_default(),
+                ]
+            })
+            .collect(),
+    };
+    let referenced_by = Section {
+        title: "Referenced By",
+        headers: &["Table", "Column", "Foreign Key"],
+        rows: schema
+            .tables
+            .iter()
+            .flat_map(|other| {
+                other
+                    .foreign_keys
+                    .iter()
+                    .filter(|fk| fk.ref_table == table.name)
+                    .map(|fk| vec![other.name.clone(), fk.column.clone(), fk.name.clone()])
+            })
+            .collect(),
+    };
+    [columns, indices, foreign_keys, referenced_by]
+        .into_iter()
+        .filter(|s| !s.rows.is_empty())
+        .collect()
+}
+
+/// text of a Markdown table cell
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+/// text escaped for HTML
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Markdown page of a heading, an optional paragraph and the sections
+fn markdown_page(title: &str, intro: Option<&str>, sections: &[Section]) -> String {
+    let mut out = format!("# {title}\n");
+    if let Some(intro) = intro {
+        let _ = write!(out, "\n{intro}\n");
+    }
+    for section in sections {
+        let _ = write!(out, "\n## {}\n\n", section.title);
+        let _ = writeln!(out, "| {} |", section.headers.join(" | "));
+        let _ = writeln!(out, "|{}", "---|".repeat(section.headers.len()));
+        for row in &section.rows {
+            let cells: Vec<String> = row.iter().map(|c| markdown_cell(c)).collect();
+            let _ = writeln!(out, "| {} |", cells.join(" | "));
+        }
+    }
+    out
+}
+
+/// HTML page of a heading, an optional paragraph and the sections
+fn html_page(title: &str, intro: Option<&str>, sections: &[Section]) -> String {
+    let title = html_escape(title);
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+         </head>\n<body>\n<h1>{title}</h1>\n"
+    );
+    if let Some(intro) = intro {
+        let _ = writeln!(out, "<p>{intro}</p>");
+    }
+    for section in sections {
+        let _ = writeln!(out, "<h2>{}</h2>\n<table>", section.title);
+        let headers: String = section
+            .headers
+            .iter()
+            .map(|h| format!("<th>{h}</th>"))
+            .collect();
+        let _ = writeln!(out, "<tr>{headers}</tr>");
+        for row in &section.rows {
+            let cells: String = row
+                .iter()
+                .map(|c| format!("<td>{}</td>", html_escape(c)))
+                .collect();
+            let _ = writeln!(out, "<tr>{cells}</tr>");
+        }
+        out.push_str("</table>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Renders the index page and one page per table.
+#[must_use]
+pub fn render(schema: &ForgeSchema, format: ForgeDocFormat) -> Vec<DocPage> {
+    let page = |title: &str, intro: Option<&str>, sections: &[Section]| match format {
+        ForgeDocFormat::Markdown => markdown_page(title, intro, sections),
+        ForgeDocFormat::Html => html_page(title, intro, sections),
+    };
+
+    let mut tables: Vec<&ForgeSchemaTable> = schema.tables.iter().collect();
+    tables.sort_by(|a, b| a.name.cmp(&b.name));
+
+    // the index links every table, cells of links are written in the syntax of the format
+    let link = |table: &ForgeSchemaTable| {
+        let file = page_name(&table.name, format);
+        match format {
+            ForgeDocFormat::Markdown => format!("[{}]({file})", markdown_cell(&table.name)),
+            ForgeDocFormat::Html => {
+                format!("<a href=\"{file}\">{}</a>", html_escape(&table.name))
+            }
+        }
+    };
+    let index_rows: Vec<(String, Vec<String>)> = tables
+        .iter()
+        .map(|t| {
+            (
+                link(t),
+                vec![
+                    t.columns.len().to_string(),
+                    t.comment.clone().unwrap_or_default(),
+                ],
+            )
+        })
+        .collect();
+    let metadata = &schema.metadata;
+    let intro = format!(
+        "Database {} ({} {}), extracted {}.",
+        metadata.source_database_name,
+        metadata.source_system,
+        metadata.source_server_version,
+        metadata.created_at
+    );
+    let index = match format {
+        ForgeDocFormat::Markdown => {
+            let mut out = markdown_page("Schema", Some(&intro), &[]);
+            out.push_str("\n| Table | Columns | Comment |\n|---|---|---|\n");
+            for (link, cells) in &index_rows {
+                let _ = writeln!(
+                    out,
+                    "| {link} | {} | {} |",
+                    cells[0],
+                    markdown_cell(&cells[1])
+                );
+            }
+            out
+        }
+        ForgeDocFormat::Html => {
+            let rows: String = index_rows
+                .iter()
+                .map(|(link, cells)| {
+                    format!(
+                        "<tr><td>{link}</td><td>{}</td><td>{}</td></tr>\n",
+                        cells[0],
+                        html_escape(&cells[1])
+                    )
+                })
+                .collect();
+            html_page("Schema", Some(&html_escape(&intro)), &[]).replace(
+                "</body>",
+                &format!(
+                    "<table>\n<tr><th>Table</th><th>Columns</th><th>Comment</th></tr>\n{rows}</table>\n</body>"
+                ),
+            )
+        }
+    };
+
+    let mut pages = vec![DocPage {
+        file: format!("index.{}", format.extension()),
+        content: index,
+    }];
+    for table in tables {
+        let comment = table.comment.as_deref().map(|c| match format {
+            ForgeDocFormat::Markdown => c.to_string(),
+            ForgeDocFormat::Html => html_escape(c),
+        });
+        pages.push(DocPage {
+            file: page_name(&table.name, format),
+            content: page(
+                &table.name,
+                comment.as_deref(),
+                &table_sections(schema, table),
+            ),
+        });
+    }
+    pages
+}
+
+/// Writes the documentation of the schema into the directory, returns the written files.
+///
+/// # Errors
+///
+/// Returns an error if the directory or a page cannot be written.
+pub async fn write_docs(
+    schema: &ForgeSchema,
+    format: ForgeDocFormat,
+    out: &Path,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    storage::create_dir_all(out).await?;
+    let mut written = Vec::new();
+    for page in render(schema, format) {
+        let path = out.join(&page.file);
+        storage::write(&path, page.content.as_bytes()).await?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::core::{ForgeSchemaForeignKey, ForgeSchemaIndex};
+
+    fn shop_schema() -> ForgeSchema {
+        let mut schema = ForgeSchema::new();
+        schema.metadata.source_database_name = "shop".to_string();
+        schema.metadata.source_system = "mysql".to_string();
+
+        let mut users = ForgeSchemaTable::new("users");
+        let mut id = ForgeSchemaColumn::new("id", "int");
+        id.is_primary_key = true;
+        id.auto_increment = true;
+        id.is_unsigned = true;
+        users.columns.push(id);
+        let mut email = ForgeSchemaColumn::new("email", "varchar");
+        email.length = Some(255);
+        email.is_nullable = true;
+        email.comment = Some("login | contact".to_string());
+        users.columns.push(email);
+        users.indices.push(ForgeSchemaIndex {
+            name: "uq_email".to_string(),
+            columns: vec!["email".to_string()],
+            is_unique: true,
+            ..Default::default()
+        });
+
+        let mut orders = ForgeSchemaTable::new("orders");
+        orders.comment = Some("Orders <all>".to_string());
+        orders
+            .columns
+            .push(ForgeSchemaColumn::new("user_id", "int"));
+        orders.foreign_keys.push(ForgeSchemaForeignKey {
+            name: "fk_user".to_string(),
+            column: "user_id".to_string(),
+            ref_table: "users".to_string(),
+            ref_column: "id".to_string(),
+            on_delete: Some("CASCADE".to_string()),
+            ..Default::default()
+        });
+        schema.tables.push(users);
+        schema.tables.push(orders);
+        schema
+    }
+
+    #[test]
+    fn markdown_pages_list_columns_indices_and_references() {
+        let pages = render(&shop_schema(), ForgeDocFormat::Markdown);
+        let files: Vec<&str> = pages.iter().map(|p| p.file.as_str()).collect();
+        assert_eq!(files, vec!["index.md", "orders.md", "users.md"]);
+        assert!(
+            pages[0]
+                .content
+                .contains("| [orders](orders.md) | 1 | Orders <all> |")
+        );
+
+        let users = &pages[2].content;
+        assert!(users.contains("| id | int unsigned | no |  | primary key, auto increment |  |"));
+        assert!(users.contains("| email | varchar(255) | yes |  |  | login \\| contact |"));
+        assert!(users.contains("## Indices"));
+        assert!(users.contains("## Referenced By\n\n| Table | Column | Foreign Key |\n|---|---|---|\n| orders | user_id | fk_user |"));
+        assert!(!users.contains("## Foreign Keys"));
+    }
+
+    #[test]
+    fn html_pages_are_escaped() {
+        let pages = render(&shop_schema(), ForgeDocFormat::Html);
+        assert_eq!(pages[1].file, "orders.html");
+        assert!(
+            pages[0]
+                .content
+                .contains("<a href=\"orders.html\">orders</a>")
+        );
+        assert!(pages[1].content.contains("<p>Orders &lt;all&gt;</p>"));
+        assert!(pages[1].content.contains(
+            "<tr><td>fk_user</td><td>user_id</td><td>users.id</td><td>CASCADE</td><td></td></tr>"
+        ));
+    }
+}
@@ -0,0 +1,267 @@
+//! Log of rows the target rejected, as text or JSON lines, with size based rotation.
+//!
+//! The drivers write into a process wide log, configured once per command from
+//! `[general.error_log]` with [`configure`]. Write failures are logged as warnings,
+//! a full disk must not abort a replication that can still write its data.
+
+use crate::ForgeConfig;
+use crate::core::ForgeErrorLogFormat;
+use serde::Serialize;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex};
+use tracing::warn;
+
+/// log in the current directory if nothing is configured
+pub const DEFAULT_ERROR_LOG: &str = "migration_errors.log";
+
+/// one JSON line of the log
+#[derive(Debug, Serialize)]
+struct JsonEntry<'a> {
+    time: String,
+    table: &'a str,
+    error: &'a str,
+    row: &'a serde_json::Value,
+}
+
+/// Error log with its format and rotation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorLog {
+    pub path: PathBuf,
+    pub format: ForgeErrorLogFormat,
+    /// rotate when a write would grow the log beyond this size, `None` never rotates
+    pub max_bytes: Option<u64>,
+    /// rotated logs kept as `<path>.1` (newest) to `<path>.<max_files>`
+    pub max_files: usize,
+}
+
+impl Default for ErrorLog {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from(DEFAULT_ERROR_LOG),
+            format: ForgeErrorLogFormat::Text,
+            max_bytes: None,
+            max_files: 5,
+        }
+    }
+}
+
+impl ErrorLog {
+    /// log of `[general.error_log]`, unset options use the defaults
+    #[must_use]
+    pub fn from_config(config: &ForgeConfig) -> Self {
+        let defaults = Self::default();
+        let Some(log) = config.general.as_ref().and_then(|g| g.error_log.as_ref()) else {
+            return defaults;
+        };
+        Self {
+            path: log.path.clone().unwrap_or(defaults.path),
+            format: log.format.unwrap_or(defaults.format),
+            max_bytes: log.max_size_mb.map(|mb| mb.saturating_mul(1024 * 1024)),
+            max_files: log.max_files.unwrap_or(defaults.max_files).max(1),
+        }
+    }
+
+    /// path of the rotated log number `n`
+    fn rotated_path(&self, n: usize) -> PathBuf {
+        let mut path = self.path.as_os_str().to_owned();
+        path.push(format!(".{n}"));
+        PathBuf::from(path)
+    }
+
+    /// shifts `<path>` to `<path>.1`, `<path>.1` to `<path>.2` and so on, the oldest is dropped
+    fn rotate(&self) -> std::io::Result<()> {
+        let oldest = self.rotated_path(self.max_files);
+        if oldest.exists() {
+            std::fs::remove_file(oldest)?;
+        }
+        for n in (1..self.max_files).rev() {
+            let from = self.rotated_path(n);
+            if from.exists() {
+                std::fs::rename(from, self.rotated_path(n + 1))?;
+            }
+        }
+        std::fs::rename(&self.path, self.rotated_path(1))
+    }
+
+    /// the line written for a rejected row, `row` is a JSON value or the row as text
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the JSON line cannot be serialized.
+    pub fn format_line(
+        &self,
+        table: &str,
+        error: &str,
+        row: &serde_json::Value,
+    ) -> Result<String, serde_json::Error> {
+        match self.format {
+            ForgeErrorLogFormat::Text => {
+                let data = match row {
+                    serde_json::Value::String(text) => format!("{text:?}"),
+                    other => other.to_string(),
+                };
+                Ok(format!("TABLE: {table} | ERROR: {error} | DATA: {data}\n"))
+            }
+            ForgeErrorLogFormat::Json => {
+                let entry = JsonEntry {
+                    time: chrono::Local::now().to_rfc3339(),
+                    table,
+                    error,
+                    row,
+                };
+                let mut line = serde_json::to_string(&entry)?;
+                line.push('\n');
+                Ok(line)
+            }
+        }
+    }
+
+    /// appends the line of a rejected row, rotates the log first if it would become too large
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the log cannot be rotated or written.
+    pub fn write(
+        &self,
+        table: &str,
+        error: &str,
+        row: &serde_json::Value,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let line = self.format_line(table, error, row)?;
+
+        if let Some(max_bytes) = self.max_bytes {
+            let size = std::fs::metadata(&self.path).map_or(0, |m| m.len());
+            if size > 0 && size + line.len() as u64 > max_bytes {
+                self.rotate()
+                    .map_err(|e| format!("Error rotating error log {:?}: {e}", self.path))?;
+            }
+        }
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Error opening error log {:?}: {e}", self.path))?;
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// the mutex also serializes the concurrent insert workers, so rotation and lines do not interleave
+static ERROR_LOG: LazyLock<Mutex<ErrorLog>> = LazyLock::new(|| Mutex::new(ErrorLog::default()));
+
+/// sets the process wide error log from `[general.error_log]`
+pub fn configure(config: &ForgeConfig) {
+    let log = ErrorLog::from_config(config);
+    match ERROR_LOG.lock() {
+        Ok(mut current) => *current = log,
+        Err(poisoned) => *poisoned.into_inner() = log,
+    }
+}
+
+/// writes a rejected row to the process wide error log, failures are logged as warnings
+pub fn record(table: &str, error: &str, row: &serde_json::Value) {
+    let result = match ERROR_LOG.lock() {
+        Ok(log) => log.write(table, error, row),
+        Err(poisoned) => poisoned.into_inner().write(table, error, row),
+    };
+    if let Err(e) = result {
+        warn!(table, error = %e, "rejected row could not be written to the error log");
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    fn temp_log(name: &str) -> ErrorLog {
+        let dir = std::env::temp_dir().join(format!("fluxforge_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        ErrorLog {
+            path: dir.join("errors.log"),
+            ..ErrorLog::default()
+        }
+    }
+
+    #[test]
+    fn from_config_reads_general_error_log() {
+        let config: ForgeConfig = toml::from_str(
+            "[general.error_log]\npath = \"/var/log/ff.jsonl\"\nformat = \"json\"\nmax_size_mb = 10\nmax_files = 0",
+        )
+        .unwrap();
+        let log = ErrorLog::from_config(&config);
+        assert_eq!(log.path, PathBuf::from("/var/log/ff.jsonl"));
+        assert_eq!(log.format, ForgeErrorLogFormat::Json);
+        assert_eq!(log.max_bytes, Some(10 * 1024 * 1024));
+        assert_eq!(log.max_files, 1);
+
+        assert_eq!(
+            ErrorLog::from_config(&ForgeConfig::default()),
+            ErrorLog::default()
+        );
+    }
+
+    #[test]
+    fn formats_text_and_json_lines() {
+        let mut log = ErrorLog::default();
+        let row = serde_json::json!({"id": {"Integer": 1}});
+        assert_eq!(
+            log.format_line("users", "duplicate key", &row).unwrap(),
+            "TABLE: users | ERROR: duplicate key | DATA: {\"id\":{\"Integer\":1}}\n"
+        );
+        assert_eq!(
+            log.format_line("users", "boom", &serde_json::json!("id: 1"))
+                .unwrap(),
+            "TABLE: users | ERROR: boom | DATA: \"id: 1\"\n"
+        );
+
+        log.format = ForgeErrorLogFormat::Json;
+        let line = log.format_line("users", "duplicate key", &row).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed["table"], "users");
+        assert_eq!(parsed["error"], "duplicate key");
+        assert_eq!(parsed["row"], row);
+        assert!(parsed["time"].is_string());
+    }
+
+    #[test]
+    fn rotates_when_the_log_grows_too_large() {
+        let log = ErrorLog {
+            max_bytes: Some(100),
+            max_files: 2,
+            ..temp_log("error_log_rotation")
+        };
+        let row = serde_json::json!("x".repeat(40));
+        for _ in 0..5 {
+            log.write("users", "boom", &row).unwrap();
+        }
+
+        let dir = log.path.parent().unwrap();
+        let mut files: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        assert_eq!(files, vec!["errors.log", "errors.log.1", "errors.log.2"]);
+        for file in &files {
+            let content = std::fs::read_to_string(dir.join(file)).unwrap();
+            assert_eq!(content.lines().count(), 1);
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn write_failures_are_errors() {
+        let log = ErrorLog {
+            path: PathBuf::from("/nonexistent/dir/errors.log"),
+            ..ErrorLog::default()
+        };
+        assert!(
+            log.write("users", "boom", &serde_json::json!(null))
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,280 @@
+//! Size estimation and predicted duration before a replication.
+//!
+//! Row counts and sizes come from the catalog statistics of the source, the duration is
+//! predicted from a short read of the largest table. The prediction measures the source
+//! only, a slow target makes the replication take longer.
+
+use crate::core::ForgeTableEstimate;
+use crate::ops::metrics;
+use crate::{DatabaseDriver, ForgeSchema};
+use futures::StreamExt;
+use std::fmt::Write as _;
+use std::time::{Duration, Instant};
+
+/// rows read by the throughput probe at most
+pub const PROBE_ROWS: u64 = 10_000;
+
+/// time the throughput probe reads at most
+pub const PROBE_TIME: Duration = Duration::from_secs(2);
+
+/// Rows read from a table in the elapsed time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThroughputProbe {
+    pub table_name: String,
+    pub rows: u64,
+    /// payload size of the read rows
+    pub bytes: u64,
+    pub elapsed: Duration,
+}
+
+/// Sizes of the tables of a replication with the throughput probe of the source.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Estimate {
+    pub tables: Vec<ForgeTableEstimate>,
+    pub probe: Option<ThroughputProbe>,
+}
+
+impl Estimate {
+    #[must_use]
+    pub fn total_rows(&self) -> u64 {
+        self.tables.iter().map(|t| t.rows).sum()
+    }
+
+    #[must_use]
+    pub fn total_bytes(&self) -> u64 {
+        self.tables.iter().map(|t| t.bytes).sum()
+    }
+
+    /// Duration of reading all tables at the speed of the probe.
+    ///
+    /// The probed rows per second are converted to catalog bytes per second with the average
+    /// row size of the probed table, so wide and narrow tables are weighted by their size.
+    #[must_use]
+    pub fn predicted_duration(&self) -> Option<Duration> {
+        let probe = self.probe.as_ref()?;
+        let table = self
+            .tables
+            .iter()
+            .find(|t| t.table_name == probe.table_name)?;
+        if probe.rows == 0 || table.rows == 0 || probe.elapsed.is_zero() {
+            return None;
+        }
+        let rows_per_second = probe.rows as f64 / probe.elapsed.as_secs_f64();
+        let bytes_per_second = rows_per_second * (table.bytes as f64 / table.rows as f64);
+        if bytes_per_second <= 0.0 {
+            return None;
+        }
+        Some(Duration::from_secs_f64(
+            self.total_bytes() as f64 / bytes_per_second,
+        ))
+    }
+}
+
+/// Reads up to `max_rows` rows of the table for at most `max_time`.
+///
+/// # Errors
+///
+/// Returns an error if the table cannot be read.
+pub async fn probe_throughput(
+    source: &dyn DatabaseDriver,
+    table_name: &str,
+    max_rows: u64,
+    max_time: Duration,
+) -> Result<ThroughputProbe, Box<dyn std::error::Error>> {
+    let started = Instant::now();
+    let mut stream = source.stream_table_data(table_name).await?;
+    let (mut rows, mut bytes) = (0u64, 0u64);
+    while rows < max_rows && started.elapsed() < max_time {
+        let Some(row) = stream.next().await else {
+            break;
+        };
+        bytes += metrics::row_size(&row?);
+        rows += 1;
+    }
+    Ok(ThroughputProbe {
+        table_name: table_name.to_string(),
+        rows,
+        bytes,
+        elapsed: started.elapsed(),
+    })
+}
+
+/// Sizes of the tables of the schema in schema order, tables without statistics are empty.
+///
+/// Tables excluded by the config are not in the schema and left out.
+#[must_use]
+pub fn schema_table_sizes(
+    sizes: &[ForgeTableEstimate],
+    schema: &ForgeSchema,
+) -> Vec<ForgeTableEstimate> {
+    schema
+        .tables
+        .iter()
+        .map(|table| {
+            sizes
+                .iter()
+                .find(|size| size.table_name == table.name)
+                .cloned()
+                .unwrap_or_else(|| ForgeTableEstimate {
+                    table_name: table.name.clone(),
+                    ..Default::default()
+                })
+        })
+        .collect()
+}
+
+/// Estimates the tables of the schema and probes the throughput with the largest one.
+///
+/// # Errors
+///
+/// Returns an error if the catalog statistics cannot be read or the probe fails.
+pub async fn estimate(
+    source: &dyn DatabaseDriver,
+    schema: &ForgeSchema,
+) -> Result<Estimate, Box<dyn std::error::Error>> {
+    let tables = schema_table_sizes(&source.estimate_table_sizes().await?, schema);
+
+    let probe = match tables.iter().filter(|t| t.rows > 0).max_by_key(|t| t.bytes) {
+        Some(largest) => {
+            Some(probe_throughput(source, &largest.table_name, PROBE_ROWS, PROBE_TIME).await?)
+        }
+        None => None,
+    };
+    Ok(Estimate { tables, probe })
+}
+
+/// size with a binary unit, i.e. "1.5 GiB"
+pub(crate) fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// duration like "1h 02m 03s"
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match (secs / 3600, secs % 3600 / 60, secs % 60) {
+        (0, 0, s) => format!("{s}s"),
+        (0, m, s) => format!("{m}m {s:02}s"),
+        (h, m, s) => format!("{h}h {m:02}m {s:02}s"),
+    }
+}
+
+/// estimation report with one line per table and the predicted duration
+#[must_use]
+pub fn estimate_report(estimate: &Estimate) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Estimate: {} tables, ~{} rows, {}",
+        estimate.tables.len(),
+        estimate.total_rows(),
+        format_bytes(estimate.total_bytes())
+    );
+    for table in &estimate.tables {
+        let _ = writeln!(
+            out,
+            "  {}: ~{} rows, {}",
+            table.table_name,
+            table.rows,
+            format_bytes(table.bytes)
+        );
+    }
+    if let Some(ref probe) = estimate.probe {
+        let _ = writeln!(
+            out,
+            "Probe: {} rows ({}) of {} read in {:.2}s",
+            probe.rows,
+            format_bytes(probe.bytes),
+            probe.table_name,
+            probe.elapsed.as_secs_f64()
+        );
+    }
+    match estimate.predicted_duration() {
+        Some(duration) => {
+            let _ = writeln!(
+                out,
+                "Predicted duration: {} (read speed of the source)",
+                format_duration(duration)
+            );
+        }
+        None => {
+            let _ = writeln!(out, "Predicted duration: unknown");
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    fn table(name: &str, rows: u64, bytes: u64) -> ForgeTableEstimate {
+        ForgeTableEstimate {
+            table_name: name.to_string(),
+            rows,
+            bytes,
+        }
+    }
+
+    #[test]
+    fn predicted_duration_scales_the_probe_by_size() {
+        let estimate = Estimate {
+            // 100 bytes per row in users
+            tables: vec![table("users", 1000, 100_000), table("orders", 10, 900_000)],
+            probe: Some(ThroughputProbe {
+                table_name: "users".to_string(),
+                rows: 500,
+                bytes: 40_000,
+                elapsed: Duration::from_secs(1),
+            }),
+        };
+        // 500 rows/s * 100 bytes = 50 kB/s for 1 MB
+        assert_eq!(estimate.predicted_duration(), Some(Duration::from_secs(20)));
+
+        let without_probe = Estimate {
+            probe: None,
+            ..estimate
+        };
+        assert_eq!(without_probe.predicted_duration(), None);
+    }
+
+    #[test]
+    fn formats_sizes_and_durations() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024), "3.0 GiB");
+        assert_eq!(format_duration(Duration::from_secs(42)), "42s");
+        assert_eq!(format_duration(Duration::from_secs(125)), "2m 05s");
+        assert_eq!(format_duration(Duration::from_secs(3723)), "1h 02m 03s");
+    }
+
+    #[test]
+    fn report_lists_tables_and_prediction() {
+        let estimate = Estimate {
+            tables: vec![table("users", 1000, 100_000)],
+            probe: Some(ThroughputProbe {
+                table_name: "users".to_string(),
+                rows: 1000,
+                bytes: 50_000,
+                elapsed: Duration::from_millis(500),
+            }),
+        };
+        assert_eq!(
+            estimate_report(&estimate),
+            "Estimate: 1 tables, ~1000 rows, 97.7 KiB\n  users: ~1000 rows, 97.7 KiB\n\
+             Probe: 1000 rows (48.8 KiB) of users read in 0.50s\n\
+             Predicted duration: 0s (read speed of the source)\n"
+        );
+    }
+}
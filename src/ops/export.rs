@@ -0,0 +1,327 @@
+//! Export of the table data into files (`fluxforge export`).
+//!
+//! The rows are read like a replication reads them: null policies, type overrides, time
+//! zones and column renames are applied before a row is written, so an export holds the
+//! values a replication would have inserted. Every table gets its own file named after
+//! its target table, the rows are encoded by a [`RowEncoder`] of the format.
+
+use crate::core::{ForgeCsvQuote, ForgeExportFormat};
+use crate::ops::{Row, prepare_row, storage};
+use crate::{DatabaseDriver, ForgeConfig, ForgeSchema, ForgeSchemaTable, ForgeUniversalDataField};
+use futures::StreamExt;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+use tracing::{debug, info};
+
+/// Field delimiter, quoting and NULL text of CSV files (`[export]`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvOptions {
+    pub delimiter: char,
+    pub quote: ForgeCsvQuote,
+    pub null_value: String,
+    pub header: bool,
+}
+
+impl CsvOptions {
+    #[must_use]
+    pub fn from_config(config: &ForgeConfig) -> Self {
+        Self {
+            delimiter: config.get_csv_delimiter(),
+            quote: config.get_csv_quote(),
+            null_value: config.get_csv_null_value().to_string(),
+            header: config.is_csv_header(),
+        }
+    }
+
+    /// field of a text, quoted as configured
+    fn field(&self, text: &str) -> String {
+        let quoted = match self.quote {
+            ForgeCsvQuote::Always => true,
+            ForgeCsvQuote::Never => false,
+            // a text equal to the NULL value is quoted to tell both apart
+            ForgeCsvQuote::Necessary => {
+                text == self.null_value
+                    || text.contains(self.delimiter)
+                    || text.contains(['"', '\n', '\r'])
+            }
+        };
+        if quoted {
+            format!("\"{}\"", text.replace('"', "\"\""))
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// CSV line of the values of a row, with line break
+    #[must_use]
+    pub fn line<'a>(
+        &self,
+        values: impl IntoIterator<Item = &'a ForgeUniversalDataField>,
+    ) -> String {
+        let mut line = String::new();
+        for (i, value) in values.into_iter().enumerate() {
+            if i > 0 {
+                line.push(self.delimiter);
+            }
+            match value {
+                ForgeUniversalDataField::Null => line.push_str(&self.null_value),
+                value => line.push_str(&self.field(&value.to_string())),
+            }
+        }
+        line.push('\n');
+        line
+    }
+
+    /// CSV line of the column names, with line break
+    #[must_use]
+    pub fn header_line(&self, columns: &[String]) -> String {
+        let fields: Vec<String> = columns.iter().map(|c| self.field(c)).collect();
+        let mut line = fields.join(&self.delimiter.to_string());
+        line.push('\n');
+        line
+    }
+}
+
+/// Encodes the rows of a table into the bytes of a file format.
+pub trait RowEncoder {
+    /// bytes at the start of the file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the format cannot encode the table.
+    fn start(&mut self) -> Result<Vec<u8>, Box<dyn std::error::Error>>;
+
+    /// bytes of a row, formats writing blocks of rows return them when a block is full
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a value cannot be encoded.
+    fn encode(&mut self, row: &Row) -> Result<Vec<u8>, Box<dyn std::error::Error>>;
+
+    /// remaining bytes at the end of the file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the buffered rows cannot be encoded.
+    fn finish(&mut self) -> Result<Vec<u8>, Box<dyn std::error::Error>>;
+}
+
+/// CSV lines of a table, with a header line of the target column names
+pub struct CsvEncoder {
+    options: CsvOptions,
+    columns: Vec<String>,
+}
+
+impl CsvEncoder {
+    #[must_use]
+    pub fn new(table: &ForgeSchemaTable, config: &ForgeConfig) -> Self {
+        Self {
+            options: CsvOptions::from_config(config),
+            columns: target_columns(table, config),
+        }
+    }
+}
+
+impl RowEncoder for CsvEncoder {
+    fn start(&mut self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        if !self.options.header {
+            return Ok(Vec::new());
+        }
+        Ok(self.options.header_line(&self.columns).into_bytes())
+    }
+
+    fn encode(&mut self, row: &Row) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        Ok(self.options.line(row.values()).into_bytes())
+    }
+
+    fn finish(&mut self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        Ok(Vec::new())
+    }
+}
+
+/// target names of the columns of a table (`[tables.column_renames]`)
+pub(crate) fn target_columns(table: &ForgeSchemaTable, config: &ForgeConfig) -> Vec<String> {
+    table
+        .columns
+        .iter()
+        .map(|c| {
+            config
+                .get_target_column_name(&table.name, &c.name)
+                .to_string()
+        })
+        .collect()
+}
+
+/// Encoder of the format for the rows of a table.
+///
+/// # Errors
+///
+/// Returns an error if the format is not part of this build.
+pub fn encoder(
+    format: ForgeExportFormat,
+    table: &ForgeSchemaTable,
+    config: &ForgeConfig,
+) -> Result<Box<dyn RowEncoder>, Box<dyn std::error::Error>> {
+    match format {
+        ForgeExportFormat::Csv => Ok(Box::new(CsvEncoder::new(table, config))),
+        #[cfg(feature = "parquet")]
+        ForgeExportFormat::Parquet => Ok(Box::new(crate::ops::parquet::ParquetEncoder::new(
+            table, config,
+        )?)),
+        #[cfg(not(feature = "parquet"))]
+        ForgeExportFormat::Parquet => {
+            Err("--format parquet requires a build with the 'parquet' feature".into())
+        }
+    }
+}
+
+/// Rows written to the file of a table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportedTable {
+    pub table_name: String,
+    pub path: PathBuf,
+    pub rows: u64,
+}
+
+/// Writes the rows of a table into `<target table>.<extension>` in the directory (or S3 prefix).
+///
+/// # Errors
+///
+/// Returns an error if the table cannot be read or the file cannot be written.
+pub async fn export_table(
+    source: &dyn DatabaseDriver,
+    table: &ForgeSchemaTable,
+    config: &ForgeConfig,
+    format: ForgeExportFormat,
+    directory: &Path,
+) -> Result<ExportedTable, Box<dyn std::error::Error>> {
+    let mut encoder = encoder(format, table, config)?;
+    let target_table = config.get_target_table_name(&table.name);
+    let path = directory.join(format!("{target_table}.{}", format.extension()));
+    let mut writer = storage::create(&path).await?;
+    writer.write_all(&encoder.start()?).await?;
+
+    let mut stream = source.stream_table_data(&table.name).await?;
+    let mut rows = 0u64;
+    while let Some(row) = stream.next().await {
+        let mut row = row?;
+        prepare_row(table, config, &mut row);
+        writer.write_all(&encoder.encode(&row)?).await?;
+        rows += 1;
+    }
+    writer.write_all(&encoder.finish()?).await?;
+    writer.shutdown().await?;
+    debug!(table = %table.name, rows, path = %path.display(), "table exported");
+
+    Ok(ExportedTable {
+        table_name: table.name.clone(),
+        path,
+        rows,
+    })
+}
+
+/// Exports every table of the schema into the directory, which is created if missing,
+/// or under an `s3://bucket/prefix`.
+///
+/// # Errors
+///
+/// Returns an error if the directory cannot be created or a table fails.
+pub async fn export_data(
+    source: &dyn DatabaseDriver,
+    schema: &ForgeSchema,
+    config: &ForgeConfig,
+    format: ForgeExportFormat,
+    directory: &Path,
+) -> Result<Vec<ExportedTable>, Box<dyn std::error::Error>> {
+    storage::create_dir_all(directory).await?;
+    let mut exported = Vec::with_capacity(schema.tables.len());
+    for table in &schema.tables {
+        let table = export_table(source, table, config, format, directory).await?;
+        info!(table = %table.table_name, rows = table.rows, "exported");
+        exported.push(table);
+    }
+    Ok(exported)
+}
+
+/// report of the exported tables with their files
+#[must_use]
+pub fn export_report(exported: &[ExportedTable]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Exported {} tables, {} rows",
+        exported.len(),
+        exported.iter().map(|t| t.rows).sum::<u64>()
+    );
+    for table in exported {
+        let _ = writeln!(
+            out,
+            "  {}: {} rows -> {}",
+            table.table_name,
+            table.rows,
+            table.path.display()
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use ForgeUniversalDataField::{Integer, Null, Text};
+
+    fn options(config: &str) -> CsvOptions {
+        CsvOptions::from_config(&toml::from_str(config).unwrap())
+    }
+
+    #[test]
+    fn quotes_fields_when_necessary() {
+        let csv = options("");
+        let row = [
+            Integer(1),
+            Text("plain".into()),
+            Text("a,b".into()),
+            Text("say \"hi\"".into()),
+            Text("two\nlines".into()),
+            Text(String::new()),
+            Null,
+        ];
+        assert_eq!(
+            csv.line(&row),
+            "1,plain,\"a,b\",\"say \"\"hi\"\"\",\"two\nlines\",\"\",\n"
+        );
+        assert_eq!(
+            csv.header_line(&["id".to_string(), "full name".to_string()]),
+            "id,full name\n"
+        );
+    }
+
+    #[test]
+    fn applies_delimiter_quote_and_null_value() {
+        let csv = options("[export]\ndelimiter = ';'\nquote = 'always'\nnull_value = '\\N'");
+        assert_eq!(
+            csv.line(&[Integer(1), Text("a;b".into()), Null]),
+            "\"1\";\"a;b\";\\N\n"
+        );
+        assert!(csv.header);
+
+        let csv = options("[export]\ndelimiter = \"\\t\"\nquote = 'never'\nheader = false");
+        assert_eq!(csv.line(&[Text("a,b".into()), Null]), "a,b\t\n");
+        assert!(!csv.header);
+    }
+
+    #[test]
+    fn report_lists_the_files() {
+        let exported = vec![ExportedTable {
+            table_name: "users".to_string(),
+            path: PathBuf::from("out/users.csv"),
+            rows: 3,
+        }];
+        assert_eq!(
+            export_report(&exported),
+            "Exported 1 tables, 3 rows\n  users: 3 rows -> out/users.csv\n"
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! Table-level grants: grouping, grantee names and the access report.
+
+use crate::ForgeSchemaGrant;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+/// groups (grantee, table, privilege) rows into one grant per grantee and table,
+/// sorted by grantee and table
+#[must_use]
+pub fn group_grants(rows: Vec<(String, String, String)>) -> Vec<ForgeSchemaGrant> {
+    let mut grouped: BTreeMap<(String, String), Vec<String>> = BTreeMap::new();
+    for (grantee, table_name, privilege) in rows {
+        let privileges = grouped.entry((grantee, table_name)).or_default();
+        let privilege = privilege.to_uppercase();
+        if !privileges.contains(&privilege) {
+            privileges.push(privilege);
+        }
+    }
+
+    grouped
+        .into_iter()
+        .map(|((grantee, table_name), mut privileges)| {
+            privileges.sort();
+            ForgeSchemaGrant {
+                grantee,
+                table_name,
+                privileges,
+            }
+        })
+        .collect()
+}
+
+/// user part of a MySQL grantee (`'app'@'%'` -> `app`), other names are returned unchanged
+#[must_use]
+pub fn grantee_user_name(grantee: &str) -> String {
+    match grantee.rsplit_once('@') {
+        Some((user, _host)) => user.trim_matches(|c| c == '\'' || c == '`').to_string(),
+        None => grantee.to_string(),
+    }
+}
+
+/// resolves the target grantee with the configured mapping,
+/// `None` if the grantee is mapped to an empty string (= skip)
+#[must_use]
+pub fn map_grantee(grantee: &str, config: &crate::ForgeConfig) -> Option<String> {
+    match config.get_grantee_mapping(grantee) {
+        Some("") => None,
+        Some(mapped) => Some(mapped.to_string()),
+        None => Some(grantee.to_string()),
+    }
+}
+
+/// human readable report of who has access to what
+#[must_use]
+pub fn grants_report(grants: &[ForgeSchemaGrant]) -> String {
+    let mut by_grantee: BTreeMap<&str, Vec<&ForgeSchemaGrant>> = BTreeMap::new();
+    for grant in grants {
+        by_grantee.entry(&grant.grantee).or_default().push(grant);
+    }
+
+    let mut out = String::new();
+    if by_grantee.is_empty() {
+        out.push_str("No table-level grants found.\n");
+        return out;
+    }
+
+    for (grantee, grants) in by_grantee {
+        let _ = writeln!(out, "{grantee}:");
+        for grant in grants {
+            let _ = writeln!(
+                out,
+                "  {:<30} {}",
+                grant.table_name,
+                grant.privileges.join(", ")
+            );
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::ForgeConfig;
+
+    fn row(grantee: &str, table: &str, privilege: &str) -> (String, String, String) {
+        (
+            grantee.to_string(),
+            table.to_string(),
+            privilege.to_string(),
+        )
+    }
+
+    #[test]
+    fn group_grants_merges_privileges() {
+        let grants = group_grants(vec![
+            row("'app'@'%'", "users", "UPDATE"),
+            row("'app'@'%'", "users", "select"),
+            row("'app'@'%'", "users", "SELECT"),
+            row("'report'@'%'", "orders", "SELECT"),
+            row("'app'@'%'", "orders", "INSERT"),
+        ]);
+
+        assert_eq!(grants.len(), 3);
+        assert_eq!(grants[0].table_name, "orders");
+        assert_eq!(grants[1].table_name, "users");
+        assert_eq!(grants[1].privileges, vec!["SELECT", "UPDATE"]);
+        assert_eq!(grants[2].grantee, "'report'@'%'");
+    }
+
+    #[test]
+    fn grantee_names_and_mapping() {
+        assert_eq!(grantee_user_name("'app'@'%'"), "app");
+        assert_eq!(grantee_user_name("'ops'@'10.0.0.%'"), "ops");
+        assert_eq!(grantee_user_name("reporting"), "reporting");
+
+        let config: ForgeConfig = toml::from_str(
+            r#"
+            [grants.user_mapping]
+            "'app'@'%'" = "app_rw"
+            "'root'@'localhost'" = ""
+            "#,
+        )
+        .unwrap();
+        assert_eq!(map_grantee("'app'@'%'", &config).as_deref(), Some("app_rw"));
+        assert_eq!(map_grantee("'root'@'localhost'", &config), None);
+        assert_eq!(
+            map_grantee("'other'@'%'", &config).as_deref(),
+            Some("'other'@'%'")
+        );
+    }
+
+    #[test]
+    fn report_lists_tables_per_grantee() {
+        let report = grants_report(&group_grants(vec![
+            row("app", "users", "SELECT"),
+            row("app", "users", "INSERT"),
+        ]));
+        assert!(report.starts_with("app:\n"));
+        assert!(report.contains("users"));
+        assert!(report.contains("INSERT, SELECT"));
+
+        assert_eq!(grants_report(&[]), "No table-level grants found.\n");
+    }
+}
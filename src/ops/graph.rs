@@ -0,0 +1,167 @@
+//! Entity relationship diagrams of a schema (`fluxforge graph`).
+//!
+//! The diagram is drawn from the foreign key graph of [`dependency_graph`], the graph the
+//! replication order is sorted by, so foreign keys to tables outside of the schema are
+//! left out in both.
+
+use crate::core::ForgeGraphFormat;
+use crate::ops::dependency_graph;
+use crate::{ForgeSchema, ForgeSchemaTable};
+use petgraph::visit::EdgeRef;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+/// identifier of Mermaid, characters other than letters, digits, `-` and `_` become `_`
+fn mermaid_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// quoted string of Mermaid, which has no escapes for double quotes
+fn mermaid_quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "'").replace('\n', " "))
+}
+
+/// quoted ID of DOT
+fn dot_quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Mermaid `erDiagram` with the columns of every table and a relationship per foreign key.
+fn mermaid(schema: &ForgeSchema) -> String {
+    let tables: HashMap<&str, &ForgeSchemaTable> =
+        schema.tables.iter().map(|t| (t.name.as_str(), t)).collect();
+    let graph = dependency_graph(schema);
+
+    let mut out = String::from("erDiagram\n");
+    for edge in graph.edge_references() {
+        let (referenced, referencing, fk) =
+            (graph[edge.source()], graph[edge.target()], edge.weight());
+        // a nullable foreign key column makes the parent optional
+        let optional = tables
+            .get(referencing)
+            .and_then(|t| t.columns.iter().find(|c| c.name == fk.column))
+            .is_some_and(|c| c.is_nullable);
+        let _ = writeln!(
+            out,
+            "    {} {}--o{{ {} : {}",
+            mermaid_name(referenced),
+            if optional { "|o" } else { "||" },
+            mermaid_name(referencing),
+            mermaid_quote(&fk.name)
+        );
+    }
+    for table in &schema.tables {
+        let _ = writeln!(out, "    {} {{", mermaid_name(&table.name));
+        for column in &table.columns {
+            let mut keys = Vec::new();
+            if column.is_primary_key {
+                keys.push("PK");
+            }
+            if table.foreign_keys.iter().any(|fk| fk.column == column.name) {
+                keys.push("FK");
+            }
+            let _ = write!(
+                out,
+                "        {} {}",
+                mermaid_name(&column.data_type),
+                mermaid_name(&column.name)
+            );
+            if !keys.is_empty() {
+                let _ = write!(out, " {}", keys.join(", "));
+            }
+            if let Some(ref comment) = column.comment {
+                let _ = write!(out, " {}", mermaid_quote(comment));
+            }
+            out.push('\n');
+        }
+        out.push_str("    }\n");
+    }
+    out
+}
+
+/// Graphviz digraph with an edge per foreign key from the referencing to the referenced table.
+fn dot(schema: &ForgeSchema) -> String {
+    let graph = dependency_graph(schema);
+
+    let mut out = String::from("digraph schema {\n    rankdir=LR;\n    node [shape=box];\n");
+    for node in graph.node_indices() {
+        let _ = writeln!(out, "    {};", dot_quote(graph[node]));
+    }
+    for edge in graph.edge_references() {
+        let _ = writeln!(
+            out,
+            "    {} -> {} [label={}];",
+            dot_quote(graph[edge.target()]),
+            dot_quote(graph[edge.source()]),
+            dot_quote(&edge.weight().name)
+        );
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Renders the foreign key graph of the schema as a diagram.
+#[must_use]
+pub fn render(schema: &ForgeSchema, format: ForgeGraphFormat) -> String {
+    match format {
+        ForgeGraphFormat::Mermaid => mermaid(schema),
+        ForgeGraphFormat::Dot => dot(schema),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::ForgeSchemaColumn;
+    use crate::core::ForgeSchemaForeignKey;
+
+    fn shop_schema() -> ForgeSchema {
+        let mut schema = ForgeSchema::new();
+        let mut users = ForgeSchemaTable::new("users");
+        let mut id = ForgeSchemaColumn::new("id", "integer");
+        id.is_primary_key = true;
+        users.columns.push(id);
+        let mut orders = ForgeSchemaTable::new("order items");
+        let mut user_id = ForgeSchemaColumn::new("user_id", "integer");
+        user_id.is_nullable = true;
+        user_id.comment = Some("the \"buyer\"".to_string());
+        orders.columns.push(user_id);
+        for (name, ref_table) in [("fk_user", "users"), ("fk_tenant", "tenants")] {
+            orders.foreign_keys.push(ForgeSchemaForeignKey {
+                name: name.to_string(),
+                column: "user_id".to_string(),
+                ref_table: ref_table.to_string(),
+                ref_column: "id".to_string(),
+                ..Default::default()
+            });
+        }
+        schema.tables.push(users);
+        schema.tables.push(orders);
+        schema
+    }
+
+    #[test]
+    fn mermaid_diagram_has_entities_and_relationships() {
+        assert_eq!(
+            render(&shop_schema(), ForgeGraphFormat::Mermaid),
+            "erDiagram\n    users |o--o{ order_items : \"fk_user\"\n    users {\n        integer id PK\n    }\n    order_items {\n        integer user_id FK \"the 'buyer'\"\n    }\n"
+        );
+    }
+
+    #[test]
+    fn dot_edges_point_to_the_referenced_table() {
+        assert_eq!(
+            render(&shop_schema(), ForgeGraphFormat::Dot),
+            "digraph schema {\n    rankdir=LR;\n    node [shape=box];\n    \"users\";\n    \"order items\";\n    \"order items\" -> \"users\" [label=\"fk_user\"];\n}\n"
+        );
+    }
+}
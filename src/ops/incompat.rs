@@ -0,0 +1,429 @@
+//! Catalog of known incompatibilities with stable codes and hints.
+//!
+//! The codes never change their meaning, so they can be looked up in the README
+//! or filtered in log pipelines.
+
+use crate::core::ForgeDialect;
+use crate::{ForgeSchema, ForgeSchemaColumn, ForgeSchemaTable};
+use serde::Serialize;
+use std::fmt;
+
+/// how serious a catalog entry is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// the data is copied, but behaves differently on the target
+    Warning,
+    /// the migration is likely to fail or to lose data
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// a known-problem construct with its stable code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CatalogEntry {
+    pub code: &'static str,
+    pub severity: Severity,
+    /// target dialects the construct causes trouble on
+    pub targets: &'static [ForgeDialect],
+    pub title: &'static str,
+    /// config rule or manual step, `<table>`, `<column>`, ... are filled in per occurrence
+    pub hint: &'static str,
+}
+
+pub const ZEROFILL: CatalogEntry = CatalogEntry {
+    code: "FF001",
+    severity: Severity::Warning,
+    targets: &[ForgeDialect::Postgres, ForgeDialect::Mysql],
+    title: "ZEROFILL is not supported by PostgreSQL and deprecated since MySQL 8.0.17, leading zeros are lost",
+    hint: "[tables.column_overrides.<table>] <column> = \"varchar(<width>)\", then pad the values on the target: UPDATE <table> SET <column> = lpad(<column>, <width>, '0')",
+};
+
+pub const YEAR2: CatalogEntry = CatalogEntry {
+    code: "FF002",
+    severity: Severity::Warning,
+    targets: &[ForgeDialect::Postgres, ForgeDialect::Mysql],
+    title: "YEAR(2) was removed in MySQL 8.0, two-digit years are read as four-digit years",
+    hint: "[tables.column_overrides.<table>] <column> = \"smallint\"",
+};
+
+pub const MONEY: CatalogEntry = CatalogEntry {
+    code: "FF003",
+    severity: Severity::Warning,
+    targets: &[ForgeDialect::Mysql],
+    title: "money depends on lc_monetary and has no MySQL equivalent",
+    hint: "add the rule [postgres.types.on_read] \"money\" = \"numeric\"",
+};
+
+pub const ENUM_PRIMARY_KEY: CatalogEntry = CatalogEntry {
+    code: "FF004",
+    severity: Severity::Warning,
+    targets: &[ForgeDialect::Postgres],
+    title: "ENUM/SET in a primary key sorts by declaration order in MySQL but by text elsewhere",
+    hint: "declare the values in alphabetical order or add the rule [mysql.types.on_read] \"enum\" = \"varchar\" on both sides, otherwise verification compares rows out of order",
+};
+
+pub const TEXT_INDEX_PREFIX: CatalogEntry = CatalogEntry {
+    code: "FF005",
+    severity: Severity::Warning,
+    targets: &[ForgeDialect::Postgres],
+    title: "prefix index on a TEXT/BLOB column, PostgreSQL indexes the complete value and rejects rows larger than 2704 bytes",
+    hint: "recreate the index as an expression index after the migration: CREATE INDEX <index> ON <table> (left(<column>, <length>))",
+};
+
+pub const MYSQL_INDEX_FUNCTION: CatalogEntry = CatalogEntry {
+    code: "FF006",
+    severity: Severity::Warning,
+    targets: &[ForgeDialect::Postgres],
+    title: "functional index with a MySQL-only function, the index is not created on PostgreSQL",
+    hint: "create an equivalent expression index on the target afterwards, i.e. split_part(<column>, '@', -1) for substring_index or coalesce for ifnull",
+};
+
+/// all entries of the catalog, ordered by code
+pub const CATALOG: &[CatalogEntry] = &[
+    ZEROFILL,
+    YEAR2,
+    MONEY,
+    ENUM_PRIMARY_KEY,
+    TEXT_INDEX_PREFIX,
+    MYSQL_INDEX_FUNCTION,
+];
+
+/// functions of MySQL index expressions PostgreSQL lacks or evaluates differently
+/// (`left` converts numbers and dates to text implicitly in MySQL only)
+const MYSQL_ONLY_FUNCTIONS: &[&str] = &[
+    "substring_index",
+    "ifnull",
+    "if",
+    "left",
+    "date_format",
+    "json_extract",
+    "json_unquote",
+    "unix_timestamp",
+    "from_unixtime",
+];
+
+/// The first MySQL-only function called in an index expression, i.e. `substring_index` of
+/// `` substring_index(`email`,_utf8mb4'@',-(1)) ``.
+#[must_use]
+pub fn mysql_only_function(expression: &str) -> Option<&'static str> {
+    let lower = expression.to_lowercase();
+    let mut rest = lower.as_str();
+    while let Some(open) = rest.find('(') {
+        let name = rest[..open]
+            .trim_end()
+            .rsplit(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .next()
+            .unwrap_or_default();
+        if let Some(function) = MYSQL_ONLY_FUNCTIONS.iter().find(|f| **f == name) {
+            return Some(function);
+        }
+        rest = &rest[open + 1..];
+    }
+    None
+}
+
+/// one occurrence of a catalog entry in a schema
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Incompatibility {
+    pub code: &'static str,
+    pub severity: Severity,
+    pub table: String,
+    /// column or index name
+    pub object: String,
+    pub message: &'static str,
+    /// hint of the catalog entry with the names of this occurrence
+    pub hint: String,
+}
+
+impl Incompatibility {
+    fn new(entry: &CatalogEntry, table: &str, object: &str) -> Self {
+        Self {
+            code: entry.code,
+            severity: entry.severity,
+            table: table.to_string(),
+            object: object.to_string(),
+            message: entry.title,
+            hint: entry.hint.replace("<table>", table),
+        }
+    }
+
+    /// fills in a placeholder of the hint
+    fn fill(mut self, placeholder: &str, value: &str) -> Self {
+        self.hint = self.hint.replace(placeholder, value);
+        self
+    }
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} [{}] {}.{}: {} (hint: {})",
+            self.severity, self.code, self.table, self.object, self.message, self.hint
+        )
+    }
+}
+
+/// native type of the column, falls back to the mapped type for schema files of older versions
+fn native_type(column: &ForgeSchemaColumn) -> String {
+    column
+        .source_type
+        .as_deref()
+        .unwrap_or(&column.data_type)
+        .to_lowercase()
+}
+
+fn check_column(table: &ForgeSchemaTable, column: &ForgeSchemaColumn) -> Vec<Incompatibility> {
+    let native = native_type(column);
+    let base = native.split(['(', ' ']).next().unwrap_or_default();
+    let mut found = Vec::new();
+
+    if native.split_whitespace().any(|w| w == "zerofill") {
+        // display width of "int(5) unsigned zerofill", 10 digits hold every int
+        let width = native
+            .split_once('(')
+            .and_then(|(_, rest)| rest.split_once(')'))
+            .map_or("10", |(width, _)| width);
+        found.push(
+            Incompatibility::new(&ZEROFILL, &table.name, &column.name)
+                .fill("<column>", &column.name)
+                .fill("<width>", width),
+        );
+    }
+    if native == "year(2)" {
+        found.push(
+            Incompatibility::new(&YEAR2, &table.name, &column.name).fill("<column>", &column.name),
+        );
+    }
+    if base == "money" || column.data_type.eq_ignore_ascii_case("money") {
+        found.push(Incompatibility::new(&MONEY, &table.name, &column.name));
+    }
+    if column.is_primary_key && (column.enum_values.is_some() || base == "enum" || base == "set") {
+        found.push(Incompatibility::new(
+            &ENUM_PRIMARY_KEY,
+            &table.name,
+            &column.name,
+        ));
+    }
+    found
+}
+
+fn check_indices(table: &ForgeSchemaTable) -> Vec<Incompatibility> {
+    let mut found = Vec::new();
+    for index in &table.indices {
+        let expressions = index.expressions.iter().flatten().flatten();
+        if expressions
+            .into_iter()
+            .any(|e| mysql_only_function(e).is_some())
+        {
+            found.push(Incompatibility::new(
+                &MYSQL_INDEX_FUNCTION,
+                &table.name,
+                &index.name,
+            ));
+        }
+        let Some(ref prefixes) = index.column_prefixes else {
+            continue;
+        };
+        let prefixed_text = index
+            .columns
+            .iter()
+            .zip(prefixes)
+            .filter_map(|(name, prefix)| Some((name, (*prefix)?)))
+            .find(|(name, _)| {
+                table.columns.iter().any(|c| {
+                    let native = native_type(c);
+                    &c.name == *name && (native.contains("text") || native.contains("blob"))
+                })
+            });
+        if let Some((column, length)) = prefixed_text {
+            found.push(
+                Incompatibility::new(&TEXT_INDEX_PREFIX, &table.name, &index.name)
+                    .fill("<index>", &index.name)
+                    .fill("<column>", column)
+                    .fill("<length>", &length.to_string()),
+            );
+        }
+    }
+    found
+}
+
+/// Known incompatibilities of the schema with the `target` dialect (all of them without a
+/// target, i.e. for `extract`), in table order.
+#[must_use]
+pub fn check_schema(schema: &ForgeSchema, target: Option<ForgeDialect>) -> Vec<Incompatibility> {
+    let mut found = Vec::new();
+    for table in &schema.tables {
+        for column in &table.columns {
+            found.extend(check_column(table, column));
+        }
+        found.extend(check_indices(table));
+    }
+    if let Some(target) = target {
+        found.retain(|i| lookup(i.code).is_some_and(|entry| entry.targets.contains(&target)));
+    }
+    found
+}
+
+/// looks up a catalog entry by its code (case-insensitive)
+#[must_use]
+pub fn lookup(code: &str) -> Option<&'static CatalogEntry> {
+    CATALOG.iter().find(|e| e.code.eq_ignore_ascii_case(code))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::core::ForgeSchemaIndex;
+
+    fn column(name: &str, source_type: &str) -> ForgeSchemaColumn {
+        let mut col = ForgeSchemaColumn::new(name, "mapped");
+        col.source_type = Some(source_type.to_string());
+        col
+    }
+
+    fn codes(schema: &ForgeSchema) -> Vec<(&'static str, String)> {
+        check_schema(schema, None)
+            .into_iter()
+            .map(|i| (i.code, i.object))
+            .collect()
+    }
+
+    #[test]
+    fn test_check_columns() {
+        let mut table = ForgeSchemaTable::new("t");
+        table
+            .columns
+            .push(column("zip", "int(5) unsigned zerofill"));
+        table.columns.push(column("born", "year(2)"));
+        table.columns.push(column("since", "year(4)"));
+        table.columns.push(column("price", "money"));
+        let mut kind = column("kind", "enum('b','a')");
+        kind.is_primary_key = true;
+        kind.enum_values = Some(vec!["b".to_string(), "a".to_string()]);
+        table.columns.push(kind);
+
+        let schema = ForgeSchema {
+            tables: vec![table],
+            ..ForgeSchema::default()
+        };
+        assert_eq!(
+            codes(&schema),
+            vec![
+                ("FF001", "zip".to_string()),
+                ("FF002", "born".to_string()),
+                ("FF003", "price".to_string()),
+                ("FF004", "kind".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_text_index_prefix() {
+        let mut table = ForgeSchemaTable::new("posts");
+        table.columns.push(column("title", "varchar(255)"));
+        table.columns.push(column("body", "mediumtext"));
+        let index =
+            |name: &str, columns: &[&str], prefixes: Option<Vec<Option<u32>>>| ForgeSchemaIndex {
+                name: name.to_string(),
+                columns: columns.iter().map(ToString::to_string).collect(),
+                column_prefixes: prefixes,
+                ..ForgeSchemaIndex::default()
+            };
+        table.indices = vec![
+            index("idx_title", &["title"], Some(vec![Some(10)])),
+            index("idx_body", &["title", "body"], Some(vec![None, Some(100)])),
+            index("idx_plain", &["body"], None),
+        ];
+
+        let schema = ForgeSchema {
+            tables: vec![table],
+            ..ForgeSchema::default()
+        };
+        assert_eq!(codes(&schema), vec![("FF005", "idx_body".to_string())]);
+        assert_eq!(
+            check_schema(&schema, None)[0].hint,
+            "recreate the index as an expression index after the migration: CREATE INDEX idx_body ON posts (left(body, 100))"
+        );
+    }
+
+    #[test]
+    fn test_check_schema_filters_by_target() {
+        let mut table = ForgeSchemaTable::new("t");
+        table
+            .columns
+            .push(column("zip", "int(5) unsigned zerofill"));
+        table.columns.push(column("price", "money"));
+        let schema = ForgeSchema {
+            tables: vec![table],
+            ..ForgeSchema::default()
+        };
+        let found = |target| -> Vec<&'static str> {
+            check_schema(&schema, Some(target))
+                .into_iter()
+                .map(|i| i.code)
+                .collect()
+        };
+        assert_eq!(found(ForgeDialect::Postgres), vec!["FF001"]);
+        assert_eq!(found(ForgeDialect::Mysql), vec!["FF001", "FF003"]);
+        assert_eq!(
+            check_schema(&schema, None)[0].hint,
+            "[tables.column_overrides.t] zip = \"varchar(5)\", then pad the values on the target: UPDATE t SET zip = lpad(zip, 5, '0')"
+        );
+    }
+
+    #[test]
+    fn test_check_mysql_index_function() {
+        assert_eq!(
+            mysql_only_function("substring_index(`email`,_utf8mb4'@',-(1))"),
+            Some("substring_index")
+        );
+        assert_eq!(
+            mysql_only_function("IFNULL(`nick`, `name`)"),
+            Some("ifnull")
+        );
+        assert_eq!(mysql_only_function("left(`code`,3)"), Some("left"));
+        assert_eq!(mysql_only_function("lower(`name`)"), None);
+        assert_eq!(mysql_only_function("coalesce(`name`,_utf8mb4'-')"), None);
+
+        let mut table = ForgeSchemaTable::new("users");
+        table.indices = vec![
+            ForgeSchemaIndex {
+                name: "idx_domain".to_string(),
+                columns: vec![String::new()],
+                expressions: Some(vec![Some("substring_index(`email`,'@',-(1))".to_string())]),
+                ..ForgeSchemaIndex::default()
+            },
+            ForgeSchemaIndex {
+                name: "idx_lower".to_string(),
+                columns: vec![String::new()],
+                expressions: Some(vec![Some("lower(`name`)".to_string())]),
+                ..ForgeSchemaIndex::default()
+            },
+        ];
+        let schema = ForgeSchema {
+            tables: vec![table],
+            ..ForgeSchema::default()
+        };
+        assert_eq!(codes(&schema), vec![("FF006", "idx_domain".to_string())]);
+    }
+
+    #[test]
+    fn test_catalog_codes_are_unique() {
+        for (i, entry) in CATALOG.iter().enumerate() {
+            assert!(CATALOG[i + 1..].iter().all(|e| e.code != entry.code));
+            assert_eq!(lookup(&entry.code.to_lowercase()), Some(entry));
+        }
+        assert_eq!(lookup("FF999"), None);
+    }
+}
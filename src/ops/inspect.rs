@@ -0,0 +1,160 @@
+//! Inspection of a database (`fluxforge list-tables`, `fluxforge inspect`).
+//!
+//! `list-tables` prints the tables of the schema with their row counts and sizes,
+//! `inspect` a single table as FluxForge sees it after the type mapping of the config,
+//! which shows which rule a column went through.
+
+use crate::core::ForgeTableEstimate;
+use crate::ops::document::{column_attributes, column_type};
+use crate::ops::estimate::{format_bytes, schema_table_sizes};
+use crate::{DatabaseDriver, ForgeSchema, ForgeSchemaTable};
+use std::fmt::Write as _;
+
+/// Tables of the schema with their sizes, the row counts are counted with `exact`.
+///
+/// # Errors
+///
+/// Returns an error if the catalog statistics cannot be read or a count fails.
+pub async fn list_tables(
+    source: &dyn DatabaseDriver,
+    schema: &ForgeSchema,
+    exact: bool,
+) -> Result<Vec<ForgeTableEstimate>, Box<dyn std::error::Error>> {
+    let mut tables = schema_table_sizes(&source.estimate_table_sizes().await?, schema);
+    if exact {
+        for table in &mut tables {
+            table.rows = source.get_table_row_count(&table.table_name).await?;
+        }
+    }
+    Ok(tables)
+}
+
+/// one line per table with rows and size, estimated counts are marked with `~`
+#[must_use]
+pub fn tables_report(tables: &[ForgeTableEstimate], exact: bool) -> String {
+    let approx = if exact { "" } else { "~" };
+    let width = tables.iter().map(|t| t.table_name.len()).max().unwrap_or(0);
+    let mut out = String::new();
+    for table in tables {
+        let _ = writeln!(
+            out,
+            "{:width$}  {approx}{:>12} rows  {:>10}",
+            table.table_name,
+            table.rows,
+            format_bytes(table.bytes)
+        );
+    }
+    let _ = writeln!(out, "{} tables", tables.len());
+    out
+}
+
+/// Position of the table in the schema.
+///
+/// # Errors
+///
+/// Returns an error naming the table if the schema does not have it.
+pub fn find_table(schema: &ForgeSchema, name: &str) -> Result<usize, String> {
+    schema
+        .tables
+        .iter()
+        .position(|t| t.name == name)
+        .ok_or_else(|| format!("table {name} not found, or excluded by the config"))
+}
+
+/// columns with source and mapped type, indices and foreign keys of a table
+#[must_use]
+pub fn table_report(table: &ForgeSchemaTable) -> String {
+    let mut out = format!("Table {}\n", table.name);
+    if let Some(ref comment) = table.comment {
+        let _ = writeln!(out, "  comment: {comment}");
+    }
+    out.push_str("Columns:\n");
+    for column in &table.columns {
+        let mapped = column_type(column);
+        let _ = write!(out, "  {}: ", column.name);
+        match column.source_type {
+            Some(ref source_type) if *source_type != mapped => {
+                let _ = write!(out, "{source_type} -> {mapped}");
+            }
+            _ => out.push_str(&mapped),
+        }
+        out.push_str(if column.is_nullable {
+            " NULL"
+        } else {
+            " NOT NULL"
+        });
+        if let Some(ref default) = column.default {
+            let _ = write!(out, " DEFAULT {default}");
+        }
+        let attributes = column_attributes(column);
+        if !attributes.is_empty() {
+            let _ = write!(out, " ({attributes})");
+        }
+        out.push('\n');
+    }
+    if !table.indices.is_empty() {
+        out.push_str("Indices:\n");
+        for index in &table.indices {
+            let _ = writeln!(
+                out,
+                "  {}{}: {}",
+                index.name,
+                if index.is_unique { " (unique)" } else { "" },
+                index.columns.join(", ")
+            );
+        }
+    }
+    if !table.foreign_keys.is_empty() {
+        out.push_str("Foreign keys:\n");
+        for fk in &table.foreign_keys {
+            let _ = writeln!(
+                out,
+                "  {}: {} -> {}.{}",
+                fk.name, fk.column, fk.ref_table, fk.ref_column
+            );
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::ForgeSchemaColumn;
+
+    #[test]
+    fn reports_show_sizes_and_mapped_types() {
+        let tables = vec![
+            ForgeTableEstimate {
+                table_name: "users".to_string(),
+                rows: 1200,
+                bytes: 3 * 1024 * 1024,
+            },
+            ForgeTableEstimate {
+                table_name: "tags".to_string(),
+                rows: 7,
+                bytes: 512,
+            },
+        ];
+        assert_eq!(
+            tables_report(&tables, false),
+            "users  ~        1200 rows     3.0 MiB\ntags   ~           7 rows       512 B\n2 tables\n"
+        );
+
+        let mut table = ForgeSchemaTable::new("users");
+        let mut id = ForgeSchemaColumn::new("id", "integer");
+        id.source_type = Some("int".to_string());
+        id.is_primary_key = true;
+        table.columns.push(id);
+        let mut name = ForgeSchemaColumn::new("name", "varchar");
+        name.source_type = Some("varchar(40)".to_string());
+        name.length = Some(40);
+        name.is_nullable = true;
+        table.columns.push(name);
+        assert_eq!(
+            table_report(&table),
+            "Table users\nColumns:\n  id: int -> integer NOT NULL (primary key)\n  name: varchar(40) NULL\n"
+        );
+    }
+}
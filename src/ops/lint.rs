@@ -0,0 +1,456 @@
+//! Schema linting (`fluxforge lint`).
+//!
+//! The rules report constructs that make a migration fail or behave differently, before
+//! anything is written. Every rule has a stable name and a default severity, `[lint.rules]`
+//! overrides the severity or turns a rule off.
+
+use crate::core::ForgeLintSeverity;
+use crate::ops::incompat::Severity;
+use crate::{ForgeConfig, ForgeSchema, ForgeSchemaColumn, ForgeSchemaTable};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Write as _;
+
+/// A lint rule with its default severity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LintRule {
+    pub name: &'static str,
+    pub severity: Severity,
+    pub description: &'static str,
+}
+
+pub const MISSING_PRIMARY_KEY: LintRule = LintRule {
+    name: "missing_primary_key",
+    severity: Severity::Warning,
+    description: "table without primary key, verification and resume cannot read its rows in order",
+};
+
+pub const RESERVED_WORD: LintRule = LintRule {
+    name: "reserved_word",
+    severity: Severity::Warning,
+    description: "identifier is a reserved word of MySQL or PostgreSQL and must be quoted in every query",
+};
+
+pub const ROW_SIZE: LintRule = LintRule {
+    name: "row_size",
+    severity: Severity::Warning,
+    description: "estimated maximum row size exceeds [lint] max_row_bytes",
+};
+
+pub const DUPLICATE_INDEX: LintRule = LintRule {
+    name: "duplicate_index",
+    severity: Severity::Warning,
+    description: "index has the same key parts as another index of the table",
+};
+
+pub const FOREIGN_KEY_TYPE_MISMATCH: LintRule = LintRule {
+    name: "foreign_key_type_mismatch",
+    severity: Severity::Error,
+    description: "foreign key column and referenced column have different types",
+};
+
+/// all rules, in the order they are checked
+pub const RULES: &[LintRule] = &[
+    MISSING_PRIMARY_KEY,
+    RESERVED_WORD,
+    ROW_SIZE,
+    DUPLICATE_INDEX,
+    FOREIGN_KEY_TYPE_MISMATCH,
+];
+
+/// names of all rules, the keys of `[lint.rules]`
+pub const RULE_NAMES: &[&str] = &[
+    MISSING_PRIMARY_KEY.name,
+    RESERVED_WORD.name,
+    ROW_SIZE.name,
+    DUPLICATE_INDEX.name,
+    FOREIGN_KEY_TYPE_MISMATCH.name,
+];
+
+/// reserved words of MySQL 8 and PostgreSQL that are likely table or column names
+const RESERVED_WORDS: &[&str] = &[
+    "all",
+    "analyse",
+    "analyze",
+    "and",
+    "any",
+    "array",
+    "as",
+    "asc",
+    "between",
+    "both",
+    "by",
+    "case",
+    "cast",
+    "check",
+    "collate",
+    "column",
+    "condition",
+    "constraint",
+    "create",
+    "cross",
+    "current_date",
+    "current_time",
+    "current_timestamp",
+    "current_user",
+    "default",
+    "delete",
+    "desc",
+    "distinct",
+    "do",
+    "drop",
+    "else",
+    "end",
+    "except",
+    "exists",
+    "false",
+    "fetch",
+    "for",
+    "foreign",
+    "from",
+    "grant",
+    "group",
+    "groups",
+    "having",
+    "in",
+    "index",
+    "inner",
+    "insert",
+    "interval",
+    "intersect",
+    "into",
+    "is",
+    "join",
+    "key",
+    "keys",
+    "lateral",
+    "leading",
+    "left",
+    "like",
+    "limit",
+    "load",
+    "lock",
+    "natural",
+    "not",
+    "null",
+    "offset",
+    "on",
+    "only",
+    "option",
+    "or",
+    "order",
+    "outer",
+    "primary",
+    "range",
+    "rank",
+    "read",
+    "references",
+    "release",
+    "rename",
+    "repeat",
+    "replace",
+    "require",
+    "returning",
+    "right",
+    "row",
+    "rows",
+    "select",
+    "session_user",
+    "set",
+    "show",
+    "signal",
+    "some",
+    "table",
+    "then",
+    "to",
+    "trailing",
+    "trigger",
+    "true",
+    "union",
+    "unique",
+    "update",
+    "usage",
+    "user",
+    "using",
+    "values",
+    "when",
+    "where",
+    "window",
+    "with",
+    "write",
+];
+
+/// A rule violation in a table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LintFinding {
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub table: String,
+    /// column, index or foreign key name, empty for the table itself
+    pub object: String,
+    pub message: String,
+}
+
+impl fmt::Display for LintFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}] {}", self.severity, self.rule, self.table)?;
+        if !self.object.is_empty() {
+            write!(f, ".{}", self.object)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// severity of the rule with the config, `None` if the rule is off
+fn severity(rule: &LintRule, config: &ForgeConfig) -> Option<Severity> {
+    match config.get_lint_severity(rule.name) {
+        None => Some(rule.severity),
+        Some(ForgeLintSeverity::Off) => None,
+        Some(ForgeLintSeverity::Warning) => Some(Severity::Warning),
+        Some(ForgeLintSeverity::Error) => Some(Severity::Error),
+    }
+}
+
+/// Estimated maximum bytes of a column value in a row, large objects count as their pointer.
+fn column_bytes(column: &ForgeSchemaColumn) -> u64 {
+    let data_type = column.data_type.to_lowercase();
+    let base = data_type.split(['(', ' ']).next().unwrap_or_default();
+    match base {
+        "tinyint" | "boolean" | "bool" | "year" => 1,
+        "smallint" => 2,
+        "mediumint" | "date" | "time" => 3,
+        "int" | "integer" | "float" | "real" => 4,
+        "bigint" | "double" | "datetime" | "timestamp" | "timestamptz" | "datetimetz" => 8,
+        "uuid" => 16,
+        "decimal" | "numeric" => u64::from(column.precision.unwrap_or(10)) / 2 + 1,
+        // 4 bytes per character of utf8mb4 and a length prefix
+        "char" | "varchar" | "character" | "binary" | "varbinary" => {
+            u64::from(column.length.unwrap_or(255)) * 4 + 2
+        }
+        _ => 12,
+    }
+}
+
+/// key parts of an index, expressions in place of their empty column
+fn key_parts(index: &crate::core::ForgeSchemaIndex) -> Vec<String> {
+    index
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            let prefix = index
+                .column_prefixes
+                .as_ref()
+                .and_then(|p| p.get(i).copied().flatten());
+            let part = index.expression(i).unwrap_or(column).to_lowercase();
+            match prefix {
+                Some(prefix) => format!("{part}({prefix})"),
+                None => part,
+            }
+        })
+        .collect()
+}
+
+/// type of a column as compared between foreign key ends
+fn key_type(column: &ForgeSchemaColumn) -> (String, bool, Option<u32>, Option<u32>) {
+    (
+        column.data_type.to_lowercase(),
+        column.is_unsigned,
+        column.precision,
+        column.scale,
+    )
+}
+
+fn check_table(
+    schema: &ForgeSchema,
+    table: &ForgeSchemaTable,
+    config: &ForgeConfig,
+    found: &mut Vec<LintFinding>,
+) {
+    let mut report = |rule: &LintRule, object: &str, message: String| {
+        if let Some(severity) = severity(rule, config) {
+            found.push(LintFinding {
+                rule: rule.name,
+                severity,
+                table: table.name.clone(),
+                object: object.to_string(),
+                message,
+            });
+        }
+    };
+
+    if !table.columns.iter().any(|c| c.is_primary_key) {
+        report(
+            &MISSING_PRIMARY_KEY,
+            "",
+            MISSING_PRIMARY_KEY.description.to_string(),
+        );
+    }
+
+    if RESERVED_WORDS.contains(&table.name.to_lowercase().as_str()) {
+        report(
+            &RESERVED_WORD,
+            "",
+            format!("table name {} is reserved", table.name),
+        );
+    }
+    for column in &table.columns {
+        if RESERVED_WORDS.contains(&column.name.to_lowercase().as_str()) {
+            report(
+                &RESERVED_WORD,
+                &column.name,
+                format!("column name {} is reserved", column.name),
+            );
+        }
+    }
+
+    let row_bytes: u64 = table.columns.iter().map(column_bytes).sum();
+    let max_row_bytes = config.get_lint_max_row_bytes();
+    if row_bytes > max_row_bytes {
+        report(
+            &ROW_SIZE,
+            "",
+            format!("rows take up to ~{row_bytes} bytes, more than {max_row_bytes}"),
+        );
+    }
+
+    let mut seen: HashMap<Vec<String>, &str> = HashMap::new();
+    for index in &table.indices {
+        let parts = key_parts(index);
+        match seen.get(&parts) {
+            Some(first) => report(
+                &DUPLICATE_INDEX,
+                &index.name,
+                format!("same key parts ({}) as index {first}", parts.join(", ")),
+            ),
+            None => {
+                seen.insert(parts, &index.name);
+            }
+        }
+    }
+
+    for fk in &table.foreign_keys {
+        let column = table.columns.iter().find(|c| c.name == fk.column);
+        // references outside of the schema cannot be compared
+        let referenced = schema
+            .tables
+            .iter()
+            .find(|t| t.name == fk.ref_table)
+            .and_then(|t| t.columns.iter().find(|c| c.name == fk.ref_column));
+        if let (Some(column), Some(referenced)) = (column, referenced)
+            && key_type(column) != key_type(referenced)
+        {
+            report(
+                &FOREIGN_KEY_TYPE_MISMATCH,
+                &fk.name,
+                format!(
+                    "{} is {}, {}.{} is {}",
+                    column.name,
+                    crate::ops::document::column_type(column),
+                    fk.ref_table,
+                    fk.ref_column,
+                    crate::ops::document::column_type(referenced)
+                ),
+            );
+        }
+    }
+}
+
+/// Checks the schema with all rules that are not off, in table order.
+#[must_use]
+pub fn lint_schema(schema: &ForgeSchema, config: &ForgeConfig) -> Vec<LintFinding> {
+    let mut found = Vec::new();
+    for table in &schema.tables {
+        check_table(schema, table, config, &mut found);
+    }
+    found
+}
+
+/// one line per finding and a summary
+#[must_use]
+pub fn lint_report(found: &[LintFinding]) -> String {
+    let mut out = String::new();
+    for finding in found {
+        let _ = writeln!(out, "{finding}");
+    }
+    let errors = found
+        .iter()
+        .filter(|f| f.severity == Severity::Error)
+        .count();
+    let _ = writeln!(out, "{} problem(s), {errors} error(s)", found.len());
+    out
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::core::{ForgeSchemaForeignKey, ForgeSchemaIndex};
+
+    fn schema() -> ForgeSchema {
+        let mut users = ForgeSchemaTable::new("users");
+        let mut id = ForgeSchemaColumn::new("id", "bigint");
+        id.is_primary_key = true;
+        id.is_unsigned = true;
+        users.columns.push(id);
+        let mut bio = ForgeSchemaColumn::new("bio", "varchar");
+        bio.length = Some(20_000);
+        users.columns.push(bio);
+
+        let mut log = ForgeSchemaTable::new("log");
+        log.columns.push(ForgeSchemaColumn::new("user", "integer"));
+        for name in ["idx_user", "idx_user_again"] {
+            log.indices.push(ForgeSchemaIndex {
+                name: name.to_string(),
+                columns: vec!["user".to_string()],
+                ..Default::default()
+            });
+        }
+        log.foreign_keys.push(ForgeSchemaForeignKey {
+            name: "fk_user".to_string(),
+            column: "user".to_string(),
+            ref_table: "users".to_string(),
+            ref_column: "id".to_string(),
+            ..Default::default()
+        });
+        ForgeSchema {
+            tables: vec![users, log],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn rules_report_their_findings() {
+        let found = lint_schema(&schema(), &ForgeConfig::default());
+        let lines: Vec<String> = found.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "warning [row_size] users: rows take up to ~80010 bytes, more than 65535",
+                "warning [missing_primary_key] log: table without primary key, verification and resume cannot read its rows in order",
+                "warning [reserved_word] log.user: column name user is reserved",
+                "warning [duplicate_index] log.idx_user_again: same key parts (user) as index idx_user",
+                "error [foreign_key_type_mismatch] log.fk_user: user is integer, users.id is bigint unsigned",
+            ]
+        );
+    }
+
+    #[test]
+    fn config_overrides_severities() {
+        let config: ForgeConfig = toml::from_str(
+            "[lint]\nmax_row_bytes = 100000\n[lint.rules]\nreserved_word = 'off'\nmissing_primary_key = 'error'\nforeign_key_type_mismatch = 'warning'",
+        )
+        .unwrap();
+        let found = lint_schema(&schema(), &config);
+        let rules: Vec<(&str, Severity)> = found.iter().map(|f| (f.rule, f.severity)).collect();
+        assert_eq!(
+            rules,
+            vec![
+                ("missing_primary_key", Severity::Error),
+                ("duplicate_index", Severity::Warning),
+                ("foreign_key_type_mismatch", Severity::Warning),
+            ]
+        );
+    }
+}
@@ -0,0 +1,511 @@
+//! Detection of type conversions that narrow the data.
+//!
+//! Every column of the source schema is compared with the column the target is written
+//! with (see [`convert_schema`]): unsigned integers without unsigned counterpart, shorter
+//! strings, fewer decimal digits or fractional seconds. `--strict-types` turns the
+//! warnings into an error before anything is written.
+
+use crate::core::{ForgeDialect, convert_schema};
+use crate::{DatabaseDriver, ForgeConfig, ForgeSchema, ForgeSchemaColumn};
+use serde::Serialize;
+use std::fmt;
+use std::fmt::Write as _;
+
+/// A column whose target type cannot hold every value of its source type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LossyConversion {
+    pub table: String,
+    pub column: String,
+    pub source_type: String,
+    pub target_type: String,
+    /// which values are lost, i.e. "values above 9223372036854775807 do not fit"
+    pub reason: String,
+}
+
+impl fmt::Display for LossyConversion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}: {} -> {}, {}",
+            self.table, self.column, self.source_type, self.target_type, self.reason
+        )
+    }
+}
+
+/// value range and precision of a type, as far as it matters for narrowing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    Integer {
+        bytes: u32,
+        unsigned: bool,
+    },
+    Float {
+        bytes: u32,
+    },
+    /// `None` is an unconstrained PostgreSQL numeric
+    Decimal {
+        digits: Option<(u32, u32)>,
+    },
+    /// maximum length in characters, `None` is unbounded
+    Text {
+        length: Option<u64>,
+    },
+    /// fractional second digits, `narrow_range` for the MySQL TIMESTAMP (1970-2038)
+    Temporal {
+        fraction: u32,
+        narrow_range: bool,
+    },
+    Other,
+}
+
+/// numbers in parentheses of a type like "varchar(255)" or "decimal(10,2)"
+fn type_params(type_name: &str) -> Vec<u32> {
+    type_name
+        .split_once('(')
+        .and_then(|(_, rest)| rest.split_once(')'))
+        .map(|(params, _)| {
+            params
+                .split(',')
+                .filter_map(|p| p.trim().parse().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// shape of a type name, parameters missing in the name are taken from the column
+fn shape(type_name: &str, column: &ForgeSchemaColumn, dialect: ForgeDialect) -> Shape {
+    let lower = type_name.to_lowercase();
+    let params = type_params(&lower);
+    let unsigned = column.is_unsigned || lower.split_whitespace().any(|w| w == "unsigned");
+    let base = lower
+        .split('(')
+        .next()
+        .unwrap_or_default()
+        .split_whitespace()
+        .filter(|w| *w != "unsigned" && *w != "zerofill")
+        .collect::<Vec<_>>()
+        .join(" ");
+    let length = params.first().copied().or(column.length);
+
+    let integer = |bytes| Shape::Integer { bytes, unsigned };
+    match base.as_str() {
+        "tinyint" | "int1" => integer(1),
+        "smallint" | "int2" | "smallserial" => integer(2),
+        "mediumint" | "int3" => integer(3),
+        "int" | "integer" | "int4" | "serial" => integer(4),
+        "bigint" | "int8" | "bigserial" => integer(8),
+        "real" | "float4" => Shape::Float { bytes: 4 },
+        "float" if dialect == ForgeDialect::Mysql => Shape::Float { bytes: 4 },
+        "float" | "double" | "double precision" | "float8" => Shape::Float { bytes: 8 },
+        "decimal" | "numeric" => {
+            let precision = params.first().copied().or(column.precision);
+            let scale = params.get(1).copied().or(column.scale).unwrap_or(0);
+            Shape::Decimal {
+                digits: precision.map(|p| (p, scale)),
+            }
+        }
+        "char" | "varchar" | "character" | "character varying" | "bpchar" => Shape::Text {
+            length: length.map(u64::from),
+        },
+        "tinytext" => Shape::Text { length: Some(255) },
+        "text" if dialect == ForgeDialect::Mysql => Shape::Text {
+            length: Some(65_535),
+        },
+        "mediumtext" => Shape::Text {
+            length: Some(16_777_215),
+        },
+        "text" | "longtext" | "citext" => Shape::Text { length: None },
+        "datetime"
+        | "timestamp"
+        | "timestamptz"
+        | "timestamp without time zone"
+        | "timestamp with time zone"
+        | "time"
+        | "time without time zone" => Shape::Temporal {
+            // MySQL defaults to whole seconds, PostgreSQL to microseconds
+            fraction: length.unwrap_or(match dialect {
+                ForgeDialect::Mysql => 0,
+                ForgeDialect::Postgres => 6,
+            }),
+            narrow_range: dialect == ForgeDialect::Mysql && base == "timestamp",
+        },
+        _ => Shape::Other,
+    }
+}
+
+/// decimal digits of the largest integer value
+fn integer_digits(bytes: u32, unsigned: bool) -> u32 {
+    match (bytes, unsigned) {
+        (1, _) => 3,
+        (2, _) => 5,
+        (3, _) => 8,
+        (4, _) => 10,
+        (_, false) => 19,
+        (_, true) => 20,
+    }
+}
+
+/// largest integer value
+fn integer_max(bytes: u32, unsigned: bool) -> u64 {
+    let bits = bytes * 8 - u32::from(!unsigned);
+    u64::MAX >> (64 - bits)
+}
+
+/// why values of `source` do not fit into `target`, `None` if the conversion keeps every value
+fn narrowing(source: Shape, target: Shape) -> Option<String> {
+    match (source, target) {
+        (
+            Shape::Integer {
+                bytes: sb,
+                unsigned: su,
+            },
+            Shape::Integer {
+                bytes: tb,
+                unsigned: tu,
+            },
+        ) => {
+            if !su && tu {
+                Some("negative values do not fit".to_string())
+            } else if tb < sb || (su && !tu && tb == sb) {
+                Some(format!("values above {} do not fit", integer_max(tb, tu)))
+            } else {
+                None
+            }
+        }
+        (Shape::Integer { bytes, .. }, Shape::Float { bytes: tb })
+            if (tb == 4 && bytes >= 3) || bytes == 8 =>
+        {
+            Some("large values lose precision".to_string())
+        }
+        (
+            Shape::Integer { bytes, unsigned },
+            Shape::Decimal {
+                digits: Some((p, s)),
+            },
+        ) if p - s.min(p) < integer_digits(bytes, unsigned) => Some(format!(
+            "values with more than {} digits do not fit",
+            p - s.min(p)
+        )),
+        (Shape::Float { bytes: 8 }, Shape::Float { bytes: 4 }) => {
+            Some("double precision values are rounded to single precision".to_string())
+        }
+        (Shape::Decimal { .. }, Shape::Float { .. }) => {
+            Some("decimal values are rounded to binary floating point".to_string())
+        }
+        (
+            Shape::Decimal { digits: None },
+            Shape::Decimal {
+                digits: Some((p, s)),
+            },
+        ) => Some(format!(
+            "values are limited to {p} digits with {s} decimals"
+        )),
+        (
+            Shape::Decimal {
+                digits: Some((sp, ss)),
+            },
+            Shape::Decimal {
+                digits: Some((tp, ts)),
+            },
+        ) => {
+            if ts < ss {
+                Some(format!("decimals beyond {ts} are rounded"))
+            } else if tp - ts.min(tp) < sp - ss.min(sp) {
+                Some(format!(
+                    "values with more than {} integer digits do not fit",
+                    tp - ts.min(tp)
+                ))
+            } else {
+                None
+            }
+        }
+        (Shape::Text { length: sl }, Shape::Text { length: Some(tl) })
+            if sl.is_none_or(|sl| sl > tl) =>
+        {
+            Some(format!("values longer than {tl} characters do not fit"))
+        }
+        (
+            Shape::Temporal {
+                fraction: sf,
+                narrow_range: sn,
+            },
+            Shape::Temporal {
+                fraction: tf,
+                narrow_range: tn,
+            },
+        ) => {
+            if tf < sf {
+                Some(format!("fractional seconds are cut to {tf} digits"))
+            } else if tn && !sn {
+                Some("dates outside of 1970-2038 do not fit".to_string())
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// target type with the parameters of the column, i.e. "varchar(255)" or "bigint unsigned"
+fn type_label(column: &ForgeSchemaColumn, shape: Shape) -> String {
+    let name = column.data_type.to_lowercase();
+    if name.contains('(') {
+        return name;
+    }
+    match shape {
+        Shape::Integer { unsigned: true, .. } => format!("{name} unsigned"),
+        Shape::Decimal {
+            digits: Some((p, s)),
+        } => format!("{name}({p},{s})"),
+        Shape::Text { length: Some(l) } if column.length.is_some() => format!("{name}({l})"),
+        Shape::Temporal { fraction, .. } => format!("{name}({fraction})"),
+        _ => name,
+    }
+}
+
+/// Columns of the schema whose conversion to the target dialect narrows the data, in table order.
+#[must_use]
+pub fn check_schema(
+    schema: &ForgeSchema,
+    config: &ForgeConfig,
+    target_dialect: ForgeDialect,
+) -> Vec<LossyConversion> {
+    let source_dialect = schema
+        .metadata
+        .source_system
+        .parse()
+        .unwrap_or(ForgeDialect::Mysql);
+    let converted = convert_schema(schema, config, target_dialect);
+
+    let mut found = Vec::new();
+    for (source_table, target_table) in schema.tables.iter().zip(&converted.tables) {
+        for (source, target) in source_table.columns.iter().zip(&target_table.columns) {
+            let source_name = source.source_type.as_deref().unwrap_or(&source.data_type);
+            let source_shape = shape(source_name, source, source_dialect);
+            let target_shape = shape(&target.data_type, target, target_dialect);
+            if let Some(reason) = narrowing(source_shape, target_shape) {
+                found.push(LossyConversion {
+                    table: source_table.name.clone(),
+                    column: source.name.clone(),
+                    source_type: source_name.to_lowercase(),
+                    target_type: type_label(target, target_shape),
+                    reason,
+                });
+            }
+        }
+    }
+    found
+}
+
+/// Rows of an unsigned bigint column whose value exceeds the signed bigint of the target.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnsignedOverflow {
+    pub table: String,
+    pub column: String,
+    pub rows: u64,
+}
+
+/// Counts the values above 2^63 - 1 of the `bigint unsigned` columns written as signed bigint.
+///
+/// These rows are rejected by the target, `unsigned_bigint_to_numeric` in `[mysql.rules.on_read]`
+/// writes the columns as `numeric(20,0)` instead. Only columns with overflowing rows are returned.
+///
+/// # Errors
+///
+/// Returns an error if a column cannot be scanned.
+pub async fn scan_unsigned_overflow(
+    source: &dyn DatabaseDriver,
+    schema: &ForgeSchema,
+    config: &ForgeConfig,
+    target_dialect: ForgeDialect,
+) -> Result<Vec<UnsignedOverflow>, Box<dyn std::error::Error>> {
+    let source_dialect = schema
+        .metadata
+        .source_system
+        .parse()
+        .unwrap_or(ForgeDialect::Mysql);
+    let converted = convert_schema(schema, config, target_dialect);
+
+    let mut found = Vec::new();
+    for (source_table, target_table) in schema.tables.iter().zip(&converted.tables) {
+        for (column, target) in source_table.columns.iter().zip(&target_table.columns) {
+            let source_name = column.source_type.as_deref().unwrap_or(&column.data_type);
+            let unsigned_bigint = shape(source_name, column, source_dialect)
+                == Shape::Integer {
+                    bytes: 8,
+                    unsigned: true,
+                };
+            let signed_target = matches!(
+                shape(&target.data_type, target, target_dialect),
+                Shape::Integer {
+                    unsigned: false,
+                    ..
+                }
+            );
+            if !unsigned_bigint || !signed_target {
+                continue;
+            }
+            let rows = source
+                .count_unsigned_overflow(&source_table.name, &column.name)
+                .await?;
+            if rows > 0 {
+                found.push(UnsignedOverflow {
+                    table: source_table.name.clone(),
+                    column: column.name.clone(),
+                    rows,
+                });
+            }
+        }
+    }
+    Ok(found)
+}
+
+/// report of the overflowing unsigned bigint columns, empty if there are none
+#[must_use]
+pub fn unsigned_overflow_report(found: &[UnsignedOverflow]) -> String {
+    let mut out = String::new();
+    if found.is_empty() {
+        return out;
+    }
+    let _ = writeln!(out, "Unsigned bigint values above {}:", i64::MAX);
+    for overflow in found {
+        let _ = writeln!(
+            out,
+            "  {}.{}: {} rows",
+            overflow.table, overflow.column, overflow.rows
+        );
+    }
+    let _ = writeln!(
+        out,
+        "These rows are rejected by the target, set unsigned_bigint_to_numeric in [mysql.rules.on_read] to keep them."
+    );
+    out
+}
+
+/// report of the lossy conversions, empty if there are none
+#[must_use]
+pub fn lossy_types_report(found: &[LossyConversion]) -> String {
+    let mut out = String::new();
+    if found.is_empty() {
+        return out;
+    }
+    let _ = writeln!(out, "Lossy type conversions: {}", found.len());
+    for conversion in found {
+        let _ = writeln!(out, "  {conversion}");
+    }
+    out
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::ForgeSchemaTable;
+
+    fn column(name: &str, source_type: &str, data_type: &str) -> ForgeSchemaColumn {
+        let mut col = ForgeSchemaColumn::new(name, data_type);
+        col.source_type = Some(source_type.to_string());
+        col.length = type_params(source_type).first().copied();
+        col
+    }
+
+    fn mysql_schema(columns: Vec<ForgeSchemaColumn>) -> ForgeSchema {
+        let mut table = ForgeSchemaTable::new("t");
+        table.columns = columns;
+        let mut schema = ForgeSchema {
+            tables: vec![table],
+            ..ForgeSchema::default()
+        };
+        schema.metadata.source_system = "mysql".to_string();
+        schema
+    }
+
+    fn found(schema: &ForgeSchema, config: &str, dialect: ForgeDialect) -> Vec<String> {
+        let config: ForgeConfig = toml::from_str(config).unwrap();
+        check_schema(schema, &config, dialect)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn detects_unsigned_and_narrowed_integers() {
+        let schema = mysql_schema(vec![
+            column("big", "bigint(20) unsigned", "bigint"),
+            column("widened", "int(10) unsigned", "bigint"),
+            column("plain", "int(11)", "integer"),
+        ]);
+        assert_eq!(
+            found(&schema, "", ForgeDialect::Postgres),
+            vec![
+                "t.big: bigint(20) unsigned -> bigint, values above 9223372036854775807 do not fit"
+            ]
+        );
+
+        // read as numeric(20,0)
+        let mut numeric = column("big", "bigint(20) unsigned", "numeric");
+        numeric.precision = Some(20);
+        numeric.scale = Some(0);
+        assert!(found(&mysql_schema(vec![numeric]), "", ForgeDialect::Postgres).is_empty());
+
+        // MySQL keeps the unsigned flag
+        let mut unsigned = column("big", "bigint(20) unsigned", "bigint");
+        unsigned.is_unsigned = true;
+        assert!(found(&mysql_schema(vec![unsigned]), "", ForgeDialect::Mysql).is_empty());
+    }
+
+    #[test]
+    fn detects_shorter_strings_and_fractional_seconds() {
+        let schema = mysql_schema(vec![
+            column("title", "varchar(500)", "varchar"),
+            column("created", "datetime(6)", "datetime"),
+            column("body", "text", "text"),
+        ]);
+        let config = "[tables.column_overrides.t]\n\
+            title = \"varchar(255)\"\ncreated = \"timestamp(0)\"\nbody = \"varchar(1000)\"";
+        assert_eq!(
+            found(&schema, config, ForgeDialect::Mysql),
+            vec![
+                "t.title: varchar(500) -> varchar(255), values longer than 255 characters do not fit",
+                "t.created: datetime(6) -> timestamp(0), fractional seconds are cut to 0 digits",
+                "t.body: text -> varchar(1000), values longer than 1000 characters do not fit",
+            ]
+        );
+        // PostgreSQL timestamps keep microseconds
+        assert!(found(&schema, "", ForgeDialect::Postgres).is_empty());
+    }
+
+    #[test]
+    fn detects_narrowed_numbers() {
+        let mut price = column("price", "decimal(12,4)", "numeric");
+        price.precision = Some(12);
+        price.scale = Some(4);
+        price.length = None;
+        let schema = mysql_schema(vec![price, column("ratio", "double", "double precision")]);
+        let config = "[tables.column_overrides.t]\nprice = \"numeric(10,2)\"\nratio = \"real\"";
+        assert_eq!(
+            found(&schema, config, ForgeDialect::Postgres),
+            vec![
+                "t.price: decimal(12,4) -> numeric(10,2), decimals beyond 2 are rounded",
+                "t.ratio: double -> real, double precision values are rounded to single precision",
+            ]
+        );
+    }
+
+    #[test]
+    fn report_lists_every_conversion() {
+        let conversion = LossyConversion {
+            table: "t".to_string(),
+            column: "big".to_string(),
+            source_type: "bigint unsigned".to_string(),
+            target_type: "bigint".to_string(),
+            reason: "values above 9223372036854775807 do not fit".to_string(),
+        };
+        assert_eq!(lossy_types_report(&[]), "");
+        assert_eq!(unsigned_overflow_report(&[]), "");
+        assert_eq!(
+            lossy_types_report(&[conversion]),
+            "Lossy type conversions: 1\n  t.big: bigint unsigned -> bigint, values above 9223372036854775807 do not fit\n"
+        );
+    }
+}
@@ -0,0 +1,459 @@
+//! Prometheus metrics for long-running replications.
+//!
+//! The counters are always recorded into a process wide registry (plain atomics, cheap enough
+//! for every chunk). They are only exposed if an endpoint is started with [`serve`],
+//! i.e. by the CLI option `--metrics-addr`.
+
+use crate::ForgeUniversalDataField;
+use indexmap::IndexMap;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tracing::{debug, info, warn};
+
+/// counters of one table
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TableMetrics {
+    pub rows_read: u64,
+    pub rows_written: u64,
+    pub rows_total: u64,
+    pub errors: u64,
+}
+
+/// Counters of a point in time, i.e. the start and end of a run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    pub tables: BTreeMap<String, TableMetrics>,
+    pub schema_changes: u64,
+}
+
+impl MetricsSnapshot {
+    /// counts recorded after `earlier`, the registry is shared by all runs of the process
+    #[must_use]
+    pub fn since(&self, earlier: &Self) -> Self {
+        let tables = self
+            .tables
+            .iter()
+            .map(|(name, now)| {
+                let before = earlier.tables.get(name).copied().unwrap_or_default();
+                let counts = TableMetrics {
+                    rows_read: now.rows_read.saturating_sub(before.rows_read),
+                    rows_written: now.rows_written.saturating_sub(before.rows_written),
+                    rows_total: now.rows_total,
+                    errors: now.errors.saturating_sub(before.errors),
+                };
+                (name.clone(), counts)
+            })
+            .collect();
+        Self {
+            tables,
+            schema_changes: self.schema_changes.saturating_sub(earlier.schema_changes),
+        }
+    }
+}
+
+/// process wide metrics registry
+#[derive(Debug, Default)]
+pub struct ReplicationMetrics {
+    tables: Mutex<BTreeMap<String, TableMetrics>>,
+    current_table: Mutex<Option<String>>,
+    bytes_transferred: AtomicU64,
+    errors: AtomicU64,
+    /// unix timestamp (seconds) of the last successful chunk write, 0 = nothing written yet
+    last_write: AtomicU64,
+    /// chunks read but not yet taken by an insert worker
+    queued_chunks: AtomicU64,
+    /// schema statements applied to the target
+    schema_changes: AtomicU64,
+}
+
+static METRICS: LazyLock<ReplicationMetrics> = LazyLock::new(ReplicationMetrics::default);
+
+/// the global registry used by replication and the drivers
+#[must_use]
+pub fn global() -> &'static ReplicationMetrics {
+    &METRICS
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// approximate payload size of a value, used for the bytes counter
+fn field_size(field: &ForgeUniversalDataField) -> u64 {
+    let size = match field {
+        ForgeUniversalDataField::Text(s) => s.len(),
+        ForgeUniversalDataField::Binary(b) => b.len(),
+        ForgeUniversalDataField::Json(j) => j.to_string().len(),
+        ForgeUniversalDataField::Array(a) => a.to_json().to_string().len(),
+        ForgeUniversalDataField::Null | ForgeUniversalDataField::ZeroDateTime => 0,
+        ForgeUniversalDataField::Boolean(_) => 1,
+        ForgeUniversalDataField::Year(_) | ForgeUniversalDataField::Date(_) => 4,
+        ForgeUniversalDataField::Uuid(_)
+        | ForgeUniversalDataField::Decimal(_)
+        | ForgeUniversalDataField::Inet(_) => 16,
+        _ => 8,
+    };
+    size as u64
+}
+
+/// approximate payload size of a row
+#[must_use]
+pub fn row_size(row: &IndexMap<String, ForgeUniversalDataField>) -> u64 {
+    row.values().map(field_size).sum()
+}
+
+/// escapes a label value for the prometheus text format
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// writes one metric family with a sample per table
+fn write_table_metric(
+    out: &mut String,
+    name: &str,
+    kind: &str,
+    help: &str,
+    tables: &BTreeMap<String, TableMetrics>,
+    value: impl Fn(&TableMetrics) -> u64,
+) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+    for (table, metrics) in tables {
+        let _ = writeln!(
+            out,
+            "{name}{{table=\"{}\"}} {}",
+            escape_label(table),
+            value(metrics)
+        );
+    }
+}
+
+impl ReplicationMetrics {
+    // a poisoned lock only means another thread panicked while counting, the numbers are still usable
+    fn lock_tables(&self) -> MutexGuard<'_, BTreeMap<String, TableMetrics>> {
+        self.tables
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn lock_current(&self) -> MutexGuard<'_, Option<String>> {
+        self.current_table
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// marks `table` as the table currently replicated, `rows_total` is the source row count
+    pub fn start_table(&self, table: &str, rows_total: u64) {
+        self.lock_tables()
+            .entry(table.to_string())
+            .or_default()
+            .rows_total = rows_total;
+        *self.lock_current() = Some(table.to_string());
+    }
+
+    pub fn finish_table(&self, table: &str) {
+        let mut current = self.lock_current();
+        if current.as_deref() == Some(table) {
+            *current = None;
+        }
+    }
+
+    pub fn record_rows_read(&self, table: &str, rows: u64, bytes: u64) {
+        self.lock_tables()
+            .entry(table.to_string())
+            .or_default()
+            .rows_read += rows;
+        self.bytes_transferred.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub fn record_rows_written(&self, table: &str, rows: u64) {
+        self.lock_tables()
+            .entry(table.to_string())
+            .or_default()
+            .rows_written += rows;
+        self.last_write.store(unix_now(), Ordering::Relaxed);
+    }
+
+    /// a chunk was put into the queue of the insert workers
+    pub fn record_chunk_queued(&self) {
+        self.queued_chunks.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// an insert worker took a chunk from the queue
+    pub fn record_chunk_dequeued(&self) {
+        let _ = self
+            .queued_chunks
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1));
+    }
+
+    #[must_use]
+    pub fn queued_chunks(&self) -> u64 {
+        self.queued_chunks.load(Ordering::Relaxed)
+    }
+
+    pub fn record_error(&self, table: &str) {
+        self.lock_tables()
+            .entry(table.to_string())
+            .or_default()
+            .errors += 1;
+        self.errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// statements of a schema apply were executed on the target
+    pub fn record_schema_changes(&self, statements: u64) {
+        self.schema_changes.fetch_add(statements, Ordering::Relaxed);
+    }
+
+    #[must_use]
+    pub fn schema_changes(&self) -> u64 {
+        self.schema_changes.load(Ordering::Relaxed)
+    }
+
+    #[must_use]
+    pub fn table(&self, table: &str) -> Option<TableMetrics> {
+        self.lock_tables().get(table).copied()
+    }
+
+    /// counters of the tables and schema changes at this moment
+    #[must_use]
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            tables: self.lock_tables().clone(),
+            schema_changes: self.schema_changes(),
+        }
+    }
+
+    #[must_use]
+    pub fn current_table(&self) -> Option<String> {
+        self.lock_current().clone()
+    }
+
+    #[must_use]
+    pub fn errors(&self) -> u64 {
+        self.errors.load(Ordering::Relaxed)
+    }
+
+    #[must_use]
+    pub fn bytes_transferred(&self) -> u64 {
+        self.bytes_transferred.load(Ordering::Relaxed)
+    }
+
+    /// seconds since the last successful chunk write, 0 if nothing was written yet
+    #[must_use]
+    pub fn lag_seconds(&self) -> u64 {
+        match self.last_write.load(Ordering::Relaxed) {
+            0 => 0,
+            last => unix_now().saturating_sub(last),
+        }
+    }
+
+    /// renders all metrics in the prometheus text exposition format
+    #[must_use]
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let tables = self.lock_tables().clone();
+
+        write_table_metric(
+            &mut out,
+            "fluxforge_rows_read_total",
+            "counter",
+            "Rows read from the source",
+            &tables,
+            |t| t.rows_read,
+        );
+        write_table_metric(
+            &mut out,
+            "fluxforge_rows_written_total",
+            "counter",
+            "Rows written to the target",
+            &tables,
+            |t| t.rows_written,
+        );
+        write_table_metric(
+            &mut out,
+            "fluxforge_table_rows",
+            "gauge",
+            "Row count of the source table at start of replication",
+            &tables,
+            |t| t.rows_total,
+        );
+        write_table_metric(
+            &mut out,
+            "fluxforge_table_errors_total",
+            "counter",
+            "Failed row inserts per table",
+            &tables,
+            |t| t.errors,
+        );
+
+        let _ = writeln!(out, "# HELP fluxforge_errors_total Failed row inserts");
+        let _ = writeln!(out, "# TYPE fluxforge_errors_total counter");
+        let _ = writeln!(out, "fluxforge_errors_total {}", self.errors());
+
+        let _ = writeln!(
+            out,
+            "# HELP fluxforge_bytes_transferred_total Approximate payload bytes read from the source"
+        );
+        let _ = writeln!(out, "# TYPE fluxforge_bytes_transferred_total counter");
+        let _ = writeln!(
+            out,
+            "fluxforge_bytes_transferred_total {}",
+            self.bytes_transferred()
+        );
+
+        let _ = writeln!(
+            out,
+            "# HELP fluxforge_current_table Table currently replicated (value is always 1)"
+        );
+        let _ = writeln!(out, "# TYPE fluxforge_current_table gauge");
+        if let Some(table) = self.current_table() {
+            let _ = writeln!(
+                out,
+                "fluxforge_current_table{{table=\"{}\"}} 1",
+                escape_label(&table)
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP fluxforge_queued_chunks Chunks read from the source and waiting for an insert worker"
+        );
+        let _ = writeln!(out, "# TYPE fluxforge_queued_chunks gauge");
+        let _ = writeln!(out, "fluxforge_queued_chunks {}", self.queued_chunks());
+
+        let _ = writeln!(
+            out,
+            "# HELP fluxforge_schema_changes_total Schema statements applied to the target"
+        );
+        let _ = writeln!(out, "# TYPE fluxforge_schema_changes_total counter");
+        let _ = writeln!(
+            out,
+            "fluxforge_schema_changes_total {}",
+            self.schema_changes()
+        );
+
+        let _ = writeln!(
+            out,
+            "# HELP fluxforge_replication_lag_seconds Seconds since the last successful chunk write"
+        );
+        let _ = writeln!(out, "# TYPE fluxforge_replication_lag_seconds gauge");
+        let _ = writeln!(
+            out,
+            "fluxforge_replication_lag_seconds {}",
+            self.lag_seconds()
+        );
+
+        out
+    }
+}
+
+/// Starts the metrics endpoint on `addr` in a background task.
+///
+/// Every HTTP request (usually `GET /metrics`) is answered with the current metrics.
+///
+/// # Errors
+///
+/// Returns an error if the address cannot be bound.
+pub async fn serve(addr: SocketAddr) -> Result<(), Box<dyn std::error::Error>> {
+    let listener = TcpListener::bind(addr).await?;
+    info!(%addr, "metrics endpoint listening");
+
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, peer) = match listener.accept().await {
+                Ok(conn) => conn,
+                Err(e) => {
+                    warn!(error = %e, "metrics endpoint accept failed");
+                    continue;
+                }
+            };
+
+            tokio::spawn(async move {
+                // we do not route, the request only has to be consumed
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf).await;
+
+                let body = global().render();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                if let Err(e) = stream.write_all(response.as_bytes()).await {
+                    debug!(%peer, error = %e, "metrics response failed");
+                }
+                let _ = stream.shutdown().await;
+            });
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_contains_table_counters() {
+        let metrics = ReplicationMetrics::default();
+        metrics.start_table("users", 10);
+        metrics.record_rows_read("users", 4, 128);
+        metrics.record_rows_written("users", 3);
+        metrics.record_error("users");
+
+        let text = metrics.render();
+        assert!(text.contains("fluxforge_rows_read_total{table=\"users\"} 4"));
+        assert!(text.contains("fluxforge_rows_written_total{table=\"users\"} 3"));
+        assert!(text.contains("fluxforge_table_rows{table=\"users\"} 10"));
+        assert!(text.contains("fluxforge_errors_total 1"));
+        assert!(text.contains("fluxforge_bytes_transferred_total 128"));
+        assert!(text.contains("fluxforge_current_table{table=\"users\"} 1"));
+
+        metrics.finish_table("users");
+        assert!(metrics.current_table().is_none());
+        assert!(!metrics.render().contains("fluxforge_current_table{"));
+    }
+
+    #[test]
+    fn queued_chunks_gauge() {
+        let metrics = ReplicationMetrics::default();
+        metrics.record_chunk_queued();
+        metrics.record_chunk_queued();
+        metrics.record_chunk_dequeued();
+        assert!(metrics.render().contains("fluxforge_queued_chunks 1\n"));
+
+        metrics.record_chunk_dequeued();
+        metrics.record_chunk_dequeued();
+        assert_eq!(metrics.queued_chunks(), 0);
+    }
+
+    #[test]
+    fn row_size_sums_field_sizes() {
+        let mut row = IndexMap::new();
+        row.insert(
+            "name".to_string(),
+            ForgeUniversalDataField::Text("abc".to_string()),
+        );
+        row.insert("id".to_string(), ForgeUniversalDataField::Integer(1));
+        row.insert("gone".to_string(), ForgeUniversalDataField::Null);
+        assert_eq!(row_size(&row), 11);
+    }
+
+    #[test]
+    fn escape_label_quotes() {
+        assert_eq!(escape_label("a\"b\\c"), "a\\\"b\\\\c");
+    }
+}
@@ -0,0 +1,288 @@
+//! Data dumps of `fluxforge dump-data` and `load-data`.
+//!
+//! Every row is one `ForgeUniversalDataTransferPacket` JSON line with the target table
+//! name, the format of `replicate --dump` and the dead-letter file. The rows are converted
+//! like in a replication before they are dumped, so a dump is loaded into tables created
+//! from the same schema and config (i.e. with `migrate`) without further mapping.
+//! Files ending with `.gz` are gzip compressed, dumps can be written to `s3://bucket/key`.
+
+use crate::ops::{Row, prepare_row, storage};
+use crate::{DatabaseDriver, ForgeConfig, ForgeSchema, ForgeUniversalDataTransferPacket};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use tokio::io::AsyncWriteExt;
+use tracing::{debug, info};
+
+/// rows per insert of `load-data`, the size of a replication chunk
+const LOAD_CHUNK_SIZE: usize = 1000;
+
+/// encoded bytes buffered before they are written to the file or object
+const WRITE_BUFFER_BYTES: usize = 1024 * 1024;
+
+/// Rows of a table dumped or loaded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableRows {
+    pub table_name: String,
+    pub rows: u64,
+}
+
+/// true for a gzip compressed dump, decided by the `.gz` extension
+#[must_use]
+pub fn is_gzip(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("gz"))
+}
+
+/// encoder of the lines of a dump, the bytes are taken out while the dump is written
+enum DumpEncoder {
+    Plain(Vec<u8>),
+    Gzip(GzEncoder<Vec<u8>>),
+}
+
+impl DumpEncoder {
+    /// encoder of the file, gzip compressed if the name ends with `.gz`
+    fn new(path: &Path) -> Self {
+        if is_gzip(path) {
+            Self::Gzip(GzEncoder::new(Vec::new(), Compression::default()))
+        } else {
+            Self::Plain(Vec::new())
+        }
+    }
+
+    /// encoded bytes ready to be written
+    fn buffered(&mut self) -> &mut Vec<u8> {
+        match self {
+            Self::Plain(bytes) => bytes,
+            Self::Gzip(encoder) => encoder.get_mut(),
+        }
+    }
+
+    /// remaining bytes including the end of the gzip stream
+    fn finish(self) -> std::io::Result<Vec<u8>> {
+        match self {
+            Self::Plain(bytes) => Ok(bytes),
+            Self::Gzip(encoder) => encoder.finish(),
+        }
+    }
+}
+
+impl Write for DumpEncoder {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Self::Plain(bytes) => Write::write(bytes, buf),
+            Self::Gzip(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Self::Plain(_) => Ok(()),
+            Self::Gzip(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// reader of a dump file, decompressed if the name ends with `.gz`
+fn open_reader(path: &Path) -> Result<Box<dyn BufRead>, Box<dyn std::error::Error>> {
+    let file =
+        std::fs::File::open(path).map_err(|e| format!("Error opening dump file {path:?}: {e}"))?;
+    if is_gzip(path) {
+        Ok(Box::new(BufReader::new(GzDecoder::new(file))))
+    } else {
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
+
+/// Dumps the rows of every table of the schema into one packet file (or S3 object),
+/// tables in schema order.
+///
+/// # Errors
+///
+/// Returns an error if a table cannot be read or the file cannot be written.
+pub async fn dump_data(
+    source: &dyn DatabaseDriver,
+    schema: &ForgeSchema,
+    config: &ForgeConfig,
+    path: &Path,
+) -> Result<Vec<TableRows>, Box<dyn std::error::Error>> {
+    let mut writer = storage::create(path).await?;
+    let mut encoder = DumpEncoder::new(path);
+    let mut dumped = Vec::with_capacity(schema.tables.len());
+    for table in &schema.tables {
+        let target_table = config.get_target_table_name(&table.name).to_string();
+        let mut stream = source.stream_table_data(&table.name).await?;
+        let mut rows = 0u64;
+        while let Some(row) = stream.next().await {
+            let mut row = row?;
+            prepare_row(table, config, &mut row);
+            let packet = ForgeUniversalDataTransferPacket {
+                t: target_table.clone(),
+                r: row,
+            };
+            serde_json::to_writer(&mut encoder, &packet)?;
+            encoder.write_all(b"\n")?;
+            rows += 1;
+            if encoder.buffered().len() >= WRITE_BUFFER_BYTES {
+                writer
+                    .write_all(&std::mem::take(encoder.buffered()))
+                    .await?;
+            }
+        }
+        info!(table = %table.name, rows, "dumped");
+        dumped.push(TableRows {
+            table_name: target_table,
+            rows,
+        });
+    }
+    writer.write_all(&encoder.finish()?).await?;
+    writer.shutdown().await?;
+    Ok(dumped)
+}
+
+/// inserts the rows of a chunk and counts them for their table
+async fn insert_rows(
+    target: &dyn DatabaseDriver,
+    table: String,
+    rows: Vec<Row>,
+    dry_run: bool,
+    halt_on_error: bool,
+    loaded: &mut Vec<TableRows>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let count = rows.len() as u64;
+    debug!(table = %table, rows = count, "loading chunk");
+    target
+        .insert_chunk(&table, dry_run, halt_on_error, rows)
+        .await?;
+    match loaded.last_mut() {
+        Some(last) if last.table_name == table => last.rows += count,
+        _ => loaded.push(TableRows {
+            table_name: table,
+            rows: count,
+        }),
+    }
+    Ok(())
+}
+
+/// Inserts the rows of a packet file into the tables of the target in file order.
+///
+/// The file is read line by line, consecutive rows of a table are inserted in chunks.
+/// Rows the target rejects are logged and recorded in the dead-letter file unless
+/// `halt_on_error` is set.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read, a line is no packet or an insert fails.
+pub async fn load_data(
+    target: &dyn DatabaseDriver,
+    path: &Path,
+    dry_run: bool,
+    halt_on_error: bool,
+) -> Result<Vec<TableRows>, Box<dyn std::error::Error>> {
+    let reader = open_reader(path)?;
+    let mut loaded: Vec<TableRows> = Vec::new();
+    let mut chunk: Option<(String, Vec<Row>)> = None;
+
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let packet: ForgeUniversalDataTransferPacket = serde_json::from_str(&line)
+            .map_err(|e| format!("Error parsing dump file {path:?} line {}: {e}", number + 1))?;
+        match chunk {
+            Some((ref table, ref mut rows))
+                if *table == packet.t && rows.len() < LOAD_CHUNK_SIZE =>
+            {
+                rows.push(packet.r);
+            }
+            _ => {
+                if let Some((table, rows)) = chunk.replace((packet.t, vec![packet.r])) {
+                    insert_rows(target, table, rows, dry_run, halt_on_error, &mut loaded).await?;
+                }
+            }
+        }
+    }
+    if let Some((table, rows)) = chunk {
+        insert_rows(target, table, rows, dry_run, halt_on_error, &mut loaded).await?;
+    }
+    Ok(loaded)
+}
+
+/// report of the dumped or loaded rows per table
+#[must_use]
+pub fn rows_report(action: &str, tables: &[TableRows]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{action} {} rows of {} tables",
+        tables.iter().map(|t| t.rows).sum::<u64>(),
+        tables.len()
+    );
+    for table in tables {
+        let _ = writeln!(out, "  {}: {} rows", table.table_name, table.rows);
+    }
+    out
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::ForgeUniversalDataField;
+    use indexmap::IndexMap;
+
+    #[test]
+    fn gzip_is_decided_by_the_extension() {
+        assert!(is_gzip(Path::new("shop.ndjson.gz")));
+        assert!(is_gzip(Path::new("shop.GZ")));
+        assert!(!is_gzip(Path::new("shop.ndjson")));
+    }
+
+    #[test]
+    fn compressed_files_are_read_back() {
+        let path = std::env::temp_dir().join(format!("fluxforge-{}.ndjson.gz", std::process::id()));
+        let packet = ForgeUniversalDataTransferPacket {
+            t: "users".to_string(),
+            r: IndexMap::from([("id".to_string(), ForgeUniversalDataField::Integer(7))]),
+        };
+        let mut encoder = DumpEncoder::new(&path);
+        serde_json::to_writer(&mut encoder, &packet).unwrap();
+        encoder.write_all(b"\n").unwrap();
+        // bytes taken out early are part of the stream
+        let mut bytes = std::mem::take(encoder.buffered());
+        bytes.extend(encoder.finish().unwrap());
+        std::fs::write(&path, bytes).unwrap();
+
+        let lines: Vec<String> = open_reader(&path)
+            .unwrap()
+            .lines()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(lines, vec![r#"{"t":"users","r":{"id":{"Integer":7}}}"#]);
+    }
+
+    #[test]
+    fn report_sums_the_rows() {
+        let tables = vec![
+            TableRows {
+                table_name: "users".to_string(),
+                rows: 2,
+            },
+            TableRows {
+                table_name: "orders".to_string(),
+                rows: 3,
+            },
+        ];
+        assert_eq!(
+            rows_report("Dumped", &tables),
+            "Dumped 5 rows of 2 tables\n  users: 2 rows\n  orders: 3 rows\n"
+        );
+    }
+}
@@ -0,0 +1,415 @@
+//! Parquet files of `fluxforge export --format parquet` (feature `parquet`).
+//!
+//! The Arrow type of a column follows its schema type: integers, floats, booleans, dates,
+//! times and timestamps keep their type, decimals up to 38 digits become `Decimal128`,
+//! binary columns `Binary` and everything else (JSON, UUID, enums, arrays) text.
+
+use crate::core::ForgeParquetCompression;
+use crate::ops::Row;
+use crate::ops::export::{RowEncoder, target_columns};
+use crate::{ForgeConfig, ForgeSchemaColumn, ForgeSchemaTable, ForgeUniversalDataField};
+use arrow_array::builder::{
+    ArrayBuilder, BinaryBuilder, BooleanBuilder, Date32Builder, Decimal128Builder, Float64Builder,
+    Int64Builder, StringBuilder, Time64MicrosecondBuilder, TimestampMicrosecondBuilder,
+    UInt64Builder,
+};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use chrono::{NaiveDate, Timelike};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+use rust_decimal::prelude::ToPrimitive;
+use std::sync::Arc;
+
+/// rows converted into one record batch, the writer collects them into row groups
+const BATCH_ROWS: usize = 10_000;
+
+/// largest precision of an Arrow `Decimal128`
+const MAX_DECIMAL_PRECISION: u32 = 38;
+
+/// Arrow type of a column.
+#[must_use]
+pub fn arrow_type(column: &ForgeSchemaColumn) -> DataType {
+    let data_type = column.data_type.to_lowercase();
+    let base = data_type.split('(').next().unwrap_or_default().trim();
+    match base {
+        "tinyint" | "smallint" | "mediumint" | "int" | "integer" | "bigint" | "int2" | "int4"
+        | "int8" | "smallserial" | "serial" | "bigserial" | "year" => {
+            if column.is_unsigned && base == "bigint" {
+                DataType::UInt64
+            } else {
+                DataType::Int64
+            }
+        }
+        "bool" | "boolean" => DataType::Boolean,
+        "float" | "double" | "real" | "double precision" | "float4" | "float8" => DataType::Float64,
+        "decimal" | "numeric" => match column.precision {
+            Some(p) if p <= MAX_DECIMAL_PRECISION => {
+                DataType::Decimal128(p as u8, column.scale.unwrap_or(0) as i8)
+            }
+            // PostgreSQL numeric without precision or MySQL decimal(65,30)
+            _ => DataType::Utf8,
+        },
+        "date" => DataType::Date32,
+        "time" | "time without time zone" | "time with time zone" | "timetz" => {
+            DataType::Time64(TimeUnit::Microsecond)
+        }
+        "datetime"
+        | "timestamp"
+        | "timestamptz"
+        | "timestamp without time zone"
+        | "timestamp with time zone"
+        | "datetimetz" => DataType::Timestamp(TimeUnit::Microsecond, None),
+        "binary" | "varbinary" | "tinyblob" | "blob" | "mediumblob" | "longblob" | "bytea" => {
+            DataType::Binary
+        }
+        _ => DataType::Utf8,
+    }
+}
+
+/// Arrow schema of a table with the target column names, every column is nullable.
+#[must_use]
+pub fn arrow_schema(table: &ForgeSchemaTable, config: &ForgeConfig) -> Schema {
+    let fields: Vec<Field> = table
+        .columns
+        .iter()
+        .zip(target_columns(table, config))
+        .map(|(column, name)| Field::new(name, arrow_type(column), true))
+        .collect();
+    Schema::new(fields)
+}
+
+/// error of a value the column type cannot hold
+fn mismatch(value: &ForgeUniversalDataField, data_type: &DataType) -> Box<dyn std::error::Error> {
+    format!("value {value} cannot be written as {data_type}").into()
+}
+
+/// appends a value to the builder of its column, NULL and zero dates are appended as NULL
+fn append(
+    builder: &mut dyn ArrayBuilder,
+    data_type: &DataType,
+    value: &ForgeUniversalDataField,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use ForgeUniversalDataField as V;
+
+    let null = matches!(value, V::Null | V::ZeroDateTime);
+    let any = builder.as_any_mut();
+    match data_type {
+        DataType::Int64 => {
+            let b = any.downcast_mut::<Int64Builder>().ok_or("builder type")?;
+            let v = match value {
+                _ if null => None,
+                V::Integer(i) => Some(*i),
+                V::UnsignedInteger(u) => Some(i64::try_from(*u)?),
+                V::Year(y) => Some(i64::from(*y)),
+                V::Boolean(b) => Some(i64::from(*b)),
+                V::Decimal(d) => Some(d.to_i64().ok_or_else(|| mismatch(value, data_type))?),
+                other => return Err(mismatch(other, data_type)),
+            };
+            b.append_option(v);
+        }
+        DataType::UInt64 => {
+            let b = any.downcast_mut::<UInt64Builder>().ok_or("builder type")?;
+            let v = match value {
+                _ if null => None,
+                V::UnsignedInteger(u) => Some(*u),
+                V::Integer(i) => Some(u64::try_from(*i)?),
+                V::Decimal(d) => Some(d.to_u64().ok_or_else(|| mismatch(value, data_type))?),
+                other => return Err(mismatch(other, data_type)),
+            };
+            b.append_option(v);
+        }
+        DataType::Boolean => {
+            let b = any.downcast_mut::<BooleanBuilder>().ok_or("builder type")?;
+            let v = match value {
+                _ if null => None,
+                V::Boolean(b) => Some(*b),
+                V::Integer(i) => Some(*i != 0),
+                other => return Err(mismatch(other, data_type)),
+            };
+            b.append_option(v);
+        }
+        DataType::Float64 => {
+            let b = any.downcast_mut::<Float64Builder>().ok_or("builder type")?;
+            let v = match value {
+                _ if null => None,
+                V::Float(f) => Some(*f),
+                V::Integer(i) => Some(*i as f64),
+                V::Decimal(d) => Some(d.to_f64().ok_or_else(|| mismatch(value, data_type))?),
+                other => return Err(mismatch(other, data_type)),
+            };
+            b.append_option(v);
+        }
+        DataType::Decimal128(_, scale) => {
+            let b = any
+                .downcast_mut::<Decimal128Builder>()
+                .ok_or("builder type")?;
+            let decimal = match value {
+                _ if null => None,
+                V::Decimal(d) => Some(*d),
+                V::Integer(i) => Some(rust_decimal::Decimal::from(*i)),
+                V::UnsignedInteger(u) => Some(rust_decimal::Decimal::from(*u)),
+                other => return Err(mismatch(other, data_type)),
+            };
+            b.append_option(decimal.map(|mut d| {
+                d.rescale(u32::try_from(*scale).unwrap_or(0));
+                d.mantissa()
+            }));
+        }
+        DataType::Date32 => {
+            let b = any.downcast_mut::<Date32Builder>().ok_or("builder type")?;
+            let v = match value {
+                _ if null => None,
+                V::Date(d) => Some(days_since_epoch(*d)),
+                V::DateTime(dt) => Some(days_since_epoch(dt.date())),
+                other => return Err(mismatch(other, data_type)),
+            };
+            b.append_option(v);
+        }
+        DataType::Time64(_) => {
+            let b = any
+                .downcast_mut::<Time64MicrosecondBuilder>()
+                .ok_or("builder type")?;
+            let v = match value {
+                _ if null => None,
+                V::Time(t) => Some(
+                    i64::from(t.num_seconds_from_midnight()) * 1_000_000
+                        + i64::from(t.nanosecond() / 1_000),
+                ),
+                other => return Err(mismatch(other, data_type)),
+            };
+            b.append_option(v);
+        }
+        DataType::Timestamp(_, _) => {
+            let b = any
+                .downcast_mut::<TimestampMicrosecondBuilder>()
+                .ok_or("builder type")?;
+            let v = match value {
+                _ if null => None,
+                V::DateTime(dt) => Some(dt.and_utc().timestamp_micros()),
+                V::Date(d) => d
+                    .and_hms_opt(0, 0, 0)
+                    .map(|dt| dt.and_utc().timestamp_micros()),
+                other => return Err(mismatch(other, data_type)),
+            };
+            b.append_option(v);
+        }
+        DataType::Binary => {
+            let b = any.downcast_mut::<BinaryBuilder>().ok_or("builder type")?;
+            match value {
+                _ if null => b.append_null(),
+                V::Binary(bytes) => b.append_value(bytes),
+                V::Text(text) => b.append_value(text.as_bytes()),
+                other => return Err(mismatch(other, data_type)),
+            }
+        }
+        _ => {
+            let b = any.downcast_mut::<StringBuilder>().ok_or("builder type")?;
+            if null {
+                b.append_null();
+            } else {
+                b.append_value(value.to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// days since 1970-01-01 of an Arrow `Date32`
+fn days_since_epoch(date: NaiveDate) -> i32 {
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap_or_default();
+    i32::try_from((date - epoch).num_days()).unwrap_or(i32::MAX)
+}
+
+/// empty builder of an Arrow type
+fn builder(data_type: &DataType) -> Box<dyn ArrayBuilder> {
+    match data_type {
+        DataType::Int64 => Box::new(Int64Builder::new()),
+        DataType::UInt64 => Box::new(UInt64Builder::new()),
+        DataType::Boolean => Box::new(BooleanBuilder::new()),
+        DataType::Float64 => Box::new(Float64Builder::new()),
+        DataType::Decimal128(p, s) => Box::new(
+            Decimal128Builder::new()
+                .with_precision_and_scale(*p, *s)
+                .unwrap_or_default(),
+        ),
+        DataType::Date32 => Box::new(Date32Builder::new()),
+        DataType::Time64(_) => Box::new(Time64MicrosecondBuilder::new()),
+        DataType::Timestamp(_, _) => Box::new(TimestampMicrosecondBuilder::new()),
+        DataType::Binary => Box::new(BinaryBuilder::new()),
+        _ => Box::new(StringBuilder::new()),
+    }
+}
+
+/// writer properties of `[export]`
+fn writer_properties(config: &ForgeConfig) -> WriterProperties {
+    let compression = match config.get_parquet_compression() {
+        ForgeParquetCompression::None => Compression::UNCOMPRESSED,
+        ForgeParquetCompression::Snappy => Compression::SNAPPY,
+        ForgeParquetCompression::Zstd => Compression::ZSTD(ZstdLevel::default()),
+    };
+    WriterProperties::builder()
+        .set_max_row_group_size(config.get_parquet_row_group_size())
+        .set_compression(compression)
+        .build()
+}
+
+/// Parquet file of a table, the rows are converted in batches of [`BATCH_ROWS`].
+pub struct ParquetEncoder {
+    schema: SchemaRef,
+    builders: Vec<Box<dyn ArrayBuilder>>,
+    rows: usize,
+    writer: Option<ArrowWriter<Vec<u8>>>,
+}
+
+impl ParquetEncoder {
+    /// # Errors
+    ///
+    /// Returns an error if the writer cannot be created for the schema of the table.
+    pub fn new(
+        table: &ForgeSchemaTable,
+        config: &ForgeConfig,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let schema = Arc::new(arrow_schema(table, config));
+        let writer =
+            ArrowWriter::try_new(Vec::new(), schema.clone(), Some(writer_properties(config)))?;
+        Ok(Self {
+            builders: schema
+                .fields()
+                .iter()
+                .map(|f| builder(f.data_type()))
+                .collect(),
+            schema,
+            rows: 0,
+            writer: Some(writer),
+        })
+    }
+
+    /// writes the buffered rows as record batch and takes the bytes written so far
+    fn flush_batch(&mut self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let writer = self
+            .writer
+            .as_mut()
+            .ok_or("parquet file already finished")?;
+        if self.rows > 0 {
+            let columns: Vec<ArrayRef> = self.builders.iter_mut().map(|b| b.finish()).collect();
+            writer.write(&RecordBatch::try_new(self.schema.clone(), columns)?)?;
+            self.rows = 0;
+        }
+        // the writer counts the written bytes itself, taking them keeps the buffer small
+        Ok(std::mem::take(writer.inner_mut()))
+    }
+}
+
+impl RowEncoder for ParquetEncoder {
+    fn start(&mut self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        Ok(Vec::new())
+    }
+
+    fn encode(&mut self, row: &Row) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        for ((builder, field), value) in self
+            .builders
+            .iter_mut()
+            .zip(self.schema.fields())
+            .zip(row.values())
+        {
+            append(builder.as_mut(), field.data_type(), value)
+                .map_err(|e| format!("column {}: {e}", field.name()))?;
+        }
+        self.rows += 1;
+        if self.rows >= BATCH_ROWS {
+            return self.flush_batch();
+        }
+        Ok(Vec::new())
+    }
+
+    fn finish(&mut self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let mut bytes = self.flush_batch()?;
+        let writer = self.writer.take().ok_or("parquet file already finished")?;
+        bytes.extend(writer.into_inner()?);
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use indexmap::IndexMap;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    fn column(name: &str, data_type: &str) -> ForgeSchemaColumn {
+        ForgeSchemaColumn::new(name, data_type)
+    }
+
+    #[test]
+    fn maps_column_types() {
+        let mut price = column("price", "decimal");
+        price.precision = Some(12);
+        price.scale = Some(2);
+        let mut wide = price.clone();
+        wide.precision = Some(65);
+        let mut big = column("big", "bigint");
+        big.is_unsigned = true;
+
+        assert_eq!(arrow_type(&column("id", "int")), DataType::Int64);
+        assert_eq!(arrow_type(&big), DataType::UInt64);
+        assert_eq!(arrow_type(&price), DataType::Decimal128(12, 2));
+        assert_eq!(arrow_type(&wide), DataType::Utf8);
+        assert_eq!(
+            arrow_type(&column("created", "datetime")),
+            DataType::Timestamp(TimeUnit::Microsecond, None)
+        );
+        assert_eq!(arrow_type(&column("photo", "longblob")), DataType::Binary);
+        assert_eq!(arrow_type(&column("doc", "json")), DataType::Utf8);
+    }
+
+    #[test]
+    fn writes_row_groups_of_the_configured_size() {
+        let mut table = ForgeSchemaTable::new("users");
+        let mut id = column("id", "bigint");
+        id.is_primary_key = true;
+        table.columns = vec![id, column("name", "varchar"), column("born", "date")];
+        let config: ForgeConfig =
+            toml::from_str("[export]\nrow_group_size = 2\ncompression = 'zstd'").unwrap();
+
+        let mut encoder = ParquetEncoder::new(&table, &config).unwrap();
+        let mut bytes = encoder.start().unwrap();
+        for i in 0..5 {
+            let row: Row = IndexMap::from([
+                ("id".to_string(), ForgeUniversalDataField::Integer(i)),
+                (
+                    "name".to_string(),
+                    ForgeUniversalDataField::Text(format!("user {i}")),
+                ),
+                ("born".to_string(), ForgeUniversalDataField::Null),
+            ]);
+            bytes.extend(encoder.encode(&row).unwrap());
+        }
+        bytes.extend(encoder.finish().unwrap());
+
+        let path = std::env::temp_dir().join(format!("fluxforge-{}.parquet", std::process::id()));
+        std::fs::write(&path, bytes).unwrap();
+        let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let metadata = reader.metadata();
+        assert_eq!(metadata.file_metadata().num_rows(), 5);
+        assert_eq!(metadata.num_row_groups(), 3);
+        assert!(matches!(
+            metadata.row_group(0).column(0).compression(),
+            Compression::ZSTD(_)
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rejects_values_the_column_cannot_hold() {
+        let error = append(
+            &mut Int64Builder::new(),
+            &DataType::Int64,
+            &ForgeUniversalDataField::Text("abc".to_string()),
+        )
+        .unwrap_err();
+        assert_eq!(error.to_string(), "value abc cannot be written as Int64");
+    }
+}
@@ -0,0 +1,285 @@
+//! Pipe mode of `fluxforge send` and `fluxforge receive`.
+//!
+//! `send` writes the target schema and the rows of every table as a stream of length-prefixed
+//! messages to stdout, `receive` reads them from stdin, creates the schema and inserts the rows,
+//! so two networks only connected through a jump host replicate with
+//! `fluxforge send ... | ssh host fluxforge receive ...`.
+//!
+//! The stream starts with [`PIPE_MAGIC`], every message is a 4-byte big-endian length followed
+//! by its JSON. A stream without the final [`PipeMessage::End`] was cut off by a failed sender.
+
+use crate::ops::ndjson::TableRows;
+use crate::ops::{Row, prepare_row};
+use crate::{DatabaseDriver, ForgeConfig, ForgeObjectFilter, ForgeSchema, MigrateOptions};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tracing::{debug, info};
+
+/// first bytes of a pipe stream, the digit is the version of the message format
+pub const PIPE_MAGIC: &[u8; 8] = b"FLUXPIP1";
+
+/// rows per message, the size of a replication chunk
+const ROWS_PER_MESSAGE: usize = 1000;
+
+/// upper limit of a message, a larger length is a corrupt stream
+const MAX_MESSAGE_BYTES: u32 = 1024 * 1024 * 1024;
+
+/// Message of a pipe stream.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum PipeMessage {
+    /// schema with the target names of tables and columns, always the first message
+    Schema(Box<ForgeSchema>),
+    /// rows of a table, keyed by the target column names
+    Rows { table: String, rows: Vec<Row> },
+    /// rows sent per table, the last message
+    End { tables: Vec<TableRows> },
+}
+
+/// Writes one length-prefixed message.
+///
+/// # Errors
+///
+/// Returns an error if the message cannot be encoded or written.
+pub async fn write_message<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    message: &PipeMessage,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let json = serde_json::to_vec(message)?;
+    let length = u32::try_from(json.len())
+        .ok()
+        .filter(|length| *length <= MAX_MESSAGE_BYTES)
+        .ok_or_else(|| format!("pipe message of {} bytes is too large", json.len()))?;
+    writer.write_all(&length.to_be_bytes()).await?;
+    writer.write_all(&json).await?;
+    Ok(())
+}
+
+/// Reads the next message, `None` at the end of the stream.
+///
+/// # Errors
+///
+/// Returns an error if the stream ends within a message or a message cannot be decoded.
+pub async fn read_message<R: AsyncRead + Unpin>(
+    reader: &mut R,
+) -> Result<Option<PipeMessage>, Box<dyn std::error::Error>> {
+    let mut length = [0u8; 4];
+    match reader.read_exact(&mut length).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+    let length = u32::from_be_bytes(length);
+    if length > MAX_MESSAGE_BYTES {
+        return Err(format!("corrupt pipe stream: message length {length}").into());
+    }
+    let mut json = vec![0u8; length as usize];
+    reader
+        .read_exact(&mut json)
+        .await
+        .map_err(|e| format!("pipe stream ended within a message: {e}"))?;
+    Ok(Some(serde_json::from_slice(&json)?))
+}
+
+/// Sends the target schema and the rows of every table of the source schema.
+///
+/// The rows are read with the source names and converted like in a replication,
+/// `target_schema` is the source schema with the renames of the config applied.
+///
+/// # Errors
+///
+/// Returns an error if a table cannot be read or the stream cannot be written.
+pub async fn send<W: AsyncWrite + Unpin>(
+    source: &dyn DatabaseDriver,
+    source_schema: &ForgeSchema,
+    target_schema: &ForgeSchema,
+    config: &ForgeConfig,
+    writer: &mut W,
+) -> Result<Vec<TableRows>, Box<dyn std::error::Error>> {
+    writer.write_all(PIPE_MAGIC).await?;
+    write_message(
+        writer,
+        &PipeMessage::Schema(Box::new(target_schema.clone())),
+    )
+    .await?;
+
+    let mut sent = Vec::with_capacity(source_schema.tables.len());
+    for table in &source_schema.tables {
+        let target_table = config.get_target_table_name(&table.name).to_string();
+        let mut stream = source.stream_table_data(&table.name).await?;
+        let mut rows = Vec::with_capacity(ROWS_PER_MESSAGE);
+        let mut count = 0u64;
+        while let Some(row) = stream.next().await {
+            let mut row = row?;
+            prepare_row(table, config, &mut row);
+            rows.push(row);
+            count += 1;
+            if rows.len() >= ROWS_PER_MESSAGE {
+                let message = PipeMessage::Rows {
+                    table: target_table.clone(),
+                    rows: std::mem::take(&mut rows),
+                };
+                write_message(writer, &message).await?;
+            }
+        }
+        if !rows.is_empty() {
+            let message = PipeMessage::Rows {
+                table: target_table.clone(),
+                rows,
+            };
+            write_message(writer, &message).await?;
+        }
+        info!(table = %table.name, rows = count, "sent");
+        sent.push(TableRows {
+            table_name: target_table,
+            rows: count,
+        });
+    }
+
+    write_message(
+        writer,
+        &PipeMessage::End {
+            tables: sent.clone(),
+        },
+    )
+    .await?;
+    writer.flush().await?;
+    Ok(sent)
+}
+
+/// Receives a pipe stream: creates the schema on the target and inserts the rows.
+///
+/// Rows the target rejects are logged and recorded in the dead-letter file unless
+/// `halt_on_error` is set.
+///
+/// # Errors
+///
+/// Returns an error if the stream is no pipe stream, ends before its end message,
+/// or the schema or an insert fails.
+pub async fn receive<R: AsyncRead + Unpin>(
+    target: &dyn DatabaseDriver,
+    config: &ForgeConfig,
+    reader: &mut R,
+    dry_run: bool,
+    halt_on_error: bool,
+) -> Result<Vec<TableRows>, Box<dyn std::error::Error>> {
+    let mut magic = [0u8; 8];
+    reader
+        .read_exact(&mut magic)
+        .await
+        .map_err(|e| format!("no pipe stream on stdin: {e}"))?;
+    if &magic != PIPE_MAGIC {
+        return Err("no pipe stream on stdin, expected the output of `fluxforge send`".into());
+    }
+
+    let mut received: Vec<TableRows> = Vec::new();
+    // the foreign keys are created after the rows
+    let options = MigrateOptions {
+        dry_run,
+        destructive: true,
+        defer_foreign_keys: true,
+        ..Default::default()
+    };
+    let mut applied_schema = None;
+    while let Some(message) = read_message(reader).await? {
+        match message {
+            PipeMessage::Schema(schema) => {
+                let statements = target
+                    .diff_and_apply_schema(
+                        &schema,
+                        config,
+                        &ForgeObjectFilter::default(),
+                        options.clone(),
+                    )
+                    .await?;
+                info!(statements = statements.len(), "schema applied");
+                applied_schema = Some(schema);
+            }
+            PipeMessage::Rows { .. } if applied_schema.is_none() => {
+                return Err("pipe stream sent rows before the schema".into());
+            }
+            PipeMessage::Rows { table, rows } => {
+                let count = rows.len() as u64;
+                debug!(table = %table, rows = count, "receiving chunk");
+                target
+                    .insert_chunk(&table, dry_run, halt_on_error, rows)
+                    .await?;
+                match received.last_mut() {
+                    Some(last) if last.table_name == table => last.rows += count,
+                    _ => received.push(TableRows {
+                        table_name: table,
+                        rows: count,
+                    }),
+                }
+            }
+            PipeMessage::End { tables } => {
+                let sent: u64 = tables.iter().map(|t| t.rows).sum();
+                let got: u64 = received.iter().map(|t| t.rows).sum();
+                if sent != got {
+                    return Err(
+                        format!("pipe stream lost rows: {sent} sent, {got} received").into(),
+                    );
+                }
+                if let Some(ref schema) = applied_schema {
+                    target
+                        .apply_foreign_keys(schema, config, &ForgeObjectFilter::default(), options)
+                        .await?;
+                }
+                return Ok(received);
+            }
+        }
+    }
+    Err("pipe stream ended before its end message, the sender failed".into())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::ForgeUniversalDataField;
+    use indexmap::IndexMap;
+
+    #[tokio::test]
+    async fn messages_survive_the_framing() {
+        let rows = PipeMessage::Rows {
+            table: "users".to_string(),
+            rows: vec![IndexMap::from([(
+                "id".to_string(),
+                ForgeUniversalDataField::Integer(7),
+            )])],
+        };
+        let end = PipeMessage::End {
+            tables: vec![TableRows {
+                table_name: "users".to_string(),
+                rows: 1,
+            }],
+        };
+        let mut stream = Vec::new();
+        write_message(&mut stream, &rows).await.unwrap();
+        write_message(&mut stream, &end).await.unwrap();
+
+        let mut reader = stream.as_slice();
+        match read_message(&mut reader).await.unwrap() {
+            Some(PipeMessage::Rows { table, rows }) => {
+                assert_eq!(table, "users");
+                assert_eq!(rows[0]["id"], ForgeUniversalDataField::Integer(7));
+            }
+            other => panic!("unexpected message {other:?}"),
+        }
+        match read_message(&mut reader).await.unwrap() {
+            Some(PipeMessage::End { tables }) => assert_eq!(tables[0].rows, 1),
+            other => panic!("unexpected message {other:?}"),
+        }
+        assert!(read_message(&mut reader).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn truncated_messages_are_errors() {
+        let mut stream = Vec::new();
+        write_message(&mut stream, &PipeMessage::End { tables: Vec::new() })
+            .await
+            .unwrap();
+        stream.truncate(stream.len() - 1);
+        assert!(read_message(&mut stream.as_slice()).await.is_err());
+    }
+}
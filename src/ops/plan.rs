@@ -0,0 +1,307 @@
+//! Schema change plans written by `migrate --dry-run --plan-out` and applied verbatim by
+//! `fluxforge apply-plan`.
+//!
+//! A plan holds the statements of a schema apply, each classified as additive, destructive
+//! or data-rewriting. It is written as JSON (`.json`, including the rollback statements)
+//! or as an SQL script whose statements are separated by marker comments, so statements
+//! containing `;` (i.e. trigger functions) are read back unchanged.
+
+use crate::ForgeRollbackPlan;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Marker comment in front of every statement of an SQL plan, followed by its kind.
+pub const STATEMENT_MARKER: &str = "-- fluxforge:statement ";
+
+/// Effect of a statement on the target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatementKind {
+    /// creates objects, existing data is untouched
+    Additive,
+    /// drops objects or deletes data
+    Destructive,
+    /// changes column types, the table data is rewritten and may be converted
+    DataRewriting,
+}
+
+impl fmt::Display for StatementKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Additive => write!(f, "additive"),
+            Self::Destructive => write!(f, "destructive"),
+            Self::DataRewriting => write!(f, "data_rewriting"),
+        }
+    }
+}
+
+impl std::str::FromStr for StatementKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "additive" => Ok(Self::Additive),
+            "destructive" => Ok(Self::Destructive),
+            "data_rewriting" => Ok(Self::DataRewriting),
+            other => Err(format!("unknown statement kind: {other}")),
+        }
+    }
+}
+
+/// One statement of a plan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlannedStatement {
+    pub kind: StatementKind,
+    pub sql: String,
+}
+
+/// Statements of a schema apply with the target they were planned against.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaPlan {
+    pub created_at: String,
+    /// Target URL without password
+    pub target: String,
+    pub statements: Vec<PlannedStatement>,
+    /// Statements reverting the plan, recorded in the audit log by `apply-plan` (JSON only)
+    #[serde(default)]
+    pub rollback: ForgeRollbackPlan,
+}
+
+impl SchemaPlan {
+    /// plan of the statements in execution order, classified by [`classify`]
+    #[must_use]
+    pub fn new(target: &str, statements: &[String], rollback: ForgeRollbackPlan) -> Self {
+        Self {
+            created_at: chrono::Local::now().to_rfc3339(),
+            target: target.to_string(),
+            statements: statements
+                .iter()
+                .map(|sql| PlannedStatement {
+                    kind: classify(sql),
+                    sql: sql.clone(),
+                })
+                .collect(),
+            rollback,
+        }
+    }
+
+    /// statements in execution order
+    #[must_use]
+    pub fn sql(&self) -> Vec<String> {
+        self.statements.iter().map(|s| s.sql.clone()).collect()
+    }
+
+    /// number of statements of a kind
+    #[must_use]
+    pub fn count(&self, kind: StatementKind) -> usize {
+        self.statements.iter().filter(|s| s.kind == kind).count()
+    }
+}
+
+/// Classifies a generated schema statement by its effect on the existing data.
+#[must_use]
+pub fn classify(sql: &str) -> StatementKind {
+    let upper = sql.trim_start().to_uppercase();
+    let words: Vec<&str> = upper.split_whitespace().collect();
+    let has = |pair: [&str; 2]| words.windows(2).any(|w| w == pair);
+
+    if upper.starts_with("DROP ")
+        || upper.starts_with("TRUNCATE ")
+        || upper.starts_with("DELETE ")
+        || has(["DROP", "COLUMN"])
+        || has(["DROP", "PARTITION"])
+    {
+        StatementKind::Destructive
+    } else if upper.starts_with("UPDATE ")
+        || has(["MODIFY", "COLUMN"])
+        || has(["CHANGE", "COLUMN"])
+        || (upper.starts_with("ALTER TABLE") && has(["ALTER", "COLUMN"]) && words.contains(&"TYPE"))
+    {
+        StatementKind::DataRewriting
+    } else {
+        StatementKind::Additive
+    }
+}
+
+/// Preview of the destructive statements, `None` if nothing is dropped.
+#[must_use]
+pub fn destructive_preview(statements: &[String]) -> Option<String> {
+    let dropped: Vec<&String> = statements
+        .iter()
+        .filter(|sql| classify(sql) == StatementKind::Destructive)
+        .collect();
+    if dropped.is_empty() {
+        return None;
+    }
+    let mut out = format!(
+        "{} destructive statement(s), the dropped tables, columns and indices lose their data:\n",
+        dropped.len()
+    );
+    for sql in dropped {
+        let _ = writeln!(out, "  {}", sql.trim_end().trim_end_matches(';'));
+    }
+    Some(out)
+}
+
+/// SQL script of a plan, every statement follows a marker comment with its kind.
+#[must_use]
+pub fn plan_sql(plan: &SchemaPlan) -> String {
+    let mut out = format!(
+        "-- FluxForge schema plan for {}, created {}\n-- {} additive, {} destructive, {} data-rewriting statements\n",
+        plan.target,
+        plan.created_at,
+        plan.count(StatementKind::Additive),
+        plan.count(StatementKind::Destructive),
+        plan.count(StatementKind::DataRewriting)
+    );
+    for statement in &plan.statements {
+        let sql = statement.sql.trim_end();
+        let _ = writeln!(out, "\n{STATEMENT_MARKER}{}", statement.kind);
+        if sql.ends_with(';') {
+            let _ = writeln!(out, "{sql}");
+        } else {
+            let _ = writeln!(out, "{sql};");
+        }
+    }
+    out
+}
+
+/// Plan file content, JSON for a `.json` path and the SQL script otherwise.
+///
+/// # Errors
+///
+/// Returns an error if the plan cannot be serialized.
+pub fn render_plan(plan: &SchemaPlan, path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    if is_json(path) {
+        Ok(serde_json::to_string_pretty(plan)? + "\n")
+    } else {
+        Ok(plan_sql(plan))
+    }
+}
+
+/// Reads a plan written by [`render_plan`].
+///
+/// # Errors
+///
+/// Returns an error if the JSON is invalid, or the SQL script has no statement markers.
+pub fn parse_plan(content: &str, path: &Path) -> Result<SchemaPlan, Box<dyn std::error::Error>> {
+    if is_json(path) {
+        return Ok(serde_json::from_str(content)?);
+    }
+
+    let mut plan = SchemaPlan::default();
+    let mut current: Option<(StatementKind, String)> = None;
+    for line in content.lines() {
+        if let Some(kind) = line.strip_prefix(STATEMENT_MARKER) {
+            plan.statements.extend(current.take().map(statement));
+            current = Some((kind.parse()?, String::new()));
+        } else if let Some((_, ref mut sql)) = current {
+            sql.push_str(line);
+            sql.push('\n');
+        } else if let Some((target, created_at)) = line
+            .strip_prefix("-- FluxForge schema plan for ")
+            .and_then(|rest| rest.split_once(", created "))
+        {
+            plan.target = target.to_string();
+            plan.created_at = created_at.to_string();
+        }
+    }
+    plan.statements.extend(current.map(statement));
+    if plan.statements.is_empty() {
+        return Err(format!("{path:?} contains no plan statements").into());
+    }
+    plan.rollback.irreversible.push(
+        "an SQL plan records no rollback statements, write a JSON plan to revert it".to_string(),
+    );
+    Ok(plan)
+}
+
+/// statement of an SQL plan without its terminating `;`
+fn statement((kind, sql): (StatementKind, String)) -> PlannedStatement {
+    let sql = sql.trim_end();
+    PlannedStatement {
+        kind,
+        sql: sql.strip_suffix(';').unwrap_or(sql).to_string(),
+    }
+}
+
+/// true for a `.json` plan path
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("json"))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statements_are_classified() {
+        assert_eq!(
+            classify("CREATE TABLE users (\n  id int4 NOT NULL\n)"),
+            StatementKind::Additive
+        );
+        assert_eq!(
+            classify("ALTER TABLE users ADD COLUMN email text NULL"),
+            StatementKind::Additive
+        );
+        assert_eq!(
+            classify("ALTER TABLE users ALTER COLUMN id TYPE int8, ALTER COLUMN id DROP NOT NULL"),
+            StatementKind::DataRewriting
+        );
+        assert_eq!(
+            classify("ALTER TABLE `users` MODIFY COLUMN `id` bigint NOT NULL;"),
+            StatementKind::DataRewriting
+        );
+        assert_eq!(
+            classify("ALTER TABLE users DROP COLUMN name"),
+            StatementKind::Destructive
+        );
+        assert_eq!(
+            classify("DROP INDEX IF EXISTS idx_name"),
+            StatementKind::Destructive
+        );
+
+        let statements = vec![
+            "ALTER TABLE users ADD COLUMN email text NULL".to_string(),
+            "DROP TABLE `orders`;".to_string(),
+        ];
+        assert_eq!(
+            destructive_preview(&statements).unwrap(),
+            "1 destructive statement(s), the dropped tables, columns and indices lose their data:\n  DROP TABLE `orders`\n"
+        );
+        assert_eq!(destructive_preview(&statements[..1]), None);
+    }
+
+    #[test]
+    fn sql_plan_roundtrips_statements_verbatim() {
+        let statements = vec![
+            "CREATE FUNCTION set_updated_at() RETURNS trigger AS $$\nBEGIN\n  NEW.updated_at = now();\n  RETURN NEW;\nEND;\n$$ LANGUAGE plpgsql".to_string(),
+            "ALTER TABLE `users` DROP COLUMN `name`;".to_string(),
+        ];
+        let plan = SchemaPlan::new(
+            "postgres://app@db/shop",
+            &statements,
+            ForgeRollbackPlan::default(),
+        );
+        let path = Path::new("plan.sql");
+        let parsed = parse_plan(&render_plan(&plan, path).unwrap(), path).unwrap();
+        assert_eq!(parsed.target, "postgres://app@db/shop");
+        assert_eq!(
+            parsed.sql(),
+            vec![
+                statements[0].clone(),
+                "ALTER TABLE `users` DROP COLUMN `name`".to_string()
+            ]
+        );
+        assert_eq!(parsed.statements[1].kind, StatementKind::Destructive);
+
+        let path = Path::new("plan.json");
+        let parsed = parse_plan(&render_plan(&plan, path).unwrap(), path).unwrap();
+        assert_eq!(parsed, plan);
+        assert!(parse_plan("SELECT 1;", Path::new("plan.sql")).is_err());
+    }
+}
@@ -0,0 +1,354 @@
+//! Progress events of a replication and the observers receiving them.
+//!
+//! Like the [`metrics`](super::metrics), the events are sent to a process wide list of
+//! observers: the replication emits them whether anyone listens or not, a daemon or
+//! a progress display subscribes for as long as it is interested.
+//!
+//! The displays of `--progress` are observers too, see [`display`].
+
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, MutexGuard};
+use std::time::Instant;
+
+/// One step of a replication.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    /// a table is copied next, `rows_total` is the source row count
+    TableStart { table: String, rows_total: u64 },
+    /// a chunk was written, `rows` counts all rows of the table written so far
+    ChunkDone { table: String, rows: u64 },
+    /// all rows of the table are copied
+    TableDone { table: String, rows: u64 },
+    /// the rows of the table are compared next, `rows_total` is the expected row count
+    VerifyStart { table: String, rows_total: u64 },
+    /// `rows` rows of the table are compared so far
+    VerifyProgress { table: String, rows: u64 },
+    /// all rows of the table matched
+    VerifyDone { table: String, rows: u64 },
+}
+
+impl ProgressEvent {
+    /// source table of the event
+    #[must_use]
+    pub fn table(&self) -> &str {
+        match self {
+            Self::TableStart { table, .. }
+            | Self::ChunkDone { table, .. }
+            | Self::TableDone { table, .. }
+            | Self::VerifyStart { table, .. }
+            | Self::VerifyProgress { table, .. }
+            | Self::VerifyDone { table, .. } => table,
+        }
+    }
+}
+
+/// Receiver of the progress events, called on the task of the replication.
+///
+/// Implementations must return quickly, a slow observer slows down the copy.
+pub trait ProgressObserver: Send + Sync {
+    fn on_event(&self, event: &ProgressEvent);
+}
+
+type Observers = Vec<(u64, Arc<dyn ProgressObserver>)>;
+
+static OBSERVERS: LazyLock<Mutex<Observers>> = LazyLock::new(Mutex::default);
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+// a poisoned lock only means an observer panicked, the list itself is intact
+fn lock_observers() -> MutexGuard<'static, Observers> {
+    OBSERVERS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Registration of an observer, removed when it is dropped.
+#[derive(Debug)]
+#[must_use = "the observer is removed when the subscription is dropped"]
+pub struct Subscription {
+    id: u64,
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        lock_observers().retain(|(id, _)| *id != self.id);
+    }
+}
+
+/// Sends all following events to `observer` until the subscription is dropped.
+pub fn subscribe(observer: Arc<dyn ProgressObserver>) -> Subscription {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    lock_observers().push((id, observer));
+    Subscription { id }
+}
+
+/// Sends an event to all observers.
+pub fn emit(event: &ProgressEvent) {
+    // the observers are called outside of the lock, they may subscribe themselves
+    let observers: Vec<Arc<dyn ProgressObserver>> = lock_observers()
+        .iter()
+        .map(|(_, observer)| Arc::clone(observer))
+        .collect();
+    for observer in observers {
+        observer.on_event(event);
+    }
+}
+
+/// Progress output of `--progress`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProgressMode {
+    /// no progress output, only errors
+    None,
+    /// a summary line per table, without control characters for CI logs
+    Plain,
+    /// a progress bar per table
+    #[default]
+    Bar,
+    /// the events as JSON lines, for dashboards
+    Events,
+}
+
+impl std::str::FromStr for ProgressMode {
+    type Err = String;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode.trim().to_lowercase().as_str() {
+            "none" => Ok(ProgressMode::None),
+            "plain" => Ok(ProgressMode::Plain),
+            "bar" => Ok(ProgressMode::Bar),
+            "events" => Ok(ProgressMode::Events),
+            other => Err(format!(
+                "unknown progress mode '{other}', expected none, plain, bar or events"
+            )),
+        }
+    }
+}
+
+/// Shows the progress on stderr in the given mode until the subscription is dropped.
+pub fn display(mode: ProgressMode) -> Option<Subscription> {
+    match mode {
+        ProgressMode::None => None,
+        ProgressMode::Plain => Some(subscribe(Arc::new(PlainDisplay::default()))),
+        ProgressMode::Bar => Some(subscribe(Arc::new(BarDisplay::default()))),
+        ProgressMode::Events => Some(subscribe(Arc::new(EventsDisplay))),
+    }
+}
+
+/// prints every event as a JSON line
+struct EventsDisplay;
+
+impl ProgressObserver for EventsDisplay {
+    fn on_event(&self, event: &ProgressEvent) {
+        if let Ok(line) = serde_json::to_string(event) {
+            eprintln!("{line}");
+        }
+    }
+}
+
+/// prints a line when a table is copied or verified
+#[derive(Default)]
+struct PlainDisplay {
+    started: Mutex<HashMap<String, Instant>>,
+}
+
+impl PlainDisplay {
+    /// line of a finished step, `None` for the events in between
+    fn line(&self, event: &ProgressEvent) -> Option<String> {
+        let mut started = self
+            .started
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        match event {
+            ProgressEvent::TableStart { table, .. } | ProgressEvent::VerifyStart { table, .. } => {
+                started.insert(table.clone(), Instant::now());
+                None
+            }
+            ProgressEvent::TableDone { table, rows } => {
+                let secs = started
+                    .remove(table)
+                    .map_or(0.0, |start| start.elapsed().as_secs_f64());
+                Some(format!("{table}: {rows} rows replicated in {secs:.1} s"))
+            }
+            ProgressEvent::VerifyDone { table, rows } => {
+                let secs = started
+                    .remove(table)
+                    .map_or(0.0, |start| start.elapsed().as_secs_f64());
+                Some(format!("{table}: {rows} rows verified in {secs:.1} s"))
+            }
+            ProgressEvent::ChunkDone { .. } | ProgressEvent::VerifyProgress { .. } => None,
+        }
+    }
+}
+
+impl ProgressObserver for PlainDisplay {
+    fn on_event(&self, event: &ProgressEvent) {
+        if let Some(line) = self.line(event) {
+            eprintln!("{line}");
+        }
+    }
+}
+
+/// a progress bar per table
+struct BarDisplay {
+    multi: MultiProgress,
+    style: ProgressStyle,
+    bars: Mutex<HashMap<String, ProgressBar>>,
+}
+
+impl Default for BarDisplay {
+    fn default() -> Self {
+        let style = ProgressStyle::with_template(
+            "{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {pos}/{len} rows ({msg}) {per_sec}",
+        )
+        .unwrap_or_else(|_| ProgressStyle::default_bar())
+        .progress_chars("#>-");
+        Self {
+            multi: MultiProgress::new(),
+            style,
+            bars: Mutex::default(),
+        }
+    }
+}
+
+impl BarDisplay {
+    fn start(&self, table: &str, rows_total: u64, message: String) {
+        let bar = self.multi.add(ProgressBar::new(rows_total));
+        bar.set_style(self.style.clone());
+        bar.set_message(message);
+        self.lock_bars().insert(table.to_string(), bar);
+    }
+
+    fn lock_bars(&self) -> MutexGuard<'_, HashMap<String, ProgressBar>> {
+        self.bars
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl ProgressObserver for BarDisplay {
+    fn on_event(&self, event: &ProgressEvent) {
+        match event {
+            ProgressEvent::TableStart { table, rows_total } => {
+                self.start(table, *rows_total, format!("Forging table: {table}"));
+            }
+            ProgressEvent::VerifyStart { table, rows_total } => {
+                self.start(table, *rows_total, format!("Verifying table: {table}"));
+            }
+            ProgressEvent::ChunkDone { table, rows }
+            | ProgressEvent::VerifyProgress { table, rows } => {
+                // chunks of concurrent workers may report out of order
+                if let Some(bar) = self.lock_bars().get(table)
+                    && *rows > bar.position()
+                {
+                    bar.set_position(*rows);
+                }
+            }
+            ProgressEvent::TableDone { table, rows } => {
+                if let Some(bar) = self.lock_bars().remove(table) {
+                    bar.finish_with_message(format!("Done: {table} ({rows} rows)"));
+                }
+            }
+            ProgressEvent::VerifyDone { table, rows } => {
+                if let Some(bar) = self.lock_bars().remove(table) {
+                    bar.set_position(*rows);
+                    bar.finish_with_message(format!("Verified: {table} ({rows} rows)"));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<ProgressEvent>>);
+
+    impl ProgressObserver for Recorder {
+        fn on_event(&self, event: &ProgressEvent) {
+            self.0.lock().unwrap().push(event.clone());
+        }
+    }
+
+    #[test]
+    fn observers_receive_events_until_unsubscribed() {
+        let recorder = Arc::new(Recorder::default());
+        let subscription = subscribe(recorder.clone());
+        let event = ProgressEvent::TableStart {
+            table: "progress_test".to_string(),
+            rows_total: 3,
+        };
+        emit(&event);
+        drop(subscription);
+        emit(&event);
+
+        // other tests emit events concurrently
+        let received: Vec<ProgressEvent> = recorder
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|e| e.table() == "progress_test")
+            .cloned()
+            .collect();
+        assert_eq!(received, vec![event]);
+        assert_eq!(
+            serde_json::to_string(&received[0]).unwrap(),
+            r#"{"event":"table_start","table":"progress_test","rows_total":3}"#
+        );
+    }
+
+    #[test]
+    fn events_serialize_to_json_lines() {
+        let chunk = ProgressEvent::ChunkDone {
+            table: "users".to_string(),
+            rows: 2000,
+        };
+        let verified = ProgressEvent::VerifyDone {
+            table: "users".to_string(),
+            rows: 2500,
+        };
+        assert_eq!(
+            serde_json::to_string(&chunk).unwrap(),
+            r#"{"event":"chunk_done","table":"users","rows":2000}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&verified).unwrap(),
+            r#"{"event":"verify_done","table":"users","rows":2500}"#
+        );
+        assert_eq!("events".parse(), Ok(ProgressMode::Events));
+    }
+
+    #[test]
+    fn plain_display_prints_a_line_per_finished_table() {
+        let display = PlainDisplay::default();
+        let table = "users".to_string();
+        let events = [
+            ProgressEvent::TableStart {
+                table: table.clone(),
+                rows_total: 2,
+            },
+            ProgressEvent::ChunkDone {
+                table: table.clone(),
+                rows: 2,
+            },
+            ProgressEvent::TableDone {
+                table: table.clone(),
+                rows: 2,
+            },
+            ProgressEvent::VerifyDone { table, rows: 2 },
+        ];
+        let lines: Vec<String> = events.iter().filter_map(|e| display.line(e)).collect();
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("users: 2 rows replicated in "));
+        assert!(lines[1].starts_with("users: 2 rows verified in "));
+        assert_eq!("none".parse(), Ok(ProgressMode::None));
+        assert!("quiet".parse::<ProgressMode>().is_err());
+    }
+}
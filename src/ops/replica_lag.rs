@@ -0,0 +1,107 @@
+//! Replication lag guard of reads from a replica (`[general.replica_lag]`).
+//!
+//! The lag of the source is checked before the replication starts and again every
+//! `check_interval_secs` while tables are copied. A lag above `max_secs` pauses the reads
+//! until the replica caught up, or fails the replication with `action = "abort"`.
+//! A source that is no replica fails the check, the config demands reads from a replica.
+
+use crate::core::ForgeReplicaLagAction;
+use crate::{DatabaseDriver, ForgeConfig};
+use std::error::Error;
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{debug, warn};
+
+/// Lag limit and the time of the last check.
+#[derive(Debug, Clone)]
+pub struct ReplicaLagGuard {
+    pub max_lag: Duration,
+    pub interval: Duration,
+    pub action: ForgeReplicaLagAction,
+    last_check: Option<Instant>,
+}
+
+impl ReplicaLagGuard {
+    /// guard of `[general.replica_lag]`, `None` without `max_secs`
+    #[must_use]
+    pub fn from_config(config: &ForgeConfig) -> Option<Self> {
+        let lag = config.general.as_ref()?.replica_lag.as_ref()?;
+        Some(Self {
+            max_lag: Duration::from_secs(lag.max_secs?),
+            interval: Duration::from_secs(lag.check_interval_secs.unwrap_or(30)),
+            action: lag.action.unwrap_or_default(),
+            last_check: None,
+        })
+    }
+
+    /// Checks the lag now, waits while it is too large unless the action is abort.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source is no replica, its lag cannot be measured or is
+    /// too large with `action = "abort"`.
+    pub async fn check(&mut self, source: &dyn DatabaseDriver) -> Result<(), Box<dyn Error>> {
+        loop {
+            let lag = source
+                .replication_lag()
+                .await?
+                .ok_or("[general.replica_lag] is set, but the source is no replica")?;
+            self.last_check = Some(Instant::now());
+            if lag <= self.max_lag {
+                debug!(lag_secs = lag.as_secs_f64(), "replica lag within the limit");
+                return Ok(());
+            }
+            match self.action {
+                ForgeReplicaLagAction::Abort => {
+                    return Err(format!(
+                        "replica lag of {:.0} s exceeds the limit of {} s",
+                        lag.as_secs_f64(),
+                        self.max_lag.as_secs()
+                    )
+                    .into());
+                }
+                ForgeReplicaLagAction::Pause => {
+                    warn!(
+                        lag_secs = lag.as_secs_f64(),
+                        max_secs = self.max_lag.as_secs(),
+                        "replica lag too large, pausing"
+                    );
+                    tokio::time::sleep(self.interval).await;
+                }
+            }
+        }
+    }
+
+    /// Checks the lag if the interval passed since the last check.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`check`](Self::check).
+    pub async fn check_due(&mut self, source: &dyn DatabaseDriver) -> Result<(), Box<dyn Error>> {
+        match self.last_check {
+            Some(last) if last.elapsed() < self.interval => Ok(()),
+            _ => self.check(source).await,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guard_needs_a_limit() {
+        assert!(ReplicaLagGuard::from_config(&ForgeConfig::default()).is_none());
+        let config: ForgeConfig =
+            toml::from_str("[general.replica_lag]\ncheck_interval_secs = 5").unwrap();
+        assert!(ReplicaLagGuard::from_config(&config).is_none());
+
+        let config: ForgeConfig =
+            toml::from_str("[general.replica_lag]\nmax_secs = 10\naction = 'abort'").unwrap();
+        let guard = ReplicaLagGuard::from_config(&config).unwrap();
+        assert_eq!(guard.max_lag, Duration::from_secs(10));
+        assert_eq!(guard.interval, Duration::from_secs(30));
+        assert_eq!(guard.action, ForgeReplicaLagAction::Abort);
+    }
+}
@@ -0,0 +1,389 @@
+//! Retry policy with exponential backoff for transient database errors.
+//!
+//! Dropped connections, pool timeouts, deadlocks and lock wait timeouts are retried,
+//! all other errors are returned immediately.
+
+use crate::{ForgeConfig, ForgeError};
+use futures::future::BoxFuture;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::error::Error;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::warn;
+
+/// Errors that can be classified as transient (worth another attempt).
+pub trait RetryableError: std::fmt::Display {
+    fn is_transient(&self) -> bool;
+}
+
+/// SQLSTATEs of transient errors: serialization failure/deadlock, connection exceptions
+/// and PostgreSQL shutdowns; MySQL numbers of deadlock and lock wait timeout
+fn is_transient_database_error(error: &dyn sqlx::error::DatabaseError) -> bool {
+    if let Some(mysql) = error.try_downcast_ref::<sqlx::mysql::MySqlDatabaseError>()
+        && matches!(mysql.number(), 1205 | 1213)
+    {
+        return true;
+    }
+    error.code().is_some_and(|code| {
+        code == "40001"
+            || code == "40P01"
+            || code.starts_with("08")
+            || matches!(code.as_ref(), "57P01" | "57P02" | "57P03")
+    })
+}
+
+impl RetryableError for sqlx::Error {
+    fn is_transient(&self) -> bool {
+        match self {
+            sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut => true,
+            sqlx::Error::Database(db) => is_transient_database_error(db.as_ref()),
+            _ => false,
+        }
+    }
+}
+
+impl RetryableError for ForgeError {
+    fn is_transient(&self) -> bool {
+        match self {
+            ForgeError::Database(e) | ForgeError::ColumnDecode { source: e, .. } => {
+                e.is_transient()
+            }
+            _ => false,
+        }
+    }
+}
+
+impl RetryableError for Box<dyn Error> {
+    fn is_transient(&self) -> bool {
+        if let Some(e) = self.downcast_ref::<sqlx::Error>() {
+            e.is_transient()
+        } else if let Some(e) = self.downcast_ref::<ForgeError>() {
+            e.is_transient()
+        } else {
+            false
+        }
+    }
+}
+
+/// Returns true for a lock timeout: PostgreSQL `lock_not_available` (`lock_timeout`) and the
+/// MySQL lock wait timeout (`lock_wait_timeout`, `innodb_lock_wait_timeout`)
+#[must_use]
+pub fn is_lock_timeout(error: &sqlx::Error) -> bool {
+    let sqlx::Error::Database(db) = error else {
+        return false;
+    };
+    if let Some(mysql) = db.try_downcast_ref::<sqlx::mysql::MySqlDatabaseError>() {
+        return mysql.number() == 1205;
+    }
+    db.code().is_some_and(|code| code == "55P03")
+}
+
+/// Random source of the jitter: the generator seeded by `[general] seed`, shared by the clones
+/// of a policy so a seeded run draws the same sequence, or the thread-local generator.
+#[derive(Debug, Clone, Default)]
+pub struct JitterRng(Option<Arc<Mutex<StdRng>>>);
+
+impl JitterRng {
+    #[must_use]
+    pub fn seeded(seed: u64) -> Self {
+        Self(Some(Arc::new(Mutex::new(StdRng::seed_from_u64(seed)))))
+    }
+
+    /// factor of 50-100% applied to a backoff
+    fn factor(&self) -> f64 {
+        match self.0 {
+            Some(ref rng) => rng
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .random_range(0.5..=1.0),
+            None => rand::random_range(0.5..=1.0),
+        }
+    }
+}
+
+impl PartialEq for JitterRng {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.0, &other.0) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (a, b) => a.is_none() && b.is_none(),
+        }
+    }
+}
+
+impl Eq for JitterRng {}
+
+/// How often and how long to wait before an operation is attempted again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// attempts including the first one, 1 disables retries
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// waits a random 50-100% of the backoff, so parallel workers do not retry in lockstep
+    pub jitter: bool,
+    /// table streams with a primary key reconnect after their last key (`resume_streams`)
+    pub resume_streams: bool,
+    pub rng: JitterRng,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            jitter: true,
+            resume_streams: true,
+            rng: JitterRng::default(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// policy of `[general.retry]`, unset options use the defaults
+    #[must_use]
+    pub fn from_config(config: &ForgeConfig) -> Self {
+        let defaults = Self::default();
+        let Some(retry) = config.general.as_ref().and_then(|g| g.retry.as_ref()) else {
+            return Self {
+                rng: config
+                    .get_run_seed()
+                    .map_or_else(JitterRng::default, JitterRng::seeded),
+                ..defaults
+            };
+        };
+        Self {
+            max_attempts: retry.max_attempts.unwrap_or(defaults.max_attempts).max(1),
+            initial_backoff: retry
+                .initial_backoff_ms
+                .map_or(defaults.initial_backoff, Duration::from_millis),
+            max_backoff: retry
+                .max_backoff_ms
+                .map_or(defaults.max_backoff, Duration::from_millis),
+            jitter: retry.jitter.unwrap_or(defaults.jitter),
+            resume_streams: config.is_resume_streams(),
+            rng: config
+                .get_run_seed()
+                .map_or_else(JitterRng::default, JitterRng::seeded),
+        }
+    }
+
+    /// policy of the schema statements: `[general.ddl] lock_retries` attempts with the backoff
+    /// of `[general.retry]`
+    #[must_use]
+    pub fn ddl_from_config(config: &ForgeConfig) -> Self {
+        Self {
+            max_attempts: config.get_ddl().lock_retries.unwrap_or(3).max(1),
+            ..Self::from_config(config)
+        }
+    }
+
+    /// whether an interrupted table stream is read again after its last key
+    #[must_use]
+    pub fn resumes_streams(&self) -> bool {
+        self.resume_streams && self.max_attempts > 1
+    }
+
+    /// waiting time after the failed attempt `attempt` (1-based), doubled per attempt
+    #[must_use]
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff);
+        if self.jitter {
+            backoff.mul_f64(self.rng.factor())
+        } else {
+            backoff
+        }
+    }
+
+    /// Runs `operation` until it succeeds, fails with a permanent error or the attempts are used up.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the last attempt.
+    pub async fn run<T, E, F, Fut>(&self, name: &str, mut operation: F) -> Result<T, E>
+    where
+        E: RetryableError,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match operation().await {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt < self.max_attempts && e.is_transient() => {
+                    warn!(operation = name, attempt, error = %e, "transient error, retrying");
+                }
+                Err(e) => return Err(e),
+            }
+            tokio::time::sleep(self.backoff(attempt)).await;
+            attempt += 1;
+        }
+    }
+
+    /// Runs the schema `operation` on `conn` again after a lock timeout, until it succeeds, fails
+    /// with another error or the attempts are used up.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the last attempt.
+    pub async fn run_on_lock_timeout<C, T, F>(
+        &self,
+        name: &str,
+        conn: &mut C,
+        mut operation: F,
+    ) -> Result<T, sqlx::Error>
+    where
+        C: ?Sized,
+        F: for<'c> FnMut(&'c mut C) -> BoxFuture<'c, Result<T, sqlx::Error>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match operation(conn).await {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt < self.max_attempts && is_lock_timeout(&e) => {
+                    warn!(operation = name, attempt, error = %e, "lock timeout, retrying");
+                }
+                Err(e) => return Err(e),
+            }
+            tokio::time::sleep(self.backoff(attempt)).await;
+            attempt += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(4),
+            jitter: false,
+            resume_streams: false,
+            rng: JitterRng::default(),
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_maximum() {
+        let policy = policy(5);
+        let waits: Vec<u128> = (1..=5).map(|a| policy.backoff(a).as_millis()).collect();
+        assert_eq!(waits, vec![1, 2, 4, 4, 4]);
+
+        let jittered = RetryPolicy {
+            jitter: true,
+            initial_backoff: Duration::from_millis(100),
+            ..policy
+        };
+        let wait = jittered.backoff(1);
+        assert!(wait >= Duration::from_millis(2) && wait <= Duration::from_millis(4));
+    }
+
+    #[test]
+    fn seeded_jitter_is_reproducible() {
+        let config: ForgeConfig =
+            toml::from_str("[general]\nseed = 7\n[general.retry]\nmax_backoff_ms = 60000").unwrap();
+        let waits = || {
+            let policy = RetryPolicy::from_config(&config);
+            (1..=4).map(|a| policy.backoff(a)).collect::<Vec<_>>()
+        };
+        assert_eq!(waits(), waits());
+
+        let defaults = RetryPolicy::from_config(&toml::from_str("[general]\nseed = 7").unwrap());
+        assert_ne!(defaults.rng, JitterRng::default());
+    }
+
+    #[test]
+    fn from_config_reads_general_retry() {
+        let config: ForgeConfig = toml::from_str(
+            "[general.retry]\nmax_attempts = 0\ninitial_backoff_ms = 200\njitter = false",
+        )
+        .unwrap();
+        let policy = RetryPolicy::from_config(&config);
+        assert_eq!(policy.max_attempts, 1);
+        assert_eq!(policy.initial_backoff, Duration::from_millis(200));
+        assert_eq!(policy.max_backoff, Duration::from_secs(30));
+        assert!(!policy.jitter);
+        assert!(!policy.resumes_streams());
+
+        let config: ForgeConfig =
+            toml::from_str("[general.retry]\nmax_attempts = 2\nresume_streams = true").unwrap();
+        assert!(RetryPolicy::from_config(&config).resumes_streams());
+        assert!(RetryPolicy::from_config(&ForgeConfig::default()).resumes_streams());
+        let config: ForgeConfig =
+            toml::from_str("[general.retry]\nresume_streams = false").unwrap();
+        assert!(!RetryPolicy::from_config(&config).resumes_streams());
+    }
+
+    #[test]
+    fn ddl_policy_reads_lock_retries() {
+        let config: ForgeConfig =
+            toml::from_str("[general.retry]\nmax_attempts = 7\n[general.ddl]\nlock_retries = 5")
+                .unwrap();
+        assert_eq!(RetryPolicy::ddl_from_config(&config).max_attempts, 5);
+        assert_eq!(
+            RetryPolicy::ddl_from_config(&ForgeConfig::default()).max_attempts,
+            3
+        );
+        assert!(!is_lock_timeout(&sqlx::Error::PoolTimedOut));
+    }
+
+    #[test]
+    fn classifies_transient_errors() {
+        assert!(sqlx::Error::PoolTimedOut.is_transient());
+        assert!(!sqlx::Error::RowNotFound.is_transient());
+
+        let io: Box<dyn Error> = Box::new(sqlx::Error::Io(std::io::Error::from(
+            std::io::ErrorKind::ConnectionReset,
+        )));
+        assert!(io.is_transient());
+        let other: Box<dyn Error> = "syntax error".into();
+        assert!(!other.is_transient());
+    }
+
+    #[tokio::test]
+    async fn run_retries_transient_errors_only() {
+        let calls = AtomicU32::new(0);
+        let result = policy(3)
+            .run("test", || async {
+                if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                    Err(sqlx::Error::PoolTimedOut)
+                } else {
+                    Ok(42)
+                }
+            })
+            .await;
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let calls = AtomicU32::new(0);
+        let result: Result<(), _> = policy(3)
+            .run("test", || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(sqlx::Error::PoolTimedOut)
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let calls = AtomicU32::new(0);
+        let result: Result<(), _> = policy(3)
+            .run("test", || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(sqlx::Error::RowNotFound)
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,169 @@
+//! Count-only verification: compares the row counts of source and target per table.
+
+use crate::{DatabaseDriver, ForgeConfig, ForgeSchema};
+use std::fmt::Write as _;
+use tracing::{info, warn};
+
+/// Row counts of one table on both sides.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableRowCount {
+    pub table: String,
+    pub source: u64,
+    pub target: u64,
+}
+
+impl TableRowCount {
+    #[must_use]
+    pub fn matches(&self) -> bool {
+        self.source == self.target
+    }
+}
+
+/// Error of a verification whose row counts differ, it carries all counts for the report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowCountMismatch {
+    pub counts: Vec<TableRowCount>,
+}
+
+impl std::fmt::Display for RowCountMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut mismatched: Vec<&str> = self
+            .counts
+            .iter()
+            .filter(|c| !c.matches())
+            .map(|c| c.table.as_str())
+            .collect();
+        mismatched.sort_unstable();
+        write!(
+            f,
+            "Verification failed: row counts differ in {} table(s): {}",
+            mismatched.len(),
+            mismatched.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for RowCountMismatch {}
+
+/// Returns the counts if all of them match.
+///
+/// # Errors
+///
+/// Returns a [`RowCountMismatch`] with all counts if a table has different row counts.
+pub fn check_row_counts(
+    counts: Vec<TableRowCount>,
+) -> Result<Vec<TableRowCount>, RowCountMismatch> {
+    if counts.iter().all(TableRowCount::matches) {
+        Ok(counts)
+    } else {
+        Err(RowCountMismatch { counts })
+    }
+}
+
+/// Counts the rows of all tables of `schema` on source and target (renamed by `[tables.renames]`).
+///
+/// # Errors
+///
+/// Returns an error if a table cannot be counted on one of the sides.
+pub async fn count_rows(
+    source: &dyn DatabaseDriver,
+    target: &dyn DatabaseDriver,
+    schema: &ForgeSchema,
+    config: &ForgeConfig,
+) -> Result<Vec<TableRowCount>, Box<dyn std::error::Error>> {
+    let mut counts = Vec::with_capacity(schema.tables.len());
+    for table in &schema.tables {
+        let (source_rows, target_rows) = futures::try_join!(
+            source.get_table_row_count(&table.name),
+            target.get_table_row_count(config.get_target_table_name(&table.name))
+        )?;
+        let count = TableRowCount {
+            table: table.name.clone(),
+            source: source_rows,
+            target: target_rows,
+        };
+        if count.matches() {
+            info!(table = %count.table, rows = count.source, "row counts match");
+        } else {
+            warn!(table = %count.table, source = count.source, target = count.target, "row counts differ");
+        }
+        counts.push(count);
+    }
+    Ok(counts)
+}
+
+/// summary table of the row counts
+#[must_use]
+pub fn row_counts_report(counts: &[TableRowCount]) -> String {
+    let width = counts
+        .iter()
+        .map(|c| c.table.len())
+        .chain(std::iter::once("Table".len()))
+        .max()
+        .unwrap_or_default();
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{:<width$}  {:>12}  {:>12}  Status",
+        "Table", "Source", "Target"
+    );
+    for c in counts {
+        let status = if c.matches() { "OK" } else { "MISMATCH" };
+        let _ = writeln!(
+            out,
+            "{:<width$}  {:>12}  {:>12}  {status}",
+            c.table, c.source, c.target
+        );
+    }
+    let mismatched = counts.iter().filter(|c| !c.matches()).count();
+    let _ = writeln!(
+        out,
+        "Row counts: {}/{} tables match",
+        counts.len() - mismatched,
+        counts.len()
+    );
+    out
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_lists_all_tables() {
+        let counts = vec![
+            TableRowCount {
+                table: "users".to_string(),
+                source: 100,
+                target: 100,
+            },
+            TableRowCount {
+                table: "order_items".to_string(),
+                source: 5000,
+                target: 4999,
+            },
+        ];
+
+        let report = row_counts_report(&counts);
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Table              Source        Target  Status",
+                "users                 100           100  OK",
+                "order_items          5000          4999  MISMATCH",
+                "Row counts: 1/2 tables match",
+            ]
+        );
+
+        let error = check_row_counts(counts.clone()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Verification failed: row counts differ in 1 table(s): order_items"
+        );
+        assert_eq!(error.counts, counts);
+        assert_eq!(check_row_counts(counts[..1].to_vec()).unwrap().len(), 1);
+    }
+}
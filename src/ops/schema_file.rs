@@ -0,0 +1,342 @@
+//! Versioned schema files, written by `extract` and read by `migrate` and `diff`.
+//!
+//! Schema files are JSON or YAML of the same [`ForgeSchema`] structures, YAML is read
+//! into the same JSON document, so upgrades and validation do not depend on the format.
+//!
+//! `metadata.format_version` identifies the layout of a schema file. Files of an older
+//! format are upgraded in memory when they are read, files of a newer format are rejected,
+//! this version would silently drop the fields it does not know. Files without a version
+//! (or version 0) are format 1, written before the version was recorded.
+
+use crate::core::{ForgeSchema, ForgeSchemaFormat};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::error::Error;
+use tracing::{info, warn};
+
+/// Format of the schema files written by this version.
+pub const SCHEMA_FORMAT_VERSION: u32 = 2;
+
+/// inserts the value if the object lacks the key
+fn insert_missing(object: &mut Map<String, Value>, key: &str, value: Value) {
+    object.entry(key).or_insert(value);
+}
+
+/// objects of the array under the key, none if it is missing
+fn objects_mut<'a>(
+    object: &'a mut Map<String, Value>,
+    key: &str,
+) -> impl Iterator<Item = &'a mut Map<String, Value>> {
+    object
+        .get_mut(key)
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object_mut)
+}
+
+/// Format 1 files may lack fields that format 2 requires, they get the defaults of the
+/// extraction: empty metadata, no indices and foreign keys, nullable columns.
+fn upgrade_from_v1(schema: &mut Map<String, Value>) {
+    if let Some(metadata) = schema.get_mut("metadata").and_then(Value::as_object_mut) {
+        for key in [
+            "source_system",
+            "source_database_name",
+            "created_at",
+            "forge_version",
+            "config_file",
+        ] {
+            insert_missing(metadata, key, Value::String(String::new()));
+        }
+    }
+    for table in objects_mut(schema, "tables") {
+        insert_missing(table, "indices", Value::Array(Vec::new()));
+        insert_missing(table, "foreign_keys", Value::Array(Vec::new()));
+        for column in objects_mut(table, "columns") {
+            insert_missing(column, "is_nullable", Value::Bool(true));
+            for key in ["is_primary_key", "is_unsigned", "auto_increment"] {
+                insert_missing(column, key, Value::Bool(false));
+            }
+        }
+        for index in objects_mut(table, "indices") {
+            insert_missing(index, "is_unique", Value::Bool(false));
+        }
+    }
+}
+
+/// shim upgrading a schema document of one format to the next format
+type UpgradeShim = fn(&mut Map<String, Value>);
+
+/// Upgrade shims by the format they upgrade from.
+const UPGRADES: [(u32, UpgradeShim); 1] = [(1, upgrade_from_v1)];
+
+/// Upgrades a schema document to the current format, returns its original format.
+///
+/// # Errors
+///
+/// Returns an error if the document is no schema, or its format is newer than
+/// [`SCHEMA_FORMAT_VERSION`] or invalid.
+pub fn upgrade(document: &mut Value) -> Result<u32, Box<dyn Error>> {
+    let schema = document
+        .as_object_mut()
+        .ok_or("schema file holds no JSON object")?;
+    let metadata = schema
+        .get_mut("metadata")
+        .and_then(Value::as_object_mut)
+        .ok_or("schema file has no metadata")?;
+    // 0 is the version of a schema built in memory without one
+    let version = match metadata.get("format_version") {
+        None => 1,
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| format!("invalid format_version {version} of the schema file"))?
+            .max(1),
+    };
+    if version > SCHEMA_FORMAT_VERSION {
+        let written_by = metadata
+            .get("forge_version")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown");
+        return Err(format!(
+            "schema file has format version {version} (written by FluxForge {written_by}), \
+             this version reads format {SCHEMA_FORMAT_VERSION} and older"
+        )
+        .into());
+    }
+    metadata.insert(
+        "format_version".to_string(),
+        Value::from(SCHEMA_FORMAT_VERSION),
+    );
+
+    for (from, shim) in UPGRADES {
+        if from >= version {
+            shim(schema);
+        }
+    }
+    Ok(version)
+}
+
+/// Reports the fields of the file that the schema does not have, they would be dropped.
+fn unknown_fields(file: &Value, parsed: &Value, path: &str, problems: &mut Vec<String>) {
+    match (file, parsed) {
+        (Value::Object(file), Value::Object(parsed)) => {
+            for (key, value) in file {
+                let field = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                match parsed.get(key) {
+                    Some(parsed_value) => unknown_fields(value, parsed_value, &field, problems),
+                    None => problems.push(format!("unknown field {field}")),
+                }
+            }
+        }
+        (Value::Array(file), Value::Array(parsed)) => {
+            for (i, (value, parsed_value)) in file.iter().zip(parsed).enumerate() {
+                unknown_fields(value, parsed_value, &format!("{path}[{i}]"), problems);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Consistency problems of a schema: duplicate names and references to missing columns.
+#[must_use]
+pub fn schema_problems(schema: &ForgeSchema) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut tables = HashSet::new();
+    for table in &schema.tables {
+        if table.name.is_empty() {
+            problems.push("table without name".to_string());
+        }
+        if !tables.insert(table.name.as_str()) {
+            problems.push(format!("duplicate table {}", table.name));
+        }
+        let mut columns = HashSet::new();
+        for column in &table.columns {
+            if !columns.insert(column.name.as_str()) {
+                problems.push(format!("duplicate column {}.{}", table.name, column.name));
+            }
+        }
+        for index in &table.indices {
+            // functional key parts have an empty column
+            for column in index.columns.iter().filter(|c| !c.is_empty()) {
+                if !columns.contains(column.as_str()) {
+                    problems.push(format!(
+                        "index {}.{} references the missing column {column}",
+                        table.name, index.name
+                    ));
+                }
+            }
+        }
+        for foreign_key in &table.foreign_keys {
+            if !columns.contains(foreign_key.column.as_str()) {
+                problems.push(format!(
+                    "foreign key {}.{} references the missing column {}",
+                    table.name, foreign_key.name, foreign_key.column
+                ));
+            }
+        }
+    }
+    problems
+}
+
+/// Serializes a schema in the file format.
+///
+/// # Errors
+///
+/// Returns an error if the schema cannot be serialized.
+pub fn serialize_schema(
+    schema: &ForgeSchema,
+    format: ForgeSchemaFormat,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    Ok(match format {
+        ForgeSchemaFormat::Json => serde_json::to_vec_pretty(schema)?,
+        ForgeSchemaFormat::Yaml => serde_yaml::to_string(schema)?.into_bytes(),
+    })
+}
+
+/// Parses a JSON or YAML schema file, files of an older format are upgraded.
+///
+/// `strict` rejects files with unknown fields or inconsistent tables, otherwise these
+/// problems are logged as warnings.
+///
+/// # Errors
+///
+/// Returns an error if the file is no valid schema JSON, has a newer format, or has
+/// problems and `strict` is set.
+pub fn parse_schema(bytes: &[u8], strict: bool) -> Result<ForgeSchema, Box<dyn Error>> {
+    // a JSON file starts with its object, anything else is YAML
+    let is_json = bytes
+        .iter()
+        .find(|b| !b.is_ascii_whitespace())
+        .is_some_and(|b| *b == b'{');
+    let mut document: Value = if is_json {
+        serde_json::from_slice(bytes)?
+    } else {
+        serde_yaml::from_slice(bytes)?
+    };
+    let version = upgrade(&mut document)?;
+    if version < SCHEMA_FORMAT_VERSION {
+        info!(
+            from = version,
+            to = SCHEMA_FORMAT_VERSION,
+            "schema file upgraded"
+        );
+    }
+    let schema: ForgeSchema = serde_json::from_value(document.clone())?;
+
+    let mut problems = Vec::new();
+    unknown_fields(
+        &document,
+        &serde_json::to_value(&schema)?,
+        "",
+        &mut problems,
+    );
+    problems.extend(schema_problems(&schema));
+    if strict && !problems.is_empty() {
+        return Err(format!("schema file is invalid: {}", problems.join("; ")).into());
+    }
+    for problem in &problems {
+        warn!(problem = %problem, "schema file");
+    }
+    Ok(schema)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn unversioned_files_are_upgraded() {
+        // a format 1 file without the flags of its columns
+        let file = json!({
+            "metadata": {"source_system": "mysql", "source_database_name": "shop"},
+            "tables": [{
+                "name": "users",
+                "columns": [{"name": "id", "data_type": "integer", "is_primary_key": true}]
+            }]
+        });
+        let schema = parse_schema(file.to_string().as_bytes(), true).unwrap();
+        assert_eq!(schema.metadata.format_version, SCHEMA_FORMAT_VERSION);
+        let column = &schema.tables[0].columns[0];
+        assert!(column.is_primary_key && column.is_nullable && !column.auto_increment);
+        assert!(schema.tables[0].indices.is_empty());
+    }
+
+    #[test]
+    fn newer_formats_and_strict_problems_are_rejected() {
+        let newer = json!({
+            "metadata": {"format_version": SCHEMA_FORMAT_VERSION + 1, "forge_version": "9.0.0"},
+            "tables": []
+        });
+        assert_eq!(
+            parse_schema(newer.to_string().as_bytes(), false)
+                .unwrap_err()
+                .to_string(),
+            format!(
+                "schema file has format version {} (written by FluxForge 9.0.0), \
+                 this version reads format {SCHEMA_FORMAT_VERSION} and older",
+                SCHEMA_FORMAT_VERSION + 1
+            )
+        );
+
+        let mut schema = ForgeSchema::new();
+        let mut table = crate::core::ForgeSchemaTable::new("users");
+        table
+            .columns
+            .push(crate::core::ForgeSchemaColumn::new("id", "integer"));
+        table.indices.push(crate::core::ForgeSchemaIndex {
+            name: "idx_mail".to_string(),
+            columns: vec!["mail".to_string()],
+            ..Default::default()
+        });
+        schema.tables.push(table);
+        let mut file = serde_json::to_value(&schema).unwrap();
+        file["tables"][0]["colums"] = json!([]);
+        let bytes = file.to_string();
+
+        assert_eq!(
+            parse_schema(bytes.as_bytes(), true)
+                .unwrap_err()
+                .to_string(),
+            "schema file is invalid: unknown field tables[0].colums; \
+             index users.idx_mail references the missing column mail"
+        );
+        assert_eq!(
+            parse_schema(bytes.as_bytes(), false).unwrap().tables.len(),
+            1
+        );
+    }
+
+    #[test]
+    fn yaml_files_round_trip() {
+        let mut schema = ForgeSchema::new();
+        schema.metadata.format_version = SCHEMA_FORMAT_VERSION;
+        schema.metadata.source_system = "mysql".to_string();
+        let mut table = crate::core::ForgeSchemaTable::new("users");
+        let mut id = crate::core::ForgeSchemaColumn::new("id", "integer");
+        id.is_primary_key = true;
+        id.comment = Some("key: \"quoted\"".to_string());
+        table.columns.push(id);
+        table.indices.push(crate::core::ForgeSchemaIndex {
+            name: "idx_id".to_string(),
+            columns: vec!["id".to_string()],
+            column_prefixes: Some(vec![None]),
+            ..Default::default()
+        });
+        schema.tables.push(table);
+
+        let yaml = serialize_schema(&schema, ForgeSchemaFormat::Yaml).unwrap();
+        assert!(String::from_utf8_lossy(&yaml).contains("- name: users"));
+        let parsed = parse_schema(&yaml, true).unwrap();
+        assert_eq!(
+            serde_json::to_value(&parsed).unwrap(),
+            serde_json::to_value(&schema).unwrap()
+        );
+    }
+}
@@ -0,0 +1,621 @@
+//! A replication from a source to a target URL, the orchestration of `fluxforge replicate`.
+//!
+//! [`ReplicationSession`] wires the drivers, the schema preparation, the schema changes of the
+//! target and the data copy that `replicate` runs. Library users call [`ReplicationSession::run`];
+//! the command line runs the same stages one by one, with its reports and prompts in between.
+//!
+//! ```no_run
+//! use fluxforge::ForgeConfig;
+//! use fluxforge::ops::session::ReplicationSession;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let outcome = ReplicationSession::new("mysql://app@db1/shop", "postgres://app@db2/shop")
+//!     .config(ForgeConfig::default())
+//!     .verify(true)
+//!     .run()
+//!     .await?;
+//! println!("{} tables replicated", outcome.tables);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::core::{
+    ForgeAppliedStatements, apply_column_overrides, apply_column_renames, apply_table_renames,
+    flatten_partitions,
+};
+use crate::ops::ReplicateOptions;
+use crate::ops::checkpoint::ReplicationCheckpoint;
+use crate::ops::progress::{self, ProgressObserver};
+use crate::ops::row_counts::TableRowCount;
+use crate::{
+    CancellationToken, DatabaseDriver, ForgeConfig, ForgeObjectFilter, ForgeSchema, MigrateOptions,
+    drivers,
+};
+use std::borrow::Cow;
+use std::error::Error;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Settings of one replication, built with the chained setters.
+pub struct ReplicationSession {
+    source: String,
+    target: String,
+    config: ForgeConfig,
+    options: ReplicateOptions,
+    require_empty_target: bool,
+    force_nonempty: bool,
+    truncate: bool,
+    schema_only: bool,
+    data_only: bool,
+    checkpoint: ReplicationCheckpoint,
+    applied: ForgeAppliedStatements,
+    observer: Option<Arc<dyn ProgressObserver>>,
+}
+
+/// Drivers and source schema of a connected session.
+pub struct ConnectedSession {
+    pub source: Box<dyn DatabaseDriver>,
+    pub target: Box<dyn DatabaseDriver>,
+    /// schema as read from the source, before the column overrides
+    pub source_schema: ForgeSchema,
+}
+
+/// Result of [`ReplicationSession::run`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplicationOutcome {
+    /// schema statements applied to the target (the foreign keys last), printed only by a dry run
+    pub statements: Vec<String>,
+    /// tables whose rows were copied, none for a schema only run
+    pub tables: usize,
+    /// row counts of the tables verified at the `counts` level
+    pub row_counts: Vec<TableRowCount>,
+}
+
+impl ReplicationSession {
+    /// Session from `source` to `target` with the default config, into an empty target.
+    #[must_use]
+    pub fn new(source: impl Into<String>, target: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            target: target.into(),
+            config: ForgeConfig::default(),
+            options: ReplicateOptions::default(),
+            require_empty_target: true,
+            force_nonempty: false,
+            truncate: false,
+            schema_only: false,
+            data_only: false,
+            checkpoint: ReplicationCheckpoint::new(),
+            applied: ForgeAppliedStatements::default(),
+            observer: None,
+        }
+    }
+
+    #[must_use]
+    pub fn config(mut self, config: ForgeConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// print the schema statements instead of applying them, no rows are written
+    #[must_use]
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.options.dry_run = dry_run;
+        self
+    }
+
+    #[must_use]
+    pub fn verbose(mut self, verbose: bool) -> Self {
+        self.options.verbose = verbose;
+        self
+    }
+
+    /// abort on the first rejected row instead of logging it
+    #[must_use]
+    pub fn halt_on_error(mut self, halt_on_error: bool) -> Self {
+        self.options.halt_on_error = halt_on_error;
+        self
+    }
+
+    /// compare the rows of source and target after the copy
+    #[must_use]
+    pub fn verify(mut self, verify: bool) -> Self {
+        self.options.verify = verify;
+        self
+    }
+
+    /// refuse a target with tables, the data loss protection of `replicate` (default)
+    #[must_use]
+    pub fn require_empty_target(mut self, require_empty_target: bool) -> Self {
+        self.require_empty_target = require_empty_target;
+        self
+    }
+
+    /// Allows a target with tables, i.e. a prepared database with roles and extensions.
+    ///
+    /// Tables of the replication that already have rows are refused, unless
+    /// [`truncate`](Self::truncate) is set too.
+    #[must_use]
+    pub fn force_nonempty(mut self, force_nonempty: bool) -> Self {
+        self.force_nonempty = force_nonempty;
+        self
+    }
+
+    /// delete the rows of target tables that already have data before the load (with `force_nonempty`)
+    #[must_use]
+    pub fn truncate(mut self, truncate: bool) -> Self {
+        self.truncate = truncate;
+        self
+    }
+
+    /// only create the tables of the target, with their foreign keys, no rows are copied
+    #[must_use]
+    pub fn schema_only(mut self, schema_only: bool) -> Self {
+        self.schema_only = schema_only;
+        self
+    }
+
+    /// Only copy the rows, into the tables of an earlier [`schema_only`](Self::schema_only) run.
+    ///
+    /// The target schema is not changed and may have tables, differences to the source
+    /// schema are logged as warnings. The constraints are disabled during the load if the
+    /// foreign keys form a cycle.
+    #[must_use]
+    pub fn data_only(mut self, data_only: bool) -> Self {
+        self.data_only = data_only;
+        self
+    }
+
+    /// write the rows to a newline-delimited JSON file too
+    #[must_use]
+    pub fn dump(mut self, dump: Option<PathBuf>) -> Self {
+        self.options.dump = dump;
+        self
+    }
+
+    /// continue an aborted run, its completed tables are skipped and the target may have tables
+    /// with rows
+    #[must_use]
+    pub fn resume(mut self, checkpoint: ReplicationCheckpoint) -> Self {
+        self.checkpoint = checkpoint;
+        self.require_empty_target = false;
+        self
+    }
+
+    /// Receives the progress events while the data is copied.
+    ///
+    /// The events of all replications of the process reach the observer, run one session
+    /// at a time to tell them apart.
+    #[must_use]
+    pub fn observer(mut self, observer: Arc<dyn ProgressObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Stops the schema apply before the next statement and the copy after the running chunks,
+    /// the run then fails with [`ForgeError::Cancelled`](crate::ForgeError::Cancelled).
+    #[must_use]
+    pub fn cancel(mut self, cancel: CancellationToken) -> Self {
+        self.options.cancel = cancel;
+        self
+    }
+
+    /// all flags of the copy at once
+    #[must_use]
+    pub fn options(mut self, options: ReplicateOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    #[must_use]
+    pub fn forge_config(&self) -> &ForgeConfig {
+        &self.config
+    }
+
+    /// completed tables, i.e. for a resume token after a failure
+    #[must_use]
+    pub fn checkpoint(&self) -> &ReplicationCheckpoint {
+        &self.checkpoint
+    }
+
+    /// schema statements executed so far, also those of a failed apply
+    #[must_use]
+    pub fn applied_statements(&self) -> Vec<String> {
+        self.applied.snapshot()
+    }
+
+    /// Connects to target and source and reads the source schema.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for an unsupported combination of databases, a failed connection,
+    /// a target with tables (unless allowed) or an unreadable source schema.
+    pub async fn connect(&self) -> Result<ConnectedSession, Box<dyn Error>> {
+        let source_type = drivers::database_system(&self.source);
+        let target_type = drivers::database_system(&self.target);
+        if !drivers::is_supported_combination(source_type, target_type) {
+            return Err(format!(
+                "ERROR: Combination {source_type} -> {target_type} is not allowed.\n\
+                 Allowed combinations are:\n\
+                 - mysql -> postgres\n\
+                 - mysql -> mysql\n\
+                 - postgres -> postgres\n\
+                 - postgres -> mysql"
+            )
+            .into());
+        }
+
+        if self.schema_only && self.data_only {
+            return Err("ERROR: schema only and data only exclude each other.".into());
+        }
+
+        let target = drivers::create_driver(&self.target, &self.config, false).await?;
+        // the rows of a data only run go into the tables of the schema run, a forced run
+        // checks the tables one by one in apply_schema
+        if self.require_empty_target
+            && !self.data_only
+            && !self.force_nonempty
+            && !target.db_is_empty().await?
+        {
+            return Err("ERROR: Target is not empty!  \
+                For data loss protection the replication is only allowed into an empty database."
+                .into());
+        }
+        let source = drivers::create_driver(&self.source, &self.config, true).await?;
+        let source_schema = source.fetch_schema(&self.config).await?;
+        Ok(ConnectedSession {
+            source,
+            target,
+            source_schema,
+        })
+    }
+
+    /// Applies the column overrides and the dependency order to the source schema and returns
+    /// the schema of the target, with the renamed tables and columns.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tables cannot be ordered by their foreign keys.
+    pub fn prepare(&self, source_schema: &mut ForgeSchema) -> Result<ForgeSchema, Box<dyn Error>> {
+        apply_column_overrides(source_schema, &self.config);
+        source_schema.tables = super::sort_tables_by_dependencies(source_schema)
+            .map_err(|e| format!("Circular Dependency Error: {e}"))?;
+
+        // tables are created with their target names, the data is read with the source names
+        let mut target_schema = source_schema.clone();
+        apply_column_renames(&mut target_schema, &self.config);
+        apply_table_renames(&mut target_schema, &self.config);
+        flatten_partitions(&mut target_schema, &self.config);
+        Ok(target_schema)
+    }
+
+    /// Brings the target schema in line, a dry run only returns the statements.
+    ///
+    /// With `force_nonempty`, existing tables of the replication that have rows are refused
+    /// first, or emptied with `truncate`.
+    ///
+    /// The foreign keys are left to [`apply_foreign_keys`](Self::apply_foreign_keys), so the
+    /// rows can be loaded in any order. A schema only run creates them at once, a data only
+    /// run changes nothing and warns about the differences.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a statement fails or a forced run finds tables with rows.
+    pub async fn apply_schema(
+        &self,
+        target: &dyn DatabaseDriver,
+        target_schema: &ForgeSchema,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        if self.require_empty_target && self.force_nonempty {
+            self.clear_loaded_tables(target, target_schema).await?;
+        }
+        if self.data_only {
+            let pending = target
+                .diff_and_apply_schema(
+                    target_schema,
+                    &self.config,
+                    &ForgeObjectFilter::default(),
+                    MigrateOptions {
+                        dry_run: true,
+                        ..Default::default()
+                    },
+                )
+                .await?;
+            if let Some(first) = pending.first() {
+                warn!(
+                    statements = pending.len(),
+                    first = %first,
+                    "target schema differs from the source, the rows are copied anyway"
+                );
+            }
+            return Ok(Vec::new());
+        }
+        let statements = target
+            .diff_and_apply_schema(
+                target_schema,
+                &self.config,
+                &ForgeObjectFilter::default(),
+                MigrateOptions {
+                    dry_run: self.options.dry_run,
+                    verbose: self.options.verbose,
+                    destructive: self.is_destructive(),
+                    defer_foreign_keys: !self.schema_only,
+                    cancel: self.options.cancel.clone(),
+                    applied: self.applied.clone(),
+                },
+            )
+            .await?;
+        if !self.options.dry_run {
+            super::metrics::global().record_schema_changes(statements.len() as u64);
+        }
+        Ok(statements)
+    }
+
+    /// only changes of an empty target may drop what the source lacks, a prepared or forced
+    /// target keeps its own tables and columns
+    fn is_destructive(&self) -> bool {
+        self.require_empty_target && !self.force_nonempty
+    }
+
+    /// Refuses the existing target tables of the replication that have rows, or deletes their
+    /// rows with `truncate` (a dry run only logs them).
+    async fn clear_loaded_tables(
+        &self,
+        target: &dyn DatabaseDriver,
+        target_schema: &ForgeSchema,
+    ) -> Result<(), Box<dyn Error>> {
+        let existing = target.fetch_schema(&self.config).await?;
+        let mut loaded = Vec::new();
+        for table in &target_schema.tables {
+            if !existing.tables.iter().any(|t| t.name == table.name) {
+                continue;
+            }
+            let rows = target.get_table_row_count(&table.name).await?;
+            if rows > 0 {
+                loaded.push((table.name.as_str(), rows));
+            }
+        }
+        if loaded.is_empty() {
+            return Ok(());
+        }
+        if !self.truncate {
+            let tables: Vec<String> = loaded
+                .iter()
+                .map(|(name, rows)| format!("{name} ({rows} rows)"))
+                .collect();
+            return Err(format!(
+                "ERROR: Target tables already contain data: {}.\n\
+                 Use --truncate to delete their rows before the load.",
+                tables.join(", ")
+            )
+            .into());
+        }
+        // referencing tables come later in the dependency order, so they are cleared first
+        for (name, rows) in loaded.into_iter().rev() {
+            if self.options.dry_run {
+                info!(
+                    table = name,
+                    rows, "dry run: rows would be deleted before the load"
+                );
+            } else {
+                info!(table = name, rows, "deleting the rows of the target table");
+                target.delete_table_data(name, None).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates the foreign keys of the target after the rows are loaded, a dry run only
+    /// returns the statements.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a statement fails, i.e. because loaded rows violate a foreign key.
+    pub async fn apply_foreign_keys(
+        &self,
+        target: &dyn DatabaseDriver,
+        target_schema: &ForgeSchema,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        // the schema run created them with the tables
+        if self.schema_only || self.data_only {
+            return Ok(Vec::new());
+        }
+        let statements = target
+            .apply_foreign_keys(
+                target_schema,
+                &self.config,
+                &ForgeObjectFilter::default(),
+                MigrateOptions {
+                    dry_run: self.options.dry_run,
+                    verbose: self.options.verbose,
+                    destructive: self.is_destructive(),
+                    defer_foreign_keys: false,
+                    cancel: self.options.cancel.clone(),
+                    applied: self.applied.clone(),
+                },
+            )
+            .await?;
+        if !self.options.dry_run {
+            super::metrics::global().record_schema_changes(statements.len() as u64);
+        }
+        Ok(statements)
+    }
+
+    /// Copies (and verifies) the rows of the prepared source schema, nothing for a schema only run.
+    /// Returns the row counts of the count-only verification.
+    ///
+    /// # Errors
+    ///
+    /// See [`replicate_data`](super::replicate_data).
+    pub async fn replicate(
+        &self,
+        source: &dyn DatabaseDriver,
+        target: &dyn DatabaseDriver,
+        source_schema: &ForgeSchema,
+    ) -> Result<Vec<TableRowCount>, Box<dyn Error>> {
+        if self.schema_only {
+            return Ok(Vec::new());
+        }
+        let _subscription = self.observer.clone().map(progress::subscribe);
+        super::replicate_data(
+            source,
+            target,
+            source_schema,
+            &self.load_config(source_schema),
+            ReplicateOptions {
+                // apply_foreign_keys creates them, a data only run finds them in the target
+                foreign_keys_after_load: !self.data_only,
+                ..self.options.clone()
+            },
+            &self.checkpoint,
+        )
+        .await
+    }
+
+    /// Config of the data load. A data only run finds the foreign keys in the target, those of a
+    /// cycle cannot be satisfied in any table order: their checks are disabled during the load.
+    fn load_config(&self, source_schema: &ForgeSchema) -> Cow<'_, ForgeConfig> {
+        if !self.data_only
+            || self.config.is_disable_constraints()
+            || super::deferred_foreign_keys(source_schema).is_empty()
+        {
+            return Cow::Borrowed(&self.config);
+        }
+        info!(
+            "the foreign keys of the target form a cycle, disabling the constraints for the load"
+        );
+        let mut config = self.config.clone();
+        config
+            .general
+            .get_or_insert_with(Default::default)
+            .disable_constraints = Some(true);
+        Cow::Owned(config)
+    }
+
+    /// Runs all stages: connect, prepare the schema, apply it to the target, copy the rows and
+    /// create the foreign keys.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the first failing stage.
+    pub async fn run(&self) -> Result<ReplicationOutcome, Box<dyn Error>> {
+        let ConnectedSession {
+            source,
+            target,
+            mut source_schema,
+        } = self.connect().await?;
+        let target_schema = self.prepare(&mut source_schema)?;
+        let mut statements = self.apply_schema(target.as_ref(), &target_schema).await?;
+        let row_counts = self
+            .replicate(source.as_ref(), target.as_ref(), &source_schema)
+            .await?;
+        statements.extend(
+            self.apply_foreign_keys(target.as_ref(), &target_schema)
+                .await?,
+        );
+        Ok(ReplicationOutcome {
+            statements,
+            tables: if self.schema_only {
+                0
+            } else {
+                source_schema.tables.len()
+            },
+            row_counts,
+        })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::ForgeSchemaTable;
+    use crate::core::ForgeSchemaColumn;
+
+    fn table(name: &str, references: Option<&str>) -> ForgeSchemaTable {
+        let mut table = ForgeSchemaTable {
+            name: name.to_string(),
+            columns: vec![ForgeSchemaColumn {
+                name: "name".to_string(),
+                data_type: "varchar".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        if let Some(referenced) = references {
+            table.foreign_keys.push(crate::ForgeSchemaForeignKey {
+                name: format!("fk_{name}"),
+                column: "name".to_string(),
+                ref_table: referenced.to_string(),
+                ref_column: "name".to_string(),
+                on_delete: None,
+                on_update: None,
+            });
+        }
+        table
+    }
+
+    #[test]
+    fn prepare_orders_the_tables_and_renames_the_target() {
+        let config: ForgeConfig = toml::from_str(
+            "[tables.renames]\nusers = \"accounts\"\n\
+             [tables.column_renames.users]\nname = \"full_name\"",
+        )
+        .unwrap();
+        let session = ReplicationSession::new("mysql://db1/shop", "postgres://db2/shop")
+            .config(config)
+            .verify(true);
+        let mut schema = ForgeSchema {
+            tables: vec![table("orders", Some("users")), table("users", None)],
+            ..Default::default()
+        };
+
+        let target = session.prepare(&mut schema).unwrap();
+
+        let names = |schema: &ForgeSchema| -> Vec<String> {
+            schema.tables.iter().map(|t| t.name.clone()).collect()
+        };
+        assert_eq!(names(&schema), ["users", "orders"]);
+        assert_eq!(names(&target), ["accounts", "orders"]);
+        assert_eq!(target.tables[0].columns[0].name, "full_name");
+        assert!(session.options.verify);
+    }
+
+    #[test]
+    fn data_only_disables_the_constraints_of_a_foreign_key_cycle() {
+        let cyclic = ForgeSchema {
+            tables: vec![
+                table("orders", Some("users")),
+                table("users", Some("orders")),
+            ],
+            ..Default::default()
+        };
+        let ordered = ForgeSchema {
+            tables: vec![table("orders", Some("users")), table("users", None)],
+            ..Default::default()
+        };
+        let session = ReplicationSession::new("mysql://db1/shop", "postgres://db2/shop");
+        assert!(!session.load_config(&cyclic).is_disable_constraints());
+
+        let session = session.data_only(true);
+        assert!(session.load_config(&cyclic).is_disable_constraints());
+        assert!(!session.load_config(&ordered).is_disable_constraints());
+    }
+
+    #[tokio::test]
+    async fn unsupported_combinations_are_refused() {
+        let session = ReplicationSession::new("sqlite://a.db", "postgres://db2/shop");
+        let err = session.connect().await.err().unwrap();
+        assert!(err.to_string().contains("is not allowed"));
+    }
+
+    #[tokio::test]
+    async fn schema_only_and_data_only_are_refused_together() {
+        let session = ReplicationSession::new("mysql://db1/shop", "postgres://db2/shop")
+            .schema_only(true)
+            .data_only(true);
+        let err = session.connect().await.err().unwrap();
+        assert!(err.to_string().contains("exclude each other"));
+    }
+}
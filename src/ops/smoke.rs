@@ -0,0 +1,183 @@
+//! Post-migration smoke tests.
+//!
+//! The queries of the `[[smoke_tests]]` config sections are run against the target after the
+//! data load, each must return the expected value.
+
+use crate::core::ForgeSmokeTest;
+use crate::{DatabaseDriver, ForgeConfig, ForgeSchema, ForgeUniversalDataField};
+use std::fmt::Write as _;
+use tracing::{info, warn};
+
+/// Result of one smoke test.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmokeTestResult {
+    pub name: String,
+    pub sql: String,
+    pub expected: String,
+    /// returned value, `None` if the query failed
+    pub actual: Option<String>,
+    pub error: Option<String>,
+    pub passed: bool,
+}
+
+fn expected_to_string(expected: &serde_json::Value) -> String {
+    match expected {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Null => "NULL".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// compares a query result with the expected value, numbers are compared numerically
+/// (i.e. expected `0` matches a returned `0.00` decimal)
+#[must_use]
+pub fn matches_expected(expected: &serde_json::Value, actual: &ForgeUniversalDataField) -> bool {
+    let expected = expected_to_string(expected);
+    let actual = actual.to_string();
+
+    match (expected.parse::<f64>(), actual.parse::<f64>()) {
+        (Ok(e), Ok(a)) => (e - a).abs() < f64::EPSILON,
+        _ => expected == actual,
+    }
+}
+
+/// Runs the configured smoke tests against `target`.
+///
+/// Tests bound to a table are skipped if the table is not part of `schema`.
+/// A failing query does not abort the run, it is reported as failed test.
+pub async fn run_smoke_tests(
+    target: &dyn DatabaseDriver,
+    schema: &ForgeSchema,
+    config: &ForgeConfig,
+) -> Vec<SmokeTestResult> {
+    let tests: &[ForgeSmokeTest] = config.smoke_tests.as_deref().unwrap_or_default();
+    let mut results = Vec::with_capacity(tests.len());
+
+    for test in tests {
+        if let Some(ref table) = test.table
+            && !schema.tables.iter().any(|t| &t.name == table)
+        {
+            info!(table = %table, sql = %test.sql, "smoke test skipped, table not replicated");
+            continue;
+        }
+
+        let name = test.name.clone().unwrap_or_else(|| test.sql.clone());
+        let expected = expected_to_string(&test.expect);
+
+        let result = match target.query_scalar(&test.sql).await {
+            Ok(value) => SmokeTestResult {
+                passed: matches_expected(&test.expect, &value),
+                actual: Some(value.to_string()),
+                error: None,
+                name,
+                sql: test.sql.clone(),
+                expected,
+            },
+            Err(e) => SmokeTestResult {
+                passed: false,
+                actual: None,
+                error: Some(e.to_string()),
+                name,
+                sql: test.sql.clone(),
+                expected,
+            },
+        };
+
+        if result.passed {
+            info!(test = %result.name, "smoke test passed");
+        } else {
+            warn!(test = %result.name, expected = %result.expected, actual = ?result.actual, error = ?result.error, "smoke test failed");
+        }
+        results.push(result);
+    }
+
+    results
+}
+
+/// report section for the smoke tests
+#[must_use]
+pub fn smoke_report(results: &[SmokeTestResult]) -> String {
+    let mut out = String::new();
+    let passed = results.iter().filter(|r| r.passed).count();
+    let _ = writeln!(out, "Smoke tests: {passed}/{} passed", results.len());
+
+    for r in results {
+        let status = if r.passed { "OK  " } else { "FAIL" };
+        let _ = write!(out, "  [{status}] {}", r.name);
+        if !r.passed {
+            match (&r.actual, &r.error) {
+                (_, Some(err)) => {
+                    let _ = write!(out, " (error: {err})");
+                }
+                (Some(actual), None) => {
+                    let _ = write!(out, " (expected {}, got {actual})", r.expected);
+                }
+                (None, None) => {}
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    #[test]
+    fn matches_expected_numbers_and_text() {
+        let zero = serde_json::json!(0);
+        assert!(matches_expected(
+            &zero,
+            &ForgeUniversalDataField::Integer(0)
+        ));
+        assert!(matches_expected(
+            &zero,
+            &ForgeUniversalDataField::Decimal(Decimal::from_str("0.00").unwrap())
+        ));
+        assert!(!matches_expected(
+            &zero,
+            &ForgeUniversalDataField::Integer(3)
+        ));
+
+        let text = serde_json::json!("active");
+        assert!(matches_expected(
+            &text,
+            &ForgeUniversalDataField::Text("active".to_string())
+        ));
+        assert!(matches_expected(
+            &serde_json::Value::Null,
+            &ForgeUniversalDataField::Null
+        ));
+    }
+
+    #[test]
+    fn report_shows_failures() {
+        let results = vec![
+            SmokeTestResult {
+                name: "no negative totals".to_string(),
+                sql: "SELECT 1".to_string(),
+                expected: "0".to_string(),
+                actual: Some("0".to_string()),
+                error: None,
+                passed: true,
+            },
+            SmokeTestResult {
+                name: "orders".to_string(),
+                sql: "SELECT 2".to_string(),
+                expected: "10".to_string(),
+                actual: Some("7".to_string()),
+                error: None,
+                passed: false,
+            },
+        ];
+
+        let report = smoke_report(&results);
+        assert!(report.starts_with("Smoke tests: 1/2 passed\n"));
+        assert!(report.contains("[OK  ] no negative totals\n"));
+        assert!(report.contains("[FAIL] orders (expected 10, got 7)"));
+    }
+}
@@ -0,0 +1,162 @@
+//! Destinations of exports, dumps and schema snapshots: local files or S3 objects.
+//!
+//! A path like `s3://bucket/exports/shop` addresses objects on S3 or S3-compatible storage
+//! (built with the `s3` feature). Credentials, region and endpoint are read from the usual
+//! `AWS_*` environment variables (`AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`, `AWS_REGION`,
+//! `AWS_ENDPOINT` for S3-compatible storage, `AWS_ALLOW_HTTP`). Objects are written with
+//! multipart uploads while the data is produced, nothing is staged on the local filesystem.
+
+use std::path::Path;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// Writer of a local file or an S3 object, `shutdown` completes the upload.
+pub type StorageWriter = Box<dyn AsyncWrite + Send + Unpin>;
+
+/// true for an `s3://bucket/key` path
+#[must_use]
+pub fn is_s3(path: &Path) -> bool {
+    path.to_str().is_some_and(|p| p.starts_with("s3://"))
+}
+
+/// Bucket and key of an `s3://bucket/key` path.
+///
+/// # Errors
+///
+/// Returns an error if the path is no S3 path or names no bucket.
+pub fn s3_bucket_and_key(path: &Path) -> Result<(String, String), Box<dyn std::error::Error>> {
+    let url = path
+        .to_str()
+        .and_then(|p| p.strip_prefix("s3://"))
+        .ok_or_else(|| format!("{} is no s3:// path", path.display()))?;
+    let (bucket, key) = url.split_once('/').unwrap_or((url, ""));
+    if bucket.is_empty() {
+        return Err(format!("{} names no bucket", path.display()).into());
+    }
+    Ok((bucket.to_string(), key.trim_matches('/').to_string()))
+}
+
+#[cfg(feature = "s3")]
+mod s3 {
+    use object_store::ObjectStore;
+    use object_store::aws::AmazonS3Builder;
+    use object_store::buffered::BufWriter;
+    use object_store::path::Path as ObjectPath;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    /// store of the bucket configured from the environment, and the location of the key
+    fn store(
+        path: &Path,
+    ) -> Result<(Arc<dyn ObjectStore>, ObjectPath), Box<dyn std::error::Error>> {
+        let (bucket, key) = super::s3_bucket_and_key(path)?;
+        let store = AmazonS3Builder::from_env()
+            .with_bucket_name(bucket)
+            .build()?;
+        Ok((Arc::new(store), ObjectPath::from(key)))
+    }
+
+    pub(super) fn create(path: &Path) -> Result<super::StorageWriter, Box<dyn std::error::Error>> {
+        let (store, location) = store(path)?;
+        Ok(Box::new(BufWriter::new(store, location)))
+    }
+
+    pub(super) async fn read(path: &Path) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let (store, location) = store(path)?;
+        let object = store.get(&location).await?;
+        Ok(object.bytes().await?.to_vec())
+    }
+}
+
+#[cfg(not(feature = "s3"))]
+fn s3_not_built() -> Box<dyn std::error::Error> {
+    "s3:// paths require a build with the 's3' feature".into()
+}
+
+/// Writer of the file or S3 object, a local file is created (or truncated).
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be created or the S3 path is invalid.
+pub async fn create(path: &Path) -> Result<StorageWriter, Box<dyn std::error::Error>> {
+    if is_s3(path) {
+        #[cfg(feature = "s3")]
+        return s3::create(path);
+        #[cfg(not(feature = "s3"))]
+        return Err(s3_not_built());
+    }
+    let file = tokio::fs::File::create(path)
+        .await
+        .map_err(|e| format!("Error creating {}: {e}", path.display()))?;
+    Ok(Box::new(tokio::io::BufWriter::new(file)))
+}
+
+/// Creates the directory of the files of an export, S3 has no directories.
+///
+/// # Errors
+///
+/// Returns an error if the local directory cannot be created.
+pub async fn create_dir_all(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    if !is_s3(path) {
+        tokio::fs::create_dir_all(path).await?;
+    }
+    Ok(())
+}
+
+/// Writes the bytes into the file or S3 object.
+///
+/// # Errors
+///
+/// Returns an error if the file or object cannot be written.
+pub async fn write(path: &Path, bytes: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut writer = create(path).await?;
+    writer.write_all(bytes).await?;
+    writer.shutdown().await?;
+    Ok(())
+}
+
+/// Reads the whole file or S3 object.
+///
+/// # Errors
+///
+/// Returns an error if the file or object cannot be read.
+pub async fn read(path: &Path) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    if is_s3(path) {
+        #[cfg(feature = "s3")]
+        return s3::read(path).await;
+        #[cfg(not(feature = "s3"))]
+        return Err(s3_not_built());
+    }
+    Ok(tokio::fs::read(path)
+        .await
+        .map_err(|e| format!("Error reading {}: {e}", path.display()))?)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn s3_paths_name_bucket_and_key() {
+        let path = Path::new("s3://backups/exports/shop/");
+        assert!(is_s3(path));
+        assert_eq!(
+            s3_bucket_and_key(path).unwrap(),
+            ("backups".to_string(), "exports/shop".to_string())
+        );
+        assert_eq!(
+            s3_bucket_and_key(&path.join("users.csv")).unwrap().1,
+            "exports/shop/users.csv"
+        );
+        assert!(s3_bucket_and_key(Path::new("s3:///key")).is_err());
+        assert!(!is_s3(Path::new("exports/shop")));
+    }
+
+    #[tokio::test]
+    async fn local_files_are_written_and_read() {
+        let path = std::env::temp_dir().join(format!("fluxforge-storage-{}", std::process::id()));
+        write(&path, b"{}").await.unwrap();
+        assert_eq!(read(&path).await.unwrap(), b"{}");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
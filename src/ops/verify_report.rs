@@ -0,0 +1,228 @@
+//! Report of all mismatching rows found by verification (`--verify-report`).
+//!
+//! Without a report verification stops at the first difference. With a report every
+//! mismatching row is recorded (up to a limit) and the replication continues, the
+//! summary is printed and the report written after the last table.
+
+use crate::ForgeUniversalDataField;
+use indexmap::IndexMap;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Kind of a mismatching row, seen from the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RowMismatchKind {
+    /// Row exists in both, but with different values
+    Changed,
+    /// Row exists in the source but not in the target
+    MissingInTarget,
+    /// Row exists in the target but not in the source
+    ExtraInTarget,
+}
+
+/// One differing column of a changed row.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ColumnMismatch {
+    pub column: String,
+    pub source: ForgeUniversalDataField,
+    pub target: ForgeUniversalDataField,
+}
+
+/// One mismatching row.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RowMismatch {
+    pub table: String,
+    /// Primary key values (all columns for tables without primary key)
+    pub key: IndexMap<String, ForgeUniversalDataField>,
+    pub kind: RowMismatchKind,
+    /// Differing columns, empty for missing and extra rows
+    pub columns: Vec<ColumnMismatch>,
+}
+
+/// Mismatches of all verified tables.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VerifyReport {
+    /// Maximum number of recorded rows, further mismatches are only counted
+    pub limit: usize,
+    /// Number of mismatching rows per table, including those beyond the limit
+    pub mismatch_counts: BTreeMap<String, u64>,
+    /// Tables with different row counts as (source, target)
+    pub row_counts: BTreeMap<String, (u64, u64)>,
+    pub mismatches: Vec<RowMismatch>,
+}
+
+impl VerifyReport {
+    #[must_use]
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            ..Self::default()
+        }
+    }
+
+    pub fn record(&mut self, mismatch: RowMismatch) {
+        *self
+            .mismatch_counts
+            .entry(mismatch.table.clone())
+            .or_default() += 1;
+        if self.mismatches.len() < self.limit {
+            self.mismatches.push(mismatch);
+        }
+    }
+
+    pub fn record_row_counts(&mut self, table: &str, source: u64, target: u64) {
+        if source != target {
+            self.row_counts.insert(table.to_string(), (source, target));
+        }
+    }
+
+    /// total number of mismatching rows
+    #[must_use]
+    pub fn total(&self) -> u64 {
+        self.mismatch_counts.values().sum()
+    }
+
+    /// true if no differences were found
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.mismatch_counts.is_empty() && self.row_counts.is_empty()
+    }
+
+    /// Human readable summary with one line per table
+    #[must_use]
+    pub fn summary(&self) -> String {
+        use std::fmt::Write as _;
+
+        if self.is_clean() {
+            return "Verification passed: no mismatching rows.\n".to_string();
+        }
+
+        let mut out = format!(
+            "Verification found {} mismatching rows ({} recorded):\n",
+            self.total(),
+            self.mismatches.len()
+        );
+        for (table, count) in &self.mismatch_counts {
+            let _ = writeln!(out, "  {table}: {count} rows");
+        }
+        for (table, (source, target)) in &self.row_counts {
+            let _ = writeln!(
+                out,
+                "  {table}: row count {source} in source, {target} in target"
+            );
+        }
+        out
+    }
+
+    /// writes the report as pretty JSON
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn write(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let file = std::fs::File::create(path)
+            .map_err(|e| format!("Error creating verify report {path:?}: {e}"))?;
+        serde_json::to_writer_pretty(std::io::BufWriter::new(file), self)?;
+        Ok(())
+    }
+}
+
+/// key values of a row
+#[must_use]
+pub fn row_key(
+    key_columns: &[String],
+    row: &IndexMap<String, ForgeUniversalDataField>,
+) -> IndexMap<String, ForgeUniversalDataField> {
+    key_columns
+        .iter()
+        .map(|col| {
+            let value = row
+                .get(col)
+                .cloned()
+                .unwrap_or(ForgeUniversalDataField::Null);
+            (col.clone(), value)
+        })
+        .collect()
+}
+
+/// Order of two rows by their keys, `None` if the database order cannot be reproduced.
+///
+/// Only integer keys are compared, text sorts by the collation of each server.
+#[must_use]
+pub fn compare_keys(
+    key_columns: &[String],
+    source_row: &IndexMap<String, ForgeUniversalDataField>,
+    target_row: &IndexMap<String, ForgeUniversalDataField>,
+) -> Option<Ordering> {
+    use ForgeUniversalDataField::{Integer, UnsignedInteger};
+
+    for col in key_columns {
+        let ordering = match (source_row.get(col)?, target_row.get(col)?) {
+            (Integer(a), Integer(b)) => a.cmp(b),
+            (UnsignedInteger(a), UnsignedInteger(b)) => a.cmp(b),
+            (Integer(a), UnsignedInteger(b)) => i128::from(*a).cmp(&i128::from(*b)),
+            (UnsignedInteger(a), Integer(b)) => i128::from(*a).cmp(&i128::from(*b)),
+            _ => return None,
+        };
+        if ordering != Ordering::Equal {
+            return Some(ordering);
+        }
+    }
+    Some(Ordering::Equal)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    fn key_row(id: i64) -> IndexMap<String, ForgeUniversalDataField> {
+        let mut row = IndexMap::new();
+        row.insert("id".to_string(), ForgeUniversalDataField::Integer(id));
+        row
+    }
+
+    #[test]
+    fn compare_keys_orders_integers_only() {
+        let keys = vec!["id".to_string()];
+        assert_eq!(
+            compare_keys(&keys, &key_row(1), &key_row(2)),
+            Some(Ordering::Less)
+        );
+        assert_eq!(
+            compare_keys(&keys, &key_row(2), &key_row(2)),
+            Some(Ordering::Equal)
+        );
+
+        let mut text = IndexMap::new();
+        text.insert(
+            "id".to_string(),
+            ForgeUniversalDataField::Text("a".to_string()),
+        );
+        assert_eq!(compare_keys(&keys, &text, &text), None);
+    }
+
+    #[test]
+    fn report_limits_recorded_rows() {
+        let mut report = VerifyReport::new(1);
+        for id in 1..=3 {
+            report.record(RowMismatch {
+                table: "users".to_string(),
+                key: key_row(id),
+                kind: RowMismatchKind::MissingInTarget,
+                columns: Vec::new(),
+            });
+        }
+        report.record_row_counts("users", 5, 2);
+
+        assert_eq!(report.total(), 3);
+        assert_eq!(report.mismatches.len(), 1);
+        assert_eq!(
+            report.summary(),
+            "Verification found 3 mismatching rows (1 recorded):\n  users: 3 rows\n  users: row count 5 in source, 2 in target\n"
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! Continuous periodic sync of `fluxforge replicate --watch`.
+//!
+//! After the initial load, the replication stays alive and appends the rows added to the
+//! source every `--interval` (see [`sync_new_rows`](super::sync_new_rows)), small tables
+//! with `sync = "reload"` are copied again completely and tables with `sync = "merge"` get
+//! their missing and changed rows. With drift
+//! detection the source schema is compared to the replicated schema before every cycle,
+//! new columns and tables are not replicated until the next full run. Ctrl-C or SIGTERM
+//! stop the watch after the running cycle.
+
+use crate::core::{ForgeSchemaDiff, ForgeSyncMode};
+use crate::{DatabaseDriver, ForgeConfig, ForgeSchema};
+use std::error::Error;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Settings of a watch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchOptions {
+    /// Pause between the end of a cycle and the start of the next one
+    pub interval: Duration,
+    pub halt_on_error: bool,
+    /// Compare the source schema to the replicated schema before every cycle
+    pub detect_drift: bool,
+}
+
+/// Parses an interval like `90`, `30s`, `15m` or `2h` (seconds without unit).
+///
+/// # Errors
+///
+/// Returns an error for an unknown unit, a missing number or a zero interval.
+pub fn parse_interval(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid interval {s:?}, expected i.e. 30s, 15m or 2h"))?;
+    let seconds = match unit {
+        "" | "s" => number,
+        "m" => number * 60,
+        "h" => number * 3600,
+        _ => return Err(format!("unknown unit of interval {s:?}, use s, m or h")),
+    };
+    if seconds == 0 {
+        return Err("the interval must be at least 1 s".to_string());
+    }
+    Ok(Duration::from_secs(seconds))
+}
+
+/// completes on Ctrl-C or SIGTERM
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+            }
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+/// Appends new source rows every interval until the process is asked to stop.
+///
+/// A failed cycle is logged and attempted again after the interval, the watch ends only
+/// on Ctrl-C or SIGTERM. With drift detection every change of the source schema is passed to
+/// `on_drift`.
+///
+/// # Errors
+///
+/// Returns an error if the baseline source schema of drift detection cannot be read.
+pub async fn watch(
+    source: &dyn DatabaseDriver,
+    target: &dyn DatabaseDriver,
+    schema: &ForgeSchema,
+    config: &ForgeConfig,
+    options: WatchOptions,
+    on_drift: &(dyn Fn(&ForgeSchemaDiff) + Sync),
+) -> Result<(), Box<dyn Error>> {
+    let mut baseline = if options.detect_drift {
+        Some(source.fetch_schema(config).await?)
+    } else {
+        None
+    };
+    for table in &schema.tables {
+        if config.get_table_sync_mode(&table.name) != ForgeSyncMode::Reload
+            && !table.columns.iter().any(|c| c.is_primary_key)
+        {
+            warn!(table = %table.name, "no primary key, new rows are not replicated by --watch");
+        }
+    }
+    info!(
+        interval_secs = options.interval.as_secs(),
+        "watching the source for new rows, Ctrl-C to stop"
+    );
+
+    let mut shutdown = std::pin::pin!(shutdown_signal());
+    loop {
+        tokio::select! {
+            () = tokio::time::sleep(options.interval) => {}
+            () = &mut shutdown => break,
+        }
+
+        let cycle = async {
+            if let Some(ref mut baseline) = baseline {
+                let current = source.fetch_schema(config).await?;
+                let diff = ForgeSchemaDiff::compare(&current, baseline);
+                if !diff.is_empty() {
+                    warn!(
+                        "source schema changed since the replication started, run a full replication to pick it up"
+                    );
+                    on_drift(&diff);
+                    *baseline = current;
+                }
+            }
+            super::sync_new_rows(source, target, schema, config, options.halt_on_error).await
+        };
+        let mut cycle = std::pin::pin!(cycle);
+        let (result, stop) = tokio::select! {
+            result = &mut cycle => (result, false),
+            () = &mut shutdown => {
+                info!("stopping after the running sync cycle");
+                (cycle.await, true)
+            }
+        };
+        match result {
+            Ok(rows) => info!(rows, "sync cycle finished"),
+            Err(e) => warn!(error = %e, "sync cycle failed, retrying after the interval"),
+        }
+        if stop {
+            break;
+        }
+    }
+    info!("watch stopped");
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intervals_are_parsed() {
+        assert_eq!(parse_interval("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_interval("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_interval("15m"), Ok(Duration::from_secs(900)));
+        assert_eq!(parse_interval("2h"), Ok(Duration::from_secs(7200)));
+        assert!(parse_interval("0m").is_err());
+        assert!(parse_interval("15d").is_err());
+        assert!(parse_interval("m").is_err());
+    }
+}
@@ -0,0 +1,40 @@
+//! Optional OpenTelemetry export of the tracing spans (feature `otel`).
+//!
+//! The spans for schema extraction, table replication, chunk inserts and verification
+//! are sent via OTLP/gRPC to the collector given with `--otel-endpoint`.
+
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::trace::{SdkTracer, SdkTracerProvider};
+use tracing::Subscriber;
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::registry::LookupSpan;
+
+/// creates the tracer provider and the tracing layer forwarding spans to it
+pub fn init<S>(
+    endpoint: &str,
+) -> Result<(SdkTracerProvider, OpenTelemetryLayer<S, SdkTracer>), Box<dyn std::error::Error>>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()?;
+
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name("fluxforge").build())
+        .build();
+
+    let tracer = provider.tracer("fluxforge");
+    Ok((provider, tracing_opentelemetry::layer().with_tracer(tracer)))
+}
+
+/// flushes the remaining spans, must be called before the process exits
+pub fn shutdown(provider: &SdkTracerProvider) {
+    if let Err(e) = provider.shutdown() {
+        eprintln!("OpenTelemetry shutdown failed: {e}");
+    }
+}
@@ -0,0 +1,132 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+#[cfg(feature = "integration-tests")]
+mod common;
+
+#[cfg(feature = "integration-tests")]
+mod tests {
+    use crate::common::TestContext;
+    use fluxforge::core::{ForgeConfig, ForgeObjectFilter, MigrateOptions};
+    use fluxforge::ops::ReplicateOptions;
+    use fluxforge::ops::checkpoint::ReplicationCheckpoint;
+    use fluxforge::{drivers, ops};
+
+    use std::env;
+
+    /// extract schema from the mysql 5.7 reference and replicate it to a dynamic mysql 8.0 target
+    #[tokio::test]
+    async fn test_mysql57_to_mysql80_replicate_with_verify() {
+        let ctx = TestContext::setup().await;
+
+        let config_toml = r#"
+
+[mysql.rules.on_read]
+sql_mode = ""
+
+[mysql.rules.on_write]
+zero_date = true
+sql_mode = ""
+
+"#;
+
+        let forge_config: ForgeConfig =
+            toml::from_str(config_toml).expect("Error parsing mysql mysql config");
+
+        let source_url =
+            env::var("MYSQL57_URL_REFERENCE").expect("MYSQL57_URL_REFERENCE is missing");
+        let target_url = format!("{}/{}", ctx.mysql_admin_url, ctx.db_name);
+
+        let source_driver = drivers::create_driver(&source_url, &forge_config, true)
+            .await
+            .expect("Error creating source driver");
+        let target_driver = drivers::create_driver(&target_url, &forge_config, false)
+            .await
+            .expect("Error creating target driver");
+
+        let source_schema = source_driver
+            .fetch_schema(&forge_config)
+            .await
+            .expect("Error fetching source schema");
+
+        assert!(
+            source_schema
+                .metadata
+                .source_server_version
+                .starts_with("5.7")
+        );
+
+        // views are not part of the schema
+        assert_eq!(source_schema.tables.len(), 1);
+        let table = &source_schema.tables[0];
+        assert_eq!(table.name, "legacy_person");
+        assert_eq!(table.comment.as_deref(), Some("legacy table"));
+
+        let full_name = table
+            .columns
+            .iter()
+            .find(|c| c.name == "full_name")
+            .expect("full_name missing");
+        assert!(full_name.generation_expression.is_some());
+        assert!(!full_name.is_generated_stored);
+
+        let name_len = table
+            .columns
+            .iter()
+            .find(|c| c.name == "name_len")
+            .expect("name_len missing");
+        assert!(name_len.generation_expression.is_some());
+        assert!(name_len.is_generated_stored);
+
+        let updated_at = table
+            .columns
+            .iter()
+            .find(|c| c.name == "updated_at")
+            .expect("updated_at missing");
+        assert!(updated_at.on_update.is_some());
+
+        target_driver
+            .diff_and_apply_schema(
+                &source_schema,
+                &forge_config,
+                &ForgeObjectFilter::default(),
+                MigrateOptions {
+                    destructive: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .expect("Error applying schema to target");
+
+        ops::replicate_data(
+            source_driver.as_ref(),
+            target_driver.as_ref(),
+            &source_schema,
+            &forge_config,
+            ReplicateOptions {
+                halt_on_error: true,
+                verify: true,
+                ..Default::default()
+            },
+            &ReplicationCheckpoint::new(),
+        )
+        .await
+        .expect("Error replicating data with verify");
+
+        let target_schema = target_driver
+            .fetch_schema(&forge_config)
+            .await
+            .expect("Error fetching target schema");
+        assert!(
+            target_schema
+                .metadata
+                .source_server_version
+                .starts_with('8')
+        );
+        let target_full_name = target_schema.tables[0]
+            .columns
+            .iter()
+            .find(|c| c.name == "full_name")
+            .expect("full_name missing in target");
+        assert!(target_full_name.generation_expression.is_some());
+    }
+}
@@ -6,10 +6,9 @@ mod common;
 #[cfg(feature = "integration-tests")]
 mod tests {
     use crate::common::TestContext;
-    use fluxforge::core::ForgeConfig;
+    use fluxforge::core::ForgeNullOrdering;
     use fluxforge::core::ForgeUniversalDataField;
     use fluxforge::drivers::mysql::MySqlDriver;
-    use fluxforge::{drivers, ops};
     use std::env;
 
     /// test if mysql-test-infrastucture is working
@@ -64,6 +63,15 @@ mod tests {
         let mapper = MySqlDriver {
             pool: ctx.mysql_target_pool.clone(),
             zero_date_on_write: true,
+            null_ordering: ForgeNullOrdering::default(),
+            retry: fluxforge::ops::retry::RetryPolicy::default(),
+            write_tx: tokio::sync::Mutex::new(None),
+            foreign_key_checks_off: Default::default(),
+            tunnel: None,
+            row_filters: Default::default(),
+            idempotent_ddl: false,
+            online_ddl: None,
+            metadata: Default::default(),
         };
 
         // check row 1: correct types?
@@ -5,11 +5,19 @@
     clippy::field_reassign_with_default
 )]
 
+use fluxforge::core::ForgeCheckStatus;
 use fluxforge::core::{
-    ForgeConfig, ForgeDbConfig, ForgeRuleGeneralConfig, ForgeRulesDirectionConfig,
-    ForgeSchemaColumn, ForgeSchemaIndex, ForgeSchemaTable, ForgeTypeDirectionConfig,
+    ForgeConfig, ForgeDbConfig, ForgeNullOrdering, ForgeRuleGeneralConfig,
+    ForgeRulesDirectionConfig, ForgeSchemaColumn, ForgeSchemaForeignKey, ForgeSchemaGrant,
+    ForgeSchemaIndex, ForgeSchemaPartition, ForgeSchemaPartitioning, ForgeSchemaTable,
+    ForgeTypeDirectionConfig, ForgeVerifySample,
 };
 use fluxforge::drivers::MySqlDriver;
+use fluxforge::drivers::mysql::{
+    MySqlServerVersion, missing_privileges, mysql_default_sql, mysql_partition_bound,
+    mysql_setting_checks, mysql_time_zone_sql,
+};
+use fluxforge::ops::retry::RetryPolicy;
 use std::collections::HashMap;
 
 // sqlx lazy pool imports (no real DB connection attempted)
@@ -23,6 +31,15 @@ fn mk_driver() -> MySqlDriver {
     MySqlDriver {
         pool,
         zero_date_on_write: true,
+        null_ordering: ForgeNullOrdering::default(),
+        retry: RetryPolicy::default(),
+        write_tx: tokio::sync::Mutex::new(None),
+        foreign_key_checks_off: Default::default(),
+        tunnel: None,
+        row_filters: Default::default(),
+        idempotent_ddl: false,
+        online_ddl: None,
+        metadata: Default::default(),
     }
 }
 
@@ -45,6 +62,7 @@ fn idx(name: &str, cols: &[&str], unique: bool) -> ForgeSchemaIndex {
         is_unique: unique,
         index_type: None,
         column_prefixes: None,
+        expressions: None,
     }
 }
 
@@ -119,6 +137,18 @@ async fn test_build_create_index_and_drop_index_sql() {
         sql_create_prefix, "CREATE INDEX `idx_prefix` ON `posts` (`title`(20));",
         "build_mysql_create_index_sql failed for prefix length index"
     );
+
+    // functional key part next to a column
+    let mut i5 = idx("idx_lower_name", &["", "id"], true);
+    i5.expressions = Some(vec![Some("lower(`name`)".to_string()), None]);
+    assert_eq!(
+        drv.build_mysql_create_index_sql("users", &i5),
+        "CREATE UNIQUE INDEX `idx_lower_name` ON `users` ((lower(`name`)), `id`);"
+    );
+    let mut changed = i5.clone();
+    changed.expressions = Some(vec![Some("upper(`name`)".to_string()), None]);
+    assert!(drv.indices_equal(&i5, &i5.clone()));
+    assert!(!drv.indices_equal(&i5, &changed));
 }
 
 #[tokio::test]
@@ -698,6 +728,45 @@ async fn test_fetch_columns_mapping_logic() {
         "int",
         "Test failed: 'int' (not unsigned) should remain 'int' even if rule is on"
     );
+
+    // bigint unsigned does not fit into bigint, numeric rule takes precedence
+    let mut config_numeric = config.clone();
+    config_numeric
+        .mysql
+        .as_mut()
+        .unwrap()
+        .rules
+        .as_mut()
+        .unwrap()
+        .on_read
+        .as_mut()
+        .unwrap()
+        .unsigned_bigint_to_numeric = Some(true);
+    assert_eq!(
+        drv.map_mysql_type("bigint(20) unsigned", "bigint", true, &config_numeric),
+        "numeric"
+    );
+    assert_eq!(
+        drv.map_mysql_type("int(11) unsigned", "int", true, &config_numeric),
+        "bigint"
+    );
+}
+
+#[tokio::test]
+async fn test_mysql_time_zone_sql() {
+    assert_eq!(
+        mysql_time_zone_sql("UTC".parse().unwrap()),
+        "SET time_zone = '+00:00'"
+    );
+}
+
+#[tokio::test]
+async fn test_unsigned_overflow_sql() {
+    let drv = mk_driver();
+    assert_eq!(
+        drv.unsigned_overflow_sql("orders", "id"),
+        "SELECT CAST(COUNT(*) AS SIGNED) FROM `orders` WHERE `id` > 9223372036854775807"
+    );
 }
 
 #[tokio::test]
@@ -766,3 +835,559 @@ async fn test_map_mysql_type_unsigned_matrix() {
         "Matrix: 'bigint' should always remain 'bigint'"
     );
 }
+
+#[tokio::test]
+async fn test_mysql_server_version_parsing() {
+    let v80 = MySqlServerVersion::parse("8.0.36");
+    assert_eq!((v80.major, v80.minor, v80.patch), (8, 0, 36));
+    assert!(!v80.is_mariadb);
+    assert!(v80.has_data_dictionary());
+    assert!(v80.has_generated_columns());
+    assert!(v80.reports_utf8mb3());
+
+    let v57 = MySqlServerVersion::parse("5.7.44-log");
+    assert_eq!((v57.major, v57.minor, v57.patch), (5, 7, 44));
+    assert!(!v57.has_data_dictionary());
+    assert!(v57.has_generated_columns());
+    assert!(!v57.reports_utf8mb3());
+
+    let v56 = MySqlServerVersion::parse("5.6.51");
+    assert!(!v56.has_generated_columns());
+    assert!(!v56.has_data_dictionary());
+
+    let maria = MySqlServerVersion::parse("10.11.6-MariaDB-1:10.11.6+maria~ubu2204");
+    assert!(maria.is_mariadb);
+    assert_eq!((maria.major, maria.minor, maria.patch), (10, 11, 6));
+    assert!(maria.has_generated_columns());
+    assert!(!maria.has_data_dictionary());
+
+    assert_eq!(MySqlServerVersion::parse(""), MySqlServerVersion::default());
+}
+
+#[tokio::test]
+async fn test_source_position_sql_by_version() {
+    let v84 = MySqlServerVersion::parse("8.4.0");
+    assert_eq!(v84.binlog_status_sql(), "SHOW BINARY LOG STATUS");
+    assert_eq!(v84.gtid_set_sql(), "SELECT @@GLOBAL.gtid_executed");
+
+    let v80 = MySqlServerVersion::parse("8.0.36");
+    assert_eq!(v80.binlog_status_sql(), "SHOW MASTER STATUS");
+
+    let maria = MySqlServerVersion::parse("11.4.2-MariaDB");
+    assert_eq!(maria.binlog_status_sql(), "SHOW MASTER STATUS");
+    assert_eq!(maria.gtid_set_sql(), "SELECT @@GLOBAL.gtid_current_pos");
+}
+
+#[tokio::test]
+async fn test_mysql_server_version_charset_normalization() {
+    let v8036 = MySqlServerVersion::parse("8.0.36");
+    assert_eq!(v8036.normalize_charset_name("utf8"), "utf8mb3");
+    assert_eq!(
+        v8036.normalize_charset_name("utf8_general_ci"),
+        "utf8mb3_general_ci"
+    );
+    assert_eq!(v8036.normalize_charset_name("utf8mb4"), "utf8mb4");
+
+    let v57 = MySqlServerVersion::parse("5.7.44");
+    assert_eq!(v57.normalize_charset_name("utf8mb3"), "utf8");
+    assert_eq!(
+        v57.normalize_charset_name("UTF8MB3_UNICODE_CI"),
+        "utf8_unicode_ci"
+    );
+    assert_eq!(
+        v57.normalize_charset_name("utf8mb4_general_ci"),
+        "utf8mb4_general_ci"
+    );
+}
+
+#[tokio::test]
+async fn test_field_migration_sql_generated_columns() {
+    let d = mk_driver();
+    let config = mk_config();
+
+    let mut virt = col("full_name", "varchar");
+    virt.length = Some(200);
+    virt.is_nullable = true;
+    virt.generation_expression = Some("concat(`first`,' ',`last`)".to_string());
+    assert_eq!(
+        d.field_migration_sql(virt, &config),
+        "`full_name` varchar(200) GENERATED ALWAYS AS (concat(`first`,' ',`last`)) VIRTUAL"
+    );
+
+    let mut stored = col("total", "int");
+    stored.is_nullable = false;
+    stored.default = Some("0".to_string());
+    stored.generation_expression = Some("(`a` + `b`)".to_string());
+    stored.is_generated_stored = true;
+    assert_eq!(
+        d.field_migration_sql(stored, &config),
+        "`total` int GENERATED ALWAYS AS ((`a` + `b`)) STORED NOT NULL"
+    );
+}
+
+#[tokio::test]
+async fn test_invisible_columns() {
+    let d = mk_driver();
+    let config = mk_config();
+
+    // generated invisible primary key of MySQL 8.0.30+
+    let mut gipk = col("my_row_id", "bigint");
+    gipk.is_unsigned = true;
+    gipk.is_primary_key = true;
+    gipk.auto_increment = true;
+    gipk.is_invisible = true;
+    let mut table = ForgeSchemaTable::new("t");
+    table.columns.push(gipk);
+    table.columns.push(col("name", "varchar"));
+    assert_eq!(
+        d.build_mysql_create_table_sql(&table, &config),
+        "CREATE TABLE `t` (\n\
+         `my_row_id` bigint unsigned NOT NULL AUTO_INCREMENT INVISIBLE,\n\
+         `name` varchar NOT NULL,\n  \
+         PRIMARY KEY (`my_row_id`)\n\
+         ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;"
+    );
+
+    let mut hidden = col("total", "int");
+    hidden.generation_expression = Some("(`a` + `b`)".to_string());
+    hidden.is_invisible = true;
+    assert_eq!(
+        d.field_migration_sql(hidden, &config),
+        "`total` int GENERATED ALWAYS AS ((`a` + `b`)) VIRTUAL NOT NULL INVISIBLE"
+    );
+
+    // visibility change is a column change
+    let mut visible = col("note", "text");
+    visible.is_nullable = true;
+    let mut invisible = visible.clone();
+    invisible.is_invisible = true;
+    assert_eq!(
+        d.modify_column_migration("t", &invisible, &visible, &config, false),
+        "ALTER TABLE `t` MODIFY COLUMN `note` text NULL INVISIBLE;"
+    );
+
+    assert_eq!(d.build_select_all_sql("t", &[]), "SELECT * FROM `t`");
+    assert_eq!(
+        d.build_select_all_sql("t", &["my_row_id".to_string()]),
+        "SELECT *, `my_row_id` FROM `t`"
+    );
+}
+
+#[tokio::test]
+async fn test_build_sample_predicate() {
+    let d = mk_driver();
+    let sample: ForgeVerifySample = "1%".parse().unwrap();
+    assert_eq!(
+        d.build_sample_predicate(&["id".to_string()], sample),
+        "CONV(SUBSTRING(MD5(CONCAT_WS('|', `id`)), 1, 8), 16, 10) % 10000 < 100"
+    );
+    assert_eq!(
+        d.build_sample_predicate(&["a".to_string(), "b".to_string()], sample),
+        "CONV(SUBSTRING(MD5(CONCAT_WS('|', `a`, `b`)), 1, 8), 16, 10) % 10000 < 100"
+    );
+    assert_eq!(
+        d.build_sample_predicate(&["id".to_string()], sample.with_seed(42)),
+        "CONV(SUBSTRING(MD5(CONCAT_WS('|', '42', `id`)), 1, 8), 16, 10) % 10000 < 100"
+    );
+}
+
+#[tokio::test]
+async fn test_build_keyset_clause() {
+    let d = mk_driver();
+    let keys = vec!["a".to_string(), "b".to_string()];
+    assert_eq!(d.build_keyset_clause(&keys, false), " ORDER BY `a`, `b`");
+    assert_eq!(
+        d.build_keyset_clause(&keys, true),
+        " WHERE (`a`, `b`) > (?, ?) ORDER BY `a`, `b`"
+    );
+    assert_eq!(d.build_key_clause(&keys), " WHERE (`a`, `b`) = (?, ?)");
+    assert_eq!(
+        d.build_last_key_sql("t", &keys),
+        "SELECT `a`, `b` FROM `t` ORDER BY `a` DESC, `b` DESC LIMIT 1"
+    );
+}
+
+#[tokio::test]
+async fn test_build_select_digest_sql() {
+    let d = mk_driver();
+    let columns = vec!["id".to_string(), "photo".to_string(), "name".to_string()];
+    assert_eq!(
+        d.build_select_digest_sql("users", &columns, &["photo".to_string()]),
+        "SELECT `id`, SHA2(`photo`, 256) AS `photo`, `name` FROM `users`"
+    );
+}
+
+#[tokio::test]
+async fn test_build_grant_sql() {
+    let d = mk_driver();
+    let config: ForgeConfig = toml::from_str(
+        r#"
+        [grants.user_mapping]
+        reporting = "'report'@'10.0.0.%'"
+        "#,
+    )
+    .unwrap();
+
+    let grant = |grantee: &str, privileges: &[&str]| ForgeSchemaGrant {
+        grantee: grantee.to_string(),
+        table_name: "orders".to_string(),
+        privileges: privileges.iter().map(ToString::to_string).collect(),
+    };
+
+    assert_eq!(
+        d.build_grant_sql(&grant("'app'@'%'", &["INSERT", "SELECT"]), &config),
+        Some("GRANT INSERT, SELECT ON `orders` TO 'app'@'%';".to_string())
+    );
+    // mapped postgres role
+    assert_eq!(
+        d.build_grant_sql(&grant("reporting", &["SELECT"]), &config),
+        Some("GRANT SELECT ON `orders` TO 'report'@'10.0.0.%';".to_string())
+    );
+    // unmapped postgres role, TRUNCATE has no mysql table privilege
+    assert_eq!(
+        d.build_grant_sql(&grant("etl", &["SELECT", "TRUNCATE"]), &config),
+        Some("GRANT SELECT ON `orders` TO 'etl'@'%';".to_string())
+    );
+    assert_eq!(
+        d.build_grant_sql(&grant("etl", &["TRUNCATE"]), &config),
+        None
+    );
+}
+
+#[tokio::test]
+async fn test_alter_table_migration_sql_stable_order() {
+    let d = mk_driver();
+    let config = mk_config();
+
+    let mut src = ForgeSchemaTable::new("t");
+    src.columns.push(col("id", "int"));
+    for name in ["idx_zeta", "idx_alpha", "idx_mid"] {
+        src.indices.push(idx(name, &["id"], false));
+    }
+
+    let mut dst = ForgeSchemaTable::new("t");
+    dst.columns.push(col("id", "int"));
+    dst.columns.push(col("old_b", "int"));
+    dst.columns.push(col("old_a", "int"));
+    for name in ["old_z", "old_a"] {
+        dst.indices.push(idx(name, &["id"], false));
+    }
+
+    let stmts = d
+        .alter_table_migration_sql(&src, &dst, &config, true)
+        .unwrap();
+    assert_eq!(
+        stmts,
+        vec![
+            "ALTER TABLE `t` DROP COLUMN `old_a`;".to_string(),
+            "ALTER TABLE `t` DROP COLUMN `old_b`;".to_string(),
+            d.build_mysql_create_index_sql("t", &idx("idx_alpha", &["id"], false)),
+            d.build_mysql_create_index_sql("t", &idx("idx_mid", &["id"], false)),
+            d.build_mysql_create_index_sql("t", &idx("idx_zeta", &["id"], false)),
+            d.build_mysql_drop_index_sql("t", "old_a"),
+            d.build_mysql_drop_index_sql("t", "old_z"),
+        ]
+    );
+
+    // consecutive runs produce the identical plan
+    for _ in 0..10 {
+        assert_eq!(
+            d.alter_table_migration_sql(&src, &dst, &config, true)
+                .unwrap(),
+            stmts
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_foreign_key_violations_sql() {
+    let drv = mk_driver();
+    let foreign_key = ForgeSchemaForeignKey {
+        name: "fk_orders_user".to_string(),
+        column: "user_id".to_string(),
+        ref_table: "users".to_string(),
+        ref_column: "id".to_string(),
+        ..Default::default()
+    };
+    assert_eq!(
+        drv.foreign_key_violations_sql("orders", std::slice::from_ref(&foreign_key)),
+        "SELECT CAST(COUNT(*) AS SIGNED) FROM `orders` c LEFT JOIN `users` r \
+         ON c.`user_id` = r.`id` WHERE c.`user_id` IS NOT NULL AND r.`id` IS NULL"
+    );
+
+    // a composite foreign key is joined on all its column pairs
+    let tenant = ForgeSchemaForeignKey {
+        column: "tenant".to_string(),
+        ref_column: "tenant".to_string(),
+        ..foreign_key.clone()
+    };
+    assert_eq!(
+        drv.foreign_key_violations_sql("orders", &[tenant, foreign_key]),
+        "SELECT CAST(COUNT(*) AS SIGNED) FROM `orders` c LEFT JOIN `users` r \
+         ON c.`tenant` = r.`tenant` AND c.`user_id` = r.`id` \
+         WHERE c.`tenant` IS NOT NULL AND c.`user_id` IS NOT NULL AND r.`tenant` IS NULL"
+    );
+}
+
+#[tokio::test]
+async fn test_missing_privileges_from_show_grants() {
+    let grants = vec![
+        "GRANT USAGE ON *.* TO `app`@`%`".to_string(),
+        "GRANT SELECT, INSERT, CREATE ON `shop\\_db`.* TO `app`@`%`".to_string(),
+        "GRANT DELETE ON `other`.* TO `app`@`%`".to_string(),
+        "GRANT UPDATE (`name`) ON `shop_db`.`users` TO `app`@`%`".to_string(),
+        "GRANT `reader`@`%` TO `app`@`%`".to_string(),
+    ];
+    assert_eq!(
+        missing_privileges(
+            &grants,
+            "shop_db",
+            &["SELECT", "INSERT", "DELETE", "TRIGGER"]
+        ),
+        vec!["DELETE", "TRIGGER"]
+    );
+
+    let all =
+        vec!["GRANT ALL PRIVILEGES ON *.* TO `root`@`localhost` WITH GRANT OPTION".to_string()];
+    assert!(missing_privileges(&all, "shop_db", &["TRIGGER", "REPLICATION CLIENT"]).is_empty());
+}
+
+#[tokio::test]
+async fn test_mysql_setting_checks() {
+    let status = |checks: Vec<fluxforge::ForgePreflightCheck>| -> Vec<ForgeCheckStatus> {
+        checks.into_iter().map(|c| c.status).collect()
+    };
+    assert_eq!(
+        status(mysql_setting_checks(
+            "STRICT_TRANS_TABLES,NO_ZERO_DATE",
+            64 * 1024 * 1024,
+            28800,
+            60,
+            true
+        )),
+        vec![ForgeCheckStatus::Pass; 3]
+    );
+    // non-strict target, small packets and a short write timeout on the source
+    assert_eq!(
+        status(mysql_setting_checks("", 4 * 1024 * 1024, 28800, 30, true)),
+        vec![
+            ForgeCheckStatus::Warn,
+            ForgeCheckStatus::Warn,
+            ForgeCheckStatus::Pass
+        ]
+    );
+    assert_eq!(
+        status(mysql_setting_checks("", 64 * 1024 * 1024, 28800, 30, false)),
+        vec![
+            ForgeCheckStatus::Pass,
+            ForgeCheckStatus::Pass,
+            ForgeCheckStatus::Warn
+        ]
+    );
+
+    assert_eq!(
+        MySqlServerVersion::parse("5.5.62").version_check().status,
+        ForgeCheckStatus::Fail
+    );
+    assert_eq!(
+        MySqlServerVersion::parse("5.6.51").version_check().status,
+        ForgeCheckStatus::Warn
+    );
+    assert_eq!(
+        MySqlServerVersion::parse("8.0.36").version_check().detail,
+        "MySQL 8.0.36"
+    );
+}
+
+#[tokio::test]
+async fn test_postgres_source_columns() {
+    let d = mk_driver();
+    let config = mk_config();
+
+    let nullable = |name: &str, data_type: &str| {
+        let mut c = col(name, data_type);
+        c.is_nullable = true;
+        c
+    };
+    let cases = vec![
+        (nullable("data", "bytea"), "`data` longblob NULL"),
+        (nullable("doc", "jsonb"), "`doc` json NULL"),
+        (nullable("tags", "json"), "`tags` json NULL"),
+        (nullable("uid", "uuid"), "`uid` char(36) NULL DEFAULT NULL"),
+        (nullable("ip", "inet"), "`ip` varchar(43) NULL DEFAULT NULL"),
+        (
+            nullable("active", "boolean"),
+            "`active` tinyint(1) NULL DEFAULT NULL",
+        ),
+        (
+            nullable("note", "character varying"),
+            "`note` longtext NULL",
+        ),
+        (nullable("login", "citext"), "`login` longtext NULL"),
+        (nullable("scores", "int4[]"), "`scores` json NULL"),
+        (
+            nullable("amount", "numeric"),
+            "`amount` decimal(65,30) NULL DEFAULT NULL",
+        ),
+        (
+            nullable("created", "timestamp with time zone"),
+            "`created` datetime(6) NULL DEFAULT NULL",
+        ),
+        (
+            nullable("ratio", "double precision"),
+            "`ratio` double NULL DEFAULT NULL",
+        ),
+    ];
+    for (column, expected) in cases {
+        assert_eq!(d.field_migration_sql(column, &config), expected);
+    }
+
+    let mut name = col("name", "character varying");
+    name.length = Some(80);
+    name.default = Some("'new'::character varying".to_string());
+    assert_eq!(
+        d.field_migration_sql(name, &config),
+        "`name` varchar(80) NOT NULL DEFAULT 'new'"
+    );
+
+    // serial column, the sequence is replaced by AUTO_INCREMENT
+    let mut id = col("id", "integer");
+    id.auto_increment = true;
+    id.default = Some("nextval('users_id_seq'::regclass)".to_string());
+    assert_eq!(
+        d.field_migration_sql(id, &config),
+        "`id` integer NOT NULL AUTO_INCREMENT"
+    );
+}
+
+#[test]
+fn test_mysql_default_sql() {
+    assert_eq!(
+        mysql_default_sql("'it''s'::text", "longtext", None).as_deref(),
+        Some("'it''s'")
+    );
+    assert_eq!(
+        mysql_default_sql("(-1)", "integer", None).as_deref(),
+        Some("'-1'")
+    );
+    assert_eq!(
+        mysql_default_sql("now()", "datetime(6)", Some(6)).as_deref(),
+        Some("CURRENT_TIMESTAMP(6)")
+    );
+    assert_eq!(
+        mysql_default_sql("true", "tinyint(1)", None).as_deref(),
+        Some("1")
+    );
+    assert_eq!(
+        mysql_default_sql("NULL::character varying", "varchar", None),
+        None
+    );
+    assert_eq!(
+        mysql_default_sql("gen_random_uuid()", "char(36)", None),
+        None
+    );
+    // raw MySQL defaults are quoted as before
+    assert_eq!(
+        mysql_default_sql("abc", "varchar", None).as_deref(),
+        Some("'abc'")
+    );
+}
+
+#[tokio::test]
+async fn test_text_keys_get_a_prefix() {
+    let d = mk_driver();
+    let config = mk_config();
+
+    let mut table = ForgeSchemaTable::new("tags");
+    let mut name = col("name", "text");
+    name.is_primary_key = true;
+    table.columns.push(name);
+    table.columns.push(col("code", "integer"));
+    table.indices.push(ForgeSchemaIndex {
+        name: "tags_name_code_key".to_string(),
+        columns: vec!["name".to_string(), "code".to_string()],
+        is_unique: true,
+        index_type: None,
+        column_prefixes: None,
+        expressions: None,
+    });
+
+    let stmts = d.create_table_migration_sql(&table, &config).unwrap();
+    assert!(stmts[0].contains("PRIMARY KEY (`name`(255))"));
+    assert_eq!(
+        stmts[1],
+        "CREATE UNIQUE INDEX `tags_name_code_key` ON `tags` (`name`(255), `code`);"
+    );
+
+    // an index with the same prefixes on the target is unchanged
+    let mut target = table.clone();
+    target.indices[0].column_prefixes = Some(vec![Some(255), None]);
+    let stmts = d
+        .alter_table_migration_sql(&table, &target, &config, false)
+        .unwrap();
+    assert!(stmts.is_empty(), "{stmts:?}");
+}
+
+#[tokio::test]
+async fn test_partitioned_table() {
+    let d = mk_driver();
+    let config = mk_config();
+
+    assert_eq!(
+        mysql_partition_bound("RANGE", Some("2025")),
+        "VALUES LESS THAN (2025)"
+    );
+    assert_eq!(
+        mysql_partition_bound("RANGE COLUMNS", Some("MAXVALUE")),
+        "VALUES LESS THAN MAXVALUE"
+    );
+    assert_eq!(
+        mysql_partition_bound("LIST", Some("1,2")),
+        "VALUES IN (1,2)"
+    );
+    assert_eq!(mysql_partition_bound("HASH", None), "");
+
+    let partition = |name: &str, bound: &str| ForgeSchemaPartition {
+        name: name.to_string(),
+        bound: bound.to_string(),
+    };
+    let mut table = ForgeSchemaTable::new("events");
+    table.columns.push(col("id", "int"));
+    table.partitioning = Some(ForgeSchemaPartitioning {
+        strategy: "range".to_string(),
+        key: "year(`created`)".to_string(),
+        partitions: vec![
+            partition("p2024", "VALUES LESS THAN (2025)"),
+            partition("pmax", "VALUES LESS THAN MAXVALUE"),
+        ],
+    });
+    assert_eq!(
+        d.build_mysql_create_table_sql(&table, &config),
+        "CREATE TABLE `events` (\n`id` int NOT NULL\n) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4\n\
+         PARTITION BY RANGE (year(`created`))\n\
+         (PARTITION `p2024` VALUES LESS THAN (2025),\n \
+         PARTITION `pmax` VALUES LESS THAN MAXVALUE);"
+    );
+
+    // hash partitions have no bound
+    table.partitioning = Some(ForgeSchemaPartitioning {
+        strategy: "hash".to_string(),
+        key: "`id`".to_string(),
+        partitions: vec![partition("p0", ""), partition("p1", "")],
+    });
+    assert!(
+        d.build_mysql_create_table_sql(&table, &config)
+            .ends_with("PARTITION BY HASH (`id`)\n(PARTITION `p0`,\n PARTITION `p1`);")
+    );
+
+    // PostgreSQL bounds are not valid MySQL, the table is created without partitions
+    table.partitioning = Some(ForgeSchemaPartitioning {
+        strategy: "list".to_string(),
+        key: "id".to_string(),
+        partitions: vec![partition("events_a", "FOR VALUES IN (1)")],
+    });
+    assert!(
+        d.build_mysql_create_table_sql(&table, &config)
+            .ends_with("DEFAULT CHARSET=utf8mb4;")
+    );
+}
@@ -6,7 +6,9 @@ mod common;
 #[cfg(feature = "integration-tests")]
 mod tests {
     use crate::common::TestContext;
-    use fluxforge::core::ForgeConfig;
+    use fluxforge::core::{ForgeConfig, ForgeObjectFilter, MigrateOptions};
+    use fluxforge::ops::ReplicateOptions;
+    use fluxforge::ops::checkpoint::ReplicationCheckpoint;
     use fluxforge::{drivers, ops};
 
     use std::env;
@@ -139,7 +141,15 @@ sql_mode = ""
         source_schema.tables = sorted_tables;
 
         target_driver
-            .diff_and_apply_schema(&source_schema, &forge_config, false, false, true)
+            .diff_and_apply_schema(
+                &source_schema,
+                &forge_config,
+                &ForgeObjectFilter::default(),
+                MigrateOptions {
+                    destructive: true,
+                    ..Default::default()
+                },
+            )
             .await
             .expect("Error applying schema to target");
 
@@ -147,11 +157,12 @@ sql_mode = ""
             source_driver.as_ref(),
             target_driver.as_ref(),
             &source_schema,
-            None,
-            false,
-            false,
-            false,
-            true,
+            &forge_config,
+            ReplicateOptions {
+                verify: true,
+                ..Default::default()
+            },
+            &ReplicationCheckpoint::new(),
         )
         .await
         .expect("Error replicating data with verify");
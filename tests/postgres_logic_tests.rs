@@ -2,12 +2,33 @@
 
 #[cfg(test)]
 mod tests {
-    use fluxforge::core::{ForgeConfig, ForgeSchemaColumn, ForgeSchemaTable};
-    use fluxforge::drivers::postgres::PostgresDriver;
+    use fluxforge::core::ForgeCheckStatus;
+    use fluxforge::core::{
+        ForgeConfig, ForgeNullOrdering, ForgeSchemaColumn, ForgeSchemaDomain,
+        ForgeSchemaForeignKey, ForgeSchemaGrant, ForgeSchemaIndex, ForgeSchemaPartition,
+        ForgeSchemaPartitioning, ForgeSchemaTable, ForgeVerifySample,
+    };
+    use fluxforge::drivers::postgres::{
+        PostgresDriver, build_postgres_create_domain_sql, create_extension_statements,
+        parse_partition_key, postgres_ddl_timeout_sql, postgres_partitioning_of_mysql,
+        postgres_setting_checks, postgres_time_zone_sql, runs_outside_transaction,
+        set_updated_at_function_sql,
+    };
+    use fluxforge::ops::retry::RetryPolicy;
+    use std::collections::HashSet;
 
     // Helper to create a driver without a real pool (will fail on DB calls, but ok for pure logic)
     fn mock_driver() -> PostgresDriver {
-        PostgresDriver { pool: None }
+        PostgresDriver {
+            pool: None,
+            null_ordering: ForgeNullOrdering::default(),
+            retry: RetryPolicy::default(),
+            write_tx: tokio::sync::Mutex::new(None),
+            time_zone: None,
+            tunnel: None,
+            row_filters: Default::default(),
+            idempotent_ddl: false,
+        }
     }
 
     #[test]
@@ -119,4 +140,589 @@ mod tests {
         assert!(stmts[0].contains("ALTER COLUMN id TYPE int8"));
         assert!(stmts[0].contains("ALTER COLUMN id DROP NULL"));
     }
+
+    #[test]
+    fn test_partitioned_table() {
+        let driver = mock_driver();
+        let config = ForgeConfig::default();
+        let partition = |name: &str, from: &str, to: &str| ForgeSchemaPartition {
+            name: name.to_string(),
+            bound: format!("FOR VALUES FROM ('{from}') TO ('{to}')"),
+        };
+
+        let mut partitioning = parse_partition_key("RANGE (created_at)").unwrap();
+        assert_eq!(
+            (partitioning.strategy.as_str(), partitioning.key.as_str()),
+            ("range", "created_at")
+        );
+        assert!(parse_partition_key("RANGE").is_none());
+        partitioning.partitions = vec![
+            partition("events_2024", "2024-01-01", "2025-01-01"),
+            partition("events_2025", "2025-01-01", "2026-01-01"),
+        ];
+
+        let mut table = ForgeSchemaTable::new("events");
+        table.columns.push(ForgeSchemaColumn::new("id", "bigint"));
+        table
+            .columns
+            .push(ForgeSchemaColumn::new("created_at", "date"));
+        table.partitioning = Some(partitioning);
+        table.indices.push(ForgeSchemaIndex {
+            name: "idx_events_created_at".to_string(),
+            columns: vec!["created_at".to_string()],
+            ..ForgeSchemaIndex::default()
+        });
+
+        let stmts = driver.create_table_migration_sql(&table, &config).unwrap();
+        assert_eq!(
+            stmts,
+            vec![
+                "CREATE TABLE events (\n  id bigint NOT NULL,\n  created_at date NULL\n) \
+                 PARTITION BY RANGE (created_at)",
+                "CREATE TABLE events_2024 PARTITION OF events \
+                 FOR VALUES FROM ('2024-01-01') TO ('2025-01-01')",
+                "CREATE TABLE events_2025 PARTITION OF events \
+                 FOR VALUES FROM ('2025-01-01') TO ('2026-01-01')",
+                "CREATE INDEX idx_events_created_at ON events (created_at)",
+            ]
+        );
+
+        // a new source partition is added, an extra target partition is dropped if destructive
+        let mut target = table.clone();
+        if let Some(ref mut p) = target.partitioning {
+            p.partitions = vec![
+                partition("events_2023", "2023-01-01", "2024-01-01"),
+                partition("events_2024", "2024-01-01", "2025-01-01"),
+            ];
+        }
+        let stmts = driver
+            .alter_table_migration_sql(&table, &target, &config, true)
+            .unwrap();
+        assert_eq!(
+            stmts,
+            vec![
+                "CREATE TABLE events_2025 PARTITION OF events \
+                 FOR VALUES FROM ('2025-01-01') TO ('2026-01-01')",
+                "DROP TABLE IF EXISTS events_2023",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mysql_partitioning_is_translated() {
+        let partition = |name: &str, bound: &str| ForgeSchemaPartition {
+            name: name.to_string(),
+            bound: bound.to_string(),
+        };
+        let mysql = |strategy: &str, key: &str, partitions: Vec<ForgeSchemaPartition>| {
+            ForgeSchemaPartitioning {
+                strategy: strategy.to_string(),
+                key: key.to_string(),
+                partitions,
+            }
+        };
+        let bounds = |p: ForgeSchemaPartitioning| -> Vec<String> {
+            p.partitions.into_iter().map(|p| p.bound).collect()
+        };
+
+        let range = postgres_partitioning_of_mysql(&mysql(
+            "range columns",
+            "`created`",
+            vec![
+                partition("p2024", "VALUES LESS THAN ('2025-01-01')"),
+                partition("pmax", "VALUES LESS THAN MAXVALUE"),
+            ],
+        ))
+        .unwrap();
+        assert_eq!(
+            (range.strategy.as_str(), range.key.as_str()),
+            ("range", "created")
+        );
+        assert_eq!(
+            bounds(range),
+            vec![
+                "FOR VALUES FROM (MINVALUE) TO ('2025-01-01')",
+                "FOR VALUES FROM ('2025-01-01') TO (MAXVALUE)",
+            ]
+        );
+
+        let list = postgres_partitioning_of_mysql(&mysql(
+            "list",
+            "`region`",
+            vec![partition("p_eu", "VALUES IN (1,2)")],
+        ))
+        .unwrap();
+        assert_eq!(bounds(list), vec!["FOR VALUES IN (1,2)"]);
+
+        let hash = postgres_partitioning_of_mysql(&mysql(
+            "key",
+            "`id`",
+            vec![partition("p0", ""), partition("p1", "")],
+        ))
+        .unwrap();
+        assert_eq!(
+            bounds(hash),
+            vec![
+                "FOR VALUES WITH (MODULUS 2, REMAINDER 0)",
+                "FOR VALUES WITH (MODULUS 2, REMAINDER 1)",
+            ]
+        );
+
+        // expression keys have no counterpart
+        assert!(
+            postgres_partitioning_of_mysql(&mysql(
+                "range",
+                "year(`created`)",
+                vec![partition("p2024", "VALUES LESS THAN (2025)")],
+            ))
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn test_create_extension_statements() {
+        let required = vec![
+            "citext".to_string(),
+            "pgcrypto".to_string(),
+            "timescaledb".to_string(),
+        ];
+        let installed: HashSet<String> = ["pgcrypto".to_string()].into();
+
+        // the default allowlist holds the contrib modules
+        assert_eq!(
+            create_extension_statements(&required, &installed, &ForgeConfig::default()),
+            vec!["CREATE EXTENSION IF NOT EXISTS \"citext\""]
+        );
+
+        let config: ForgeConfig =
+            toml::from_str("[general]\nallowed_extensions = [\"timescaledb\"]").unwrap();
+        assert_eq!(
+            create_extension_statements(&required, &installed, &config),
+            vec!["CREATE EXTENSION IF NOT EXISTS \"timescaledb\""]
+        );
+    }
+
+    #[test]
+    fn test_array_columns_keep_their_type() {
+        let driver = mock_driver();
+        let config = ForgeConfig::default();
+        let mut col = ForgeSchemaColumn::new("tags", "varchar[]");
+        col.is_nullable = true;
+        assert_eq!(
+            driver.field_migration_sql(&col, &config),
+            "tags varchar[] NULL"
+        );
+        let col = ForgeSchemaColumn::new("scores", "int4[]");
+        assert_eq!(
+            driver.field_migration_sql(&col, &config),
+            "scores int4[] NOT NULL"
+        );
+    }
+
+    #[test]
+    fn test_domain_columns() {
+        let driver = mock_driver();
+        let config = ForgeConfig::default();
+        let domain = ForgeSchemaDomain {
+            name: "email_address".to_string(),
+            data_type: "character varying(254)".to_string(),
+            not_null: true,
+            default: Some("''::character varying".to_string()),
+            checks: vec!["CHECK (((VALUE)::text ~~ '%@%'::text))".to_string()],
+        };
+        assert_eq!(
+            build_postgres_create_domain_sql(&domain),
+            "CREATE DOMAIN email_address AS character varying(254) DEFAULT ''::character varying \
+             NOT NULL CHECK (((VALUE)::text ~~ '%@%'::text))"
+        );
+
+        // the column is created with the domain instead of its base type
+        let mut col = ForgeSchemaColumn::new("email", "character varying");
+        col.length = Some(254);
+        col.domain = Some("email_address".to_string());
+        assert_eq!(
+            driver.field_migration_sql(&col, &config),
+            "email email_address NOT NULL"
+        );
+
+        // an override replaces the domain
+        let config: ForgeConfig =
+            toml::from_str("[tables.column_overrides.users]\nemail = \"text\"").unwrap();
+        let mut table = ForgeSchemaTable::new("users");
+        table.columns = vec![col];
+        let mut schema = fluxforge::ForgeSchema {
+            tables: vec![table],
+            ..Default::default()
+        };
+        fluxforge::core::apply_column_overrides(&mut schema, &config);
+        let col = &schema.tables[0].columns[0];
+        assert_eq!(col.domain, None);
+        assert_eq!(
+            driver.field_migration_sql(col, &config),
+            "email text NOT NULL"
+        );
+    }
+
+    #[test]
+    fn test_functional_index() {
+        let driver = mock_driver();
+        let index = ForgeSchemaIndex {
+            name: "idx_lower_name".to_string(),
+            columns: vec![String::new(), "id".to_string()],
+            expressions: Some(vec![Some("coalesce(`name`,_utf8mb4'-')".to_string()), None]),
+            ..ForgeSchemaIndex::default()
+        };
+        assert_eq!(
+            driver.build_postgres_create_index_sql("users", &index),
+            "CREATE INDEX idx_lower_name ON users ((coalesce(name,'-')), id)"
+        );
+    }
+
+    #[test]
+    fn test_functional_index_with_mysql_function_is_skipped() {
+        let driver = mock_driver();
+        let config = ForgeConfig::default();
+        let mut table = ForgeSchemaTable::new("users");
+        table.indices.push(ForgeSchemaIndex {
+            name: "idx_domain".to_string(),
+            columns: vec![String::new()],
+            expressions: Some(vec![Some(
+                "substring_index(`email`,_utf8mb4'@',-(1))".to_string(),
+            )]),
+            ..ForgeSchemaIndex::default()
+        });
+        let statements = driver.create_table_migration_sql(&table, &config).unwrap();
+        assert!(statements.iter().all(|s| !s.contains("idx_domain")));
+    }
+
+    #[test]
+    fn test_ddl_timeout_sql() {
+        let config: ForgeConfig =
+            toml::from_str("[general.ddl]\nlock_timeout_ms = 5000\nstatement_timeout_ms = 60000")
+                .unwrap();
+        assert_eq!(
+            postgres_ddl_timeout_sql(&config),
+            vec!["SET lock_timeout = 5000", "SET statement_timeout = 60000"]
+        );
+        assert!(postgres_ddl_timeout_sql(&ForgeConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn test_runs_outside_transaction() {
+        assert!(runs_outside_transaction(
+            "CREATE INDEX CONCURRENTLY idx_id ON users (id)"
+        ));
+        assert!(runs_outside_transaction("vacuum analyze users"));
+        assert!(!runs_outside_transaction(
+            "CREATE INDEX idx_id ON users (id)"
+        ));
+        assert!(!runs_outside_transaction(
+            "ALTER TABLE users ADD COLUMN concurrently int4"
+        ));
+    }
+
+    #[test]
+    fn test_idempotent_ddl() {
+        let driver = PostgresDriver {
+            idempotent_ddl: true,
+            ..mock_driver()
+        };
+        let config = ForgeConfig::default();
+
+        let mut source_table = ForgeSchemaTable::new("users");
+        source_table
+            .columns
+            .push(ForgeSchemaColumn::new("id", "int4"));
+        source_table.indices.push(ForgeSchemaIndex {
+            name: "idx_id".to_string(),
+            columns: vec!["id".to_string()],
+            ..ForgeSchemaIndex::default()
+        });
+        let stmts = driver
+            .create_table_migration_sql(&source_table, &config)
+            .unwrap();
+        assert_eq!(
+            stmts,
+            vec![
+                "CREATE TABLE IF NOT EXISTS users (\n  id int4 NOT NULL\n)",
+                "CREATE INDEX IF NOT EXISTS idx_id ON users (id)",
+            ]
+        );
+
+        let mut target_table = ForgeSchemaTable::new("users");
+        target_table
+            .columns
+            .push(ForgeSchemaColumn::new("name", "text"));
+        target_table.indices = source_table.indices.clone();
+        let stmts = driver
+            .alter_table_migration_sql(&source_table, &target_table, &config, true)
+            .unwrap();
+        assert_eq!(
+            stmts,
+            vec![
+                "ALTER TABLE users ADD COLUMN IF NOT EXISTS id int4 NOT NULL",
+                "ALTER TABLE users DROP COLUMN IF EXISTS name",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_on_update_trigger() {
+        let driver = mock_driver();
+        let mut table = ForgeSchemaTable::new("users");
+        table.columns.push(ForgeSchemaColumn::new("id", "integer"));
+        let mut updated_at = ForgeSchemaColumn::new("updated_at", "timestamp");
+        updated_at.on_update = Some("CURRENT_TIMESTAMP".into());
+        table.columns.push(updated_at);
+
+        // without the rule the behavior is not emulated
+        let stmts = driver
+            .create_table_migration_sql(&table, &ForgeConfig::default())
+            .unwrap();
+        assert_eq!(stmts.len(), 1);
+
+        let config: ForgeConfig = toml::from_str(
+            r#"
+            [postgres.rules.on_write]
+            on_update_trigger = true
+            "#,
+        )
+        .unwrap();
+        let stmts = driver.create_table_migration_sql(&table, &config).unwrap();
+        assert_eq!(
+            stmts[1..],
+            [
+                set_updated_at_function_sql(),
+                "DROP TRIGGER IF EXISTS users_set_updated_at ON users".to_string(),
+                "CREATE TRIGGER users_set_updated_at BEFORE UPDATE ON users FOR EACH ROW \
+                 EXECUTE FUNCTION set_updated_at('updated_at')"
+                    .to_string(),
+            ]
+        );
+        assert!(set_updated_at_function_sql().contains("FOREACH col IN ARRAY TG_ARGV"));
+
+        // an existing trigger is replaced if an ON UPDATE column is added
+        let mut target = ForgeSchemaTable::new("users");
+        target.columns.push(ForgeSchemaColumn::new("id", "integer"));
+        let stmts = driver
+            .alter_table_migration_sql(&table, &target, &config, false)
+            .unwrap();
+        assert_eq!(stmts.len(), 4);
+        assert!(stmts[0].contains("ADD COLUMN updated_at timestamp"));
+        assert!(stmts[3].starts_with("CREATE TRIGGER users_set_updated_at"));
+
+        // the target reports no ON UPDATE, an unchanged table needs no statement
+        let stmts = driver
+            .alter_table_migration_sql(&table, &table, &config, false)
+            .unwrap();
+        assert!(stmts.is_empty());
+    }
+
+    #[test]
+    fn test_build_grant_sql() {
+        let driver = mock_driver();
+        let config: ForgeConfig = toml::from_str(
+            r#"
+            [grants.user_mapping]
+            "'app'@'%'" = "app_rw"
+            "'root'@'localhost'" = ""
+            "#,
+        )
+        .unwrap();
+
+        let grant = |grantee: &str, privileges: &[&str]| ForgeSchemaGrant {
+            grantee: grantee.to_string(),
+            table_name: "users".to_string(),
+            privileges: privileges.iter().map(ToString::to_string).collect(),
+        };
+
+        // mapped grantee, ALTER does not exist on postgres table level
+        assert_eq!(
+            driver.build_grant_sql(&grant("'app'@'%'", &["ALTER", "SELECT", "UPDATE"]), &config),
+            Some("GRANT SELECT, UPDATE ON users TO \"app_rw\";".to_string())
+        );
+        // unmapped mysql grantee becomes role of the user name
+        assert_eq!(
+            driver.build_grant_sql(&grant("'report'@'10.%'", &["SELECT"]), &config),
+            Some("GRANT SELECT ON users TO \"report\";".to_string())
+        );
+        assert_eq!(
+            driver.build_grant_sql(&grant("PUBLIC", &["SELECT"]), &config),
+            Some("GRANT SELECT ON users TO PUBLIC;".to_string())
+        );
+        // skipped grantee and no supported privilege
+        assert_eq!(
+            driver.build_grant_sql(&grant("'root'@'localhost'", &["SELECT"]), &config),
+            None
+        );
+        assert_eq!(
+            driver.build_grant_sql(&grant("'app'@'%'", &["INDEX"]), &config),
+            None
+        );
+    }
+
+    #[test]
+    fn test_alter_table_migration_sql_stable_order() {
+        let driver = mock_driver();
+        let config = ForgeConfig::default();
+
+        let index = |name: &str| ForgeSchemaIndex {
+            name: name.to_string(),
+            columns: vec!["id".to_string()],
+            is_unique: false,
+            index_type: None,
+            column_prefixes: None,
+            expressions: None,
+        };
+
+        let mut source = ForgeSchemaTable::new("t");
+        source.columns.push(ForgeSchemaColumn::new("id", "integer"));
+        source.indices = vec![index("idx_c"), index("idx_a"), index("idx_b")];
+
+        let mut target = ForgeSchemaTable::new("t");
+        target.columns.push(ForgeSchemaColumn::new("id", "integer"));
+        target.columns.push(ForgeSchemaColumn::new("zz", "integer"));
+        target.columns.push(ForgeSchemaColumn::new("aa", "integer"));
+        target.indices = vec![index("old_y"), index("old_x")];
+
+        let stmts = driver
+            .alter_table_migration_sql(&source, &target, &config, true)
+            .unwrap();
+        assert_eq!(
+            stmts,
+            vec![
+                "ALTER TABLE t DROP COLUMN aa",
+                "ALTER TABLE t DROP COLUMN zz",
+                "CREATE INDEX idx_a ON t (id)",
+                "CREATE INDEX idx_b ON t (id)",
+                "CREATE INDEX idx_c ON t (id)",
+                "DROP INDEX IF EXISTS old_x",
+                "DROP INDEX IF EXISTS old_y",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_postgres_time_zone_sql() {
+        assert_eq!(
+            postgres_time_zone_sql("-03:30".parse().unwrap()),
+            "SET TIME ZONE INTERVAL '-03:30' HOUR TO MINUTE"
+        );
+    }
+
+    #[test]
+    fn test_build_sample_predicate() {
+        let driver = mock_driver();
+        let sample: ForgeVerifySample = "0.5%".parse().unwrap();
+        assert_eq!(
+            driver.build_sample_predicate(&["a".to_string(), "b".to_string()], sample),
+            "('x' || substr(md5(concat_ws('|', a, b)), 1, 8))::bit(32)::bigint % 10000 < 50"
+        );
+        assert_eq!(
+            driver.build_sample_predicate(&["id".to_string()], sample.with_seed(42)),
+            "('x' || substr(md5(concat_ws('|', '42', id)), 1, 8))::bit(32)::bigint % 10000 < 50"
+        );
+    }
+
+    #[test]
+    fn test_build_keyset_clause() {
+        let driver = mock_driver();
+        let keys = vec!["a".to_string(), "b".to_string()];
+        assert_eq!(driver.build_keyset_clause(&keys, false), " ORDER BY a, b");
+        assert_eq!(
+            driver.build_keyset_clause(&keys, true),
+            " WHERE (a, b) > ($1, $2) ORDER BY a, b"
+        );
+        assert_eq!(driver.build_key_clause(&keys), " WHERE (a, b) = ($1, $2)");
+        assert_eq!(
+            driver.build_last_key_sql("t", &keys),
+            "SELECT a, b FROM t ORDER BY a DESC, b DESC LIMIT 1"
+        );
+    }
+
+    #[test]
+    fn test_disable_triggers_and_foreign_key_violations_sql() {
+        let driver = mock_driver();
+        let tables = vec!["users".to_string(), "orders".to_string()];
+        assert_eq!(
+            driver.triggers_sql(&tables, false),
+            vec![
+                "ALTER TABLE users DISABLE TRIGGER ALL",
+                "ALTER TABLE orders DISABLE TRIGGER ALL"
+            ]
+        );
+        assert_eq!(
+            driver.triggers_sql(&tables[..1], true),
+            vec!["ALTER TABLE users ENABLE TRIGGER ALL"]
+        );
+
+        let foreign_key = ForgeSchemaForeignKey {
+            name: "fk_orders_user".to_string(),
+            column: "user_id".to_string(),
+            ref_table: "users".to_string(),
+            ref_column: "id".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            driver.foreign_key_violations_sql("orders", std::slice::from_ref(&foreign_key)),
+            "SELECT count(*) FROM orders c LEFT JOIN users r ON c.user_id = r.id \
+             WHERE c.user_id IS NOT NULL AND r.id IS NULL"
+        );
+
+        // a composite foreign key is joined on all its column pairs
+        let tenant = ForgeSchemaForeignKey {
+            column: "tenant".to_string(),
+            ref_column: "tenant".to_string(),
+            ..foreign_key.clone()
+        };
+        assert_eq!(
+            driver.foreign_key_violations_sql("orders", &[tenant, foreign_key]),
+            "SELECT count(*) FROM orders c LEFT JOIN users r \
+             ON c.tenant = r.tenant AND c.user_id = r.id \
+             WHERE c.tenant IS NOT NULL AND c.user_id IS NOT NULL AND r.tenant IS NULL"
+        );
+    }
+
+    #[test]
+    fn test_postgres_setting_checks() {
+        let checks = postgres_setting_checks("16.2", 160_002, "replica", "0", false);
+        let status: Vec<ForgeCheckStatus> = checks.iter().map(|c| c.status).collect();
+        assert_eq!(
+            status,
+            vec![
+                ForgeCheckStatus::Pass,
+                ForgeCheckStatus::Warn,
+                ForgeCheckStatus::Pass
+            ]
+        );
+        assert_eq!(checks[0].detail, "PostgreSQL 16.2");
+
+        // wal_level only matters for a source
+        let checks = postgres_setting_checks("11.22", 110_022, "replica", "30s", true);
+        let status: Vec<ForgeCheckStatus> = checks.iter().map(|c| c.status).collect();
+        assert_eq!(
+            status,
+            vec![
+                ForgeCheckStatus::Warn,
+                ForgeCheckStatus::Pass,
+                ForgeCheckStatus::Warn
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_select_digest_sql() {
+        let driver = mock_driver();
+        let columns = vec!["id".to_string(), "photo".to_string()];
+        assert_eq!(
+            driver.build_select_digest_sql("users", &columns, &["photo".to_string()], &[]),
+            "SELECT id, encode(sha256(photo), 'hex') AS photo FROM users"
+        );
+        let columns = vec!["id".to_string(), "bio".to_string()];
+        let bio = ["bio".to_string()];
+        assert_eq!(
+            driver.build_select_digest_sql("users", &columns, &bio, &bio),
+            "SELECT id, encode(sha256(convert_to(bio, 'UTF8')), 'hex') AS bio FROM users"
+        );
+    }
 }